@@ -0,0 +1,90 @@
+// Quad shader
+// Draws instanced rectangles with rounded corners and borders, plus lines
+// (rotated rectangles) and circles (fully rounded squares), antialiased with
+// a signed distance to the rounded box and cut off outside their clip rect
+
+struct Screen {
+    size: vec2<f32>,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> screen: Screen;
+
+struct Instance {
+    @location(0) rect: vec4<f32>,         // center x, center y, half width, half height in pixels
+    @location(1) color: vec4<f32>,
+    @location(2) border_color: vec4<f32>,
+    @location(3) params: vec4<f32>,       // corner radius, border width, rotation in radians, unused
+    @location(4) clip: vec4<f32>,         // min x, min y, max x, max y in pixels
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) local: vec2<f32>,        // offset from the quad's center before rotation
+    @location(1) half_size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) border_color: vec4<f32>,
+    @location(4) radius: f32,
+    @location(5) border: f32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+};
+
+// Vertex shader
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, instance: Instance) -> VertexOutput {
+    // Two triangles covering the quad
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0)
+    );
+
+    // Grow by a pixel so the antialiased edge isn't cut off
+    let half_size = instance.rect.zw;
+    let local = corners[vertex_index] * (half_size + vec2<f32>(1.0, 1.0));
+    let c = cos(instance.params.z);
+    let s = sin(instance.params.z);
+    let pixel = instance.rect.xy + vec2<f32>(local.x * c - local.y * s, local.x * s + local.y * c);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(pixel.x / screen.size.x * 2.0 - 1.0, 1.0 - pixel.y / screen.size.y * 2.0, 0.0, 1.0);
+    out.local = local;
+    out.half_size = half_size;
+    out.color = instance.color;
+    out.border_color = instance.border_color;
+    out.radius = instance.params.x;
+    out.border = instance.params.y;
+    out.clip = instance.clip;
+    return out;
+}
+
+// Distance from a point to the edge of a rounded box, negative inside
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius, radius);
+    return length(max(q, vec2<f32>(0.0, 0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+// Fragment shader
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The position builtin holds the pixel's coordinates here
+    let pixel = in.position.xy;
+    if (pixel.x < in.clip.x || pixel.y < in.clip.y || pixel.x > in.clip.z || pixel.y > in.clip.w) {
+        discard;
+    }
+
+    let radius = min(in.radius, min(in.half_size.x, in.half_size.y));
+    let distance = rounded_box(in.local, in.half_size, radius);
+    let coverage = clamp(0.5 - distance, 0.0, 1.0);
+
+    // The border is the band within `border` pixels of the edge
+    var border_amount = 0.0;
+    if (in.border > 0.0) {
+        border_amount = clamp(distance + in.border + 0.5, 0.0, 1.0);
+    }
+    let color = mix(in.color, in.border_color, border_amount);
+    return vec4<f32>(color.rgb, color.a * coverage);
+}
@@ -0,0 +1,112 @@
+// Benchmarks for the CPU side of drawing the todo list
+//
+// Everything the list does before queueing glyphs: filtering the items,
+// building or reconciling the widgets of the rows in view, and laying out and
+// hit-testing entity widgets. Queueing and drawing the glyphs needs a wgpu
+// device and isn't covered here. Run with
+// `cargo bench --bench widgets`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use std::sync::{Arc, Mutex};
+use tewduwu::core::prelude::*;
+use tewduwu::ui::ecs::{self, Bounds, Interactive, Position, Row, World};
+use tewduwu::ui::{TodoListWidget, Widget};
+
+/// A flat list of `count` tasks with mixed statuses and priorities
+fn list(count: usize) -> Dispatcher {
+    let mut list = TodoList::new("Benchmark");
+    for n in 0..count {
+        list.add_item(
+            TodoItem::new(&format!("Task {} needs doing", n))
+                .with_priority([Priority::Low, Priority::Medium, Priority::High][n % 3])
+                .with_status([Status::NotStarted, Status::InProgress, Status::Completed][n / 3 % 3]),
+        );
+    }
+    Dispatcher::new(Arc::new(Mutex::new(list)))
+}
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("todo_list_widget_new");
+    for count in [100, 1_000, 10_000] {
+        let todo_list = list(count);
+        group.bench_function(count.to_string(), |b| {
+            b.iter(|| black_box(TodoListWidget::new(50.0, 100.0, 924.0, 568.0, todo_list.clone())))
+        });
+    }
+    group.finish();
+}
+
+fn refresh(c: &mut Criterion) {
+    let todo_list = list(10_000);
+    let widget = TodoListWidget::new(50.0, 100.0, 924.0, 568.0, todo_list.clone());
+
+    // Reconciling with an unchanged list reuses every row widget
+    c.bench_function("refresh_unchanged_10k", |b| {
+        b.iter_batched_ref(|| widget.clone(), |widget| widget.refresh(), BatchSize::LargeInput)
+    });
+
+    // Completing one task: rows read their item when drawn, so none is rebuilt
+    let id = todo_list.lock().all_items()[0].id();
+    c.bench_function("refresh_one_changed_10k", |b| {
+        b.iter_batched_ref(
+            || {
+                let widget = widget.clone();
+                let completed = todo_list.lock().get_item(id).unwrap().is_completed();
+                let status = if completed { Status::NotStarted } else { Status::Completed };
+                todo_list.execute(Command::SetStatus { id, status }).unwrap();
+                todo_list.take_applied();
+                widget
+            },
+            |widget| widget.refresh(),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn idle_update(c: &mut Criterion) {
+    let mut widget = TodoListWidget::new(50.0, 100.0, 924.0, 568.0, list(10_000));
+    c.bench_function("idle_update_10k", |b| {
+        b.iter(|| {
+            if widget.needs_update() {
+                widget.update(1.0 / 60.0);
+            }
+        })
+    });
+}
+
+fn scroll(c: &mut Criterion) {
+    let mut widget = TodoListWidget::new(50.0, 100.0, 924.0, 568.0, list(10_000));
+
+    // Each step brings a few rows into view and drops as many
+    let mut offset = 0.0;
+    c.bench_function("scroll_10k", |b| {
+        b.iter(|| {
+            offset = (offset + 120.0) % 390_000.0;
+            widget.scroll_to(black_box(offset));
+        })
+    });
+}
+
+/// Laying out and hit-testing 10k entity widgets in rows of 100
+fn entities(c: &mut Criterion) {
+    let mut world = World::new();
+    for row_index in 0..100 {
+        let children = (0..100)
+            .map(|_| {
+                let entity = world.spawn();
+                world.bounds.insert(entity, Bounds { width: 10.0, height: 10.0 });
+                world.interactive.insert(entity, Interactive::default());
+                entity
+            })
+            .collect();
+        let row = world.spawn();
+        world.positions.insert(row, Position { x: 0.0, y: row_index as f32 * 10.0 });
+        world.rows.insert(row, Row { gap: 0.0, children });
+    }
+
+    c.bench_function("ecs_layout_10k", |b| b.iter(|| ecs::layout(black_box(&mut world))));
+    c.bench_function("ecs_hit_test_10k", |b| b.iter(|| ecs::hit_test(black_box(&world), 505.0, 505.0)));
+}
+
+criterion_group!(benches, build, refresh, idle_update, scroll, entities);
+criterion_main!(benches);
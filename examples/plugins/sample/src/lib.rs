@@ -0,0 +1,102 @@
+//! Sample tewduwu plugin
+//!
+//! Registers two commands:
+//! - `count` logs how many tasks are still open (needs `read-tasks`)
+//! - `done` marks the selected task completed (needs `write-tasks`), also
+//!   offered in the task context menu
+//!
+//! Bind them in config.toml and grant the permissions:
+//!
+//! ```toml
+//! [keybinds]
+//! "plugin:sample/count" = "Ctrl+K"
+//! "plugin:sample/done" = "Ctrl+D"
+//!
+//! [plugins.grants]
+//! sample = ["read-tasks", "write-tasks"]
+//! ```
+
+#[link(wasm_import_module = "tewduwu")]
+extern "C" {
+    fn log(ptr: *const u8, len: usize);
+    fn register_command(name_ptr: *const u8, name_len: usize, label_ptr: *const u8, label_len: usize);
+    fn register_menu_entry(cmd_ptr: *const u8, cmd_len: usize, label_ptr: *const u8, label_len: usize);
+    fn tasks_json(buf_ptr: *mut u8, buf_len: usize) -> i32;
+    fn task_set_status(id_ptr: *const u8, id_len: usize, status: i32) -> i32;
+}
+
+const STATUS_COMPLETED: i32 = 2;
+
+fn host_log(message: &str) {
+    unsafe { log(message.as_ptr(), message.len()) }
+}
+
+fn command(name: &str, label: &str) {
+    unsafe { register_command(name.as_ptr(), name.len(), label.as_ptr(), label.len()) }
+}
+
+/// All tasks as JSON, growing the buffer until the host's answer fits
+fn all_tasks() -> Result<serde_json::Value, i32> {
+    let mut buffer = vec![0u8; 4096];
+    loop {
+        let len = unsafe { tasks_json(buffer.as_mut_ptr(), buffer.len()) };
+        if len < 0 {
+            return Err(len);
+        }
+        let len = len as usize;
+        if len <= buffer.len() {
+            buffer.truncate(len);
+            return serde_json::from_slice(&buffer).map_err(|_| -2);
+        }
+        buffer.resize(len, 0);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn tewduwu_api_version() -> i32 {
+    1
+}
+
+/// Hand out memory for strings passed in by the host; it is leaked, which is
+/// fine for the handful of bytes a command invocation needs
+#[no_mangle]
+pub extern "C" fn tewduwu_alloc(len: usize) -> *mut u8 {
+    let mut buffer = vec![0u8; len.max(1)];
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+#[no_mangle]
+pub extern "C" fn tewduwu_init() {
+    command("count", "Count open tasks");
+    command("done", "Mark task done");
+    let (cmd, label) = ("done", "Mark done (sample plugin)");
+    unsafe { register_menu_entry(cmd.as_ptr(), cmd.len(), label.as_ptr(), label.len()) }
+}
+
+#[no_mangle]
+pub extern "C" fn tewduwu_run(cmd_ptr: *const u8, cmd_len: usize, sel_ptr: *const u8, sel_len: usize) -> i32 {
+    let command = unsafe { std::slice::from_raw_parts(cmd_ptr, cmd_len) };
+    let selected = unsafe { std::slice::from_raw_parts(sel_ptr, sel_len) };
+
+    match command {
+        b"count" => match all_tasks() {
+            Ok(tasks) => {
+                let open = tasks
+                    .as_array()
+                    .map(|tasks| tasks.iter().filter(|task| task["status"] != "Completed").count())
+                    .unwrap_or(0);
+                host_log(&format!("{} open tasks", open));
+                0
+            }
+            Err(code) => code,
+        },
+        b"done" if selected.is_empty() => {
+            host_log("Select a task first");
+            0
+        }
+        b"done" => unsafe { task_set_status(selected.as_ptr(), selected.len(), STATUS_COMPLETED) },
+        _ => -2,
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use log::info;
+use tewduwu::core::prelude::*;
+use tewduwu::headless::{self, HeadlessRenderer};
+use tewduwu::ui::prelude::*;
+
+/// Render the sample list offscreen and save it as a PNG, without opening a window
+///
+///     cargo run --example screenshot -- [out.png] [WIDTHxHEIGHT]
+///
+/// With `--check <golden.png>` the frame is compared with a golden image
+/// instead, and the example fails if they differ.
+fn main() -> Result<(), String> {
+    env_logger::init();
+
+    let mut args = std::env::args().skip(1);
+    let mut output = PathBuf::from("screenshot.png");
+    let mut golden = None;
+    let (mut width, mut height) = (1024, 768);
+    while let Some(arg) = args.next() {
+        if arg == "--check" {
+            golden = Some(PathBuf::from(args.next().ok_or("--check needs a golden image")?));
+        } else if let Some((w, h)) = arg.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?))) {
+            (width, height) = (w, h);
+        } else {
+            output = PathBuf::from(arg);
+        }
+    }
+
+    // Lay text out with the fonts it's drawn with, as the app does
+    tewduwu::ui::text_metrics::set_fonts(tewduwu::ui::fonts::bundled());
+
+    let mut renderer = HeadlessRenderer::new(width, height)?;
+    let list = Arc::new(Mutex::new(headless::sample_list()));
+    let widget = TodoListWidget::new(50.0, 100.0, width as f32 - 100.0, height as f32 - 200.0, Dispatcher::new(list));
+    let frame = renderer.render_list(&widget)?;
+
+    match golden {
+        Some(golden) => {
+            headless::check_golden(&frame, &golden)?;
+            info!("Frame matches {}", golden.display());
+        }
+        None => {
+            frame.save_png(&output)?;
+            info!("Saved {}x{} frame to {}", width, height, output.display());
+        }
+    }
+    Ok(())
+}
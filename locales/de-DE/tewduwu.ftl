@@ -0,0 +1,265 @@
+# German (Germany)
+
+## Formatting
+date-format = %d.%m.%Y
+datetime-format = %d.%m.%Y %H:%M
+day-format = %d.%m.
+date-months = Januar Februar März April Mai Juni Juli August September Oktober November Dezember
+date-weekdays = Mo Di Mi Do Fr Sa So
+number-decimal-separator = ,
+number-group-separator = .
+
+## Task list
+add-task = Hinzufügen
+new-task-placeholder = Neue Aufgabe...
+search-placeholder = Suchen...
+filter-all-tasks = Alle Aufgaben
+filter-active = Offen
+filter-completed = Erledigt
+filter-field-title = Titel
+filter-field-description = Beschreibung
+filter-field-all = Alle Felder
+filter-status-all = Jeder Status
+filter-priority-all = Jede Priorität
+sort-manual = Eigene Reihenfolge
+sort-priority = Nach Priorität
+sort-due-date = Nach Fälligkeit
+sort-created = Nach Erstellung
+sort-alphabetical = A bis Z
+
+## Task properties
+status-not-started = Nicht begonnen
+status-in-progress = In Arbeit
+status-completed = Erledigt
+priority-low = Niedrig
+priority-medium = Mittel
+priority-high = Hoch
+
+## Task details
+item-status = Status: { $status }
+item-priority = Priorität: { $priority }
+item-created = Erstellt: { $date }
+item-updated = Geändert: { $date }
+item-completed = Erledigt: { $date }
+item-due = Fällig: { $date }
+item-description = Beschreibung:
+item-no-description = Keine Beschreibung
+item-attachments = Anhänge:
+
+## Context menu
+menu-complete = Als erledigt markieren
+menu-reopen = Als offen markieren
+menu-edit = Bearbeiten...
+menu-add-subtask = Unteraufgabe hinzufügen
+menu-new-subtask = Neue Unteraufgabe
+menu-copy-title = Titel kopieren
+menu-snooze = Schlummern
+menu-focus = Fokussieren
+menu-delete = Löschen
+menu-bulk-complete = { $count } Aufgaben erledigen
+menu-bulk-move-top = Auf oberste Ebene verschieben
+menu-bulk-move-here = { $count ->
+    [1] Die gewählte Aufgabe hierher verschieben
+   *[other] { $count } gewählte Aufgaben hierher verschieben
+}
+menu-bulk-delete = { $count } Aufgaben löschen
+
+## Task editor
+edit-title = Aufgabe bearbeiten
+edit-field-title = Titel
+edit-field-description = Beschreibung
+edit-field-status = Status
+edit-field-priority = Priorität
+edit-field-due = Fällig am
+edit-due-placeholder = TT.MM.JJJJ, oder leer für keins
+edit-field-repeat = Wiederholung
+recurrence-never = Nie
+recurrence-daily = Täglich
+recurrence-weekly = Wöchentlich
+recurrence-monthly = Monatlich
+recurrence-every-days = Alle { $days } Tage
+edit-field-reminder = Erinnerung
+reminder-default = Standard
+reminder-at-due = Bei Fälligkeit
+reminder-minutes-before = { $minutes } Minuten vorher
+reminder-hours-before = { $hours ->
+    [1] Eine Stunde vorher
+   *[other] { $hours } Stunden vorher
+}
+reminder-days-before = { $days ->
+    [1] Einen Tag vorher
+   *[other] { $days } Tage vorher
+}
+reminder-off = Aus
+edit-field-tags = Schlagwörter
+edit-tags-placeholder = Durch Kommas getrennt, z. B. Arbeit, Besorgungen
+edit-field-project = Projekt
+edit-no-project = Keins
+edit-field-attachments = Anhänge
+edit-attachment-placeholder = Link oder Dateipfad, dann Enter
+edit-pick-files = Dateien anhängen
+edit-empty-title = Der Titel darf nicht leer sein
+edit-invalid-date = { $text } ist kein Datum wie 31.12.2025
+edit-save = Speichern
+edit-cancel = Abbrechen
+date-picker-today = Heute
+date-picker-clear = Kein Datum
+
+## Main window
+instructions = { $quit } zum Beenden, { $settings } für Einstellungen
+
+## Lists sidebar
+sidebar-title = Listen
+sidebar-new-list = + Neue Liste
+sidebar-default-name = Liste { $number }
+sidebar-projects = Projekte
+sidebar-new-project = + Neues Projekt
+sidebar-default-project = Projekt { $number }
+
+## Views
+view-list = Liste
+view-board = Board
+view-timeline = Zeitleiste
+board-empty = Aufgaben hierher ziehen
+timeline-empty = Keine Aufgaben mit Fälligkeitsdatum
+
+## Status bar
+status-counts = { $total } gesamt · { $active } offen · { $completed } erledigt
+status-overdue = { $overdue } überfällig
+status-filter = Filter: { $filter }
+status-filter-none = Kein Filter
+status-filter-search = Suche „{ $query }“
+status-filter-script = Skriptfilter
+status-mode-normal = NORMAL
+status-mode-insert = EINFÜGEN
+status-fps = { $fps } fps
+status-sync-syncing = Synchronisiere…
+status-sync-synced = Synchronisiert { $time }
+status-sync-failed = Synchronisierung fehlgeschlagen
+
+## Recovery prompt
+recovery-title = Ungespeicherte Änderungen wiederherstellen?
+recovery-message = tewduwu wurde zuletzt nicht sauber beendet.
+recovery-restore = Wiederherstellen
+recovery-discard = Verwerfen
+
+## Settings
+settings-title = Einstellungen
+settings-present-mode = Darstellungsmodus
+settings-present-mode-fifo = Fifo (VSync)
+settings-present-mode-mailbox = Mailbox
+settings-present-mode-immediate = Sofort
+settings-max-fps = Bildratenbegrenzung
+settings-max-fps-value = { $fps } FPS
+settings-redraw = Neu zeichnen
+settings-redraw-on-demand = Bei Änderung
+settings-redraw-continuous = Jedes Bild
+settings-bloom = Bloom
+settings-glow = Neon-Glühen
+settings-autosave = Automatisch sichern alle
+settings-autosave-value = { $seconds } s
+settings-scroll-speed = Scrollgeschwindigkeit
+settings-scroll-speed-value = { $factor }×
+settings-natural-scrolling = Natürliches Scrollen
+settings-focus-follows-mouse = Fokus folgt der Maus
+settings-auto-complete-parents = Oberaufgaben automatisch erledigen
+settings-archive-after = Erledigte automatisch archivieren
+settings-archive-after-value = { $days ->
+    [1] nach 1 Tag
+   *[other] nach { $days } Tagen
+}
+settings-on = An
+settings-off = Aus
+settings-save = Speichern
+settings-close = Schließen
+settings-saved = Einstellungen gespeichert in { $path }
+fx-title = FX
+fx-bloom-threshold = Bloom-Schwelle
+fx-bloom-intensity = Bloom-Stärke
+fx-bloom-saturation = Bloom-Sättigung
+fx-glow-color = Glühfarbe
+fx-glow-size = Glühgröße
+
+## Debug inspector
+inspector-title = Inspektor
+inspector-nothing = Kein Widget unter dem Zeiger
+inspector-position = Position { $x }, { $y }
+inspector-size = Größe { $width } × { $height }
+inspector-depth = Tiefe { $depth }
+inspector-hovered = Überfahren
+inspector-focused = Fokussiert
+inspector-clip = Beschnitten auf { $x }, { $y }, { $width } × { $height }
+
+## Profiler
+profiler-title = Profiler: Frame { $frame }, { $ms } ms
+
+## Frame diagnostics
+diagnostics-title = Frame
+diagnostics-cpu = CPU: { $ms } ms
+diagnostics-gpu-pass = GPU { $pass }: { $ms } ms
+diagnostics-gpu-waiting = GPU: wird gemessen…
+diagnostics-gpu-unsupported = GPU: keine Zeitstempel-Abfragen auf diesem Adapter
+diagnostics-pass-scene = Szene
+diagnostics-pass-text = Text
+diagnostics-pass-bloom = Bloom
+diagnostics-pass-glow = Glühen
+diagnostics-draws = { $draws } Draw-Calls
+diagnostics-quads = { $quads } Quads, { $glyphs } Glyphen
+diagnostics-memory = Render-Ziele: { $size }
+
+## Notifications
+saved = Gespeichert
+journal-failed = Ungespeicherte Änderungen können nicht gesichert werden: { $error }
+present-mode-changed = Darstellungsmodus: { $mode }
+present-mode-unsupported = { $mode } wird hier nicht unterstützt, verwende Fifo (VSync)
+title-copied = „{ $title }“ kopiert
+link-open-failed = { $url } konnte nicht geöffnet werden
+attachment-open-failed = { $name } konnte nicht geöffnet werden: { $error }
+sync-done = Synchronisiert: { $pushed } gesendet, { $pulled } empfangen
+sync-failed = Synchronisierung fehlgeschlagen: { $error }
+sync-not-configured = Richte die Synchronisierung in der Tabelle [sync] der config.toml ein
+sync-unavailable = Dieser Build kann nicht synchronisieren; baue ihn mit dem Feature caldav
+reminder-upcoming = Bald fällig: { $title } ({ $due })
+reminder-overdue = Überfällig: { $title } (war fällig { $due })
+reminder-snooze = Schlummern
+snoozed = „{ $title }“ geschlummert bis { $due }
+
+## Tray icon and quick add
+tray-quick-add = Aufgabe schnell hinzufügen…
+tray-show-window = Fenster zeigen
+tray-hide-window = Fenster ausblenden
+tray-tooltip = { $overdue ->
+    [0] tewduwu-neon: nichts überfällig
+    [1] tewduwu-neon: 1 Aufgabe überfällig
+   *[other] tewduwu-neon: { $overdue } Aufgaben überfällig
+}
+quick-add-title = Schnell hinzufügen
+quick-add-hint = Enter fügt sie zu { $list } hinzu, Escape schließt
+
+## Archive
+archive-title = Archiv
+archive-empty = Noch nichts archiviert
+archive-restore = Zurückholen
+archive-completed = Erledigte archivieren
+archive-close = Schließen
+archive-nothing-completed = Keine erledigten Aufgaben zum Archivieren
+
+## Focus timer
+focus-session = Fokus
+focus-break = Pause
+focus-paused = Angehalten
+focus-no-task = Wähle eine Aufgabe, um dich darauf zu konzentrieren
+focus-stopped = Fokus-Timer gestoppt
+focus-session-done = Fokus-Einheit zu „{ $title }“ beendet, bisher { $count }
+focus-break-done = Pause vorbei, zurück zu „{ $title }“
+
+## Crash dialog
+crash-title = tewduwu-neon ist abgestürzt
+crash-message = Leider ist ein Fehler aufgetreten und tewduwu-neon muss beendet werden.
+crash-rescued = Deine Aufgaben werden beim nächsten Start zur Wiederherstellung angeboten.
+crash-report = Ein Absturzbericht wurde unter { $path } gespeichert.
+
+## Startup errors
+startup-failed-title = tewduwu-neon konnte nicht starten
+startup-failed-message = Etwas, das tewduwu-neon zum Laufen braucht, ist nicht verfügbar.
+startup-no-adapter = Kein Grafiktreiber konnte das Fenster zeichnen. Meist hilft es, die Treiber der Grafikkarte (Vulkan, Metal, DirectX 12 oder OpenGL) zu installieren oder zu aktualisieren.
@@ -0,0 +1,265 @@
+# English (United States) - the reference bundle; every message must exist here
+
+## Formatting
+date-format = %Y-%m-%d
+datetime-format = %Y-%m-%d %H:%M
+day-format = %m-%d
+date-months = January February March April May June July August September October November December
+date-weekdays = Mo Tu We Th Fr Sa Su
+number-decimal-separator = .
+number-group-separator = ,
+
+## Task list
+add-task = Add Task
+new-task-placeholder = New task...
+search-placeholder = Search...
+filter-all-tasks = All Tasks
+filter-active = Active
+filter-completed = Completed
+filter-field-title = Title
+filter-field-description = Description
+filter-field-all = All Fields
+filter-status-all = All Status
+filter-priority-all = All Priority
+sort-manual = Manual order
+sort-priority = By priority
+sort-due-date = By due date
+sort-created = By creation
+sort-alphabetical = A to Z
+
+## Task properties
+status-not-started = Not Started
+status-in-progress = In Progress
+status-completed = Completed
+priority-low = Low
+priority-medium = Medium
+priority-high = High
+
+## Task details
+item-status = Status: { $status }
+item-priority = Priority: { $priority }
+item-created = Created: { $date }
+item-updated = Updated: { $date }
+item-completed = Completed: { $date }
+item-due = Due: { $date }
+item-description = Description:
+item-no-description = No description
+item-attachments = Attachments:
+
+## Context menu
+menu-complete = Mark as Done
+menu-reopen = Mark as Not Done
+menu-edit = Edit...
+menu-add-subtask = Add Subtask
+menu-new-subtask = New subtask
+menu-copy-title = Copy Title
+menu-snooze = Snooze
+menu-focus = Focus
+menu-delete = Delete
+menu-bulk-complete = Complete { $count } tasks
+menu-bulk-move-top = Move to top level
+menu-bulk-move-here = { $count ->
+    [1] Move the picked task here
+   *[other] Move { $count } picked tasks here
+}
+menu-bulk-delete = Delete { $count } tasks
+
+## Task editor
+edit-title = Edit Task
+edit-field-title = Title
+edit-field-description = Description
+edit-field-status = Status
+edit-field-priority = Priority
+edit-field-due = Due date
+edit-due-placeholder = YYYY-MM-DD, or empty for none
+edit-field-repeat = Repeats
+recurrence-never = Never
+recurrence-daily = Daily
+recurrence-weekly = Weekly
+recurrence-monthly = Monthly
+recurrence-every-days = Every { $days } days
+edit-field-reminder = Reminder
+reminder-default = Default
+reminder-at-due = When it's due
+reminder-minutes-before = { $minutes } minutes before
+reminder-hours-before = { $hours ->
+    [1] An hour before
+   *[other] { $hours } hours before
+}
+reminder-days-before = { $days ->
+    [1] A day before
+   *[other] { $days } days before
+}
+reminder-off = Off
+edit-field-tags = Tags
+edit-tags-placeholder = Comma separated, e.g. work, errands
+edit-field-project = Project
+edit-no-project = None
+edit-field-attachments = Attachments
+edit-attachment-placeholder = Link or file path, then Enter
+edit-pick-files = Attach files
+edit-empty-title = The title can't be empty
+edit-invalid-date = { $text } is not a date like 2025-12-31
+edit-save = Save
+edit-cancel = Cancel
+date-picker-today = Today
+date-picker-clear = No date
+
+## Main window
+instructions = Press { $quit } to exit, { $settings } for settings
+
+## Lists sidebar
+sidebar-title = Lists
+sidebar-new-list = + New list
+sidebar-default-name = List { $number }
+sidebar-projects = Projects
+sidebar-new-project = + New project
+sidebar-default-project = Project { $number }
+
+## Views
+view-list = List
+view-board = Board
+view-timeline = Timeline
+board-empty = Drop tasks here
+timeline-empty = No tasks with due dates
+
+## Status bar
+status-counts = { $total } total · { $active } active · { $completed } done
+status-overdue = { $overdue } overdue
+status-filter = Filter: { $filter }
+status-filter-none = No filter
+status-filter-search = Search "{ $query }"
+status-filter-script = Script filter
+status-mode-normal = NORMAL
+status-mode-insert = INSERT
+status-fps = { $fps } fps
+status-sync-syncing = Syncing…
+status-sync-synced = Synced { $time }
+status-sync-failed = Sync failed
+
+## Recovery prompt
+recovery-title = Recover unsaved changes?
+recovery-message = tewduwu did not shut down cleanly last time.
+recovery-restore = Restore
+recovery-discard = Discard
+
+## Settings
+settings-title = Settings
+settings-present-mode = Present mode
+settings-present-mode-fifo = Fifo (VSync)
+settings-present-mode-mailbox = Mailbox
+settings-present-mode-immediate = Immediate
+settings-max-fps = Frame rate cap
+settings-max-fps-value = { $fps } FPS
+settings-redraw = Redraw
+settings-redraw-on-demand = On change
+settings-redraw-continuous = Every frame
+settings-bloom = Bloom
+settings-glow = Neon glow
+settings-autosave = Autosave interval
+settings-autosave-value = { $seconds }s
+settings-scroll-speed = Scroll speed
+settings-scroll-speed-value = { $factor }×
+settings-natural-scrolling = Natural scrolling
+settings-focus-follows-mouse = Focus follows mouse
+settings-auto-complete-parents = Auto-complete parents
+settings-archive-after = Auto-archive completed
+settings-archive-after-value = { $days ->
+    [1] after 1 day
+   *[other] after { $days } days
+}
+settings-on = On
+settings-off = Off
+settings-save = Save
+settings-close = Close
+settings-saved = Settings saved to { $path }
+fx-title = FX
+fx-bloom-threshold = Bloom threshold
+fx-bloom-intensity = Bloom intensity
+fx-bloom-saturation = Bloom saturation
+fx-glow-color = Glow color
+fx-glow-size = Glow size
+
+## Debug inspector
+inspector-title = Inspector
+inspector-nothing = No widget under the pointer
+inspector-position = Position { $x }, { $y }
+inspector-size = Size { $width } × { $height }
+inspector-depth = Depth { $depth }
+inspector-hovered = Hovered
+inspector-focused = Focused
+inspector-clip = Clips to { $x }, { $y }, { $width } × { $height }
+
+## Profiler
+profiler-title = Profiler: frame { $frame }, { $ms } ms
+
+## Frame diagnostics
+diagnostics-title = Frame
+diagnostics-cpu = CPU: { $ms } ms
+diagnostics-gpu-pass = GPU { $pass }: { $ms } ms
+diagnostics-gpu-waiting = GPU: measuring…
+diagnostics-gpu-unsupported = GPU: no timestamp queries on this adapter
+diagnostics-pass-scene = scene
+diagnostics-pass-text = text
+diagnostics-pass-bloom = bloom
+diagnostics-pass-glow = glow
+diagnostics-draws = { $draws } draw calls
+diagnostics-quads = { $quads } quads, { $glyphs } glyphs
+diagnostics-memory = Render targets: { $size }
+
+## Notifications
+saved = Saved
+journal-failed = Unsaved changes can't be journaled: { $error }
+present-mode-changed = Present mode: { $mode }
+present-mode-unsupported = { $mode } isn't supported here, using Fifo (VSync)
+title-copied = Copied "{ $title }"
+link-open-failed = Couldn't open { $url }
+attachment-open-failed = Couldn't open { $name }: { $error }
+sync-done = Synced: { $pushed } sent, { $pulled } received
+sync-failed = Sync failed: { $error }
+sync-not-configured = Set up syncing in the [sync] table of config.toml
+sync-unavailable = This build can't sync; build it with the caldav feature
+reminder-upcoming = Due soon: { $title } ({ $due })
+reminder-overdue = Overdue: { $title } (was due { $due })
+reminder-snooze = Snooze
+snoozed = Snoozed "{ $title }" until { $due }
+
+## Tray icon and quick add
+tray-quick-add = Quick add task…
+tray-show-window = Show window
+tray-hide-window = Hide window
+tray-tooltip = { $overdue ->
+    [0] tewduwu-neon: nothing overdue
+    [1] tewduwu-neon: 1 task overdue
+   *[other] tewduwu-neon: { $overdue } tasks overdue
+}
+quick-add-title = Quick add
+quick-add-hint = Enter adds it to { $list }, Escape closes
+
+## Archive
+archive-title = Archive
+archive-empty = Nothing archived yet
+archive-restore = Restore
+archive-completed = Archive completed
+archive-close = Close
+archive-nothing-completed = No completed tasks to archive
+
+## Focus timer
+focus-session = Focus
+focus-break = Break
+focus-paused = Paused
+focus-no-task = Select a task to focus on
+focus-stopped = Focus timer stopped
+focus-session-done = Focus session on "{ $title }" done, { $count } so far
+focus-break-done = Break's over, back to "{ $title }"
+
+## Crash dialog
+crash-title = tewduwu-neon crashed
+crash-message = Sorry, something went wrong and tewduwu-neon has to close.
+crash-rescued = Your tasks will be offered for recovery on the next launch.
+crash-report = A crash report was written to { $path }
+
+## Startup errors
+startup-failed-title = tewduwu-neon couldn't start
+startup-failed-message = Something tewduwu-neon needs to run isn't available.
+startup-no-adapter = No graphics driver could draw the window. Installing or updating the drivers for your graphics card (Vulkan, Metal, DirectX 12 or OpenGL) usually fixes this.
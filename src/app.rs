@@ -0,0 +1,526 @@
+// Embeddable application runner (native only)
+//
+// `App` describes a neon todo window: the list to show, the window title and
+// size, extra widgets drawn over the list, and hooks called at startup and for
+// every event. `App::run` opens the window and runs the winit loop until it is
+// closed. Programs that own their event loop create a `Runner` for a window of
+// theirs instead and pass it the window's events.
+//
+//     App::new(todo_list)
+//         .with_title("My tasks")
+//         .on_event(|ctx, event| {
+//             if let AppEvent::Ui(UiEvent::TaskAdded { id }) = event {
+//                 println!("added {}", id);
+//             }
+//             false
+//         })
+//         .run()
+//
+// The runner draws the list the way the app does (list, bloom, modals, neon
+// glow) but leaves out the app's config, persistence and shortcuts; saving is
+// up to the embedder, e.g. from `on_event` or after `run` returns.
+
+use std::sync::{Arc, Mutex};
+use wgpu::util::StagingBelt;
+use wgpu::{Device, Queue, Surface, SurfaceConfiguration};
+use wgpu_glyph::{ab_glyph, GlyphBrush, GlyphBrushBuilder};
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::EventLoop;
+use winit::keyboard::{Key, ModifiersState};
+use winit::window::{CursorIcon, Window, WindowBuilder};
+
+use crate::config::InputConfig;
+use crate::frame_pacing::FrameClock;
+use crate::core::prelude::{Dispatcher, TodoList};
+use crate::profiling::profile_scope;
+use crate::ui::fonts;
+use crate::ui::input::{named_keycode, now_secs};
+use crate::ui::prelude::*;
+
+/// Something for `on_event` hooks to react to
+#[derive(Debug)]
+pub enum AppEvent<'a> {
+    /// A window event, before the runner handles it
+    Window(&'a WindowEvent),
+    /// An event from the todo list widget, after the list was changed
+    Ui(&'a UiEvent),
+}
+
+/// What hooks can reach while the app runs
+pub struct AppContext<'a> {
+    pub window: &'a Window,
+    /// Runs commands against the shown list
+    pub commands: &'a Dispatcher,
+    pub list_widget: &'a mut TodoListWidget,
+    /// Widgets added with `App::with_widget`, in drawing order
+    pub widgets: &'a mut [Box<dyn Widget>],
+    exit: &'a mut bool,
+}
+
+impl AppContext<'_> {
+    /// Close the window and return from `App::run`
+    pub fn exit(&mut self) {
+        *self.exit = true;
+    }
+}
+
+type InitHook = Box<dyn FnMut(&mut AppContext)>;
+type EventHook = Box<dyn FnMut(&mut AppContext, &AppEvent) -> bool>;
+
+/// Call `hooks` in order for `event`, returning whether one handled it
+///
+/// A window event stops at the first hook that returns true; a list event goes
+/// to every hook and is never handled.
+fn call_hooks<H>(hooks: &mut [H], event: &AppEvent, mut call: impl FnMut(&mut H) -> bool) -> bool {
+    match event {
+        AppEvent::Window(_) => hooks.iter_mut().any(call),
+        AppEvent::Ui(_) => {
+            for hook in hooks {
+                call(hook);
+            }
+            false
+        }
+    }
+}
+
+/// A neon todo window, set up with the builder methods and started with `run`
+pub struct App {
+    commands: Dispatcher,
+    title: String,
+    size: (u32, u32),
+    font: Option<ab_glyph::FontArc>,
+    widgets: Vec<Box<dyn Widget>>,
+    on_init: Vec<InitHook>,
+    on_event: Vec<EventHook>,
+}
+
+impl App {
+    /// Show a todo list
+    pub fn new(todo_list: TodoList) -> Self {
+        Self::with_dispatcher(Dispatcher::new(Arc::new(Mutex::new(todo_list))))
+    }
+
+    /// Show a list that is also changed elsewhere, through the same dispatcher
+    pub fn with_dispatcher(commands: Dispatcher) -> Self {
+        Self {
+            commands,
+            title: "tewduwu-neon".to_string(),
+            size: (1280, 720),
+            font: None,
+            widgets: Vec::new(),
+            on_init: Vec::new(),
+            on_event: Vec::new(),
+        }
+    }
+
+    /// Title of the window and the heading above the list
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Initial window size in logical pixels
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    /// Use another font instead of the bundled Inconsolata
+    ///
+    /// Glyphs it lacks are still drawn from the bundled symbol font.
+    pub fn with_font(mut self, font: ab_glyph::FontArc) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Draw a widget of your own over the list
+    ///
+    /// Widgets are updated every frame they need it and drawn in the order they
+    /// were added. The `Widget` trait has no input methods, so route input to
+    /// them from an `on_event` hook through `AppContext::widgets`.
+    pub fn with_widget(mut self, widget: Box<dyn Widget>) -> Self {
+        self.widgets.push(widget);
+        self
+    }
+
+    /// Call `hook` once the window and GPU are ready, before the first frame
+    pub fn on_init(mut self, hook: impl FnMut(&mut AppContext) + 'static) -> Self {
+        self.on_init.push(Box::new(hook));
+        self
+    }
+
+    /// Call `hook` for every window and list event
+    ///
+    /// Hooks run in the order they were added. Returning true from an
+    /// `AppEvent::Window` event stops the runner, and the hooks after this one,
+    /// from handling it. A list event has already happened, so every hook sees
+    /// an `AppEvent::Ui` and the return value is ignored.
+    pub fn on_event(mut self, hook: impl FnMut(&mut AppContext, &AppEvent) -> bool + 'static) -> Self {
+        self.on_event.push(Box::new(hook));
+        self
+    }
+
+    /// The dispatcher the app changes the list through, e.g. to save it after `run`
+    pub fn commands(&self) -> &Dispatcher {
+        &self.commands
+    }
+
+    /// Open the window and run until it's closed
+    pub fn run(self) -> Result<(), String> {
+        let event_loop = EventLoop::new().map_err(|e| format!("Failed to create event loop: {}", e))?;
+        let window = WindowBuilder::new()
+            .with_title(&self.title)
+            .with_inner_size(LogicalSize::new(self.size.0, self.size.1))
+            .build(&event_loop)
+            .map_err(|e| format!("Failed to create window: {}", e))?;
+        let mut runner = Runner::new(self, Arc::new(window))?;
+
+        event_loop
+            .run(move |event, elwt| match event {
+                Event::WindowEvent { event, window_id } if window_id == runner.window().id() => {
+                    runner.handle_window_event(&event);
+                    if runner.exit_requested() {
+                        elwt.exit();
+                    }
+                }
+                Event::AboutToWait => runner.window().request_redraw(),
+                _ => {}
+            })
+            .map_err(|e| format!("Event loop error: {}", e))
+    }
+}
+
+/// An `App` running in a window: the GPU state, widgets and input state
+pub struct Runner {
+    window: Arc<Window>,
+    surface: Surface<'static>,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    config: SurfaceConfiguration,
+    glyph_brush: GlyphBrush<()>,
+    quad_renderer: QuadRenderer,
+    staging_belt: StagingBelt,
+    frame_arena: FrameArena,
+    post_process: PostProcessStack,
+
+    title: String,
+    commands: Dispatcher,
+    list_widget: TodoListWidget,
+    widgets: Vec<Box<dyn Widget>>,
+    on_event: Vec<EventHook>,
+
+    input: InputConfig,
+    mouse_pos: (f32, f32),
+    modifiers: ModifiersState,
+    click_tracker: ClickTracker,
+    cursor_icon: CursorIcon,
+    clock: FrameClock,
+    exit: bool,
+}
+
+impl Runner {
+    /// Set up the GPU for `window` and run the app's `on_init` hooks
+    ///
+    /// Blocks until the GPU is ready.
+    pub fn new(app: App, window: Arc<Window>) -> Result<Self, String> {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(|e| format!("Failed to create surface: {}", e))?;
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface: Some(&surface),
+        }))
+        .ok_or("No suitable GPU adapter found")?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+            .map_err(|e| format!("Failed to create device: {}", e))?;
+        let (device, queue) = (Arc::new(device), Arc::new(queue));
+
+        let caps = surface.get_capabilities(&adapter);
+        let format = caps.formats.iter().copied().find(|f| f.is_srgb()).unwrap_or(caps.formats[0]);
+        let config = SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        let mut fonts = fonts::bundled();
+        if let Some(font) = app.font {
+            fonts[0] = font;
+        }
+        let glyph_brush = GlyphBrushBuilder::using_fonts(fonts).build(&device, format);
+        let quad_renderer = QuadRenderer::new(&device, format);
+
+        let post_process = PostProcessStack::new(device.clone(), queue.clone(), format, &CyberpunkTheme::new(), config.width, config.height);
+
+        // Laid out like the app's list, below the heading, in logical pixels
+        let logical_size = size.to_logical::<f32>(window.scale_factor());
+        let list_widget = TodoListWidget::new(
+            50.0,
+            100.0,
+            logical_size.width - 100.0,
+            logical_size.height - 200.0,
+            app.commands.clone(),
+        );
+
+        let mut runner = Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            glyph_brush,
+            quad_renderer,
+            staging_belt: StagingBelt::new(1024),
+            frame_arena: FrameArena::new(),
+            post_process,
+            title: app.title,
+            commands: app.commands,
+            list_widget,
+            widgets: app.widgets,
+            on_event: app.on_event,
+            input: InputConfig::default(),
+            mouse_pos: (0.0, 0.0),
+            modifiers: ModifiersState::empty(),
+            click_tracker: ClickTracker::new(),
+            cursor_icon: CursorIcon::Default,
+            clock: FrameClock::new(),
+            exit: false,
+        };
+        for mut hook in app.on_init {
+            hook(&mut runner.context().0);
+        }
+        Ok(runner)
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// The dispatcher the list is changed through
+    pub fn commands(&self) -> &Dispatcher {
+        &self.commands
+    }
+
+    pub fn list_widget(&mut self) -> &mut TodoListWidget {
+        &mut self.list_widget
+    }
+
+    /// Whether a hook asked to exit or the window was closed
+    pub fn exit_requested(&self) -> bool {
+        self.exit
+    }
+
+    /// Handle an event for the runner's window, redrawing on `RedrawRequested`
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        if self.emit(&AppEvent::Window(event)) {
+            return;
+        }
+
+        match event {
+            WindowEvent::CloseRequested => self.exit = true,
+            WindowEvent::Resized(size) => self.resize(*size),
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            WindowEvent::Focused(false) => self.click_tracker.reset(),
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = position.to_logical::<f32>(self.window.scale_factor());
+                self.mouse_pos = (position.x, position.y);
+                self.list_widget.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1);
+                self.update_cursor();
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                // Touchpads scroll by physical pixels, the list by logical ones
+                let delta = match *delta {
+                    MouseScrollDelta::PixelDelta(position) => {
+                        let scale_factor = self.window.scale_factor();
+                        MouseScrollDelta::PixelDelta(PhysicalPosition::new(position.x / scale_factor, position.y / scale_factor))
+                    }
+                    lines => lines,
+                };
+                self.list_widget.handle_mouse_wheel(self.input.scroll_pixels(&delta));
+            }
+            WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                let (x, y) = self.mouse_pos;
+                if *state == ElementState::Pressed {
+                    let click_count = self.click_tracker.press(x, y, now_secs());
+                    let (width, height) = self.logical_size();
+                    self.list_widget.handle_mouse_down(x, y, click_count, width, height);
+                } else {
+                    self.list_widget.handle_mouse_up(x, y);
+                }
+                self.update_cursor();
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                // Ctrl/Super chords don't type their letter, as in the app
+                let command_held = (self.modifiers.control_key() || self.modifiers.super_key()) && !self.modifiers.alt_key();
+                match &event.logical_key {
+                    Key::Character(text) if !command_held => {
+                        text.chars().for_each(|c| self.list_widget.handle_char_input(c));
+                    }
+                    key => {
+                        if let Some(code) = named_keycode(key) {
+                            self.list_widget.handle_key_press(code);
+                        }
+                    }
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                let delta_time = self.clock.tick(now_secs());
+                self.update(delta_time);
+                match self.render() {
+                    Ok(()) => {}
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => self.resize(self.window.inner_size()),
+                    Err(wgpu::SurfaceError::OutOfMemory) => self.exit = true,
+                    Err(e) => tracing::error!("Render error: {:?}", e),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Resize the surface and relayout the list
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.surface.configure(&self.device, &self.config);
+        self.post_process.resize(size.width, size.height);
+        let (width, height) = self.logical_size();
+        self.list_widget.set_dimensions(width - 100.0, height - 200.0);
+    }
+
+    /// The window's size in logical pixels, which the list is laid out in
+    fn logical_size(&self) -> (f32, f32) {
+        let size = PhysicalSize::new(self.config.width, self.config.height).to_logical::<f32>(self.window.scale_factor());
+        (size.width, size.height)
+    }
+
+    /// Advance animations and pass the list's events to the hooks
+    pub fn update(&mut self, delta_time: f32) {
+        profile_scope!("update");
+        if self.list_widget.needs_update() {
+            self.list_widget.update(delta_time);
+        }
+        for event in self.list_widget.take_events() {
+            self.emit(&AppEvent::Ui(&event));
+        }
+        for widget in self.widgets.iter_mut().filter(|widget| widget.needs_update()) {
+            widget.update(delta_time);
+        }
+    }
+
+    /// Draw a frame: the list and widgets, bloom, then modals and the neon glow
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        profile_scope!("render");
+        let output = self.surface.get_current_texture()?;
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let parts = FrameParts {
+            device: &self.device,
+            queue: &self.queue,
+            staging_belt: &mut self.staging_belt,
+            glyph_brush: &mut self.glyph_brush,
+            quad_renderer: &mut self.quad_renderer,
+            frame_arena: &mut self.frame_arena,
+            post_process: &mut self.post_process,
+            color_glyphs: None,
+            diagnostics: None,
+        };
+        let settings = FrameSettings {
+            width: self.config.width,
+            height: self.config.height,
+            scale_factor: self.window.scale_factor() as f32,
+            time: self.clock.elapsed(),
+            glow_color: CyberpunkTheme::new().cyan(),
+        };
+
+        // The list and custom widgets glow; modals stay sharp on top of the bloom
+        let (title, list_widget, widgets) = (&self.title, &self.list_widget, &self.widgets);
+        let scene = |ctx: &mut RenderContext| {
+            ctx.draw_text(title, 30.0, 30.0, 48.0, [1.0, 0.255, 0.639, 1.0]);
+            list_widget.render_base(ctx);
+            for widget in widgets {
+                widget.render(ctx);
+            }
+        };
+        if let Err(e) = draw_frame(parts, settings, &view, scene, |ctx| list_widget.render_modals(ctx)) {
+            tracing::error!("Failed to draw the frame: {}", e);
+        }
+
+        output.present();
+        self.staging_belt.recall();
+        Ok(())
+    }
+
+    /// Pass an event to the hooks, returning whether one handled it
+    fn emit(&mut self, event: &AppEvent) -> bool {
+        let (mut ctx, hooks) = self.context();
+        call_hooks(hooks, event, |hook| hook(&mut ctx, event))
+    }
+
+    /// Borrow what the hooks may touch, alongside the hooks themselves
+    fn context(&mut self) -> (AppContext<'_>, &mut Vec<EventHook>) {
+        let ctx = AppContext {
+            window: &self.window,
+            commands: &self.commands,
+            list_widget: &mut self.list_widget,
+            widgets: &mut self.widgets,
+            exit: &mut self.exit,
+        };
+        (ctx, &mut self.on_event)
+    }
+
+    fn update_cursor(&mut self) {
+        let (x, y) = self.mouse_pos;
+        let (width, height) = self.logical_size();
+        let icon = self.list_widget
+            .modal_cursor_at(x, y, width, height)
+            .or_else(|| self.list_widget.cursor_at(x, y))
+            .unwrap_or(CursorIcon::Default);
+        if icon != self.cursor_icon {
+            self.cursor_icon = icon;
+            self.window.set_cursor_icon(icon);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_every_hook_sees_list_events() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let hook = |name: &'static str| {
+            let calls = calls.clone();
+            Box::new(move |_: &AppEvent| {
+                calls.borrow_mut().push(name);
+                true
+            }) as Box<dyn FnMut(&AppEvent) -> bool>
+        };
+        let mut hooks = vec![hook("first"), hook("second")];
+
+        // The first hook handling a window event keeps it from the second
+        let window_event = WindowEvent::Focused(true);
+        assert!(call_hooks(&mut hooks, &AppEvent::Window(&window_event), |hook| hook(&AppEvent::Window(&window_event))));
+        assert_eq!(*calls.borrow(), ["first"]);
+
+        // A list event already happened, so both hooks hear of it
+        calls.borrow_mut().clear();
+        let ui_event = UiEvent::TaskAdded { id: uuid::Uuid::new_v4() };
+        assert!(!call_hooks(&mut hooks, &AppEvent::Ui(&ui_event), |hook| hook(&AppEvent::Ui(&ui_event))));
+        assert_eq!(*calls.borrow(), ["first", "second"]);
+    }
+}
@@ -0,0 +1,457 @@
+// Command line interface
+//
+// `tewduwu` on its own opens the window. With a subcommand it works on the
+// data file without one, so scripts and shell workflows manage the same tasks
+// the app shows:
+//
+//     tewduwu add "Buy milk" -p high -t errands
+//     tewduwu list --pending
+//     tewduwu done 3f2a9c1b
+//     tewduwu export --format csv > tasks.csv
+//     task export | tewduwu import --format taskwarrior -
+//
+// While the app is running it owns the data file and would overwrite it on its
+// next save, so changes are handed to it over the single-instance socket instead,
+// and it answers with what to print.
+// The output is meant for scripts and isn't translated.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::core::prelude::*;
+use crate::core::formats::taskwarrior;
+use crate::core::storage;
+use crate::ipc::{self, IpcMessage};
+
+/// Characters of a task id `list` prints
+const SHORT_ID_LEN: usize = 8;
+
+#[derive(Debug, Parser)]
+#[command(name = "tewduwu", version, about = "A neon todo list. Run without a command to open the window.")]
+pub struct Cli {
+    /// Work on the list with this name instead of the one last open in the app
+    #[arg(long, global = true)]
+    pub list: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum CliCommand {
+    /// Add a task
+    Add {
+        /// The task's title; several words are joined with spaces
+        #[arg(required = true, num_args = 1..)]
+        title: Vec<String>,
+        #[arg(short, long, value_parser = parse_priority)]
+        priority: Option<Priority>,
+        /// Tag the task; repeat for several tags
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Print the tasks, subtasks indented under their parents
+    List {
+        /// Leave out completed tasks
+        #[arg(long)]
+        pending: bool,
+    },
+    /// Mark a task completed
+    Done {
+        /// The task's id, or enough of its start to tell it apart, as `list` prints it
+        id: String,
+    },
+    /// Write the tasks to standard output
+    Export {
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+    },
+    /// Add the tasks another app exported; ones imported before are skipped
+    Import {
+        #[arg(short, long, value_enum)]
+        format: ImportFormat,
+        /// File to read, or - for standard input
+        file: PathBuf,
+    },
+}
+
+/// What `export` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The list as the data file stores it
+    Json,
+    /// One row per task, with a header
+    Csv,
+    /// JSON that `task import` reads
+    Taskwarrior,
+}
+
+/// What `import` reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// The JSON `task export` writes
+    Taskwarrior,
+}
+
+fn parse_priority(name: &str) -> Result<Priority, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "low" => Ok(Priority::Low),
+        "medium" => Ok(Priority::Medium),
+        "high" => Ok(Priority::High),
+        _ => Err(format!("Unknown priority {:?}, expected low, medium or high", name)),
+    }
+}
+
+/// A change made on the command line
+///
+/// It names its task the way the user typed it, so the instance that makes the
+/// change, this one or a running app, finds the task in its own copy of the list.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CliChange {
+    Add { item: Box<TodoItem> },
+    /// Complete the task whose id is, or starts with, this
+    Complete { id: String },
+    /// Add the tasks the list doesn't have yet
+    Import { items: Vec<TodoItem> },
+}
+
+impl CliChange {
+    /// The command that makes the change to `list`, if there's anything to do,
+    /// and the line to print once it's made
+    pub fn to_command(self, list: &TodoList) -> Result<(Option<Command>, String), String> {
+        match self {
+            CliChange::Add { item } => {
+                let report = format!("Added {} {}", short_id(item.id()), item.title());
+                Ok((Some(Command::AddItem { item: *item }), report))
+            }
+            CliChange::Complete { id } => {
+                let id = resolve_id(list, &id)?;
+                let title = list.get_item(id).map(TodoItem::title).unwrap_or_default();
+                let report = format!("Completed {} {}", short_id(id), title);
+                Ok((Some(Command::SetStatus { id, status: Status::Completed }), report))
+            }
+            CliChange::Import { items } => {
+                let total = items.len();
+                let added: Vec<Command> = items
+                    .into_iter()
+                    .filter(|item| list.get_item(item.id()).is_none())
+                    .map(|item| Command::AddItem { item })
+                    .collect();
+                let report = format!("Imported {} of {} tasks", added.len(), total);
+                Ok(((!added.is_empty()).then_some(Command::Batch(added)), report))
+            }
+        }
+    }
+}
+
+/// The index of the list with a name, ignoring case, or of the active list
+pub fn find_list(workspace: &Workspace, name: Option<&str>) -> Result<usize, String> {
+    match name {
+        Some(name) => workspace
+            .names()
+            .iter()
+            .position(|other| other.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("No list named {:?}; there are {}", name, workspace.names().join(", "))),
+        None => Ok(workspace.active()),
+    }
+}
+
+/// Run a subcommand on the data file at `path`, printing to `out`
+pub fn run(list: Option<&str>, command: CliCommand, path: &Path, out: &mut impl Write) -> Result<(), String> {
+    run_with(list, command, path, out, ipc::try_handoff)
+}
+
+/// `run`, with `handoff` delivering changes to a running instance and
+/// returning its answer, or `None` if there is none
+fn run_with(
+    list_name: Option<&str>,
+    command: CliCommand,
+    path: &Path,
+    out: &mut impl Write,
+    handoff: impl Fn(&IpcMessage) -> Option<ipc::Reply>,
+) -> Result<(), String> {
+    let write_error = |e: std::io::Error| format!("Failed to write output: {}", e);
+    let change = match command {
+        CliCommand::Add { title, priority, tags } => {
+            let title = title.join(" ");
+            if title.trim().is_empty() {
+                return Err("The title is empty".to_string());
+            }
+            let mut item = TodoItem::new(title.trim()).with_priority(priority.unwrap_or_default());
+            for tag in &tags {
+                item = item.with_tag(tag);
+            }
+            CliChange::Add { item: Box::new(item) }
+        }
+        CliCommand::Done { id } => CliChange::Complete { id },
+        CliCommand::Import { format: ImportFormat::Taskwarrior, file } => {
+            CliChange::Import { items: taskwarrior::from_json(&read_input(&file)?)? }
+        }
+        CliCommand::List { .. } | CliCommand::Export { .. } => {
+            let workspace = storage::load_workspace(path)?.ok_or_else(|| format!("No tasks saved at {} yet", path.display()))?;
+            let index = find_list(&workspace, list_name)?;
+            let list = workspace.list(index).ok_or("The workspace has no lists")?.lock().unwrap();
+            return match command {
+                CliCommand::List { pending } => print_list(&list, pending, out).map_err(write_error),
+                CliCommand::Export { format: ExportFormat::Json } => writeln!(out, "{}", storage::to_json(&list)?).map_err(write_error),
+                CliCommand::Export { format: ExportFormat::Csv } => write_csv(&list, out).map_err(write_error),
+                CliCommand::Export { format: ExportFormat::Taskwarrior } => {
+                    writeln!(out, "{}", taskwarrior::to_json(&list)?).map_err(write_error)
+                }
+                _ => unreachable!("only reading commands get here"),
+            };
+        }
+    };
+
+    // A running instance owns the data file; it makes the change and says how it went
+    let message = IpcMessage::Execute { list: list_name.map(str::to_string), change: change.clone() };
+    if let Some(reply) = handoff(&message) {
+        return writeln!(out, "{}", reply?).map_err(write_error);
+    }
+
+    let workspace = match storage::load_workspace(path)? {
+        Some(workspace) => workspace,
+        // Nothing saved yet: a first task starts the file
+        None if matches!(change, CliChange::Add { .. }) => Workspace::new(TodoList::new("Tasks")),
+        None => return Err(format!("No tasks saved at {} yet", path.display())),
+    };
+    let shared = workspace.list(find_list(&workspace, list_name)?).cloned().ok_or("The workspace has no lists")?;
+    let (command, report) = change.to_command(&shared.lock().unwrap())?;
+    if let Some(command) = command {
+        Dispatcher::new(shared).execute(command)?;
+        storage::save_workspace(&workspace, path)?;
+    }
+    writeln!(out, "{}", report).map_err(write_error)
+}
+
+/// Read a file, or standard input for `-`
+fn read_input(file: &Path) -> Result<String, String> {
+    if file == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read standard input: {}", e))?;
+        return Ok(text);
+    }
+    std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))
+}
+
+/// The start of an id, which is enough to tell tasks apart in practice
+fn short_id(id: Uuid) -> String {
+    id.to_string()[..SHORT_ID_LEN].to_string()
+}
+
+/// Find the task whose id starts with `prefix`
+fn resolve_id(list: &TodoList, prefix: &str) -> Result<Uuid, String> {
+    let prefix = prefix.trim().to_ascii_lowercase();
+    if prefix.is_empty() {
+        return Err("The task id is empty".to_string());
+    }
+    let matches: Vec<Uuid> = list
+        .all_items()
+        .iter()
+        .map(|item| item.id())
+        .filter(|id| id.to_string().starts_with(&prefix))
+        .collect();
+    match matches.as_slice() {
+        [id] => Ok(*id),
+        [] => Err(format!("No task with an id starting with {:?}", prefix)),
+        _ => Err(format!("{} tasks have ids starting with {:?}; give more of the id", matches.len(), prefix)),
+    }
+}
+
+/// Print one line per task: id, status, priority, then the title indented by depth, tags and due date
+fn print_list(list: &TodoList, pending: bool, out: &mut impl Write) -> std::io::Result<()> {
+    // Children of a left out task are left out with it
+    let mut skipped_depth = None;
+    for (item, depth) in list.hierarchical_view() {
+        match skipped_depth {
+            Some(skipped) if depth > skipped => continue,
+            _ => skipped_depth = None,
+        }
+        if pending && item.is_completed() {
+            skipped_depth = Some(depth);
+            continue;
+        }
+        let mark = match item.status() {
+            Status::NotStarted => ' ',
+            Status::InProgress => '~',
+            Status::Completed => 'x',
+        };
+        let priority = item.priority().to_string().to_lowercase();
+        write!(out, "{} [{}] {:<6} {}{}", short_id(item.id()), mark, priority, "  ".repeat(depth), item.title())?;
+        for tag in item.tags() {
+            write!(out, " #{}", tag)?;
+        }
+        if let Some(due) = item.due_date().and_then(|due| chrono::DateTime::from_timestamp(due as i64, 0)) {
+            write!(out, " (due {})", due.format("%Y-%m-%d"))?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Write the tasks as CSV, parents before their children
+fn write_csv(list: &TodoList, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "id,parent,title,status,priority,due,tags")?;
+    for (item, _) in list.hierarchical_view() {
+        let status = match item.status() {
+            Status::NotStarted => "not-started",
+            Status::InProgress => "in-progress",
+            Status::Completed => "completed",
+        };
+        let due = item
+            .due_date()
+            .and_then(|due| chrono::DateTime::from_timestamp(due as i64, 0))
+            .map(|due| due.to_rfc3339())
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            item.id(),
+            item.parent_id().map(|id| id.to_string()).unwrap_or_default(),
+            csv_field(item.title()),
+            status,
+            item.priority().to_string().to_lowercase(),
+            due,
+            csv_field(&item.tags().join(" ")),
+        )?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if it needs it, doubling any quotes inside
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A data file in a fresh temporary directory
+    fn data_path(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("tewduwu-cli-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("tewduwu.json")
+    }
+
+    fn run_offline(args: &[&str], path: &Path) -> Result<String, String> {
+        let cli = Cli::try_parse_from(std::iter::once("tewduwu").chain(args.iter().copied())).map_err(|e| e.to_string())?;
+        let mut out = Vec::new();
+        run_with(cli.list.as_deref(), cli.command.unwrap(), path, &mut out, |_| None)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_add_list_and_complete() {
+        let path = data_path("add");
+        let added = run_offline(&["add", "Buy", "milk", "-p", "high", "-t", "errands"], &path).unwrap();
+        let id = added.split_whitespace().nth(1).unwrap().to_string();
+        assert_eq!(added, format!("Added {} Buy milk\n", id));
+        run_offline(&["add", "Call mom"], &path).unwrap();
+
+        let listed = run_offline(&["list"], &path).unwrap();
+        assert!(listed.contains(&format!("{} [ ] high   Buy milk #errands\n", id)), "{}", listed);
+        assert!(listed.contains("[ ] medium Call mom\n"), "{}", listed);
+
+        run_offline(&["done", &id[..4]], &path).unwrap();
+        let pending = run_offline(&["list", "--pending"], &path).unwrap();
+        assert_eq!(pending.lines().count(), 1);
+        assert!(pending.contains("Call mom"));
+
+        let workspace = storage::load_workspace(&path).unwrap().unwrap();
+        let list = workspace.active_list().lock().unwrap();
+        assert!(list.all_items().iter().any(|item| item.title() == "Buy milk" && item.is_completed()));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_changes_go_to_a_running_instance() {
+        let path = data_path("handoff");
+        run_offline(&["add", "Saved"], &path).unwrap();
+
+        // The list goes by name and the task by the id as typed, for the running instance to find
+        let cli = Cli::try_parse_from(["tewduwu", "--list", "Errands", "done", "3f2a"]).unwrap();
+        let sent = std::cell::RefCell::new(Vec::new());
+        let mut out = Vec::new();
+        run_with(cli.list.as_deref(), cli.command.unwrap(), &path, &mut out, |message| {
+            sent.borrow_mut().push(message.clone());
+            Some(Ok("Completed 3f2a9c1b Milk".to_string()))
+        })
+        .unwrap();
+        assert!(matches!(
+            sent.borrow().as_slice(),
+            [IpcMessage::Execute { list: Some(list), change: CliChange::Complete { id } }] if list == "Errands" && id == "3f2a"
+        ));
+        assert_eq!(String::from_utf8(out).unwrap(), "Completed 3f2a9c1b Milk\n");
+        // The running instance saves it, not us
+        let workspace = storage::load_workspace(&path).unwrap().unwrap();
+        assert_eq!(workspace.item_count(), 1);
+
+        // What it refused isn't reported as done
+        let cli = Cli::try_parse_from(["tewduwu", "add", "Handed off"]).unwrap();
+        let mut out = Vec::new();
+        let refused = run_with(None, cli.command.unwrap(), &path, &mut out, |_| Some(Err("No list named \"Errands\"".to_string())));
+        assert!(refused.is_err());
+        assert!(out.is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_failed_changes_print_nothing() {
+        let path = data_path("failed");
+        run_offline(&["add", "One"], &path).unwrap();
+        let cli = Cli::try_parse_from(["tewduwu", "done", "zz"]).unwrap();
+        let mut out = Vec::new();
+        assert!(run_with(None, cli.command.unwrap(), &path, &mut out, |_| None).is_err());
+        assert!(out.is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_ids_and_lists_must_be_unambiguous() {
+        let path = data_path("resolve");
+        assert!(run_offline(&["list"], &path).unwrap_err().starts_with("No tasks saved"));
+        run_offline(&["add", "One"], &path).unwrap();
+        assert!(run_offline(&["done", "zz"], &path).unwrap_err().starts_with("No task with an id"));
+        assert!(run_offline(&["list", "--list", "Groceries"], &path).unwrap_err().starts_with("No list named"));
+        assert!(run_offline(&["add", "-p", "urgent", "Two"], &path).is_err());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_taskwarrior_import_skips_known_tasks() {
+        let path = data_path("taskwarrior");
+        let export = path.with_file_name("export.json");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&export, r#"[{"uuid":"5f4c2a8e-1f2b-4c3d-9e8f-0a1b2c3d4e5f","description":"Buy milk","status":"pending","priority":"H"}]"#).unwrap();
+
+        run_offline(&["add", "Existing"], &path).unwrap();
+        let import = ["import", "--format", "taskwarrior", export.to_str().unwrap()];
+        assert_eq!(run_offline(&import, &path).unwrap(), "Imported 1 of 1 tasks\n");
+        assert_eq!(run_offline(&import, &path).unwrap(), "Imported 0 of 1 tasks\n");
+        assert!(run_offline(&["list"], &path).unwrap().contains("5f4c2a8e [ ] high   Buy milk"));
+
+        let exported = run_offline(&["export", "--format", "taskwarrior"], &path).unwrap();
+        assert_eq!(taskwarrior::from_json(&exported).unwrap().len(), 2);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let mut list = TodoList::new("Export");
+        let id = list.add_item(TodoItem::new("Milk, eggs and \"bread\"").with_tag("errands"));
+        let mut out = Vec::new();
+        write_csv(&list, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("id,parent,title,status,priority,due,tags\n{},,\"Milk, eggs and \"\"bread\"\"\",not-started,medium,,errands\n", id)
+        );
+    }
+}
@@ -0,0 +1,1111 @@
+// User configuration, loaded from ~/.config/tewduwu/config.toml
+//
+// Every field has a default, so a partial (or missing) file is fine. Problems
+// are collected as human-readable messages instead of aborting startup; the
+// offending values fall back to their defaults.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::core::storage;
+use crate::i18n;
+use crate::ui::input::{Action, KeyChord, Keymap};
+
+/// Themes that can be selected in the config file
+pub const THEMES: &[&str] = &["cyberpunk"];
+
+/// Keybind actions starting with this run a plugin command, e.g. `"plugin:sample/count" = "Ctrl+K"`
+pub const PLUGIN_ACTION_PREFIX: &str = "plugin:";
+
+/// Permissions that can be granted to plugins in the `[plugins]` table
+pub const PLUGIN_PERMISSIONS: &[&str] = &["read-tasks", "write-tasks"];
+
+/// Default key chord for each action; every `Action` has one
+const DEFAULT_KEYBINDS: &[(&str, &str)] = &[
+    ("quit", "Escape"),
+    ("save", "F5"),
+    ("settings", "F2"),
+    ("inspector", "F12"),
+    ("profiler", "F9"),
+    ("diagnostics", "F3"),
+    ("undo", "Ctrl+Z"),
+    ("redo", "Ctrl+Shift+Z"),
+    ("add_and_continue", "Ctrl+Enter"),
+    ("focus_search", "Ctrl+F"),
+    ("select_left", "Shift+ArrowLeft"),
+    ("select_right", "Shift+ArrowRight"),
+    ("select_home", "Shift+Home"),
+    ("select_end", "Shift+End"),
+    ("toggle_sidebar", "Ctrl+B"),
+    ("select_previous", "ArrowUp"),
+    ("select_next", "ArrowDown"),
+    ("delete_task", "Delete"),
+    ("edit_task", "Enter"),
+    ("scroll_page_up", "PageUp"),
+    ("scroll_page_down", "PageDown"),
+    ("scroll_top", "Home"),
+    ("scroll_bottom", "End"),
+    ("focus_next", "Tab"),
+    ("focus_previous", "Shift+Tab"),
+    ("select_all", "Ctrl+A"),
+    ("copy", "Ctrl+C"),
+    ("cut", "Ctrl+X"),
+    ("paste", "Ctrl+V"),
+    ("word_left", "Ctrl+ArrowLeft"),
+    ("word_right", "Ctrl+ArrowRight"),
+    ("select_word_left", "Ctrl+Shift+ArrowLeft"),
+    ("select_word_right", "Ctrl+Shift+ArrowRight"),
+    ("cycle_present_mode", "F8"),
+    ("context_menu", "Shift+F10"),
+    ("sync", "F6"),
+    ("archive", "F7"),
+    ("focus_timer", "Ctrl+P"),
+    ("stop_focus_timer", "Ctrl+Shift+P"),
+];
+
+/// Modifier names in the order chords are normalized to, with accepted aliases
+const MODIFIERS: &[(&str, &[&str])] = &[
+    ("ctrl", &["ctrl", "control"]),
+    ("alt", &["alt", "option"]),
+    ("shift", &["shift"]),
+    ("super", &["super", "cmd", "meta", "win"]),
+];
+
+/// Normalize a key chord such as `"Shift+Ctrl+f"` to `"ctrl+shift+f"`
+///
+/// Modifiers may be given in any order and case; returns None for unknown
+/// modifiers or a missing key.
+pub fn normalize_chord(chord: &str) -> Option<String> {
+    let mut parts: Vec<&str> = chord.split('+').map(str::trim).collect();
+    let key = parts.pop()?.to_lowercase();
+    if key.is_empty() {
+        return None;
+    }
+
+    let mut held = [false; 4];
+    for part in parts {
+        let part = part.to_lowercase();
+        let index = MODIFIERS
+            .iter()
+            .position(|(_, aliases)| aliases.contains(&part.as_str()))?;
+        held[index] = true;
+    }
+
+    let mut normalized = String::new();
+    for ((name, _), _) in MODIFIERS.iter().zip(held).filter(|(_, held)| *held) {
+        normalized.push_str(name);
+        normalized.push('+');
+    }
+    normalized.push_str(&key);
+    Some(normalized)
+}
+
+/// Autosave intervals offered in the Settings panel, in seconds
+pub const AUTOSAVE_PRESETS: &[f32] = &[5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// Scroll speed multipliers offered in the Settings panel
+pub const SCROLL_SPEED_PRESETS: &[f32] = &[0.5, 1.0, 1.5, 2.0, 3.0];
+
+/// Frame rate caps offered in the Settings panel; 0 is uncapped
+pub const MAX_FPS_PRESETS: &[u32] = &[0, 30, 60, 120, 144];
+
+/// Highest frame rate cap accepted in the config file
+const MAX_FPS_LIMIT: u32 = 1000;
+
+/// Auto-archive ages offered in the Settings panel, in days; 0 is off
+pub const ARCHIVE_AFTER_PRESETS: &[u32] = &[0, 1, 7, 30, 90];
+
+/// Longest auto-archive age accepted in the config file, ten years
+const MAX_ARCHIVE_AFTER_DAYS: u32 = 3650;
+
+/// Longest focus session and break accepted in the config file, three hours
+const MAX_FOCUS_MINUTES: u32 = 180;
+
+/// Shortest time between scheduled syncs, to go easy on the server
+const MIN_SYNC_INTERVAL_SECS: f32 = 30.0;
+
+/// Longest reminder lead time and snooze accepted in the config file, a week
+const MAX_REMINDER_MINUTES: u32 = 7 * 24 * 60;
+
+/// How finished frames are handed to the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresentMode {
+    /// Wait for the display's refresh (vsync); supported everywhere
+    Fifo,
+    /// Wait for the refresh, but let newer frames replace queued ones
+    Mailbox,
+    /// Show frames as soon as they're done, which may tear
+    Immediate,
+}
+
+impl PresentMode {
+    pub const ALL: &'static [PresentMode] = &[PresentMode::Fifo, PresentMode::Mailbox, PresentMode::Immediate];
+
+    /// Name used in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            PresentMode::Fifo => "fifo",
+            PresentMode::Mailbox => "mailbox",
+            PresentMode::Immediate => "immediate",
+        }
+    }
+
+    /// The mode after this one, wrapping around to the first
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The matching wgpu mode if the surface supports it, or Fifo, which every surface does
+    pub fn resolve(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let mode = match self {
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        };
+        if supported.contains(&mode) {
+            mode
+        } else {
+            wgpu::PresentMode::Fifo
+        }
+    }
+}
+
+/// When frames are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RedrawMode {
+    /// Only after input or while something animates, idling otherwise
+    OnDemand,
+    /// Every frame, as fast as the present mode and frame cap allow
+    Continuous,
+}
+
+/// Presenting and pacing frames
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    pub present_mode: PresentMode,
+    /// Frames per second to draw at most; 0 leaves it to the present mode
+    pub max_fps: u32,
+    pub redraw: RedrawMode,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            present_mode: PresentMode::Fifo,
+            max_fps: 0,
+            redraw: RedrawMode::OnDemand,
+        }
+    }
+}
+
+/// Theme selection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub name: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self { name: "cyberpunk".to_string() }
+    }
+}
+
+/// Font selection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontConfig {
+    /// TTF/OTF file to use instead of the bundled Inconsolata
+    pub path: Option<PathBuf>,
+
+    /// Fonts tried in order for characters the main font lacks, such as emoji or CJK,
+    /// before the bundled symbol font and common system fonts
+    pub fallbacks: Vec<PathBuf>,
+
+    /// Draw emoji in color from the system's color emoji font, if it has one
+    pub color_emoji: bool,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        Self { path: None, fallbacks: Vec::new(), color_emoji: true }
+    }
+}
+
+/// Post-processing settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EffectsConfig {
+    pub bloom: bool,
+    pub bloom_threshold: f32,
+    pub bloom_intensity: f32,
+    pub bloom_saturation: f32,
+    pub glow: bool,
+    /// Hue of the glow in degrees, 0 to 360; the default is the theme's cyan
+    pub glow_hue: f32,
+    pub glow_intensity: f32,
+    pub glow_size: f32,
+    /// How far the glow swells and fades, as a share of its intensity; 0 holds it steady
+    pub glow_pulse: f32,
+}
+
+impl Default for EffectsConfig {
+    fn default() -> Self {
+        Self {
+            bloom: true,
+            bloom_threshold: 0.7,
+            bloom_intensity: 0.5,
+            bloom_saturation: 1.1,
+            glow: true,
+            glow_hue: 177.0,
+            glow_intensity: 0.8,
+            glow_size: 10.0,
+            glow_pulse: 0.0,
+        }
+    }
+}
+
+impl EffectsConfig {
+    /// The glow's color: its hue at full saturation and brightness
+    pub fn glow_color(&self) -> [f32; 4] {
+        let channel = |n: f32| {
+            let k = (n + self.glow_hue / 60.0) % 6.0;
+            1.0 - k.min(4.0 - k).clamp(0.0, 1.0)
+        };
+        [channel(5.0), channel(3.0), channel(1.0), 1.0]
+    }
+}
+
+/// WASM plugins, loaded from the `plugins` directory next to the config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginsConfig {
+    pub enabled: bool,
+
+    /// Plugin name -> permissions it may use, e.g. `sample = ["read-tasks"]`
+    pub grants: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            grants: BTreeMap::new(),
+        }
+    }
+}
+
+/// Syncing a list with a CalDAV task collection, e.g. a Nextcloud Tasks list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// The collection's URL; syncing is off while unset
+    pub url: Option<String>,
+    pub username: String,
+    /// Preferably an app password, as it's stored in plain text
+    pub password: String,
+    /// Name of the list to sync; the first list when unset
+    pub list: Option<String>,
+    /// Seconds between syncs while the app runs; 0 syncs only at startup and on the sync key
+    pub interval_secs: f32,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            username: String::new(),
+            password: String::new(),
+            list: None,
+            interval_secs: 300.0,
+        }
+    }
+}
+
+/// Reminders of tasks coming due and overdue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    /// Minutes before a task is due to remind of it, unless the task sets its own
+    pub lead_minutes: u32,
+    /// Minutes snoozing pushes a task's due date back by
+    pub snooze_minutes: u32,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lead_minutes: 15,
+            snooze_minutes: 10,
+        }
+    }
+}
+
+/// Focus sessions on a task (pomodoro)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusConfig {
+    /// Minutes a focus session counts down
+    pub session_minutes: u32,
+    /// Minutes of the break that follows a finished session; 0 skips it
+    pub break_minutes: u32,
+    /// Notify when a session or break is over
+    pub notify: bool,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            session_minutes: 25,
+            break_minutes: 5,
+            notify: true,
+        }
+    }
+}
+
+/// The quick-add popup, opened from any app by a hotkey (hotkey feature)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuickAddConfig {
+    /// System-wide chord that opens the popup, written like a keybind; empty turns it off
+    pub hotkey: String,
+    /// Name of the list the hotkey's popup adds to; the first list when unset
+    pub list: Option<String>,
+}
+
+impl Default for QuickAddConfig {
+    fn default() -> Self {
+        Self {
+            hotkey: "Ctrl+Alt+Space".to_string(),
+            list: None,
+        }
+    }
+}
+
+/// Keyboard and mouse behaviour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    /// Seconds a navigation or deletion key must be held before it starts repeating
+    pub key_repeat_delay_secs: f32,
+    /// Seconds between repeats while the key stays held
+    pub key_repeat_interval_secs: f32,
+    /// Multiplier applied to every wheel and touchpad scroll
+    pub scroll_speed: f32,
+    /// Pixels scrolled per wheel notch; touchpads report pixels and ignore this
+    pub scroll_line_height: f32,
+    /// Invert the scroll direction so content follows the fingers
+    pub natural_scrolling: bool,
+    /// Let the keyboard selection follow the hovered row
+    pub focus_follows_mouse: bool,
+    /// Seconds the pointer must rest on the search box before it takes focus
+    pub hover_focus_delay_secs: f32,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            key_repeat_delay_secs: 0.4,
+            key_repeat_interval_secs: 0.035,
+            scroll_speed: 1.0,
+            scroll_line_height: 20.0,
+            natural_scrolling: false,
+            focus_follows_mouse: false,
+            hover_focus_delay_secs: 0.5,
+        }
+    }
+}
+
+impl InputConfig {
+    /// How far a wheel or touchpad event should scroll, in pixels (positive scrolls down)
+    ///
+    /// Wheels report lines and touchpads report pixels; both come out as pixels
+    /// scaled by the speed setting.
+    pub fn scroll_pixels(&self, delta: &winit::event::MouseScrollDelta) -> f32 {
+        // winit reports positive values for scrolling up, i.e. towards the top of the content
+        let pixels = match delta {
+            winit::event::MouseScrollDelta::LineDelta(_, lines) => -lines * self.scroll_line_height,
+            winit::event::MouseScrollDelta::PixelDelta(position) => -position.y as f32,
+        };
+        let direction = if self.natural_scrolling { -1.0 } else { 1.0 };
+        pixels * self.scroll_speed * direction
+    }
+}
+
+/// Application configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: ThemeConfig,
+    pub font: FontConfig,
+
+    /// Action name -> key chord, e.g. `settings = "F2"` or `focus_search = "Ctrl+F"`
+    pub keybinds: BTreeMap<String, String>,
+
+    pub effects: EffectsConfig,
+    pub input: InputConfig,
+    pub display: DisplayConfig,
+    pub plugins: PluginsConfig,
+    pub sync: SyncConfig,
+    pub notifications: NotificationsConfig,
+    pub quick_add: QuickAddConfig,
+    pub focus: FocusConfig,
+
+    /// UI language such as `de-DE`; detected from the system when unset
+    pub language: Option<String>,
+
+    /// Where the todo list is stored; defaults to `tewduwu.json` in the working directory
+    pub data_path: Option<PathBuf>,
+
+    /// Older files' switch for the present mode; `false` reads as `display.present_mode = "immediate"`
+    #[serde(skip_serializing)]
+    pub vsync: Option<bool>,
+
+    /// Seconds between crash-recovery snapshots of unsaved changes
+    pub autosave_interval_secs: f32,
+
+    /// Seconds without further edits before changes are written to the data file
+    pub autosave_delay_secs: f32,
+
+    /// Complete a task once all its subtasks are, and reopen it when one of them is
+    pub auto_complete_parents: bool,
+
+    /// Archive tasks this many days after they're completed; 0 keeps them in the list
+    pub archive_after_days: u32,
+
+    /// Re-apply this file whenever it's saved, without restarting
+    pub watch: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let keybinds = DEFAULT_KEYBINDS
+            .iter()
+            .map(|(action, key)| (action.to_string(), key.to_string()))
+            .collect();
+
+        Self {
+            theme: ThemeConfig::default(),
+            font: FontConfig::default(),
+            keybinds,
+            effects: EffectsConfig::default(),
+            input: InputConfig::default(),
+            display: DisplayConfig::default(),
+            plugins: PluginsConfig::default(),
+            sync: SyncConfig::default(),
+            notifications: NotificationsConfig::default(),
+            quick_add: QuickAddConfig::default(),
+            focus: FocusConfig::default(),
+            language: None,
+            data_path: None,
+            vsync: None,
+            autosave_interval_secs: 10.0,
+            autosave_delay_secs: 1.0,
+            auto_complete_parents: false,
+            archive_after_days: 0,
+            watch: true,
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file
+    ///
+    /// In the browser this names the localStorage entry instead.
+    pub fn path() -> PathBuf {
+        #[cfg(target_arch = "wasm32")]
+        {
+            PathBuf::from("config.toml")
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            dirs::config_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("tewduwu")
+                .join("config.toml")
+        }
+    }
+
+    /// Load the config file, falling back to defaults
+    ///
+    /// Returns the config together with any problems found, meant to be shown to the user.
+    pub fn load() -> (Self, Vec<String>) {
+        match storage::read_text(&Self::path()) {
+            Ok(Some(text)) => Self::from_toml(&text),
+            Ok(None) => (Self::default(), Vec::new()),
+            Err(e) => (Self::default(), vec![e]),
+        }
+    }
+
+    /// Parse and validate TOML text
+    pub fn from_toml(text: &str) -> (Self, Vec<String>) {
+        match toml::from_str::<Config>(text) {
+            Ok(config) => config.validated(),
+            Err(e) => (
+                Self::default(),
+                vec![format!("Invalid config file, using defaults: {}", e.message())],
+            ),
+        }
+    }
+
+    /// Watch the config file for changes, to hot-apply them
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch() -> ConfigWatcher {
+        ConfigWatcher::at_path(Self::path())
+    }
+
+    /// Serialize the config to TOML text
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize config: {}", e))
+    }
+
+    /// Write the config back to its file
+    pub fn save(&self) -> Result<(), String> {
+        storage::write_text(&Self::path(), &self.to_toml()?)
+    }
+
+    /// Replace invalid values with defaults, describing each fix
+    pub fn validated(mut self) -> (Self, Vec<String>) {
+        let defaults = Self::default();
+        let mut problems = Vec::new();
+
+        if !THEMES.contains(&self.theme.name.as_str()) {
+            problems.push(format!(
+                "Unknown theme {:?}, using {:?}",
+                self.theme.name, defaults.theme.name
+            ));
+            self.theme = defaults.theme.clone();
+        }
+
+        if let Some(language) = self.language.as_deref().filter(|language| !i18n::is_supported(language)) {
+            problems.push(format!(
+                "Unsupported language {:?}, available: {}",
+                language,
+                i18n::available_locales().join(", ")
+            ));
+            self.language = None;
+        }
+
+        if !(self.autosave_interval_secs.is_finite() && self.autosave_interval_secs >= 1.0) {
+            problems.push(format!(
+                "autosave_interval_secs must be at least 1, using {}",
+                defaults.autosave_interval_secs
+            ));
+            self.autosave_interval_secs = defaults.autosave_interval_secs;
+        }
+
+        if !(self.autosave_delay_secs.is_finite() && self.autosave_delay_secs >= 0.0) {
+            problems.push(format!(
+                "autosave_delay_secs must not be negative, using {}",
+                defaults.autosave_delay_secs
+            ));
+            self.autosave_delay_secs = defaults.autosave_delay_secs;
+        }
+
+        let effects = &mut self.effects;
+        let default_effects = &defaults.effects;
+        for (name, value, default, max) in [
+            ("bloom_threshold", &mut effects.bloom_threshold, default_effects.bloom_threshold, 1.0),
+            ("bloom_intensity", &mut effects.bloom_intensity, default_effects.bloom_intensity, 4.0),
+            ("bloom_saturation", &mut effects.bloom_saturation, default_effects.bloom_saturation, 4.0),
+            ("glow_hue", &mut effects.glow_hue, default_effects.glow_hue, 360.0),
+            ("glow_intensity", &mut effects.glow_intensity, default_effects.glow_intensity, 4.0),
+            ("glow_size", &mut effects.glow_size, default_effects.glow_size, 64.0),
+            ("glow_pulse", &mut effects.glow_pulse, default_effects.glow_pulse, 1.0),
+        ] {
+            if !(value.is_finite() && *value >= 0.0 && *value <= max) {
+                problems.push(format!("effects.{} must be between 0 and {}, using {}", name, max, default));
+                *value = default;
+            }
+        }
+
+        if self.vsync.take() == Some(false) && self.display.present_mode == PresentMode::Fifo {
+            self.display.present_mode = PresentMode::Immediate;
+        }
+
+        if self.display.max_fps > MAX_FPS_LIMIT {
+            problems.push(format!(
+                "display.max_fps must be at most {}, using {}",
+                MAX_FPS_LIMIT, defaults.display.max_fps
+            ));
+            self.display.max_fps = defaults.display.max_fps;
+        }
+
+        let input = &mut self.input;
+        let default_input = &defaults.input;
+        for (name, value, default, min, max) in [
+            ("key_repeat_delay_secs", &mut input.key_repeat_delay_secs, default_input.key_repeat_delay_secs, 0.05, 2.0),
+            ("key_repeat_interval_secs", &mut input.key_repeat_interval_secs, default_input.key_repeat_interval_secs, 0.01, 1.0),
+            ("scroll_speed", &mut input.scroll_speed, default_input.scroll_speed, 0.1, 10.0),
+            ("scroll_line_height", &mut input.scroll_line_height, default_input.scroll_line_height, 1.0, 200.0),
+            ("hover_focus_delay_secs", &mut input.hover_focus_delay_secs, default_input.hover_focus_delay_secs, 0.0, 5.0),
+        ] {
+            if !(value.is_finite() && *value >= min && *value <= max) {
+                problems.push(format!("input.{} must be between {} and {}, using {}", name, min, max, default));
+                *value = default;
+            }
+        }
+
+        for (plugin, permissions) in &mut self.plugins.grants {
+            permissions.retain(|permission| {
+                let known = PLUGIN_PERMISSIONS.contains(&permission.as_str());
+                if !known {
+                    problems.push(format!(
+                        "Unknown permission {:?} for plugin {:?}, available: {}",
+                        permission,
+                        plugin,
+                        PLUGIN_PERMISSIONS.join(", ")
+                    ));
+                }
+                known
+            });
+        }
+
+        if let Some(url) = self.sync.url.as_deref().filter(|url| !(url.starts_with("https://") || url.starts_with("http://"))) {
+            problems.push(format!("sync.url must be an http or https URL, not {:?}; syncing is off", url));
+            self.sync.url = None;
+        }
+
+        if !(self.sync.interval_secs.is_finite() && (self.sync.interval_secs == 0.0 || self.sync.interval_secs >= MIN_SYNC_INTERVAL_SECS)) {
+            problems.push(format!(
+                "sync.interval_secs must be 0 or at least {}, using {}",
+                MIN_SYNC_INTERVAL_SECS, defaults.sync.interval_secs
+            ));
+            self.sync.interval_secs = defaults.sync.interval_secs;
+        }
+
+        let notifications = &mut self.notifications;
+        let default_notifications = &defaults.notifications;
+        for (name, value, default, min, max) in [
+            ("lead_minutes", &mut notifications.lead_minutes, default_notifications.lead_minutes, 0, MAX_REMINDER_MINUTES),
+            ("snooze_minutes", &mut notifications.snooze_minutes, default_notifications.snooze_minutes, 1, MAX_REMINDER_MINUTES),
+        ] {
+            if !(min..=max).contains(value) {
+                problems.push(format!("notifications.{} must be between {} and {}, using {}", name, min, max, default));
+                *value = default;
+            }
+        }
+
+        let focus = &mut self.focus;
+        let default_focus = &defaults.focus;
+        for (name, value, default, min) in [
+            ("session_minutes", &mut focus.session_minutes, default_focus.session_minutes, 1),
+            ("break_minutes", &mut focus.break_minutes, default_focus.break_minutes, 0),
+        ] {
+            if !(min..=MAX_FOCUS_MINUTES).contains(value) {
+                problems.push(format!("focus.{} must be between {} and {}, using {}", name, min, MAX_FOCUS_MINUTES, default));
+                *value = default;
+            }
+        }
+
+        if self.archive_after_days > MAX_ARCHIVE_AFTER_DAYS {
+            problems.push(format!(
+                "archive_after_days must be at most {}, using {}",
+                MAX_ARCHIVE_AFTER_DAYS, defaults.archive_after_days
+            ));
+            self.archive_after_days = defaults.archive_after_days;
+        }
+
+        // Without a modifier, the hotkey would take its key away from every other app
+        let hotkey = self.quick_add.hotkey.trim();
+        if !hotkey.is_empty() && KeyChord::parse(hotkey).filter(|chord| !chord.modifiers.is_empty()).is_none() {
+            problems.push(format!(
+                "quick_add.hotkey must be a key chord with Ctrl, Alt, Shift or Super, not {:?}; using {:?}",
+                hotkey, defaults.quick_add.hotkey
+            ));
+            self.quick_add.hotkey = defaults.quick_add.hotkey.clone();
+        }
+
+        let mut seen_keys: BTreeMap<String, String> = BTreeMap::new();
+        let mut invalid_actions = Vec::new();
+        for (action, key) in &self.keybinds {
+            let is_plugin_action = action
+                .strip_prefix(PLUGIN_ACTION_PREFIX)
+                .is_some_and(|command| command.contains('/'));
+            if Action::from_name(action).is_none() && !is_plugin_action {
+                problems.push(format!("Unknown keybind action {:?}", action));
+                invalid_actions.push(action.clone());
+            } else if key.trim().is_empty() {
+                problems.push(format!("Keybind {:?} has no key", action));
+                invalid_actions.push(action.clone());
+            } else if KeyChord::parse(key).is_none() {
+                problems.push(format!("Keybind {:?} has an invalid key chord {:?}", action, key));
+                invalid_actions.push(action.clone());
+            } else if let Some(other) = normalize_chord(key).and_then(|chord| seen_keys.insert(chord, action.clone())) {
+                problems.push(format!("Key {:?} is bound to both {:?} and {:?}", key, other, action));
+                invalid_actions.push(action.clone());
+            }
+        }
+        for action in invalid_actions {
+            self.keybinds.remove(&action);
+        }
+        // A default key the user bound to something else stays with that
+        for (action, key) in defaults.keybinds {
+            let taken = normalize_chord(&key).is_some_and(|chord| seen_keys.contains_key(&chord));
+            if !taken {
+                self.keybinds.entry(action).or_insert(key);
+            }
+        }
+
+        (self, problems)
+    }
+
+    /// The key bound to an action, if any
+    pub fn keybind(&self, action: &str) -> Option<&str> {
+        self.keybinds.get(action).map(String::as_str)
+    }
+
+    /// Check whether a key chord (as produced by the event loop) triggers an action
+    pub fn is_bound(&self, action: &str, chord: &str) -> bool {
+        match (self.keybind(action).and_then(KeyChord::parse), KeyChord::parse(chord)) {
+            (Some(bound), Some(pressed)) => bound == pressed,
+            _ => false,
+        }
+    }
+
+    /// The action bound to a key chord
+    pub fn action_for(&self, chord: &str) -> Option<&'static str> {
+        let chord = KeyChord::parse(chord)?;
+        self.keymap().action(chord.modifiers, chord.key).map(Action::name)
+    }
+
+    /// The `[keybinds]` table as a lookup from key chords to actions
+    pub fn keymap(&self) -> Keymap {
+        Keymap::from_bindings(self.keybinds.iter().map(|(action, key)| (action.as_str(), key.as_str())))
+    }
+
+    /// The plugin command bound to a key chord, e.g. `plugin:sample/count`
+    pub fn plugin_command_for(&self, chord: &str) -> Option<&str> {
+        self.keybinds
+            .keys()
+            .filter(|action| action.starts_with(PLUGIN_ACTION_PREFIX))
+            .find(|action| self.is_bound(action, chord))
+            .map(String::as_str)
+    }
+
+    /// Directory plugins are loaded from, next to the config file
+    pub fn plugins_dir() -> PathBuf {
+        Self::path().with_file_name("plugins")
+    }
+
+    /// Startup script, next to the config file
+    pub fn script_path() -> PathBuf {
+        Self::path().with_file_name("init.rhai")
+    }
+
+    /// Resolved location of the todo list data
+    pub fn data_path(&self) -> PathBuf {
+        self.data_path.clone().unwrap_or_else(storage::default_data_path)
+    }
+
+    /// Surface present mode to use, given the modes the surface supports
+    pub fn present_mode(&self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        self.display.present_mode.resolve(supported)
+    }
+}
+
+/// Re-reads the config file whenever it changes on disk
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<std::time::SystemTime>,
+    /// Wall-clock time of the next check, in seconds
+    next_check: f64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ConfigWatcher {
+    /// Seconds between checks of the file's modification time
+    const POLL_INTERVAL_SECS: f64 = 1.0;
+
+    /// Watch a specific file; only changes made after this are reported
+    pub fn at_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            modified: Self::modified(&path),
+            path,
+            next_check: 0.0,
+        }
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// The file's config and its problems if it changed since the last poll
+    ///
+    /// Checks at most every `POLL_INTERVAL_SECS` of wall-clock time `now`, so it
+    /// can be called whenever the event loop wakes, whether frames are drawn or
+    /// not. A deleted file reads as the defaults, like a missing one does at startup.
+    pub fn poll(&mut self, now: f64) -> Option<(Config, Vec<String>)> {
+        if now < self.next_check {
+            return None;
+        }
+        self.next_check = now + Self::POLL_INTERVAL_SECS;
+
+        let modified = Self::modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(match storage::read_text(&self.path) {
+            Ok(Some(text)) => Config::from_toml(&text),
+            Ok(None) => (Config::default(), Vec::new()),
+            Err(e) => (Config::default(), vec![e]),
+        })
+    }
+
+    fn modified(path: &std::path::Path) -> Option<std::time::SystemTime> {
+        std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_file_uses_defaults() {
+        let (config, problems) = Config::from_toml("vsync = false\n[effects]\nbloom = false\n");
+        assert!(problems.is_empty());
+        assert_eq!(config.display.present_mode, PresentMode::Immediate);
+        assert_eq!(config.display.redraw, RedrawMode::OnDemand);
+        assert!(!config.effects.bloom);
+        assert!(config.effects.glow);
+        assert_eq!(config.keybind("settings"), Some("F2"));
+    }
+
+    #[test]
+    fn test_invalid_values_are_reported_and_replaced() {
+        let text = "autosave_interval_secs = 0\nautosave_delay_secs = -1\nlanguage = \"tlh\"\n[theme]\nname = \"vaporwave\"\n[keybinds]\nfly = \"F\"\n[input]\nkey_repeat_interval_secs = 0.0\n";
+        let (config, problems) = Config::from_toml(text);
+        assert_eq!(problems.len(), 6);
+        assert!(config.language.is_none());
+        assert_eq!(config.theme.name, "cyberpunk");
+        assert_eq!(config.autosave_interval_secs, 10.0);
+        assert_eq!(config.autosave_delay_secs, 1.0);
+        assert!(config.keybind("fly").is_none());
+        assert_eq!(config.input, InputConfig::default());
+    }
+
+    #[test]
+    fn test_chords_are_normalized() {
+        assert_eq!(normalize_chord("Shift+Ctrl+f").as_deref(), Some("ctrl+shift+f"));
+        assert_eq!(normalize_chord(" cmd + Enter ").as_deref(), Some("super+enter"));
+        assert_eq!(normalize_chord("Hyper+F"), None);
+        assert_eq!(normalize_chord("Ctrl+"), None);
+
+        let (config, problems) = Config::from_toml("[keybinds]\nsave = \"control+s\"\nquit = \"CTRL+S\"\n");
+        assert_eq!(problems.len(), 1);
+        assert!(config.is_bound("focus_search", "Ctrl+F"));
+        assert!(!config.is_bound("focus_search", "F"));
+        assert_eq!(config.action_for("Shift+ArrowLeft"), Some("select_left"));
+    }
+
+    #[test]
+    fn test_every_action_has_a_default_key() {
+        let keymap = Config::default().keymap();
+        for &action in Action::ALL {
+            assert!(keymap.chord_for(action).is_some(), "{} has no default key", action.name());
+        }
+
+        // Rebinding moves the action off its default key
+        let (config, problems) = Config::from_toml("[keybinds]\nselect_next = \"J\"\nselect_previous = \"K\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        let keymap = config.keymap();
+        assert_eq!(keymap.action(winit::keyboard::ModifiersState::empty(), winit::keyboard::KeyCode::KeyJ), Some(Action::SelectNext));
+        assert_eq!(keymap.action(winit::keyboard::ModifiersState::empty(), winit::keyboard::KeyCode::ArrowDown), None);
+
+        // Taking an action's default key leaves that action unbound
+        let (config, problems) = Config::from_toml("[keybinds]\nquit = \"Delete\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(config.keybind("delete_task"), None);
+        assert_eq!(config.action_for("Delete"), Some("quit"));
+    }
+
+    #[test]
+    fn test_every_theme_has_colors() {
+        for &name in THEMES {
+            assert!(crate::ui::CyberpunkTheme::named(name).is_some(), "{} has no colors", name);
+        }
+        assert!(crate::ui::CyberpunkTheme::named("solarized").is_none());
+    }
+
+    #[test]
+    fn test_display_settings() {
+        let (config, problems) = Config::from_toml("[display]\npresent_mode = \"mailbox\"\nmax_fps = 5000\nredraw = \"continuous\"\n");
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert_eq!(config.display.max_fps, 0);
+        assert_eq!(config.display.redraw, RedrawMode::Continuous);
+
+        // Modes the surface lacks fall back to Fifo
+        let supported = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Immediate];
+        assert_eq!(config.present_mode(&supported), wgpu::PresentMode::Fifo);
+        assert_eq!(PresentMode::Immediate.resolve(&supported), wgpu::PresentMode::Immediate);
+        assert_eq!(PresentMode::Immediate.next(), PresentMode::Fifo);
+
+        // An explicit present mode wins over the old vsync switch
+        let (config, _) = Config::from_toml("vsync = false\n[display]\npresent_mode = \"mailbox\"\n");
+        assert_eq!(config.display.present_mode, PresentMode::Mailbox);
+        assert!(!config.to_toml().unwrap().contains("vsync"));
+    }
+
+    #[test]
+    fn test_scroll_deltas() {
+        use winit::dpi::PhysicalPosition;
+        use winit::event::MouseScrollDelta;
+
+        let mut input = InputConfig::default();
+        assert_eq!(input.scroll_pixels(&MouseScrollDelta::LineDelta(0.0, -1.0)), 20.0);
+        assert_eq!(input.scroll_pixels(&MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 12.0))), -12.0);
+
+        input.scroll_speed = 2.0;
+        input.natural_scrolling = true;
+        assert_eq!(input.scroll_pixels(&MouseScrollDelta::LineDelta(0.0, -1.0)), -40.0);
+    }
+
+    #[test]
+    fn test_glow_color_follows_the_hue() {
+        let mut effects = EffectsConfig::default();
+        let cyan = crate::ui::CyberpunkTheme::new().cyan();
+        for (channel, expected) in effects.glow_color().iter().zip(cyan) {
+            assert!((channel - expected).abs() < 1e-5);
+        }
+
+        effects.glow_hue = 0.0;
+        assert_eq!(effects.glow_color(), [1.0, 0.0, 0.0, 1.0]);
+        effects.glow_hue = 300.0;
+        assert_eq!(effects.glow_color(), [1.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut config = Config { data_path: Some(PathBuf::from("/tmp/todos.json")), ..Config::default() };
+        config.effects.glow_size = 4.0;
+
+        let (loaded, problems) = Config::from_toml(&config.to_toml().unwrap());
+        assert!(problems.is_empty());
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn test_plugin_settings() {
+        let text = "[keybinds]\n\"plugin:sample/count\" = \"Ctrl+K\"\n\"plugin:sample\" = \"Ctrl+J\"\n[plugins.grants]\nsample = [\"read-tasks\", \"launch-missiles\"]\n";
+        let (config, problems) = Config::from_toml(text);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(config.plugins.enabled);
+        assert_eq!(config.plugins.grants["sample"], vec!["read-tasks".to_string()]);
+        assert_eq!(config.plugin_command_for("Ctrl+K"), Some("plugin:sample/count"));
+        assert_eq!(config.plugin_command_for("Ctrl+J"), None);
+        assert_eq!(config.action_for("Ctrl+K"), None);
+    }
+
+    #[test]
+    fn test_sync_settings() {
+        let (config, problems) = Config::from_toml("[sync]\nurl = \"https://cloud.example.com/tasks/\"\nusername = \"me\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(config.sync.url.as_deref(), Some("https://cloud.example.com/tasks/"));
+        assert_eq!(config.sync.interval_secs, 300.0);
+
+        let (config, problems) = Config::from_toml("[sync]\nurl = \"cloud.example.com\"\ninterval_secs = 5\n");
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert_eq!(config.sync, SyncConfig::default());
+    }
+
+    #[test]
+    fn test_notification_settings() {
+        let (config, problems) = Config::from_toml("[notifications]\nlead_minutes = 60\nsnooze_minutes = 0\n");
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert_eq!(config.notifications.lead_minutes, 60);
+        assert_eq!(config.notifications.snooze_minutes, 10);
+        assert!(config.notifications.enabled);
+    }
+
+    #[test]
+    fn test_archive_after_days() {
+        let (config, problems) = Config::from_toml("archive_after_days = 30\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(config.archive_after_days, 30);
+
+        let (config, problems) = Config::from_toml("archive_after_days = 100000\n");
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert_eq!(config.archive_after_days, 0);
+    }
+
+    #[test]
+    fn test_focus_settings() {
+        let (config, problems) = Config::from_toml("[focus]\nsession_minutes = 50\nbreak_minutes = 0\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!((config.focus.session_minutes, config.focus.break_minutes), (50, 0));
+        assert!(config.focus.notify);
+
+        let (config, problems) = Config::from_toml("[focus]\nsession_minutes = 0\nbreak_minutes = 600\n");
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert_eq!(config.focus, FocusConfig::default());
+    }
+
+    #[test]
+    fn test_quick_add_settings() {
+        let (config, problems) = Config::from_toml("[quick_add]\nhotkey = \"Super+N\"\nlist = \"Inbox\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(config.quick_add.hotkey, "Super+N");
+        assert_eq!(config.quick_add.list.as_deref(), Some("Inbox"));
+
+        // Off when empty; a bare key or a typo falls back to the default
+        let (config, problems) = Config::from_toml("[quick_add]\nhotkey = \"\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(config.quick_add.hotkey, "");
+        for hotkey in ["N", "Ctrl+Spcae"] {
+            let (config, problems) = Config::from_toml(&format!("[quick_add]\nhotkey = {:?}\n", hotkey));
+            assert_eq!(problems.len(), 1, "{:?}", problems);
+            assert_eq!(config.quick_add, QuickAddConfig::default());
+        }
+    }
+
+    #[test]
+    fn test_watcher_reports_changes() {
+        let path = std::env::temp_dir().join(format!("tewduwu-test-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "archive_after_days = 7\n").unwrap();
+        let mut watcher = ConfigWatcher::at_path(&path);
+
+        // The file as it was when watching started has already been loaded
+        assert!(watcher.poll(100.0).is_none());
+
+        // Bump the modification time explicitly; the file system's may be coarse
+        std::fs::write(&path, "archive_after_days = 14\nwatch = false\n").unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(watcher.poll(100.5).is_none());
+        let (config, problems) = watcher.poll(101.0).unwrap();
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(config.archive_after_days, 14);
+        assert!(!config.watch);
+        assert!(watcher.poll(102.0).is_none());
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll(103.0).unwrap().0, Config::default());
+    }
+
+    /// An idle app draws no frames and only polls when its event loop wakes
+    #[test]
+    fn test_watcher_reports_changes_while_idle() {
+        let path = std::env::temp_dir().join(format!("tewduwu-test-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "archive_after_days = 7\n").unwrap();
+        let mut watcher = ConfigWatcher::at_path(&path);
+        assert!(watcher.poll(0.0).is_none());
+
+        std::fs::write(&path, "archive_after_days = 14\n").unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        // One wake after a long sleep is enough, however few frames were drawn
+        assert_eq!(watcher.poll(60.0).unwrap().0.archive_after_days, 14);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+// Panic reporting (native only)
+//
+// A panic anywhere in the app runs the hook installed here before the process
+// goes down: the workspace is snapshotted to the recovery journal, so the next
+// launch offers to restore it, a crash report with the panic message and a
+// backtrace is written next to the data file, and a native message box points
+// the user at the report. Dialogs are shown with the tools each platform ships
+// (zenity or kdialog, osascript, PowerShell); without one, the report path is
+// only printed to stderr.
+
+use std::backtrace::Backtrace;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, TryLockError};
+
+use crate::core::journal::Journal;
+use crate::core::prelude::Workspace;
+use crate::core::storage;
+use crate::i18n;
+
+/// Set to skip the message box, e.g. in CI or when running under a debugger
+pub const NO_DIALOG_ENV: &str = "TEWDUWU_NO_CRASH_DIALOG";
+
+/// The workspace to rescue on a panic and the data file it belongs to
+struct Watched {
+    workspace: Arc<Mutex<Workspace>>,
+    data_path: PathBuf,
+}
+
+static WATCHED: Mutex<Option<Watched>> = Mutex::new(None);
+
+/// Install the panic hook, keeping the default one for the stderr message
+pub fn install() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        report_panic(info);
+    }));
+}
+
+/// Rescue this workspace into the recovery journal if the app panics
+pub fn watch(workspace: Arc<Mutex<Workspace>>, data_path: PathBuf) {
+    *WATCHED.lock().unwrap_or_else(|e| e.into_inner()) = Some(Watched { workspace, data_path });
+}
+
+fn report_panic(info: &PanicHookInfo) {
+    // Nothing in here may panic again: that would abort without a report
+    let watched = match WATCHED.try_lock() {
+        Ok(watched) => watched,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return,
+    };
+    let Some(watched) = watched.as_ref() else {
+        return;
+    };
+
+    let rescue = rescue_workspace(&watched.workspace, &watched.data_path);
+    let report = format_report(info, &rescue, &Backtrace::force_capture());
+    let report_dir = watched.data_path.parent().unwrap_or(Path::new("."));
+    let mut message = vec![i18n::tr("crash-message")];
+    if matches!(rescue, Ok(Some(_))) {
+        message.push(i18n::tr("crash-rescued"));
+    }
+    match write_report(report_dir, &report) {
+        Ok(path) => {
+            eprintln!("Crash report written to {}", path.display());
+            message.push(i18n::tr_args("crash-report", &[("path", &path.display().to_string())]));
+        }
+        Err(e) => eprintln!("{}", e),
+    }
+
+    if std::env::var_os(NO_DIALOG_ENV).is_none() {
+        show_dialog(&i18n::tr("crash-title"), &message.join("\n\n"));
+    }
+}
+
+/// Snapshot the workspace to the recovery journal, returning the journal's path
+///
+/// Returns `None` if the data file is already up to date, so the next launch
+/// doesn't ask about changes that aren't there. The panicking thread may be the
+/// one holding the workspace's or a list's lock, in which case the last
+/// periodic journal snapshot is the best there is.
+fn rescue_workspace(workspace: &Mutex<Workspace>, data_path: &Path) -> Result<Option<PathBuf>, String> {
+    let workspace = match workspace.try_lock() {
+        Ok(workspace) => workspace,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return Err("the workspace was locked by the panicking thread".to_string()),
+    };
+    let snapshot = workspace.try_snapshot().map_err(|e| format!("{} while panicking", e))?;
+    let path = data_path.with_extension("journal");
+    let mut journal = Journal::at_path(&path);
+    if let Ok(Some(saved)) = storage::read_text(data_path) {
+        journal.mark_saved_json(saved);
+    }
+    Ok(journal.snapshot(&snapshot)?.then_some(path))
+}
+
+/// The text of a crash report
+fn format_report(info: &PanicHookInfo, rescue: &Result<Option<PathBuf>, String>, backtrace: &Backtrace) -> String {
+    let message = info.payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "(no message)".to_string());
+    let location = info.location().map_or_else(|| "unknown".to_string(), |l| l.to_string());
+    let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+    let rescue = match rescue {
+        Ok(Some(path)) => format!("saved to {}", path.display()),
+        Ok(None) => "none".to_string(),
+        Err(e) => format!("not saved: {}", e),
+    };
+
+    format!(
+        "tewduwu-neon {} crashed at {}\n\nPanic: {}\nLocation: {}\nThread: {}\nOS: {} {}\nUnsaved changes: {}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        chrono::Local::now().to_rfc3339(),
+        message,
+        location,
+        thread,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        rescue,
+        backtrace,
+    )
+}
+
+/// Write a report to a new timestamped file in `dir`, returning its path
+fn write_report(dir: &Path, report: &str) -> Result<PathBuf, String> {
+    let name = format!("crash-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S-%3f"));
+    let path = dir.join(name);
+    storage::write_text(&path, report)?;
+    Ok(path)
+}
+
+/// Show a blocking error message box, if the platform has a way to
+pub fn show_dialog(title: &str, message: &str) {
+    let shown = if cfg!(target_os = "windows") {
+        let script = format!(
+            "Add-Type -AssemblyName PresentationFramework; [System.Windows.MessageBox]::Show('{}', '{}', 'OK', 'Error')",
+            message.replace('\'', "''"),
+            title.replace('\'', "''"),
+        );
+        run(Command::new("powershell").args(["-NoProfile", "-Command", &script]))
+    } else if cfg!(target_os = "macos") {
+        let script = format!(
+            "display alert \"{}\" message \"{}\" as critical",
+            title.replace('\\', "\\\\").replace('"', "\\\""),
+            message.replace('\\', "\\\\").replace('"', "\\\""),
+        );
+        run(Command::new("osascript").args(["-e", &script]))
+    } else {
+        run(Command::new("zenity").args(["--error", "--no-markup", "--title", title, "--text", message]))
+            || run(Command::new("kdialog").args(["--title", title, "--error", message]))
+    };
+    if !shown {
+        eprintln!("{}: {}", title, message);
+    }
+}
+
+/// Run a dialog command, returning false if it couldn't be started
+fn run(command: &mut Command) -> bool {
+    command.status().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::TodoList;
+
+    #[test]
+    fn test_rescue_writes_the_journal() {
+        let data_path = std::env::temp_dir().join(format!("tewduwu-test-{}.json", uuid::Uuid::new_v4()));
+        let mut list = TodoList::new("Crash");
+        list.create_item("Unsaved task");
+        let workspace = Mutex::new(Workspace::new(list));
+
+        let path = rescue_workspace(&workspace, &data_path).unwrap().expect("nothing was saved yet");
+        let recovered = Journal::for_data_path(&data_path).recover().unwrap().unwrap();
+        assert_eq!(recovered.item_count(), 1);
+        std::fs::remove_file(&path).unwrap();
+
+        // Nothing to rescue once the data file has it all
+        storage::save_workspace(&workspace.lock().unwrap(), &data_path).unwrap();
+        assert_eq!(rescue_workspace(&workspace, &data_path).unwrap(), None);
+        assert!(Journal::for_data_path(&data_path).recover().unwrap().is_none());
+
+        // A list locked by the (pretend) panicking thread
+        let list = workspace.lock().unwrap().active_list().clone();
+        let guard = list.lock().unwrap();
+        assert!(rescue_workspace(&workspace, &data_path).is_err());
+        drop(guard);
+
+        // The workspace itself locked
+        let _guard = workspace.lock().unwrap();
+        assert!(rescue_workspace(&workspace, &data_path).is_err());
+        std::fs::remove_file(&data_path).unwrap();
+    }
+
+    #[test]
+    fn test_reports_get_unique_files() {
+        let dir = std::env::temp_dir().join(format!("tewduwu-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = write_report(&dir, "first").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = write_report(&dir, "second").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "second");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
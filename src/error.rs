@@ -0,0 +1,71 @@
+// Errors that stop the app from starting
+//
+// Everything after startup reports its problems as toasts or log lines and
+// carries on, but without a window or a GPU there's nothing to show them in.
+// These errors end the app instead, after a native message box (the same one
+// the crash reporter uses) tells the user what went wrong and what to try.
+
+use std::fmt;
+
+use crate::i18n;
+
+/// Why the app couldn't start
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// The event loop or the main window couldn't be created
+    Window(String),
+    /// No backend gave a surface and an adapter that could draw to it,
+    /// with what went wrong for each backend tried
+    NoAdapter(Vec<String>),
+    /// A background thread the app needs couldn't be started
+    Startup(String),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Window(e) => write!(f, "Failed to create the window: {}", e),
+            AppError::NoAdapter(attempts) => write!(f, "No usable graphics adapter ({})", attempts.join("; ")),
+            AppError::Startup(e) => write!(f, "Failed to start: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl AppError {
+    /// What to tell the user, in the UI language, followed by the details
+    pub fn user_message(&self) -> String {
+        let hint = match self {
+            AppError::NoAdapter(_) => i18n::tr("startup-no-adapter"),
+            _ => i18n::tr("startup-failed-message"),
+        };
+        format!("{}\n\n{}", hint, self)
+    }
+
+    /// Log the error and show it in a message box
+    pub fn report(&self) {
+        tracing::error!("{}", self);
+        #[cfg(not(target_arch = "wasm32"))]
+        if std::env::var_os(crate::crash::NO_DIALOG_ENV).is_none() {
+            crate::crash::show_dialog(&i18n::tr("startup-failed-title"), &self.user_message());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_adapter_lists_each_attempt() {
+        let error = AppError::NoAdapter(vec![
+            "PRIMARY: no adapter".to_string(),
+            "GL: surface creation failed".to_string(),
+        ]);
+        assert_eq!(error.to_string(), "No usable graphics adapter (PRIMARY: no adapter; GL: surface creation failed)");
+        // The details follow the hint, so they end up in bug reports
+        assert!(error.user_message().ends_with(&error.to_string()));
+        assert!(error.user_message().len() > error.to_string().len());
+    }
+}
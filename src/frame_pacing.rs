@@ -0,0 +1,218 @@
+// Frame pacing and timing
+//
+// Decides, each time the event loop runs out of events, whether to draw a
+// frame now, later or not until something happens. In the on-demand mode the
+// app only draws after input that damaged the frame (see `ui::damage`) or
+// while something animates, so it idles at next to no CPU; the frame rate cap
+// spaces out the frames it does draw.
+// `FrameClock` measures how far each drawn frame advances animations, and
+// `FpsCounter` how many frames are drawn a second.
+// Times are seconds from `ui::input::now_secs` rather than `Instant`s, which
+// panic in the browser.
+
+use crate::config::{DisplayConfig, RedrawMode};
+
+/// Longest step one frame advances animations and timers by
+///
+/// The first frame after the window was minimized, or after idling in the
+/// on-demand mode, would otherwise jump every animation to its end.
+pub const MAX_FRAME_DELTA: f32 = 0.1;
+
+/// Measures the time between frames, and the animation time since the first
+#[derive(Debug, Clone, Default)]
+pub struct FrameClock {
+    last_frame: Option<f64>,
+    elapsed: f64,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a frame at `time`, returning the seconds since the last one
+    ///
+    /// The first frame takes no time, and the step is clamped to
+    /// `MAX_FRAME_DELTA`, or to zero if the system clock went backwards.
+    pub fn tick(&mut self, time: f64) -> f32 {
+        let delta = self.last_frame.map_or(0.0, |last| (time - last).clamp(0.0, MAX_FRAME_DELTA as f64));
+        self.last_frame = Some(time);
+        self.elapsed += delta;
+        delta as f32
+    }
+
+    /// Seconds of animation time so far: every frame's step added up
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed as f32
+    }
+}
+
+/// Seconds the frame rate is averaged over
+const FPS_WINDOW: f64 = 1.0;
+
+/// Counts drawn frames to report the frame rate, updated about once a second
+///
+/// In the on-demand redraw mode this is how often frames are actually drawn,
+/// not how fast they could be.
+#[derive(Debug, Clone, Default)]
+pub struct FpsCounter {
+    window_start: Option<f64>,
+    /// Frames drawn since the window started
+    frames: u32,
+    fps: f32,
+}
+
+impl FpsCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a frame drawn at `time`
+    pub fn frame_drawn(&mut self, time: f64) {
+        let Some(start) = self.window_start else {
+            self.window_start = Some(time);
+            return;
+        };
+        self.frames += 1;
+        let elapsed = time - start;
+        if elapsed >= FPS_WINDOW {
+            self.fps = (self.frames as f64 / elapsed) as f32;
+            self.window_start = Some(time);
+            self.frames = 0;
+        }
+    }
+
+    /// Frames per second over the last full window, 0 before there was one
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+}
+
+/// What the event loop should do next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacing {
+    /// Draw a frame now
+    Redraw,
+    /// Draw a frame after this many seconds, to keep to the frame rate cap
+    RedrawIn(f64),
+    /// Nothing to draw; sleep until an event arrives
+    Idle,
+}
+
+/// Tracks when frames are due
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    redraw: RedrawMode,
+    /// Shortest time between frames, from the frame rate cap
+    min_interval: f64,
+    last_frame: Option<f64>,
+    /// Something changed since the last frame, e.g. input arrived
+    dirty: bool,
+}
+
+impl FramePacer {
+    pub fn new(display: &DisplayConfig) -> Self {
+        let mut pacer = Self {
+            redraw: display.redraw,
+            min_interval: 0.0,
+            last_frame: None,
+            // The first frame is always drawn
+            dirty: true,
+        };
+        pacer.configure(display);
+        pacer
+    }
+
+    /// Apply changed display settings
+    pub fn configure(&mut self, display: &DisplayConfig) {
+        self.redraw = display.redraw;
+        self.min_interval = match display.max_fps {
+            0 => 0.0,
+            fps => 1.0 / fps as f64,
+        };
+        self.dirty = true;
+    }
+
+    /// Ask for a frame, e.g. because input changed what's shown
+    pub fn request_redraw(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Record that a frame was drawn at `time`
+    pub fn frame_drawn(&mut self, time: f64) {
+        self.last_frame = Some(time);
+        self.dirty = false;
+    }
+
+    /// Whether and when to draw the next frame; `animating` if anything is still moving
+    pub fn next(&self, animating: bool, time: f64) -> Pacing {
+        if !(self.dirty || animating || self.redraw == RedrawMode::Continuous) {
+            return Pacing::Idle;
+        }
+        match self.last_frame {
+            Some(last) if time - last < self.min_interval => Pacing::RedrawIn(last + self.min_interval - time),
+            _ => Pacing::Redraw,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_on_demand_idles_until_something_changes() {
+        let mut pacer = FramePacer::new(&DisplayConfig::default());
+        assert_eq!(pacer.next(false, 0.0), Pacing::Redraw);
+        pacer.frame_drawn(0.0);
+        assert_eq!(pacer.next(false, 1.0), Pacing::Idle);
+        assert_eq!(pacer.next(true, 1.0), Pacing::Redraw);
+
+        pacer.request_redraw();
+        assert_eq!(pacer.next(false, 1.0), Pacing::Redraw);
+        pacer.frame_drawn(1.0);
+        assert_eq!(pacer.next(false, 1.0), Pacing::Idle);
+    }
+
+    #[test]
+    fn test_clock_clamps_spikes() {
+        let mut clock = FrameClock::new();
+        assert_eq!(clock.tick(100.0), 0.0);
+        assert!((clock.tick(100.02) - 0.02).abs() < 1e-6);
+        // Back from being minimized for a minute
+        assert_eq!(clock.tick(160.0), MAX_FRAME_DELTA);
+        assert_eq!(clock.tick(159.0), 0.0);
+        assert!((clock.elapsed() - 0.12).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fps_over_a_second() {
+        let mut counter = FpsCounter::new();
+        for frame in 0..60 {
+            counter.frame_drawn(5.0 + frame as f64 / 60.0);
+        }
+        assert_eq!(counter.fps(), 0.0);
+        counter.frame_drawn(6.0);
+        assert!((counter.fps() - 60.0).abs() < 1e-3);
+
+        // Idling on demand, the next window spans the gap
+        counter.frame_drawn(10.0);
+        assert!((counter.fps() - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_frame_rate_cap() {
+        let display = DisplayConfig { max_fps: 50, redraw: RedrawMode::Continuous, ..DisplayConfig::default() };
+        let mut pacer = FramePacer::new(&display);
+        pacer.frame_drawn(10.0);
+        match pacer.next(false, 10.005) {
+            Pacing::RedrawIn(wait) => assert!((wait - 0.015).abs() < 1e-9),
+            other => panic!("expected a wait, got {:?}", other),
+        }
+        assert_eq!(pacer.next(false, 10.021), Pacing::Redraw);
+
+        // Uncapped draws right away
+        pacer.configure(&DisplayConfig { max_fps: 0, ..display });
+        assert_eq!(pacer.next(false, 10.005), Pacing::Redraw);
+    }
+}
@@ -0,0 +1,563 @@
+// Offscreen rendering (native only)
+//
+// `HeadlessRenderer` draws frames the way the app does (scene, bloom, modals,
+// neon glow) into a texture instead of a window's surface, and reads them back
+// as RGBA pixels. With no winit window involved it runs in tests and examples,
+// which save frames as PNGs and compare them against golden images to catch
+// regressions in the bloom/glow pipeline:
+//
+//     let mut renderer = HeadlessRenderer::new(1024, 768)?;
+//     let frame = renderer.render_list(&list_widget)?;
+//     frame.save_png(Path::new("list.png"))?;
+//
+// Text is laid out with whatever `ui::text_metrics` measures with. The app
+// sets its font there; tests that share the process with widget tests leave
+// it alone so their estimated widths stay put.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use wgpu::util::StagingBelt;
+use wgpu::{Device, Queue, Texture, TextureFormat};
+use wgpu_glyph::{GlyphBrush, GlyphBrushBuilder};
+
+use crate::core::prelude::{Priority, TodoItem, TodoList};
+use crate::profiling::profile_scope;
+use crate::ui::fonts;
+use crate::ui::prelude::*;
+
+/// Frames are drawn and read back in this format, like the sRGB surfaces the app prefers
+const FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// Color channels may be this far off a golden image before a pixel counts as different
+///
+/// GPUs and drivers round blur and blend math differently.
+const CHANNEL_TOLERANCE: u8 = 8;
+
+/// Share of a frame's pixels that may differ from its golden image
+const PIXEL_TOLERANCE: f64 = 0.001;
+
+/// Set to rewrite golden images from the current output instead of comparing against them
+pub const UPDATE_GOLDEN_VAR: &str = "TEWDUWU_UPDATE_GOLDEN";
+
+/// A rendered frame: 8-bit RGBA pixels, row by row from the top
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Write the frame as a PNG, creating the directory it goes in
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Read a frame saved with `save_png`
+    pub fn load_png(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut reader = png::Decoder::new(std::io::BufReader::new(file))
+            .read_info()
+            .map_err(|e| format!("Invalid PNG {}: {}", path.display(), e))?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut pixels)
+            .map_err(|e| format!("Invalid PNG {}: {}", path.display(), e))?;
+        if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+            return Err(format!("{} is not an 8-bit RGBA image", path.display()));
+        }
+        pixels.truncate(info.buffer_size());
+        Ok(Self { width: info.width, height: info.height, pixels })
+    }
+
+    /// Number of pixels with a channel more than `tolerance` away from the other frame's
+    pub fn differing_pixels(&self, other: &Frame, tolerance: u8) -> Result<usize, String> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(format!(
+                "Frame is {}x{}, expected {}x{}",
+                self.width, self.height, other.width, other.height
+            ));
+        }
+        Ok(self.pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > tolerance))
+            .count())
+    }
+}
+
+/// Compare a frame with the golden image at `path`
+///
+/// With `TEWDUWU_UPDATE_GOLDEN` set the golden image is written from the frame
+/// instead; without it a missing golden image is an error, so a fresh checkout
+/// can't pass by comparing frames with themselves. On a mismatch the frame is
+/// saved next to the golden image as `<name>.actual.png` for a look at what
+/// changed.
+pub fn check_golden(frame: &Frame, path: &Path) -> Result<(), String> {
+    compare_golden(frame, path, std::env::var_os(UPDATE_GOLDEN_VAR).is_some())
+}
+
+/// `check_golden`, writing the golden image if `update`
+fn compare_golden(frame: &Frame, path: &Path, update: bool) -> Result<(), String> {
+    if update {
+        tracing::info!("Writing golden image {}", path.display());
+        return frame.save_png(path);
+    }
+    if !path.exists() {
+        let actual = actual_path(path);
+        frame.save_png(&actual)?;
+        return Err(format!(
+            "No golden image at {}; the frame was saved to {}, set {}=1 to accept it",
+            path.display(),
+            actual.display(),
+            UPDATE_GOLDEN_VAR
+        ));
+    }
+    let golden = Frame::load_png(path)?;
+    let differing = frame.differing_pixels(&golden, CHANNEL_TOLERANCE)?;
+    let allowed = (golden.pixels.len() / 4) as f64 * PIXEL_TOLERANCE;
+    if differing as f64 <= allowed {
+        return Ok(());
+    }
+    let actual = actual_path(path);
+    frame.save_png(&actual)?;
+    Err(format!(
+        "{} pixels differ from {}; the frame was saved to {}",
+        differing,
+        path.display(),
+        actual.display()
+    ))
+}
+
+/// Where the frame that failed to match a golden image is saved, e.g. `list.actual.png` for `list.png`
+fn actual_path(golden: &Path) -> PathBuf {
+    golden.with_extension("actual.png")
+}
+
+/// A list that renders the same on every run
+///
+/// A high priority `release` task with a medium priority `gpu` subtask, which
+/// has a completed low priority subtask of its own: every priority, tags,
+/// completion and two levels of nesting.
+pub fn sample_list() -> TodoList {
+    let mut list = TodoList::new("Golden");
+    let launch = list.add_item(TodoItem::new("Launch the neon release").with_priority(Priority::High).with_tag("release"));
+    let bloom = list.add_item(
+        TodoItem::new("Tune the bloom threshold")
+            .with_parent(launch)
+            .with_priority(Priority::Medium)
+            .with_tag("gpu"),
+    );
+    let glow = list.add_item(TodoItem::new("Check the glow on dark panels").with_parent(bloom).with_priority(Priority::Low));
+    if let Some(item) = list.get_item_mut(glow) {
+        item.mark_completed();
+    }
+    list
+}
+
+/// Renders frames into a texture and reads them back, without a window
+pub struct HeadlessRenderer {
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    width: u32,
+    height: u32,
+    target: Texture,
+    glyph_brush: GlyphBrush<()>,
+    quad_renderer: QuadRenderer,
+    staging_belt: StagingBelt,
+    frame_arena: FrameArena,
+    post_process: PostProcessStack,
+    /// Color widgets glow in unless they pick one
+    glow_color: [f32; 4],
+    /// Physical pixels per logical pixel, as on a HiDPI display
+    scale_factor: f32,
+}
+
+impl HeadlessRenderer {
+    /// Set up a GPU device and a `width` by `height` target
+    ///
+    /// Any adapter will do, software ones included. Blocks until the GPU is
+    /// ready, and fails where there is none, e.g. on CI machines without drivers.
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        let (width, height) = (width.max(1), height.max(1));
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = [false, true]
+            .into_iter()
+            .find_map(|force_fallback_adapter| {
+                pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    force_fallback_adapter,
+                    compatible_surface: None,
+                }))
+            })
+            .ok_or("No GPU adapter found")?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+            .map_err(|e| format!("Failed to create device: {}", e))?;
+        let (device, queue) = (Arc::new(device), Arc::new(queue));
+
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Target"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let glyph_brush = GlyphBrushBuilder::using_fonts(fonts::bundled()).build(&device, FORMAT);
+        let quad_renderer = QuadRenderer::new(&device, FORMAT);
+
+        let post_process = PostProcessStack::new(device.clone(), queue.clone(), FORMAT, &CyberpunkTheme::new(), width, height);
+
+        Ok(Self {
+            device,
+            queue,
+            width,
+            height,
+            target,
+            glyph_brush,
+            quad_renderer,
+            staging_belt: StagingBelt::new(1024),
+            frame_arena: FrameArena::new(),
+            post_process,
+            glow_color: CyberpunkTheme::new().cyan(),
+            scale_factor: 1.0,
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Use the app's bloom settings, e.g. to check a config's effects
+    pub fn set_bloom(&mut self, threshold: f32, intensity: f32, saturation: f32) {
+        self.post_process.set_bloom(threshold, intensity, saturation);
+    }
+
+    /// Draw like a display with this many physical pixels per logical pixel
+    ///
+    /// Widgets then lay out in a frame `size() / scale_factor` logical pixels big.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Draw the effects in `theme`'s colors, like the app with that theme in its config
+    pub fn set_theme(&mut self, theme: &CyberpunkTheme) {
+        self.glow_color = theme.cyan();
+        self.post_process.set_theme(theme);
+    }
+
+    /// Use the app's glow settings
+    pub fn set_glow(&mut self, color: [f32; 4], intensity: f32, size: f32) {
+        self.glow_color = color;
+        self.post_process.set_glow(intensity, size);
+    }
+
+    /// Draw a list widget like the app does: its rows glow, its modals stay sharp on top
+    pub fn render_list(&mut self, list: &TodoListWidget) -> Result<Frame, String> {
+        self.render(|ctx| list.render_base(ctx), |ctx| list.render_modals(ctx))
+    }
+
+    /// Draw a frame and read it back
+    ///
+    /// What `scene` draws goes through the bloom; what `overlay` draws is put
+    /// on top of the bloom result. Whatever either draws with `draw_glow` glows.
+    pub fn render(
+        &mut self,
+        scene: impl FnOnce(&mut RenderContext),
+        overlay: impl FnOnce(&mut RenderContext),
+    ) -> Result<Frame, String> {
+        profile_scope!("headless_render");
+        let view = self.target.create_view(&wgpu::TextureViewDescriptor::default());
+        let parts = FrameParts {
+            device: &self.device,
+            queue: &self.queue,
+            staging_belt: &mut self.staging_belt,
+            glyph_brush: &mut self.glyph_brush,
+            quad_renderer: &mut self.quad_renderer,
+            frame_arena: &mut self.frame_arena,
+            post_process: &mut self.post_process,
+            color_glyphs: None,
+            diagnostics: None,
+        };
+        let settings = FrameSettings {
+            width: self.width,
+            height: self.height,
+            scale_factor: self.scale_factor,
+            time: 0.0,
+            glow_color: self.glow_color,
+        };
+        draw_frame(parts, settings, &view, scene, overlay)?;
+        self.staging_belt.recall();
+        self.read_target()
+    }
+
+    /// Copy the target's pixels back from the GPU
+    fn read_target(&self) -> Result<Frame, String> {
+        // Rows are copied at a fixed alignment and trimmed afterwards
+        let row_bytes = self.width * 4;
+        let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: padded_row_bytes as u64 * self.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Readback Encoder") });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.target,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        let _ = self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|_| "The GPU dropped the frame readback".to_string())?
+            .map_err(|e| format!("Failed to read the frame back: {}", e))?;
+
+        let mut pixels = Vec::with_capacity((row_bytes * self.height) as usize);
+        for row in slice.get_mapped_range().chunks_exact(padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes as usize]);
+        }
+        buffer.unmap();
+        Ok(Frame { width: self.width, height: self.height, pixels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::Dispatcher;
+    use chrono::TimeZone;
+    use crate::ui::Quad;
+    use std::sync::Mutex;
+
+    fn temp_png(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("tewduwu-test-{}-{}.png", name, uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_png_round_trip_and_diff() {
+        let frame = Frame { width: 2, height: 1, pixels: vec![10, 20, 30, 255, 200, 100, 50, 255] };
+        let path = temp_png("round-trip");
+        frame.save_png(&path).unwrap();
+        assert_eq!(Frame::load_png(&path).unwrap(), frame);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut changed = frame.clone();
+        changed.pixels[4] = 205;
+        assert_eq!(changed.differing_pixels(&frame, 8), Ok(0));
+        assert_eq!(changed.differing_pixels(&frame, 4), Ok(1));
+        let cropped = Frame { width: 1, height: 1, pixels: vec![10, 20, 30, 255] };
+        assert!(cropped.differing_pixels(&frame, 0).is_err());
+    }
+
+    #[test]
+    fn test_golden_mismatch_saves_the_frame() {
+        let golden = temp_png("golden");
+        let frame = Frame { width: 1, height: 1, pixels: vec![0, 0, 0, 255] };
+        // Without a golden image there's nothing to pass against
+        assert!(compare_golden(&frame, &golden, false).is_err());
+        assert!(!golden.exists());
+        compare_golden(&frame, &golden, true).unwrap();
+        compare_golden(&frame, &golden, false).unwrap();
+
+        let brighter = Frame { pixels: vec![0, 255, 255, 255], ..frame };
+        assert!(compare_golden(&brighter, &golden, false).is_err());
+        assert_eq!(Frame::load_png(&actual_path(&golden)).unwrap(), brighter);
+        std::fs::remove_file(&golden).unwrap();
+        std::fs::remove_file(actual_path(&golden)).unwrap();
+    }
+
+    /// A task checked off changes few pixels, but more than the tolerances let through
+    #[test]
+    fn test_golden_catches_a_completed_task() {
+        let Some(frame) = render_list_frame("mismatch", sample_list(), |_| {}) else {
+            return;
+        };
+        let golden = temp_png("completed");
+        compare_golden(&frame, &golden, true).unwrap();
+
+        let mut list = sample_list();
+        let launch = list.root_item_ids()[0];
+        list.get_item_mut(launch).unwrap().mark_completed();
+        let completed = render_list_frame("mismatch", list, |_| {}).unwrap();
+        assert!(compare_golden(&completed, &golden, false).is_err());
+        std::fs::remove_file(&golden).unwrap();
+        std::fs::remove_file(actual_path(&golden)).unwrap();
+    }
+
+    /// Render `list` through the bloom and glow and compare it with its golden
+    /// image under tests/golden
+    ///
+    /// The list is drawn in the default theme, the only one there is so far.
+    /// Skipped where there's no GPU adapter, software or not.
+    fn check_list_golden(name: &str, list: TodoList, prepare: impl Fn(&mut TodoListWidget)) {
+        let Some(frame) = render_list_frame(name, list, prepare) else {
+            return;
+        };
+        check_golden(&frame, &Path::new("tests/golden").join(format!("{}.png", name))).unwrap();
+    }
+
+    /// Render `list` at the golden images' size, or `None` without a GPU adapter
+    fn render_list_frame(name: &str, list: TodoList, prepare: impl Fn(&mut TodoListWidget)) -> Option<Frame> {
+        let mut renderer = match HeadlessRenderer::new(800, 400) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("Skipping the {} golden image test: {}", name, e);
+                return None;
+            }
+        };
+        let mut widget = TodoListWidget::new(0.0, 0.0, 800.0, 400.0, Dispatcher::new(Arc::new(Mutex::new(list))));
+        prepare(&mut widget);
+        Some(renderer.render_list(&widget).unwrap())
+    }
+
+    #[test]
+    fn test_list_matches_golden() {
+        check_list_golden("todo_list", sample_list(), |_| {});
+    }
+
+    #[test]
+    fn test_empty_list_matches_golden() {
+        check_list_golden("todo_list_empty", TodoList::new("Golden"), |_| {});
+    }
+
+    /// More tasks than fit, scrolled part of the way down
+    #[test]
+    fn test_scrolled_list_matches_golden() {
+        let mut list = TodoList::new("Golden");
+        let priorities = [Priority::Low, Priority::Medium, Priority::High];
+        for n in 0..30 {
+            list.add_item(TodoItem::new(&format!("Task number {}", n + 1)).with_priority(priorities[n % 3]));
+        }
+        check_list_golden("todo_list_scrolled", list, |widget| widget.scroll_to(400.0));
+    }
+
+    /// The details of a task open over the list
+    ///
+    /// They show times in the local time zone, so the golden image is only
+    /// compared where that is UTC, as on CI.
+    #[test]
+    fn test_details_modal_matches_golden() {
+        let created = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        if chrono::Local.offset_from_utc_datetime(&created.naive_utc()).local_minus_utc() != 0 {
+            eprintln!("Skipping the details golden image test outside UTC");
+            return;
+        }
+        let mut list = sample_list();
+        // Fixed times, as the details show them
+        let item: TodoItem = serde_json::from_value(serde_json::json!({
+            "id": "6a5d3b9f-2a3c-4d4e-8f9a-1b2c3d4e5f60",
+            "title": "Write the release notes",
+            "description": "Cover the bloom and glow changes",
+            "status": "InProgress",
+            "priority": "High",
+            "created_at": 1_700_000_000u64,
+            "updated_at": 1_700_003_600u64,
+            "due_date": null,
+            "parent_id": null
+        }))
+        .unwrap();
+        let id = list.add_item(item);
+        // Opened with a double click on its row, as in the app
+        check_list_golden("todo_list_details", list, |widget| {
+            let (x, y) = widget.item_widget(id).unwrap().position();
+            let (width, height) = widget.dimensions();
+            widget.handle_mouse_move(x + 200.0, y + 10.0);
+            for click_count in 1..=2 {
+                widget.handle_mouse_down(x + 200.0, y + 10.0, click_count, width, height);
+                widget.handle_mouse_up(x + 200.0, y + 10.0);
+            }
+            widget.update(0.016);
+            assert!(widget.item_widget(id).unwrap().is_expanded());
+        });
+    }
+
+    /// At a scale factor of 2, logical pixels cover two physical ones each way
+    #[test]
+    fn test_scale_factor_draws_larger() {
+        let mut renderer = match HeadlessRenderer::new(200, 200) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("Skipping the scale factor test: {}", e);
+                return;
+            }
+        };
+        let frame = |renderer: &mut HeadlessRenderer, scale_factor| {
+            renderer.set_scale_factor(scale_factor);
+            renderer
+                .render(|ctx| {
+                    assert_eq!(ctx.width, 200.0 / scale_factor);
+                    ctx.draw_rect(10.0, 10.0, 20.0, 20.0, [1.0, 0.0, 0.0, 1.0]);
+                }, |_| {})
+                .unwrap()
+        };
+        let red = |frame: &Frame, x: u32, y: u32| frame.pixels[((y * frame.width + x) * 4) as usize];
+        let (normal, scaled) = (frame(&mut renderer, 1.0), frame(&mut renderer, 2.0));
+        // (45, 45) is past the rect's corner at (30, 30) unless it's scaled to (60, 60)
+        assert!(red(&scaled, 45, 45) > 200);
+        assert!(red(&normal, 45, 45) < red(&scaled, 45, 45));
+        assert!(red(&normal, 20, 20) > 200);
+    }
+
+    /// Only what's drawn into the glow mask glows, in its own color
+    #[test]
+    fn test_glow_spreads_from_the_mask() {
+        let mut renderer = match HeadlessRenderer::new(400, 200) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("Skipping the glow mask test: {}", e);
+                return;
+            }
+        };
+        renderer.set_glow([0.0, 1.0, 1.0, 1.0], 1.0, 10.0);
+        let plain = renderer.render(|_| {}, |_| {}).unwrap();
+        let glowing = renderer
+            .render(|ctx| ctx.draw_glow(Quad::rect(150.0, 50.0, 100.0, 100.0, [1.0, 0.0, 0.0, 1.0])), |_| {})
+            .unwrap();
+
+        let pixel = |frame: &Frame, x: u32, y: u32| {
+            let i = ((y * frame.width + x) * 4) as usize;
+            [frame.pixels[i], frame.pixels[i + 1], frame.pixels[i + 2]]
+        };
+        // Just above the shape it glows red, whatever the glow color setting is
+        let (before, after) = (pixel(&plain, 200, 47), pixel(&glowing, 200, 47));
+        assert!(after[0] > before[0] + 16, "{:?} -> {:?}", before, after);
+        assert!(after[1].abs_diff(before[1]) <= 2 && after[2].abs_diff(before[2]) <= 2);
+        // Far from it, nothing changes
+        assert_eq!(pixel(&plain, 20, 20), pixel(&glowing, 20, 20));
+    }
+}
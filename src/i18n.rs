@@ -0,0 +1,418 @@
+// Localization based on Fluent (https://projectfluent.org)
+//
+// Message bundles live in locales/<lang>/tewduwu.ftl and are embedded in the
+// binary. The active locale is process-wide: widgets call `tr` while building
+// their labels, so it should be chosen (see `init`) before the UI is created.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use tracing::{error, info};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+use crate::core::prelude::{Priority, RecurrenceRule, Reminder, SortKey, Status};
+
+/// Locale used when nothing better matches; its bundle must contain every message
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Embedded message bundles, keyed by locale
+const BUNDLES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US/tewduwu.ftl")),
+    ("de-DE", include_str!("../locales/de-DE/tewduwu.ftl")),
+];
+
+/// Locales with a bundle
+pub fn available_locales() -> Vec<&'static str> {
+    BUNDLES.iter().map(|(locale, _)| *locale).collect()
+}
+
+/// Check whether a requested locale resolves to one of our bundles
+pub fn is_supported(requested: &str) -> bool {
+    requested.parse::<LanguageIdentifier>().is_ok_and(|requested| {
+        let available = available_identifiers();
+        !negotiate_languages(&[requested], &available, None, NegotiationStrategy::Filtering).is_empty()
+    })
+}
+
+fn available_identifiers() -> Vec<LanguageIdentifier> {
+    BUNDLES
+        .iter()
+        .map(|(locale, _)| locale.parse().expect("Invalid bundled locale"))
+        .collect()
+}
+
+/// Build the bundle for one of the embedded locales
+fn load_bundle(locale: &LanguageIdentifier) -> FluentBundle<FluentResource> {
+    let source = BUNDLES
+        .iter()
+        .find(|(id, _)| id.parse::<LanguageIdentifier>().ok().as_ref() == Some(locale))
+        .map(|(_, source)| *source)
+        .expect("No bundle for negotiated locale");
+
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+        error!("Errors in {} messages: {:?}", locale, errors);
+        resource
+    });
+
+    let mut bundle = FluentBundle::new_concurrent(vec![locale.clone()]);
+    // Unicode isolation marks have no glyphs in our font and would render as boxes
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        error!("Duplicate messages in {} bundle: {:?}", locale, errors);
+    }
+    bundle
+}
+
+/// Looks up translated messages for one locale, falling back to English
+pub struct Localizer {
+    bundle: FluentBundle<FluentResource>,
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    /// Create a localizer for the best match of the requested locale
+    pub fn new(requested: &str) -> Self {
+        let default: LanguageIdentifier = DEFAULT_LOCALE.parse().expect("Invalid default locale");
+        let requested: Vec<LanguageIdentifier> = requested.parse().into_iter().collect();
+        let available = available_identifiers();
+        let locale = negotiate_languages(&requested, &available, Some(&default), NegotiationStrategy::Filtering)
+            .first()
+            .map(|locale| (*locale).clone())
+            .unwrap_or_else(|| default.clone());
+
+        let fallback = (locale != default).then(|| load_bundle(&default));
+        Self {
+            bundle: load_bundle(&locale),
+            fallback,
+        }
+    }
+
+    /// The locale messages are actually taken from
+    pub fn locale(&self) -> String {
+        self.bundle.locales[0].to_string()
+    }
+
+    /// Translate a message without arguments
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    /// Translate a message with arguments
+    pub fn get_args(&self, id: &str, args: &FluentArgs) -> String {
+        self.format(id, Some(args))
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in std::iter::once(&self.bundle).chain(self.fallback.as_ref()) {
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = Vec::new();
+                let text = bundle.format_pattern(pattern, args, &mut errors);
+                if !errors.is_empty() {
+                    error!("Errors formatting message {:?}: {:?}", id, errors);
+                }
+                return text.into_owned();
+            }
+        }
+
+        // Showing the id makes missing translations obvious without crashing
+        error!("Missing message {:?}", id);
+        id.to_string()
+    }
+
+    /// Format a Unix timestamp as a local date
+    pub fn format_date(&self, timestamp: u64) -> String {
+        self.format_timestamp(timestamp, "date-format")
+    }
+
+    /// Format a Unix timestamp as a local date and time
+    pub fn format_datetime(&self, timestamp: u64) -> String {
+        self.format_timestamp(timestamp, "datetime-format")
+    }
+
+    /// Format a Unix timestamp as a local day and month, without the year
+    pub fn format_day(&self, timestamp: u64) -> String {
+        self.format_timestamp(timestamp, "day-format")
+    }
+
+    /// Read a date written in the locale's date format as local midnight
+    pub fn parse_date(&self, text: &str) -> Option<u64> {
+        let date = chrono::NaiveDate::parse_from_str(text.trim(), &self.get("date-format")).ok()?;
+        local_midnight(date)
+    }
+
+    fn format_timestamp(&self, timestamp: u64, format_id: &str) -> String {
+        match chrono::DateTime::from_timestamp(timestamp as i64, 0) {
+            Some(utc) => utc
+                .with_timezone(&chrono::Local)
+                .format(&self.get(format_id))
+                .to_string(),
+            None => timestamp.to_string(),
+        }
+    }
+
+    /// Format a number with the locale's decimal and digit grouping separators
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = match formatted.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (formatted.as_str(), None),
+        };
+
+        let group_separator = self.get("number-group-separator");
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push_str(&group_separator);
+            }
+            grouped.push(digit);
+        }
+
+        let mut result = String::new();
+        if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        result.push_str(&grouped);
+        if let Some(fraction) = fraction {
+            result.push_str(&self.get("number-decimal-separator"));
+            result.push_str(fraction);
+        }
+        result
+    }
+}
+
+/// Bumped whenever `init` switches the language, so cached translations can be dropped
+static LOCALE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn localizer() -> &'static RwLock<Localizer> {
+    static LOCALIZER: OnceLock<RwLock<Localizer>> = OnceLock::new();
+    LOCALIZER.get_or_init(|| RwLock::new(Localizer::new(&detect_locale())))
+}
+
+/// The user's preferred locale as reported by the OS (or browser)
+pub fn detect_locale() -> String {
+    sys_locale::get_locale().unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Select the UI language: the requested one if given, otherwise the system's
+pub fn init(requested: Option<&str>) {
+    let requested = requested.map(str::to_string).unwrap_or_else(detect_locale);
+    let new_localizer = Localizer::new(&requested);
+    info!("Using locale {} (requested {})", new_localizer.locale(), requested);
+    *localizer().write().unwrap() = new_localizer;
+    LOCALE_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Changes every time the language is switched
+pub fn locale_generation() -> u64 {
+    LOCALE_GENERATION.load(Ordering::Relaxed)
+}
+
+/// The active locale
+pub fn current_locale() -> String {
+    localizer().read().unwrap().locale()
+}
+
+/// Translate a message in the active locale
+pub fn tr(id: &str) -> String {
+    localizer().read().unwrap().get(id)
+}
+
+/// Translate a message with `(name, value)` arguments in the active locale
+pub fn tr_args(id: &str, args: &[(&str, &str)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, *value);
+    }
+    localizer().read().unwrap().get_args(id, &fluent_args)
+}
+
+/// Translate a message with a `name` count in the active locale
+///
+/// Unlike the text arguments of `tr_args`, the count selects `[1]` style variants.
+pub fn tr_count(id: &str, name: &str, count: usize) -> String {
+    let mut fluent_args = FluentArgs::new();
+    fluent_args.set(name, count);
+    localizer().read().unwrap().get_args(id, &fluent_args)
+}
+
+/// Format a Unix timestamp as a date in the active locale
+pub fn format_date(timestamp: u64) -> String {
+    localizer().read().unwrap().format_date(timestamp)
+}
+
+/// Format a Unix timestamp as a date and time in the active locale
+pub fn format_datetime(timestamp: u64) -> String {
+    localizer().read().unwrap().format_datetime(timestamp)
+}
+
+/// Format a Unix timestamp as a day and month in the active locale
+pub fn format_day(timestamp: u64) -> String {
+    localizer().read().unwrap().format_day(timestamp)
+}
+
+/// Read a date in the active locale's format, as a Unix timestamp
+pub fn parse_date(text: &str) -> Option<u64> {
+    localizer().read().unwrap().parse_date(text)
+}
+
+/// The local calendar day a Unix timestamp falls on
+pub fn local_date(timestamp: u64) -> Option<chrono::NaiveDate> {
+    let utc = chrono::DateTime::from_timestamp(i64::try_from(timestamp).ok()?, 0)?;
+    Some(utc.with_timezone(&chrono::Local).date_naive())
+}
+
+/// Unix timestamp of the start of a local calendar day
+pub fn local_midnight(date: chrono::NaiveDate) -> Option<u64> {
+    let midnight = date.and_hms_opt(0, 0, 0)?.and_local_timezone(chrono::Local).earliest()?;
+    u64::try_from(midnight.timestamp()).ok()
+}
+
+/// Name of a month, 1 being January
+pub fn month_name(month: u32) -> String {
+    let names = tr("date-months");
+    names.split_whitespace().nth(month.saturating_sub(1) as usize).unwrap_or_default().to_string()
+}
+
+/// Short weekday names, Monday first
+pub fn weekday_names() -> Vec<String> {
+    tr("date-weekdays").split_whitespace().map(str::to_string).collect()
+}
+
+/// Format a number in the active locale
+pub fn format_number(value: f64, decimals: usize) -> String {
+    localizer().read().unwrap().format_number(value, decimals)
+}
+
+/// Message id of a task status's name
+pub fn status_label_id(status: Status) -> &'static str {
+    match status {
+        Status::NotStarted => "status-not-started",
+        Status::InProgress => "status-in-progress",
+        Status::Completed => "status-completed",
+    }
+}
+
+/// Translated name of a task status
+pub fn status_label(status: Status) -> String {
+    tr(status_label_id(status))
+}
+
+/// Message id of a task priority's name
+pub fn priority_label_id(priority: Priority) -> &'static str {
+    match priority {
+        Priority::Low => "priority-low",
+        Priority::Medium => "priority-medium",
+        Priority::High => "priority-high",
+    }
+}
+
+/// Translated name of a task priority
+pub fn priority_label(priority: Priority) -> String {
+    tr(priority_label_id(priority))
+}
+
+/// Message id of what a list can be sorted by
+pub fn sort_label_id(key: SortKey) -> &'static str {
+    match key {
+        SortKey::Manual => "sort-manual",
+        SortKey::Priority => "sort-priority",
+        SortKey::DueDate => "sort-due-date",
+        SortKey::Created => "sort-created",
+        SortKey::Alphabetical => "sort-alphabetical",
+    }
+}
+
+/// Translated description of how often a task repeats
+pub fn recurrence_label(recurrence: Option<RecurrenceRule>) -> String {
+    match recurrence {
+        None => tr("recurrence-never"),
+        Some(RecurrenceRule::Daily) => tr("recurrence-daily"),
+        Some(RecurrenceRule::Weekly) => tr("recurrence-weekly"),
+        Some(RecurrenceRule::Monthly) => tr("recurrence-monthly"),
+        Some(RecurrenceRule::EveryNDays(days)) => tr_args("recurrence-every-days", &[("days", &days.to_string())]),
+    }
+}
+
+/// Translated description of when a task's reminder comes
+pub fn reminder_label(reminder: Reminder) -> String {
+    match reminder {
+        Reminder::Default => tr("reminder-default"),
+        Reminder::MinutesBefore(0) => tr("reminder-at-due"),
+        Reminder::MinutesBefore(minutes) if minutes % (24 * 60) == 0 => {
+            tr_args("reminder-days-before", &[("days", &(minutes / (24 * 60)).to_string())])
+        }
+        Reminder::MinutesBefore(minutes) if minutes % 60 == 0 => tr_args("reminder-hours-before", &[("hours", &(minutes / 60).to_string())]),
+        Reminder::MinutesBefore(minutes) => tr_args("reminder-minutes-before", &[("minutes", &minutes.to_string())]),
+        Reminder::Off => tr("reminder-off"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiates_closest_locale() {
+        assert_eq!(Localizer::new("de-AT").locale(), "de-DE");
+        assert_eq!(Localizer::new("fr-FR").locale(), DEFAULT_LOCALE);
+        assert_eq!(Localizer::new("not a locale").locale(), DEFAULT_LOCALE);
+        assert!(is_supported("de"));
+        assert!(!is_supported("fr"));
+    }
+
+    #[test]
+    fn test_messages_and_arguments() {
+        let german = Localizer::new("de-DE");
+        assert_eq!(german.get("add-task"), "Hinzufügen");
+
+        let mut args = FluentArgs::new();
+        args.set("date", "01.02.2025");
+        assert_eq!(german.get_args("item-due", &args), "Fällig: 01.02.2025");
+
+        // Numbers select variants, text doesn't
+        let mut args = FluentArgs::new();
+        args.set("overdue", 1);
+        assert_eq!(german.get_args("tray-tooltip", &args), "tewduwu-neon: 1 Aufgabe überfällig");
+        args.set("overdue", "1");
+        assert_eq!(german.get_args("tray-tooltip", &args), "tewduwu-neon: 1 Aufgaben überfällig");
+    }
+
+    #[test]
+    fn test_dates_round_trip() {
+        let german = Localizer::new("de-DE");
+        let timestamp = german.parse_date("01.02.2025").unwrap();
+        assert_eq!(german.format_date(timestamp), "01.02.2025");
+        assert_eq!(Localizer::new("en-US").format_date(timestamp), "2025-02-01");
+        assert!(german.parse_date("2025-02-01").is_none());
+        assert!(german.parse_date("31.02.2025").is_none());
+        assert_eq!(local_date(timestamp), chrono::NaiveDate::from_ymd_opt(2025, 2, 1));
+    }
+
+    #[test]
+    fn test_every_bundle_is_complete() {
+        let english_ids: Vec<&str> = BUNDLES[0]
+            .1
+            .lines()
+            .filter_map(|line| line.split_once(" = ").map(|(id, _)| id))
+            .filter(|id| !id.starts_with(' ') && !id.starts_with('#'))
+            .collect();
+        assert!(!english_ids.is_empty());
+
+        for locale in available_locales() {
+            let localizer = Localizer::new(locale);
+            for id in &english_ids {
+                assert!(localizer.bundle.has_message(id), "{} is missing {}", locale, id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_number_formatting() {
+        assert_eq!(Localizer::new("en-US").format_number(1234567.891, 2), "1,234,567.89");
+        assert_eq!(Localizer::new("de-DE").format_number(1234567.891, 2), "1.234.567,89");
+        assert_eq!(Localizer::new("en-US").format_number(-42.0, 0), "-42");
+        assert_eq!(Localizer::new("en-US").format_number(999.0, 0), "999");
+    }
+}
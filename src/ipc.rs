@@ -0,0 +1,316 @@
+// Single-instance support: the first tewduwu process listens on a local socket,
+// later launches hand their command line over to it and exit.
+//
+// Everything lives in a directory only the current user can enter. The process
+// holding the lock file in it is the primary instance, so two launches racing
+// each other can't both become one, and only the lock holder ever removes a
+// socket left behind by a crash. Where Unix sockets are unavailable the primary
+// listens on a loopback port instead; as any local process can connect to that,
+// it writes the port with a per-session token to the private directory and
+// drops connections that don't start with the token.
+
+use tracing::{error, info, warn};
+use std::fs::{File, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use crate::cli::CliChange;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(not(unix))]
+use std::net::{TcpListener, TcpStream};
+
+/// Held by the primary instance for as long as it runs
+const LOCK_FILE: &str = "tewduwu.lock";
+
+/// The socket the primary instance listens on
+#[cfg(unix)]
+const SOCKET_FILE: &str = "tewduwu.sock";
+
+/// Port and token of the primary instance's loopback listener
+#[cfg(not(unix))]
+const ENDPOINT_FILE: &str = "tewduwu.endpoint";
+
+/// How long a second instance waits for the running one to answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether this process is the primary instance, listening for the others
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+/// A request forwarded from a second instance to the running one
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum IpcMessage {
+    /// Bring the existing window to the front
+    Focus,
+    /// Make a change given on the command line to the list with a name, or the
+    /// active one, so it isn't lost when the running instance next saves
+    ///
+    /// The list and the task are looked up in the running instance, whose
+    /// workspace may differ from the data file. It answers with what to print.
+    Execute { list: Option<String>, change: CliChange },
+}
+
+impl IpcMessage {
+    /// Whether the sender waits for an answer
+    fn wants_reply(&self) -> bool {
+        matches!(self, IpcMessage::Execute { .. })
+    }
+}
+
+/// The running instance's answer: the line to print, or why the change failed
+pub type Reply = Result<String, String>;
+
+/// The per-user directory holding the lock and the socket, created if needed
+///
+/// `$XDG_RUNTIME_DIR` is private by definition; the shared temporary directory
+/// is the fallback, where someone else could have created ours first.
+#[cfg(unix)]
+fn private_dir() -> Result<PathBuf, String> {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime) if !runtime.is_empty() => PathBuf::from(runtime).join("tewduwu"),
+        _ => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "default".to_string());
+            std::env::temp_dir().join(format!("tewduwu-{}", user))
+        }
+    };
+    ensure_private(&dir)?;
+    Ok(dir)
+}
+
+/// Create `dir` with access for its owner only, or check an existing one is like that
+///
+/// A directory made by another user with the same mode can't be entered, so
+/// binding or connecting in it fails rather than talking to a stranger.
+#[cfg(unix)]
+fn ensure_private(dir: &Path) -> Result<(), String> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(format!("Failed to create {}: {}", dir.display(), e)),
+    }
+    // Not followed if it's a link: it has to be the directory itself
+    let metadata = std::fs::symlink_metadata(dir).map_err(|e| format!("Failed to inspect {}: {}", dir.display(), e))?;
+    if !metadata.is_dir() || metadata.permissions().mode() & 0o077 != 0 {
+        return Err(format!("{} is not a directory private to this user", dir.display()));
+    }
+    Ok(())
+}
+
+/// The per-user directory holding the lock and the endpoint, created if needed
+///
+/// The local application data folder is only open to its user.
+#[cfg(not(unix))]
+fn private_dir() -> Result<PathBuf, String> {
+    let dir = dirs::data_local_dir()
+        .ok_or_else(|| "No local data directory".to_string())?
+        .join("tewduwu");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Port and token the primary instance wrote, if one is running
+#[cfg(not(unix))]
+fn read_endpoint(dir: &std::path::Path) -> Option<(u16, String)> {
+    let text = std::fs::read_to_string(dir.join(ENDPOINT_FILE)).ok()?;
+    let (port, token) = text.trim().split_once(' ')?;
+    Some((port.parse().ok()?, token.to_string()))
+}
+
+/// Try to deliver `message` to an already running instance.
+///
+/// Returns `None` if no other instance is running. Otherwise this process
+/// should exit, after printing the answer of a message that waits for one;
+/// others are answered with an empty line right away.
+pub fn try_handoff(message: &IpcMessage) -> Option<Reply> {
+    let Ok(dir) = private_dir() else {
+        return None;
+    };
+
+    #[cfg(unix)]
+    let stream = UnixStream::connect(dir.join(SOCKET_FILE));
+    #[cfg(not(unix))]
+    let stream = match read_endpoint(&dir) {
+        Some((port, token)) => TcpStream::connect(("127.0.0.1", port)).and_then(|mut stream| {
+            writeln!(stream, "{}", token)?;
+            Ok(stream)
+        }),
+        None => return None,
+    };
+
+    let mut stream = match stream {
+        Ok(stream) => stream,
+        Err(_) => return None, // Nobody is listening, we're the primary instance
+    };
+
+    let line = match serde_json::to_string(message) {
+        Ok(line) => line,
+        Err(e) => {
+            error!("Failed to encode IPC message: {}", e);
+            return None;
+        }
+    };
+
+    if let Err(e) = writeln!(stream, "{}", line) {
+        warn!("Running instance did not accept the handoff: {}", e);
+        return None;
+    }
+    info!("Handed {:?} off to the running instance", message);
+    if !message.wants_reply() {
+        return Some(Ok(String::new()));
+    }
+
+    // It may have made the change whatever happens now, so it isn't made here too
+    let _ = stream.set_read_timeout(Some(REPLY_TIMEOUT));
+    let mut answer = String::new();
+    Some(match BufReader::new(stream).read_line(&mut answer) {
+        Ok(0) | Err(_) => Err("The running instance didn't answer; check whether the change was made".to_string()),
+        Ok(_) => serde_json::from_str(&answer).unwrap_or_else(|e| Err(format!("Unreadable answer from the running instance: {}", e))),
+    })
+}
+
+/// Start listening for other instances on a background thread.
+///
+/// Every decoded message is passed to `on_message`, which typically forwards it
+/// to the event loop through an `EventLoopProxy`, along with where to send the
+/// answer to a message that waits for one. Returns false if another instance
+/// became the primary one since `try_handoff` looked; hand off to it instead.
+pub fn start_server<F>(on_message: F) -> Result<bool, String>
+where
+    F: Fn(IpcMessage, Sender<Reply>) + Send + 'static,
+{
+    let dir = private_dir()?;
+    let lock_path = dir.join(LOCK_FILE);
+    let lock = File::create(&lock_path).map_err(|e| format!("Failed to open {}: {}", lock_path.display(), e))?;
+    match lock.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Ok(false),
+        Err(TryLockError::Error(e)) => return Err(format!("Failed to lock {}: {}", lock_path.display(), e)),
+    }
+
+    #[cfg(unix)]
+    let listener = {
+        let path = dir.join(SOCKET_FILE);
+        match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                // Someone answering without the lock still owns the socket
+                if UnixStream::connect(&path).is_ok() {
+                    return Ok(false);
+                }
+                // Left behind by a crashed instance, which no longer holds the lock
+                std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                UnixListener::bind(&path).map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?
+            }
+            Err(e) => return Err(format!("Failed to bind {}: {}", path.display(), e)),
+        }
+    };
+    #[cfg(not(unix))]
+    let (listener, token) = {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).map_err(|e| format!("Failed to bind an IPC port: {}", e))?;
+        let port = listener.local_addr().map_err(|e| format!("Failed to read the IPC port: {}", e))?.port();
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let path = dir.join(ENDPOINT_FILE);
+        std::fs::write(&path, format!("{} {}", port, token)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        (listener, token)
+    };
+
+    std::thread::Builder::new()
+        .name("tewduwu-ipc".to_string())
+        .spawn(move || {
+            // Released when the process ends, however it ends
+            let _lock = lock;
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("IPC connection failed: {}", e);
+                        continue;
+                    }
+                };
+                let mut answers = match stream.try_clone() {
+                    Ok(answers) => answers,
+                    Err(e) => {
+                        warn!("IPC connection failed: {}", e);
+                        continue;
+                    }
+                };
+                let lines = BufReader::new(stream).lines().map_while(Result::ok);
+                #[cfg(not(unix))]
+                let lines = {
+                    let mut lines = lines;
+                    if lines.next().as_deref() != Some(token.as_str()) {
+                        warn!("Ignoring an IPC connection without the session token");
+                        continue;
+                    }
+                    lines
+                };
+                for line in lines {
+                    let message = match serde_json::from_str::<IpcMessage>(&line) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            warn!("Ignoring malformed IPC message {:?}: {}", line, e);
+                            continue;
+                        }
+                    };
+                    let wants_reply = message.wants_reply();
+                    let (reply, answer) = std::sync::mpsc::channel();
+                    on_message(message, reply);
+                    if wants_reply {
+                        let answer = answer
+                            .recv_timeout(REPLY_TIMEOUT)
+                            .unwrap_or_else(|_| Err("The running instance couldn't make the change".to_string()));
+                        let sent = serde_json::to_string(&answer)
+                            .map_err(std::io::Error::other)
+                            .and_then(|answer| writeln!(answers, "{}", answer));
+                        if let Err(e) = sent {
+                            warn!("Failed to answer an IPC message: {}", e);
+                        }
+                    }
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to spawn IPC thread: {}", e))?;
+
+    LISTENING.store(true, Ordering::Relaxed);
+    Ok(true)
+}
+
+/// Remove the socket or endpoint file so the next launch doesn't have to clean it up
+pub fn shutdown() {
+    if !LISTENING.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(dir) = private_dir() else {
+        return;
+    };
+    #[cfg(unix)]
+    let _ = std::fs::remove_file(dir.join(SOCKET_FILE));
+    #[cfg(not(unix))]
+    let _ = std::fs::remove_file(dir.join(ENDPOINT_FILE));
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_private_dir_rejects_shared_directories() {
+        let dir = std::env::temp_dir().join(format!("tewduwu-ipc-test-{}", uuid::Uuid::new_v4()));
+        ensure_private(&dir).unwrap();
+        assert_eq!(std::fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+
+        // Made by someone else first, and open to others
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(ensure_private(&dir).is_err());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
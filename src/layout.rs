@@ -0,0 +1,254 @@
+// Top-level screen layout
+//
+// Where the title, the list, the instructions and the status bar go, and
+// which filter controls the list's toolbar shows in which order. Builds use
+// the values below, which assets/layout.toml mirrors; with the `dev` feature
+// that file is watched and re-applied whenever it's saved, so the UI's
+// structure can be iterated on without rebuilding the app.
+
+use serde::{Deserialize, Serialize};
+
+/// The layout file as shipped; it must describe `Layout::default()`
+pub const DEFAULT_LAYOUT: &str = include_str!("../assets/layout.toml");
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Layout {
+    pub title: TitleLayout,
+    pub list: ListLayout,
+    pub toolbar: ToolbarLayout,
+    pub instructions: InstructionsLayout,
+    pub status_bar: StatusBarLayout,
+}
+
+/// The heading in the top-left corner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TitleLayout {
+    pub visible: bool,
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+    pub size: f32,
+    pub color: [f32; 4],
+}
+
+impl Default for TitleLayout {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            text: "✨ tewduwu ✨".to_string(),
+            x: 30.0,
+            y: 30.0,
+            size: 48.0,
+            color: [1.0, 0.255, 0.639, 1.0],
+        }
+    }
+}
+
+/// Distance from each window edge to the list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListLayout {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl Default for ListLayout {
+    fn default() -> Self {
+        Self { left: 50.0, top: 100.0, right: 50.0, bottom: 100.0 }
+    }
+}
+
+/// The list's row of filter controls, left to right; missing ones are hidden
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolbarLayout {
+    pub controls: Vec<FilterControl>,
+}
+
+impl Default for ToolbarLayout {
+    fn default() -> Self {
+        Self {
+            controls: vec![
+                FilterControl::Search,
+                FilterControl::Field,
+                FilterControl::Status,
+                FilterControl::Priority,
+                FilterControl::Sort,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterControl {
+    Search,
+    Field,
+    Status,
+    Priority,
+    /// The sort dropdown with its direction toggle
+    Sort,
+}
+
+/// The keybind hint along the bottom
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstructionsLayout {
+    pub visible: bool,
+    pub x: f32,
+    /// Distance from the bottom of the window
+    pub bottom: f32,
+    pub size: f32,
+}
+
+impl Default for InstructionsLayout {
+    fn default() -> Self {
+        Self { visible: true, x: 30.0, bottom: 50.0, size: 20.0 }
+    }
+}
+
+/// The strip of task counts, filter, input mode and frame rate along the bottom edge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusBarLayout {
+    pub visible: bool,
+    pub height: f32,
+    pub size: f32,
+}
+
+impl Default for StatusBarLayout {
+    fn default() -> Self {
+        Self { visible: true, height: 26.0, size: 16.0 }
+    }
+}
+
+impl Layout {
+    /// Parse a layout file; missing tables and keys keep their defaults
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| format!("Invalid layout file: {}", e.message()))
+    }
+
+    /// The list's position and size in a window of this size
+    pub fn list_rect(&self, width: f32, height: f32) -> (f32, f32, f32, f32) {
+        let list = &self.list;
+        (
+            list.left,
+            list.top,
+            (width - list.left - list.right).max(0.0),
+            (height - list.top - list.bottom).max(0.0),
+        )
+    }
+}
+
+/// Re-reads the layout file whenever it changes on disk (the `dev` feature)
+#[cfg(all(feature = "dev", not(target_arch = "wasm32")))]
+pub struct LayoutWatcher {
+    path: std::path::PathBuf,
+    modified: Option<std::time::SystemTime>,
+    timer: f32,
+}
+
+#[cfg(all(feature = "dev", not(target_arch = "wasm32")))]
+impl LayoutWatcher {
+    /// Seconds between checks of the file's modification time
+    const POLL_INTERVAL_SECS: f32 = 0.5;
+
+    /// Watch `TEWDUWU_LAYOUT` if set, or else assets/layout.toml in the source tree
+    pub fn new() -> Self {
+        let path = std::env::var_os("TEWDUWU_LAYOUT")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|| std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/layout.toml"));
+        Self::at_path(path)
+    }
+
+    /// Watch a specific file; the first `poll` loads it
+    pub fn at_path(path: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            timer: Self::POLL_INTERVAL_SECS,
+        }
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// The file's layout if it changed since the last poll, or why it couldn't be read
+    ///
+    /// Checks at most every `POLL_INTERVAL_SECS`, so it can be called every frame.
+    pub fn poll(&mut self, delta_time: f32) -> Option<Result<Layout, String>> {
+        self.timer += delta_time;
+        if self.timer < Self::POLL_INTERVAL_SECS {
+            return None;
+        }
+        self.timer = 0.0;
+        self.reload_if_changed()
+    }
+
+    fn reload_if_changed(&mut self) -> Option<Result<Layout, String>> {
+        let modified = std::fs::metadata(&self.path).and_then(|meta| meta.modified()).ok()?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+        Some(match crate::core::storage::read_text(&self.path) {
+            Ok(Some(text)) => Layout::from_toml(&text),
+            Ok(None) => Err(format!("Layout file {} disappeared", self.path.display())),
+            Err(e) => Err(e),
+        })
+    }
+}
+
+#[cfg(all(feature = "dev", not(target_arch = "wasm32")))]
+impl Default for LayoutWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shipped_file_matches_defaults() {
+        assert_eq!(Layout::from_toml(DEFAULT_LAYOUT).unwrap(), Layout::default());
+    }
+
+    #[test]
+    fn test_partial_files_keep_defaults() {
+        let layout = Layout::from_toml("[list]\ntop = 140\n\n[toolbar]\ncontrols = [\"status\", \"search\"]\n").unwrap();
+        assert_eq!(layout.list.top, 140.0);
+        assert_eq!(layout.list.left, 50.0);
+        assert_eq!(layout.toolbar.controls, vec![FilterControl::Status, FilterControl::Search]);
+        assert_eq!(layout.title, TitleLayout::default());
+        assert_eq!(layout.list_rect(800.0, 600.0), (50.0, 140.0, 700.0, 360.0));
+
+        assert!(Layout::from_toml("[toolbar]\ncontrols = [\"calendar\"]\n").is_err());
+    }
+
+    #[cfg(all(feature = "dev", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_watcher_reloads_on_change() {
+        let path = std::env::temp_dir().join(format!("tewduwu-test-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "[title]\nvisible = false\n").unwrap();
+        let mut watcher = LayoutWatcher::at_path(&path);
+
+        assert!(!watcher.poll(0.0).unwrap().unwrap().title.visible);
+        assert!(watcher.poll(1.0).is_none());
+
+        // Bump the modification time explicitly; the file system's may be coarse
+        std::fs::write(&path, "[title]\nvisible = true\n").unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(watcher.poll(0.1).is_none());
+        assert!(watcher.poll(1.0).unwrap().unwrap().title.visible);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,9 +1,44 @@
 // This is the library entry point for the tewduwu application
-// It exposes our core and UI modules for use in examples and binaries
+// It exposes the embeddable app runner, the command line interface, offscreen rendering, single-instance handoff and our core, config, crash reporting, startup errors, focus timer, frame pacing, i18n, layout, notifications, persistence, session, tasks, tray and UI modules (plus plugins, scripting, input recording, profiling, CalDAV sync and the global hotkey when enabled) for use in examples and binaries
 
-pub mod core;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod app;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod crash;
+pub mod error;
+pub mod frame_pacing;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
+pub use tewduwu_core as core;
+pub mod i18n;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ipc;
+pub mod layout;
+pub mod notifications;
+pub mod persistence;
+pub mod pomodoro;
+#[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+pub mod platform;
+pub mod profiling;
+#[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+pub mod recording;
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+pub mod plugins;
+#[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+pub mod scripting;
+pub mod session;
+#[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+pub mod sync;
+pub mod tasks;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tray;
 pub mod ui;
 
 // Re-export commonly used types in the root module
 pub use core::prelude;
+#[cfg(not(target_arch = "wasm32"))]
+pub use app::{App, AppContext, AppEvent, Runner};
 pub use ui::prelude as ui_prelude; 
\ No newline at end of file
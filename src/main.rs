@@ -1,9 +1,11 @@
-use log::{error, info};
+use tracing::{debug, error, info, trace_span};
 use winit::{
-    event::{Event, WindowEvent, KeyEvent, ElementState},
-    event_loop::{EventLoop},
-    window::{Window, WindowBuilder},
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoopBuilder, EventLoopProxy, EventLoopWindowTarget},
+    window::{CursorIcon, Window, WindowBuilder},
 };
+#[cfg(target_arch = "wasm32")]
+use winit::platform::web::{EventLoopExtWebSys, WindowBuilderExtWebSys};
 use wgpu::{
     Adapter,
     Device,
@@ -16,128 +18,508 @@ use wgpu::{
     SurfaceError,
     TextureUsages,
     util::StagingBelt,
-    TextureDescriptor,
-    Extent3d,
-    TextureDimension,
-    TextureViewDescriptor,
 };
 use std::sync::Arc; // Use Arc for window sharing
 use std::sync::Mutex;
+use std::time::Duration;
 
 // Use types from wgpu_glyph
-use wgpu_glyph::ab_glyph;
 use wgpu_glyph::{GlyphBrush, GlyphBrushBuilder};
 
-// Import our core module
-mod core;
+// Import our core crate under the name the rest of the app uses
+use tewduwu_core as core;
 use core::prelude::*;
+use core::storage;
+use core::journal::Journal;
+use core::sync::SyncStatus;
+
+// User configuration
+use tewduwu::config;
+use config::{Config, PresentMode};
+
+// Errors that stop the app from starting
+use tewduwu::error;
+use error::AppError;
+
+// Drawing frames only when needed, up to the frame rate cap
+use tewduwu::frame_pacing;
+use frame_pacing::{FpsCounter, FrameClock, FramePacer, Pacing};
+
+// Localization
+use tewduwu::i18n;
+
+// Screen layout, hot reloaded from assets/layout.toml with the dev feature
+use tewduwu::layout;
+use layout::Layout;
+
+// WASM plugins (native only: the browser has no plugins directory)
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+use tewduwu::plugins;
+
+// Rhai scripting (init.rhai next to the config file)
+#[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+use tewduwu::scripting;
+
+// Due-date reminders, as desktop notifications with the notifications feature
+use tewduwu::notifications;
+use notifications::{Notice, NoticeKind, ReminderScheduler};
+
+// Filters, scroll position and selection restored on launch
+use tewduwu::session;
+use session::Session;
+
+// Background I/O off the render thread
+use tewduwu::tasks;
+use tasks::{BackgroundTasks, TaskEvent};
+
+// Input recording and replay for reproducing bugs
+#[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+use tewduwu::recording;
+
+// Saves and journal snapshots on a dedicated worker
+use tewduwu::persistence;
+use persistence::{SaveRequest, SaveWorker};
+
+// Focus sessions on a task, counted down in the header
+use tewduwu::pomodoro;
+use pomodoro::{FocusTimer, Phase};
+
+// Profiler scopes, shown by the flame view (profiling feature)
+use tewduwu::profiling;
+use profiling::profile_scope;
 
 // Import our UI module
-mod ui;
+use tewduwu::ui;
 use ui::prelude::*;
+use ui::input::{binding_keycode, named_keycode, key_to_keycode, Action, InputEvent, InputMode, Keymap, TextEvent};
+
+// Single-instance handoff between processes (native only)
+#[cfg(not(target_arch = "wasm32"))]
+use tewduwu::ipc;
+
+// Subcommands that manage tasks from the shell without a window (native only)
+#[cfg(not(target_arch = "wasm32"))]
+use tewduwu::cli;
+#[cfg(not(target_arch = "wasm32"))]
+use clap::Parser;
 
-// We need to create a window wrapper that preserves the window
-// for the lifetime of the surface
-struct WindowWrapper {
+// Crash reports and list rescue on panic (native only)
+#[cfg(not(target_arch = "wasm32"))]
+use tewduwu::crash;
+
+// CalDAV sync with a task server such as Nextcloud Tasks (caldav feature)
+#[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+use tewduwu::sync;
+
+// System-wide hotkey for the quick-add popup (hotkey feature)
+#[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+use tewduwu::platform;
+
+// System tray icon with a quick-add popup (tray feature)
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+use tewduwu::tray;
+
+/// Custom events delivered to the winit event loop
+enum AppEvent {
+    /// Async initialization finished (web only, native blocks instead)
+    #[cfg(target_arch = "wasm32")]
+    Initialized(Box<State>),
+    /// A second instance forwarded its command line to us, waiting for the answer to some
+    #[cfg(not(target_arch = "wasm32"))]
+    Ipc(ipc::IpcMessage, std::sync::mpsc::Sender<ipc::Reply>),
+    /// The `[quick_add]` hotkey was pressed, whichever app has focus
+    #[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+    QuickAddHotkey,
+    /// A tray menu entry was picked
+    #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+    Tray(tray::TrayCommand),
+}
+
+/// Logical size of the quick-add popup's window
+///
+/// Only the tray and the hotkey open the popup, so without either there's nothing to size.
+#[cfg(all(feature = "quick-add", any(feature = "tray", feature = "hotkey"), not(target_arch = "wasm32")))]
+const QUICK_ADD_SIZE: (f32, f32) = (420.0, 120.0);
+
+/// The small borderless window the tray's quick add and the hotkey open
+///
+/// It draws with the main window's device and renderers, so only its surface is its own.
+#[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+struct QuickAddWindow {
     window: Arc<Window>,
+    surface: Surface<'static>,
+    config: SurfaceConfiguration,
+    popup: widgets::QuickAddPopup,
+    /// Where the typed task goes
+    list: Arc<Mutex<TodoList>>,
+    /// Logical cursor position over the window
+    mouse_pos: (f32, f32),
+    modifiers: winit::keyboard::ModifiersState,
+    /// App time of the last frame, for the cursor's blink
+    last_frame: f64,
 }
 
-impl WindowWrapper {
-    fn new(window: Arc<Window>) -> Self {
-        Self { window }
-    }
-    
-    fn create_surface(&self, instance: &Instance) -> Surface<'static> {
-        // This is unsafe because we're tying the surface lifetime to static,
-        // but we're ensuring the window stays alive for the duration of the surface
-        // through the WindowWrapper in State
-        let surface = unsafe {
-            // We're using the WGPU internal API to convert a non-static surface to 'static
-            // This is safe because we guarantee the window will live as long as the surface
-            let temp_surface = instance.create_surface(self.window.as_ref())
-                .expect("Failed to create surface");
-            std::mem::transmute::<Surface<'_>, Surface<'static>>(temp_surface)
-        };
-        surface
+#[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+impl QuickAddWindow {
+    fn resize(&mut self, device: &Device, size: winit::dpi::PhysicalSize<u32>) {
+        if size.width > 0 && size.height > 0 {
+            self.config.width = size.width;
+            self.config.height = size.height;
+            self.surface.configure(device, &self.config);
+        }
     }
-    
-    fn window(&self) -> &Window {
-        &self.window
+
+    /// Pass input on to the popup, returning what Enter or Escape did
+    fn handle_input(&mut self, input: InputEvent, keymap: &Keymap, clipboard: &mut ui::Clipboard) -> Option<widgets::QuickAddEvent> {
+        match input {
+            InputEvent::Text(event) => self.popup.handle_text(&event),
+            InputEvent::Modifiers { shift, control, alt, logo } => {
+                self.modifiers = InputEvent::modifiers(shift, control, alt, logo);
+            }
+            InputEvent::CursorMoved { x, y } => self.mouse_pos = (x, y),
+            InputEvent::MouseButton { middle: false, pressed: true } => {
+                self.popup.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1, 1);
+            }
+            InputEvent::Key { key, pressed: true, text, .. } => {
+                let key = key.to_key();
+                // Selecting, moving by word and the clipboard follow the keymap, as in the task editor
+                let action = binding_keycode(&key).and_then(|code| keymap.action(self.modifiers, code));
+                if let Some(action) = action {
+                    if self.popup.handle_action(action) || self.popup.handle_clipboard(action, clipboard) {
+                        return None;
+                    }
+                }
+                let command_held = (self.modifiers.control_key() || self.modifiers.super_key()) && !self.modifiers.alt_key();
+                match (&key, text) {
+                    (winit::keyboard::Key::Character(_), Some(text)) if !command_held => {
+                        self.popup.handle_text(&TextEvent::Insert(text));
+                    }
+                    _ => return named_keycode(&key).and_then(|code| self.popup.handle_key_press(code)),
+                }
+            }
+            _ => {}
+        }
+        None
     }
 }
 
 struct State {
-    window_wrapper: WindowWrapper, // Wrapper that keeps the window alive
+    /// Shared with the surface, which holds its own reference so it can't outlive the window
+    window: Arc<Window>,
     _instance: Instance,  
     surface: Surface<'static>,
     _adapter: Adapter,    
-    device: Device,
-    queue: Queue,
+    /// Shared with the post-processing stack, which keeps its own references
+    device: Arc<Device>,
+    queue: Arc<Queue>,
     config: SurfaceConfiguration,
+    /// Present modes the surface supports
+    present_modes: Vec<wgpu::PresentMode>,
     size: winit::dpi::PhysicalSize<u32>,
+    /// Physical pixels per logical pixel, e.g. 2 on most HiDPI displays
+    scale_factor: f64,
+    frame_pacer: FramePacer,
+    clock: FrameClock,
+    fps_counter: FpsCounter,
     
-    // Text Rendering State
+    // Text and Shape Rendering State
     glyph_brush: GlyphBrush<()>, 
+    quad_renderer: QuadRenderer,
+    color_glyphs: ColorGlyphs,
     staging_belt: StagingBelt, 
+    frame_arena: FrameArena,
     
-    // Application State
-    todo_list: Arc<Mutex<TodoList>>,
+    // Application State; every change to the active list goes through the dispatcher
+    workspace: Arc<Mutex<Workspace>>,
+    commands: Dispatcher,
     
     // UI State
     todo_list_widget: TodoListWidget,
-    theme: CyberpunkTheme,
+    /// Shown in the list's place while the board or timeline view is
+    board: KanbanBoard,
+    timeline: TimelineView,
+    view_switcher: ViewSwitcher,
+    sidebar: WorkspaceSidebar,
+    status_bar: StatusBar,
+    focus_ring: FocusRing,
     
     // Input State
     mouse_pos: (f32, f32),
+    click_tracker: ClickTracker,
+    pointer: PointerState,
+    /// Mouse buttons held down; moving the pointer then drags what they grabbed
+    buttons_held: u8,
+    key_repeat: KeyRepeat,
+    /// The `[keybinds]` table of `app_config`, ready for lookups
+    keymap: Keymap,
+    modifiers: winit::keyboard::ModifiersState,
+    cursor_icon: CursorIcon,
+    /// Cursor bounds of the focused text field, which the input method (IME) is enabled for
+    ime_caret: Option<(f32, f32, f32, f32)>,
+    
+    // Post-processing effects and the textures they work on
+    post_process: PostProcessStack,
+    
+    // Crash recovery; the journal itself belongs to the save worker
+    journal_timer: f32,
+    /// Seconds since the last edit, while it hasn't been written to the data file
+    autosave_timer: Option<f32>,
+    recovered_workspace: Option<Workspace>,
+    recovery_dialog: ConfirmDialog,
+    edit_modal: EditTaskModal,
+    /// Shared by every text field for copy and paste
+    clipboard: ui::Clipboard,
+    
+    // User configuration and the UI for editing it
+    app_config: Config,
+    #[cfg(not(target_arch = "wasm32"))]
+    config_watcher: Option<config::ConfigWatcher>,
+    settings_panel: SettingsPanel,
+    archive_view: ArchiveView,
+    toasts: ToastManager,
+    inspector: Inspector,
+    /// Frame times and draw counts, toggled with F3
+    diagnostics: Diagnostics,
+    layout: Layout,
+    #[cfg(all(feature = "dev", not(target_arch = "wasm32")))]
+    layout_watcher: layout::LayoutWatcher,
+    #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+    profiler: FlameView,
+    
+    // Background work; saves go to their own worker, which reports through `tasks`
+    tasks: BackgroundTasks,
+    saver: SaveWorker,
+    /// How the last CalDAV sync went, for the status bar; `None` while syncing is off
+    sync_status: Option<SyncStatus>,
+    /// When the next scheduled sync is due
+    #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+    next_sync: Option<std::time::Instant>,
+    reminders: ReminderScheduler,
+    /// App time of the next look for tasks coming due
+    next_reminder_check: f64,
+    /// The focus session or break counting down, with the list of its task
+    focus: Option<(FocusTimer, Arc<Mutex<TodoList>>)>,
+    
+    #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+    plugins: plugins::PluginHost,
+    #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+    scripts: scripting::ScriptHost,
+    #[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+    hotkey: Option<platform::hotkey::GlobalHotkey>,
+    #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+    tray: Option<tray::Tray>,
+    /// Whether the tray's show/hide entry last left the main window shown
+    #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+    window_shown: bool,
+    #[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+    quick_add: Option<QuickAddWindow>,
+}
+
+/// How long exiting waits for the save worker to finish
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often an idle app wakes to pick up results of background work
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often timers and background work are stepped while no frames are drawn
+const PENDING_WORK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often to look for tasks to remind of
+const REMINDER_CHECK_INTERVAL_SECS: f64 = 30.0;
+
+/// Backends to try, in order of preference, when creating the surface, and
+/// whether to ask for a software adapter.
+/// In the browser we prefer WebGPU and fall back to WebGL2. On native the
+/// primary backend (Vulkan, Metal or DX12) comes first, then OpenGL for
+/// machines without those drivers, then any software rasterizer installed.
+fn backend_candidates() -> Vec<(wgpu::Backends, bool)> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        vec![(wgpu::Backends::BROWSER_WEBGPU, false), (wgpu::Backends::GL, false)]
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        vec![
+            (wgpu::Backends::PRIMARY, false),
+            (wgpu::Backends::GL, false),
+            (wgpu::Backends::all(), true),
+        ]
+    }
+}
+
+/// Name of a key as written in the `[keybinds]` config table, e.g. "Escape", "F2" or "S"
+fn key_name(key: &winit::keyboard::Key) -> Option<String> {
+    match key {
+        winit::keyboard::Key::Named(named) => Some(format!("{:?}", named)),
+        winit::keyboard::Key::Character(c) => Some(c.to_uppercase()),
+        _ => None,
+    }
+}
+
+/// Key name prefixed with the held modifiers, e.g. "Ctrl+Shift+F"
+fn chord_name(modifiers: winit::keyboard::ModifiersState, key_name: &str) -> String {
+    let mut chord = String::new();
+    for (held, name) in [
+        (modifiers.control_key(), "Ctrl+"),
+        (modifiers.alt_key(), "Alt+"),
+        (modifiers.shift_key(), "Shift+"),
+        (modifiers.super_key(), "Super+"),
+    ] {
+        if held {
+            chord.push_str(name);
+        }
+    }
+    chord.push_str(key_name);
+    chord
+}
+
+/// Build the example list shown on first launch, before anything has been saved
+fn demo_todo_list() -> TodoList {
+    let mut todo_list_inner = TodoList::new("Project Tasks");
+    
+    // Create some example tasks
+    let _project_tasks_id = todo_list_inner.add_item(TodoItem::new("Project Management"));
+    
+    // Create GPU Effects section
+    let gpu_effects_id = todo_list_inner.add_item(TodoItem::new("GPU Effects")
+        .with_priority(Priority::High));
+    todo_list_inner.add_item(TodoItem::new("Implement bloom/glow shader")
+        .with_parent(gpu_effects_id)
+        .with_priority(Priority::High));
+    todo_list_inner.add_item(TodoItem::new("Create custom WGSL shaders")
+        .with_parent(gpu_effects_id)
+        .with_priority(Priority::High));
+    todo_list_inner.add_item(TodoItem::new("Add particle system for task completion")
+        .with_parent(gpu_effects_id)
+        .with_priority(Priority::Medium));
+    
+    // Create Input section
+    let input_id = todo_list_inner.add_item(TodoItem::new("Input Improvements")
+        .with_priority(Priority::Medium));
+    todo_list_inner.add_item(TodoItem::new("Implement Vim-inspired navigation")
+        .with_parent(input_id)
+        .with_priority(Priority::Medium));
+    todo_list_inner.add_item(TodoItem::new("Add context menus")
+        .with_parent(input_id)
+        .with_priority(Priority::Low));
+    
+    // Create Polishing section
+    let polish_id = todo_list_inner.add_item(TodoItem::new("Visual Polish")
+        .with_priority(Priority::Low));
+    todo_list_inner.add_item(TodoItem::new("Refine animations and transitions")
+        .with_parent(polish_id)
+        .with_priority(Priority::Low));
+    
+    // Create Completed section
+    let completed_id = todo_list_inner.add_item(TodoItem::new("Completed Features"));
+    let ui_comp_id = todo_list_inner.add_item(TodoItem::new("UI Components")
+        .with_parent(completed_id)
+        .with_priority(Priority::Medium));
+    let filtering_id = todo_list_inner.add_item(TodoItem::new("Task filtering")
+        .with_parent(completed_id)
+        .with_priority(Priority::Medium));
+    let hierarchy_id = todo_list_inner.add_item(TodoItem::new("Task hierarchy visualization")
+        .with_parent(completed_id)
+        .with_priority(Priority::Medium));
+    
+    // Mark completed tasks
+    todo_list_inner.get_item_mut(ui_comp_id).unwrap().mark_completed();
+    todo_list_inner.get_item_mut(filtering_id).unwrap().mark_completed();
+    todo_list_inner.get_item_mut(hierarchy_id).unwrap().mark_completed();
     
-    // Post-processing effects
-    bloom_effect: BloomEffect,
-    neon_glow_effect: NeonGlowEffect,
+    todo_list_inner
 }
 
 impl State {
     // Creating some of the wgpu types requires async code
-    async fn new(window: Arc<Window>) -> Self {
+    async fn new(window: Arc<Window>) -> Result<Self, AppError> {
         let size = window.inner_size();
+        // Widgets are laid out in logical pixels, which the scale factor maps to the window's
+        let scale_factor = window.scale_factor();
+        let logical_size = size.to_logical::<f32>(scale_factor);
         
-        info!("Creating wgpu instance...");
-        let instance = Instance::new(InstanceDescriptor::default());
+        // Problems in config.toml are reported as toasts once the UI is up
+        let (app_config, mut config_problems) = Config::load();
         
-        // Create our window wrapper which guarantees the window stays alive
-        let window_wrapper = WindowWrapper::new(window);
+        // Pick the UI language before any widget builds its labels
+        i18n::init(app_config.language.as_deref());
         
-        info!("Creating surface from window...");
-        // Create the surface using our wrapper which handles the lifetime properly
-        let surface = window_wrapper.create_surface(&instance);
-        
-        info!("Selecting GPU adapter...");
-        let adapter = instance.request_adapter(
-            &RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
-            },
-        ).await.expect("Failed to find an appropriate adapter");
-        
-        info!("Selected adapter: {:?}", adapter.get_info().name);
+        // Try each backend in turn until one gives us a surface, a compatible adapter
+        // and a device, noting why each one that didn't failed
+        let mut selected = None;
+        let mut attempts = Vec::new();
+        for (backends, software) in backend_candidates() {
+            let name = if software { format!("{:?} (software)", backends) } else { format!("{:?}", backends) };
+            info!("Creating wgpu instance for {}...", name);
+            let instance = Instance::new(InstanceDescriptor {
+                backends,
+                ..Default::default()
+            });
+            
+            info!("Creating surface from window...");
+            // The surface keeps a clone of the Arc, which is what makes it 'static
+            let surface = match instance.create_surface(window.clone()) {
+                Ok(surface) => surface,
+                Err(e) => {
+                    info!("Surface creation failed for {}: {}", name, e);
+                    attempts.push(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+            
+            info!("Selecting GPU adapter...");
+            let adapter = instance.request_adapter(
+                &RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    force_fallback_adapter: software,
+                    compatible_surface: Some(&surface),
+                },
+            ).await;
+            let Some(adapter) = adapter else {
+                info!("No suitable adapter for {}", name);
+                attempts.push(format!("{}: no suitable adapter", name));
+                continue;
+            };
+            
+            // WebGL2 can't satisfy the default limits, so ask for what it supports
+            let required_limits = if adapter.get_info().backend == wgpu::Backend::Gl {
+                wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+            } else {
+                wgpu::Limits::default()
+            };
+            
+            let device = adapter.request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Device"),
+                    // Timestamp queries for the diagnostics overlay, where there are any
+                    required_features: adapter.features() & ui::diagnostics::GPU_TIMER_FEATURES,
+                    required_limits,
+                    memory_hints: wgpu::MemoryHints::default(),
+                },
+                None, // Trace path
+            ).await;
+            match device {
+                Ok((device, queue)) => {
+                    selected = Some((instance, surface, adapter, device, queue));
+                    break;
+                }
+                Err(e) => {
+                    info!("Device creation failed for {}: {}", name, e);
+                    attempts.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+        let (instance, surface, adapter, device, queue) = selected.ok_or(AppError::NoAdapter(attempts))?;
+        let (device, queue) = (Arc::new(device), Arc::new(queue));
         
-        let (device, queue) = adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                label: Some("Device"),
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
-                memory_hints: wgpu::MemoryHints::default(),
-            },
-            None, // Trace path
-        ).await.expect("Failed to create device");
+        info!("Selected adapter: {:?} ({:?})", adapter.get_info().name, adapter.get_info().backend);
         
         // Configure the surface
         let surface_caps = surface.get_capabilities(&adapter);
         // We'll use sRGB for better color accuracy
         let surface_format = surface_caps.formats.iter()
             .copied()
-            .filter(|f| f.is_srgb())
-            .next()
+            .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
         
         let config = SurfaceConfiguration {
@@ -145,491 +527,2614 @@ impl State {
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: wgpu::PresentMode::Fifo, // VSync
+            present_mode: app_config.present_mode(&surface_caps.present_modes),
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
         };
         
+        if let Some(problem) = unsupported_present_mode(app_config.display.present_mode, config.present_mode) {
+            config_problems.push(problem);
+        }
+        
         info!("Configuring surface...");
         surface.configure(&device, &config);
         
         // --- Text Rendering Setup --- 
-        // Load the font
-        let font_data = std::fs::read("fonts/Inconsolata-Regular.ttf").expect("Failed to read font file");
-        // wgpu_glyph uses FontArc directly in the builder
-        let font = ab_glyph::FontArc::try_from_vec(font_data).expect("Failed to load font from data");
-        info!("Font loaded successfully.");
+        // Load the fonts: the configured or bundled one, then fallbacks for glyphs it lacks
+        let fonts = ui::fonts::load(&app_config.font, &mut config_problems);
+        info!("Loaded {} fonts.", fonts.len());
+        ui::text_metrics::set_fonts(fonts.clone());
         
         // Create glyph_brush and staging belt
         info!("Creating GlyphBrush...");
-        let glyph_brush = GlyphBrushBuilder::using_font(font)
+        let color_glyphs = ColorGlyphs::new(&device, surface_format, fonts.clone());
+        let diagnostics = Diagnostics::new(&device, &queue);
+        let glyph_brush = GlyphBrushBuilder::using_fonts(fonts)
             .build(&device, surface_format);
+        let quad_renderer = QuadRenderer::new(&device, surface_format);
             
         info!("Creating StagingBelt...");
         // Create a staging belt for the text rendering pipeline
         let staging_belt = StagingBelt::new(1024); // 1KB staging belt
         
         // --- Todo List Setup ---
-        info!("Setting up todo list...");
-        let mut todo_list_inner = TodoList::new("Project Tasks");
-        
-        // Create some example tasks
-        let project_tasks_id = todo_list_inner.add_item(TodoItem::new("Project Management"));
-        
-        // Create GPU Effects section
-        let gpu_effects_id = todo_list_inner.add_item(TodoItem::new("GPU Effects")
-            .with_priority(Priority::High));
-        todo_list_inner.add_item(TodoItem::new("Implement bloom/glow shader")
-            .with_parent(gpu_effects_id)
-            .with_priority(Priority::High));
-        todo_list_inner.add_item(TodoItem::new("Create custom WGSL shaders")
-            .with_parent(gpu_effects_id)
-            .with_priority(Priority::High));
-        todo_list_inner.add_item(TodoItem::new("Add particle system for task completion")
-            .with_parent(gpu_effects_id)
-            .with_priority(Priority::Medium));
-        
-        // Create Input section
-        let input_id = todo_list_inner.add_item(TodoItem::new("Input Improvements")
-            .with_priority(Priority::Medium));
-        todo_list_inner.add_item(TodoItem::new("Implement Vim-inspired navigation")
-            .with_parent(input_id)
-            .with_priority(Priority::Medium));
-        todo_list_inner.add_item(TodoItem::new("Add context menus")
-            .with_parent(input_id)
-            .with_priority(Priority::Low));
-        
-        // Create Polishing section
-        let polish_id = todo_list_inner.add_item(TodoItem::new("Visual Polish")
-            .with_priority(Priority::Low));
-        todo_list_inner.add_item(TodoItem::new("Refine animations and transitions")
-            .with_parent(polish_id)
-            .with_priority(Priority::Low));
-        
-        // Create Completed section
-        let completed_id = todo_list_inner.add_item(TodoItem::new("Completed Features"));
-        let ui_comp_id = todo_list_inner.add_item(TodoItem::new("UI Components")
-            .with_parent(completed_id)
-            .with_priority(Priority::Medium));
-        let filtering_id = todo_list_inner.add_item(TodoItem::new("Task filtering")
-            .with_parent(completed_id)
-            .with_priority(Priority::Medium));
-        let hierarchy_id = todo_list_inner.add_item(TodoItem::new("Task hierarchy visualization")
-            .with_parent(completed_id)
-            .with_priority(Priority::Medium));
-        
-        // Mark completed tasks
-        todo_list_inner.get_item_mut(ui_comp_id).unwrap().mark_completed();
-        todo_list_inner.get_item_mut(filtering_id).unwrap().mark_completed();
-        todo_list_inner.get_item_mut(hierarchy_id).unwrap().mark_completed();
-        
-        info!("Todo list initialized with {} items", todo_list_inner.len());
-        
-        // Wrap the TodoList in an Arc<Mutex>
-        let todo_list = Arc::new(Mutex::new(todo_list_inner));
-        
-        // Initialize the CyberpunkTheme
-        let theme = CyberpunkTheme::new();
+        info!("Loading workspace...");
+        let data_path = app_config.data_path();
+        let workspace = match storage::load_workspace(&data_path) {
+            Ok(Some(workspace)) => workspace,
+            Ok(None) => {
+                info!("No saved todo list found, starting with the demo list");
+                Workspace::new(demo_todo_list())
+            }
+            Err(e) => {
+                error!("{}; starting with the demo list", e);
+                Workspace::new(demo_todo_list())
+            }
+        };
+        
+        info!("Workspace initialized with {} items in {} lists", workspace.item_count(), workspace.len());
+        
+        // A journal left behind means the last session ended without saving
+        let mut journal = Journal::for_data_path(&data_path);
+        let recovered_workspace = match journal.recover() {
+            Ok(recovered) => recovered,
+            Err(e) => {
+                error!("Ignoring unreadable recovery journal: {}", e);
+                None
+            }
+        };
+        journal.mark_saved(&workspace);
+        
+        let mut recovery_dialog = ConfirmDialog::new(
+            i18n::tr("recovery-title"),
+            i18n::tr("recovery-message"),
+            i18n::tr("recovery-restore"),
+            i18n::tr("recovery-discard"),
+        );
+        if let Some(recovered) = &recovered_workspace {
+            info!("Found recovery journal with {} items", recovered.item_count());
+            recovery_dialog.show(logical_size.width, logical_size.height);
+        }
+        
+        // Each list is an Arc<Mutex>, shared by everything that edits it; the active one is edited
+        let commands = Dispatcher::new(workspace.active_list().clone());
+        commands.set_auto_complete_parents(app_config.auto_complete_parents);
+        let mut sidebar = WorkspaceSidebar::new(0.0, 100.0, logical_size.height - 200.0);
+        sidebar.set_lists(workspace.names(), workspace.active());
+        let workspace = Arc::new(Mutex::new(workspace));
+        #[cfg(not(target_arch = "wasm32"))]
+        crash::watch(workspace.clone(), data_path.clone());
+        
+        let tasks = BackgroundTasks::new().map_err(AppError::Startup)?;
+        let saver = SaveWorker::spawn(data_path.clone(), journal, tasks.sender(), persistence::MIN_WRITE_INTERVAL)
+            .map_err(AppError::Startup)?;
+        
+        #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+        let plugins = {
+            let mut host = plugins::PluginHost::new();
+            if app_config.plugins.enabled {
+                let grants = app_config.plugins.grants
+                    .iter()
+                    .map(|(plugin, permissions)| {
+                        let permissions = permissions.iter().filter_map(|p| plugins::Permission::from_name(p)).collect();
+                        (plugin.clone(), permissions)
+                    })
+                    .collect();
+                config_problems.extend(host.load_dir(&Config::plugins_dir(), &grants, &commands));
+            }
+            host
+        };
+        
+        // The theme the config names, which validation has checked
+        let theme = CyberpunkTheme::named(&app_config.theme.name).unwrap_or_default();
         
         // Create the TodoListWidget
-        let todo_list_widget = TodoListWidget::new(
+        let mut todo_list_widget = TodoListWidget::themed(
             50.0, // x
             100.0, // y
-            size.width as f32 - 100.0, // width
-            size.height as f32 - 200.0, // height
-            todo_list.clone()
-        )
-        .with_on_status_change(|item| {
-            info!("Status changed for item {}: {:?}", item.id(), item.status());
-        })
-        .with_on_edit(|item| {
-            info!("Edit requested for item {}: {}", item.id(), item.title());
-        })
-        .with_on_delete(|item| {
-            info!("Delete requested for item {}", item.id());
-        });
-        
-        // Create post-processing effects
-        let bloom_effect = BloomEffect::new(
-            Arc::new(device.clone()),
-            Arc::new(queue.clone()),
-            config.format
+            logical_size.width - 100.0, // width
+            logical_size.height - 200.0, // height
+            commands.clone(),
+            theme.clone(),
         );
-
-        let neon_glow_effect = NeonGlowEffect::new(
-            Arc::new(device.clone()),
-            Arc::new(queue.clone()),
+        todo_list_widget.set_focus_follows_mouse(
+            app_config.input.focus_follows_mouse,
+            app_config.input.hover_focus_delay_secs,
+        );
+        
+        // Resume the view the last session ended with
+        let mut view_switcher = ViewSwitcher::new();
+        match Session::load(&data_path) {
+            Ok(session) => {
+                todo_list_widget.restore_session(&session);
+                view_switcher.set_mode(session.view);
+            }
+            Err(e) => error!("{}; starting with the default view", e),
+        }
+        
+        // Create post-processing effects, sized for the window
+        let post_process = PostProcessStack::new(
+            Arc::clone(&device),
+            Arc::clone(&queue),
             config.format,
-            &theme
+            &theme,
+            size.width,
+            size.height,
         );
 
-        // Initialize effects with the window size
-        bloom_effect.resize(size.width, size.height);
+        let mut toasts = ToastManager::new(logical_size.width, logical_size.height);
+        for problem in config_problems {
+            error!("Config: {}", problem);
+            toasts.error(problem);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let config_watcher = app_config.watch.then(Config::watch);
+        // The first sync runs as soon as the event loop does
+        #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+        let next_sync = app_config.sync.url.is_some().then(std::time::Instant::now);
 
         info!("WGPU state initialized successfully.");
         
-        Self {
-            window_wrapper,
+        let mut state = Self {
+            window,
             _instance: instance,
             surface,
             _adapter: adapter,
             device,
             queue,
             config,
+            present_modes: surface_caps.present_modes,
             size,
+            scale_factor,
+            frame_pacer: FramePacer::new(&app_config.display),
+            clock: FrameClock::new(),
+            fps_counter: FpsCounter::new(),
             glyph_brush,
+            quad_renderer,
+            color_glyphs,
             staging_belt,
-            todo_list,
+            frame_arena: FrameArena::new(),
+            workspace,
+            commands: commands.clone(),
             todo_list_widget,
-            theme,
+            board: KanbanBoard::new(),
+            timeline: TimelineView::new(),
+            view_switcher,
+            sidebar,
+            status_bar: StatusBar::new(26.0, 16.0),
+            focus_ring: FocusRing::new(),
             mouse_pos: (0.0, 0.0),
-            bloom_effect,
-            neon_glow_effect,
+            click_tracker: ClickTracker::new(),
+            pointer: PointerState::new(),
+            buttons_held: 0,
+            key_repeat: KeyRepeat::new().with_timing(
+                app_config.input.key_repeat_delay_secs,
+                app_config.input.key_repeat_interval_secs,
+            ),
+            keymap: app_config.keymap(),
+            modifiers: winit::keyboard::ModifiersState::empty(),
+            cursor_icon: CursorIcon::Default,
+            ime_caret: None,
+            post_process,
+            journal_timer: 0.0,
+            autosave_timer: None,
+            recovered_workspace,
+            recovery_dialog,
+            edit_modal: EditTaskModal::new(commands.clone()),
+            clipboard: ui::Clipboard::new(),
+            app_config,
+            settings_panel: SettingsPanel::new(),
+            archive_view: ArchiveView::new(),
+            toasts,
+            inspector: Inspector::new(),
+            diagnostics,
+            #[cfg(not(target_arch = "wasm32"))]
+            config_watcher,
+            layout: Layout::default(),
+            #[cfg(all(feature = "dev", not(target_arch = "wasm32")))]
+            layout_watcher: layout::LayoutWatcher::new(),
+            #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+            profiler: FlameView::new(),
+            tasks,
+            saver,
+            sync_status: None,
+            #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+            next_sync,
+            reminders: ReminderScheduler::new(),
+            next_reminder_check: 0.0,
+            focus: None,
+            #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+            plugins,
+            #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+            scripts: scripting::ScriptHost::new(commands),
+            #[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+            hotkey: None,
+            #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+            tray: None,
+            #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+            window_shown: true,
+            #[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+            quick_add: None,
+        };
+        state.apply_effect_settings();
+        state.apply_layout();
+        #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+        state.run_startup_script();
+        Ok(state)
+    }
+
+    /// Make a change given on the command line while we're running, to the
+    /// list with a name or the active one, returning the line it prints
+    #[cfg(not(target_arch = "wasm32"))]
+    fn execute_cli_change(&mut self, list: Option<&str>, change: cli::CliChange) -> Result<String, String> {
+        let shared = {
+            let workspace = self.workspace.lock().unwrap();
+            let index = cli::find_list(&workspace, list)?;
+            workspace.list(index).cloned().ok_or("The workspace has no lists")?
+        };
+        let (command, report) = change.to_command(&shared.lock().unwrap())?;
+        if let Some(command) = command {
+            if self.dispatcher_for(shared).execute(command)? {
+                self.autosave_timer = Some(0.0);
+                self.todo_list_widget.refresh();
+            }
         }
+        Ok(report)
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-            info!("Surface reconfigured for resize: {:?}", self.config);
-            
-            // Resize post-processing effects
-            self.bloom_effect.resize(new_size.width, new_size.height);
-            
-            // Update UI components with new size
-            self.todo_list_widget.set_dimensions(
-                new_size.width as f32 - 100.0,
-                new_size.height as f32 - 200.0
-            );
+    /// A dispatcher for a list of the workspace
+    ///
+    /// The active list goes through our own dispatcher, so its edits can be undone.
+    fn dispatcher_for(&self, shared: Arc<Mutex<TodoList>>) -> Dispatcher {
+        if Arc::ptr_eq(&shared, self.commands.list()) {
+            self.commands.clone()
+        } else {
+            Dispatcher::new(shared)
         }
     }
 
-    fn update(&mut self, delta_time: f32) {
-        // Update UI widgets
-        self.todo_list_widget.update(delta_time);
+    /// Sync the configured list with the CalDAV server in the background
+    ///
+    /// `announce` is set when the user asked for it (the sync keybind), so the
+    /// outcome is toasted as well as shown in the status bar.
+    fn start_sync(&mut self, announce: bool) {
+        #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+        {
+            if self.sync_status == Some(SyncStatus::Syncing) {
+                return;
+            }
+            let server = match sync::HttpCalDav::from_config(&self.app_config.sync) {
+                Some(server) => server,
+                None => {
+                    if announce {
+                        self.toasts.info(i18n::tr("sync-not-configured"));
+                    }
+                    return;
+                }
+            };
+            let target = server.and_then(|server| Ok((server, self.sync_target()?)));
+            let (server, list) = match target {
+                Ok(target) => target,
+                Err(e) => {
+                    self.sync_failed(e, announce);
+                    return;
+                }
+            };
+            // The sync works on a copy; what it pulls is applied once it's done
+            let snapshot = list.lock().unwrap().clone();
+            self.sync_status = Some(SyncStatus::Syncing);
+            self.tasks.spawn(async move {
+                let result = core::sync::caldav::sync(&snapshot, snapshot.sync_state(), &server).await;
+                TaskEvent::Synced { list, result, announce }
+            });
+        }
+        #[cfg(not(all(feature = "caldav", not(target_arch = "wasm32"))))]
+        if announce {
+            self.toasts.info(i18n::tr("sync-unavailable"));
+        }
     }
 
-    fn render(&mut self) -> Result<(), SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+    /// The list the `[sync]` table names, or the first one
+    #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+    fn sync_target(&self) -> Result<Arc<Mutex<TodoList>>, String> {
+        let workspace = self.workspace.lock().unwrap();
+        let index = match self.app_config.sync.list.as_deref() {
+            Some(name) => workspace
+                .names()
+                .iter()
+                .position(|list| list == name)
+                .ok_or_else(|| format!("No list named {:?} to sync", name))?,
+            None => 0,
+        };
+        workspace.list(index).cloned().ok_or_else(|| "No list to sync".to_string())
+    }
 
-        // Create temporary textures for post-processing
-        let scene_buffer_desc = wgpu::TextureDescriptor {
-            label: Some("Scene Buffer"),
-            size: wgpu::Extent3d {
-                width: self.size.width,
-                height: self.size.height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: self.config.format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
+    /// Apply what a sync pulled to the list it synced, and keep where it left things
+    ///
+    /// Returns how many tasks were sent and received.
+    #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+    fn finish_sync(&mut self, list: Arc<Mutex<TodoList>>, outcome: core::sync::caldav::SyncOutcome) -> Result<(usize, usize), String> {
+        let commands = self.dispatcher_for(list);
+        // As one change, so undoing it takes back the whole pull
+        if !outcome.commands.is_empty() && commands.execute(Command::Batch(outcome.commands))? {
+            self.todo_list_widget.refresh();
+        }
+        commands.list().lock().unwrap().set_sync_state(outcome.state);
+        self.autosave_timer = Some(0.0);
+        Ok((outcome.pushed, outcome.pulled))
+    }
+
+    #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+    fn sync_failed(&mut self, error: String, announce: bool) {
+        error!("Sync failed: {}", error);
+        if announce {
+            self.toasts.error(i18n::tr_args("sync-failed", &[("error", &error)]));
+        }
+        self.sync_status = Some(SyncStatus::Failed { error });
+    }
+
+    /// Remind of the tasks of every list that are coming due or just became overdue
+    fn check_reminders(&mut self) {
+        if !self.app_config.notifications.enabled {
+            return;
+        }
+        let now = ui::input::now_secs() as u64;
+        let lists: Vec<_> = {
+            let workspace = self.workspace.lock().unwrap();
+            (0..workspace.len()).filter_map(|index| workspace.list(index).cloned()).collect()
         };
-        
-        let scene_buffer = self.device.create_texture(&scene_buffer_desc);
-        let scene_view = scene_buffer.create_view(&wgpu::TextureViewDescriptor::default());
-        
-        let bloom_buffer = self.device.create_texture(&scene_buffer_desc);
-        let bloom_view = bloom_buffer.create_view(&wgpu::TextureViewDescriptor::default());
+        for list in lists {
+            let notices = self.reminders.check(&list.lock().unwrap(), now, self.app_config.notifications.lead_minutes);
+            for notice in notices {
+                self.remind(list.clone(), notice);
+            }
+        }
+    }
 
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Render Encoder"),
-        });
+    /// Archive the tasks of every list completed longer ago than the configured age
+    ///
+    /// Not an edit made by the user, so it isn't recorded for undo; the archive
+    /// view brings tasks back.
+    fn auto_archive(&mut self) {
+        let days = self.app_config.archive_after_days;
+        if days == 0 {
+            return;
+        }
+        let before = (ui::input::now_secs() as u64).saturating_sub(u64::from(days) * 24 * 60 * 60);
+        let lists: Vec<_> = {
+            let workspace = self.workspace.lock().unwrap();
+            (0..workspace.len()).filter_map(|index| workspace.list(index).cloned()).collect()
+        };
+        let archived: usize = lists
+            .iter()
+            .map(|list| list.lock().unwrap().archive_completed(Some(before)).len())
+            .sum();
+        if archived > 0 {
+            info!("Archived {} tasks completed over {} days ago", archived, days);
+            self.todo_list_widget.refresh();
+            self.autosave_timer = Some(0.0);
+            ui::damage::invalidate();
+        }
+    }
 
-        // --- First render pass - render scene to scene_buffer ---
+    /// Show a reminder as a desktop notification, or as a toast without the notifications feature
+    fn remind(&mut self, list: Arc<Mutex<TodoList>>, notice: Notice) {
+        let message = match notice.kind {
+            NoticeKind::Upcoming => "reminder-upcoming",
+            NoticeKind::Overdue => "reminder-overdue",
+        };
+        let text = i18n::tr_args(message, &[("title", &notice.title), ("due", &i18n::format_datetime(notice.due))]);
+        info!("{}", text);
+        #[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
         {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Scene Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &scene_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.039, // Very dark blue/purple background (#0A0A14)
-                            g: 0.039,
-                            b: 0.078,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
+            let sender = self.tasks.sender();
+            let id = notice.id;
+            notifications::show_desktop(text, i18n::tr("reminder-snooze"), move || {
+                let _ = sender.send(TaskEvent::Snoozed { list, id });
             });
         }
+        #[cfg(not(all(feature = "notifications", not(target_arch = "wasm32"))))]
+        {
+            let _ = list;
+            self.toasts.info(text);
+        }
+    }
 
-        // Create a render context for this frame
-        let mut render_ctx = RenderContext::new(
-            &self.queue,
-            &mut self.staging_belt,
-            &mut self.glyph_brush,
-            self.size.width as f32,
-            self.size.height as f32,
-        );
-        
-        // --- Render base widgets to scene_buffer ---
-        
-        // Render the application title
-        render_ctx.draw_text(
-            "✨ tewduwu ✨",
-            30.0,
-            30.0,
-            48.0,
-            [1.0, 0.255, 0.639, 1.0] // Neon Pink
-        );
+    /// Push a task's due date back by the configured snooze time
+    fn snooze(&mut self, list: Arc<Mutex<TodoList>>, id: uuid::Uuid) {
+        let item = list.lock().unwrap().get_item(id).map(|item| (item.title().to_string(), item.due_date()));
+        let Some((title, Some(due))) = item else {
+            return;
+        };
+        let due = notifications::snoozed_due(due, ui::input::now_secs() as u64, self.app_config.notifications.snooze_minutes);
+        match self.dispatcher_for(list).execute(Command::SetDueDate { id, due_date: Some(due) }) {
+            Ok(_) => {
+                self.reminders.snoozed(id, due);
+                self.todo_list_widget.refresh();
+                self.toasts.info(i18n::tr_args("snoozed", &[("title", &title), ("due", &i18n::format_datetime(due))]));
+            }
+            Err(e) => error!("Couldn't snooze item {}: {}", id, e),
+        }
+    }
 
-        // Render the base TodoListWidget elements (without modals)
-        self.todo_list_widget.render_base(&mut render_ctx);
-        
-        // Render instructions
-        render_ctx.draw_text(
-            "Press ESC to exit",
-            30.0,
-            self.size.height as f32 - 50.0,
-            20.0,
-            [0.5, 0.5, 0.5, 1.0]
-        );
-        
-        // --- Draw Text to scene_buffer --- 
-        self.glyph_brush
-            .draw_queued(
-                &self.device,
-                &mut self.staging_belt,
-                &mut encoder,
-                &scene_view,
-                self.size.width,
-                self.size.height,
-            )
-            .expect("Draw queued glyphs failed");
-        
-        // --- Apply Bloom Effect ---
-        self.bloom_effect.apply(&mut encoder, &scene_view, &bloom_view);
-        
-        // --- Render modals and other UI overlays ---
-        // Draw the modals on top of the bloom result
-        self.todo_list_widget.render_modals(&mut render_ctx);
-        
-        self.glyph_brush
-            .draw_queued(
-                &self.device,
-                &mut self.staging_belt,
-                &mut encoder,
-                &bloom_view,
-                self.size.width,
-                self.size.height,
-            )
-            .expect("Draw queued modal glyphs failed");
-        
-        // --- Apply Neon Glow Effect and output to the screen ---
-        self.neon_glow_effect.apply(&mut encoder, &bloom_view, &view);
-        
-        // Finish the staging belt BEFORE submitting the commands
-        self.staging_belt.finish();
-        
-        // Submit commands and present
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+    /// Open a link in a task's title or description in the browser or mail client
+    fn open_link(&mut self, id: uuid::Uuid, index: usize) {
+        let url = self.todo_list_widget.todo_list().lock().ok().and_then(|list| {
+            let item = list.get_item(id)?;
+            ui::markdown::item_links(item.title(), item.description()).into_iter().nth(index)
+        });
+        let Some(url) = url else {
+            return;
+        };
+        info!("Opening {}", url);
+        if let Err(e) = open_target(&url) {
+            error!("Couldn't open {}: {}", url, e);
+            self.toasts.error(i18n::tr_args("link-open-failed", &[("url", &url)]));
+        }
+    }
 
-        Ok(())
+    /// Open a task's attachment with the app the system picks for it
+    fn open_attachment(&mut self, id: uuid::Uuid, index: usize) {
+        let attachment = self.todo_list_widget.todo_list().lock().ok()
+            .and_then(|list| list.get_item(id)?.attachments().get(index).cloned());
+        let Some(attachment) = attachment else {
+            return;
+        };
+        let target = attachment.target();
+        info!("Opening {}", target);
+        if let Err(e) = open_target(&target) {
+            error!("Couldn't open {}: {}", target, e);
+            self.toasts.error(i18n::tr_args("attachment-open-failed", &[("name", &attachment.name()), ("error", &e)]));
+        }
     }
 
-    fn handle_mouse_input(&mut self, event: &WindowEvent) -> bool {
-        match event {
-            WindowEvent::CursorMoved { position, .. } => {
-                // Convert screen coordinates to logical
-                self.mouse_pos = (position.x as f32, position.y as f32);
-                
-                // Forward to TodoListWidget
-                self.todo_list_widget.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1);
-                true
-            },
-            WindowEvent::MouseWheel { delta, .. } => {
-                let scroll_amount = match delta {
-                    winit::event::MouseScrollDelta::LineDelta(_, y) => *y,
-                    winit::event::MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 20.0,
-                };
-                
-                // Forward scroll to TodoListWidget
-                self.todo_list_widget.handle_mouse_wheel(scroll_amount);
-                true
-            },
-            WindowEvent::MouseInput { state, button, .. } => {
-                match (button, state) {
-                    (winit::event::MouseButton::Left, winit::event::ElementState::Pressed) => {
-                        // Pass screen dimensions to handle expanded item modals correctly
-                        self.todo_list_widget.handle_mouse_down(
-                            self.mouse_pos.0, 
-                            self.mouse_pos.1, 
-                            self.size.width as f32,
-                            self.size.height as f32
-                        );
-                        true
-                    },
-                    (winit::event::MouseButton::Left, winit::event::ElementState::Released) => {
-                        self.todo_list_widget.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1);
-                        true
-                    },
-                    _ => false,
-                }
-            },
-            _ => false,
+    /// Open the file dialog to attach files to task `id` in the edit modal
+    ///
+    /// The files picked arrive as a background task's result.
+    fn pick_files(&mut self, id: uuid::Uuid) {
+        #[cfg(all(feature = "file-picker", not(target_arch = "wasm32")))]
+        {
+            // Made here, as some platforms only show dialogs from the main thread
+            let dialog = rfd::AsyncFileDialog::new()
+                .set_title(i18n::tr("edit-pick-files"))
+                .set_parent(self.window.as_ref())
+                .pick_files();
+            self.tasks.spawn(async move {
+                let files = dialog.await.unwrap_or_default();
+                TaskEvent::FilesPicked { id, paths: files.iter().map(|file| file.path().to_path_buf()).collect() }
+            });
         }
+        #[cfg(not(all(feature = "file-picker", not(target_arch = "wasm32"))))]
+        let _ = id;
     }
 
-    fn handle_keyboard_input(&mut self, event: &KeyEvent) -> bool {
-        match &event.logical_key {
-            winit::keyboard::Key::Character(c) if c.len() == 1 => {
-                // Get the first character
-                if let Some(ch) = c.chars().next() {
-                    self.todo_list_widget.handle_char_input(ch);
-                    true
-                } else {
-                    false
-                }
-            },
-            winit::keyboard::Key::Named(key) => {
-                if let Some(code) = key_to_keycode(key) {
-                    self.todo_list_widget.handle_key_press(code);
-                    true
-                } else {
-                    false
-                }
-            },
-            _ => false,
+    /// Start a focus session on the selected task, or pause or resume the one running
+    fn toggle_focus(&mut self) {
+        let now = ui::input::now_secs();
+        if let Some((timer, _)) = &mut self.focus {
+            timer.toggle_pause(now);
+            self.show_focus(now);
+            return;
+        }
+        match self.todo_list_widget.selected_item() {
+            Some(id) => self.start_focus(id),
+            None => self.toasts.info(i18n::tr("focus-no-task")),
         }
     }
-}
 
-// Helper function to convert winit::keyboard::NamedKey to winit::keyboard::KeyCode
-fn key_to_keycode(key: &winit::keyboard::NamedKey) -> Option<winit::keyboard::KeyCode> {
-    use winit::keyboard::{NamedKey, KeyCode};
-    
-    match key {
-        NamedKey::Escape => Some(KeyCode::Escape),
-        NamedKey::Enter => Some(KeyCode::Enter),
-        NamedKey::Delete => Some(KeyCode::Delete),
-        NamedKey::Backspace => Some(KeyCode::Backspace),
-        NamedKey::ArrowUp => Some(KeyCode::ArrowUp),
-        NamedKey::ArrowDown => Some(KeyCode::ArrowDown),
-        NamedKey::ArrowLeft => Some(KeyCode::ArrowLeft),
-        NamedKey::ArrowRight => Some(KeyCode::ArrowRight),
-        NamedKey::Tab => Some(KeyCode::Tab),
-        NamedKey::Space => Some(KeyCode::Space),
-        NamedKey::Home => Some(KeyCode::Home),
-        NamedKey::End => Some(KeyCode::End),
-        _ => None,
+    /// Start a focus session on an open task of the active list, in place of any running one
+    fn start_focus(&mut self, id: uuid::Uuid) {
+        let list = self.commands.list().clone();
+        let title = match list.lock().unwrap().get_item(id) {
+            Some(item) if !item.is_completed() => item.title().to_string(),
+            _ => return,
+        };
+        let now = ui::input::now_secs();
+        let timer = FocusTimer::start(id, &title, self.app_config.focus.session_minutes, now);
+        info!("Focusing on item {} for {} minutes", id, timer.minutes());
+        self.focus = Some((timer, list));
+        self.show_focus(now);
     }
-}
 
-fn main() {
-    // Setup logging with environment variables
-    // Use RUST_LOG=debug if you want to see all logs
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    /// Stop the focus session or break without logging it
+    fn stop_focus(&mut self) {
+        if self.focus.take().is_some() {
+            self.toasts.info(i18n::tr("focus-stopped"));
+            self.show_focus(ui::input::now_secs());
+        }
+    }
 
-    info!("Initializing tewduwu-neon (Rust)");
+    /// Move on from a focus session or break that ran out, and show the timer as it stands
+    fn check_focus(&mut self) {
+        let now = ui::input::now_secs();
+        self.focus = match self.focus.take() {
+            Some((timer, list)) if timer.is_finished(now) => self.finish_focus(timer, list, now),
+            focus => focus,
+        };
+        self.show_focus(now);
+    }
 
-    // 1. Create Event Loop and Window Builder
-    let event_loop = EventLoop::new().expect("Failed to create event loop");
-    let window_builder = WindowBuilder::new() // Store builder, not window yet
-        .with_title("tewduwu-neon (Rust)")
-        .with_inner_size(winit::dpi::LogicalSize::new(1280, 720));
+    /// Log a finished focus session on its task and start the break after it,
+    /// if there is one; returns the break
+    fn finish_focus(&mut self, timer: FocusTimer, list: Arc<Mutex<TodoList>>, now: f64) -> Option<(FocusTimer, Arc<Mutex<TodoList>>)> {
+        let focus = self.app_config.focus.clone();
+        let text = match timer.phase() {
+            Phase::Focus => {
+                let count = self.log_focus_session(&timer, list.clone(), now);
+                i18n::tr_args("focus-session-done", &[("title", timer.title()), ("count", &count.to_string())])
+            }
+            Phase::Break => i18n::tr_args("focus-break-done", &[("title", timer.title())]),
+        };
+        info!("{}", text);
+        if focus.notify {
+            #[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+            notifications::show_desktop_message(text);
+            #[cfg(not(all(feature = "notifications", not(target_arch = "wasm32"))))]
+            self.toasts.info(text);
+        }
+        (timer.phase() == Phase::Focus && focus.break_minutes > 0).then(|| (timer.into_break(focus.break_minutes, now), list))
+    }
 
-    // Initialize state outside the loop closure
-    let mut state_option: Option<State> = None;
+    /// Add a finished focus session to its task's log in the metadata,
+    /// returning how many sessions the log holds now
+    fn log_focus_session(&mut self, timer: &FocusTimer, list: Arc<Mutex<TodoList>>, now: f64) -> usize {
+        let id = timer.task();
+        let log = list.lock().unwrap().get_item(id).map(|item| item.metadata().get(pomodoro::LOG_KEY).cloned());
+        let Some(log) = log else {
+            info!("Item {} is gone, so its focus session isn't logged", id);
+            return 0;
+        };
+        let value = pomodoro::log_session(log.as_deref(), now as u64, timer.minutes());
+        let command = Command::SetMetadata { id, key: pomodoro::LOG_KEY.to_string(), value: Some(value) };
+        match self.dispatcher_for(list.clone()).execute(command) {
+            Ok(_) => self.autosave_timer = Some(0.0),
+            Err(e) => error!("Couldn't log the focus session of item {}: {}", id, e),
+        }
+        list.lock().map_or(0, |list| list.get_item(id).map_or(0, pomodoro::session_count))
+    }
 
-    info!("Entering event loop...");
+    /// Show the timer in the header as it stands at `now`, asking for a frame when that changes
+    fn show_focus(&mut self, now: f64) {
+        if self.focus_ring.set_timer(self.focus.as_ref().map(|(timer, _)| timer), now) {
+            self.frame_pacer.request_redraw();
+        }
+    }
+
+    /// Bring the window to the front, e.g. when another instance hands off to us
+    fn focus_window(&self) {
+        let window = &self.window;
+        window.set_minimized(false);
+        window.focus_window();
+        window.request_redraw();
+    }
+
+    /// Register the `[quick_add]` hotkey, forwarding its presses to the event loop
+    #[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+    fn with_hotkey(mut self, proxy: EventLoopProxy<AppEvent>) -> Self {
+        // An empty hotkey turns it off; validation left anything else parseable
+        let Some(chord) = ui::input::KeyChord::parse(&self.app_config.quick_add.hotkey) else {
+            return self;
+        };
+        let proxy = Mutex::new(proxy);
+        match platform::hotkey::GlobalHotkey::register(chord, move || {
+            let _ = proxy.lock().unwrap().send_event(AppEvent::QuickAddHotkey);
+        }) {
+            Ok(hotkey) => {
+                info!("Quick add hotkey is {}", chord);
+                self.hotkey = Some(hotkey);
+            }
+            Err(e) => {
+                error!("{}", e);
+                self.toasts.error(e);
+            }
+        }
+        self
+    }
+
+    /// The list the `[quick_add]` table names, or the first one
+    #[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+    fn quick_add_list(&self) -> Result<Arc<Mutex<TodoList>>, String> {
+        let workspace = self.workspace.lock().unwrap();
+        let index = match self.app_config.quick_add.list.as_deref() {
+            Some(name) => workspace
+                .names()
+                .iter()
+                .position(|list| list == name)
+                .ok_or_else(|| format!("No list named {:?} to quick add to", name))?,
+            None => 0,
+        };
+        workspace.list(index).cloned().ok_or_else(|| "No list to quick add to".to_string())
+    }
+
+    /// Open the quick-add popup for the `[quick_add]` list, on the hotkey
+    #[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+    fn handle_quick_add_hotkey(&mut self, target: &EventLoopWindowTarget<AppEvent>) {
+        match self.quick_add_list() {
+            Ok(list) => self.open_quick_add(target, list),
+            Err(e) => {
+                error!("{}", e);
+                self.toasts.error(e);
+                self.frame_pacer.request_redraw();
+            }
+        }
+    }
+
+    /// Put the icon in the tray, forwarding its menu picks to the event loop
+    #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+    fn with_tray(mut self, proxy: EventLoopProxy<AppEvent>) -> Self {
+        // Picks come in on whatever thread the platform delivers them on
+        let proxy = Mutex::new(proxy);
+        match tray::Tray::new(move |command| {
+            let _ = proxy.lock().unwrap().send_event(AppEvent::Tray(command));
+        }) {
+            Ok(tray) => self.tray = Some(tray),
+            Err(e) => error!("{}", e),
+        }
+        self.update_tray();
+        self
+    }
+
+    /// Count the overdue tasks of every list on the tray icon
+    #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+    fn update_tray(&self) {
+        let Some(tray) = &self.tray else {
+            return;
+        };
+        let lists: Vec<_> = {
+            let workspace = self.workspace.lock().unwrap();
+            (0..workspace.len()).filter_map(|index| workspace.list(index).cloned()).collect()
+        };
+        let overdue = lists.iter().map(|list| TaskCounts::of(&list.lock().unwrap()).overdue).sum();
+        tray.set_overdue(overdue);
+    }
+
+    /// Act on a tray menu pick
+    #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+    fn handle_tray_command(&mut self, command: tray::TrayCommand, target: &EventLoopWindowTarget<AppEvent>) {
+        match command {
+            tray::TrayCommand::QuickAdd => self.open_quick_add(target, self.commands.list().clone()),
+            tray::TrayCommand::ToggleWindow => {
+                self.window_shown = !self.window_shown;
+                self.window.set_visible(self.window_shown);
+                if self.window_shown {
+                    self.focus_window();
+                }
+                if let Some(tray) = &self.tray {
+                    tray.set_window_shown(self.window_shown);
+                }
+            }
+        }
+    }
+
+    /// Open the quick-add popup to add to `list`, or bring it to the front if it's open already
+    ///
+    /// The main window is left as it is, hidden or not.
+    #[cfg(all(feature = "quick-add", any(feature = "tray", feature = "hotkey"), not(target_arch = "wasm32")))]
+    fn open_quick_add(&mut self, target: &EventLoopWindowTarget<AppEvent>, list: Arc<Mutex<TodoList>>) {
+        if let Some(quick_add) = &self.quick_add {
+            quick_add.window.focus_window();
+            return;
+        }
+        let (width, height) = QUICK_ADD_SIZE;
+        let mut builder = WindowBuilder::new()
+            .with_title(i18n::tr("quick-add-title"))
+            .with_inner_size(winit::dpi::LogicalSize::new(width, height))
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_window_level(winit::window::WindowLevel::AlwaysOnTop);
+        // Near the top of the primary monitor, where the platform lets us place windows
+        if let Some(monitor) = target.primary_monitor() {
+            let origin = monitor.position().to_logical::<f64>(monitor.scale_factor());
+            let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+            builder = builder.with_position(winit::dpi::LogicalPosition::new(
+                origin.x + (size.width - width as f64) / 2.0,
+                origin.y + size.height / 4.0,
+            ));
+        }
+        let window = match builder.build(target) {
+            Ok(window) => Arc::new(window),
+            Err(e) => {
+                error!("Failed to open the quick-add window: {}", e);
+                return;
+            }
+        };
+        let surface = match self._instance.create_surface(window.clone()) {
+            Ok(surface) => surface,
+            Err(e) => {
+                error!("Failed to create the quick-add surface: {}", e);
+                return;
+            }
+        };
+        let size = window.inner_size();
+        let config = SurfaceConfiguration {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            ..self.config.clone()
+        };
+        surface.configure(&self.device, &config);
+
+        let mut popup = widgets::QuickAddPopup::new(width, height);
+        popup.set_list_name(list.lock().unwrap().name());
+        window.set_ime_allowed(true);
+        window.request_redraw();
+        self.quick_add = Some(QuickAddWindow {
+            window,
+            surface,
+            config,
+            popup,
+            list,
+            mouse_pos: (0.0, 0.0),
+            modifiers: winit::keyboard::ModifiersState::empty(),
+            last_frame: ui::input::now_secs(),
+        });
+    }
+
+    /// Whether a window event is for the quick-add popup's window
+    #[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+    fn is_quick_add_window(&self, window_id: winit::window::WindowId) -> bool {
+        self.quick_add.as_ref().is_some_and(|quick_add| quick_add.window.id() == window_id)
+    }
+
+    /// Handle an event for the quick-add popup's window
+    #[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+    fn handle_quick_add_event(&mut self, event: WindowEvent) {
+        let Some(quick_add) = self.quick_add.as_mut() else {
+            return;
+        };
+        let result = match event {
+            // Clicking elsewhere dismisses the popup like Escape does
+            WindowEvent::CloseRequested | WindowEvent::Focused(false) => Some(widgets::QuickAddEvent::Cancelled),
+            WindowEvent::Resized(size) => {
+                quick_add.resize(&self.device, size);
+                None
+            }
+            WindowEvent::RedrawRequested => {
+                self.render_quick_add();
+                return;
+            }
+            event => {
+                let Some(input) = InputEvent::from_window_event(&event, quick_add.window.scale_factor()) else {
+                    return;
+                };
+                quick_add.handle_input(input, &self.keymap, &mut self.clipboard)
+            }
+        };
+        match result {
+            Some(widgets::QuickAddEvent::Submitted(title)) => {
+                let list = quick_add.list.clone();
+                self.quick_add = None;
+                self.add_quick_task(list, &title);
+            }
+            Some(widgets::QuickAddEvent::Cancelled) => self.quick_add = None,
+            None => {
+                if let Some((x, y, width, height)) = quick_add.popup.caret_bounds() {
+                    quick_add.window.set_ime_cursor_area(
+                        winit::dpi::LogicalPosition::new(x, y),
+                        winit::dpi::LogicalSize::new(width, height),
+                    );
+                }
+                quick_add.window.request_redraw();
+            }
+        }
+    }
+
+    /// Add a task typed into the quick-add popup to the list it was opened for
+    #[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+    fn add_quick_task(&mut self, list: Arc<Mutex<TodoList>>, title: &str) {
+        match self.dispatcher_for(list).execute(Command::AddItem { item: TodoItem::new(title) }) {
+            Ok(true) => {
+                info!("Quick added {:?}", title);
+                self.autosave_timer = Some(0.0);
+                self.todo_list_widget.refresh();
+                self.frame_pacer.request_redraw();
+                #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+                self.update_tray();
+            }
+            Ok(false) => {}
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    /// Draw the quick-add popup into its window
+    ///
+    /// The popup has no post-processing, so it goes straight to the surface.
+    #[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+    fn render_quick_add(&mut self) {
+        let Some(quick_add) = self.quick_add.as_mut() else {
+            return;
+        };
+        let now = ui::input::now_secs();
+        quick_add.popup.update((now - quick_add.last_frame) as f32);
+        quick_add.last_frame = now;
+
+        let output = match quick_add.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(e) => {
+                error!("Quick-add render error: {:?}", e);
+                return;
+            }
+        };
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Quick Add Encoder"),
+        });
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Quick Add Clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+
+        self.frame_arena.begin_frame();
+        let mut render_ctx = RenderContext::new(
+            &self.queue,
+            &mut self.staging_belt,
+            &mut self.glyph_brush,
+            &mut self.quad_renderer,
+            &mut self.frame_arena,
+            quick_add.config.width as f32,
+            quick_add.config.height as f32,
+        )
+        .with_color_glyphs(&mut self.color_glyphs)
+        .with_scale_factor(quick_add.window.scale_factor() as f32)
+        .with_glow_color(self.app_config.effects.glow_color());
+        quick_add.popup.render(&mut render_ctx);
+        render_ctx
+            .draw_queued(&self.device, &mut encoder, &view)
+            .expect("Draw queued quick-add glyphs failed");
+        // Its focus glow would otherwise turn up in the main window's next frame
+        render_ctx.discard_glow();
+
+        self.staging_belt.finish();
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+        if quick_add.popup.needs_update() {
+            quick_add.window.request_redraw();
+        }
+    }
+
+    /// Persist the todo list to the configured data path, returning whether it succeeded
+    ///
+    /// Blocks until the worker wrote everything outstanding; used when exiting.
+    /// While the app runs, prefer `save_in_background`.
+    fn save(&mut self) -> bool {
+        self.save_in_background(false);
+        self.saver.shutdown(SHUTDOWN_TIMEOUT);
+        
+        let mut saved = false;
+        for event in self.tasks.poll() {
+            saved |= matches!(event, TaskEvent::Saved { result: Ok(()), .. });
+            self.handle_task_event(event);
+        }
+        saved
+    }
+    
+    /// Remember the current view for the next launch
+    fn save_session(&self) {
+        let session = Session { view: self.view_switcher.mode(), ..self.todo_list_widget.session() };
+        if let Err(e) = session.save(&self.app_config.data_path()) {
+            error!("{}", e);
+        }
+    }
+    
+    /// Write the workspace on the save worker, toasting when done if `announce` is set
+    ///
+    /// Only copying the lists happens here; serializing and writing don't hold up the frame.
+    fn save_in_background(&mut self, announce: bool) {
+        let workspace = self.workspace.lock().unwrap().snapshot();
+        // A journal we're still offering to restore must survive the save
+        let keep_journal = self.recovered_workspace.is_some();
+        self.saver.send(SaveRequest::Save { workspace, announce, keep_journal });
+    }
+    
+    /// Apply the result of a finished background task
+    fn handle_task_event(&mut self, event: TaskEvent) {
+        match event {
+            TaskEvent::Saved { announce, result } => match result {
+                Ok(()) => {
+                    if announce {
+                        self.toasts.info(i18n::tr("saved"));
+                    }
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    self.toasts.error(e);
+                }
+            },
+            TaskEvent::JournalFailed { error } => {
+                self.toasts.error(i18n::tr_args("journal-failed", &[("error", &error)]));
+            }
+            #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+            TaskEvent::Synced { list, result, announce } => {
+                match result.and_then(|outcome| self.finish_sync(list, outcome)) {
+                    Ok((pushed, pulled)) => {
+                        info!("Synced: {} sent, {} received", pushed, pulled);
+                        if announce {
+                            self.toasts.info(i18n::tr_args("sync-done", &[
+                                ("pushed", &pushed.to_string()),
+                                ("pulled", &pulled.to_string()),
+                            ]));
+                        }
+                        self.sync_status = Some(SyncStatus::Synced { at: ui::input::now_secs() as u64 });
+                    }
+                    Err(e) => self.sync_failed(e, announce),
+                }
+                let interval = self.app_config.sync.interval_secs;
+                self.next_sync = (interval > 0.0).then(|| std::time::Instant::now() + Duration::from_secs_f32(interval));
+                // Nothing else may be drawing a frame to show the new state
+                self.window.request_redraw();
+            }
+            #[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+            TaskEvent::Snoozed { list, id } => {
+                self.snooze(list, id);
+                self.window.request_redraw();
+            }
+            #[cfg(all(feature = "file-picker", not(target_arch = "wasm32")))]
+            TaskEvent::FilesPicked { id, paths } => {
+                self.edit_modal.add_files(id, paths);
+                self.window.request_redraw();
+            }
+        }
+    }
+    
+    /// Handle the events the todo list emitted since the last frame
+    ///
+    /// The list has already applied them to the data model; this is where the
+    /// application reacts to them.
+    fn handle_ui_events(&mut self) {
+        for event in self.todo_list_widget.take_events() {
+            match event {
+                UiEvent::StatusChanged { id, status } => info!("Status changed for item {}: {:?}", id, status),
+                UiEvent::EditRequested { id } => self.edit_item(id),
+                UiEvent::DeleteRequested { id } => info!("Deleted item {}", id),
+                UiEvent::TaskAdded { id } => info!("Added item {}", id),
+                UiEvent::PriorityChanged { id, priority } => info!("Priority changed for item {}: {:?}", id, priority),
+                UiEvent::CopyTitleRequested { id } => {
+                    let title = self.todo_list_widget.todo_list().lock().ok()
+                        .and_then(|list| list.get_item(id).map(|item| item.title().to_string()));
+                    if let Some(title) = title {
+                        self.clipboard.set_text(&title);
+                        self.toasts.info(i18n::tr_args("title-copied", &[("title", &title)]));
+                    }
+                }
+                UiEvent::SnoozeRequested { id } => {
+                    let list = self.todo_list_widget.todo_list();
+                    self.snooze(list, id);
+                }
+                UiEvent::FocusRequested { id } => self.start_focus(id),
+                UiEvent::LinkClicked { id, index } => self.open_link(id, index),
+                UiEvent::AttachmentClicked { id, index } => self.open_attachment(id, index),
+                UiEvent::BulkEdited { edit, count } => info!("Applied {:?} to {} items", edit, count),
+                UiEvent::SortChanged { order } => {
+                    info!("Sorted by {:?}{}", order.key, if order.descending { ", descending" } else { "" });
+                    // Not an edit, so no command brings it to the autosave
+                    self.autosave_timer = Some(0.0);
+                }
+                // The list filters by the tag and collapses rows itself
+                UiEvent::TagClicked { .. } | UiEvent::CollapseToggled { .. } => {}
+            }
+        }
+    }
+    
+    /// Take the changes made to the list since the last frame
+    ///
+    /// Every edit, whether from the UI, a script or a plugin, arrives here as
+    /// the command that made it, together with the command that reverts it.
+    fn handle_commands(&mut self) {
+        for applied in self.commands.take_applied() {
+            debug!("Applied {} (undone by {})", applied.command.name(), applied.undo.name());
+            // Every edit restarts the wait, so a burst of them is written once
+            self.autosave_timer = Some(0.0);
+        }
+    }
+
+    /// Revert the last change to the list, including removing a task with its subtasks
+    fn undo(&mut self) {
+        match self.commands.undo() {
+            Ok(true) => self.todo_list_widget.refresh(),
+            Ok(false) => info!("Nothing to undo"),
+            Err(e) => error!("Couldn't undo: {}", e),
+        }
+    }
+
+    /// Make the last undone change again
+    fn redo(&mut self) {
+        match self.commands.redo() {
+            Ok(true) => self.todo_list_widget.refresh(),
+            Ok(false) => info!("Nothing to redo"),
+            Err(e) => error!("Couldn't redo: {}", e),
+        }
+    }
+
+    /// Snapshot unsaved changes to the recovery journal
+    fn write_journal(&mut self) {
+        // Don't overwrite a journal we're still offering to restore
+        if self.recovered_workspace.is_some() {
+            return;
+        }
+        let workspace = self.workspace.lock().unwrap().snapshot();
+        self.saver.send(SaveRequest::Snapshot(workspace));
+    }
+    
+    /// Apply the user's answer to the recovery prompt
+    fn resolve_recovery(&mut self, choice: DialogChoice) {
+        if let Some(recovered) = self.recovered_workspace.take() {
+            match choice {
+                DialogChoice::Confirm => {
+                    info!("Restoring {} items from the recovery journal", recovered.item_count());
+                    self.restore_workspace(recovered);
+                }
+                DialogChoice::Cancel => info!("Discarded the recovery journal"),
+            }
+            self.saver.send(SaveRequest::ClearJournal);
+        }
+    }
+
+    /// Put the journaled lists back, replacing each list's contents as an undoable edit
+    ///
+    /// Lists created or removed after the last save are created or removed again.
+    fn restore_workspace(&mut self, recovered: Workspace) {
+        self.handle_commands();
+        let active = recovered.active();
+        let lists = recovered.into_lists();
+        {
+            let mut workspace = self.workspace.lock().unwrap();
+            while workspace.len() > lists.len() {
+                let last = workspace.len() - 1;
+                if let Err(e) = workspace.remove_list(last) {
+                    error!("{}", e);
+                    break;
+                }
+            }
+            for (index, list) in lists.into_iter().enumerate() {
+                let Some(shared) = workspace.list(index).cloned() else {
+                    workspace.push_list(list);
+                    continue;
+                };
+                // The active list goes through our own dispatcher so the edit is recorded
+                let commands = if Arc::ptr_eq(&shared, self.commands.list()) {
+                    self.commands.clone()
+                } else {
+                    Dispatcher::new(shared)
+                };
+                if let Err(e) = commands.execute(Command::ReplaceList { list }) {
+                    error!("{}", e);
+                }
+            }
+        }
+        self.show_list(active);
+        self.todo_list_widget.refresh();
+    }
+
+    /// Make the list at `index` the one shown and edited
+    fn show_list(&mut self, index: usize) {
+        // Changes made through the old dispatcher still need saving
+        self.handle_commands();
+        let (list, names, active) = {
+            let mut workspace = self.workspace.lock().unwrap();
+            if let Err(e) = workspace.set_active(index) {
+                error!("{}", e);
+                return;
+            }
+            (workspace.active_list().clone(), workspace.names(), workspace.active())
+        };
+        self.sidebar.set_lists(names, active);
+        if !Arc::ptr_eq(&list, self.commands.list()) {
+            info!("Switched to list {}", index);
+            self.set_commands(Dispatcher::new(list));
+        }
+        // The active list is part of what gets saved
+        self.autosave_timer = Some(0.0);
+    }
+
+    /// Append a new, empty list and show it
+    fn add_list(&mut self) {
+        let added = {
+            let mut workspace = self.workspace.lock().unwrap();
+            let number = (workspace.len() + 1).to_string();
+            workspace.add_list(&i18n::tr_args("sidebar-default-name", &[("number", &number)]))
+        };
+        match added {
+            Ok(index) => self.show_list(index),
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    /// Route every edit, from the UI, scripts and plugins, to another list
+    fn set_commands(&mut self, commands: Dispatcher) {
+        commands.set_auto_complete_parents(self.app_config.auto_complete_parents);
+        self.todo_list_widget.set_dispatcher(commands.clone());
+        self.edit_modal.set_dispatcher(commands.clone());
+        #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+        self.plugins.set_dispatcher(&commands);
+        #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+        self.scripts.set_dispatcher(commands.clone());
+        self.commands = commands;
+    }
+
+    /// Apply a click on the lists sidebar
+    fn handle_sidebar_event(&mut self, event: SidebarEvent) {
+        match event {
+            SidebarEvent::Selected(index) => self.show_list(index),
+            SidebarEvent::AddRequested => self.add_list(),
+            SidebarEvent::ProjectSelected(index) => {
+                // Clicking the project the list is filtered to shows everything again
+                let project_id = self.commands.lock().projects().get(index).map(|project| project.id());
+                let filter = project_id.filter(|&id| self.todo_list_widget.project_filter() != Some(id));
+                self.todo_list_widget.set_project_filter(filter);
+            }
+            SidebarEvent::RecolorRequested(index) => {
+                let palette = CyberpunkTheme::new().project_palette();
+                let Some(mut project) = self.commands.lock().projects().get(index).cloned() else {
+                    return;
+                };
+                let next = palette.iter().position(|&color| color == project.color()).map_or(0, |current| current + 1);
+                project.set_color(palette[next % palette.len()]);
+                if let Err(e) = self.commands.execute(Command::PutProject { project }) {
+                    error!("Couldn't recolor the project: {}", e);
+                }
+            }
+            SidebarEvent::AddProjectRequested => {
+                let palette = CyberpunkTheme::new().project_palette();
+                let count = self.commands.lock().projects().len();
+                let name = i18n::tr_args("sidebar-default-project", &[("number", &(count + 1).to_string())]);
+                let project = Project::new(&name, palette[count % palette.len()]);
+                if let Err(e) = self.commands.execute(Command::PutProject { project }) {
+                    error!("Couldn't add a project: {}", e);
+                }
+            }
+            SidebarEvent::Toggled(_) => self.place_list(),
+        }
+    }
+
+    /// Place the list and arrange its toolbar as the layout describes
+    fn apply_layout(&mut self) {
+        self.place_list();
+        self.todo_list_widget.set_toolbar(&self.layout.toolbar.controls);
+    }
+
+    /// Put the sidebar along the left edge, the list, the board and the timeline
+    /// where the layout says beside it, the status bar along the bottom and the view switcher and
+    /// focus timer above the list
+    fn place_list(&mut self) {
+        let (screen_width, screen_height) = self.logical_size();
+        let (x, y, width, height) = self.layout.list_rect(screen_width, screen_height);
+        self.sidebar.set_position(0.0, y);
+        self.sidebar.set_dimensions(0.0, height);
+        let sidebar_width = self.sidebar.width();
+        self.todo_list_widget.set_position(x + sidebar_width, y);
+        self.todo_list_widget.set_dimensions((width - sidebar_width).max(0.0), height);
+        self.board.set_position(x + sidebar_width, y);
+        self.board.set_dimensions((width - sidebar_width).max(0.0), height);
+        self.timeline.set_position(x + sidebar_width, y);
+        self.timeline.set_dimensions((width - sidebar_width).max(0.0), height);
+        
+        let status_bar = &self.layout.status_bar;
+        self.status_bar.set_text_size(status_bar.size);
+        self.status_bar.set_position(0.0, screen_height - status_bar.height);
+        self.status_bar.set_dimensions(screen_width, status_bar.height);
+        
+        // The view switcher sits at the right of the header, above the list, and the focus timer before it
+        let (switcher_width, switcher_height) = ViewSwitcher::size();
+        self.view_switcher.set_position(x + width - switcher_width, ((y - switcher_height) / 2.0).max(0.0));
+        let (ring_width, ring_height) = FocusRing::size();
+        self.focus_ring.set_position(x + width - switcher_width - 16.0 - ring_width, ((y - ring_height) / 2.0).max(0.0));
+    }
+    
+    /// Show the active list's counts, the filter, the input mode, how syncing went and the frame rate
+    fn update_status_bar(&mut self) {
+        if let Ok(list) = self.commands.list().lock() {
+            self.status_bar.set_counts(TaskCounts::of(&list));
+        }
+        self.status_bar.set_filter(&self.todo_list_widget.filter_summary());
+        let typing = self.todo_list_widget.is_editing_text() || self.edit_modal.is_visible();
+        self.status_bar.set_mode(if typing { InputMode::Insert } else { InputMode::Normal });
+        self.status_bar.set_sync(self.sync_status.as_ref());
+        self.status_bar.set_fps(self.fps_counter.fps());
+    }
+    
+    /// Show the active list's projects in the sidebar, with their open task counts
+    fn update_sidebar_projects(&mut self) {
+        let Ok(list) = self.commands.list().lock() else {
+            return;
+        };
+        let theme = CyberpunkTheme::new();
+        let projects = list.projects().iter()
+            .map(|project| SidebarProject {
+                name: project.name().to_string(),
+                accent: theme.project_accent(project.color()),
+                open: list.items_by_project(Some(project.id())).iter().filter(|item| !item.is_completed()).count(),
+            })
+            .collect();
+        let filter = self.todo_list_widget.project_filter();
+        let selected = list.projects().iter().position(|project| Some(project.id()) == filter);
+        drop(list);
+        self.sidebar.set_projects(projects, selected);
+    }
+    
+    /// Show the active list on the board or the timeline while it's the view
+    /// shown, only the sidebar's project if one is picked
+    fn update_views(&mut self) {
+        let project = self.todo_list_widget.project_filter();
+        match self.view_switcher.mode() {
+            ViewMode::List => {}
+            ViewMode::Board => self.board.set_list(&self.commands.lock(), project),
+            ViewMode::Timeline => self.timeline.set_list(&self.commands.lock(), project, ui::input::now_secs() as u64),
+        }
+    }
+    
+    /// Open the task editor on an item
+    fn edit_item(&mut self, id: uuid::Uuid) {
+        let (width, height) = self.logical_size();
+        if !self.edit_modal.show(id, width, height) {
+            error!("Can't edit item {}, it no longer exists", id);
+        }
+    }
+    
+    /// Give a task the status of the column its card was dropped on, or open a clicked one in the editor
+    fn handle_board_event(&mut self, event: BoardEvent) {
+        match event {
+            BoardEvent::Moved { id, status } => {
+                if let Err(e) = self.commands.execute(Command::SetStatus { id, status }) {
+                    error!("Couldn't move item {}: {}", id, e);
+                    self.toasts.error(e);
+                }
+                self.todo_list_widget.refresh();
+                self.update_views();
+            }
+            BoardEvent::Opened(id) => self.edit_item(id),
+        }
+    }
+    
+    /// Push the configured effect settings to the post-processing passes
+    fn apply_effect_settings(&mut self) {
+        let effects = &self.app_config.effects;
+        // Disabled effects still run, just at zero strength, so the render graph stays the same
+        let bloom_intensity = if effects.bloom { effects.bloom_intensity } else { 0.0 };
+        let glow_intensity = if effects.glow { effects.glow_intensity } else { 0.0 };
+        self.post_process.set_bloom(effects.bloom_threshold, bloom_intensity, effects.bloom_saturation);
+        self.post_process.set_glow(glow_intensity, effects.glow_size * self.scale_factor as f32);
+        self.post_process.set_glow_pulse(effects.glow_pulse);
+    }
+    
+    /// Switch to a new configuration, applying whatever can change at runtime
+    fn apply_config(&mut self, app_config: Config) {
+        if app_config.display != self.app_config.display {
+            let present_mode = app_config.present_mode(&self.present_modes);
+            if present_mode != self.config.present_mode {
+                self.config.present_mode = present_mode;
+                self.surface.configure(&self.device, &self.config);
+                if let Some(problem) = unsupported_present_mode(app_config.display.present_mode, present_mode) {
+                    self.toasts.error(problem);
+                }
+            }
+            self.frame_pacer.configure(&app_config.display);
+        }
+        self.key_repeat.set_timing(app_config.input.key_repeat_delay_secs, app_config.input.key_repeat_interval_secs);
+        self.todo_list_widget.set_focus_follows_mouse(app_config.input.focus_follows_mouse, app_config.input.hover_focus_delay_secs);
+        self.keymap = app_config.keymap();
+        self.commands.set_auto_complete_parents(app_config.auto_complete_parents);
+        self.app_config = app_config;
+        self.apply_effect_settings();
+    }
+    
+    /// Switch to the next present mode the surface supports (the cycle_present_mode keybind)
+    fn cycle_present_mode(&mut self) {
+        let mut mode = self.app_config.display.present_mode.next();
+        while mode.resolve(&self.present_modes) == wgpu::PresentMode::Fifo && mode != PresentMode::Fifo {
+            mode = mode.next();
+        }
+        let mut app_config = self.app_config.clone();
+        app_config.display.present_mode = mode;
+        self.apply_config(app_config);
+        self.toasts.info(i18n::tr_args("present-mode-changed", &[("mode", &ui::present_mode_name(mode))]));
+    }
+    
+    /// Open the settings panel, or close it if it's already open
+    fn toggle_settings(&mut self) {
+        if self.settings_panel.is_visible() {
+            self.settings_panel.hide();
+        } else {
+            let (width, height) = self.logical_size();
+            self.settings_panel.show(&self.app_config, width, height);
+        }
+    }
+    
+    /// Open the active list's archive, or close it if it's already open
+    fn toggle_archive(&mut self) {
+        if self.archive_view.is_visible() {
+            self.archive_view.hide();
+        } else {
+            let (width, height) = self.logical_size();
+            self.archive_view.show(&self.commands.lock(), width, height);
+        }
+    }
+    
+    /// Make the changes asked for in the archive view
+    fn handle_archive_event(&mut self, event: ArchiveEvent) {
+        let command = match event {
+            ArchiveEvent::Restore(id) => Command::Restore { id },
+            ArchiveEvent::ArchiveCompleted => {
+                let ids = self.commands.lock().archivable(None);
+                if ids.is_empty() {
+                    self.toasts.info(i18n::tr("archive-nothing-completed"));
+                    return;
+                }
+                Command::Batch(ids.into_iter().map(|id| Command::Archive { id }).collect())
+            }
+            ArchiveEvent::Closed => return,
+        };
+        if let Err(e) = self.commands.execute(command) {
+            error!("Couldn't change the archive: {}", e);
+            self.toasts.error(e);
+        }
+        self.todo_list_widget.refresh();
+        self.archive_view.set_list(&self.commands.lock());
+    }
+    
+    /// React to changes made in the settings panel
+    fn handle_settings_event(&mut self, event: SettingsEvent) {
+        match event {
+            SettingsEvent::Changed(app_config) => self.apply_config(app_config),
+            SettingsEvent::Saved(app_config) => {
+                self.apply_config(app_config);
+                match self.app_config.save() {
+                    Ok(()) => self.toasts.info(i18n::tr_args("settings-saved", &[("path", &Config::path().display().to_string())])),
+                    Err(e) => {
+                        error!("{}", e);
+                        self.toasts.error(e);
+                    }
+                }
+            }
+            SettingsEvent::Closed => {}
+        }
+    }
+
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        let _span = trace_span!("layout", width = new_size.width, height = new_size.height).entered();
+        profile_scope!("layout");
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+            info!("Surface reconfigured for resize: {:?}", self.config);
+            
+            // Resize post-processing effects; their textures are made anew for the new size
+            self.post_process.resize(new_size.width, new_size.height);
+            
+            self.layout_widgets();
+        }
+    }
+
+    /// The window's size in logical pixels, which layout and hit-testing work in
+    fn logical_size(&self) -> (f32, f32) {
+        let size = self.size.to_logical::<f32>(self.scale_factor);
+        (size.width, size.height)
+    }
+
+    /// Fit the UI components to the window's logical size
+    fn layout_widgets(&mut self) {
+        let (width, height) = self.logical_size();
+        self.place_list();
+        self.recovery_dialog.layout(width, height);
+        self.settings_panel.layout(width, height);
+        self.archive_view.layout(width, height);
+        self.edit_modal.layout(width, height);
+        self.toasts.set_dimensions(width, height);
+    }
+
+    /// Lay the UI out again for a display with a different scale factor
+    ///
+    /// The window reports its new physical size in a `Resized` event of its own.
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        self.layout_widgets();
+        // The glow's size is in pixels, so it grows with the UI
+        self.apply_effect_settings();
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        let _span = trace_span!("update").entered();
+        profile_scope!("update");
+        
+        // Replay held navigation/deletion keys at our own repeat rate
+        if let Some((code, count)) = self.key_repeat.tick(delta_time) {
+            // Repeatable KeyCodes (arrows, Backspace, Delete) share their names with NamedKey
+            let chord = chord_name(self.modifiers, &format!("{:?}", code));
+            let action = self.keymap.action(self.modifiers, code);
+            for _ in 0..count {
+                // The task editor's fields take selection and word movement too (Shift/Ctrl+Arrow)
+                let edited = action.is_some_and(|action| self.edit_modal.handle_action(action));
+                if !edited && !self.handle_shortcut(&chord, action) {
+                    self.handle_key_code(code);
+                }
+            }
+        }
+        
+        // Update UI widgets
+        self.todo_list_widget.update(delta_time);
+        self.handle_ui_events();
+        self.handle_commands();
+        if self.recovery_dialog.needs_update() {
+            self.recovery_dialog.update(delta_time);
+        }
+        if self.settings_panel.needs_update() {
+            self.settings_panel.update(delta_time);
+        }
+        if self.archive_view.needs_update() {
+            self.archive_view.update(delta_time);
+        }
+        if self.edit_modal.needs_update() {
+            self.edit_modal.update(delta_time);
+        }
+        self.update_ime();
+        if self.toasts.needs_update() {
+            self.toasts.update(delta_time);
+        }
+        self.update_status_bar();
+        self.update_sidebar_projects();
+        self.update_views();
+        let main_view: &dyn Widget = match self.view_switcher.mode() {
+            ViewMode::List => &self.todo_list_widget,
+            ViewMode::Board => &self.board,
+            ViewMode::Timeline => &self.timeline,
+        };
+        self.inspector.collect(
+            &[main_view, &self.view_switcher, &self.sidebar, &self.status_bar, &self.focus_ring, &self.recovery_dialog, &self.settings_panel, &self.archive_view, &self.edit_modal, &self.toasts],
+            self.mouse_pos,
+        );
+        #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+        self.profiler.collect();
+        
+        // Re-apply the layout file whenever it's saved
+        #[cfg(all(feature = "dev", not(target_arch = "wasm32")))]
+        match self.layout_watcher.poll(delta_time) {
+            Some(Ok(layout)) => {
+                info!("Reloaded {}", self.layout_watcher.path().display());
+                self.layout = layout;
+                self.apply_layout();
+            }
+            Some(Err(e)) => {
+                error!("{}", e);
+                self.toasts.error(e);
+            }
+            None => {}
+        }
+        
+        // Write the data file once edits pause
+        if let Some(quiet) = self.autosave_timer.as_mut() {
+            *quiet += delta_time;
+            if *quiet >= self.app_config.autosave_delay_secs {
+                self.autosave_timer = None;
+                self.save_in_background(false);
+            }
+        }
+        
+        // Periodically journal unsaved changes so a crash loses at most a few seconds
+        self.journal_timer += delta_time;
+        if self.journal_timer >= self.app_config.autosave_interval_secs {
+            self.journal_timer = 0.0;
+            self.write_journal();
+        }
+    }
+
+    /// Hand queued saves to the worker and handle finished background work
+    ///
+    /// Runs whenever the event loop wakes, so results arrive while no frames are drawn.
+    fn poll_background(&mut self) {
+        self.saver.tick();
+        for event in self.tasks.poll() {
+            self.handle_task_event(event);
+            ui::damage::invalidate();
+        }
+        #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+        if self.next_sync.is_some_and(|due| std::time::Instant::now() >= due) {
+            self.next_sync = None;
+            self.start_sync(false);
+        }
+        self.check_focus();
+        let now = ui::input::now_secs();
+        
+        // Hot-apply edits to the config file, on the clock so it happens while idle too
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(watcher) = self.config_watcher.as_mut() {
+            if let Some((app_config, problems)) = watcher.poll(now) {
+                info!("Reloaded {}", watcher.path().display());
+                for problem in problems {
+                    error!("Config: {}", problem);
+                    self.toasts.error(problem);
+                }
+                if !app_config.watch {
+                    self.config_watcher = None;
+                }
+                self.apply_config(app_config);
+                ui::damage::invalidate();
+            }
+        }
+        if now >= self.next_reminder_check {
+            self.next_reminder_check = now + REMINDER_CHECK_INTERVAL_SECS;
+            self.check_reminders();
+            self.auto_archive();
+            #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+            self.update_tray();
+        }
+    }
+    
+    /// Whether anything on screen is moving, so frames must keep coming
+    fn is_animating(&self) -> bool {
+        #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+        if self.profiler.is_visible() {
+            return true;
+        }
+        if self.diagnostics.is_visible() {
+            return true;
+        }
+        self.todo_list_widget.needs_update()
+            || self.recovery_dialog.needs_update()
+            || self.settings_panel.needs_update()
+            || self.archive_view.needs_update()
+            || self.edit_modal.needs_update()
+            || self.toasts.needs_update()
+            || self.key_repeat.held().is_some()
+            || (self.app_config.effects.glow && self.app_config.effects.glow_pulse > 0.0)
+    }
+    
+    /// Whether timers or background work need `update` to keep running, drawn or not
+    fn has_pending_work(&self) -> bool {
+        // The layout file is polled once per update
+        cfg!(all(feature = "dev", not(target_arch = "wasm32")))
+            || self.autosave_timer.is_some()
+            || !self.tasks.is_idle()
+    }
+    
+    /// Record and submit a frame
+    ///
+    /// The spans time how long each pass takes to encode on the CPU; the GPU
+    /// runs the work after submit.
+    fn render(&mut self) -> Result<(), SurfaceError> {
+        profile_scope!("render");
+        let output = self.surface.get_current_texture()?;
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let parts = FrameParts {
+            device: &self.device,
+            queue: &self.queue,
+            staging_belt: &mut self.staging_belt,
+            glyph_brush: &mut self.glyph_brush,
+            quad_renderer: &mut self.quad_renderer,
+            frame_arena: &mut self.frame_arena,
+            post_process: &mut self.post_process,
+            color_glyphs: Some(&mut self.color_glyphs),
+            diagnostics: Some(&mut self.diagnostics),
+        };
+        let settings = FrameSettings {
+            width: self.size.width,
+            height: self.size.height,
+            scale_factor: self.scale_factor as f32,
+            time: self.clock.elapsed(),
+            glow_color: self.app_config.effects.glow_color(),
+        };
+
+        // The title, the lists sidebar and the list without its modals, or the view in its place
+        let scene = |render_ctx: &mut RenderContext| {
+            let title = &self.layout.title;
+            if title.visible {
+                render_ctx.draw_text(&title.text, title.x, title.y, title.size, title.color);
+            }
+            self.sidebar.render(render_ctx);
+            match self.view_switcher.mode() {
+                ViewMode::List => self.todo_list_widget.render_base(render_ctx),
+                ViewMode::Board => self.board.render(render_ctx),
+                ViewMode::Timeline => self.timeline.render(render_ctx),
+            }
+            let instructions = &self.layout.instructions;
+            if instructions.visible {
+                let quit_key = self.app_config.keybind("quit").unwrap_or("-");
+                let settings_key = self.app_config.keybind("settings").unwrap_or("-");
+                render_ctx.draw_text(
+                    &i18n::tr_args("instructions", &[("quit", quit_key), ("settings", settings_key)]),
+                    instructions.x,
+                    render_ctx.height - instructions.bottom,
+                    instructions.size,
+                    [0.5, 0.5, 0.5, 1.0]
+                );
+            }
+            if self.layout.status_bar.visible {
+                self.status_bar.render(render_ctx);
+            }
+            self.focus_ring.render(render_ctx);
+            self.view_switcher.render(render_ctx);
+        };
+
+        // Modals and other overlays on top of the bloom result
+        let overlay = |render_ctx: &mut RenderContext| {
+            self.todo_list_widget.render_modals(render_ctx);
+            self.recovery_dialog.render(render_ctx);
+            self.settings_panel.render(render_ctx);
+            self.archive_view.render(render_ctx);
+            self.edit_modal.render(render_ctx);
+            self.toasts.render(render_ctx);
+            self.inspector.render(render_ctx);
+            #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+            self.profiler.render(render_ctx);
+        };
+
+        draw_frame(parts, settings, &view, scene, overlay).expect("Drawing the frame failed");
+        let _present_span = trace_span!("present").entered();
+        output.present();
+
+        Ok(())
+    }
+
+    /// Route pointer input, `time` being when it happened in seconds
+    fn handle_mouse_input(&mut self, input: &InputEvent, time: f64) -> bool {
+        // The recovery prompt is modal and swallows all mouse input while shown
+        if self.recovery_dialog.is_visible() {
+            match *input {
+                InputEvent::CursorMoved { x, y } => {
+                    self.mouse_pos = (x, y);
+                    self.recovery_dialog.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1);
+                }
+                InputEvent::MouseButton { middle: false, pressed: true } => {
+                    self.recovery_dialog.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1);
+                }
+                InputEvent::MouseButton { middle: false, pressed: false } => {
+                    if let Some(choice) = self.recovery_dialog.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1) {
+                        self.resolve_recovery(choice);
+                    }
+                }
+                _ => {}
+            }
+            return true;
+        }
+        
+        // Likewise for the settings panel
+        if self.settings_panel.is_visible() {
+            match *input {
+                InputEvent::CursorMoved { x, y } => {
+                    self.mouse_pos = (x, y);
+                    if let Some(settings_event) = self.settings_panel.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1) {
+                        self.handle_settings_event(settings_event);
+                    }
+                }
+                InputEvent::MouseButton { middle: false, pressed: true } => {
+                    if let Some(settings_event) = self.settings_panel.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1) {
+                        self.handle_settings_event(settings_event);
+                    }
+                }
+                InputEvent::MouseButton { middle: false, pressed: false } => {
+                    if let Some(settings_event) = self.settings_panel.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1) {
+                        self.handle_settings_event(settings_event);
+                    }
+                }
+                _ => {}
+            }
+            return true;
+        }
+        
+        // And for the archive view, which scrolls as well
+        if self.archive_view.is_visible() {
+            match *input {
+                InputEvent::CursorMoved { x, y } => {
+                    self.mouse_pos = (x, y);
+                    self.archive_view.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1);
+                }
+                InputEvent::MouseWheel { x, y, pixels } => {
+                    let scroll_amount = self.app_config.input.scroll_pixels(&InputEvent::scroll_delta(x, y, pixels));
+                    self.archive_view.handle_mouse_wheel(scroll_amount);
+                }
+                InputEvent::MouseButton { middle: false, pressed: true } => {
+                    self.archive_view.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1);
+                }
+                InputEvent::MouseButton { middle: false, pressed: false } => {
+                    if let Some(archive_event) = self.archive_view.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1) {
+                        self.handle_archive_event(archive_event);
+                    }
+                }
+                _ => {}
+            }
+            return true;
+        }
+        
+        // And for the task editor, which also needs click counts for its text fields
+        if self.edit_modal.is_visible() {
+            match *input {
+                InputEvent::CursorMoved { x, y } => {
+                    self.mouse_pos = (x, y);
+                    self.edit_modal.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1);
+                }
+                InputEvent::MouseWheel { x, y, pixels } => {
+                    let scroll_amount = self.app_config.input.scroll_pixels(&InputEvent::scroll_delta(x, y, pixels));
+                    self.edit_modal.handle_mouse_wheel(self.mouse_pos.0, self.mouse_pos.1, scroll_amount);
+                }
+                InputEvent::MouseButton { middle: false, pressed: true } => {
+                    let click_count = self.click_tracker.press(self.mouse_pos.0, self.mouse_pos.1, time);
+                    self.edit_modal.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1, click_count);
+                }
+                InputEvent::MouseButton { middle: false, pressed: false } => {
+                    if let Some(edit_event) = self.edit_modal.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1) {
+                        self.handle_edit_event(edit_event);
+                    }
+                }
+                _ => {}
+            }
+            return true;
+        }
+        
+        match *input {
+            InputEvent::CursorMoved { x, y } => {
+                self.mouse_pos = (x, y);
+                
+                // Middle-button drags pan the list like grabbing the content
+                let moved = self.pointer.move_to(self.mouse_pos.0, self.mouse_pos.1);
+                if let Some((_, dy)) = moved.filter(|_| self.pointer.is_dragging(winit::event::MouseButton::Middle)) {
+                    self.todo_list_widget.pan_by(-dy);
+                    return true;
+                }
+                
+                // Forward to the sidebar, the view switcher and the view shown
+                self.sidebar.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1);
+                self.view_switcher.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1);
+                match self.view_switcher.mode() {
+                    ViewMode::List => self.todo_list_widget.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1),
+                    ViewMode::Board => self.board.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1),
+                    ViewMode::Timeline => self.timeline.handle_mouse_move(self.mouse_pos.0, self.mouse_pos.1),
+                }
+                true
+            },
+            InputEvent::MouseWheel { x, y, pixels } => {
+                // Lines vs. pixels, speed and direction all come from the [input] config
+                let scroll_amount = self.app_config.input.scroll_pixels(&InputEvent::scroll_delta(x, y, pixels));
+                
+                // Forward scroll to the view shown; the board scrolls the column under the pointer
+                // and the timeline zooms with Ctrl held
+                match self.view_switcher.mode() {
+                    ViewMode::List => self.todo_list_widget.handle_mouse_wheel(scroll_amount),
+                    ViewMode::Board => self.board.handle_mouse_wheel(self.mouse_pos.0, self.mouse_pos.1, scroll_amount),
+                    ViewMode::Timeline => self.timeline.handle_mouse_wheel(self.mouse_pos.0, scroll_amount, self.modifiers),
+                }
+                true
+            },
+            InputEvent::MouseButton { middle, pressed } => {
+                let button = InputEvent::button(middle);
+                match (middle, pressed) {
+                    (false, true) => {
+                        self.pointer.press(button, self.mouse_pos.0, self.mouse_pos.1);
+                        let click_count = self.click_tracker.press(self.mouse_pos.0, self.mouse_pos.1, time);
+                        
+                        if let Some(sidebar_event) = self.sidebar.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1) {
+                            self.handle_sidebar_event(sidebar_event);
+                            return true;
+                        }
+                        if self.view_switcher.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1).is_some() {
+                            self.board.cancel_drag();
+                            self.update_views();
+                            return true;
+                        }
+                        match self.view_switcher.mode() {
+                            ViewMode::List => {}
+                            ViewMode::Board => {
+                                self.board.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1);
+                                return true;
+                            }
+                            ViewMode::Timeline => {
+                                if let Some(id) = self.timeline.handle_mouse_down(self.mouse_pos.0, self.mouse_pos.1) {
+                                    self.edit_item(id);
+                                }
+                                return true;
+                            }
+                        }
+                        
+                        // Pass screen dimensions to handle expanded item modals correctly
+                        let (width, height) = self.logical_size();
+                        self.todo_list_widget.handle_mouse_down(
+                            self.mouse_pos.0, 
+                            self.mouse_pos.1, 
+                            click_count,
+                            width,
+                            height
+                        );
+                    },
+                    (false, false) => {
+                        self.pointer.release(button);
+                        if self.focus_ring.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1) {
+                            self.toggle_focus();
+                        }
+                        match self.view_switcher.mode() {
+                            ViewMode::List => self.todo_list_widget.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1),
+                            ViewMode::Board => {
+                                if let Some(board_event) = self.board.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1) {
+                                    self.handle_board_event(board_event);
+                                }
+                            }
+                            ViewMode::Timeline => {}
+                        }
+                    },
+                    (true, true) => self.pointer.press(button, self.mouse_pos.0, self.mouse_pos.1),
+                    (true, false) => {
+                        // A middle-button pan lets go with a fling
+                        if self.pointer.release(button) {
+                            self.todo_list_widget.end_pan();
+                        }
+                    }
+                }
+                true
+            },
+            // Only the list has a context menu
+            InputEvent::RightButton { pressed: true } if self.view_switcher.mode() != ViewMode::List => false,
+            InputEvent::RightButton { pressed: true } => {
+                let (width, height) = self.logical_size();
+                self.todo_list_widget.handle_right_click(self.mouse_pos.0, self.mouse_pos.1, width, height)
+            }
+            _ => false,
+        }
+    }
+
+    /// Show the cursor matching whatever is under the pointer
+    fn update_cursor(&mut self) {
+        let (x, y) = self.mouse_pos;
+        let (width, height) = self.logical_size();
+        let icon = if self.pointer.is_dragging(winit::event::MouseButton::Middle) || self.board.is_dragging() {
+            Some(CursorIcon::Grabbing)
+        } else if self.recovery_dialog.is_visible() {
+            self.recovery_dialog.cursor_at(x, y)
+        } else if self.settings_panel.is_visible() {
+            self.settings_panel.cursor_at(x, y)
+        } else if self.archive_view.is_visible() {
+            self.archive_view.cursor_at(x, y)
+        } else if self.edit_modal.is_visible() {
+            self.edit_modal.cursor_at(x, y)
+        } else {
+            self.todo_list_widget
+                .modal_cursor_at(x, y, width, height)
+                .or_else(|| self.sidebar.cursor_at(x, y))
+                .or_else(|| self.view_switcher.cursor_at(x, y))
+                .or_else(|| self.focus_ring.cursor_at(x, y))
+                .or_else(|| match self.view_switcher.mode() {
+                    ViewMode::List => self.todo_list_widget.cursor_at(x, y),
+                    ViewMode::Board => self.board.cursor_at(x, y),
+                    ViewMode::Timeline => self.timeline.cursor_at(x, y),
+                })
+        }
+        .unwrap_or(CursorIcon::Default);
+        
+        // Only talk to the window system when the cursor actually changes
+        if icon != self.cursor_icon {
+            self.cursor_icon = icon;
+            self.window.set_cursor_icon(icon);
+        }
+    }
+    
+    /// Handle an input event that happened at `time` seconds, returning true if it asks to quit
+    ///
+    /// Live and replayed input both come through here.
+    fn handle_input(&mut self, input: &InputEvent, time: f64) -> bool {
+        match input {
+            InputEvent::Focused(false) => {
+                // A click after switching back shouldn't pair up with one from before
+                self.click_tracker.reset();
+                
+                // Releases outside the window never arrive, so drop any drag in progress
+                self.pointer.reset();
+                self.buttons_held = 0;
+                self.board.cancel_drag();
+                self.key_repeat.reset();
+                self.modifiers = winit::keyboard::ModifiersState::empty();
+                self.todo_list_widget.set_modifiers(self.modifiers);
+                if self.todo_list_widget.is_scrolling() {
+                    self.todo_list_widget.handle_mouse_up(self.mouse_pos.0, self.mouse_pos.1);
+                }
+                
+                // Browsers give no reliable exit hook, so also save when focus leaves
+                self.save_in_background(false);
+            }
+            InputEvent::Focused(true) => {}
+            InputEvent::Text(event) => self.handle_text(event),
+            &InputEvent::Modifiers { shift, control, alt, logo } => {
+                self.modifiers = InputEvent::modifiers(shift, control, alt, logo);
+                self.todo_list_widget.set_modifiers(self.modifiers);
+            }
+            InputEvent::Key { key, pressed, repeat, text } => {
+                let key = key.to_key();
+                let code = named_keycode(&key);
+                
+                if !pressed {
+                    if let Some(code) = code {
+                        self.key_repeat.release(code);
+                    }
+                } else if *repeat && code.is_some_and(ui::input::is_repeatable) {
+                    // Our own KeyRepeat drives these; the OS repeats would double up
+                } else {
+                    info!("Key pressed: {:?}", key);
+                    if let Some(code) = code {
+                        self.key_repeat.press(code);
+                    }
+                    
+                    // Scripts and plugins bind chord names, everything else goes through the keymap
+                    let chord = key_name(&key)
+                        .map(|name| chord_name(self.modifiers, &name))
+                        .unwrap_or_default();
+                    let action = binding_keycode(&key).and_then(|code| self.keymap.action(self.modifiers, code));
+                    
+                    // The inspector, profiler and diagnostics can be toggled over anything, modals included
+                    if action == Some(Action::Inspector) {
+                        self.inspector.toggle();
+                    } else if action == Some(Action::Diagnostics) {
+                        self.diagnostics.toggle();
+                    } else if action == Some(Action::Profiler) {
+                        #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+                        self.profiler.toggle();
+                    // Open modals get keys (including ESC) before anything else
+                    } else if self.is_modal_open() {
+                        self.handle_keyboard_input(&key, text.as_deref(), action);
+                    // Then the configured global shortcuts, quitting first
+                    } else if action == Some(Action::Quit) {
+                        info!("{} pressed, exiting application", chord);
+                        return true;
+                    } else if action == Some(Action::Settings) {
+                        self.toggle_settings();
+                    } else if action == Some(Action::Save) {
+                        self.save_in_background(true);
+                    } else if !self.handle_shortcut(&chord, action) {
+                        // Handle other keyboard input in the UI
+                        self.handle_keyboard_input(&key, text.as_deref(), action);
+                    }
+                }
+            }
+            InputEvent::CursorMoved { .. } | InputEvent::MouseWheel { .. } | InputEvent::MouseButton { .. } | InputEvent::RightButton { .. } => {
+                match *input {
+                    InputEvent::MouseButton { pressed, .. } | InputEvent::RightButton { pressed } => {
+                        self.buttons_held = if pressed { self.buttons_held.saturating_add(1) } else { self.buttons_held.saturating_sub(1) };
+                    }
+                    // Widgets flag hover changes themselves; drags and the inspector follow every move
+                    InputEvent::CursorMoved { .. } if self.buttons_held > 0 || self.inspector.is_enabled() => ui::damage::invalidate(),
+                    _ => {}
+                }
+                self.handle_mouse_input(input, time);
+                self.update_cursor();
+            }
+        }
+        false
+    }
+    
+    /// Send text composed with an input method to the focused text field
+    fn handle_text(&mut self, event: &TextEvent) {
+        if self.edit_modal.is_visible() {
+            self.edit_modal.handle_text(event);
+        } else if !self.is_modal_open() && self.view_switcher.mode() == ViewMode::List {
+            self.todo_list_widget.handle_text(event);
+        }
+    }
+    
+    /// Enable the input method only while a text field has focus, so it
+    /// doesn't swallow shortcut keys, and show its candidates at the cursor
+    fn update_ime(&mut self) {
+        let caret = if self.edit_modal.is_visible() {
+            self.edit_modal.caret_bounds()
+        } else if self.is_modal_open() || self.view_switcher.mode() != ViewMode::List {
+            None
+        } else {
+            self.todo_list_widget.caret_bounds()
+        };
+        if caret == self.ime_caret {
+            return;
+        }
+        
+        let window = &self.window;
+        if caret.is_some() != self.ime_caret.is_some() {
+            window.set_ime_allowed(caret.is_some());
+        }
+        if let Some((x, y, width, height)) = caret {
+            window.set_ime_cursor_area(
+                winit::dpi::LogicalPosition::new(x, y),
+                winit::dpi::LogicalSize::new(width, height),
+            );
+        }
+        self.ime_caret = caret;
+    }
+    
+    /// `text` is what the key typed, if it typed anything
+    fn handle_keyboard_input(&mut self, key: &winit::keyboard::Key, text: Option<&str>, action: Option<Action>) -> bool {
+        // The settings panel's own shortcut closes it again
+        if self.settings_panel.is_visible() && action == Some(Action::Settings) {
+            self.settings_panel.hide();
+            return true;
+        }
+        // Likewise for the archive view
+        if self.archive_view.is_visible() && action == Some(Action::Archive) {
+            self.archive_view.hide();
+            return true;
+        }
+        
+        // Ctrl/Super chords without a binding shouldn't type their letter. Alt is
+        // left alone because AltGr, needed for many characters, reports as Ctrl+Alt.
+        let command_held = (self.modifiers.control_key() || self.modifiers.super_key()) && !self.modifiers.alt_key();
+        
+        // The task editor's fields follow the focus bindings, so Shift+Tab goes back
+        if self.edit_modal.is_visible() && matches!(action, Some(Action::FocusNext | Action::FocusPrevious)) {
+            self.edit_modal.cycle_focus(action == Some(Action::FocusPrevious));
+            return true;
+        }
+        
+        // Enter starts a new line in the description, so the add-and-continue chord saves from any field
+        if self.edit_modal.is_visible() && action == Some(Action::AddAndContinue) {
+            if let Some(edit_event) = self.edit_modal.save() {
+                self.handle_edit_event(edit_event);
+            }
+            return true;
+        }
+        
+        // ...and the text editing ones, for selecting, moving by word and the clipboard
+        if let Some(action) = action.filter(|_| self.edit_modal.is_visible()) {
+            if self.edit_modal.handle_action(action) || self.edit_modal.handle_clipboard(action, &mut self.clipboard) {
+                return true;
+            }
+        }
+        
+        // The task editor is the only modal that takes text
+        if self.edit_modal.is_visible() && !command_held {
+            if let (winit::keyboard::Key::Character(_), Some(text)) = (key, text) {
+                self.edit_modal.handle_text(&TextEvent::Insert(text.to_string()));
+            }
+        }
+        
+        // Open modals take all keys while shown
+        if self.is_modal_open() {
+            if let Some(code) = named_keycode(key) {
+                self.handle_key_code(code);
+            }
+            return true;
+        }
+        
+        // The list's fields and keys don't apply while another view is shown
+        if self.view_switcher.mode() != ViewMode::List {
+            return false;
+        }
+        
+        match key {
+            winit::keyboard::Key::Character(_) if command_held => false,
+            // Whole typed text, so composed characters and emoji come through too
+            winit::keyboard::Key::Character(_) => match text {
+                Some(text) => {
+                    self.todo_list_widget.handle_text(&TextEvent::Insert(text.to_string()));
+                    true
+                }
+                None => false,
+            },
+            winit::keyboard::Key::Named(key) => {
+                if let Some(code) = key_to_keycode(key) {
+                    self.handle_key_code(code);
+                    true
+                } else {
+                    false
+                }
+            },
+            _ => false,
+        }
+    }
+    
+    /// Run a plugin command on the selected task, showing failures as toasts
+    #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+    fn run_plugin_command(&mut self, command: &str) {
+        let selected = self.todo_list_widget.selected_item();
+        match self.plugins.run_command(command, selected) {
+            Ok(true) => self.todo_list_widget.refresh(),
+            Ok(false) => {}
+            Err(e) => {
+                error!("{}", e);
+                self.toasts.error(e);
+            }
+        }
+    }
+    
+    /// Run `init.rhai` if the user has one
+    #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+    fn run_startup_script(&mut self) {
+        let path = Config::script_path();
+        match storage::read_text(&path) {
+            Ok(Some(source)) => {
+                info!("Running {}", path.display());
+                let outcome = self.scripts.run(&source, self.todo_list_widget.selected_item());
+                self.apply_script_outcome(outcome);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!("{}", e);
+                self.toasts.error(e);
+            }
+        }
+    }
+    
+    /// Show what a script changed and surface its messages and errors as toasts
+    #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+    fn apply_script_outcome(&mut self, outcome: scripting::ScriptOutcome) {
+        match outcome.filter {
+            // Rebuilds the item widgets, so a separate refresh isn't needed
+            Some(filter) => self.todo_list_widget.set_custom_filter(filter),
+            None if outcome.changed => self.todo_list_widget.refresh(),
+            None => {}
+        }
+        for message in outcome.messages {
+            self.toasts.info(message);
+        }
+        if let Some(e) = outcome.error {
+            error!("{}", e);
+            self.toasts.error(e);
+        }
+    }
+    
+    /// Run what a key chord is bound to, returning whether it did anything
+    ///
+    /// Plugin and script bindings come first, then the keymap's `action`.
+    /// Global actions (quit, save, settings, inspector) are handled by the event loop itself.
+    fn handle_shortcut(&mut self, chord: &str, action: Option<Action>) -> bool {
+        let _span = trace_span!("shortcut", chord).entered();
+        if self.is_modal_open() {
+            return false;
+        }
+        
+        #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+        if let Some(command) = self.app_config.plugin_command_for(chord).map(str::to_string) {
+            self.run_plugin_command(&command);
+            return true;
+        }
+        
+        #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+        if let Some(outcome) = self.scripts.run_binding(chord, self.todo_list_widget.selected_item()) {
+            self.apply_script_outcome(outcome);
+            return true;
+        }
+        
+        match action {
+            Some(Action::Undo) => self.undo(),
+            Some(Action::Redo) => self.redo(),
+            Some(Action::CyclePresentMode) => self.cycle_present_mode(),
+            Some(Action::Sync) => self.start_sync(true),
+            Some(Action::Archive) => self.toggle_archive(),
+            Some(Action::FocusTimer) => self.toggle_focus(),
+            Some(Action::StopFocusTimer) => self.stop_focus(),
+            Some(Action::ToggleSidebar) => {
+                let collapsed = !self.sidebar.is_collapsed();
+                self.sidebar.set_collapsed(collapsed);
+                self.place_list();
+            }
+            Some(_) if self.view_switcher.mode() != ViewMode::List => return false,
+            Some(action @ (Action::Copy | Action::Cut | Action::Paste)) => {
+                return self.todo_list_widget.handle_clipboard(action, &mut self.clipboard);
+            }
+            // The list's own actions don't apply while one of its text fields is focused
+            Some(action) => return self.todo_list_widget.handle_action(action),
+            None => return false,
+        }
+        true
+    }
+    
+    /// Route a non-character key to whatever currently has keyboard focus
+    ///
+    /// Also used for key repeats, which have no winit event of their own.
+    fn handle_key_code(&mut self, code: winit::keyboard::KeyCode) {
+        if self.recovery_dialog.is_visible() {
+            if let Some(choice) = self.recovery_dialog.handle_key_press(code) {
+                self.resolve_recovery(choice);
+            }
+        } else if self.settings_panel.is_visible() {
+            if let Some(settings_event) = self.settings_panel.handle_key_press(code) {
+                self.handle_settings_event(settings_event);
+            }
+        } else if self.archive_view.is_visible() {
+            if let Some(archive_event) = self.archive_view.handle_key_press(code) {
+                self.handle_archive_event(archive_event);
+            }
+        } else if self.edit_modal.is_visible() {
+            if let Some(edit_event) = self.edit_modal.handle_key_press(code) {
+                self.handle_edit_event(edit_event);
+            }
+        } else if self.view_switcher.mode() == ViewMode::List {
+            self.todo_list_widget.handle_key_press(code);
+        }
+    }
+    
+    /// Whether a modal that takes all keyboard input is shown, the list's context menu included
+    fn is_modal_open(&self) -> bool {
+        self.recovery_dialog.is_visible()
+            || self.settings_panel.is_visible()
+            || self.archive_view.is_visible()
+            || self.edit_modal.is_visible()
+            || self.todo_list_widget.is_menu_open()
+    }
+    
+    /// Show the task editor's changes in the list
+    fn handle_edit_event(&mut self, event: EditTaskEvent) {
+        match event {
+            EditTaskEvent::Saved(id) => {
+                info!("Saved changes to item {}", id);
+                self.todo_list_widget.refresh();
+            }
+            EditTaskEvent::Cancelled => {}
+            EditTaskEvent::PickFiles(id) => self.pick_files(id),
+        }
+    }
+}
+
+/// Hand an address or file to the system to open, with the app it picks
+#[cfg(not(target_arch = "wasm32"))]
+fn open_target(target: &str) -> Result<(), String> {
+    open::that_detached(target).map_err(|e| e.to_string())
+}
+
+/// Open an address in a new tab
+#[cfg(target_arch = "wasm32")]
+fn open_target(target: &str) -> Result<(), String> {
+    web_sys::window()
+        .and_then(|window| window.open_with_url_and_target(target, "_blank").ok().flatten())
+        .map(|_| ())
+        .ok_or_else(|| "The browser didn't open a tab for it".to_string())
+}
+
+/// The problem to report when the surface can't present the way the config asks
+fn unsupported_present_mode(requested: PresentMode, used: wgpu::PresentMode) -> Option<String> {
+    (requested != PresentMode::Fifo && used == wgpu::PresentMode::Fifo)
+        .then(|| i18n::tr_args("present-mode-unsupported", &[("mode", &ui::present_mode_name(requested))]))
+}
+
+/// Environment variable naming the Chrome trace file to write
+#[cfg(all(feature = "chrome-trace", not(target_arch = "wasm32")))]
+const TRACE_ENV: &str = "TEWDUWU_TRACE";
+
+/// Flushes the Chrome trace when dropped at the end of main
+#[cfg(all(feature = "chrome-trace", not(target_arch = "wasm32")))]
+type TraceGuard = Option<tracing_chrome::FlushGuard>;
+#[cfg(not(all(feature = "chrome-trace", not(target_arch = "wasm32"))))]
+type TraceGuard = ();
+
+/// Set up logging for the current platform
+///
+/// Natively, log messages and the per-frame spans (update, layout, the render
+/// passes, present) go through `tracing`. With the `chrome-trace` feature and
+/// TEWDUWU_TRACE=<file> set, every span is also written to a Chrome trace that
+/// chrome://tracing or Perfetto can open.
+fn init_logging() -> TraceGuard {
+    #[cfg(target_arch = "wasm32")]
+    {
+        console_error_panic_hook::set_once();
+        console_log::init_with_level(log::Level::Info).expect("Failed to initialize console logger");
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use tracing_subscriber::prelude::*;
+        
+        // Use RUST_LOG=debug if you want to see all logs
+        let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+        let fmt = tracing_subscriber::fmt::layer().with_filter(filter);
+        
+        #[cfg(feature = "chrome-trace")]
+        {
+            let (chrome, guard) = match std::env::var_os(TRACE_ENV) {
+                Some(path) => {
+                    let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new().file(path).build();
+                    (Some(layer), Some(guard))
+                }
+                None => (None, None),
+            };
+            tracing_subscriber::registry().with(fmt).with(chrome).init();
+            guard
+        }
+        #[cfg(not(feature = "chrome-trace"))]
+        tracing_subscriber::registry().with(fmt).init();
+    }
+}
+
+/// Create the State for a freshly built window.
+///
+/// On native this blocks until the GPU is ready. In the browser we can't block,
+/// so initialization is spawned and the finished State arrives as a user event;
+/// a failure there is reported from the spawned task.
+fn init_state(window: Arc<Window>, proxy: EventLoopProxy<AppEvent>) -> Result<Option<State>, AppError> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        wasm_bindgen_futures::spawn_local(async move {
+            match State::new(window).await {
+                Ok(state) => {
+                    if proxy.send_event(AppEvent::Initialized(Box::new(state))).is_err() {
+                        error!("Event loop closed before initialization finished");
+                    }
+                }
+                Err(e) => e.report(),
+            }
+        });
+        Ok(None)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let state = pollster::block_on(State::new(window))?;
+        #[cfg(feature = "hotkey")]
+        let state = state.with_hotkey(proxy.clone());
+        #[cfg(feature = "tray")]
+        let state = state.with_tray(proxy);
+        #[cfg(not(feature = "tray"))]
+        let _ = proxy;
+        Ok(Some(state))
+    }
+}
+
+fn main() {
+    // A subcommand runs without a window, and without log lines between its output
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let cli = cli::Cli::parse();
+        if let Some(command) = cli.command {
+            let (app_config, _) = Config::load();
+            let result = cli::run(cli.list.as_deref(), command, &app_config.data_path(), &mut std::io::stdout());
+            if let Err(e) = result {
+                eprintln!("tewduwu: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
+
+    // A unit value unless the chrome-trace feature is on
+    #[allow(clippy::let_unit_value)]
+    let _trace = init_logging();
+    #[cfg(not(target_arch = "wasm32"))]
+    crash::install();
+    profiling::enable();
+
+    info!("Initializing tewduwu-neon (Rust)");
+
+    // If another instance is already running, bring it to the front and exit
+    // so two processes never fight over the data file
+    #[cfg(not(target_arch = "wasm32"))]
+    if ipc::try_handoff(&ipc::IpcMessage::Focus).is_some() {
+        return;
+    }
+
+    // 1. Create Event Loop and Window Builder
+    let event_loop = match EventLoopBuilder::<AppEvent>::with_user_event().build() {
+        Ok(event_loop) => event_loop,
+        Err(e) => {
+            AppError::Window(e.to_string()).report();
+            return;
+        }
+    };
+    let proxy = event_loop.create_proxy();
+    
+    // We're the primary instance: listen for later launches
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let ipc_proxy = event_loop.create_proxy();
+        match ipc::start_server(move |message, reply| {
+            let _ = ipc_proxy.send_event(AppEvent::Ipc(message, reply));
+        }) {
+            Ok(true) => {}
+            // Another launch got there first
+            Ok(false) => {
+                ipc::try_handoff(&ipc::IpcMessage::Focus);
+                return;
+            }
+            Err(e) => error!("Single-instance server unavailable: {}", e),
+        }
+    }
+    let window_builder = WindowBuilder::new() // Store builder, not window yet
+        .with_title("tewduwu-neon (Rust)")
+        .with_inner_size(winit::dpi::LogicalSize::new(1280, 720));
+    
+    // In the browser, let winit create a canvas and append it to the page body
+    #[cfg(target_arch = "wasm32")]
+    let window_builder = window_builder.with_append(true);
+
+    // Initialize state outside the loop closure
+    let mut state_option: Option<State> = None;
+    let mut window_created = false;
+
+    // Input recording and replay, if asked for by the environment
+    #[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+    let mut input_log = recording::InputLog::from_env().unwrap_or_else(|e| {
+        error!("{}", e);
+        recording::InputLog::default()
+    });
+
+    info!("Entering event loop...");
 
     // 4. Main Event Loop
     // Closure takes event and event_loop_target
-    event_loop.run(move |event, event_loop_target| {
+    let event_handler = move |event: Event<AppEvent>, event_loop_target: &EventLoopWindowTarget<AppEvent>| {
         match event {
-            Event::Resumed => {
-                if state_option.is_none() {
-                    // Clone the window_builder before building to avoid ownership issues
-                    let window_arc = Arc::new(window_builder.clone().build(event_loop_target).expect("Failed to build window"));
-                    info!("Window created successfully on Resumed event");
-                    // Now that window is created, create the state
-                    state_option = Some(pollster::block_on(State::new(window_arc.clone())));
-                    info!("WGPU Initialized successfully on Resumed event.");
+            Event::Resumed if !window_created => {
+                window_created = true;
+                // Clone the window_builder before building to avoid ownership issues
+                let started = window_builder.clone()
+                    .build(event_loop_target)
+                    .map_err(|e| AppError::Window(e.to_string()))
+                    .and_then(|window| {
+                        info!("Window created successfully on Resumed event");
+                        // Now that window is created, create the state
+                        init_state(Arc::new(window), proxy.clone())
+                    });
+                match started {
+                    Ok(state) => state_option = state,
+                    Err(e) => {
+                        e.report();
+                        event_loop_target.exit();
+                    }
+                }
+            }
+            #[cfg(target_arch = "wasm32")]
+            Event::UserEvent(AppEvent::Initialized(state)) => {
+                info!("WGPU Initialized asynchronously.");
+                state.window.request_redraw();
+                state_option = Some(*state);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Event::UserEvent(AppEvent::Ipc(message, reply)) => {
+                // Without a window yet, dropping the reply tells the sender nothing was done
+                if let Some(state) = state_option.as_mut() {
+                    info!("Received {:?} from another instance", message);
+                    state.frame_pacer.request_redraw();
+                    match message {
+                        ipc::IpcMessage::Focus => state.focus_window(),
+                        ipc::IpcMessage::Execute { list, change } => {
+                            let _ = reply.send(state.execute_cli_change(list.as_deref(), change));
+                        }
+                    }
+                }
+            }
+            #[cfg(all(feature = "hotkey", not(target_arch = "wasm32")))]
+            Event::UserEvent(AppEvent::QuickAddHotkey) => {
+                if let Some(state) = state_option.as_mut() {
+                    info!("Quick add hotkey pressed");
+                    state.handle_quick_add_hotkey(event_loop_target);
+                }
+            }
+            #[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+            Event::UserEvent(AppEvent::Tray(command)) => {
+                if let Some(state) = state_option.as_mut() {
+                    info!("Tray picked {:?}", command);
+                    state.handle_tray_command(command, event_loop_target);
                 }
             }
             Event::WindowEvent { event, window_id } => {
                 if let Some(state) = state_option.as_mut() { 
-                    if window_id == state.window_wrapper.window().id() {
+                    #[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+                    if state.is_quick_add_window(window_id) {
+                        state.handle_quick_add_event(event);
+                        return;
+                    }
+                    if window_id == state.window.id() {
+                        // Anything but a redraw may change what's shown; pointer moves
+                        // only do when they damage the frame
+                        if !matches!(event, WindowEvent::RedrawRequested | WindowEvent::CursorMoved { .. }) {
+                            state.frame_pacer.request_redraw();
+                        }
                         match event {
                             WindowEvent::CloseRequested => {
                                 info!("Close requested");
+                                state.save();
+                                state.save_session();
                                 event_loop_target.exit();
                             }
                             WindowEvent::Resized(physical_size) => {
                                 info!("Window resized to: {:?}", physical_size);
                                 state.resize(physical_size);
-                                
-                                // Update UI components with new size
-                                state.todo_list_widget.set_dimensions(
-                                    physical_size.width as f32 - 100.0,
-                                    physical_size.height as f32 - 200.0
-                                );
                             }
-                            WindowEvent::ScaleFactorChanged { .. } => {
-                                info!("Scale factor changed.");
-                                state.window_wrapper.window().request_redraw(); 
+                            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                                info!("Scale factor changed to {}", scale_factor);
+                                state.set_scale_factor(scale_factor);
+                                state.window.request_redraw(); 
                             }
-                            WindowEvent::KeyboardInput { event: key_event, .. } => {
-                                if key_event.state == ElementState::Pressed {
-                                    info!("Key pressed: {:?}", key_event.logical_key);
-                                    
-                                    // Check for ESC to exit first - highest priority
-                                    if let winit::keyboard::Key::Named(winit::keyboard::NamedKey::Escape) = key_event.logical_key {
-                                        info!("Escape key pressed, exiting application");
+                            
+                            WindowEvent::RedrawRequested => {
+                                let _frame = trace_span!("frame").entered();
+                                
+                                // A replay feeds its input right before the update it happened before
+                                #[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+                                for timed in input_log.replay_frame() {
+                                    if state.handle_input(&timed.input, timed.time) {
+                                        state.save();
+                                        state.save_session();
                                         event_loop_target.exit();
-                                    } else {
-                                        // Handle other keyboard input in the UI
-                                        state.handle_keyboard_input(&key_event);
+                                        return;
                                     }
                                 }
-                            }
-                            
-                            // Handle mouse input
-                            WindowEvent::CursorMoved { .. } |
-                            WindowEvent::MouseWheel { .. } |
-                            WindowEvent::MouseInput { .. } => {
-                                state.handle_mouse_input(&event);
-                            }
-                            
-                            WindowEvent::RedrawRequested => {
-                                state.update(0.016); // Assume ~60fps for now
+                                
+                                let frame_start = ui::input::now_secs();
+                                let delta_time = state.clock.tick(frame_start);
+                                // A replay steps every frame evenly so it takes the recorded path
+                                #[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+                                let delta_time = if input_log.is_replaying() { recording::FRAME_STEP } else { delta_time };
+                                state.update(delta_time);
                                 match state.render() {
                                     Ok(_) => {}
                                     Err(wgpu::SurfaceError::Lost) => state.resize(state.size),
                                     Err(wgpu::SurfaceError::OutOfMemory) => event_loop_target.exit(),
                                     Err(e) => error!("Render error: {:?}", e),
                                 }
+                                let drawn_at = ui::input::now_secs();
+                                state.diagnostics.record_cpu_time(((drawn_at - frame_start) * 1000.0) as f32);
+                                state.frame_pacer.frame_drawn(drawn_at);
+                                state.fps_counter.frame_drawn(drawn_at);
+                                profiling::new_frame();
+                                #[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+                                input_log.end_frame();
+                            }
+                            
+                            // Keyboard, mouse and focus changes
+                            event => {
+                                let Some(input) = InputEvent::from_window_event(&event, state.scale_factor) else {
+                                    return;
+                                };
+                                let time = ui::input::now_secs();
+                                #[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+                                {
+                                    if input_log.is_replaying() {
+                                        return;
+                                    }
+                                    input_log.record(&input, time);
+                                }
+                                if state.handle_input(&input, time) {
+                                    state.save();
+                                    state.save_session();
+                                    event_loop_target.exit();
+                                }
                             }
-                            _ => {}
                         }
                     }
                 }
             }
             Event::LoopExiting => { // Handle cleanup if needed
                 info!("Exiting event loop.");
+                #[cfg(not(target_arch = "wasm32"))]
+                ipc::shutdown();
             }
             Event::AboutToWait => {
                  if let Some(state) = state_option.as_mut() { 
                     state.staging_belt.recall();
-                    state.window_wrapper.window().request_redraw();
+                    state.poll_background();
+                    if ui::damage::take() {
+                        state.frame_pacer.request_redraw();
+                    }
+                    
+                    let animating = state.is_animating();
+                    // A replay feeds its input frame by frame
+                    #[cfg(all(feature = "input-recording", not(target_arch = "wasm32")))]
+                    let animating = animating || input_log.is_replaying();
+                    match state.frame_pacer.next(animating, ui::input::now_secs()) {
+                        Pacing::Redraw => {
+                            event_loop_target.set_control_flow(ControlFlow::Wait);
+                            state.window.request_redraw();
+                        }
+                        Pacing::RedrawIn(secs) => {
+                            event_loop_target.set_control_flow(ControlFlow::wait_duration(Duration::from_secs_f64(secs)));
+                        }
+                        // Nothing to draw, but timers still count down and saves finish
+                        Pacing::Idle if state.has_pending_work() => {
+                            let delta_time = state.clock.tick(ui::input::now_secs());
+                            state.update(delta_time);
+                            event_loop_target.set_control_flow(ControlFlow::wait_duration(PENDING_WORK_INTERVAL));
+                        }
+                        Pacing::Idle => {
+                            event_loop_target.set_control_flow(ControlFlow::wait_duration(BACKGROUND_POLL_INTERVAL));
+                        }
+                    }
                  }
             }
             _ => {}
         }
-    })
-    .expect("Event loop error");
+    };
+
+    // On the web the browser owns the loop, so we hand the handler over and return
+    #[cfg(target_arch = "wasm32")]
+    event_loop.spawn(event_handler);
+    #[cfg(not(target_arch = "wasm32"))]
+    event_loop.run(event_handler).expect("Event loop error");
 }
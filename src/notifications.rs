@@ -0,0 +1,207 @@
+// Due-date reminders
+//
+// The scheduler looks over the lists now and then and says which tasks are
+// coming due or have just become overdue; main turns those into desktop
+// notifications (notifications feature) or toasts. Each reminder is given
+// once per due date, so moving a task's due date, e.g. by snoozing it, arms
+// its reminders again.
+
+use std::collections::HashSet;
+use uuid::Uuid;
+#[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+use tracing::error;
+
+use crate::core::prelude::{Reminder, TodoList};
+
+/// How long after its due date a task is still reminded of as overdue, so
+/// starting the app doesn't bring up every task left undone for weeks
+const OVERDUE_REMINDER_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Which reminder a task is due
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoticeKind {
+    /// Within its reminder lead time
+    Upcoming,
+    Overdue,
+}
+
+/// A reminder to show for a task
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub id: Uuid,
+    pub title: String,
+    pub due: u64,
+    pub kind: NoticeKind,
+}
+
+/// Decides which reminders are due, remembering the ones already given
+#[derive(Debug, Default)]
+pub struct ReminderScheduler {
+    /// Reminders given, by task and the due date they were given for
+    sent: HashSet<(Uuid, u64, NoticeKind)>,
+}
+
+impl ReminderScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reminders due at `now` for the open tasks of `list`
+    ///
+    /// `lead_minutes` is how far ahead to remind of tasks that don't set their
+    /// own lead time. A task that became overdue since the last check only gets
+    /// the overdue reminder.
+    pub fn check(&mut self, list: &TodoList, now: u64, lead_minutes: u32) -> Vec<Notice> {
+        let mut notices = Vec::new();
+        for item in list.all_items() {
+            let Some(due) = item.due_date() else {
+                continue;
+            };
+            let lead = match item.reminder() {
+                _ if item.is_completed() => continue,
+                Reminder::Off => continue,
+                Reminder::Default => lead_minutes,
+                Reminder::MinutesBefore(minutes) => minutes,
+            };
+            let kind = if now >= due + OVERDUE_REMINDER_WINDOW_SECS {
+                continue;
+            } else if now >= due {
+                NoticeKind::Overdue
+            } else if now + u64::from(lead) * 60 >= due {
+                NoticeKind::Upcoming
+            } else {
+                continue;
+            };
+            if self.sent.insert((item.id(), due, kind)) {
+                notices.push(Notice { id: item.id(), title: item.title().to_string(), due, kind });
+            }
+        }
+        notices
+    }
+
+    /// A task was snoozed to `due`; it's reminded of again once that passes
+    pub fn snoozed(&mut self, id: Uuid, due: u64) {
+        self.sent.insert((id, due, NoticeKind::Upcoming));
+    }
+}
+
+/// The due date snoozing a task due at `due` gives it
+///
+/// Counts from now for a task that's already overdue.
+pub fn snoozed_due(due: u64, now: u64, minutes: u32) -> u64 {
+    due.max(now) + u64::from(minutes) * 60
+}
+
+/// Show `text` as a desktop notification (notifications feature)
+///
+/// Showing one and waiting for its buttons block, so it happens on a thread
+/// of its own. Where notifications can have buttons (the freedesktop ones on
+/// Linux and the BSDs), a button labelled `snooze_label` calls `on_snooze`.
+#[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+pub fn show_desktop(text: String, snooze_label: String, on_snooze: impl FnOnce() + Send + 'static) {
+    std::thread::spawn(move || {
+        let mut notification = notify_rust::Notification::new();
+        notification.appname("tewduwu").summary(&text);
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            notification.action("snooze", &snooze_label);
+            match notification.show() {
+                Ok(handle) => handle.wait_for_action(|action| {
+                    if action == "snooze" {
+                        on_snooze();
+                    }
+                }),
+                Err(e) => error!("Failed to show a notification: {}", e),
+            }
+        }
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        {
+            let _ = (snooze_label, on_snooze);
+            if let Err(e) = notification.show() {
+                error!("Failed to show a notification: {}", e);
+            }
+        }
+    });
+}
+
+/// Show `text` as a desktop notification without buttons, e.g. when a focus session ends (notifications feature)
+#[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+pub fn show_desktop_message(text: String) {
+    std::thread::spawn(move || {
+        if let Err(e) = notify_rust::Notification::new().appname("tewduwu").summary(&text).show() {
+            error!("Failed to show a notification: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::{Status, TodoItem};
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn list_with(items: Vec<TodoItem>) -> TodoList {
+        let mut list = TodoList::new("Reminders");
+        for item in items {
+            list.add_item(item);
+        }
+        list
+    }
+
+    #[test]
+    fn test_reminds_once_before_and_once_when_overdue() {
+        let list = list_with(vec![TodoItem::new("Call back").with_due_date(NOW + 10 * 60)]);
+        let mut scheduler = ReminderScheduler::new();
+
+        assert!(scheduler.check(&list, NOW - 60 * 60, 15).is_empty());
+        let notices = scheduler.check(&list, NOW, 15);
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].kind, NoticeKind::Upcoming);
+        assert_eq!(notices[0].title, "Call back");
+        assert!(scheduler.check(&list, NOW + 60, 15).is_empty());
+
+        let notices = scheduler.check(&list, NOW + 10 * 60, 15);
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].kind, NoticeKind::Overdue);
+        assert!(scheduler.check(&list, NOW + 20 * 60, 15).is_empty());
+
+        // Long overdue tasks are left alone
+        assert!(ReminderScheduler::new().check(&list, NOW + 2 * 24 * 60 * 60, 15).is_empty());
+    }
+
+    #[test]
+    fn test_task_reminder_settings() {
+        let mut done = TodoItem::new("Done").with_due_date(NOW);
+        done.set_status(Status::Completed);
+        let list = list_with(vec![
+            TodoItem::new("Muted").with_due_date(NOW).with_reminder(Reminder::Off),
+            TodoItem::new("Early").with_due_date(NOW + 60 * 60).with_reminder(Reminder::MinutesBefore(90)),
+            TodoItem::new("Late").with_due_date(NOW + 60 * 60).with_reminder(Reminder::MinutesBefore(0)),
+            TodoItem::new("Undated"),
+            done,
+        ]);
+        let notices = ReminderScheduler::new().check(&list, NOW, 15);
+        let titles: Vec<&str> = notices.iter().map(|notice| notice.title.as_str()).collect();
+        assert_eq!(titles, vec!["Early"]);
+    }
+
+    #[test]
+    fn test_snoozing_rearms_the_reminder() {
+        let mut list = list_with(vec![TodoItem::new("Stretch").with_due_date(NOW)]);
+        let id = list.root_item_ids()[0];
+        let mut scheduler = ReminderScheduler::new();
+        assert_eq!(scheduler.check(&list, NOW, 15)[0].kind, NoticeKind::Overdue);
+
+        let due = snoozed_due(NOW, NOW + 60, 10);
+        assert_eq!(due, NOW + 60 + 10 * 60);
+        list.get_item_mut(id).unwrap().set_due_date(Some(due));
+        scheduler.snoozed(id, due);
+
+        // Not straight away, even though it's within the lead time
+        assert!(scheduler.check(&list, NOW + 120, 15).is_empty());
+        let notices = scheduler.check(&list, due, 15);
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].kind, NoticeKind::Overdue);
+    }
+}
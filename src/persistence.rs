@@ -0,0 +1,423 @@
+// Background persistence
+//
+// Saving and journaling a big workspace means serializing it and writing a file,
+// which can take long enough to drop frames. A dedicated worker thread does
+// both instead. The main thread hands it a copy of the workspace over a small
+// bounded channel and never waits on it. The worker coalesces requests: a newer
+// save replaces an older one that wasn't written yet, and it writes at most
+// once per interval unless a write was asked for explicitly. Results come back
+// as `TaskEvent`s, so failures are reported on the main thread like any other
+// background task.
+//
+// The browser has no threads to spare; there requests are written right away.
+
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::JoinHandle;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+use tracing::{error, info};
+
+use crate::core::journal::Journal;
+use crate::core::prelude::Workspace;
+use crate::core::storage;
+use crate::tasks::TaskEvent;
+
+/// Shortest time between two background writes
+pub const MIN_WRITE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Requests the main thread can queue before they are merged locally
+#[cfg(not(target_arch = "wasm32"))]
+const CHANNEL_CAPACITY: usize = 4;
+
+/// Work for the save worker
+#[derive(Debug)]
+pub enum SaveRequest {
+    /// Write the workspace to the data file; `announce` asks for a toast and skips the wait
+    ///
+    /// A successful save makes the journal obsolete, unless `keep_journal` is
+    /// set because it still holds changes the user hasn't decided about.
+    Save { workspace: Workspace, announce: bool, keep_journal: bool },
+    /// Snapshot unsaved changes to the recovery journal
+    Snapshot(Workspace),
+    /// Remove the recovery journal
+    ClearJournal,
+    /// Write everything outstanding and stop
+    Shutdown,
+}
+
+/// Requests not written yet, with the superseded ones already dropped
+#[derive(Debug, Default)]
+struct Pending {
+    save: Option<(Workspace, bool, bool)>,
+    snapshot: Option<Workspace>,
+    clear_journal: bool,
+    shutdown: bool,
+}
+
+impl Pending {
+    fn merge(&mut self, request: SaveRequest) {
+        match request {
+            SaveRequest::Save { workspace, announce, keep_journal } => {
+                let announce = announce || self.save.as_ref().is_some_and(|(_, announce, _)| *announce);
+                self.save = Some((workspace, announce, keep_journal));
+                // The save holds the same changes or newer ones
+                self.snapshot = None;
+            }
+            SaveRequest::Snapshot(workspace) => self.snapshot = Some(workspace),
+            SaveRequest::ClearJournal => {
+                self.clear_journal = true;
+                self.snapshot = None;
+            }
+            SaveRequest::Shutdown => self.shutdown = true,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.save.is_none() && self.snapshot.is_none() && !self.clear_journal && !self.shutdown
+    }
+
+    /// Whether to write without waiting for the interval
+    fn is_urgent(&self) -> bool {
+        self.shutdown || self.save.as_ref().is_some_and(|(_, announce, _)| *announce)
+    }
+
+    /// The remaining requests, in the order they must be carried out
+    #[cfg(not(target_arch = "wasm32"))]
+    fn into_requests(self) -> Vec<SaveRequest> {
+        let mut requests = Vec::new();
+        if let Some((workspace, announce, keep_journal)) = self.save {
+            requests.push(SaveRequest::Save { workspace, announce, keep_journal });
+        }
+        if self.clear_journal {
+            requests.push(SaveRequest::ClearJournal);
+        }
+        if let Some(workspace) = self.snapshot {
+            requests.push(SaveRequest::Snapshot(workspace));
+        }
+        if self.shutdown {
+            requests.push(SaveRequest::Shutdown);
+        }
+        requests
+    }
+}
+
+/// Carries out requests: owns the data path and the journal
+struct Writer {
+    data_path: PathBuf,
+    journal: Journal,
+    events: Sender<TaskEvent>,
+
+    /// Whether the last journal write worked, so a failing disk is reported once
+    journal_ok: bool,
+}
+
+impl Writer {
+    fn write(&mut self, pending: Pending) {
+        if let Some((workspace, announce, keep_journal)) = pending.save {
+            let written = storage::workspace_to_json(&workspace)
+                .and_then(|json| storage::write_text(&self.data_path, &json).map(|()| json));
+            let result = match written {
+                Ok(json) => {
+                    info!("Saved {} items in {} lists", workspace.item_count(), workspace.len());
+                    if !keep_journal {
+                        self.journal.mark_saved_json(json);
+                        let cleared = self.journal.clear();
+                        self.journal_written(cleared);
+                    }
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            };
+            let _ = self.events.send(TaskEvent::Saved { announce, result });
+        }
+        if pending.clear_journal {
+            let cleared = self.journal.clear();
+            self.journal_written(cleared);
+        }
+        if let Some(workspace) = pending.snapshot {
+            let written = self.journal.snapshot(&workspace).map(|_| ());
+            self.journal_written(written);
+        }
+    }
+
+    fn journal_written(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.journal_ok = true,
+            Err(e) => {
+                error!("{}", e);
+                if std::mem::replace(&mut self.journal_ok, false) {
+                    let _ = self.events.send(TaskEvent::JournalFailed { error: e });
+                }
+            }
+        }
+    }
+
+    /// Take requests until shut down, writing at most once per `min_interval`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run(mut self, requests: mpsc::Receiver<SaveRequest>, min_interval: Duration) {
+        let mut pending = Pending::default();
+        let mut last_write: Option<Instant> = None;
+        loop {
+            let request = if pending.is_empty() {
+                requests.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                let wait = match last_write {
+                    Some(time) if !pending.is_urgent() => min_interval.saturating_sub(time.elapsed()),
+                    _ => Duration::ZERO,
+                };
+                requests.recv_timeout(wait)
+            };
+            match request {
+                Ok(request) => {
+                    pending.merge(request);
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // The app is gone; write what's left
+                Err(RecvTimeoutError::Disconnected) => pending.shutdown = true,
+            }
+
+            let shutdown = pending.shutdown;
+            self.write(std::mem::take(&mut pending));
+            last_write = Some(Instant::now());
+            if shutdown {
+                break;
+            }
+        }
+    }
+}
+
+/// Handle to the thread that writes the workspace and its journal
+pub struct SaveWorker {
+    #[cfg(not(target_arch = "wasm32"))]
+    requests: SyncSender<SaveRequest>,
+    #[cfg(not(target_arch = "wasm32"))]
+    thread: Option<JoinHandle<()>>,
+    #[cfg(target_arch = "wasm32")]
+    writer: Writer,
+
+    /// Requests that didn't fit in the channel, merged until there is room
+    #[cfg(not(target_arch = "wasm32"))]
+    backlog: Pending,
+}
+
+impl SaveWorker {
+    /// Start writing to `data_path` and `journal`, reporting results to `events`
+    ///
+    /// Recover the journal before handing it over; from here on only the
+    /// worker touches it.
+    pub fn spawn(data_path: PathBuf, journal: Journal, events: Sender<TaskEvent>, min_interval: Duration) -> Result<Self, String> {
+        let writer = Writer { data_path, journal, events, journal_ok: true };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (requests, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+            let thread = std::thread::Builder::new()
+                .name("tewduwu-save".to_string())
+                .spawn(move || writer.run(receiver, min_interval))
+                .map_err(|e| format!("Failed to start save worker: {}", e))?;
+            Ok(Self { requests, thread: Some(thread), backlog: Pending::default() })
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = min_interval;
+            Ok(Self { writer })
+        }
+    }
+
+    /// Queue a request without blocking
+    pub fn send(&mut self, request: SaveRequest) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.backlog.merge(request);
+            self.flush_backlog();
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let mut pending = Pending::default();
+            pending.merge(request);
+            self.writer.write(pending);
+        }
+    }
+
+    /// Hand requests that didn't fit earlier to the worker; call once per frame
+    pub fn tick(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.flush_backlog();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn flush_backlog(&mut self) {
+        if self.backlog.is_empty() {
+            return;
+        }
+        let mut requests = std::mem::take(&mut self.backlog).into_requests().into_iter();
+        while let Some(request) = requests.next() {
+            match self.requests.try_send(request) {
+                Ok(()) => {}
+                Err(TrySendError::Full(request)) => {
+                    // Keep the rest in order for the next try
+                    self.backlog.merge(request);
+                    requests.for_each(|request| self.backlog.merge(request));
+                    return;
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    error!("Save worker stopped; changes can no longer be saved");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Write everything outstanding and stop the worker, waiting at most `timeout`
+    ///
+    /// Results are reported as usual; poll the tasks' events afterwards.
+    pub fn shutdown(&mut self, timeout: Duration) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(thread) = self.thread.take() else {
+                return;
+            };
+            let deadline = Instant::now() + timeout;
+            self.backlog.merge(SaveRequest::Shutdown);
+            for request in std::mem::take(&mut self.backlog).into_requests() {
+                // Blocking is fine now, but only until the deadline
+                let mut request = request;
+                loop {
+                    match self.requests.try_send(request) {
+                        Ok(()) => break,
+                        Err(TrySendError::Full(returned)) if Instant::now() < deadline => {
+                            request = returned;
+                            std::thread::sleep(Duration::from_millis(5));
+                        }
+                        Err(_) => {
+                            error!("Save worker didn't take the final save in time");
+                            return;
+                        }
+                    }
+                }
+            }
+            while !thread.is_finished() {
+                if Instant::now() >= deadline {
+                    error!("Save worker didn't finish in time; the last changes may be lost");
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            let _ = thread.join();
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = timeout;
+    }
+}
+
+impl Drop for SaveWorker {
+    fn drop(&mut self) {
+        self.shutdown(MIN_WRITE_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::TodoList;
+    use std::sync::mpsc::Receiver;
+    use uuid::Uuid;
+
+    fn workspace_with(titles: &[&str]) -> Workspace {
+        let mut list = TodoList::new("Persistence Test");
+        for title in titles {
+            list.create_item(title);
+        }
+        Workspace::new(list)
+    }
+
+    fn save(workspace: Workspace, announce: bool) -> SaveRequest {
+        SaveRequest::Save { workspace, announce, keep_journal: false }
+    }
+
+    #[test]
+    fn test_newer_requests_replace_older_ones() {
+        let mut pending = Pending::default();
+        pending.merge(save(workspace_with(&["a"]), true));
+        pending.merge(SaveRequest::Snapshot(workspace_with(&["a", "b"])));
+        pending.merge(save(workspace_with(&["a", "b", "c"]), false));
+
+        let (workspace, announce, _) = pending.save.as_ref().unwrap();
+        assert_eq!(workspace.item_count(), 3);
+        assert!(announce, "an announced save must still be announced");
+        assert!(pending.snapshot.is_none());
+        assert!(pending.is_urgent());
+
+        pending.merge(SaveRequest::Snapshot(workspace_with(&["a", "b", "c", "d"])));
+        pending.merge(SaveRequest::ClearJournal);
+        assert!(pending.snapshot.is_none());
+        assert_eq!(pending.into_requests().len(), 2);
+    }
+
+    fn temp_data_path() -> PathBuf {
+        std::env::temp_dir().join(format!("tewduwu-test-{}.json", Uuid::new_v4()))
+    }
+
+    fn saves(events: &Receiver<TaskEvent>) -> Vec<(bool, Result<(), String>)> {
+        events
+            .try_iter()
+            .filter_map(|event| match event {
+                TaskEvent::Saved { announce, result } => Some((announce, result)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_saves_are_coalesced_and_clear_the_journal() {
+        let data_path = temp_data_path();
+        let mut journal = Journal::for_data_path(&data_path);
+        journal.snapshot(&workspace_with(&["unsaved"])).unwrap();
+
+        let (sender, events) = mpsc::channel();
+        let mut worker = SaveWorker::spawn(data_path.clone(), journal, sender, Duration::from_secs(60)).unwrap();
+        for count in 1..=20 {
+            let titles: Vec<String> = (0..count).map(|n| format!("Task {}", n)).collect();
+            let titles: Vec<&str> = titles.iter().map(String::as_str).collect();
+            worker.send(save(workspace_with(&titles), false));
+        }
+        worker.shutdown(Duration::from_secs(5));
+
+        // The first save is written right away, the rest wait for the interval and merge
+        let saves = saves(&events);
+        assert!(saves.len() < 20 && saves.iter().all(|(_, result)| result.is_ok()));
+        let saved = storage::load_workspace(&data_path).unwrap().unwrap();
+        assert_eq!(saved.item_count(), 20);
+        assert!(Journal::for_data_path(&data_path).recover().unwrap().is_none());
+
+        storage::remove(&data_path).unwrap();
+    }
+
+    #[test]
+    fn test_announced_save_skips_the_wait() {
+        let data_path = temp_data_path();
+        let (sender, events) = mpsc::channel();
+        let mut worker =
+            SaveWorker::spawn(data_path.clone(), Journal::for_data_path(&data_path), sender, Duration::from_secs(60)).unwrap();
+        worker.send(save(workspace_with(&["first"]), false));
+        worker.send(save(workspace_with(&["first", "second"]), true));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut announced = false;
+        while !announced && Instant::now() < deadline {
+            announced = saves(&events).iter().any(|(announce, _)| *announce);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(announced);
+        assert_eq!(storage::load_workspace(&data_path).unwrap().unwrap().item_count(), 2);
+
+        worker.shutdown(Duration::from_secs(5));
+        storage::remove(&data_path).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+// System-wide hotkeys (hotkey feature)
+//
+// A hotkey is written like a keybind, e.g. "Ctrl+Alt+Space", and fires
+// whichever app has focus. Presses come in on whatever thread the platform
+// delivers them on and are passed to a callback, which main forwards to the
+// event loop.
+
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use winit::keyboard::ModifiersState;
+
+use crate::ui::input::KeyChord;
+
+/// The system's name for a chord
+fn to_hotkey(chord: KeyChord) -> Result<HotKey, String> {
+    let mut modifiers = Modifiers::empty();
+    for (ours, theirs) in [
+        (ModifiersState::CONTROL, Modifiers::CONTROL),
+        (ModifiersState::ALT, Modifiers::ALT),
+        (ModifiersState::SHIFT, Modifiers::SHIFT),
+        (ModifiersState::SUPER, Modifiers::SUPER),
+    ] {
+        if chord.modifiers.contains(ours) {
+            modifiers |= theirs;
+        }
+    }
+    // Both name keys by their W3C codes
+    let code = format!("{:?}", chord.key)
+        .parse::<Code>()
+        .map_err(|_| format!("{} can't be a hotkey", chord))?;
+    Ok(HotKey::new(Some(modifiers), code))
+}
+
+/// A chord registered with the system, until it's dropped (hotkey feature)
+///
+/// Make it on the event loop's thread: Windows and macOS deliver the presses
+/// through that thread's message loop.
+pub struct GlobalHotkey {
+    manager: GlobalHotKeyManager,
+    hotkey: HotKey,
+}
+
+impl GlobalHotkey {
+    /// Register `chord` system-wide; `on_press` is called each time it's pressed
+    ///
+    /// Presses only reach the callback of the first hotkey registered.
+    pub fn register(chord: KeyChord, on_press: impl Fn() + Send + Sync + 'static) -> Result<Self, String> {
+        let hotkey = to_hotkey(chord)?;
+        let manager = GlobalHotKeyManager::new().map_err(|e| format!("Global hotkeys are unavailable: {}", e))?;
+        manager
+            .register(hotkey)
+            .map_err(|e| format!("Failed to register the hotkey {}: {}", chord, e))?;
+
+        let id = hotkey.id();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.id() == id && event.state() == HotKeyState::Pressed {
+                on_press();
+            }
+        }));
+        Ok(Self { manager, hotkey })
+    }
+}
+
+impl Drop for GlobalHotkey {
+    fn drop(&mut self) {
+        if let Err(e) = self.manager.unregister(self.hotkey) {
+            tracing::error!("Failed to unregister the hotkey: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chords_become_hotkeys() {
+        let hotkey = |text: &str| to_hotkey(KeyChord::parse(text).unwrap()).unwrap();
+        assert_eq!(hotkey("Ctrl+Alt+Space"), HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::Space));
+        assert_eq!(hotkey("Super+Shift+N"), HotKey::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyN));
+        assert_eq!(hotkey("Ctrl+1"), HotKey::new(Some(Modifiers::CONTROL), Code::Digit1));
+        assert_eq!(hotkey("Alt+F2"), HotKey::new(Some(Modifiers::ALT), Code::F2));
+        assert_eq!(hotkey("Ctrl+Backquote"), HotKey::new(Some(Modifiers::CONTROL), Code::Backquote));
+    }
+}
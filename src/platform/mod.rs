@@ -0,0 +1,4 @@
+// Hooks into the desktop outside our own windows (native only)
+
+// System-wide hotkeys (hotkey feature)
+pub mod hotkey;
@@ -0,0 +1,589 @@
+// WASM plugin host
+//
+// Plugins live in `plugins/<name>/` next to the config file: a `plugin.toml`
+// manifest and the compiled `plugin.wasm`. They run sandboxed in an interpreter
+// with a fuel budget per call and can only touch the todo list through the host
+// functions below, each guarded by a permission that the manifest requests and
+// the user grants in the `[plugins]` table of the config file.
+//
+// Plugin API, version 1
+//
+// A plugin exports:
+//   memory                                     its linear memory
+//   tewduwu_api_version() -> i32               must return API_VERSION
+//   tewduwu_alloc(len) -> ptr                  space for strings passed in by the host
+//   tewduwu_init()                             optional, called once after loading
+//   tewduwu_run(cmd_ptr, cmd_len, sel_ptr, sel_len) -> i32
+//                                              runs a registered command; the selected
+//                                              task's id is passed as a string (empty if
+//                                              nothing is selected). Negative means failure.
+//
+// and may import from the "tewduwu" module:
+//   log(ptr, len)
+//   register_command(name_ptr, name_len, label_ptr, label_len)
+//   register_menu_entry(cmd_ptr, cmd_len, label_ptr, label_len)
+//   tasks_json(buf_ptr, buf_len) -> i32        [read-tasks] writes all tasks as a JSON
+//                                              array and returns its full length; call
+//                                              again with a bigger buffer if it didn't fit
+//   task_set_status(id_ptr, id_len, status) -> i32
+//                                              [write-tasks] 0 not started, 1 in progress,
+//                                              2 completed
+//   task_add(title_ptr, title_len) -> i32      [write-tasks]
+//
+// Strings are UTF-8. Host functions returning i32 use 0 (or a length) for
+// success and the negative ERR_* codes for failure.
+
+use tracing::{info, warn};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use uuid::Uuid;
+use wasmi::{Caller, Engine, Extern, Instance, Linker, Module, Store};
+
+use crate::core::prelude::{Command, Dispatcher, Status, TodoItem};
+
+/// Version of the plugin API described above
+pub const API_VERSION: i32 = 1;
+
+/// The plugin lacks the permission for this call
+pub const ERR_DENIED: i32 = -1;
+
+/// Bad arguments, e.g. an unknown task id or invalid UTF-8
+pub const ERR_INVALID: i32 = -2;
+
+/// Instructions a plugin may execute per call before it is stopped
+const FUEL_PER_CALL: u64 = 50_000_000;
+
+/// Longest string the host reads from plugin memory
+const MAX_STRING_LEN: i32 = 64 * 1024;
+
+/// Capabilities a plugin can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Permission {
+    /// Read every task
+    ReadTasks,
+    /// Add tasks and change their status
+    WriteTasks,
+}
+
+impl Permission {
+    /// Name used in manifests and the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Permission::ReadTasks => "read-tasks",
+            Permission::WriteTasks => "write-tasks",
+        }
+    }
+
+    /// Parse a permission name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Permission::ReadTasks, Permission::WriteTasks]
+            .into_iter()
+            .find(|permission| permission.name() == name)
+    }
+}
+
+/// Contents of a plugin's `plugin.toml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Permissions the plugin needs; it only gets those the user granted
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+/// A command registered by a plugin
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginCommand {
+    pub plugin: String,
+    pub name: String,
+    pub label: String,
+}
+
+impl PluginCommand {
+    /// Identifier used to bind the command to a key, e.g. `plugin:sample/count`
+    pub fn id(&self) -> String {
+        format!("plugin:{}/{}", self.plugin, self.name)
+    }
+}
+
+/// An entry a plugin wants in a task's context menu
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuEntry {
+    pub plugin: String,
+    pub command: String,
+    pub label: String,
+}
+
+/// What host functions of one plugin can reach
+struct HostState {
+    plugin: String,
+    granted: Vec<Permission>,
+    todo_list: Dispatcher,
+    commands: Vec<(String, String)>,
+    menu_entries: Vec<(String, String)>,
+
+    /// Set when the plugin modified the todo list
+    changed: bool,
+}
+
+impl HostState {
+    /// Check a permission, logging denied calls so plugin authors can tell why they fail
+    fn allows(&self, permission: Permission) -> bool {
+        let allowed = self.granted.contains(&permission);
+        if !allowed {
+            warn!("Plugin {:?} was denied {}", self.plugin, permission.name());
+        }
+        allowed
+    }
+}
+
+/// A loaded plugin
+struct Plugin {
+    manifest: Manifest,
+    store: Store<HostState>,
+    instance: Instance,
+}
+
+/// Loads plugins and runs their commands
+pub struct PluginHost {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+}
+
+impl Default for PluginHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PluginHost {
+    /// Create a host without plugins
+    pub fn new() -> Self {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        Self {
+            engine: Engine::new(&config),
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Load every plugin in `dir`, one per subdirectory
+    ///
+    /// `grants` maps plugin names to the permissions the user allowed. Returns
+    /// the problems found, meant to be shown to the user; a missing directory
+    /// just means no plugins are installed.
+    pub fn load_dir(
+        &mut self,
+        dir: &Path,
+        grants: &BTreeMap<String, Vec<Permission>>,
+        todo_list: &Dispatcher,
+    ) -> Vec<String> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut plugin_dirs: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.join("plugin.toml").is_file())
+            .collect();
+        plugin_dirs.sort();
+
+        let mut problems = Vec::new();
+        for plugin_dir in plugin_dirs {
+            if let Err(e) = self.load_from(&plugin_dir, grants, todo_list) {
+                problems.push(format!("Plugin in {}: {}", plugin_dir.display(), e));
+            }
+        }
+        problems
+    }
+
+    /// Load the plugin in `plugin_dir`
+    fn load_from(
+        &mut self,
+        plugin_dir: &Path,
+        grants: &BTreeMap<String, Vec<Permission>>,
+        todo_list: &Dispatcher,
+    ) -> Result<(), String> {
+        let manifest_text = std::fs::read_to_string(plugin_dir.join("plugin.toml"))
+            .map_err(|e| format!("Failed to read plugin.toml: {}", e))?;
+        let manifest: Manifest = toml::from_str(&manifest_text)
+            .map_err(|e| format!("Invalid plugin.toml: {}", e.message()))?;
+        let wasm = std::fs::read(plugin_dir.join("plugin.wasm"))
+            .map_err(|e| format!("Failed to read plugin.wasm: {}", e))?;
+
+        let granted = grants.get(&manifest.name).cloned().unwrap_or_default();
+        self.load(manifest, &wasm, &granted, todo_list)
+    }
+
+    /// Instantiate a plugin module and run its initialization
+    ///
+    /// The plugin gets the permissions that its manifest requests and `granted` allows.
+    pub fn load(
+        &mut self,
+        manifest: Manifest,
+        wasm: &[u8],
+        granted: &[Permission],
+        todo_list: &Dispatcher,
+    ) -> Result<(), String> {
+        if self.plugins.iter().any(|plugin| plugin.manifest.name == manifest.name) {
+            return Err(format!("A plugin named {:?} is already loaded", manifest.name));
+        }
+
+        let missing: Vec<&str> = manifest.permissions.iter()
+            .filter(|permission| !granted.contains(permission))
+            .map(|permission| permission.name())
+            .collect();
+        if !missing.is_empty() {
+            warn!(
+                "Plugin {:?} runs without {}; grant them in the [plugins] section of the config file",
+                manifest.name,
+                missing.join(", ")
+            );
+        }
+
+        let state = HostState {
+            plugin: manifest.name.clone(),
+            granted: manifest.permissions.iter().copied().filter(|p| granted.contains(p)).collect(),
+            todo_list: todo_list.clone(),
+            commands: Vec::new(),
+            menu_entries: Vec::new(),
+            changed: false,
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+
+        let module = Module::new(&self.engine, wasm).map_err(|e| format!("Invalid module: {}", e))?;
+        let linker = Self::linker(&self.engine).map_err(|e| e.to_string())?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("Failed to instantiate: {}", e))?;
+
+        let version = instance
+            .get_typed_func::<(), i32>(&store, "tewduwu_api_version")
+            .and_then(|version| version.call(&mut store, ()))
+            .map_err(|e| format!("Missing tewduwu_api_version: {}", e))?;
+        if version != API_VERSION {
+            return Err(format!("Needs plugin API {}, this build provides {}", version, API_VERSION));
+        }
+
+        if let Ok(init) = instance.get_typed_func::<(), ()>(&store, "tewduwu_init") {
+            init.call(&mut store, ()).map_err(|e| format!("tewduwu_init failed: {}", e))?;
+        }
+
+        info!("Loaded plugin {} {}", manifest.name, manifest.version);
+        self.plugins.push(Plugin { manifest, store, instance });
+        Ok(())
+    }
+
+    /// Point every loaded plugin at another list, e.g. when the user switches lists
+    pub fn set_dispatcher(&mut self, todo_list: &Dispatcher) {
+        for plugin in &mut self.plugins {
+            plugin.store.data_mut().todo_list = todo_list.clone();
+        }
+    }
+
+    /// Manifests of the loaded plugins
+    pub fn manifests(&self) -> impl Iterator<Item = &Manifest> {
+        self.plugins.iter().map(|plugin| &plugin.manifest)
+    }
+
+    /// Commands registered by all plugins
+    pub fn commands(&self) -> Vec<PluginCommand> {
+        self.plugins
+            .iter()
+            .flat_map(|plugin| {
+                let state = plugin.store.data();
+                state.commands.iter().map(|(name, label)| PluginCommand {
+                    plugin: state.plugin.clone(),
+                    name: name.clone(),
+                    label: label.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Context menu entries registered by all plugins
+    pub fn menu_entries(&self) -> Vec<MenuEntry> {
+        self.plugins
+            .iter()
+            .flat_map(|plugin| {
+                let state = plugin.store.data();
+                state.menu_entries.iter().map(|(command, label)| MenuEntry {
+                    plugin: state.plugin.clone(),
+                    command: command.clone(),
+                    label: label.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Run a command by id (see `PluginCommand::id`), returning whether the todo list changed
+    pub fn run_command(&mut self, id: &str, selected: Option<Uuid>) -> Result<bool, String> {
+        let (plugin_name, command) = id
+            .strip_prefix("plugin:")
+            .and_then(|rest| rest.split_once('/'))
+            .ok_or_else(|| format!("{:?} is not a plugin command", id))?;
+        let plugin = self.plugins
+            .iter_mut()
+            .find(|plugin| plugin.manifest.name == plugin_name)
+            .ok_or_else(|| format!("No plugin named {:?} is loaded", plugin_name))?;
+        if !plugin.store.data().commands.iter().any(|(name, _)| name == command) {
+            return Err(format!("Plugin {:?} has no command {:?}", plugin_name, command));
+        }
+
+        let store = &mut plugin.store;
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+        store.data_mut().changed = false;
+
+        let selected = selected.map(|id| id.to_string()).unwrap_or_default();
+        let (cmd_ptr, cmd_len) = Self::pass_string(store, &plugin.instance, command)?;
+        let (sel_ptr, sel_len) = Self::pass_string(store, &plugin.instance, &selected)?;
+
+        let code = plugin.instance
+            .get_typed_func::<(i32, i32, i32, i32), i32>(&*store, "tewduwu_run")
+            .and_then(|run| run.call(&mut *store, (cmd_ptr, cmd_len, sel_ptr, sel_len)))
+            .map_err(|e| format!("Plugin {:?} failed: {}", plugin_name, e))?;
+        let changed = store.data().changed;
+        if code < 0 {
+            return Err(format!("Plugin command {} failed with code {}", id, code));
+        }
+        Ok(changed)
+    }
+
+    /// Copy a string into plugin memory, returning its pointer and length
+    fn pass_string(store: &mut Store<HostState>, instance: &Instance, text: &str) -> Result<(i32, i32), String> {
+        let len = i32::try_from(text.len()).map_err(|_| "String too long".to_string())?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&*store, "tewduwu_alloc")
+            .map_err(|e| format!("Missing tewduwu_alloc: {}", e))?;
+        let ptr = alloc.call(&mut *store, len).map_err(|e| e.to_string())?;
+        let memory = instance
+            .get_memory(&*store, "memory")
+            .ok_or_else(|| "Plugin exports no memory".to_string())?;
+        memory
+            .write(&mut *store, ptr as usize, text.as_bytes())
+            .map_err(|e| format!("tewduwu_alloc returned unusable memory: {}", e))?;
+        Ok((ptr, len))
+    }
+
+    /// Host functions available to plugins
+    fn linker(engine: &Engine) -> Result<Linker<HostState>, wasmi::errors::LinkerError> {
+        let mut linker = Linker::new(engine);
+        linker.func_wrap("tewduwu", "log", |caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            if let Some(message) = read_string(&caller, ptr, len) {
+                info!("[{}] {}", caller.data().plugin, message);
+            }
+        })?;
+        linker.func_wrap(
+            "tewduwu",
+            "register_command",
+            |mut caller: Caller<'_, HostState>, name_ptr: i32, name_len: i32, label_ptr: i32, label_len: i32| {
+                if let (Some(name), Some(label)) = (
+                    read_string(&caller, name_ptr, name_len),
+                    read_string(&caller, label_ptr, label_len),
+                ) {
+                    caller.data_mut().commands.push((name, label));
+                }
+            },
+        )?;
+        linker.func_wrap(
+            "tewduwu",
+            "register_menu_entry",
+            |mut caller: Caller<'_, HostState>, cmd_ptr: i32, cmd_len: i32, label_ptr: i32, label_len: i32| {
+                if let (Some(command), Some(label)) = (
+                    read_string(&caller, cmd_ptr, cmd_len),
+                    read_string(&caller, label_ptr, label_len),
+                ) {
+                    caller.data_mut().menu_entries.push((command, label));
+                }
+            },
+        )?;
+        linker.func_wrap(
+            "tewduwu",
+            "tasks_json",
+            |mut caller: Caller<'_, HostState>, buf_ptr: i32, buf_len: i32| -> i32 {
+                if !caller.data().allows(Permission::ReadTasks) {
+                    return ERR_DENIED;
+                }
+                let json = {
+                    let todo_list = caller.data().todo_list.lock();
+                    match serde_json::to_string(&todo_list.all_items()) {
+                        Ok(json) => json,
+                        Err(_) => return ERR_INVALID,
+                    }
+                };
+                let Ok(len) = i32::try_from(json.len()) else {
+                    return ERR_INVALID;
+                };
+                if len <= buf_len && write_bytes(&mut caller, buf_ptr, json.as_bytes()).is_none() {
+                    return ERR_INVALID;
+                }
+                len
+            },
+        )?;
+        linker.func_wrap(
+            "tewduwu",
+            "task_set_status",
+            |mut caller: Caller<'_, HostState>, id_ptr: i32, id_len: i32, status: i32| -> i32 {
+                if !caller.data().allows(Permission::WriteTasks) {
+                    return ERR_DENIED;
+                }
+                let status = match status {
+                    0 => Status::NotStarted,
+                    1 => Status::InProgress,
+                    2 => Status::Completed,
+                    _ => return ERR_INVALID,
+                };
+                let Some(id) = read_string(&caller, id_ptr, id_len).and_then(|id| Uuid::parse_str(&id).ok()) else {
+                    return ERR_INVALID;
+                };
+                match caller.data().todo_list.execute(Command::SetStatus { id, status }) {
+                    Ok(changed) => caller.data_mut().changed |= changed,
+                    Err(_) => return ERR_INVALID,
+                }
+                0
+            },
+        )?;
+        linker.func_wrap(
+            "tewduwu",
+            "task_add",
+            |mut caller: Caller<'_, HostState>, title_ptr: i32, title_len: i32| -> i32 {
+                if !caller.data().allows(Permission::WriteTasks) {
+                    return ERR_DENIED;
+                }
+                let Some(title) = read_string(&caller, title_ptr, title_len).filter(|title| !title.trim().is_empty()) else {
+                    return ERR_INVALID;
+                };
+                let item = TodoItem::new(title.trim());
+                if caller.data().todo_list.execute(Command::AddItem { item }).is_err() {
+                    return ERR_INVALID;
+                }
+                caller.data_mut().changed = true;
+                0
+            },
+        )?;
+        Ok(linker)
+    }
+}
+
+/// Read a UTF-8 string from the calling plugin's memory
+fn read_string(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Option<String> {
+    if ptr < 0 || !(0..=MAX_STRING_LEN).contains(&len) {
+        return None;
+    }
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let mut buffer = vec![0; len as usize];
+    memory.read(caller, ptr as usize, &mut buffer).ok()?;
+    String::from_utf8(buffer).ok()
+}
+
+/// Write bytes into the calling plugin's memory
+fn write_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, bytes: &[u8]) -> Option<()> {
+    if ptr < 0 {
+        return None;
+    }
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    memory.write(caller, ptr as usize, bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::core::prelude::TodoList;
+
+    /// Registers an "add" command that adds a task, with a menu entry for it
+    const ADD_TASK_PLUGIN: &str = r#"
+        (module
+          (import "tewduwu" "register_command" (func $register_command (param i32 i32 i32 i32)))
+          (import "tewduwu" "register_menu_entry" (func $register_menu_entry (param i32 i32 i32 i32)))
+          (import "tewduwu" "task_add" (func $task_add (param i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "add")
+          (data (i32.const 16) "Add a task")
+          (data (i32.const 32) "From a plugin")
+          (global $heap (mut i32) (i32.const 1024))
+          (func (export "tewduwu_api_version") (result i32) (i32.const 1))
+          (func (export "tewduwu_alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $heap))
+            (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+            (local.get $ptr))
+          (func (export "tewduwu_init")
+            (call $register_command (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 10))
+            (call $register_menu_entry (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 10)))
+          (func (export "tewduwu_run") (param i32 i32 i32 i32) (result i32)
+            (call $task_add (i32.const 32) (i32.const 13))))
+    "#;
+
+    fn manifest(name: &str, permissions: Vec<Permission>) -> Manifest {
+        Manifest {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            description: None,
+            permissions,
+        }
+    }
+
+    #[test]
+    fn test_commands_modify_the_list() {
+        let todo_list = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("Test"))));
+        let wasm = wat::parse_str(ADD_TASK_PLUGIN).unwrap();
+        let mut host = PluginHost::new();
+        host.load(manifest("adder", vec![Permission::WriteTasks]), &wasm, &[Permission::WriteTasks], &todo_list)
+            .unwrap();
+
+        let commands = host.commands();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].id(), "plugin:adder/add");
+        assert_eq!(commands[0].label, "Add a task");
+        assert_eq!(host.menu_entries()[0].command, "add");
+
+        assert_eq!(host.run_command("plugin:adder/add", None), Ok(true));
+        let todo_list = todo_list.lock();
+        assert_eq!(todo_list.len(), 1);
+        assert_eq!(todo_list.all_items()[0].title(), "From a plugin");
+    }
+
+    #[test]
+    fn test_permissions_need_a_grant() {
+        let todo_list = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("Test"))));
+        let wasm = wat::parse_str(ADD_TASK_PLUGIN).unwrap();
+        let mut host = PluginHost::new();
+
+        // Requested but not granted
+        host.load(manifest("adder", vec![Permission::WriteTasks]), &wasm, &[], &todo_list).unwrap();
+        assert!(host.run_command("plugin:adder/add", None).is_err());
+
+        // Granted but not requested
+        host.load(manifest("sneaky", Vec::new()), &wasm, &[Permission::WriteTasks], &todo_list).unwrap();
+        assert!(host.run_command("plugin:sneaky/add", None).is_err());
+
+        assert!(todo_list.lock().is_empty());
+        assert!(host.run_command("plugin:adder/missing", None).is_err());
+    }
+
+    #[test]
+    fn test_runaway_plugins_are_stopped() {
+        let looping = r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "tewduwu_api_version") (result i32) (i32.const 1))
+              (func (export "tewduwu_init") (loop $forever (br $forever))))
+        "#;
+        let todo_list = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("Test"))));
+        let wasm = wat::parse_str(looping).unwrap();
+        let mut host = PluginHost::new();
+        assert!(host.load(manifest("looper", Vec::new()), &wasm, &[], &todo_list).is_err());
+        assert_eq!(host.manifests().count(), 0);
+    }
+}
@@ -0,0 +1,227 @@
+// Focus timer (pomodoro)
+//
+// A focus session counts down on one task; once it runs out, main logs it in
+// the task's metadata and a break counts down after it. The timer only does
+// the arithmetic: it's handed the time on every call, so pausing, resuming
+// and finishing are plain functions of the clock.
+
+use uuid::Uuid;
+
+use crate::core::prelude::TodoItem;
+
+/// Metadata key of a task's log of finished focus sessions
+///
+/// The value lists `ended_at:minutes` pairs, oldest first, separated by commas.
+/// The next occurrence of a repeating task starts without it.
+pub const LOG_KEY: &str = crate::core::FOCUS_LOG_KEY;
+
+/// What the timer is counting down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Working on the task
+    Focus,
+    /// Resting after a focus session
+    Break,
+}
+
+/// A countdown on a task, which can be paused
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusTimer {
+    task: Uuid,
+    title: String,
+    phase: Phase,
+    /// Length of the countdown, in seconds
+    duration: f64,
+    /// Seconds run before the current stretch
+    elapsed: f64,
+    /// When the current stretch started, or `None` while paused
+    running_since: Option<f64>,
+}
+
+impl FocusTimer {
+    /// Start a focus session of `minutes` on a task at `now`, in seconds
+    pub fn start(task: Uuid, title: &str, minutes: u32, now: f64) -> Self {
+        Self {
+            task,
+            title: title.to_string(),
+            phase: Phase::Focus,
+            duration: f64::from(minutes) * 60.0,
+            elapsed: 0.0,
+            running_since: Some(now),
+        }
+    }
+
+    /// The break of `minutes` after this session, starting at `now`
+    pub fn into_break(self, minutes: u32, now: f64) -> Self {
+        Self {
+            phase: Phase::Break,
+            duration: f64::from(minutes) * 60.0,
+            elapsed: 0.0,
+            running_since: Some(now),
+            ..self
+        }
+    }
+
+    pub fn task(&self) -> Uuid {
+        self.task
+    }
+
+    /// The task's title when the session started
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Length of the countdown in whole minutes
+    pub fn minutes(&self) -> u32 {
+        (self.duration / 60.0).round() as u32
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.running_since.is_none()
+    }
+
+    /// Stop the countdown where it is; pausing twice changes nothing
+    pub fn pause(&mut self, now: f64) {
+        if let Some(since) = self.running_since.take() {
+            self.elapsed += (now - since).max(0.0);
+        }
+    }
+
+    /// Carry on counting down from where it was paused
+    pub fn resume(&mut self, now: f64) {
+        if self.running_since.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    /// Pause a running countdown, resume a paused one
+    pub fn toggle_pause(&mut self, now: f64) {
+        if self.is_paused() {
+            self.resume(now);
+        } else {
+            self.pause(now);
+        }
+    }
+
+    /// Seconds counted down so far, at most the whole duration
+    pub fn elapsed(&self, now: f64) -> f64 {
+        let running = self.running_since.map_or(0.0, |since| (now - since).max(0.0));
+        (self.elapsed + running).min(self.duration)
+    }
+
+    /// Seconds left
+    pub fn remaining(&self, now: f64) -> f64 {
+        self.duration - self.elapsed(now)
+    }
+
+    /// Share of the countdown still left, from 1 at the start to 0 at the end
+    pub fn fraction_left(&self, now: f64) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        (self.remaining(now) / self.duration) as f32
+    }
+
+    pub fn is_finished(&self, now: f64) -> bool {
+        self.remaining(now) <= 0.0
+    }
+}
+
+/// Time left as shown on the timer, `m:ss`, rounding up so it reads 0:00 only at the end
+pub fn format_remaining(secs: f64) -> String {
+    let secs = secs.max(0.0).ceil() as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// A task's focus log with a session of `minutes` that ended at `ended_at` added
+pub fn log_session(log: Option<&str>, ended_at: u64, minutes: u32) -> String {
+    let entry = format!("{}:{}", ended_at, minutes);
+    match log.filter(|log| !log.is_empty()) {
+        Some(log) => format!("{},{}", log, entry),
+        None => entry,
+    }
+}
+
+/// The sessions in a focus log as `(ended_at, minutes)`, skipping entries that don't parse
+pub fn logged_sessions(log: &str) -> Vec<(u64, u32)> {
+    log.split(',')
+        .filter_map(|entry| {
+            let (ended_at, minutes) = entry.trim().split_once(':')?;
+            Some((ended_at.parse().ok()?, minutes.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Number of focus sessions logged on a task
+pub fn session_count(item: &TodoItem) -> usize {
+    item.metadata().get(LOG_KEY).map_or(0, |log| logged_sessions(log).len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::RecurrenceRule;
+
+    const NOW: f64 = 1_700_000_000.0;
+
+    #[test]
+    fn test_counts_down_and_pauses() {
+        let task = Uuid::new_v4();
+        let mut timer = FocusTimer::start(task, "Write report", 25, NOW);
+        assert_eq!(timer.remaining(NOW), 25.0 * 60.0);
+        assert_eq!(timer.fraction_left(NOW), 1.0);
+        assert_eq!(timer.remaining(NOW + 60.0), 24.0 * 60.0);
+
+        // Paused, the clock moving on doesn't count
+        timer.pause(NOW + 60.0);
+        timer.pause(NOW + 90.0);
+        assert!(timer.is_paused());
+        assert_eq!(timer.remaining(NOW + 600.0), 24.0 * 60.0);
+        timer.toggle_pause(NOW + 600.0);
+        assert_eq!(timer.remaining(NOW + 660.0), 23.0 * 60.0);
+        assert!(!timer.is_finished(NOW + 600.0 + 24.0 * 60.0 - 1.0));
+        assert!(timer.is_finished(NOW + 600.0 + 24.0 * 60.0));
+        assert_eq!(timer.fraction_left(NOW + 10_000.0), 0.0);
+
+        // The break keeps the task and starts over
+        let pause = timer.into_break(5, NOW + 3000.0);
+        assert_eq!((pause.task(), pause.phase(), pause.minutes()), (task, Phase::Break, 5));
+        assert_eq!(pause.remaining(NOW + 3000.0), 5.0 * 60.0);
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(25.0 * 60.0), "25:00");
+        assert_eq!(format_remaining(59.2), "1:00");
+        assert_eq!(format_remaining(9.0), "0:09");
+        assert_eq!(format_remaining(-3.0), "0:00");
+    }
+
+    #[test]
+    fn test_session_log() {
+        let log = log_session(None, 1_700_000_000, 25);
+        let log = log_session(Some(&log), 1_700_003_000, 50);
+        assert_eq!(log, "1700000000:25,1700003000:50");
+        assert_eq!(logged_sessions(&log), [(1_700_000_000, 25), (1_700_003_000, 50)]);
+        assert_eq!(logged_sessions("garbage,1700000000:25"), [(1_700_000_000, 25)]);
+        assert_eq!(log_session(Some(""), 1, 5), "1:5");
+
+        let mut item = TodoItem::new("Focus");
+        assert_eq!(session_count(&item), 0);
+        item.set_metadata(LOG_KEY, &log);
+        assert_eq!(session_count(&item), 2);
+    }
+
+    #[test]
+    fn test_next_occurrence_starts_without_sessions() {
+        let mut item = TodoItem::new("Practice piano").with_recurrence(RecurrenceRule::Daily);
+        item.set_metadata(LOG_KEY, &log_session(None, 1_700_000_000, 25));
+        let next = item.next_occurrence().unwrap();
+        assert_eq!(session_count(&item), 1);
+        assert_eq!(session_count(&next), 0);
+    }
+}
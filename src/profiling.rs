@@ -0,0 +1,35 @@
+// Profiler scopes (the `profiling` feature)
+//
+// `profile_scope!` marks the rest of the enclosing block as a named puffin
+// scope. Without the feature, or on the web, it expands to nothing, so the
+// update, layout, render and effect code can stay instrumented in every build.
+// Scopes are shown by the in-app flame view (`ui::profiler`), toggled with the
+// `profiler` keybind.
+
+/// Time the rest of the enclosing block as a scope with this name
+///
+/// Exported so the binary's scopes follow the same feature; use it as
+/// `profiling::profile_scope!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+        puffin::profile_scope!($name);
+    };
+}
+pub use crate::profile_scope;
+
+/// Start recording scopes; until then `profile_scope!` costs a single atomic load
+pub fn enable() {
+    #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+    puffin::set_scopes_on(true);
+}
+
+/// Close the current profiler frame, handing its scopes to the flame view
+///
+/// Called once per redraw, after the frame has been presented.
+pub fn new_frame() {
+    #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+    puffin::GlobalProfiler::lock().new_frame();
+}
@@ -0,0 +1,160 @@
+// Input recording and replay (the `input-recording` feature)
+//
+// With TEWDUWU_RECORD set, every input event the app handles is appended to
+// that file as one JSON line, tagged with the frame it arrived in and its time.
+// With TEWDUWU_REPLAY set, live input is ignored and the recorded events are
+// fed back at the same frames instead. Frames advance by a fixed time step and
+// click counting uses the recorded times, so a replay takes the same path
+// through the app as the original session did. The same files can drive the
+// list in tests through `UiDriver::replay`.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tracing::{error, info};
+
+use crate::ui::input::{now_secs, InputEvent, TimedInput};
+
+/// Environment variable naming the file to record input to
+pub const RECORD_ENV: &str = "TEWDUWU_RECORD";
+
+/// Environment variable naming a recording to replay
+pub const REPLAY_ENV: &str = "TEWDUWU_REPLAY";
+
+/// Seconds each frame advances by during a replay, whatever the real frame time
+pub const FRAME_STEP: f32 = 0.016;
+
+/// Parse a recording, one `TimedInput` per line
+pub fn parse(text: &str) -> Result<Vec<TimedInput>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| serde_json::from_str(line).map_err(|e| format!("Invalid recording, line {}: {}", n + 1, e)))
+        .collect()
+}
+
+/// Load a recording from a file
+pub fn load(path: &Path) -> Result<Vec<TimedInput>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse(&text)
+}
+
+/// Records live input or replays a recording, counting frames for both
+#[derive(Default)]
+pub struct InputLog {
+    /// Frames rendered so far
+    frame: u64,
+    /// When recording started, in `now_secs` time
+    start: f64,
+    recorder: Option<BufWriter<File>>,
+    replay: Option<VecDeque<TimedInput>>,
+}
+
+impl InputLog {
+    /// Start recording or replaying as asked for by the environment
+    pub fn from_env() -> Result<Self, String> {
+        let mut log = Self { start: now_secs(), ..Self::default() };
+        if let Some(path) = std::env::var_os(REPLAY_ENV) {
+            let inputs = load(Path::new(&path))?;
+            info!("Replaying {} input events from {}", inputs.len(), Path::new(&path).display());
+            log.replay = Some(inputs.into());
+        } else if let Some(path) = std::env::var_os(RECORD_ENV) {
+            let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", Path::new(&path).display(), e))?;
+            info!("Recording input to {}", Path::new(&path).display());
+            log.recorder = Some(BufWriter::new(file));
+        }
+        Ok(log)
+    }
+
+    /// Whether live input should be ignored because a replay drives the app
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Write a live input event that arrived at `time`
+    pub fn record(&mut self, input: &InputEvent, time: f64) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        let timed = TimedInput { frame: self.frame, time: time - self.start, input: input.clone() };
+        let written = serde_json::to_string(&timed)
+            .map_err(|e| e.to_string())
+            .and_then(|line| writeln!(recorder, "{}", line).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            error!("Stopped recording input: {}", e);
+            self.recorder = None;
+        }
+    }
+
+    /// The recorded events due before this frame's update
+    ///
+    /// Once the recording runs out, live input takes over again.
+    pub fn replay_frame(&mut self) -> Vec<TimedInput> {
+        let Some(replay) = self.replay.as_mut() else {
+            return Vec::new();
+        };
+        let mut due = Vec::new();
+        while replay.front().is_some_and(|timed| timed.frame <= self.frame) {
+            due.extend(replay.pop_front());
+        }
+        if replay.is_empty() {
+            info!("Replay finished after {} frames", self.frame);
+            self.replay = None;
+        }
+        due
+    }
+
+    /// Count a rendered frame
+    pub fn end_frame(&mut self) {
+        self.frame += 1;
+        // Lines stay buffered between frames, but a crash shouldn't lose the last ones
+        if let Some(recorder) = self.recorder.as_mut() {
+            let _ = recorder.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::input::KeyName;
+
+    #[test]
+    fn test_recordings_round_trip() {
+        let path = std::env::temp_dir().join(format!("tewduwu-test-{}.jsonl", uuid::Uuid::new_v4()));
+        let inputs = vec![
+            InputEvent::CursorMoved { x: 10.0, y: 20.0 },
+            InputEvent::MouseButton { middle: false, pressed: true },
+            InputEvent::Key { key: KeyName::Named("Enter".to_string()), pressed: true, repeat: false, text: None },
+        ];
+
+        let mut log = InputLog { recorder: Some(BufWriter::new(File::create(&path).unwrap())), ..InputLog::default() };
+        log.record(&inputs[0], 0.5);
+        log.end_frame();
+        log.record(&inputs[1], 0.75);
+        log.record(&inputs[2], 1.0);
+        log.end_frame();
+
+        let recorded = load(&path).unwrap();
+        assert_eq!(recorded.iter().map(|timed| timed.frame).collect::<Vec<_>>(), vec![0, 1, 1]);
+        assert_eq!(recorded.iter().map(|timed| timed.input.clone()).collect::<Vec<_>>(), inputs);
+        assert_eq!(recorded[1].time, 0.75);
+
+        // Replayed at the same frames
+        let mut replay = InputLog { replay: Some(recorded.into()), ..InputLog::default() };
+        assert_eq!(replay.replay_frame().len(), 1);
+        replay.end_frame();
+        assert_eq!(replay.replay_frame().len(), 2);
+        assert!(!replay.is_replaying());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_bad_lines_are_reported() {
+        assert!(parse("\n").unwrap().is_empty());
+        let error = parse("{\"frame\": 0, \"time\": 0.0, \"input\": {\"Focused\": true}}\nnot json").unwrap_err();
+        assert!(error.contains("line 2"), "{}", error);
+    }
+}
@@ -0,0 +1,412 @@
+// Rhai scripting
+//
+// `init.rhai` next to the config file runs once the todo list is loaded. It can
+// edit tasks, define filters and bind functions to keys; bound functions run
+// later with the same API. Scripts see the list only through these functions:
+//
+//   tasks() -> array                 every task as a map with id, title,
+//                                    description, status, priority, parent and tags
+//   selected() -> string             id of the selected task, () if none
+//   add_task(title) -> string        creates a task and returns its id
+//   set_status(id, status)           "not-started", "in-progress" or "completed"
+//   complete(id)
+//   remove_task(id)
+//   notify(message)                  shows a toast
+//   bind_key(chord, fn)              runs fn() when the chord is pressed, e.g. "Ctrl+Shift+D"
+//   register_command(name, fn)       a named action for the command palette
+//   define_filter(name, fn)          fn(task) -> bool decides which tasks are shown
+//   use_filter(name)                 shows only the tasks the filter accepts
+//   clear_filter()
+//
+// Edits are dispatched as `Command`s like the UI's own. Filters run while the
+// list is being rebuilt and only get the task passed in; the list functions
+// fail inside them.
+//
+// Example:
+//
+//   define_filter("urgent", |task| task.priority == "high" && task.status != "completed");
+//   bind_key("Ctrl+Shift+U", || use_filter("urgent"));
+//   bind_key("Ctrl+Shift+A", || clear_filter());
+//   bind_key("Ctrl+Shift+D", || {
+//       let id = selected();
+//       if id != () { complete(id); }
+//   });
+
+use tracing::warn;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FnPtr, Map, AST};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use uuid::Uuid;
+
+use crate::config::normalize_chord;
+use crate::core::prelude::{Command, Dispatcher, Priority, Status, TodoItem, TodoList};
+use crate::ui::todo_list_widget::ItemFilter;
+
+/// Operations a script may run per call before it is stopped, so a runaway
+/// loop can't freeze the window
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// What a script run asks of the application
+#[derive(Default)]
+pub struct ScriptOutcome {
+    /// The todo list was modified
+    pub changed: bool,
+    /// `Some(Some(filter))` to apply a filter, `Some(None)` to clear it
+    pub filter: Option<Option<ItemFilter>>,
+    /// Messages passed to `notify`
+    pub messages: Vec<String>,
+    /// Why the script stopped, if it failed
+    pub error: Option<String>,
+}
+
+/// Effects recorded by the script functions during a run
+#[derive(Default)]
+struct Pending {
+    changed: bool,
+    filter: Option<Option<String>>,
+    messages: Vec<String>,
+}
+
+/// State shared between the host and the functions registered with the engine
+struct ScriptState {
+    dispatcher: Dispatcher,
+    selected: Option<Uuid>,
+    /// Normalized key chord to function
+    bindings: HashMap<String, FnPtr>,
+    commands: BTreeMap<String, FnPtr>,
+    filters: BTreeMap<String, FnPtr>,
+    pending: Pending,
+}
+
+/// Runs scripts against a todo list
+pub struct ScriptHost {
+    engine: Rc<Engine>,
+    /// Functions and closures of every script run so far
+    lib: AST,
+    state: Rc<RefCell<ScriptState>>,
+}
+
+impl ScriptHost {
+    /// Create a host whose scripts work on the dispatcher's list
+    pub fn new(dispatcher: Dispatcher) -> Self {
+        let state = Rc::new(RefCell::new(ScriptState {
+            dispatcher,
+            selected: None,
+            bindings: HashMap::new(),
+            commands: BTreeMap::new(),
+            filters: BTreeMap::new(),
+            pending: Pending::default(),
+        }));
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.on_print(|text| tracing::info!("[script] {}", text));
+        engine.on_debug(|text, _, pos| tracing::debug!("[script] {} {}", pos, text));
+        register_api(&mut engine, &state);
+
+        Self {
+            engine: Rc::new(engine),
+            lib: AST::empty(),
+            state,
+        }
+    }
+
+    /// Run a script, e.g. the contents of `init.rhai`
+    pub fn run(&mut self, source: &str, selected: Option<Uuid>) -> ScriptOutcome {
+        let ast = match self.engine.compile(source) {
+            Ok(ast) => ast,
+            Err(e) => return self.finish(Err(format!("Script error: {}", e))),
+        };
+        // Keep its functions so bound closures can still be called later
+        self.lib += ast.clone_functions_only();
+
+        self.state.borrow_mut().selected = selected;
+        let result = self.engine.run_ast(&ast).map_err(|e| format!("Script error: {}", e));
+        self.finish(result)
+    }
+
+    /// Run the function bound to a key chord, if there is one
+    pub fn run_binding(&mut self, chord: &str, selected: Option<Uuid>) -> Option<ScriptOutcome> {
+        let chord = normalize_chord(chord)?;
+        let function = self.state.borrow().bindings.get(&chord).cloned()?;
+        Some(self.call(&function, selected))
+    }
+
+    /// Run a command registered with `register_command`
+    pub fn run_command(&mut self, name: &str, selected: Option<Uuid>) -> Result<ScriptOutcome, String> {
+        let function = self.state.borrow().commands.get(name).cloned()
+            .ok_or_else(|| format!("No script command named {:?}", name))?;
+        Ok(self.call(&function, selected))
+    }
+
+    /// Run scripts against another list from now on; bindings, commands and filters stay
+    pub fn set_dispatcher(&mut self, dispatcher: Dispatcher) {
+        let mut state = self.state.borrow_mut();
+        state.dispatcher = dispatcher;
+        state.selected = None;
+    }
+
+    /// Names of the commands registered by scripts, for the command palette
+    pub fn commands(&self) -> Vec<String> {
+        self.state.borrow().commands.keys().cloned().collect()
+    }
+
+    /// Names of the filters defined by scripts
+    pub fn filters(&self) -> Vec<String> {
+        self.state.borrow().filters.keys().cloned().collect()
+    }
+
+    fn call(&mut self, function: &FnPtr, selected: Option<Uuid>) -> ScriptOutcome {
+        self.state.borrow_mut().selected = selected;
+        let result = function
+            .call::<Dynamic>(&self.engine, &self.lib, ())
+            .map(|_| ())
+            .map_err(|e| format!("Script function {} failed: {}", function.fn_name(), e));
+        self.finish(result)
+    }
+
+    /// Collect what the run asked for
+    fn finish(&mut self, result: Result<(), String>) -> ScriptOutcome {
+        let pending = std::mem::take(&mut self.state.borrow_mut().pending);
+        let filter = pending.filter.map(|name| name.and_then(|name| self.item_filter(&name)));
+        ScriptOutcome {
+            changed: pending.changed,
+            filter,
+            messages: pending.messages,
+            error: result.err(),
+        }
+    }
+
+    /// Wrap a script filter for the todo list widget
+    fn item_filter(&self, name: &str) -> Option<ItemFilter> {
+        let function = self.state.borrow().filters.get(name).cloned()?;
+        let engine = self.engine.clone();
+        let lib = self.lib.clone();
+        let name = name.to_string();
+        Some(Rc::new(move |item: &TodoItem| {
+            match function.call::<bool>(&engine, &lib, (task_map(item),)) {
+                Ok(keep) => keep,
+                Err(e) => {
+                    warn!("Filter {:?} failed: {}", name, e);
+                    true
+                }
+            }
+        }))
+    }
+}
+
+/// Register the functions described at the top of this file
+fn register_api(engine: &mut Engine, state: &Rc<RefCell<ScriptState>>) {
+    let s = state.clone();
+    engine.register_fn("tasks", move || -> ScriptResult<Array> {
+        with_list(&s, |list| list.all_items().into_iter().map(|item| Dynamic::from_map(task_map(item))).collect())
+    });
+
+    let s = state.clone();
+    engine.register_fn("selected", move || -> Dynamic {
+        match s.borrow().selected {
+            Some(id) => id.to_string().into(),
+            None => Dynamic::UNIT,
+        }
+    });
+
+    let s = state.clone();
+    engine.register_fn("add_task", move |title: &str| -> ScriptResult<String> {
+        let item = TodoItem::new(title);
+        let id = item.id();
+        dispatch(&s, Command::AddItem { item })?;
+        Ok(id.to_string())
+    });
+
+    let s = state.clone();
+    engine.register_fn("set_status", move |id: &str, status: &str| -> ScriptResult<()> {
+        let status = parse_status(status)?;
+        set_status(&s, id, status)
+    });
+
+    let s = state.clone();
+    engine.register_fn("complete", move |id: &str| -> ScriptResult<()> {
+        set_status(&s, id, Status::Completed)
+    });
+
+    let s = state.clone();
+    engine.register_fn("remove_task", move |id: &str| -> ScriptResult<()> {
+        let id = parse_id(id)?;
+        if with_list(&s, |list| list.get_item(id).is_none())? {
+            return Err(format!("No task with id {}", id).into());
+        }
+        dispatch(&s, Command::RemoveItem { id })
+    });
+
+    let s = state.clone();
+    engine.register_fn("notify", move |message: &str| {
+        s.borrow_mut().pending.messages.push(message.to_string());
+    });
+
+    let s = state.clone();
+    engine.register_fn("bind_key", move |chord: &str, function: FnPtr| -> ScriptResult<()> {
+        let normalized = normalize_chord(chord).ok_or_else(|| format!("{:?} is not a valid key chord", chord))?;
+        s.borrow_mut().bindings.insert(normalized, function);
+        Ok(())
+    });
+
+    let s = state.clone();
+    engine.register_fn("register_command", move |name: &str, function: FnPtr| {
+        s.borrow_mut().commands.insert(name.to_string(), function);
+    });
+
+    let s = state.clone();
+    engine.register_fn("define_filter", move |name: &str, function: FnPtr| {
+        s.borrow_mut().filters.insert(name.to_string(), function);
+    });
+
+    let s = state.clone();
+    engine.register_fn("use_filter", move |name: &str| -> ScriptResult<()> {
+        let mut state = s.borrow_mut();
+        if !state.filters.contains_key(name) {
+            return Err(format!("No filter named {:?}", name).into());
+        }
+        state.pending.filter = Some(Some(name.to_string()));
+        Ok(())
+    });
+
+    let s = state.clone();
+    engine.register_fn("clear_filter", move || {
+        s.borrow_mut().pending.filter = Some(None);
+    });
+}
+
+/// Run `f` on the todo list
+///
+/// Fails instead of waiting when the list is locked, which is the case while a
+/// filter runs.
+fn with_list<T>(state: &Rc<RefCell<ScriptState>>, f: impl FnOnce(&mut TodoList) -> T) -> ScriptResult<T> {
+    let todo_list = state.borrow().dispatcher.list().clone();
+    let mut list = todo_list
+        .try_lock()
+        .map_err(|_| "The todo list can't be used here".to_string())?;
+    Ok(f(&mut list))
+}
+
+/// Run a command on the todo list, failing like `with_list` when it's locked
+fn dispatch(state: &Rc<RefCell<ScriptState>>, command: Command) -> ScriptResult<()> {
+    let dispatcher = state.borrow().dispatcher.clone();
+    let changed = with_list(state, |list| dispatcher.execute_on(list, command))??;
+    if changed {
+        state.borrow_mut().pending.changed = true;
+    }
+    Ok(())
+}
+
+fn set_status(state: &Rc<RefCell<ScriptState>>, id: &str, status: Status) -> ScriptResult<()> {
+    let id = parse_id(id)?;
+    if with_list(state, |list| list.get_item(id).is_none())? {
+        return Err(format!("No task with id {}", id).into());
+    }
+    dispatch(state, Command::SetStatus { id, status })
+}
+
+fn parse_id(id: &str) -> ScriptResult<Uuid> {
+    Uuid::parse_str(id).map_err(|_| format!("{:?} is not a task id", id).into())
+}
+
+fn parse_status(name: &str) -> ScriptResult<Status> {
+    [Status::NotStarted, Status::InProgress, Status::Completed]
+        .into_iter()
+        .find(|status| status_name(*status) == name)
+        .ok_or_else(|| format!("Unknown status {:?}", name).into())
+}
+
+fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::NotStarted => "not-started",
+        Status::InProgress => "in-progress",
+        Status::Completed => "completed",
+    }
+}
+
+fn priority_name(priority: Priority) -> &'static str {
+    match priority {
+        Priority::Low => "low",
+        Priority::Medium => "medium",
+        Priority::High => "high",
+    }
+}
+
+/// A task as scripts see it
+fn task_map(item: &TodoItem) -> Map {
+    let mut map = Map::new();
+    map.insert("id".into(), item.id().to_string().into());
+    map.insert("title".into(), item.title().into());
+    map.insert("description".into(), item.description().map_or(Dynamic::UNIT, |text| text.into()));
+    map.insert("status".into(), status_name(item.status()).into());
+    map.insert("priority".into(), priority_name(item.priority()).into());
+    map.insert("parent".into(), item.parent_id().map_or(Dynamic::UNIT, |id| id.to_string().into()));
+    let tags: Array = item.tags().iter().map(|tag| tag.as_str().into()).collect();
+    map.insert("tags".into(), tags.into());
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn host() -> (ScriptHost, Arc<Mutex<TodoList>>) {
+        let mut todo_list = TodoList::new("Test");
+        todo_list.create_item("Write tests");
+        let todo_list = Arc::new(Mutex::new(todo_list));
+        (ScriptHost::new(Dispatcher::new(todo_list.clone())), todo_list)
+    }
+
+    #[test]
+    fn test_scripts_edit_the_list() {
+        let (mut host, todo_list) = host();
+        let outcome = host.run(r#"
+            let id = add_task("From a script");
+            complete(id);
+            notify(`${tasks().len()} tasks`);
+        "#, None);
+        assert!(outcome.error.is_none(), "{:?}", outcome.error);
+        assert!(outcome.changed);
+        assert_eq!(outcome.messages, vec!["2 tasks"]);
+
+        let list = todo_list.lock().unwrap();
+        let added = list.all_items().into_iter().find(|item| item.title() == "From a script").unwrap();
+        assert_eq!(added.status(), Status::Completed);
+    }
+
+    #[test]
+    fn test_key_bindings_and_filters() {
+        let (mut host, todo_list) = host();
+        let id = todo_list.lock().unwrap().all_items()[0].id();
+        let outcome = host.run(r#"
+            define_filter("open", |task| task.status != "completed");
+            bind_key("ctrl+shift+d", || complete(selected()));
+            bind_key("Ctrl+Shift+O", || use_filter("open"));
+        "#, None);
+        assert!(outcome.error.is_none(), "{:?}", outcome.error);
+        assert!(!outcome.changed);
+        assert_eq!(host.filters(), vec!["open"]);
+
+        assert!(host.run_binding("Ctrl+Shift+X", Some(id)).is_none());
+        let outcome = host.run_binding("Ctrl+Shift+D", Some(id)).unwrap();
+        assert!(outcome.changed);
+        assert_eq!(todo_list.lock().unwrap().get_item(id).unwrap().status(), Status::Completed);
+
+        let filter = host.run_binding("Ctrl+Shift+O", None).unwrap().filter.unwrap().unwrap();
+        assert!(!filter(todo_list.lock().unwrap().get_item(id).unwrap()));
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let (mut host, _) = host();
+        assert!(host.run("set_status(\"nope\", \"completed\");", None).error.is_some());
+        assert!(host.run("use_filter(\"missing\");", None).error.is_some());
+        assert!(host.run("loop {}", None).error.is_some());
+        assert!(host.run("let x = ;", None).error.is_some());
+    }
+}
@@ -0,0 +1,156 @@
+// UI session state
+//
+// What the user was looking at when the app last exited: filters, scroll
+// position, selection and open details. It's kept apart from the todo data
+// and the config, in a TOML file next to the data file, so each data file
+// resumes its own view and a lost session never costs any tasks.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::core::prelude::{Priority, Status};
+use crate::core::storage;
+use crate::ui::todo_list_widget::FilterType;
+use crate::ui::ViewMode;
+
+/// View state of the todo list, restored on the next launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Whether the list or the board was shown
+    pub view: ViewMode,
+    pub scroll_offset: f32,
+    pub filter_type: FilterType,
+    pub filter_value: String,
+    pub status_filter: Option<Status>,
+    pub priority_filter: Option<Priority>,
+    /// Only items with this tag are shown
+    pub tag_filter: Option<String>,
+    /// Only items in this project are shown
+    pub project_filter: Option<Uuid>,
+    pub selected_item: Option<Uuid>,
+    /// Items with their details open
+    pub expanded_items: Vec<Uuid>,
+    /// Items with their children hidden
+    pub collapsed_items: Vec<Uuid>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            view: ViewMode::List,
+            scroll_offset: 0.0,
+            filter_type: FilterType::None,
+            filter_value: String::new(),
+            status_filter: None,
+            priority_filter: None,
+            tag_filter: None,
+            project_filter: None,
+            selected_item: None,
+            expanded_items: Vec::new(),
+            collapsed_items: Vec::new(),
+        }
+    }
+}
+
+impl Session {
+    /// Location of the session for a data file, e.g. `todos.session.toml` for `todos.json`
+    pub fn path_for(data_path: &Path) -> PathBuf {
+        data_path.with_extension("session.toml")
+    }
+
+    /// Load the session saved for a data file
+    ///
+    /// A missing file is a fresh start; an unreadable one is reported and
+    /// replaced with the default view, since it holds nothing worth keeping.
+    pub fn load(data_path: &Path) -> Result<Self, String> {
+        match storage::read_text(&Self::path_for(data_path))? {
+            Some(text) => toml::from_str(&text).map_err(|e| format!("Invalid session file: {}", e.message())),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Write the session next to a data file
+    pub fn save(&self, data_path: &Path) -> Result<(), String> {
+        let text = toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize session: {}", e))?;
+        storage::write_text(&Self::path_for(data_path), &text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::UiDriver;
+    use crate::ui::Widget;
+
+    #[test]
+    fn test_save_and_load() {
+        let data_path = std::env::temp_dir().join(format!("tewduwu-test-{}.json", Uuid::new_v4()));
+        assert_eq!(Session::load(&data_path).unwrap(), Session::default());
+
+        let session = Session {
+            view: ViewMode::Board,
+            scroll_offset: 120.0,
+            filter_type: FilterType::Title,
+            filter_value: "milk".to_string(),
+            status_filter: Some(Status::InProgress),
+            priority_filter: Some(Priority::High),
+            tag_filter: Some("errands".to_string()),
+            project_filter: Some(Uuid::new_v4()),
+            selected_item: Some(Uuid::new_v4()),
+            expanded_items: vec![Uuid::new_v4()],
+            collapsed_items: vec![Uuid::new_v4()],
+        };
+        session.save(&data_path).unwrap();
+        assert_eq!(Session::load(&data_path).unwrap(), session);
+
+        storage::remove(&Session::path_for(&data_path)).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_and_missing_keys_fall_back() {
+        let session: Session = toml::from_str("filter_value = \"x\"\nfuture_setting = 3\n").unwrap();
+        assert_eq!(session, Session { filter_value: "x".to_string(), ..Session::default() });
+    }
+
+    #[test]
+    fn test_widget_resumes_where_it_left_off() {
+        let (mut driver, _) = UiDriver::with_numbered_tasks(40);
+        let rows = driver.widget().shown_items();
+
+        let (x, y) = driver.widget().item_widget(rows[3]).unwrap().position();
+        driver.double_click_at(x + 200.0, y + 10.0);
+        driver.tick(0.016);
+        driver.widget_mut().select_item(Some(rows[12]));
+        driver.widget_mut().scroll_to(200.0);
+        let session = driver.widget().session();
+        assert_eq!(session.selected_item, Some(rows[12]));
+        assert_eq!(session.expanded_items, vec![rows[3]]);
+
+        // A fresh widget on the same list, as on the next launch
+        let mut restored = UiDriver::new(driver.widget().todo_list().lock().unwrap().clone());
+        restored.widget_mut().restore_session(&session);
+        assert_eq!(restored.widget().session(), session);
+        assert!(restored.widget().item_widget(rows[3]).unwrap().is_expanded());
+        let row_y = |driver: &UiDriver| driver.widget().item_widget(rows[12]).unwrap().position().1;
+        assert_eq!(row_y(&restored), row_y(&driver));
+    }
+
+    #[test]
+    fn test_deleted_items_are_forgotten() {
+        let (driver, ids) = UiDriver::with_tasks(&["Alpha"]);
+        let mut widget = driver.widget().clone();
+
+        widget.restore_session(&Session {
+            selected_item: Some(Uuid::new_v4()),
+            expanded_items: vec![Uuid::new_v4(), ids[0]],
+            scroll_offset: 10_000.0,
+            ..Session::default()
+        });
+        let session = widget.session();
+        assert_eq!(session.selected_item, None);
+        assert_eq!(session.expanded_items, vec![ids[0]]);
+        assert_eq!(session.scroll_offset, 0.0);
+    }
+}
@@ -1,12 +1,16 @@
 // Bloom composite shader
 // Combines the original scene with the bloom effect
 
+struct CompositeUniforms {
+    intensity: f32,
+    saturation: f32,
+}
+
 // Bind group layout
 @group(0) @binding(0) var original_texture: texture_2d<f32>;
 @group(0) @binding(1) var bloom_texture: texture_2d<f32>;
 @group(0) @binding(2) var texture_sampler: sampler;
-@group(0) @binding(3) var<uniform> bloom_intensity: f32;
-@group(0) @binding(4) var<uniform> bloom_saturation: f32;
+@group(0) @binding(3) var<uniform> bloom: CompositeUniforms;
 
 // Vertex shader
 @vertex
@@ -36,16 +40,16 @@ fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
     
     // Sample both textures
     let original = textureSample(original_texture, texture_sampler, uv);
-    let bloom = textureSample(bloom_texture, texture_sampler, uv);
+    let blurred = textureSample(bloom_texture, texture_sampler, uv);
     
     // Adjust bloom saturation
     let adjusted_bloom = vec4<f32>(
-        adjust_saturation(bloom.rgb, bloom_saturation),
-        bloom.a
+        adjust_saturation(blurred.rgb, bloom.saturation),
+        blurred.a
     );
     
     // Combine original with bloom
-    let result = original + (adjusted_bloom * bloom_intensity);
+    let result = original + (adjusted_bloom * bloom.intensity);
     
     // Add a subtle color shift to enhance the cyberpunk feel
     let glow_tint = vec3<f32>(1.1, 0.9, 1.2); // Slight purple tint
@@ -4,7 +4,6 @@
 // Bind group layout
 @group(0) @binding(0) var input_texture: texture_2d<f32>;
 @group(0) @binding(1) var input_sampler: sampler;
-@group(0) @binding(2) var output_texture: texture_storage_2d<rgba8unorm, write>;
 
 // Vertex shader
 @vertex
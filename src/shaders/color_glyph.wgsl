@@ -0,0 +1,66 @@
+// Color glyph shader
+// Draws instanced rectangles textured from the color glyph atlas, faded with
+// the text they're part of and cut off outside their clip rect.
+// Sprites come in logical pixels.
+
+struct Screen {
+    size: vec2<f32>,        // in physical pixels
+    scale_factor: f32,      // physical pixels per logical pixel
+    _padding: f32,
+};
+
+@group(0) @binding(0) var<uniform> screen: Screen;
+@group(0) @binding(1) var atlas: texture_2d<f32>;
+@group(0) @binding(2) var atlas_sampler: sampler;
+
+struct Instance {
+    @location(0) rect: vec4<f32>,         // left, top, width, height in logical pixels
+    @location(1) uv: vec4<f32>,           // min u, min v, max u, max v in the atlas
+    @location(2) clip: vec4<f32>,         // min x, min y, max x, max y in logical pixels
+    @location(3) params: vec4<f32>,       // opacity, unused
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) clip: vec4<f32>,
+    @location(2) alpha: f32,
+};
+
+// Vertex shader
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, instance: Instance) -> VertexOutput {
+    // Two triangles covering the sprite
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0)
+    );
+
+    let corner = corners[vertex_index];
+    let pixel = (instance.rect.xy + corner * instance.rect.zw) * screen.scale_factor;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(pixel.x / screen.size.x * 2.0 - 1.0, 1.0 - pixel.y / screen.size.y * 2.0, 0.0, 1.0);
+    out.uv = mix(instance.uv.xy, instance.uv.zw, corner);
+    out.clip = instance.clip * screen.scale_factor;
+    out.alpha = instance.params.x;
+    return out;
+}
+
+// Fragment shader
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Sampled before the clip test: sampling has to happen in uniform control flow
+    let color = textureSample(atlas, atlas_sampler, in.uv);
+
+    // The position builtin holds the pixel's coordinates here
+    let pixel = in.position.xy;
+    if (pixel.x < in.clip.x || pixel.y < in.clip.y || pixel.x > in.clip.z || pixel.y > in.clip.w) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * in.alpha);
+}
@@ -1,12 +1,15 @@
 // Extract bright areas shader
 // This is the first step in the bloom pipeline
 
+struct ExtractUniforms {
+    threshold: f32,
+    intensity: f32,
+}
+
 // Bind group layout
 @group(0) @binding(0) var input_texture: texture_2d<f32>;
 @group(0) @binding(1) var input_sampler: sampler;
-@group(0) @binding(2) var output_texture: texture_storage_2d<rgba8unorm, write>;
-@group(0) @binding(3) var<uniform> threshold: f32;
-@group(0) @binding(4) var<uniform> intensity: f32;
+@group(0) @binding(2) var<uniform> settings: ExtractUniforms;
 
 // Vertex shader
 @vertex
@@ -35,6 +38,7 @@ fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
     let brightness = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722)); // Luminance formula
     
     // Only keep pixels brighter than the threshold
+    let threshold = settings.threshold;
     var bright_color = vec4<f32>(0.0, 0.0, 0.0, 0.0);
     if (brightness > threshold) {
         // Apply soft threshold
@@ -44,7 +48,7 @@ fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
         soft = clamp(soft / (2.0 * knee), 0.0, 1.0);
         
         // Apply threshold with smoothing
-        bright_color = color * soft * intensity;
+        bright_color = color * soft * settings.intensity;
     }
     
     return bright_color;
@@ -1,12 +1,23 @@
 // Neon glow shader
-// Creates a vibrant glow around UI elements
+// Spreads what was drawn into the glow mask into a halo around it
+
+struct GlowUniforms {
+    intensity: f32,
+    size: f32,
+    // Animation time in seconds, and how far the intensity swings with it
+    time: f32,
+    pulse: f32,
+}
+
+// Seconds for the glow to swell and fade once
+const PULSE_PERIOD: f32 = 2.4;
 
 // Bind group layout
 @group(0) @binding(0) var input_texture: texture_2d<f32>;
 @group(0) @binding(1) var input_sampler: sampler;
-@group(0) @binding(2) var<uniform> glow_color: vec4<f32>;
-@group(0) @binding(3) var<uniform> glow_intensity: f32;
-@group(0) @binding(4) var<uniform> glow_size: f32;
+@group(0) @binding(2) var<uniform> glow: GlowUniforms;
+// What glows, in the color it glows in; transparent everywhere else
+@group(0) @binding(3) var mask_texture: texture_2d<f32>;
 
 // Vertex shader
 @vertex
@@ -31,38 +42,29 @@ fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
     // Sample the input texture
     let original = textureSample(input_texture, input_sampler, uv);
     
-    // Calculate the amount of glow for this pixel
-    var glow_amount = 0.0;
-    let samples = 16; // Number of samples around the pixel
+    // Gather the mask's colors on two rings around the pixel, the inner one
+    // at half the glow size so the halo fades out instead of ending in a ring
+    var glow_light = vec3<f32>(0.0);
+    let samples = 16; // Number of samples on each ring
     
     for (var i = 0; i < samples; i = i + 1) {
         // Calculate angle and distance for this sample
         let angle = f32(i) * 6.28318 / f32(samples);
-        let dist = glow_size / f32(size.x);
+        let dist = glow.size / f32(size.x);
+        let direction = vec2<f32>(cos(angle), sin(angle) * f32(size.x) / f32(size.y));
         
-        // Calculate sample offset
-        let offset_x = cos(angle) * dist;
-        let offset_y = sin(angle) * dist;
-        
-        // Sample at the offset position
-        let sample_uv = uv + vec2<f32>(offset_x, offset_y);
-        let sample_color = textureSample(input_texture, input_sampler, sample_uv);
-        
-        // Add to glow amount based on the sample brightness
-        glow_amount = glow_amount + max(
-            dot(sample_color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722)), 
-            0.0
-        );
+        // The mask is drawn with alpha blending, so its colors are already weighted by coverage
+        let outer = textureSample(mask_texture, input_sampler, uv + direction * dist);
+        let inner = textureSample(mask_texture, input_sampler, uv + direction * dist * 0.5);
+        glow_light = glow_light + outer.rgb + inner.rgb;
     }
     
-    // Average and apply intensity
-    glow_amount = glow_amount / f32(samples) * glow_intensity;
-    
-    // Create the glow effect
-    let glow = glow_color * glow_amount;
+    // Average and apply intensity, swelling and fading if the glow pulses
+    let pulse = 1.0 + glow.pulse * sin(glow.time * 6.28318 / PULSE_PERIOD);
+    glow_light = glow_light / f32(2 * samples) * glow.intensity * pulse;
     
     // Add the glow to the original color
-    let result = original + vec4<f32>(glow.rgb * glow.a, 0.0);
+    let result = original + vec4<f32>(glow_light, 0.0);
     
     // Apply a slight color shift for a more vibrant effect
     let final_color = vec4<f32>(
@@ -0,0 +1,122 @@
+// Quad shader
+// Draws instanced rectangles with rounded corners and borders, plus lines
+// (rotated rectangles) and circles (fully rounded squares), antialiased with
+// a signed distance to the rounded box and cut off outside their clip rect.
+// Fills can blend into a second color, along a direction or out from the center,
+// and edges can be softened into a blur for shadows.
+// Quads come in logical pixels; edges are antialiased over one physical pixel.
+
+struct Screen {
+    size: vec2<f32>,        // in physical pixels
+    scale_factor: f32,      // physical pixels per logical pixel
+    _padding: f32,
+};
+
+@group(0) @binding(0) var<uniform> screen: Screen;
+
+struct Instance {
+    @location(0) rect: vec4<f32>,         // center x, center y, half width, half height in logical pixels
+    @location(1) color: vec4<f32>,
+    @location(2) border_color: vec4<f32>,
+    @location(3) params: vec4<f32>,       // corner radius, border width, rotation in radians, softness
+    @location(4) clip: vec4<f32>,         // min x, min y, max x, max y in logical pixels
+    @location(5) end_color: vec4<f32>,    // fill color where the gradient ends
+    @location(6) gradient: vec4<f32>,     // linear direction x, y, 1 if radial, unused
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) local: vec2<f32>,        // offset from the quad's center before rotation
+    @location(1) half_size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) border_color: vec4<f32>,
+    @location(4) radius: f32,
+    @location(5) border: f32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+    @location(7) end_color: vec4<f32>,
+    @location(8) @interpolate(flat) gradient: vec4<f32>,
+    @location(9) softness: f32,
+};
+
+// Vertex shader
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, instance: Instance) -> VertexOutput {
+    // Two triangles covering the quad
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0)
+    );
+
+    // Grow so the antialiased or softened edge isn't cut off
+    let half_size = instance.rect.zw;
+    let grow = 1.0 + instance.params.w;
+    let local = corners[vertex_index] * (half_size + vec2<f32>(grow, grow));
+    let c = cos(instance.params.z);
+    let s = sin(instance.params.z);
+    let point = instance.rect.xy + vec2<f32>(local.x * c - local.y * s, local.x * s + local.y * c);
+    let pixel = point * screen.scale_factor;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(pixel.x / screen.size.x * 2.0 - 1.0, 1.0 - pixel.y / screen.size.y * 2.0, 0.0, 1.0);
+    out.local = local;
+    out.half_size = half_size;
+    out.color = instance.color;
+    out.border_color = instance.border_color;
+    out.radius = instance.params.x;
+    out.border = instance.params.y;
+    out.clip = instance.clip;
+    out.end_color = instance.end_color;
+    out.gradient = instance.gradient;
+    out.softness = instance.params.w;
+    return out;
+}
+
+// Distance from a point to the edge of a rounded box, negative inside
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius, radius);
+    return length(max(q, vec2<f32>(0.0, 0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+// Fragment shader
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The position builtin holds the physical pixel's coordinates here
+    let point = in.position.xy / screen.scale_factor;
+    if (point.x < in.clip.x || point.y < in.clip.y || point.x > in.clip.z || point.y > in.clip.w) {
+        discard;
+    }
+
+    // Distances are logical; scaled, they count the physical pixels antialiasing spans
+    let radius = min(in.radius, min(in.half_size.x, in.half_size.y));
+    let distance = rounded_box(in.local, in.half_size, radius);
+    var coverage = clamp(0.5 - distance * screen.scale_factor, 0.0, 1.0);
+    if (in.softness > 0.0) {
+        // Soft edges fade out over `softness` pixels either side of the edge
+        coverage = 1.0 - smoothstep(-in.softness, in.softness, distance);
+    }
+
+    // The border is the band within `border` pixels of the edge
+    var border_amount = 0.0;
+    if (in.border > 0.0) {
+        border_amount = clamp((distance + in.border) * screen.scale_factor + 0.5, 0.0, 1.0);
+    }
+    // How far along the gradient the pixel is, 0 at its start and 1 at its end
+    var t = 0.0;
+    if (in.gradient.z > 0.5) {
+        // Radial: from the center to the middle of the sides
+        t = length(in.local / max(in.half_size, vec2<f32>(1.0, 1.0)));
+    } else {
+        // Linear: from the edge the direction points away from to the opposite one
+        let direction = in.gradient.xy;
+        let extent = abs(direction.x) * in.half_size.x + abs(direction.y) * in.half_size.y;
+        t = dot(in.local, direction) / max(extent, 1.0) * 0.5 + 0.5;
+    }
+    let fill = mix(in.color, in.end_color, clamp(t, 0.0, 1.0));
+
+    let color = mix(fill, in.border_color, border_amount);
+    return vec4<f32>(color.rgb, color.a * coverage);
+}
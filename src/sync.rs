@@ -0,0 +1,292 @@
+// CalDAV sync over HTTP (caldav feature)
+//
+// The engine in tewduwu-core decides what to push and what to pull; this is
+// the transport it talks through. A task collection only needs three requests:
+// a REPORT listing every VTODO with its ETag, a PUT per changed task and a
+// DELETE per removed one. The ETags make both conditional, so a task changed
+// on the server since we listed it fails the sync instead of being overwritten.
+
+use std::future::Future;
+use reqwest::{Client, Method, StatusCode, Url};
+
+use crate::config::SyncConfig;
+use crate::core::sync::caldav::{CalDavServer, RemoteObject, Resource};
+
+/// How long a request may take before the sync gives up
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Asks for every VTODO in the collection, with its ETag
+const CALENDAR_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:getetag/><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VTODO"/></c:comp-filter></c:filter>
+</c:calendar-query>"#;
+
+/// A CalDAV task collection reached over HTTP
+#[derive(Clone)]
+pub struct HttpCalDav {
+    client: Client,
+    url: Url,
+    username: String,
+    password: String,
+}
+
+impl HttpCalDav {
+    /// Connect as the `[sync]` table says, or `None` while syncing is off
+    pub fn from_config(config: &SyncConfig) -> Option<Result<Self, String>> {
+        let url = config.url.as_deref()?;
+        Some(Self::new(url, &config.username, &config.password))
+    }
+
+    pub fn new(url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let mut url = Url::parse(url).map_err(|e| format!("Invalid sync URL {}: {}", url, e))?;
+        // Task files are joined onto the collection, which only works from inside it
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to set up the sync client: {}", e))?;
+        Ok(Self {
+            client,
+            url,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    fn request(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        if self.username.is_empty() {
+            request
+        } else {
+            request.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    fn resolve(&self, href: &str) -> Result<Url, String> {
+        self.url.join(href).map_err(|e| format!("Invalid task location {}: {}", href, e))
+    }
+}
+
+impl CalDavServer for HttpCalDav {
+    fn fetch_all(&self) -> impl Future<Output = Result<Vec<RemoteObject>, String>> + Send {
+        let request = self
+            .request(Method::from_bytes(b"REPORT").expect("REPORT is a valid method"), self.url.clone())
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(CALENDAR_QUERY);
+        async move {
+            let response = request.send().await.map_err(|e| format!("Failed to reach the sync server: {}", e))?;
+            let status = response.status();
+            if status != StatusCode::MULTI_STATUS {
+                return Err(format!("The sync server refused to list tasks: {}", status));
+            }
+            let body = response.text().await.map_err(|e| format!("Failed to read the task list: {}", e))?;
+            Ok(parse_multistatus(&body))
+        }
+    }
+
+    fn put(&self, uid: &str, data: &str, existing: Option<&Resource>) -> impl Future<Output = Result<Resource, String>> + Send {
+        let href = existing.map_or_else(|| format!("{}.ics", encode_segment(uid)), |existing| existing.href.clone());
+        let request = self.resolve(&href).map(|url| {
+            let path = url.path().to_string();
+            let request = self
+                .request(Method::PUT, url)
+                .header("Content-Type", "text/calendar; charset=utf-8")
+                .body(data.to_string());
+            // Only overwrite the version we synced, and never a task we didn't know about
+            let request = match existing {
+                Some(existing) => request.header("If-Match", existing.etag.clone()),
+                None => request.header("If-None-Match", "*"),
+            };
+            (path, request)
+        });
+        async move {
+            let (href, request) = request?;
+            let response = request.send().await.map_err(|e| format!("Failed to reach the sync server: {}", e))?;
+            match response.status() {
+                status if status.is_success() => {}
+                StatusCode::PRECONDITION_FAILED => return Err("A task changed on the server during the sync".to_string()),
+                status => return Err(format!("The sync server refused a task: {}", status)),
+            }
+            // Servers that rewrite what they're sent don't return an ETag; the
+            // next sync then pulls their version back, which changes nothing here
+            let etag = response
+                .headers()
+                .get("ETag")
+                .and_then(|etag| etag.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            Ok(Resource { href, etag })
+        }
+    }
+
+    fn delete(&self, resource: &Resource) -> impl Future<Output = Result<(), String>> + Send {
+        let request = self
+            .resolve(&resource.href)
+            .map(|url| self.request(Method::DELETE, url).header("If-Match", resource.etag.clone()));
+        async move {
+            let response = request?.send().await.map_err(|e| format!("Failed to reach the sync server: {}", e))?;
+            match response.status() {
+                // Already gone is as good as deleted
+                status if status.is_success() || status == StatusCode::NOT_FOUND => Ok(()),
+                StatusCode::PRECONDITION_FAILED => Err("A task changed on the server during the sync".to_string()),
+                status => Err(format!("The sync server refused to delete a task: {}", status)),
+            }
+        }
+    }
+}
+
+/// The tasks of a REPORT's multistatus response; entries without calendar data are skipped
+fn parse_multistatus(xml: &str) -> Vec<RemoteObject> {
+    elements(xml, "response")
+        .into_iter()
+        .filter_map(|response| {
+            let href = elements(response, "href").into_iter().next()?;
+            let etag = elements(response, "getetag").into_iter().next().unwrap_or_default();
+            let data = elements(response, "calendar-data").into_iter().next()?;
+            Some(RemoteObject {
+                resource: Resource { href: unescape_xml(href.trim()), etag: unescape_xml(etag.trim()) },
+                data: unescape_xml(data),
+            })
+        })
+        .collect()
+}
+
+/// The contents of every element with a local name, whatever its namespace prefix
+///
+/// Enough XML for a multistatus response; elements of the same name must not nest.
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let tag_end = rest.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(rest.len());
+        let tag = &rest[..tag_end];
+        let local = tag.rsplit(':').next().unwrap_or(tag);
+        if local != name || tag.starts_with(['/', '?', '!']) {
+            continue;
+        }
+        let Some(open_end) = rest.find('>') else {
+            break;
+        };
+        if rest[..open_end].ends_with('/') {
+            found.push("");
+            rest = &rest[open_end + 1..];
+            continue;
+        }
+        let content = &rest[open_end + 1..];
+        let close = format!("</{}>", tag);
+        let Some(content_end) = content.find(&close) else {
+            break;
+        };
+        found.push(&content[..content_end]);
+        rest = &content[content_end + close.len()..];
+    }
+    found
+}
+
+/// Resolve entities and CDATA sections in text content
+fn unescape_xml(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(['&', '<']) {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            unescaped.push_str(&cdata[..end]);
+            rest = cdata.get(end + 3..).unwrap_or_default();
+            continue;
+        }
+        let entity_end = rest.find(';').filter(|&end| end <= 10);
+        let entity = entity_end.map(|end| &rest[1..end]);
+        let character = match entity {
+            Some("lt") => Some('<'),
+            Some("gt") => Some('>'),
+            Some("amp") => Some('&'),
+            Some("quot") => Some('"'),
+            Some("apos") => Some('\''),
+            Some(code) => code
+                .strip_prefix("#x")
+                .or_else(|| code.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| code.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+            None => None,
+        };
+        match (character, entity_end) {
+            (Some(character), Some(end)) => {
+                unescaped.push(character);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                unescaped.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Percent-encode a UID for use as a file name in the collection
+fn encode_segment(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_a_multistatus_response() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/dav/calendars/me/tasks/</d:href>
+    <d:propstat><d:prop><d:getetag/></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/calendars/me/tasks/a%40b.ics</d:href>
+    <d:propstat><d:prop>
+      <d:getetag>&quot;abc&quot;</d:getetag>
+      <cal:calendar-data>BEGIN:VCALENDAR&#13;
+SUMMARY:Fish &amp; chips&#13;
+END:VCALENDAR&#13;
+</cal:calendar-data>
+    </d:prop></d:propstat>
+  </d:response>
+  <response xmlns="DAV:"><href>/other.ics</href><getetag>"2"</getetag><calendar-data xmlns="urn:ietf:params:xml:ns:caldav"><![CDATA[BEGIN:VCALENDAR <x>]]></calendar-data></response>
+</d:multistatus>"#;
+        let objects = parse_multistatus(xml);
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].resource, Resource { href: "/dav/calendars/me/tasks/a%40b.ics".to_string(), etag: "\"abc\"".to_string() });
+        assert_eq!(objects[0].data, "BEGIN:VCALENDAR\r\nSUMMARY:Fish & chips\r\nEND:VCALENDAR\r\n");
+        assert_eq!(objects[1].resource.etag, "\"2\"");
+        assert_eq!(objects[1].data, "BEGIN:VCALENDAR <x>");
+    }
+
+    #[test]
+    fn test_task_locations() {
+        let server = HttpCalDav::new("https://cloud.example.com/dav/tasks", "me", "secret").unwrap();
+        assert_eq!(server.url.as_str(), "https://cloud.example.com/dav/tasks/");
+        assert_eq!(
+            server.resolve(&format!("{}.ics", encode_segment("20240301-abc@example.com"))).unwrap().as_str(),
+            "https://cloud.example.com/dav/tasks/20240301-abc%40example.com.ics"
+        );
+        assert_eq!(server.resolve("/dav/tasks/x.ics").unwrap().as_str(), "https://cloud.example.com/dav/tasks/x.ics");
+        assert!(HttpCalDav::new("not a url", "", "").is_err());
+    }
+}
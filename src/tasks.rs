@@ -0,0 +1,198 @@
+// Background tasks
+//
+// Slow work such as file I/O runs off the render thread: on a small tokio
+// runtime natively, and on the browser's event loop via `spawn_local` on the
+// web. Every task finishes with a `TaskEvent` sent over a channel that the main
+// loop drains once per frame, so results are handled on the main thread with
+// full access to the app state, just like UI events.
+
+use std::future::Future;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+#[cfg(any(
+    all(feature = "caldav", not(target_arch = "wasm32")),
+    all(feature = "notifications", not(target_arch = "wasm32")),
+))]
+use std::sync::{Arc, Mutex};
+
+#[cfg(any(
+    all(feature = "caldav", not(target_arch = "wasm32")),
+    all(feature = "notifications", not(target_arch = "wasm32")),
+))]
+use crate::core::TodoList;
+#[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+use crate::core::sync::caldav::SyncOutcome;
+
+/// Result of a background task, handled on the main thread
+#[derive(Debug)]
+pub enum TaskEvent {
+    /// Writing the todo list finished; `announce` if the user asked for it
+    Saved { announce: bool, result: Result<(), String> },
+    /// Writing the recovery journal started failing
+    JournalFailed { error: String },
+    /// A CalDAV sync of `list` finished; `announce` if the user asked for it
+    #[cfg(all(feature = "caldav", not(target_arch = "wasm32")))]
+    Synced {
+        list: Arc<Mutex<TodoList>>,
+        result: Result<SyncOutcome, String>,
+        announce: bool,
+    },
+    /// Snooze was clicked on the desktop notification for task `id` of `list`
+    #[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+    Snoozed { list: Arc<Mutex<TodoList>>, id: uuid::Uuid },
+    /// Files were picked in the file dialog to attach to task `id`; none if it was cancelled
+    #[cfg(all(feature = "file-picker", not(target_arch = "wasm32")))]
+    FilesPicked { id: uuid::Uuid, paths: Vec<std::path::PathBuf> },
+}
+
+/// Runs background tasks and collects their results
+pub struct BackgroundTasks {
+    #[cfg(not(target_arch = "wasm32"))]
+    runtime: tokio::runtime::Runtime,
+    sender: Sender<TaskEvent>,
+    receiver: Receiver<TaskEvent>,
+
+    /// Tasks spawned whose result hasn't been polled yet
+    pending: usize,
+}
+
+impl BackgroundTasks {
+    /// Start the runtime
+    pub fn new() -> Result<Self, String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("tewduwu-tasks")
+            .build()
+            .map_err(|e| format!("Failed to start background runtime: {}", e))?;
+
+        let (sender, receiver) = mpsc::channel();
+        Ok(Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            runtime,
+            sender,
+            receiver,
+            pending: 0,
+        })
+    }
+
+    /// A channel for long-lived workers to report on, e.g. the save worker
+    ///
+    /// Their events aren't counted as pending tasks, so `wait_idle` doesn't wait for them.
+    pub fn sender(&self) -> Sender<TaskEvent> {
+        self.sender.clone()
+    }
+
+    /// Run a future in the background, e.g. a network request
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn<F>(&mut self, task: F)
+    where
+        F: Future<Output = TaskEvent> + Send + 'static,
+    {
+        let sender = self.sender.clone();
+        self.pending += 1;
+        self.runtime.spawn(async move {
+            let _ = sender.send(task.await);
+        });
+    }
+
+    /// Run a future in the background, e.g. a network request
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn<F>(&mut self, task: F)
+    where
+        F: Future<Output = TaskEvent> + 'static,
+    {
+        let sender = self.sender.clone();
+        self.pending += 1;
+        wasm_bindgen_futures::spawn_local(async move {
+            let _ = sender.send(task.await);
+        });
+    }
+
+    /// Run blocking work such as file I/O in the background
+    ///
+    /// The browser has no threads to spare, so there it runs right away.
+    pub fn spawn_blocking<F>(&mut self, task: F)
+    where
+        F: FnOnce() -> TaskEvent + Send + 'static,
+    {
+        self.pending += 1;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let sender = self.sender.clone();
+            self.runtime.spawn_blocking(move || {
+                let _ = sender.send(task());
+            });
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = self.sender.send(task());
+        }
+    }
+
+    /// Results of the tasks that finished since the last call, without blocking
+    pub fn poll(&mut self) -> Vec<TaskEvent> {
+        let events: Vec<TaskEvent> = self.receiver.try_iter().collect();
+        self.pending = self.pending.saturating_sub(events.len());
+        events
+    }
+
+    /// Whether every spawned task has been polled
+    pub fn is_idle(&self) -> bool {
+        self.pending == 0
+    }
+
+    /// Block until every spawned task finished or `timeout` passed, e.g. before exiting
+    ///
+    /// In the browser nothing can be waited for, so this only polls.
+    pub fn wait_idle(&mut self, timeout: Duration) -> Vec<TaskEvent> {
+        let mut events = self.poll();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let deadline = std::time::Instant::now() + timeout;
+            while self.pending > 0 {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                match self.receiver.recv_timeout(remaining) {
+                    Ok(event) => {
+                        self.pending -= 1;
+                        events.push(event);
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = timeout;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(announce: bool) -> TaskEvent {
+        TaskEvent::Saved { announce, result: Ok(()) }
+    }
+
+    #[test]
+    fn test_results_arrive_on_the_main_thread() {
+        let mut tasks = BackgroundTasks::new().unwrap();
+        tasks.spawn_blocking(|| saved(false));
+        tasks.spawn(async { saved(true) });
+        assert!(!tasks.is_idle());
+
+        let mut announced: Vec<bool> = tasks
+            .wait_idle(Duration::from_secs(5))
+            .into_iter()
+            .filter_map(|event| match event {
+                TaskEvent::Saved { announce, .. } => Some(announce),
+                _ => None,
+            })
+            .collect();
+        announced.sort();
+        assert_eq!(announced, vec![false, true]);
+        assert!(tasks.is_idle());
+        assert!(tasks.poll().is_empty());
+    }
+}
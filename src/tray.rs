@@ -0,0 +1,242 @@
+// System tray icon (tray feature)
+//
+// The icon's menu opens the quick-add popup and shows or hides the main
+// window, and its tooltip counts the overdue tasks of every list. Menu picks
+// come in on whatever thread the platform delivers them on and are passed to
+// a callback, which main forwards to the event loop.
+
+use crate::i18n::{tr, tr_count};
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+/// Width and height of the icon's image, in pixels
+pub const ICON_SIZE: u32 = 32;
+
+/// What a tray menu entry asks the app to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayCommand {
+    /// Open the quick-add popup
+    QuickAdd,
+    /// Hide the main window if it's shown, show it otherwise
+    ToggleWindow,
+}
+
+impl TrayCommand {
+    /// Id of the command's menu entry
+    pub fn id(self) -> &'static str {
+        match self {
+            TrayCommand::QuickAdd => "quick-add",
+            TrayCommand::ToggleWindow => "toggle-window",
+        }
+    }
+
+    /// The command of a menu entry
+    pub fn from_id(id: &str) -> Option<Self> {
+        [TrayCommand::QuickAdd, TrayCommand::ToggleWindow]
+            .into_iter()
+            .find(|command| command.id() == id)
+    }
+}
+
+/// Tooltip of the icon with this many tasks overdue
+pub fn tooltip(overdue: usize) -> String {
+    tr_count("tray-tooltip", "overdue", overdue)
+}
+
+/// Label of the show/hide entry while the main window is or isn't shown
+pub fn toggle_label(window_shown: bool) -> String {
+    tr(if window_shown { "tray-hide-window" } else { "tray-show-window" })
+}
+
+/// RGBA pixels of the icon: a neon pink ring around a cyan dot, on transparency
+pub fn icon_rgba(size: u32) -> Vec<u8> {
+    const PINK: [f32; 3] = [1.0, 0.255, 0.639];
+    const CYAN: [f32; 3] = [0.0, 0.95, 1.0];
+    let center = size as f32 / 2.0;
+    let (ring, ring_width, dot) = (center * 0.8, center * 0.2, center * 0.35);
+
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = (x as f32 + 0.5 - center).hypot(y as f32 + 0.5 - center);
+            // Coverage fades over a pixel at each edge
+            let ring_coverage = (ring_width / 2.0 + 0.5 - (distance - ring).abs()).clamp(0.0, 1.0);
+            let dot_coverage = (dot + 0.5 - distance).clamp(0.0, 1.0);
+            let (color, coverage) = if dot_coverage > 0.0 { (CYAN, dot_coverage) } else { (PINK, ring_coverage) };
+            pixels.extend(color.map(|channel| (channel * 255.0).round() as u8));
+            pixels.push((coverage * 255.0).round() as u8);
+        }
+    }
+    pixels
+}
+
+/// Changes to what the tray shows
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+enum Update {
+    Overdue(usize),
+    WindowShown(bool),
+}
+
+/// The icon and the menu entry whose label changes
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+struct TrayState {
+    icon: TrayIcon,
+    toggle: MenuItem,
+}
+
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+impl TrayState {
+    fn build() -> Result<Self, String> {
+        let quick_add = MenuItem::with_id(TrayCommand::QuickAdd.id(), tr("tray-quick-add"), true, None);
+        let toggle = MenuItem::with_id(TrayCommand::ToggleWindow.id(), toggle_label(true), true, None);
+        let menu = Menu::new();
+        menu.append(&quick_add)
+            .and_then(|_| menu.append(&toggle))
+            .map_err(|e| format!("Failed to build the tray menu: {}", e))?;
+
+        let image = Icon::from_rgba(icon_rgba(ICON_SIZE), ICON_SIZE, ICON_SIZE)
+            .map_err(|e| format!("Invalid tray icon: {}", e))?;
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip(tooltip(0))
+            .with_icon(image)
+            .build()
+            .map_err(|e| format!("Failed to add the tray icon: {}", e))?;
+        Ok(Self { icon, toggle })
+    }
+
+    fn apply(&self, update: Update) {
+        match update {
+            Update::Overdue(count) => {
+                if let Err(e) = self.icon.set_tooltip(Some(tooltip(count))) {
+                    tracing::error!("Failed to update the tray tooltip: {}", e);
+                }
+                // The count as a badge beside the icon, where the platform shows one
+                self.icon.set_title((count > 0).then(|| count.to_string()));
+            }
+            Update::WindowShown(shown) => self.toggle.set_text(toggle_label(shown)),
+        }
+    }
+}
+
+/// The icon in the system tray (tray feature)
+///
+/// On Linux the icon lives on a GTK thread of its own, which updates are sent
+/// to; elsewhere it lives on the event loop's thread, as those platforms require.
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+pub struct Tray {
+    #[cfg(target_os = "linux")]
+    updates: std::sync::mpsc::Sender<Update>,
+    #[cfg(not(target_os = "linux"))]
+    state: TrayState,
+}
+
+#[cfg(all(feature = "tray", not(target_arch = "wasm32")))]
+impl Tray {
+    /// Put the icon in the tray; `on_command` is called with each menu entry picked
+    pub fn new(on_command: impl Fn(TrayCommand) + Send + Sync + 'static) -> Result<Self, String> {
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some(command) = TrayCommand::from_id(event.id.as_ref()) {
+                on_command(command);
+            }
+        }));
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::sync::mpsc;
+            /// How often the GTK thread looks for updates
+            const UPDATE_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+            let (updates, received) = mpsc::channel();
+            let (started, start) = mpsc::channel();
+            std::thread::Builder::new()
+                .name("tray".to_string())
+                .spawn(move || {
+                    let state = gtk::init()
+                        .map_err(|e| format!("Failed to start GTK for the tray: {}", e))
+                        .and_then(|_| TrayState::build());
+                    let state = match state {
+                        Ok(state) => state,
+                        Err(e) => {
+                            let _ = started.send(Err(e));
+                            return;
+                        }
+                    };
+                    let _ = started.send(Ok(()));
+                    gtk::glib::timeout_add_local(UPDATE_POLL, move || {
+                        while let Ok(update) = received.try_recv() {
+                            state.apply(update);
+                        }
+                        gtk::glib::ControlFlow::Continue
+                    });
+                    gtk::main();
+                })
+                .map_err(|e| format!("Failed to start the tray thread: {}", e))?;
+            start.recv().map_err(|_| "The tray thread stopped".to_string())??;
+            Ok(Self { updates })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Ok(Self { state: TrayState::build()? })
+        }
+    }
+
+    /// Show this many overdue tasks in the tooltip and the badge
+    pub fn set_overdue(&self, count: usize) {
+        self.send(Update::Overdue(count));
+    }
+
+    /// Offer to hide the main window while it's shown, and to show it otherwise
+    pub fn set_window_shown(&self, shown: bool) {
+        self.send(Update::WindowShown(shown));
+    }
+
+    fn send(&self, update: Update) {
+        #[cfg(target_os = "linux")]
+        let _ = self.updates.send(update);
+        #[cfg(not(target_os = "linux"))]
+        self.state.apply(update);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_ids_round_trip() {
+        for command in [TrayCommand::QuickAdd, TrayCommand::ToggleWindow] {
+            assert_eq!(TrayCommand::from_id(command.id()), Some(command));
+        }
+        assert_eq!(TrayCommand::from_id("quit"), None);
+    }
+
+    #[test]
+    fn test_tooltip_counts_overdue_tasks() {
+        // Whatever the locale, nothing overdue isn't a count and the others are
+        assert!(!tooltip(0).contains('0'));
+        assert!(tooltip(3).contains('3'));
+        assert_ne!(tooltip(1), tooltip(2));
+        assert_ne!(toggle_label(true), toggle_label(false));
+    }
+
+    #[test]
+    fn test_icon_is_a_ring_around_a_dot() {
+        let pixels = icon_rgba(ICON_SIZE);
+        assert_eq!(pixels.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * ICON_SIZE + x) * 4) as usize;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        };
+        // Transparent corner, opaque cyan middle, pink ring near the edge
+        assert_eq!(pixel(0, 0)[3], 0);
+        assert_eq!(pixel(16, 16), [0, 242, 255, 255]);
+        let ring = pixel(16, 3);
+        assert_eq!(ring[0], 255);
+        assert!(ring[3] > 200);
+        // Between the two
+        assert_eq!(pixel(16, 8)[3], 0);
+    }
+}
@@ -0,0 +1,145 @@
+// Scratch memory for rendering, reused from frame to frame
+//
+// Most of the text a frame draws is translated or formatted strings. Building
+// those per call meant dozens of short-lived Strings per widget per frame. The
+// arena is owned by the application and lent to each frame's `RenderContext`;
+// its buffers keep their capacity, so after the first few frames drawing
+// allocates nothing for them.
+//
+// Text inside a clip rect is held here until the end of the pass, when each
+// clip rect gets its own scissored glyph draw.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::ops::Range;
+
+use crate::i18n;
+use crate::ui::context::ClipRect;
+
+/// Where and how to draw a run of clipped text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextRun {
+    pub clip: ClipRect,
+    pub x: f32,
+    pub y: f32,
+    pub size: f32,
+    pub color: [f32; 4],
+}
+
+/// Buffers lent to a frame's `RenderContext`
+#[derive(Default)]
+pub struct FrameArena {
+    /// Text formatted by `RenderContext::draw_fmt`, overwritten on every call
+    text: String,
+
+    /// Translated messages, valid for `locale_generation`
+    labels: HashMap<&'static str, String>,
+    locale_generation: u64,
+
+    /// Clipped text waiting for its draw, all runs in one buffer
+    clipped_text: String,
+    clipped_runs: Vec<(TextRun, Range<usize>)>,
+}
+
+impl FrameArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prepare for a new frame, dropping translations if the language changed
+    pub fn begin_frame(&mut self) {
+        let generation = i18n::locale_generation();
+        if generation != self.locale_generation {
+            self.labels.clear();
+            self.locale_generation = generation;
+        }
+        self.clear_clipped();
+    }
+
+    /// Format text into the shared buffer
+    pub fn format(&mut self, args: fmt::Arguments) -> &str {
+        self.text.clear();
+        // Writing to a String only fails if a Display impl does
+        let _ = self.text.write_fmt(args);
+        &self.text
+    }
+
+    /// A message in the active locale, translated once per language
+    pub fn label(&mut self, id: &'static str) -> &str {
+        self.labels.entry(id).or_insert_with(|| i18n::tr(id))
+    }
+
+    /// Hold text until its clip rect is drawn
+    pub fn clip_text(&mut self, run: TextRun, text: &str) {
+        let start = self.clipped_text.len();
+        self.clipped_text.push_str(text);
+        self.clipped_runs.push((run, start..self.clipped_text.len()));
+    }
+
+    /// Hold formatted text until its clip rect is drawn
+    pub fn clip_fmt(&mut self, run: TextRun, args: fmt::Arguments) {
+        let start = self.clipped_text.len();
+        let _ = self.clipped_text.write_fmt(args);
+        self.clipped_runs.push((run, start..self.clipped_text.len()));
+    }
+
+    /// Hold a translated message until its clip rect is drawn
+    pub fn clip_label(&mut self, run: TextRun, id: &'static str) {
+        let start = self.clipped_text.len();
+        self.clipped_text.push_str(self.labels.entry(id).or_insert_with(|| i18n::tr(id)));
+        self.clipped_runs.push((run, start..self.clipped_text.len()));
+    }
+
+    /// Clipped text in the order it was drawn
+    pub fn clipped(&self) -> impl Iterator<Item = (&TextRun, &str)> {
+        self.clipped_runs.iter().map(|(run, range)| (run, &self.clipped_text[range.clone()]))
+    }
+
+    /// Forget clipped text once it has been drawn
+    pub fn clear_clipped(&mut self) {
+        self.clipped_text.clear();
+        self.clipped_runs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_reuses_the_buffer() {
+        let mut arena = FrameArena::new();
+        assert_eq!(arena.format(format_args!("{} of {}", 3, 10)), "3 of 10");
+
+        let capacity = arena.text.capacity();
+        assert_eq!(arena.format(format_args!("{}%", 42)), "42%");
+        assert_eq!(arena.text.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_clipped_text_shares_one_buffer() {
+        let mut arena = FrameArena::new();
+        let clip = ClipRect { x: 0.0, y: 0.0, width: 10.0, height: 10.0 };
+        let run = TextRun { clip, x: 1.0, y: 2.0, size: 12.0, color: [1.0; 4] };
+        arena.clip_text(run, "Alpha");
+        arena.clip_fmt(TextRun { y: 20.0, ..run }, format_args!("{}%", 50));
+        arena.clip_label(run, "settings-title");
+
+        let clipped: Vec<_> = arena.clipped().map(|(run, text)| (run.y, text.to_string())).collect();
+        assert_eq!(clipped, vec![(2.0, "Alpha".to_string()), (20.0, "50%".to_string()), (2.0, i18n::tr("settings-title"))]);
+
+        arena.begin_frame();
+        assert_eq!(arena.clipped().count(), 0);
+    }
+
+    #[test]
+    fn test_labels_are_translated_once() {
+        let mut arena = FrameArena::new();
+        arena.begin_frame();
+        assert_eq!(arena.label("settings-title"), i18n::tr("settings-title"));
+
+        arena.labels.insert("settings-title", "cached".to_string());
+        arena.begin_frame();
+        assert_eq!(arena.label("settings-title"), "cached");
+    }
+}
@@ -1,6 +1,7 @@
 use wgpu::Color;
 use std::sync::Arc;
-use crate::ui::{RenderContext, Widget};
+use crate::ui::{damage, RenderContext, Widget, WidgetInfo, CyberpunkTheme, Quad};
+use winit::window::CursorIcon;
 
 /// A clickable button widget
 pub struct Button {
@@ -10,10 +11,12 @@ pub struct Button {
     height: f32,
     label: String,
     background_color: Color,
-    hover_color: Color,
+    /// Background while hovered; a lighter background when unset
+    hover_color: Option<Color>,
     text_color: Color,
     border_color: Color,
     border_width: f32,
+    corner_radius: f32,
     is_hovered: bool,
     is_pressed: bool,
     on_click: Option<Arc<dyn Fn() + Send + Sync>>,
@@ -32,6 +35,7 @@ impl Clone for Button {
             text_color: self.text_color,
             border_color: self.border_color,
             border_width: self.border_width,
+            corner_radius: self.corner_radius,
             is_hovered: self.is_hovered,
             is_pressed: self.is_pressed,
             on_click: self.on_click.clone(),
@@ -54,12 +58,7 @@ impl Button {
                 b: 0.2,
                 a: 1.0,
             },
-            hover_color: Color {
-                r: 0.3,
-                g: 0.3,
-                b: 0.3,
-                a: 1.0,
-            },
+            hover_color: None,
             text_color: Color {
                 r: 0.0,
                 g: 0.9,
@@ -73,6 +72,7 @@ impl Button {
                 a: 1.0,
             },
             border_width: 1.0,
+            corner_radius: CyberpunkTheme::new().corner_radius(),
             is_hovered: false,
             is_pressed: false,
             on_click: None,
@@ -87,7 +87,7 @@ impl Button {
 
     /// Set the hover color
     pub fn with_hover_color(mut self, color: Color) -> Self {
-        self.hover_color = color;
+        self.hover_color = Some(color);
         self
     }
 
@@ -109,6 +109,12 @@ impl Button {
         self
     }
 
+    /// Set the corner radius; 0 gives square corners
+    pub fn with_corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
     /// Set the on_click handler
     pub fn with_on_click<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
         self.on_click = Some(Arc::new(callback));
@@ -122,7 +128,8 @@ impl Button {
 
     /// Handle mouse move event
     pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
-        self.is_hovered = self.contains_point(x, y);
+        let hovered = self.contains_point(x, y);
+        damage::set(&mut self.is_hovered, hovered);
     }
 
     /// Handle mouse button press
@@ -143,15 +150,26 @@ impl Button {
     }
 }
 
+/// Convert wgpu::Color to [f32; 4] RGBA values
+fn to_array(color: Color) -> [f32; 4] {
+    [color.r as f32, color.g as f32, color.b as f32, color.a as f32]
+}
+
 impl Widget for Button {
     fn update(&mut self, _delta_time: f32) {
         // Update logic if needed
     }
 
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth).with_hovered(self.is_hovered));
+    }
+
     fn render(&self, ctx: &mut RenderContext) {
-        // TODO: Draw button background, border and text
-        // For now, just draw the label as text
-        let _color = if self.is_pressed {
+        let background = if self.is_pressed {
             // Darker when pressed
             Color {
                 r: self.background_color.r * 0.8,
@@ -160,26 +178,26 @@ impl Widget for Button {
                 a: self.background_color.a,
             }
         } else if self.is_hovered {
-            self.hover_color
+            self.hover_color.unwrap_or(Color {
+                r: self.background_color.r + (1.0 - self.background_color.r) * 0.2,
+                g: self.background_color.g + (1.0 - self.background_color.g) * 0.2,
+                b: self.background_color.b + (1.0 - self.background_color.b) * 0.2,
+                a: self.background_color.a,
+            })
         } else {
             self.background_color
         };
 
-        // Future: Draw background and border here
+        ctx.draw_quad(
+            Quad::rect(self.x, self.y, self.width, self.height, to_array(background))
+                .with_corner_radius(self.corner_radius)
+                .with_border(self.border_width, to_array(self.border_color)),
+        );
 
         // Draw the button text
-        let text_x = self.x + (self.width / 2.0) - (self.label.len() as f32 * 8.0 / 2.0);  // Rough centering
+        let text_x = self.x + (self.width - ctx.measure_text(&self.label, 16.0).width) / 2.0;
         let text_y = self.y + (self.height / 2.0) - 8.0;  // Rough centering
-        
-        // Convert wgpu::Color to [f32; 4] array
-        let text_color = [
-            self.text_color.r as f32,
-            self.text_color.g as f32,
-            self.text_color.b as f32,
-            self.text_color.a as f32,
-        ];
-        
-        ctx.draw_text(&self.label, text_x, text_y, 16.0, text_color);
+        ctx.draw_text(&self.label, text_x, text_y, 16.0, to_array(self.text_color));
     }
 
     fn position(&self) -> (f32, f32) {
@@ -199,4 +217,8 @@ impl Widget for Button {
         self.width = width;
         self.height = height;
     }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.contains_point(x, y).then_some(CursorIcon::Pointer)
+    }
 } 
\ No newline at end of file
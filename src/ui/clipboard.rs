@@ -0,0 +1,82 @@
+/// Text copied from and pasted into text fields
+///
+/// Uses the system clipboard where there is one. In the browser, or when the
+/// platform refuses access (e.g. a session without a display server), text is
+/// only copied and pasted within the app.
+pub struct Clipboard {
+    #[cfg(not(target_arch = "wasm32"))]
+    system: Option<arboard::Clipboard>,
+    /// Last copied text, used when there is no system clipboard
+    local: String,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clipboard {
+    /// Connect to the system clipboard, falling back to one inside the app
+    pub fn new() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let system = arboard::Clipboard::new()
+            .map_err(|e| tracing::warn!("No system clipboard, copying within the app only: {}", e))
+            .ok();
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            system,
+            local: String::new(),
+        }
+    }
+
+    /// A clipboard that never touches the system's, for tests
+    pub fn in_memory() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            system: None,
+            local: String::new(),
+        }
+    }
+
+    /// Put text on the clipboard
+    pub fn set_text(&mut self, text: &str) {
+        self.local = text.to_string();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(system) = &mut self.system {
+            if let Err(e) = system.set_text(text) {
+                tracing::error!("Failed to copy to the clipboard: {}", e);
+            }
+        }
+    }
+
+    /// The text on the clipboard, if it holds any
+    pub fn text(&mut self) -> Option<String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(system) = &mut self.system {
+            return match system.get_text() {
+                Ok(text) => Some(text),
+                // Empty, or holding something other than text, such as an image
+                Err(arboard::Error::ContentNotAvailable) => None,
+                Err(e) => {
+                    tracing::error!("Failed to paste from the clipboard: {}", e);
+                    None
+                }
+            };
+        }
+        (!self.local.is_empty()).then(|| self.local.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_round_trip() {
+        let mut clipboard = Clipboard::in_memory();
+        assert_eq!(clipboard.text(), None);
+        clipboard.set_text("neon");
+        assert_eq!(clipboard.text().as_deref(), Some("neon"));
+    }
+}
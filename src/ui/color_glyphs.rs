@@ -0,0 +1,533 @@
+// Color emoji, drawn from a texture atlas over the text
+//
+// glyph_brush only rasterizes outlines, so glyphs that fonts such as Noto
+// Color Emoji store as PNG images come out blank: they're laid out, taking up
+// their advance, but nothing is drawn. `ColorGlyphs` finds those characters in
+// queued text, decodes their images into an atlas texture once and draws them
+// as textured quads in the gaps the glyph brush left for them.
+
+use std::collections::HashMap;
+use bytemuck::{Pod, Zeroable};
+use wgpu::*;
+use wgpu::util::StagingBelt;
+use wgpu_glyph::ab_glyph::{Font, FontArc, GlyphId, GlyphImageFormat, ScaleFont};
+use crate::profiling::profile_scope;
+use crate::ui::context::ClipRect;
+use crate::ui::{fonts, text_metrics};
+
+/// Width and height of the atlas texture
+const ATLAS_SIZE: u32 = 2048;
+
+/// Pixels per em asked of fonts; they return their closest strike (109 for Noto Color Emoji)
+const STRIKE_SIZE: u16 = 64;
+
+/// Empty texels around each image, so filtering doesn't bleed in its neighbours
+const PADDING: u32 = 1;
+
+/// A decoded glyph image, in straight-alpha RGBA
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Where an image is in the atlas, in texels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasSlot {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Packs glyph images into rows of a square texture
+///
+/// Images are decoded the first time their glyph is asked for and kept for
+/// later frames, along with glyphs found to have none. Once the atlas is
+/// full, new glyphs go without until `end_frame` empties it, so the next
+/// frame packs just what it shows.
+pub struct GlyphAtlas {
+    size: u32,
+    slots: HashMap<(usize, GlyphId), Option<AtlasSlot>>,
+    /// Top-left of the free space in the current row, and that row's height so far
+    cursor: (u32, u32),
+    row_height: u32,
+    full: bool,
+    /// Images placed since the last `take_uploads`, waiting to go to the texture
+    uploads: Vec<(AtlasSlot, Vec<u8>)>,
+}
+
+impl GlyphAtlas {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            slots: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+            full: false,
+            uploads: Vec::new(),
+        }
+    }
+
+    /// The slot of a font's glyph, placing `image()` there the first time it's asked for
+    ///
+    /// `None` if the glyph has no image or there's no room left for it.
+    pub fn slot(&mut self, key: (usize, GlyphId), image: impl FnOnce() -> Option<Image>) -> Option<AtlasSlot> {
+        if let Some(&slot) = self.slots.get(&key) {
+            return slot;
+        }
+        if self.full {
+            return None;
+        }
+        let slot = image().and_then(|image| {
+            let slot = self.allocate(image.width, image.height)?;
+            self.uploads.push((slot, image.rgba));
+            Some(slot)
+        });
+        self.slots.insert(key, slot);
+        slot
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasSlot> {
+        let (padded_width, padded_height) = (width + PADDING * 2, height + PADDING * 2);
+        if padded_width > self.size || padded_height > self.size {
+            return None;
+        }
+        if self.cursor.0 + padded_width > self.size {
+            self.cursor = (0, self.cursor.1 + self.row_height);
+            self.row_height = 0;
+        }
+        if self.cursor.1 + padded_height > self.size {
+            self.full = true;
+            return None;
+        }
+        let slot = AtlasSlot { x: self.cursor.0 + PADDING, y: self.cursor.1 + PADDING, width, height };
+        self.cursor.0 += padded_width;
+        self.row_height = self.row_height.max(padded_height);
+        Some(slot)
+    }
+
+    /// Images placed since the last call, to copy into the texture
+    pub fn take_uploads(&mut self) -> Vec<(AtlasSlot, Vec<u8>)> {
+        std::mem::take(&mut self.uploads)
+    }
+
+    /// Texture coordinates of a slot: min u, min v, max u, max v
+    pub fn uv(&self, slot: AtlasSlot) -> [f32; 4] {
+        let size = self.size as f32;
+        [
+            slot.x as f32 / size,
+            slot.y as f32 / size,
+            (slot.x + slot.width) as f32 / size,
+            (slot.y + slot.height) as f32 / size,
+        ]
+    }
+
+    /// Start over if the atlas filled up, once this frame's glyphs are drawn
+    pub fn end_frame(&mut self) {
+        if self.full {
+            *self = Self::new(self.size);
+        }
+    }
+}
+
+/// The color image of a font's glyph, if it's drawn as one instead of an outline
+fn color_image(font: &FontArc, id: GlyphId) -> Option<Image> {
+    if font.outline(id).is_some() {
+        return None;
+    }
+    let image = font.glyph_raster_image2(id, STRIKE_SIZE)?;
+    match image.format {
+        GlyphImageFormat::Png => decode_png(image.data),
+        GlyphImageFormat::BitmapPremulBgra32 => {
+            let rgba = image
+                .data
+                .chunks_exact(4)
+                .flat_map(|bgra| {
+                    let alpha = bgra[3];
+                    let unpremultiply = |c: u8| if alpha == 0 { 0 } else { (c as u32 * 255 / alpha as u32).min(255) as u8 };
+                    [unpremultiply(bgra[2]), unpremultiply(bgra[1]), unpremultiply(bgra[0]), alpha]
+                })
+                .collect();
+            Some(Image { width: image.width.into(), height: image.height.into(), rgba })
+        }
+        _ => None,
+    }
+}
+
+/// Decode PNG data into RGBA, whatever its color type
+#[cfg(not(target_arch = "wasm32"))]
+pub fn decode_png(data: &[u8]) -> Option<Image> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().ok()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).ok()?;
+    pixels.truncate(info.buffer_size());
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels,
+        png::ColorType::Rgb => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return None,
+    };
+    Some(Image { width: info.width, height: info.height, rgba })
+}
+
+/// The browser build has no PNG decoder, and no color emoji fonts to need one
+#[cfg(target_arch = "wasm32")]
+pub fn decode_png(_data: &[u8]) -> Option<Image> {
+    None
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+struct ScreenUniforms {
+    size: [f32; 2],
+    /// Physical pixels per logical pixel, which sprites are given in
+    scale_factor: f32,
+    _padding: f32,
+}
+
+/// One color glyph to draw, in logical pixels
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+struct Sprite {
+    /// Left, top, width, height
+    rect: [f32; 4],
+    /// Min u, min v, max u, max v in the atlas
+    uv: [f32; 4],
+    /// Min x, min y, max x, max y of the area it may draw in
+    clip: [f32; 4],
+    /// Opacity, unused
+    params: [f32; 4],
+}
+
+/// Draws the color glyphs in queued text, after the glyph brush drew the rest
+///
+/// Text is queued with the same position and size it's given to the glyph
+/// brush with, and placed using the text metrics, so the fonts must be the
+/// ones both of those have.
+pub struct ColorGlyphs {
+    fonts: Vec<FontArc>,
+    atlas: GlyphAtlas,
+    texture: Texture,
+    pipeline: RenderPipeline,
+    screen_buffer: Buffer,
+    bind_group: BindGroup,
+    instance_buffer: Buffer,
+    /// Sprites the instance buffer has room for
+    capacity: usize,
+    queued: Vec<Sprite>,
+    scale_factor: f32,
+}
+
+impl ColorGlyphs {
+    /// Sprites the instance buffer starts out with room for
+    const INITIAL_CAPACITY: usize = 32;
+
+    /// Draw glyphs of `fonts` onto targets of this format
+    pub fn new(device: &Device, format: TextureFormat, fonts: Vec<FontArc>) -> Self {
+        let screen_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Color Glyph Screen Uniforms"),
+            size: std::mem::size_of::<ScreenUniforms>() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Texels are written to the target as they are, so they're stored in its encoding
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Color Glyph Atlas"),
+            size: Extent3d { width: ATLAS_SIZE, height: ATLAS_SIZE, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: if format.is_srgb() { TextureFormat::Rgba8UnormSrgb } else { TextureFormat::Rgba8Unorm },
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("Color Glyph Sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Color Glyph Shader"),
+            source: ShaderSource::Wgsl(include_str!("../shaders/color_glyph.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Color Glyph Bind Group Layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Color Glyph Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                BindGroupEntry { binding: 0, resource: screen_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 1, resource: BindingResource::TextureView(&view) },
+                BindGroupEntry { binding: 2, resource: BindingResource::Sampler(&sampler) },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Color Glyph Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Color Glyph Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Sprite>() as u64,
+                    step_mode: VertexStepMode::Instance,
+                    attributes: &vertex_attr_array![0 => Float32x4, 1 => Float32x4, 2 => Float32x4, 3 => Float32x4],
+                }],
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            fonts,
+            atlas: GlyphAtlas::new(ATLAS_SIZE),
+            texture,
+            pipeline,
+            screen_buffer,
+            bind_group,
+            instance_buffer: Self::create_instance_buffer(device, Self::INITIAL_CAPACITY),
+            capacity: Self::INITIAL_CAPACITY,
+            queued: Vec::new(),
+            scale_factor: 1.0,
+        }
+    }
+
+    fn create_instance_buffer(device: &Device, capacity: usize) -> Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: Some("Color Glyph Instances"),
+            size: (capacity * std::mem::size_of::<Sprite>()) as u64,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Take text in logical pixels, this many physical pixels each, from the next draw on
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Queue the color glyphs of a line of text queued to the glyph brush at (x, y)
+    ///
+    /// Each is scaled to its advance and centered on the line. `alpha` is the
+    /// text color's, so glyphs fade with the text around them.
+    pub fn queue_text(&mut self, text: &str, x: f32, y: f32, size: f32, alpha: f32, clip: Option<ClipRect>) {
+        // Color fonts come after the UI font, which has every ASCII character
+        if text.is_ascii() || alpha <= 0.0 {
+            return;
+        }
+        for (byte, c) in text.char_indices().filter(|(_, c)| !c.is_ascii()) {
+            let font = fonts::font_for(&self.fonts, c);
+            let id = self.fonts[font].glyph_id(c);
+            let Some(slot) = self.atlas.slot((font, id), || color_image(&self.fonts[font], id)) else {
+                continue;
+            };
+            let width = self.fonts[font].as_scaled(size).h_advance(id);
+            let height = width * slot.height as f32 / slot.width as f32;
+            let left = x + text_metrics::text_width(&text[..byte], size);
+            let clip = clip.map_or([f32::MIN, f32::MIN, f32::MAX, f32::MAX], |clip| {
+                [clip.x, clip.y, clip.x + clip.width, clip.y + clip.height]
+            });
+            self.queued.push(Sprite {
+                rect: [left, y + (size - height) / 2.0, width, height],
+                uv: self.atlas.uv(slot),
+                clip,
+                params: [alpha, 0.0, 0.0, 0.0],
+            });
+        }
+    }
+
+    /// Copy images placed in the atlas since the last upload into its texture
+    pub fn upload(&mut self, queue: &Queue) {
+        for (slot, rgba) in self.atlas.take_uploads() {
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: Origin3d { x: slot.x, y: slot.y, z: 0 },
+                    aspect: TextureAspect::All,
+                },
+                &rgba,
+                ImageDataLayout { offset: 0, bytes_per_row: Some(slot.width * 4), rows_per_image: Some(slot.height) },
+                Extent3d { width: slot.width, height: slot.height, depth_or_array_layers: 1 },
+            );
+        }
+    }
+
+    /// Draw every queued glyph on top of `target`, `width` by `height` physical
+    /// pixels, and clear the queue; `upload` must have been called since they were queued
+    pub fn draw_queued(
+        &mut self,
+        device: &Device,
+        staging_belt: &mut StagingBelt,
+        encoder: &mut CommandEncoder,
+        target: &TextureView,
+        width: u32,
+        height: u32,
+    ) {
+        profile_scope!("color_glyphs");
+        self.atlas.end_frame();
+        if self.queued.is_empty() {
+            return;
+        }
+
+        if self.queued.len() > self.capacity {
+            self.capacity = self.queued.len().next_power_of_two();
+            self.instance_buffer = Self::create_instance_buffer(device, self.capacity);
+        }
+
+        let screen = ScreenUniforms { size: [width as f32, height as f32], scale_factor: self.scale_factor, _padding: 0.0 };
+        let screen_bytes: &[u8] = bytemuck::cast_slice(std::slice::from_ref(&screen));
+        let instance_bytes: &[u8] = bytemuck::cast_slice(&self.queued);
+        for (buffer, bytes) in [(&self.screen_buffer, screen_bytes), (&self.instance_buffer, instance_bytes)] {
+            // Neither is empty: the queue has sprites and the uniforms are fixed size
+            let size = BufferSize::new(bytes.len() as u64).expect("upload is not empty");
+            staging_belt.write_buffer(encoder, buffer, 0, size, device).copy_from_slice(bytes);
+        }
+
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Color Glyph Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load, // Draw over the text
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.instance_buffer.slice(..instance_bytes.len() as u64));
+        pass.draw(0..6, 0..self.queued.len() as u32); // Two triangles per sprite
+        drop(pass);
+
+        self.queued.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> Option<Image> {
+        Some(Image { width, height, rgba: vec![255; (width * height * 4) as usize] })
+    }
+
+    #[test]
+    fn test_atlas_packs_rows_and_starts_over_when_full() {
+        let mut atlas = GlyphAtlas::new(64);
+        let first = atlas.slot((0, GlyphId(1)), || image(20, 10)).unwrap();
+        assert_eq!(first, AtlasSlot { x: 1, y: 1, width: 20, height: 10 });
+        // Kept, without decoding again
+        assert_eq!(atlas.slot((0, GlyphId(1)), || panic!("decoded twice")), Some(first));
+        assert_eq!(atlas.slot((0, GlyphId(2)), || None), None);
+        assert_eq!(atlas.slot((0, GlyphId(2)), || panic!("decoded twice")), None);
+
+        let second = atlas.slot((1, GlyphId(1)), || image(30, 20)).unwrap();
+        assert_eq!((second.x, second.y), (23, 1));
+        // Too wide for the rest of the row, so it starts the next one below the taller image
+        let third = atlas.slot((1, GlyphId(2)), || image(20, 20)).unwrap();
+        assert_eq!((third.x, third.y), (1, 23));
+        assert_eq!(atlas.take_uploads().len(), 3);
+        assert!(atlas.take_uploads().is_empty());
+        assert_eq!(atlas.uv(third), [1.0 / 64.0, 23.0 / 64.0, 21.0 / 64.0, 43.0 / 64.0]);
+
+        assert_eq!(atlas.slot((1, GlyphId(3)), || image(100, 10)), None);
+        atlas.end_frame();
+        assert!(atlas.slot((1, GlyphId(4)), || image(20, 30)).is_some());
+        assert_eq!(atlas.slot((1, GlyphId(5)), || image(20, 30)), None);
+        atlas.end_frame();
+        // Emptied, so everything is placed afresh
+        assert_eq!(atlas.slot((1, GlyphId(5)), || image(20, 30)), Some(AtlasSlot { x: 1, y: 1, width: 20, height: 30 }));
+    }
+
+    #[test]
+    fn test_png_images_decode_to_rgba() {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, 2, 1);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(&[255, 0, 0, 0, 0, 255]).unwrap();
+
+        let image = decode_png(&data).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.rgba, [255, 0, 0, 255, 0, 0, 255, 255]);
+        assert_eq!(decode_png(b"not a png"), None);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::ui::{RenderContext, TextOverflow, Widget, WidgetInfo, Button, CyberpunkTheme};
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+/// The button a user picked in a ConfirmDialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogChoice {
+    Confirm,
+    Cancel,
+}
+
+/// Convert [f32; 4] RGBA values to wgpu::Color
+fn to_color(rgba: [f32; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: rgba[0] as f64,
+        g: rgba[1] as f64,
+        b: rgba[2] as f64,
+        a: rgba[3] as f64,
+    }
+}
+
+/// A modal yes/no dialog drawn centered over the whole screen
+pub struct ConfirmDialog {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    title: String,
+    message: String,
+    confirm_button: Button,
+    cancel_button: Button,
+    is_visible: bool,
+    theme: CyberpunkTheme,
+}
+
+impl ConfirmDialog {
+    /// Create a hidden dialog; call `show` to center and display it
+    pub fn new(
+        title: impl Into<String>,
+        message: impl Into<String>,
+        confirm_label: impl Into<String>,
+        cancel_label: impl Into<String>,
+    ) -> Self {
+        let theme = CyberpunkTheme::new();
+
+        let confirm_button = Button::new(0.0, 0.0, 120.0, 36.0, confirm_label)
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.neon_pink()));
+        let cancel_button = Button::new(0.0, 0.0, 120.0, 36.0, cancel_label)
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.filter_button_bg()));
+
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 480.0,
+            height: 200.0,
+            title: title.into(),
+            message: message.into(),
+            confirm_button,
+            cancel_button,
+            is_visible: false,
+            theme,
+        }
+    }
+
+    /// Check if the dialog is currently shown
+    pub fn is_visible(&self) -> bool {
+        self.is_visible
+    }
+
+    /// Show the dialog centered on a screen of the given size
+    pub fn show(&mut self, screen_width: f32, screen_height: f32) {
+        self.is_visible = true;
+        self.layout(screen_width, screen_height);
+    }
+
+    /// Hide the dialog
+    pub fn hide(&mut self) {
+        self.is_visible = false;
+    }
+
+    /// Re-center the dialog, e.g. after a window resize
+    pub fn layout(&mut self, screen_width: f32, screen_height: f32) {
+        self.width = screen_width.min(480.0);
+        self.x = (screen_width - self.width) / 2.0;
+        self.y = (screen_height - self.height) / 2.0;
+
+        let button_y = self.y + self.height - 56.0;
+        self.cancel_button.set_position(self.x + self.width - 260.0, button_y);
+        self.confirm_button.set_position(self.x + self.width - 130.0, button_y);
+    }
+
+    /// Handle mouse movement for button hover states
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        self.confirm_button.handle_mouse_move(x, y);
+        self.cancel_button.handle_mouse_move(x, y);
+    }
+
+    /// Handle mouse button press
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) {
+        self.confirm_button.handle_mouse_down(x, y);
+        self.cancel_button.handle_mouse_down(x, y);
+    }
+
+    /// Handle mouse button release, returning the choice if a button was clicked
+    pub fn handle_mouse_up(&mut self, x: f32, y: f32) -> Option<DialogChoice> {
+        if !self.is_visible {
+            return None;
+        }
+
+        let choice = if self.confirm_button.contains_point(x, y) {
+            Some(DialogChoice::Confirm)
+        } else if self.cancel_button.contains_point(x, y) {
+            Some(DialogChoice::Cancel)
+        } else {
+            None
+        };
+
+        self.confirm_button.handle_mouse_up(x, y);
+        self.cancel_button.handle_mouse_up(x, y);
+
+        if choice.is_some() {
+            self.is_visible = false;
+        }
+        choice
+    }
+
+    /// Handle keyboard input: Enter confirms, Escape cancels
+    pub fn handle_key_press(&mut self, key: KeyCode) -> Option<DialogChoice> {
+        if !self.is_visible {
+            return None;
+        }
+
+        let choice = match key {
+            KeyCode::Enter => Some(DialogChoice::Confirm),
+            KeyCode::Escape => Some(DialogChoice::Cancel),
+            _ => None,
+        };
+
+        if choice.is_some() {
+            self.is_visible = false;
+        }
+        choice
+    }
+}
+
+impl Widget for ConfirmDialog {
+    fn update(&mut self, delta_time: f32) {
+        for button in [&mut self.confirm_button, &mut self.cancel_button] {
+            if button.needs_update() {
+                button.update(delta_time);
+            }
+        }
+    }
+
+    fn needs_update(&self) -> bool {
+        self.is_visible && (self.confirm_button.needs_update() || self.cancel_button.needs_update())
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        if self.is_visible {
+            out.push(WidgetInfo::of(self, depth));
+            self.confirm_button.inspect(depth + 1, out);
+            self.cancel_button.inspect(depth + 1, out);
+        }
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_visible {
+            return;
+        }
+
+        // Dim everything behind the dialog
+        ctx.draw_rect(0.0, 0.0, ctx.width, ctx.height, self.theme.get_modal_overlay_color());
+
+        // Dialog body and header
+        ctx.draw_modal_frame(&self.theme, self.x, self.y, self.width, self.height, 40.0);
+
+        ctx.draw_text(
+            &self.title,
+            self.x + 20.0, self.y + 8.0,
+            24.0,
+            self.theme.get_modal_text_color(),
+        );
+
+        // Messages naming long task titles wrap rather than run out of the dialog
+        ctx.draw_text_bounded(
+            &self.message,
+            (self.x + 20.0, self.y + 60.0),
+            self.width - 40.0,
+            self.theme.text_size(),
+            self.theme.get_modal_text_color(),
+            TextOverflow::Wrap,
+        );
+
+        self.cancel_button.render(ctx);
+        self.confirm_button.render(ctx);
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        let dx = x - self.x;
+        let dy = y - self.y;
+        self.x = x;
+        self.y = y;
+
+        let (confirm_x, confirm_y) = self.confirm_button.position();
+        self.confirm_button.set_position(confirm_x + dx, confirm_y + dy);
+        let (cancel_x, cancel_y) = self.cancel_button.position();
+        self.cancel_button.set_position(cancel_x + dx, cancel_y + dy);
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if !self.is_visible {
+            return None;
+        }
+        self.confirm_button
+            .cursor_at(x, y)
+            .or_else(|| self.cancel_button.cursor_at(x, y))
+            .or(Some(CursorIcon::Default))
+    }
+}
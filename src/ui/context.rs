@@ -1,6 +1,11 @@
-use wgpu::Queue;
-use wgpu_glyph::{GlyphBrush, Section, Text};
+use std::borrow::Cow;
+use std::fmt;
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+use wgpu_glyph::{FontId, GlyphBrush, Region, Section, Text};
 use wgpu::util::StagingBelt;
+use crate::ui::{ColorGlyphs, CyberpunkTheme, FrameArena, Gradient, Quad, QuadRenderer};
+use crate::ui::arena::TextRun;
+use crate::ui::{fonts, text_metrics};
 
 /// Represents size information for text measurements
 pub struct TextSize {
@@ -8,21 +13,132 @@ pub struct TextSize {
     pub height: f32,
 }
 
+impl TextSize {
+    /// Size of a line of text in the UI font, also for layout done away from a render pass
+    pub fn measure(text: &str, size: f32) -> Self {
+        TextSize { width: text_metrics::text_width(text, size), height: size }
+    }
+}
+
+/// How `RenderContext::draw_text_bounded` fits text wider than its bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextOverflow {
+    /// Continue on the next line, breaking at spaces where possible
+    Wrap,
+    /// Stay on one line, cut short with "…"
+    Ellipsis,
+}
+
+/// Distance between the tops of wrapped lines, relative to the text size
+const LINE_SPACING: f32 = 1.3;
+
+/// A rectangle that drawing is confined to, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ClipRect {
+    /// The area inside both rectangles, empty if they don't overlap
+    pub fn intersect(&self, other: &ClipRect) -> ClipRect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        ClipRect { x, y, width: (right - x).max(0.0), height: (bottom - y).max(0.0) }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.width < 1.0 || self.height < 1.0
+    }
+    
+    /// Whole physical pixels covering the rectangle, within the target, for a scissored glyph draw
+    fn region(&self, scale_factor: f32, (target_width, target_height): (u32, u32)) -> Region {
+        let (x, y) = ((self.x * scale_factor).floor() as u32, (self.y * scale_factor).floor() as u32);
+        let right = (((self.x + self.width) * scale_factor).ceil() as u32).min(target_width);
+        let bottom = (((self.y + self.height) * scale_factor).ceil() as u32).min(target_height);
+        Region {
+            x: x.min(right),
+            y: y.min(bottom),
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
+        }
+    }
+}
+
+/// What a frame drew, for the diagnostics overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    /// Draw calls for quads and text
+    pub draws: u32,
+    pub quads: u32,
+    pub glyphs: u32,
+}
+
 /// Context for rendering UI components
 pub struct RenderContext<'a> {
     pub queue: &'a Queue,
     pub staging_belt: &'a mut StagingBelt,
     pub glyph_brush: &'a mut GlyphBrush<()>,
+    /// Rectangles, lines and circles, drawn below the text
+    pub quads: &'a mut QuadRenderer,
+    /// Emoji from color fonts, drawn over the text they're part of
+    color_glyphs: Option<&'a mut ColorGlyphs>,
+    /// Size of the target in logical pixels, which widgets are laid out and draw in
     pub width: f32,
     pub height: f32,
+    /// Physical pixels per logical pixel
+    scale_factor: f32,
+    /// Scratch buffers kept across frames
+    arena: &'a mut FrameArena,
+    /// Nested clip rects, each already intersected with the ones below it
+    clip_stack: Vec<ClipRect>,
+    /// Animation time of the frame, in seconds
+    time: f32,
+    /// Color of the glow the user picked, for widgets that glow in it
+    glow_color: [f32; 4],
+    /// Counts of what was drawn so far
+    stats: DrawStats,
+}
+
+/// Queue a run of text; a free function so callers can pass text borrowed from the arena
+///
+/// Position and size are logical and scaled here, so glyphs are rasterized
+/// at the target's resolution rather than stretched to it. Characters the
+/// UI font lacks are drawn in the first of the brush's fallback fonts that has them.
+/// Returns how many glyphs were queued, not counting whitespace.
+fn queue_text(glyph_brush: &mut GlyphBrush<()>, (bounds, scale_factor): ((f32, f32), f32), text: &str, x: f32, y: f32, size: f32, color: [f32; 4]) -> u32 {
+    let section = Section {
+        screen_position: (x * scale_factor, y * scale_factor),
+        bounds: (bounds.0 * scale_factor, bounds.1 * scale_factor),
+        text: fonts::runs(glyph_brush.fonts(), text)
+            .into_iter()
+            .map(|(font, run)| Text::new(run)
+                .with_font_id(FontId(font))
+                .with_color(color)
+                .with_scale(size * scale_factor))
+            .collect(),
+        ..Section::default()
+    };
+    
+    glyph_brush.queue(section);
+    text.chars().filter(|c| !c.is_whitespace()).count() as u32
 }
 
 impl<'a> RenderContext<'a> {
-    /// Create a new render context
+    /// Create a new render context for a target `width` by `height` pixels
+    ///
+    /// The arena should live as long as the application and is lent to
+    /// every frame, after `FrameArena::begin_frame`.
     pub fn new(
         queue: &'a Queue,
         staging_belt: &'a mut StagingBelt,
         glyph_brush: &'a mut GlyphBrush<()>,
+        quads: &'a mut QuadRenderer,
+        arena: &'a mut FrameArena,
         width: f32,
         height: f32,
     ) -> Self {
@@ -30,34 +146,292 @@ impl<'a> RenderContext<'a> {
             queue,
             staging_belt,
             glyph_brush,
+            quads,
+            color_glyphs: None,
             width,
             height,
+            arena,
+            scale_factor: 1.0,
+            clip_stack: Vec::new(),
+            time: 0.0,
+            glow_color: CyberpunkTheme::new().cyan(),
+            stats: DrawStats::default(),
+        }
+    }
+    
+    /// Draw for a display with this many physical pixels per logical pixel
+    ///
+    /// `width` and `height` become the target's logical size; everything is
+    /// drawn in logical pixels from then on and scaled up on the GPU.
+    pub fn with_scale_factor(mut self, scale_factor: f32) -> Self {
+        self.width /= scale_factor;
+        self.height /= scale_factor;
+        self.scale_factor = scale_factor;
+        self
+    }
+    
+    /// Physical pixels per logical pixel
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+    
+    /// Size of the target in physical pixels
+    fn target_size(&self) -> (u32, u32) {
+        ((self.width * self.scale_factor).round() as u32, (self.height * self.scale_factor).round() as u32)
+    }
+    
+    /// Draw the images of color emoji with this, instead of leaving gaps for them
+    pub fn with_color_glyphs(mut self, color_glyphs: &'a mut ColorGlyphs) -> Self {
+        self.color_glyphs = Some(color_glyphs);
+        self
+    }
+    
+    /// Draw the frame at this animation time, from the app's frame clock
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+    
+    /// Seconds of animation time at this frame, for anything that pulses or cycles while drawn
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+    
+    /// Glow in this color where widgets don't pick one, from the effect settings
+    pub fn with_glow_color(mut self, color: [f32; 4]) -> Self {
+        self.glow_color = color;
+        self
+    }
+    
+    /// The glow color the user picked
+    pub fn glow_color(&self) -> [f32; 4] {
+        self.glow_color
+    }
+    
+    /// Draw calls, quads and glyphs drawn by this context so far
+    pub fn stats(&self) -> DrawStats {
+        self.stats
+    }
+    
+    /// Draw everything queued so far onto `target`: shapes first, then text
+    ///
+    /// Widgets draw their backgrounds before their labels, so drawing all
+    /// shapes below all text keeps labels readable. Anything that must cover
+    /// text, such as a modal, belongs in a later pass.
+    ///
+    /// Quads carry their clip rect into the shader; clipped text is drawn
+    /// after the rest, one scissored draw per clip rect. Color emoji go on
+    /// top of all the text, into the gaps it left for them.
+    pub fn draw_queued(&mut self, device: &Device, encoder: &mut CommandEncoder, target: &TextureView) -> Result<(), String> {
+        self.draw_queued_shapes(device, encoder, target);
+        self.draw_queued_text(device, encoder, target)
+    }
+    
+    /// The first half of `draw_queued`: every shape queued so far onto `target`
+    ///
+    /// Split out so the diagnostics overlay can time shapes and text apart.
+    pub fn draw_queued_shapes(&mut self, device: &Device, encoder: &mut CommandEncoder, target: &TextureView) {
+        let (width, height) = self.target_size();
+        self.count_quads(self.quads.queued().len());
+        self.quads.set_scale_factor(self.scale_factor);
+        self.quads.draw_queued(device, self.staging_belt, encoder, target, width, height);
+    }
+    
+    /// The second half of `draw_queued`: all the text queued so far onto `target`
+    pub fn draw_queued_text(&mut self, device: &Device, encoder: &mut CommandEncoder, target: &TextureView) -> Result<(), String> {
+        let (width, height) = self.target_size();
+        self.glyph_brush.draw_queued(device, self.staging_belt, encoder, target, width, height)?;
+        self.stats.draws += 1;
+        
+        // Consecutive runs in the same clip rect share a draw
+        let screen = ((self.width, self.height), self.scale_factor);
+        {
+            let mut runs = self.arena.clipped().peekable();
+            while let Some((first, text)) = runs.next() {
+                self.stats.glyphs += queue_text(self.glyph_brush, screen, text, first.x, first.y, first.size, first.color);
+                if let Some(color_glyphs) = self.color_glyphs.as_deref_mut() {
+                    color_glyphs.queue_text(text, first.x, first.y, first.size, first.color[3], Some(first.clip));
+                }
+                while let Some((run, text)) = runs.next_if(|(run, _)| run.clip == first.clip) {
+                    self.stats.glyphs += queue_text(self.glyph_brush, screen, text, run.x, run.y, run.size, run.color);
+                    if let Some(color_glyphs) = self.color_glyphs.as_deref_mut() {
+                        color_glyphs.queue_text(text, run.x, run.y, run.size, run.color[3], Some(run.clip));
+                    }
+                }
+                self.stats.draws += 1;
+                self.glyph_brush.draw_queued_with_transform_and_scissoring(
+                    device,
+                    self.staging_belt,
+                    encoder,
+                    target,
+                    wgpu_glyph::orthographic_projection(width, height),
+                    first.clip.region(self.scale_factor, (width, height)),
+                )?;
+            }
         }
+        self.arena.clear_clipped();
+        
+        if let Some(color_glyphs) = self.color_glyphs.as_deref_mut() {
+            color_glyphs.set_scale_factor(self.scale_factor);
+            color_glyphs.upload(self.queue);
+            color_glyphs.draw_queued(device, self.staging_belt, encoder, target, width, height);
+        }
+        Ok(())
+    }
+    
+    /// Clear the glow mask `target` and draw every glowing shape queued so far onto it
+    ///
+    /// Called once a frame before the glow pass, which spreads the mask into a halo.
+    pub fn draw_glow_queued(&mut self, device: &Device, encoder: &mut CommandEncoder, target: &TextureView) -> Result<(), String> {
+        let (width, height) = self.target_size();
+        self.count_quads(self.quads.queued_glow().len());
+        self.quads.set_scale_factor(self.scale_factor);
+        self.quads.draw_glow_queued(device, self.staging_belt, encoder, target, width, height);
+        Ok(())
+    }
+    
+    /// Count a draw of `quads` quads, which skips the draw call when there are none
+    fn count_quads(&mut self, quads: usize) {
+        if quads > 0 {
+            self.stats.draws += 1;
+            self.stats.quads += quads as u32;
+        }
+    }
+    
+    /// Forget the glowing shapes queued so far, when drawing somewhere without a glow pass
+    pub fn discard_glow(&mut self) {
+        self.quads.discard_glow();
+    }
+    
+    /// The clip rect drawing is currently confined to, if any
+    pub fn clip(&self) -> Option<ClipRect> {
+        self.clip_stack.last().copied()
+    }
+    
+    /// How to draw clipped text, or `None` if nothing is clipped
+    fn text_run(&self, x: f32, y: f32, size: f32, color: [f32; 4]) -> Option<TextRun> {
+        self.clip().map(|clip| TextRun { clip, x, y, size, color })
+    }
+    
+    /// Whether the current clip rect hides everything
+    fn clipped_away(&self) -> bool {
+        self.clip().is_some_and(|clip| clip.is_empty())
     }
     
     /// Draw text at the specified position
     pub fn draw_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: [f32; 4]) {
-        let section = Section {
-            screen_position: (x, y),
-            bounds: (self.width, self.height),
-            text: vec![Text::new(text)
-                .with_color(color)
-                .with_scale(size)],
-            ..Section::default()
+        if self.clipped_away() {
+            return;
+        }
+        match self.text_run(x, y, size, color) {
+            Some(run) => self.arena.clip_text(run, text),
+            None => {
+                self.stats.glyphs += queue_text(self.glyph_brush, ((self.width, self.height), self.scale_factor), text, x, y, size, color);
+                if let Some(color_glyphs) = self.color_glyphs.as_deref_mut() {
+                    color_glyphs.queue_text(text, x, y, size, color[3], None);
+                }
+            }
+        }
+    }
+    
+    /// Draw text at `(x, y)` no wider than `max_width`, returning the height it took
+    pub fn draw_text_bounded(
+        &mut self,
+        text: &str,
+        (x, y): (f32, f32),
+        max_width: f32,
+        size: f32,
+        color: [f32; 4],
+        overflow: TextOverflow,
+    ) -> f32 {
+        match overflow {
+            TextOverflow::Ellipsis => {
+                self.draw_text(&text_metrics::truncate(text, max_width, size), x, y, size, color);
+                size
+            }
+            TextOverflow::Wrap => {
+                let lines = text_metrics::wrap(text, max_width, size);
+                let line_height = size * LINE_SPACING;
+                for (index, line) in lines.iter().enumerate() {
+                    self.draw_text(line, x, y + index as f32 * line_height, size, color);
+                }
+                (lines.len() as f32 - 1.0) * line_height + size
+            }
+        }
+    }
+    
+    /// Draw one line of text cut short with "…" to fit `max_width`, like
+    /// `draw_text_bounded`, with the character at each byte offset in the
+    /// color `color_at` gives for it, or `color` where it gives none
+    pub fn draw_text_colored(
+        &mut self,
+        text: &str,
+        (x, y): (f32, f32),
+        max_width: f32,
+        size: f32,
+        color: [f32; 4],
+        color_at: impl Fn(usize) -> Option<[f32; 4]>,
+    ) {
+        let shown = text_metrics::truncate(text, max_width, size);
+        // The shown text starts like the full text, up to the ellipsis if there is one
+        let kept = match &shown {
+            Cow::Borrowed(_) => shown.len(),
+            Cow::Owned(_) => shown.len() - "…".len(),
         };
         
-        self.glyph_brush.queue(section);
+        // Draw runs of characters in the same color, each where the text before it ends
+        let mut start = 0;
+        while start < shown.len() {
+            let color_of = |byte: usize| if byte < kept { color_at(byte).unwrap_or(color) } else { color };
+            let run_color = color_of(start);
+            let end = shown[start..]
+                .char_indices()
+                .map(|(offset, _)| start + offset)
+                .find(|&byte| color_of(byte) != run_color)
+                .unwrap_or(shown.len());
+            let run_x = x + text_metrics::text_width(&shown[..start], size);
+            self.draw_text(&shown[start..end], run_x, y, size, run_color);
+            start = end;
+        }
+    }
+    
+    /// Draw formatted text without allocating, e.g. `ctx.draw_fmt(format_args!("{}%", value), ...)`
+    pub fn draw_fmt(&mut self, args: fmt::Arguments, x: f32, y: f32, size: f32, color: [f32; 4]) {
+        if self.clipped_away() {
+            return;
+        }
+        match self.text_run(x, y, size, color) {
+            Some(run) => self.arena.clip_fmt(run, args),
+            None => {
+                let text = self.arena.format(args);
+                self.stats.glyphs += queue_text(self.glyph_brush, ((self.width, self.height), self.scale_factor), text, x, y, size, color);
+                if let Some(color_glyphs) = self.color_glyphs.as_deref_mut() {
+                    color_glyphs.queue_text(text, x, y, size, color[3], None);
+                }
+            }
+        }
+    }
+    
+    /// Draw a message in the active locale, translated once rather than every frame
+    pub fn draw_label(&mut self, id: &'static str, x: f32, y: f32, size: f32, color: [f32; 4]) {
+        if self.clipped_away() {
+            return;
+        }
+        match self.text_run(x, y, size, color) {
+            Some(run) => self.arena.clip_label(run, id),
+            None => {
+                let text = self.arena.label(id);
+                self.stats.glyphs += queue_text(self.glyph_brush, ((self.width, self.height), self.scale_factor), text, x, y, size, color);
+                if let Some(color_glyphs) = self.color_glyphs.as_deref_mut() {
+                    color_glyphs.queue_text(text, x, y, size, color[3], None);
+                }
+            }
+        }
     }
     
-    /// Measure text dimensions (approximate)
+    /// Measure text dimensions from the font's glyph metrics
     pub fn measure_text(&self, text: &str, size: f32) -> TextSize {
-        // This is a very simple approximation
-        // In a real app, you would use the font metrics to calculate this properly
-        let char_width = size * 0.5; // Approximate width of a character
-        let width = text.len() as f32 * char_width;
-        let height = size;
-        
-        TextSize { width, height }
+        TextSize::measure(text, size)
     }
     
     /// Alternative draw_text method that accepts tuple position and wgpu::Color
@@ -81,28 +455,40 @@ impl<'a> RenderContext<'a> {
         self.draw_text_with_color(text, position, size, color);
     }
     
+    /// Draw a shape built with `Quad`, e.g. a rounded rectangle with a border
+    pub fn draw_quad(&mut self, quad: Quad) {
+        match self.clip() {
+            Some(clip) if clip.is_empty() => {}
+            Some(clip) => self.quads.queue(quad.with_clip(clip.x, clip.y, clip.width, clip.height)),
+            None => self.quads.queue(quad),
+        }
+    }
+    
+    /// Make a shape glow in its own color, e.g. the border of a focused field
+    ///
+    /// The shape goes into the glow mask only; draw it with `draw_quad` too
+    /// for it to show up sharp under its glow.
+    pub fn draw_glow(&mut self, quad: Quad) {
+        match self.clip() {
+            Some(clip) if clip.is_empty() => {}
+            Some(clip) => self.quads.queue_glow(quad.with_clip(clip.x, clip.y, clip.width, clip.height)),
+            None => self.quads.queue_glow(quad),
+        }
+    }
+    
     /// Draw a colored rectangle
     pub fn draw_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: [f32; 4]) {
-        // Create a "block" character that will be repeated to fill the rectangle
-        let block = "█";
-        
-        // Calculate how many blocks we need to fill the width (assuming monospace font)
-        // This is an approximation and may need adjustment based on font size
-        let font_size = height;
-        let char_width = font_size * 0.6; // Approximate width of a character
-        let chars_needed = (width / char_width).ceil() as usize;
-        
-        // Create a string of blocks
-        let block_row = block.repeat(chars_needed);
-        
-        // Draw the block string with the specified color
-        self.draw_text(
-            &block_row,
-            x,
-            y,
-            font_size,
-            color,
-        );
+        self.draw_quad(Quad::rect(x, y, width, height, color));
+    }
+    
+    /// Draw a colored rectangle with rounded corners
+    pub fn draw_rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, color: [f32; 4]) {
+        self.draw_quad(Quad::rect(x, y, width, height, color).with_corner_radius(radius));
+    }
+    
+    /// Draw a rectangle filled with a gradient
+    pub fn draw_rect_gradient(&mut self, x: f32, y: f32, width: f32, height: f32, gradient: Gradient) {
+        self.draw_quad(Quad::rect(x, y, width, height, [0.0; 4]).with_gradient(gradient));
     }
     
     /// Draw a colored rectangle with wgpu::Color
@@ -116,118 +502,109 @@ impl<'a> RenderContext<'a> {
         );
     }
     
+    /// Draw the border of a rectangle, `thickness` wide inside its bounds
+    pub fn draw_outline(&mut self, x: f32, y: f32, width: f32, height: f32, thickness: f32, color: [f32; 4]) {
+        self.draw_quad(Quad::outline(x, y, width, height, thickness, color));
+    }
+
+    /// Draw the theme's soft shadow for a rounded rectangle, offset from it
+    pub fn draw_shadow(&mut self, theme: &CyberpunkTheme, x: f32, y: f32, width: f32, height: f32) {
+        let (dx, dy) = theme.shadow_offset();
+        let shadow = Quad::rect(x + dx, y + dy, width, height, theme.modal_shadow())
+            .with_corner_radius(theme.corner_radius())
+            .with_softness(theme.shadow_softness());
+        self.draw_quad(shadow);
+    }
+    
+    /// Draw a modal's body with the theme's shadow, rounded corners and neon border
+    ///
+    /// A header band `header_height` tall goes across the top; its lower corners
+    /// stay square. Pass 0 for a frame without one.
+    pub fn draw_modal_frame(&mut self, theme: &CyberpunkTheme, x: f32, y: f32, width: f32, height: f32, header_height: f32) {
+        let radius = theme.corner_radius();
+        self.draw_shadow(theme, x, y, width, height);
+        self.draw_rounded_rect(x, y, width, height, radius, theme.get_modal_bg_color());
+        if header_height > 0.0 {
+            self.push_clip_rect(x, y, width, header_height);
+            let header = Quad::rect(x, y, width, header_height + radius, [0.0; 4]).with_corner_radius(radius);
+            self.draw_quad(header.with_gradient(theme.header_gradient()));
+            self.pop_clip_rect();
+        }
+        let border = Quad::outline(x, y, width, height, theme.border_width(), theme.modal_border_glow());
+        self.draw_quad(border.with_corner_radius(radius));
+    }
+
     /// Draw a line from (x1, y1) to (x2, y2) with the specified thickness and color
     pub fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: [f32; 4]) {
-        // Calculate the length of the line
-        let dx = x2 - x1;
-        let dy = y2 - y1;
-        let length = (dx * dx + dy * dy).sqrt();
-        
-        if length < 0.01 {
-            return; // Line is too short to draw
-        }
-        
-        // Calculate the number of steps to draw
-        let steps = (length / (thickness * 0.5)).max(1.0) as usize;
-        
-        // Draw a series of small rectangles to represent the line
-        for i in 0..=steps {
-            let t = i as f32 / steps as f32;
-            let x = x1 + t * dx;
-            let y = y1 + t * dy;
-            
-            // Draw a small rect at this position
-            self.draw_rect(
-                x - thickness / 2.0,
-                y - thickness / 2.0,
-                thickness,
-                thickness,
-                color
-            );
-        }
+        self.draw_quad(Quad::line(x1, y1, x2, y2, thickness, color));
     }
     
+    /// Draw an arc of the circle around `center` as short straight lines
+    ///
+    /// `angles` are the start and the sweep in radians, clockwise from three o'clock.
+    pub fn draw_arc(&mut self, center: (f32, f32), radius: f32, angles: (f32, f32), thickness: f32, color: [f32; 4]) {
+        const SEGMENTS_PER_TURN: f32 = 64.0;
+        let (start, sweep) = angles;
+        let segments = (sweep.abs() / std::f32::consts::TAU * SEGMENTS_PER_TURN).ceil().max(1.0) as usize;
+        let point = |angle: f32| (center.0 + radius * angle.cos(), center.1 + radius * angle.sin());
+        let mut from = point(start);
+        for segment in 1..=segments {
+            let to = point(start + sweep * segment as f32 / segments as f32);
+            self.draw_line(from.0, from.1, to.0, to.1, thickness, color);
+            from = to;
+        }
+    }
+
     /// Draw a circle at (x, y) with the specified radius and color
     pub fn draw_circle(&mut self, x: f32, y: f32, radius: f32, color: [f32; 4]) {
-        // Approximate a circle using rectangles
-        
-        // For larger circles, we need finer step to make it smoother
-        let step_size = if radius < 10.0 {
-            1.0
-        } else if radius < 20.0 {
-            0.5
-        } else {
-            0.25
-        };
-        
-        // For each y offset from center
-        for y_offset in (-radius as i32)..=(radius as i32) {
-            let y_pos = y + y_offset as f32;
-            let y_delta = y_pos - y;
-            
-            // Calculate width at this y using circle equation: x² + y² = r²
-            // For a given y, x = sqrt(r² - y²)
-            let half_width = (radius * radius - y_delta * y_delta).sqrt().max(0.0);
-            
-            if half_width > 0.0 {
-                // Draw a horizontal line representing this portion of the circle
-                self.draw_rect(
-                    x - half_width,
-                    y_pos,
-                    half_width * 2.0,
-                    step_size,
-                    color
-                );
-            }
-        }
+        self.draw_quad(Quad::circle(x, y, radius, color));
     }
     
     /// Draw a colored rectangle with tuple coordinates
     pub fn draw_rect_tuple(&mut self, position: (f32, f32), size: (f32, f32), color: [f32; 4], corner_radius: f32) {
-        self.draw_rect(
+        self.draw_rounded_rect(
             position.0,
             position.1,
             size.0,
             size.1,
+            corner_radius,
             color,
         );
     }
     
     /// Draw a rectangle with tuples and wgpu::Color
     pub fn draw_rect_tuple_color(&mut self, position: (f32, f32), size: (f32, f32), color: wgpu::Color, corner_radius: f32) {
-        self.draw_rect_with_color(
-            position.0,
-            position.1,
-            size.0,
-            size.1,
-            color,
+        self.draw_rect_tuple(
+            position,
+            size,
+            [color.r as f32, color.g as f32, color.b as f32, color.a as f32],
+            corner_radius,
         );
     }
     
     /// Set a clipping rectangle for subsequent rendering
     pub fn scissor_rect(&mut self, position: (f32, f32), size: (f32, f32)) {
-        // In real implementation this would set up scissor rectangle
-        // For now just call push_clip_rect to maintain the API
         self.push_clip_rect(position.0, position.1, size.0, size.1);
     }
     
-    /// Reset scissor rectangle to full screen
+    /// Undo the last `scissor_rect`
     pub fn reset_scissor(&mut self) {
-        // In real implementation this would clear the scissor rectangle
-        // For now just call pop_clip_rect to maintain the API
         self.pop_clip_rect();
     }
     
-    /// Push a clipping rectangle onto the stack (this is a stub for now)
+    /// Confine drawing to a rectangle until the matching `pop_clip_rect`
+    ///
+    /// Nested clip rects only ever shrink the drawable area; the screen is the
+    /// outermost one.
     pub fn push_clip_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
-        // In a real implementation, this would set up a scissor rectangle
-        // or another clipping method, but for now it's just a stub
-        // since the current renderer doesn't support clipping
+        let outer = self.clip().unwrap_or(ClipRect { x: 0.0, y: 0.0, width: self.width, height: self.height });
+        self.clip_stack.push(outer.intersect(&ClipRect { x, y, width, height }));
     }
     
-    /// Pop a clipping rectangle from the stack (this is a stub for now)
+    /// Go back to the clip rect in effect before the last `push_clip_rect`
     pub fn pop_clip_rect(&mut self) {
-        // In a real implementation, this would restore the previous
-        // clipping rectangle, but for now it's just a stub
+        if self.clip_stack.pop().is_none() {
+            tracing::error!("pop_clip_rect without a matching push_clip_rect");
+        }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,312 @@
+use crate::ui::{damage, RenderContext, Widget, WidgetInfo, CyberpunkTheme, Quad};
+use crate::ui::text_metrics::text_width;
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+/// Height of each entry
+const ENTRY_HEIGHT: f32 = 28.0;
+
+/// Space around the entries and either side of their labels
+const PADDING: f32 = 6.0;
+
+/// Room left of the labels for the check mark
+const CHECK_WIDTH: f32 = 20.0;
+
+const TEXT_SIZE: f32 = 18.0;
+
+const MIN_WIDTH: f32 = 160.0;
+
+/// One entry of a context menu
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuItem<A> {
+    pub action: A,
+    pub label: String,
+    /// Shown with a check mark, e.g. the task's current priority
+    pub checked: bool,
+}
+
+impl<A> MenuItem<A> {
+    pub fn new(action: A, label: impl Into<String>) -> Self {
+        Self { action, label: label.into(), checked: false }
+    }
+
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.checked = checked;
+        self
+    }
+}
+
+/// A list of actions popped up at the pointer, e.g. on right-clicking a task
+///
+/// Picking an entry with the mouse or the keyboard hands its action back to
+/// the owner and closes the menu; Escape, or the owner on a click elsewhere,
+/// closes it without one.
+#[derive(Debug, Clone)]
+pub struct ContextMenu<A> {
+    x: f32,
+    y: f32,
+    width: f32,
+    items: Vec<MenuItem<A>>,
+    /// Entry under the pointer, or reached with the arrow keys
+    hovered: Option<usize>,
+    is_visible: bool,
+    theme: CyberpunkTheme,
+}
+
+impl<A: Copy> Default for ContextMenu<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Copy> ContextMenu<A> {
+    /// Create a hidden menu; call `show` to open it
+    pub fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: MIN_WIDTH,
+            items: Vec::new(),
+            hovered: None,
+            is_visible: false,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Check if the menu is currently open
+    pub fn is_visible(&self) -> bool {
+        self.is_visible
+    }
+
+    /// Open the menu with its top left corner at `(x, y)`, moved in so it
+    /// fits on a screen of the given size
+    pub fn show(&mut self, items: Vec<MenuItem<A>>, (x, y): (f32, f32), (screen_width, screen_height): (f32, f32)) {
+        self.width = items
+            .iter()
+            .map(|item| CHECK_WIDTH + text_width(&item.label, TEXT_SIZE) + PADDING * 3.0)
+            .fold(MIN_WIDTH, f32::max);
+        self.items = items;
+        self.x = x.min(screen_width - self.width).max(0.0);
+        self.y = y.min(screen_height - self.height()).max(0.0);
+        self.hovered = None;
+        self.is_visible = true;
+    }
+
+    /// Close the menu
+    pub fn hide(&mut self) {
+        self.is_visible = false;
+        self.hovered = None;
+    }
+
+    fn height(&self) -> f32 {
+        self.items.len() as f32 * ENTRY_HEIGHT + PADDING * 2.0
+    }
+
+    /// Index of the entry at a point
+    fn entry_at(&self, x: f32, y: f32) -> Option<usize> {
+        if !self.is_visible || x < self.x || x > self.x + self.width || y < self.y + PADDING {
+            return None;
+        }
+        let index = ((y - self.y - PADDING) / ENTRY_HEIGHT) as usize;
+        (index < self.items.len()).then_some(index)
+    }
+
+    /// Handle mouse movement for the hovered entry
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        if self.is_visible {
+            let hovered = self.entry_at(x, y);
+            damage::set(&mut self.hovered, hovered);
+        }
+    }
+
+    /// Handle mouse button release, returning the action of the entry clicked, if any
+    pub fn handle_mouse_up(&mut self, x: f32, y: f32) -> Option<A> {
+        let action = self.entry_at(x, y).map(|index| self.items[index].action);
+        if action.is_some() {
+            self.hide();
+        }
+        action
+    }
+
+    /// Handle keyboard input: the arrows move between the entries, Enter or
+    /// Space picks one and Escape closes the menu
+    pub fn handle_key_press(&mut self, key: KeyCode) -> Option<A> {
+        if !self.is_visible || self.items.is_empty() {
+            return None;
+        }
+        let count = self.items.len();
+        match key {
+            KeyCode::ArrowDown => self.hovered = Some(self.hovered.map_or(0, |index| (index + 1) % count)),
+            KeyCode::ArrowUp => self.hovered = Some(self.hovered.map_or(count - 1, |index| (index + count - 1) % count)),
+            KeyCode::Home => self.hovered = Some(0),
+            KeyCode::End => self.hovered = Some(count - 1),
+            KeyCode::Enter | KeyCode::Space => {
+                let action = self.hovered.map(|index| self.items[index].action);
+                if action.is_some() {
+                    self.hide();
+                }
+                return action;
+            }
+            KeyCode::Escape => self.hide(),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl<A: Copy> Widget for ContextMenu<A> {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        if self.is_visible {
+            out.push(WidgetInfo::of(self, depth));
+        }
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_visible {
+            return;
+        }
+
+        let height = self.height();
+        let radius = self.theme.corner_radius();
+        ctx.draw_shadow(&self.theme, self.x, self.y, self.width, height);
+        ctx.draw_rounded_rect(self.x, self.y, self.width, height, radius, self.theme.modal_background());
+        ctx.draw_quad(Quad::outline(self.x, self.y, self.width, height, 1.0, self.theme.modal_border_glow()).with_corner_radius(radius));
+
+        for (index, item) in self.items.iter().enumerate() {
+            let entry_y = self.y + PADDING + index as f32 * ENTRY_HEIGHT;
+            if self.hovered == Some(index) {
+                ctx.draw_rect(self.x + 2.0, entry_y, self.width - 4.0, ENTRY_HEIGHT, self.theme.filter_button_selected_bg());
+            }
+            let text_y = entry_y + (ENTRY_HEIGHT - TEXT_SIZE) / 2.0 - 2.0;
+            if item.checked {
+                ctx.draw_text("✓", self.x + PADDING, text_y, TEXT_SIZE, self.theme.cyan());
+            }
+            ctx.draw_text(&item.label, self.x + PADDING + CHECK_WIDTH, text_y, TEXT_SIZE, self.theme.modal_text());
+        }
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height())
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, _height: f32) {
+        self.width = width;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if !self.is_visible || !self.contains_point(x, y) {
+            return None;
+        }
+        Some(if self.entry_at(x, y).is_some() { CursorIcon::Pointer } else { CursorIcon::Default })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::prelude::{Priority, Status, TodoItem, TodoList};
+    use crate::ui::testing::UiDriver;
+    use crate::ui::{UiEvent, Widget};
+    use winit::keyboard::{KeyCode, ModifiersState};
+
+    #[test]
+    fn test_context_menu_acts_on_a_task() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Alpha", "Beta"]);
+        let (row_x, row_y) = driver.widget().item_widget(ids[1]).unwrap().position();
+
+        // Right-clicking selects the task and opens its menu at the pointer;
+        // the first entry completes it
+        let (x, y) = (row_x + 100.0, row_y + 10.0);
+        driver.right_click_at(x, y);
+        assert!(driver.widget().is_menu_open());
+        assert_eq!(driver.widget().selected_item(), Some(ids[1]));
+        driver.click_at(x + 50.0, y + 20.0);
+        assert!(!driver.widget().is_menu_open());
+        assert_eq!(driver.task(ids[1]).unwrap().status(), Status::Completed);
+        assert_eq!(driver.take_events(), vec![UiEvent::StatusChanged { id: ids[1], status: Status::Completed }]);
+
+        // A click elsewhere only closes it
+        driver.right_click_at(x, y);
+        driver.click_at(x, y + 300.0);
+        assert!(!driver.widget().is_menu_open());
+        assert!(driver.take_events().is_empty());
+
+        // From the keyboard: the third entry adds a subtask and opens the editor on it
+        driver.press_with(ModifiersState::SHIFT, KeyCode::F10);
+        assert!(driver.widget().is_menu_open());
+        for _ in 0..3 {
+            driver.press(KeyCode::ArrowDown);
+        }
+        driver.press(KeyCode::Enter);
+        let subtask = driver.widget().selected_item().unwrap();
+        assert_eq!(driver.task(subtask).unwrap().parent_id(), Some(ids[1]));
+        let rows = driver.widget().shown_items();
+        assert_eq!(rows.iter().position(|&id| id == subtask), rows.iter().position(|&id| id == ids[1]).map(|row| row + 1));
+        assert_eq!(driver.take_events(), vec![UiEvent::TaskAdded { id: subtask }, UiEvent::EditRequested { id: subtask }]);
+
+        // Escape closes the menu rather than quitting
+        driver.press_with(ModifiersState::SHIFT, KeyCode::F10);
+        driver.press(KeyCode::Escape);
+        assert!(!driver.widget().is_menu_open());
+
+        // The fourth entry is the high priority
+        driver.press_with(ModifiersState::SHIFT, KeyCode::F10);
+        for _ in 0..4 {
+            driver.press(KeyCode::ArrowDown);
+        }
+        driver.press(KeyCode::Enter);
+        assert_eq!(driver.task(subtask).unwrap().priority(), Priority::High);
+        assert_eq!(driver.take_events(), vec![UiEvent::PriorityChanged { id: subtask, priority: Priority::High }]);
+    }
+
+    #[test]
+    fn test_context_menu_snoozes_dated_tasks() {
+        let mut todo_list = TodoList::new("Test");
+        let id = todo_list.add_item(TodoItem::new("Dentist").with_due_date(1_750_000_000));
+        let mut driver = UiDriver::new(todo_list);
+        let (row_x, row_y) = driver.widget().item_widget(id).unwrap().position();
+        driver.right_click_at(row_x + 100.0, row_y + 10.0);
+        driver.press(KeyCode::Escape);
+
+        // Snooze comes after Copy Title; the app decides the new due date
+        driver.press_with(ModifiersState::SHIFT, KeyCode::F10);
+        for _ in 0..8 {
+            driver.press(KeyCode::ArrowDown);
+        }
+        driver.press(KeyCode::Enter);
+        assert_eq!(driver.take_events(), vec![UiEvent::SnoozeRequested { id }]);
+        assert_eq!(driver.task(id).unwrap().due_date(), Some(1_750_000_000));
+    }
+
+    #[test]
+    fn test_context_menu_focuses_open_tasks() {
+        let mut todo_list = TodoList::new("Test");
+        let id = todo_list.add_item(TodoItem::new("Write report"));
+        let mut driver = UiDriver::new(todo_list);
+        let (row_x, row_y) = driver.widget().item_widget(id).unwrap().position();
+        driver.right_click_at(row_x + 100.0, row_y + 10.0);
+        driver.press(KeyCode::Escape);
+
+        // Without a due date, Focus follows Copy Title
+        driver.press_with(ModifiersState::SHIFT, KeyCode::F10);
+        for _ in 0..8 {
+            driver.press(KeyCode::ArrowDown);
+        }
+        driver.press(KeyCode::Enter);
+        assert_eq!(driver.take_events(), vec![UiEvent::FocusRequested { id }]);
+    }
+}
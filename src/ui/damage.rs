@@ -0,0 +1,52 @@
+// Damage tracking: whether something changed what's on screen
+//
+// Most pointer moves change nothing visible, yet drawing a frame for each
+// runs the whole post-processing chain. Widgets set the hover and drag state
+// they draw through `set`, which flags the frame as damaged only when the
+// value actually changes; the event loop takes the flag when it runs out of
+// events and skips drawing while it stays clear. Like the text metrics, the
+// flag is process-wide so nested widgets don't need a context handed down.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DAMAGED: AtomicBool = AtomicBool::new(false);
+
+/// Flag the frame as needing a redraw
+pub fn invalidate() {
+    DAMAGED.store(true, Ordering::Relaxed);
+}
+
+/// Whether anything was invalidated since the last call, clearing the flag
+pub fn take() -> bool {
+    DAMAGED.swap(false, Ordering::Relaxed)
+}
+
+/// Set `slot` to `value`, invalidating the frame if that changed it
+///
+/// Returns whether it did.
+pub fn set<T: PartialEq>(slot: &mut T, value: T) -> bool {
+    if *slot == value {
+        return false;
+    }
+    *slot = value;
+    invalidate();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_only_damages_on_change() {
+        let mut hovered = None;
+        assert!(set(&mut hovered, Some(2)));
+        assert_eq!(hovered, Some(2));
+        assert!(!set(&mut hovered, Some(2)));
+        assert!(set(&mut hovered, None));
+
+        // Other tests may set the flag too, but only the event loop clears it
+        invalidate();
+        assert!(take());
+    }
+}
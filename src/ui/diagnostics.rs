@@ -0,0 +1,343 @@
+// Frame diagnostics overlay
+//
+// Toggled with the `diagnostics` keybind (F3). A panel in the top-left corner
+// shows how long the last frame took on the CPU, how long the GPU spent on the
+// scene, its text, the bloom and the glow, how many draw calls, quads and
+// glyphs the frame drew, and how much memory the render targets take.
+//
+// GPU times come from timestamp queries written between the passes, on
+// adapters that support writing them inside an encoder. The timestamps are
+// read back without waiting for the GPU, so the times shown are a frame or
+// two old, and none are recorded while the panel is hidden.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use wgpu::{Buffer, BufferDescriptor, BufferUsages, CommandEncoder, Device, Features, QuerySet, QuerySetDescriptor, QueryType, Queue};
+
+use crate::i18n::{format_number, tr, tr_args};
+use crate::ui::{CyberpunkTheme, DrawStats, RenderContext};
+
+/// Device features the GPU timings need; request them where the adapter has them
+pub const GPU_TIMER_FEATURES: Features = Features::TIMESTAMP_QUERY.union(Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+const PANEL_WIDTH: f32 = 280.0;
+const PANEL_MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 22.0;
+
+/// A stretch of the frame timed on the GPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuPass {
+    /// The scene's shapes, from clearing the scene target on
+    Scene,
+    /// The scene's text
+    Text,
+    Bloom,
+    /// The glow mask's effect, on its way to the screen
+    Glow,
+}
+
+impl GpuPass {
+    pub const ALL: [GpuPass; 4] = [GpuPass::Scene, GpuPass::Text, GpuPass::Bloom, GpuPass::Glow];
+
+    fn label(self) -> &'static str {
+        match self {
+            GpuPass::Scene => "diagnostics-pass-scene",
+            GpuPass::Text => "diagnostics-pass-text",
+            GpuPass::Bloom => "diagnostics-pass-bloom",
+            GpuPass::Glow => "diagnostics-pass-glow",
+        }
+    }
+
+    /// Queries of the timestamps written before and after the pass
+    fn queries(self) -> (u32, u32) {
+        let index = self as u32;
+        (index * 2, index * 2 + 1)
+    }
+}
+
+/// Timestamps for a begin and an end of each pass
+const QUERY_COUNT: u32 = GpuPass::ALL.len() as u32 * 2;
+const QUERY_BYTES: u64 = QUERY_COUNT as u64 * wgpu::QUERY_SIZE as u64;
+
+/// States of the readback buffer's mapping, set from the map callback
+const MAP_PENDING: u8 = 0;
+const MAP_DONE: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// Milliseconds each pass took, from its timestamps in `GpuPass::queries` order
+///
+/// `period` is nanoseconds per timestamp tick. A pass whose end comes before
+/// its begin, as after a GPU clock reset, takes no time.
+pub fn pass_times(timestamps: &[u64], period: f32) -> [f32; 4] {
+    GpuPass::ALL.map(|pass| {
+        let (begin, end) = pass.queries();
+        match (timestamps.get(begin as usize), timestamps.get(end as usize)) {
+            (Some(begin), Some(end)) => end.saturating_sub(*begin) as f32 * period / 1_000_000.0,
+            _ => 0.0,
+        }
+    })
+}
+
+/// A byte count in the largest unit it makes at least one of, e.g. (24.6, "MB")
+pub fn byte_unit(bytes: u64) -> (f64, &'static str) {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    (value, UNITS[unit])
+}
+
+/// A byte count for the panel, e.g. "24.6 MB" in the UI language's number format
+fn format_bytes(bytes: u64) -> String {
+    let (value, unit) = byte_unit(bytes);
+    let decimals = if unit == "B" { 0 } else { 1 };
+    format!("{} {}", format_number(value, decimals), unit)
+}
+
+/// Timestamp queries around the passes of a frame, and their readback
+struct GpuTimer {
+    queries: QuerySet,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    /// Whether this frame's passes are being timed
+    recording: bool,
+    /// Mapping state of the readback buffer while a frame's timestamps are on their way
+    in_flight: Option<Arc<AtomicU8>>,
+    /// Milliseconds per pass of the last frame read back
+    times: Option<[f32; 4]>,
+}
+
+impl GpuTimer {
+    /// A timer for `device`, or None if it wasn't made with `GPU_TIMER_FEATURES`
+    fn new(device: &Device, queue: &Queue) -> Option<Self> {
+        if !device.features().contains(GPU_TIMER_FEATURES) {
+            return None;
+        }
+        let queries = device.create_query_set(&QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let buffer = |label, usage| device.create_buffer(&BufferDescriptor {
+            label: Some(label),
+            size: QUERY_BYTES,
+            usage,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            queries,
+            resolve_buffer: buffer("Timestamp Resolve Buffer", BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC),
+            readback_buffer: buffer("Timestamp Readback Buffer", BufferUsages::MAP_READ | BufferUsages::COPY_DST),
+            period: queue.get_timestamp_period(),
+            recording: false,
+            in_flight: None,
+            times: None,
+        })
+    }
+
+    /// Pick up the last frame's timestamps if they've arrived, and time this
+    /// frame if the readback buffer is free again
+    fn begin_frame(&mut self, device: &Device, record: bool) {
+        if let Some(state) = self.in_flight.clone() {
+            device.poll(wgpu::Maintain::Poll);
+            match state.load(Ordering::Acquire) {
+                MAP_DONE => {
+                    let timestamps: Vec<u64> = self.readback_buffer
+                        .slice(..)
+                        .get_mapped_range()
+                        .chunks_exact(8)
+                        .map(|bytes| u64::from_ne_bytes(bytes.try_into().expect("chunks are 8 bytes")))
+                        .collect();
+                    self.readback_buffer.unmap();
+                    self.times = Some(pass_times(&timestamps, self.period));
+                    self.in_flight = None;
+                }
+                MAP_FAILED => self.in_flight = None,
+                _ => {}
+            }
+        }
+        self.recording = record && self.in_flight.is_none();
+    }
+
+    fn write(&self, encoder: &mut CommandEncoder, query: u32) {
+        if self.recording {
+            encoder.write_timestamp(&self.queries, query);
+        }
+    }
+
+    /// Copy this frame's timestamps to the readback buffer, after its last pass
+    fn resolve(&self, encoder: &mut CommandEncoder) {
+        if self.recording {
+            encoder.resolve_query_set(&self.queries, 0..QUERY_COUNT, &self.resolve_buffer, 0);
+            encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, QUERY_BYTES);
+        }
+    }
+
+    /// Ask for the readback buffer once the frame that fills it has been submitted
+    fn submitted(&mut self) {
+        if !self.recording {
+            return;
+        }
+        self.recording = false;
+        let state = Arc::new(AtomicU8::new(MAP_PENDING));
+        let callback_state = state.clone();
+        self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            callback_state.store(if result.is_ok() { MAP_DONE } else { MAP_FAILED }, Ordering::Release);
+        });
+        self.in_flight = Some(state);
+    }
+}
+
+/// The frame diagnostics panel and the measurements it shows
+pub struct Diagnostics {
+    visible: bool,
+    theme: CyberpunkTheme,
+    /// None where the adapter can't time passes
+    timer: Option<GpuTimer>,
+    cpu_ms: f32,
+    stats: DrawStats,
+    target_bytes: u64,
+}
+
+impl Diagnostics {
+    pub fn new(device: &Device, queue: &Queue) -> Self {
+        Self {
+            visible: false,
+            theme: CyberpunkTheme::new(),
+            timer: GpuTimer::new(device, queue),
+            cpu_ms: 0.0,
+            stats: DrawStats::default(),
+            target_bytes: 0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Start a frame: read back finished GPU timings and, while shown, time this one
+    pub fn begin_frame(&mut self, device: &Device) {
+        if let Some(timer) = self.timer.as_mut() {
+            timer.begin_frame(device, self.visible);
+        }
+    }
+
+    /// Mark where `pass` starts in the frame's commands
+    pub fn begin_pass(&self, encoder: &mut CommandEncoder, pass: GpuPass) {
+        if let Some(timer) = &self.timer {
+            timer.write(encoder, pass.queries().0);
+        }
+    }
+
+    /// Mark where `pass` ends in the frame's commands
+    pub fn end_pass(&self, encoder: &mut CommandEncoder, pass: GpuPass) {
+        if let Some(timer) = &self.timer {
+            timer.write(encoder, pass.queries().1);
+        }
+    }
+
+    /// Copy out the frame's timestamps; called after its last pass, before `finish`
+    pub fn end_frame(&self, encoder: &mut CommandEncoder) {
+        if let Some(timer) = &self.timer {
+            timer.resolve(encoder);
+        }
+    }
+
+    /// Start reading the timestamps back once the frame's commands are submitted
+    pub fn submitted(&mut self) {
+        if let Some(timer) = self.timer.as_mut() {
+            timer.submitted();
+        }
+    }
+
+    /// What the frame drew, and the bytes its render targets take
+    pub fn record_draws(&mut self, stats: DrawStats, target_bytes: u64) {
+        self.stats = stats;
+        self.target_bytes = target_bytes;
+    }
+
+    /// How long the frame took on the CPU, from the start of its update to submit
+    pub fn record_cpu_time(&mut self, ms: f32) {
+        self.cpu_ms = ms;
+    }
+
+    /// Panel lines for the last measured frame
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![tr_args("diagnostics-cpu", &[("ms", &format_number(self.cpu_ms as f64, 2))])];
+        match self.timer.as_ref().map(|timer| timer.times) {
+            Some(Some(times)) => {
+                for (pass, ms) in GpuPass::ALL.iter().zip(times) {
+                    lines.push(tr_args("diagnostics-gpu-pass", &[("pass", &tr(pass.label())), ("ms", &format_number(ms as f64, 2))]));
+                }
+            }
+            // The first timings are still on their way
+            Some(None) => lines.push(tr("diagnostics-gpu-waiting")),
+            None => lines.push(tr("diagnostics-gpu-unsupported")),
+        }
+        lines.push(tr_args("diagnostics-draws", &[("draws", &self.stats.draws.to_string())]));
+        lines.push(tr_args("diagnostics-quads", &[("quads", &self.stats.quads.to_string()), ("glyphs", &self.stats.glyphs.to_string())]));
+        lines.push(tr_args("diagnostics-memory", &[("size", &format_bytes(self.target_bytes))]));
+        lines
+    }
+
+    pub fn render(&self, ctx: &mut RenderContext) {
+        if !self.visible {
+            return;
+        }
+
+        let lines = self.lines();
+        let panel_height = LINE_HEIGHT * (lines.len() + 1) as f32 + 16.0;
+        ctx.draw_rect(PANEL_MARGIN, PANEL_MARGIN, PANEL_WIDTH, panel_height, self.theme.modal_background());
+        ctx.draw_outline(PANEL_MARGIN, PANEL_MARGIN, PANEL_WIDTH, panel_height, 1.0, self.theme.cyan());
+        ctx.draw_label(
+            "diagnostics-title",
+            PANEL_MARGIN + 10.0, PANEL_MARGIN + 8.0,
+            self.theme.text_size(),
+            self.theme.neon_pink(),
+        );
+        for (index, line) in lines.iter().enumerate() {
+            ctx.draw_text(
+                line,
+                PANEL_MARGIN + 10.0, PANEL_MARGIN + 8.0 + LINE_HEIGHT * (index + 1) as f32,
+                self.theme.small_text_size(),
+                self.theme.bright_text(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pass_times_from_timestamps() {
+        // Scene, text, bloom and glow, each a begin and an end; the overlay runs between bloom and glow
+        let timestamps = [1_000, 3_000, 3_000, 3_500, 3_500, 9_500, 20_000, 21_000];
+        assert_eq!(pass_times(&timestamps, 1_000.0), [2.0, 0.5, 6.0, 1.0]);
+        // A clock that went backwards gives no time rather than wrapping
+        assert_eq!(pass_times(&[5, 1, 0, 0, 0, 0, 0, 0], 1.0)[0], 0.0);
+        assert_eq!(pass_times(&[], 1.0), [0.0; 4]);
+    }
+
+    #[test]
+    fn test_byte_units() {
+        assert_eq!(byte_unit(512), (512.0, "B"));
+        assert_eq!(byte_unit(1536), (1.5, "KB"));
+        let (megabytes, unit) = byte_unit(3 * 1280 * 720 * 4);
+        assert_eq!(unit, "MB");
+        assert!((megabytes - 10.55).abs() < 0.01);
+        // Gigabytes are as far as it goes
+        assert_eq!(byte_unit(5 << 40), (5120.0, "GB"));
+    }
+}
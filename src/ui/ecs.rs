@@ -0,0 +1,385 @@
+// Entity/component storage for lightweight widgets
+//
+// A widget here is just an `Entity` id; what it has (a position, bounds, a
+// style, text, whether it reacts to the pointer, child layout) are separate
+// components stored densely per type, so systems walk tight arrays and a
+// widget only pays for the components it uses. Systems are free functions over
+// the `World`: `layout` places rows of children, `hover` and `hit_test` answer
+// pointer queries, and `draw` renders everything with a style.
+//
+// Each component type lives in a sparse set: `sparse` maps an entity index to
+// a slot in `dense`, so lookups are O(1) and iteration never visits holes.
+
+use winit::window::CursorIcon;
+
+use crate::profiling::profile_scope;
+use crate::ui::{damage, RenderContext, WidgetInfo};
+
+/// Id of a widget in a `World`
+///
+/// The generation tells a despawned entity apart from a later one that reuses
+/// its slot, so stale ids find nothing instead of someone else's components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+/// Top-left corner in window pixels
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Size in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Bounds {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// How `draw` paints an entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    /// Fill behind the text; fully transparent draws nothing
+    pub background: [f32; 4],
+    /// Fill while hovered, if different
+    pub hover_background: Option<[f32; 4]>,
+    pub text_color: [f32; 4],
+    pub text_size: f32,
+    /// Offset of the text from the top-left corner
+    pub padding: (f32, f32),
+}
+
+/// Text drawn by `draw`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Text {
+    /// Message id in the active locale, translated once rather than every frame
+    Label(&'static str),
+    /// Text shown as is, e.g. user input
+    Plain(String),
+}
+
+/// Makes an entity react to the pointer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interactive {
+    pub hovered: bool,
+    pub cursor: CursorIcon,
+}
+
+impl Default for Interactive {
+    fn default() -> Self {
+        Self { hovered: false, cursor: CursorIcon::Pointer }
+    }
+}
+
+/// Lays its children out left to right from its own position
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Row {
+    pub gap: f32,
+    pub children: Vec<Entity>,
+}
+
+/// Components of one type, indexed by entity
+#[derive(Debug, Clone)]
+pub struct Storage<T> {
+    sparse: Vec<Option<usize>>,
+    dense: Vec<(Entity, T)>,
+}
+
+impl<T> Default for Storage<T> {
+    fn default() -> Self {
+        Self { sparse: Vec::new(), dense: Vec::new() }
+    }
+}
+
+impl<T> Storage<T> {
+    /// Give an entity this component, replacing the one it had
+    pub fn insert(&mut self, entity: Entity, value: T) {
+        let index = entity.index as usize;
+        if index >= self.sparse.len() {
+            self.sparse.resize(index + 1, None);
+        }
+        match self.sparse[index] {
+            Some(slot) => self.dense[slot] = (entity, value),
+            None => {
+                self.sparse[index] = Some(self.dense.len());
+                self.dense.push((entity, value));
+            }
+        }
+    }
+
+    /// Take the component away from an entity
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.slot(entity)?;
+        self.sparse[entity.index as usize] = None;
+        let (_, value) = self.dense.swap_remove(slot);
+        if let Some((moved, _)) = self.dense.get(slot) {
+            self.sparse[moved.index as usize] = Some(slot);
+        }
+        Some(value)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.slot(entity).map(|slot| &self.dense[slot].1)
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.slot(entity).map(|slot| &mut self.dense[slot].1)
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.slot(entity).is_some()
+    }
+
+    /// Entities with this component and their values, in no particular order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Entity, &T)> {
+        self.dense.iter().map(|(entity, value)| (*entity, value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.dense.iter_mut().map(|(entity, value)| (*entity, value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    fn slot(&self, entity: Entity) -> Option<usize> {
+        let slot = (*self.sparse.get(entity.index as usize)?)?;
+        (self.dense[slot].0 == entity).then_some(slot)
+    }
+}
+
+/// Entities and their components
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    /// Current generation of every slot
+    generations: Vec<u32>,
+    /// Despawned slots, reused by `spawn`
+    free: Vec<u32>,
+    pub positions: Storage<Position>,
+    pub bounds: Storage<Bounds>,
+    pub styles: Storage<Style>,
+    pub texts: Storage<Text>,
+    pub interactive: Storage<Interactive>,
+    pub rows: Storage<Row>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an entity without components
+    pub fn spawn(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(index) => Entity { index, generation: self.generations[index as usize] },
+            None => {
+                self.generations.push(0);
+                Entity { index: self.generations.len() as u32 - 1, generation: 0 }
+            }
+        }
+    }
+
+    /// Remove an entity and all its components
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        self.positions.remove(entity);
+        self.bounds.remove(entity);
+        self.styles.remove(entity);
+        self.texts.remove(entity);
+        self.interactive.remove(entity);
+        self.rows.remove(entity);
+        self.generations[entity.index as usize] += 1;
+        self.free.push(entity.index);
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.generations.get(entity.index as usize) == Some(&entity.generation)
+    }
+
+    /// Number of live entities
+    pub fn len(&self) -> usize {
+        self.generations.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Position and size of an entity that has both
+    pub fn rect(&self, entity: Entity) -> Option<(Position, Bounds)> {
+        Some((*self.positions.get(entity)?, *self.bounds.get(entity)?))
+    }
+
+    /// Whether a point is inside an entity's rectangle
+    pub fn contains_point(&self, entity: Entity, x: f32, y: f32) -> bool {
+        self.rect(entity).is_some_and(|(position, bounds)| {
+            x >= position.x && x <= position.x + bounds.width && y >= position.y && y <= position.y + bounds.height
+        })
+    }
+}
+
+/// Place the children of every row, nested rows included
+pub fn layout(world: &mut World) {
+    profile_scope!("ecs_layout");
+    // Only start from rows nobody contains; nested rows move with their parent
+    let nested: Vec<Entity> = world.rows.iter().flat_map(|(_, row)| row.children.iter().copied()).collect();
+    let roots: Vec<Entity> = world.rows.iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| !nested.contains(entity))
+        .collect();
+    for root in roots {
+        layout_row(world, root, 0);
+    }
+}
+
+fn layout_row(world: &mut World, entity: Entity, depth: usize) {
+    // A row containing itself would recurse forever
+    const MAX_DEPTH: usize = 32;
+    let (Some(row), Some(&origin)) = (world.rows.get(entity), world.positions.get(entity)) else {
+        return;
+    };
+    let (gap, children) = (row.gap, row.children.clone());
+
+    let mut x = origin.x;
+    for child in children {
+        world.positions.insert(child, Position { x, y: origin.y });
+        x += world.bounds.get(child).map_or(0.0, |bounds| bounds.width) + gap;
+        if depth < MAX_DEPTH {
+            layout_row(world, child, depth + 1);
+        }
+    }
+}
+
+/// Topmost interactive entity under a point, later entities being on top
+pub fn hit_test(world: &World, x: f32, y: f32) -> Option<Entity> {
+    world.interactive.iter()
+        .rev()
+        .map(|(entity, _)| entity)
+        .find(|&entity| world.contains_point(entity, x, y))
+}
+
+/// Mark the interactive entity under the pointer as hovered, returning it
+pub fn hover(world: &mut World, x: f32, y: f32) -> Option<Entity> {
+    let hit = hit_test(world, x, y);
+    for (entity, interactive) in world.interactive.iter_mut() {
+        damage::set(&mut interactive.hovered, Some(entity) == hit);
+    }
+    hit
+}
+
+/// Cursor to show over the interactive entity under a point
+pub fn cursor_at(world: &World, x: f32, y: f32) -> Option<CursorIcon> {
+    hit_test(world, x, y).and_then(|entity| world.interactive.get(entity)).map(|interactive| interactive.cursor)
+}
+
+/// Draw every styled entity that has a position and bounds
+pub fn draw(world: &World, ctx: &mut RenderContext) {
+    profile_scope!("ecs_draw");
+    for (entity, style) in world.styles.iter() {
+        let Some((position, bounds)) = world.rect(entity) else {
+            continue;
+        };
+        let hovered = world.interactive.get(entity).is_some_and(|interactive| interactive.hovered);
+        let background = style.hover_background.filter(|_| hovered).unwrap_or(style.background);
+        if background[3] > 0.0 {
+            ctx.draw_rect(position.x, position.y, bounds.width, bounds.height, background);
+        }
+
+        let (text_x, text_y) = (position.x + style.padding.0, position.y + style.padding.1);
+        match world.texts.get(entity) {
+            Some(Text::Label(id)) => ctx.draw_label(id, text_x, text_y, style.text_size, style.text_color),
+            Some(Text::Plain(text)) => ctx.draw_text(text, text_x, text_y, style.text_size, style.text_color),
+            None => {}
+        }
+    }
+}
+
+/// Describe every entity with a rectangle to the debug inspector
+pub fn inspect(world: &World, depth: usize, out: &mut Vec<WidgetInfo>) {
+    for (entity, position) in world.positions.iter() {
+        if let Some(bounds) = world.bounds.get(entity) {
+            let hovered = world.interactive.get(entity).is_some_and(|interactive| interactive.hovered);
+            out.push(WidgetInfo::new("Entity", (position.x, position.y), (bounds.width, bounds.height), depth).with_hovered(hovered));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boxed(world: &mut World, width: f32, height: f32) -> Entity {
+        let entity = world.spawn();
+        world.bounds.insert(entity, Bounds { width, height });
+        world.interactive.insert(entity, Interactive::default());
+        entity
+    }
+
+    #[test]
+    fn test_despawned_ids_go_stale() {
+        let mut world = World::new();
+        let first = boxed(&mut world, 10.0, 10.0);
+        let second = boxed(&mut world, 20.0, 20.0);
+
+        world.despawn(first);
+        assert!(!world.is_alive(first));
+        assert!(world.bounds.get(first).is_none());
+        assert_eq!(world.bounds.get(second), Some(&Bounds { width: 20.0, height: 20.0 }));
+
+        // The slot is reused, but the old id doesn't see the new entity
+        let third = world.spawn();
+        assert_eq!(third.index, first.index);
+        world.texts.insert(third, Text::Plain("new".to_string()));
+        assert!(world.texts.get(first).is_none());
+        assert_eq!(world.len(), 2);
+    }
+
+    #[test]
+    fn test_rows_lay_out_nested_children() {
+        let mut world = World::new();
+        let a = boxed(&mut world, 100.0, 30.0);
+        let b = boxed(&mut world, 50.0, 30.0);
+        let c = boxed(&mut world, 40.0, 30.0);
+
+        let inner = world.spawn();
+        world.bounds.insert(inner, Bounds { width: 90.0, height: 30.0 });
+        world.rows.insert(inner, Row { gap: 0.0, children: vec![b, c] });
+        let outer = world.spawn();
+        world.positions.insert(outer, Position { x: 10.0, y: 20.0 });
+        world.rows.insert(outer, Row { gap: 5.0, children: vec![a, inner] });
+
+        layout(&mut world);
+        let x = |entity| world.positions.get(entity).unwrap().x;
+        assert_eq!((x(a), x(inner), x(b), x(c)), (10.0, 115.0, 115.0, 165.0));
+        assert_eq!(world.positions.get(c).unwrap().y, 20.0);
+    }
+
+    #[test]
+    fn test_hit_testing_prefers_the_topmost() {
+        let mut world = World::new();
+        let below = boxed(&mut world, 100.0, 100.0);
+        let above = boxed(&mut world, 10.0, 10.0);
+        world.positions.insert(below, Position { x: 0.0, y: 0.0 });
+        world.positions.insert(above, Position { x: 5.0, y: 5.0 });
+
+        assert_eq!(hover(&mut world, 8.0, 8.0), Some(above));
+        assert!(world.interactive.get(above).unwrap().hovered);
+        assert!(!world.interactive.get(below).unwrap().hovered);
+        assert_eq!(hit_test(&world, 50.0, 50.0), Some(below));
+        assert_eq!(cursor_at(&world, 200.0, 50.0), None);
+
+        // Removing the component makes it transparent to the pointer
+        world.interactive.remove(above);
+        assert_eq!(hit_test(&world, 8.0, 8.0), Some(below));
+    }
+}
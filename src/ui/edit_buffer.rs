@@ -0,0 +1,282 @@
+// Editable text shared by the text fields
+//
+// `TextInput` and `TextArea` lay out and draw their text differently, but edit
+// it the same way: a cursor, a selection anchored at one end, text an input
+// method is composing, and the clipboard. `EditBuffer` holds that state and
+// the edits on it; the widgets clean up incoming text, move the cursor by
+// their own layout and react to the changes.
+
+use crate::ui::Clipboard;
+
+/// Character classes used to find word boundaries on double-click and Ctrl+Arrow
+#[derive(PartialEq)]
+enum CharClass {
+    Word,
+    Space,
+    Other,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else if c.is_whitespace() {
+        CharClass::Space
+    } else {
+        CharClass::Other
+    }
+}
+
+/// The longest start of `text` that fits in `max_bytes` without splitting a character
+pub(super) fn truncate_to(text: &str, max_bytes: usize) -> &str {
+    let mut end = max_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Byte range of the word (or run of spaces/punctuation) containing the byte offset
+///
+/// An offset past the end means the last character's run; empty text has none.
+pub(super) fn word_at(text: &str, offset: usize) -> Option<(usize, usize)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    if chars.is_empty() {
+        return None;
+    }
+
+    // Index of the character under the offset; past the end means the last one
+    let index = chars
+        .iter()
+        .position(|&(byte, c)| offset < byte + c.len_utf8())
+        .unwrap_or(chars.len() - 1);
+    let class = char_class(chars[index].1);
+
+    let mut start = index;
+    while start > 0 && char_class(chars[start - 1].1) == class {
+        start -= 1;
+    }
+    let mut end = index + 1;
+    while end < chars.len() && char_class(chars[end].1) == class {
+        end += 1;
+    }
+    Some((chars[start].0, chars.get(end).map_or(text.len(), |&(byte, _)| byte)))
+}
+
+/// Byte offset of the character boundary before `offset`
+pub(super) fn previous_boundary(text: &str, offset: usize) -> usize {
+    text[..offset].char_indices().next_back().map_or(0, |(byte, _)| byte)
+}
+
+/// Byte offset of the character boundary after `offset`
+pub(super) fn next_boundary(text: &str, offset: usize) -> usize {
+    text[offset..].chars().next().map_or(offset, |c| offset + c.len_utf8())
+}
+
+/// Byte offset of the start of the word before `offset`, or the end of the one after it
+///
+/// Spaces next to the cursor are skipped first, then a run of word
+/// characters or of punctuation.
+pub(super) fn word_boundary(text: &str, offset: usize, forward: bool) -> usize {
+    // Each character in walking order, with the offset the cursor stops at before it
+    let chars: Vec<(usize, char)> = if forward {
+        text[offset..].char_indices().map(|(byte, c)| (offset + byte, c)).collect()
+    } else {
+        text[..offset].char_indices().rev().map(|(byte, c)| (byte + c.len_utf8(), c)).collect()
+    };
+
+    let mut class = None;
+    for (stop, c) in chars {
+        let next = char_class(c);
+        match &class {
+            None if next == CharClass::Space => {}
+            None => class = Some(next),
+            Some(class) if *class != next => return stop,
+            Some(_) => {}
+        }
+    }
+    if forward { text.len() } else { 0 }
+}
+
+/// Text being edited, with its cursor, selection and composed text
+#[derive(Debug, Clone, Default)]
+pub struct EditBuffer {
+    text: String,
+    /// Byte offset of the cursor
+    cursor: usize,
+    /// Other end of the selection, if any; the cursor is the moving end
+    anchor: Option<usize>,
+    /// Text an input method is composing, shown at the cursor until committed
+    preedit: String,
+    /// The input method's cursor within `preedit`, as a byte range
+    preedit_cursor: Option<(usize, usize)>,
+}
+
+impl EditBuffer {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte offset of the cursor
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replace the text, with the cursor at its end and nothing selected or composed
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.cursor = self.text.len();
+        self.anchor = None;
+        self.clear_preedit();
+    }
+
+    /// Byte range of the selected text, if anything is selected
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.anchor?;
+        if anchor == self.cursor {
+            return None;
+        }
+        Some((anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// The selected text, if anything is selected
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection().map(|(start, end)| &self.text[start..end])
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.anchor = None;
+    }
+
+    /// Select the whole text
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.text.len();
+    }
+
+    /// Select the word (or run of spaces/punctuation) containing the byte offset
+    pub fn select_word_at(&mut self, offset: usize) {
+        if let Some((start, end)) = word_at(&self.text, offset) {
+            self.anchor = Some(start);
+            self.cursor = end;
+        }
+    }
+
+    /// Move the cursor to an offset, selecting from where it was when `extend` is set
+    pub fn move_cursor(&mut self, offset: usize, extend: bool) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        self.cursor = offset;
+        self.anchor = extend.then_some(anchor);
+    }
+
+    /// Byte offset of the word boundary a Ctrl+Arrow leads to
+    pub fn word_boundary(&self, forward: bool) -> usize {
+        word_boundary(&self.text, self.cursor, forward)
+    }
+
+    /// Byte offset of the character boundary before or after the cursor
+    pub fn char_boundary(&self, forward: bool) -> usize {
+        if forward { next_boundary(&self.text, self.cursor) } else { previous_boundary(&self.text, self.cursor) }
+    }
+
+    /// Text an input method is composing
+    pub fn preedit(&self) -> &str {
+        &self.preedit
+    }
+
+    /// Byte offset of the input method's cursor within the composed text
+    pub fn preedit_cursor(&self) -> usize {
+        self.preedit_cursor
+            .map(|(start, _)| start)
+            .filter(|&start| self.preedit.is_char_boundary(start))
+            .unwrap_or(self.preedit.len())
+    }
+
+    pub fn set_preedit(&mut self, text: &str, cursor: Option<(usize, usize)>) {
+        self.preedit = text.to_string();
+        self.preedit_cursor = cursor;
+    }
+
+    pub fn clear_preedit(&mut self) {
+        self.preedit.clear();
+        self.preedit_cursor = None;
+    }
+
+    /// Insert text at the cursor, replacing the selection, returning whether
+    /// anything changed
+    pub fn insert(&mut self, text: &str) -> bool {
+        let deleted = self.delete_selection();
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+        deleted || !text.is_empty()
+    }
+
+    /// Remove the selected text, returning whether anything was removed
+    pub fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                self.text.replace_range(start..end, "");
+                self.cursor = start;
+                self.anchor = None;
+                true
+            }
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    /// Remove the selection, or else the character before the cursor (Backspace)
+    /// or after it (Delete), returning whether anything was removed
+    pub fn delete(&mut self, forward: bool) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        let boundary = self.char_boundary(forward);
+        let (start, end) = (self.cursor.min(boundary), self.cursor.max(boundary));
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+        start < end
+    }
+
+    /// Put the selected text on the clipboard, returning whether anything was selected
+    pub fn copy(&self, clipboard: &mut Clipboard) -> bool {
+        let Some(selected) = self.selected_text() else {
+            return false;
+        };
+        clipboard.set_text(selected);
+        true
+    }
+
+    /// Move the selected text to the clipboard, returning whether anything was selected
+    pub fn cut(&mut self, clipboard: &mut Clipboard) -> bool {
+        self.copy(clipboard) && self.delete_selection()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_replace_the_selection() {
+        let mut buffer = EditBuffer::default();
+        buffer.set_text("größer".to_string());
+        assert!(buffer.delete(false));
+        assert_eq!((buffer.text(), buffer.cursor()), ("größe", 7));
+        assert!(!buffer.delete(true));
+
+        buffer.move_cursor(0, false);
+        buffer.move_cursor(buffer.word_boundary(true), true);
+        assert_eq!(buffer.selected_text(), Some("größe"));
+        assert!(buffer.insert("klein"));
+        assert_eq!((buffer.text(), buffer.selection()), ("klein", None));
+
+        let mut clipboard = Clipboard::in_memory();
+        buffer.select_word_at(2);
+        assert!(buffer.cut(&mut clipboard));
+        assert_eq!(buffer.text(), "");
+        assert!(!buffer.copy(&mut clipboard));
+        assert_eq!(clipboard.text().as_deref(), Some("klein"));
+    }
+}
@@ -0,0 +1,136 @@
+// Typed UI events
+//
+// Widgets don't call back into application code. They push `UiEvent`s into an
+// `EventQueue`, and whoever owns them drains the queue after handling input and
+// reduces the events into state changes, as `Command`s run through the dispatcher. Events the application may care about
+// are passed upwards the same way.
+
+use uuid::Uuid;
+use crate::core::prelude::{BulkEdit, Command, Dispatcher, Priority, SortOrder, Status};
+
+/// Something the user did in the UI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiEvent {
+    /// An item's status was changed, e.g. with its checkbox
+    StatusChanged { id: Uuid, status: Status },
+    /// The edit button of an item was clicked
+    EditRequested { id: Uuid },
+    /// The delete button of an item was clicked
+    DeleteRequested { id: Uuid },
+    /// A task was created from the title input
+    TaskAdded { id: Uuid },
+    /// A tag chip on an item was clicked; `index` is the tag's place in the item's tags
+    TagClicked { id: Uuid, index: usize },
+    /// A link in an item's title or description was clicked; `index` is its place in `markdown::item_links`
+    LinkClicked { id: Uuid, index: usize },
+    /// An attachment in an item's details was clicked; `index` is its place in the item's attachments
+    AttachmentClicked { id: Uuid, index: usize },
+    /// The disclosure toggle of an item with children was clicked, to hide (`collapsed`) or show them
+    CollapseToggled { id: Uuid, collapsed: bool },
+    /// An item's priority was changed, e.g. from its context menu
+    PriorityChanged { id: Uuid, priority: Priority },
+    /// "Copy title" was picked in an item's context menu; the application owns the clipboard
+    CopyTitleRequested { id: Uuid },
+    /// "Snooze" was picked in an item's context menu; how long for is up to the application
+    SnoozeRequested { id: Uuid },
+    /// "Focus" was picked in an item's context menu; the application runs the timer
+    FocusRequested { id: Uuid },
+    /// A bulk action changed the picked items, as one step of the undo history
+    BulkEdited { edit: BulkEdit, count: usize },
+    /// The list was sorted differently from the sort control; the order is saved with the list
+    SortChanged { order: SortOrder },
+}
+
+/// Events waiting to be handled, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct EventQueue {
+    events: Vec<UiEvent>,
+}
+
+impl EventQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue an event
+    pub fn push(&mut self, event: UiEvent) {
+        self.events.push(event);
+    }
+
+    /// Take all queued events, leaving the queue empty
+    pub fn drain(&mut self) -> Vec<UiEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Check if there is nothing to handle
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+/// The change to the todo list an event asks for, if any
+///
+/// Events that only concern the view or the application (edit requests, added
+/// tasks, tag clicks, collapsing, copying, snoozing, focusing) leave the list alone, as
+/// do bulk edits and sorting, which the list applied already.
+pub fn command_for(event: &UiEvent) -> Option<Command> {
+    match *event {
+        UiEvent::StatusChanged { id, status } => Some(Command::SetStatus { id, status }),
+        UiEvent::DeleteRequested { id } => Some(Command::RemoveItem { id }),
+        UiEvent::PriorityChanged { id, priority } => Some(Command::SetPriority { id, priority }),
+        UiEvent::EditRequested { .. }
+        | UiEvent::TaskAdded { .. }
+        | UiEvent::TagClicked { .. }
+        | UiEvent::LinkClicked { .. }
+        | UiEvent::AttachmentClicked { .. }
+        | UiEvent::CollapseToggled { .. }
+        | UiEvent::CopyTitleRequested { .. }
+        | UiEvent::SnoozeRequested { .. }
+        | UiEvent::FocusRequested { .. }
+        | UiEvent::BulkEdited { .. }
+        | UiEvent::SortChanged { .. } => None,
+    }
+}
+
+/// Apply an event to the todo list, returning whether the list changed
+///
+/// Events about items that are gone by now change nothing.
+pub fn reduce(dispatcher: &Dispatcher, event: &UiEvent) -> bool {
+    command_for(event).is_some_and(|command| dispatcher.execute(command).unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::core::prelude::TodoList;
+
+    #[test]
+    fn test_queue_drains_in_order() {
+        let mut queue = EventQueue::new();
+        let id = Uuid::new_v4();
+        queue.push(UiEvent::EditRequested { id });
+        queue.push(UiEvent::DeleteRequested { id });
+
+        assert_eq!(queue.drain(), vec![UiEvent::EditRequested { id }, UiEvent::DeleteRequested { id }]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_reduce_updates_the_list() {
+        let mut todo_list = TodoList::new("Test");
+        let id = todo_list.create_item("Write tests");
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(todo_list)));
+
+        assert!(reduce(&dispatcher, &UiEvent::StatusChanged { id, status: Status::Completed }));
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().status(), Status::Completed);
+        assert!(!reduce(&dispatcher, &UiEvent::StatusChanged { id, status: Status::Completed }));
+
+        assert!(!reduce(&dispatcher, &UiEvent::EditRequested { id }));
+        assert!(reduce(&dispatcher, &UiEvent::DeleteRequested { id }));
+        assert!(dispatcher.lock().get_item(id).is_none());
+        assert!(!reduce(&dispatcher, &UiEvent::DeleteRequested { id }));
+        assert_eq!(dispatcher.take_applied().len(), 2);
+    }
+}
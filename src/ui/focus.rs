@@ -0,0 +1,136 @@
+use crate::ui::{CyberpunkTheme, RenderContext};
+
+/// Length of one pulse of the focus ring's glow, in seconds
+const PULSE_PERIOD: f32 = 1.6;
+
+/// Pulses after focus moves, before the ring settles at full glow
+const PULSES: f32 = 2.0;
+
+/// Gap between an element and the ring drawn around it
+const RING_GAP: f32 = 3.0;
+
+/// Which of a container's elements has keyboard focus, with the ring drawn around it
+///
+/// The container names its focusable elements with its own `T` and passes
+/// them in tab order when cycling, so the order always follows what is shown.
+/// Text inputs keep their own focus flag for the caret; containers set it
+/// from here rather than toggling the flags directly.
+#[derive(Debug, Clone)]
+pub struct FocusManager<T> {
+    focused: Option<T>,
+    /// Time since focus moved, up to the end of the last pulse
+    pulse: f32,
+    theme: CyberpunkTheme,
+}
+
+impl<T: Copy + PartialEq> Default for FocusManager<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + PartialEq> FocusManager<T> {
+    /// Create a manager with nothing focused
+    pub fn new() -> Self {
+        Self {
+            focused: None,
+            pulse: 0.0,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    pub fn focused(&self) -> Option<T> {
+        self.focused
+    }
+
+    pub fn is_focused(&self, target: T) -> bool {
+        self.focused == Some(target)
+    }
+
+    /// Focus an element, or nothing; returns whether focus moved
+    pub fn set_focus(&mut self, target: Option<T>) -> bool {
+        if self.focused == target {
+            return false;
+        }
+        self.focused = target;
+        self.pulse = 0.0;
+        true
+    }
+
+    /// Move focus to the element after the focused one in `order` (Tab), or before it (Shift+Tab)
+    ///
+    /// Wraps around at either end. Without focus, or when the focused element
+    /// is no longer in `order`, Tab starts at the first element and Shift+Tab
+    /// at the last. Returns the newly focused element.
+    pub fn cycle(&mut self, order: &[T], backward: bool) -> Option<T> {
+        if order.is_empty() {
+            self.set_focus(None);
+            return None;
+        }
+        let current = self.focused.and_then(|focused| order.iter().position(|&target| target == focused));
+        let next = match (current, backward) {
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+            (Some(index), false) => (index + 1) % order.len(),
+            (Some(index), true) => (index + order.len() - 1) % order.len(),
+        };
+        self.set_focus(Some(order[next]));
+        self.focused
+    }
+
+    /// Whether the ring's glow is still pulsing, so frames need drawing
+    pub fn is_pulsing(&self) -> bool {
+        self.focused.is_some() && self.pulse < PULSE_PERIOD * PULSES
+    }
+
+    /// Advance the ring's glow
+    pub fn update(&mut self, delta_time: f32) {
+        self.pulse = (self.pulse + delta_time).min(PULSE_PERIOD * PULSES);
+    }
+
+    /// Draw the neon ring around the bounds of the focused element
+    pub fn draw_ring(&self, ctx: &mut RenderContext, x: f32, y: f32, width: f32, height: f32) {
+        let glow = 0.5 + 0.5 * (std::f32::consts::TAU * self.pulse / PULSE_PERIOD).cos();
+        let [r, g, b, _] = self.theme.cyan();
+        for (spread, alpha) in [(4.0, 0.12 + 0.2 * glow), (2.0, 0.3 + 0.3 * glow)] {
+            let inset = RING_GAP + spread;
+            ctx.draw_outline(x - inset, y - inset, width + inset * 2.0, height + inset * 2.0, 2.0, [r, g, b, alpha]);
+        }
+        ctx.draw_outline(x - RING_GAP, y - RING_GAP, width + RING_GAP * 2.0, height + RING_GAP * 2.0, 2.0, self.theme.cyan());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_wraps_and_restarts() {
+        let mut focus = FocusManager::new();
+        let order = ['a', 'b', 'c'];
+        assert_eq!(focus.cycle(&order, true), Some('c'));
+        assert_eq!(focus.cycle(&order, false), Some('a'));
+        assert_eq!(focus.cycle(&order, false), Some('b'));
+        assert_eq!(focus.cycle(&order, true), Some('a'));
+
+        // An element that went away starts the order over
+        focus.set_focus(Some('x'));
+        assert_eq!(focus.cycle(&order, false), Some('a'));
+        assert_eq!(focus.cycle(&[], false), None);
+        assert!(!focus.is_focused('a'));
+    }
+
+    #[test]
+    fn test_pulse_settles() {
+        let mut focus = FocusManager::new();
+        assert!(!focus.is_pulsing());
+        focus.set_focus(Some('a'));
+        assert!(focus.is_pulsing());
+        focus.update(PULSE_PERIOD * PULSES);
+        assert!(!focus.is_pulsing());
+
+        // Moving focus pulses again
+        focus.set_focus(Some('b'));
+        assert!(focus.is_pulsing());
+    }
+}
@@ -0,0 +1,174 @@
+// The fonts text is drawn with, and the fallbacks for glyphs the UI font lacks
+//
+// glyph_brush draws each run of text in a single font, so text is split into
+// runs by the first font that has a glyph for each character. Both bundled
+// fonts are compiled in; emoji and CJK come from system fonts where found.
+// Color emoji fonts hold images rather than outlines, which `ColorGlyphs` draws.
+
+use std::path::Path;
+use wgpu_glyph::ab_glyph::{Font, FontArc};
+
+use crate::config::FontConfig;
+
+/// Inconsolata, the UI font unless the config file names another
+pub const DEFAULT_FONT: &[u8] = include_bytes!("../../fonts/Inconsolata-Regular.ttf");
+
+/// DejaVu Sans Mono, for symbols widgets draw that Inconsolata lacks, such as ✎ and ▶
+pub const SYMBOL_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSansMono.ttf");
+
+/// Color emoji fonts, tried ahead of the system fallbacks when `font.color_emoji` is on
+#[cfg(not(target_arch = "wasm32"))]
+const COLOR_EMOJI_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoColorEmoji.ttf",
+    "/usr/share/fonts/noto/NotoColorEmoji.ttf",
+    "/usr/share/fonts/google-noto-emoji/NotoColorEmoji.ttf",
+    "/System/Library/Fonts/Apple Color Emoji.ttc",
+];
+
+/// System fonts tried, in order, after the bundled ones; missing files are skipped
+///
+/// These are outline fonts, so emoji come out monochrome from them.
+#[cfg(not(target_arch = "wasm32"))]
+const SYSTEM_FALLBACKS: &[&str] = &[
+    // Linux
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSansSymbols2-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSansSymbols2-Regular.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    // macOS
+    "/System/Library/Fonts/Apple Symbols.ttf",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    // Windows
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+];
+
+/// The bundled fonts, the UI font first
+pub fn bundled() -> Vec<FontArc> {
+    [DEFAULT_FONT, SYMBOL_FONT]
+        .into_iter()
+        .map(|data| FontArc::try_from_slice(data).expect("Bundled fonts are valid"))
+        .collect()
+}
+
+/// The fonts to draw with, in the order glyphs are looked up
+///
+/// That's `font.path` if set (or else Inconsolata), the `font.fallbacks` in
+/// order, the bundled symbol font and then whichever system fallbacks exist,
+/// color emoji first unless turned off. Fonts that can't be used are left out,
+/// with the problem recorded.
+pub fn load(font: &FontConfig, problems: &mut Vec<String>) -> Vec<FontArc> {
+    let mut fonts = bundled();
+    if let Some(custom) = font.path.as_deref().and_then(|path| load_file(path).map_err(|e| problems.push(e)).ok()) {
+        fonts[0] = custom;
+    }
+    let fallbacks: Vec<FontArc> = font
+        .fallbacks
+        .iter()
+        .filter_map(|path| load_file(path).map_err(|e| problems.push(e)).ok())
+        .collect();
+    fonts.splice(1..1, fallbacks);
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let color_emoji = if font.color_emoji { COLOR_EMOJI_FONTS } else { &[] };
+        fonts.extend(
+            color_emoji
+                .iter()
+                .chain(SYSTEM_FALLBACKS)
+                .map(Path::new)
+                .filter(|path| path.exists())
+                .filter_map(|path| load_file(path).ok()),
+        );
+    }
+    fonts
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_file(path: &Path) -> Result<FontArc, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read font {}: {}", path.display(), e))?;
+    FontArc::try_from_vec(data).map_err(|e| format!("Invalid font {}: {}", path.display(), e))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_file(path: &Path) -> Result<FontArc, String> {
+    Err(format!("Custom fonts are not supported in the browser, ignoring {}", path.display()))
+}
+
+/// Index of the first font with a glyph for `c`, or the first font's when none has one
+pub fn font_for(fonts: &[FontArc], c: char) -> usize {
+    fonts.iter().position(|font| font.glyph_id(c).0 != 0).unwrap_or(0)
+}
+
+/// Text split into runs that each draw in one of the fonts, as (font index, run)
+///
+/// Whitespace stays with the run before it, so a line in one font is a single run.
+pub fn runs<'t>(fonts: &[FontArc], text: &'t str) -> Vec<(usize, &'t str)> {
+    if fonts.len() < 2 {
+        return vec![(0, text)];
+    }
+    let mut runs: Vec<(usize, &str)> = Vec::new();
+    let mut start = 0;
+    let mut current = None;
+    for (byte, c) in text.char_indices() {
+        if c.is_whitespace() && current.is_some() {
+            continue;
+        }
+        let font = font_for(fonts, c);
+        match current {
+            Some(previous) if previous != font => {
+                runs.push((previous, &text[start..byte]));
+                start = byte;
+            }
+            _ => {}
+        }
+        current = Some(font);
+    }
+    runs.push((current.unwrap_or(0), &text[start..]));
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols_fall_back_to_the_bundled_font() {
+        let fonts = bundled();
+        assert_eq!(font_for(&fonts, 'a'), 0);
+        assert_eq!(font_for(&fonts, '✎'), 1);
+        assert_eq!(font_for(&fonts, '▶'), 1);
+        // A character no font has stays with the UI font, which draws its missing glyph box
+        assert_eq!(font_for(&fonts, '\u{10FFFD}'), 0);
+
+        assert_eq!(runs(&fonts, ""), [(0, "")]);
+        assert_eq!(runs(&fonts, "Edit task"), [(0, "Edit task")]);
+        assert_eq!(runs(&fonts, "✎ Edit ▶▼ now"), [(1, "✎ "), (0, "Edit "), (1, "▶▼ "), (0, "now")]);
+        assert_eq!(runs(&fonts[..1], "✎ Edit"), [(0, "✎ Edit")]);
+    }
+
+    #[test]
+    fn test_load_reports_unusable_fonts() {
+        let mut problems = Vec::new();
+        let config = FontConfig {
+            path: Some("fonts/missing.ttf".into()),
+            fallbacks: vec!["Cargo.toml".into()],
+            ..FontConfig::default()
+        };
+        let fonts = load(&config, &mut problems);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        // The bundled fonts take over, ahead of any system fallbacks
+        assert!(fonts.len() >= 2);
+        assert_eq!(font_for(&fonts[..2], '✎'), 1);
+
+        let mut problems = Vec::new();
+        let config = FontConfig { path: Some("fonts/DejaVuSansMono.ttf".into()), ..FontConfig::default() };
+        let fonts = load(&config, &mut problems);
+        assert!(problems.is_empty(), "{:?}", problems);
+        // The custom font comes first, so it draws the symbols it has itself
+        assert_eq!(font_for(&fonts, '✎'), 0);
+    }
+}
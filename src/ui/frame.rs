@@ -0,0 +1,161 @@
+// The passes every frame goes through
+//
+// The app, the embeddable `Runner` and the offscreen `HeadlessRenderer` all
+// draw the same way: clear the scene, draw what glows into it, bloom it, draw
+// the overlays that stay sharp on top, then add the neon glow on the way to
+// the output. `draw_frame` records and submits those passes once for all of
+// them; callers only say what goes in the scene and what goes on top, and
+// present or read back the output afterwards.
+
+use tracing::trace_span;
+use wgpu::util::StagingBelt;
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+use wgpu_glyph::GlyphBrush;
+
+use crate::profiling::profile_scope;
+use crate::ui::{ColorGlyphs, Diagnostics, FrameArena, GpuPass, PostProcessStack, QuadRenderer, RenderContext};
+
+/// The theme's background, behind everything a frame draws (#0A0A14)
+const BACKGROUND: wgpu::Color = wgpu::Color { r: 0.039, g: 0.039, b: 0.078, a: 1.0 };
+
+/// What a frame is drawn with, borrowed from whoever keeps it between frames
+pub struct FrameParts<'a> {
+    pub device: &'a Device,
+    pub queue: &'a Queue,
+    pub staging_belt: &'a mut StagingBelt,
+    pub glyph_brush: &'a mut GlyphBrush<()>,
+    pub quad_renderer: &'a mut QuadRenderer,
+    pub frame_arena: &'a mut FrameArena,
+    pub post_process: &'a mut PostProcessStack,
+    /// Draws color emoji; without it they're left out
+    pub color_glyphs: Option<&'a mut ColorGlyphs>,
+    /// Times the passes and draws its overlay on top of everything else
+    pub diagnostics: Option<&'a mut Diagnostics>,
+}
+
+/// How a frame is drawn: its size in physical pixels and what it looks like
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSettings {
+    pub width: u32,
+    pub height: u32,
+    /// Physical pixels per logical pixel
+    pub scale_factor: f32,
+    /// Animation time in seconds, from the frame clock
+    pub time: f32,
+    /// Color widgets glow in unless they pick one
+    pub glow_color: [f32; 4],
+}
+
+/// Draw a frame into `output` and submit it
+///
+/// What `scene` draws goes through the bloom; what `overlay` draws is put on
+/// top of the bloom result. Whatever either draws with `draw_glow` glows. The
+/// staging belt is finished but not recalled, so callers recall it once the
+/// frame is submitted, and present or read back `output` themselves.
+pub fn draw_frame(
+    parts: FrameParts,
+    settings: FrameSettings,
+    output: &TextureView,
+    scene: impl FnOnce(&mut RenderContext),
+    overlay: impl FnOnce(&mut RenderContext),
+) -> Result<(), String> {
+    profile_scope!("draw_frame");
+    let FrameParts { device, queue, staging_belt, glyph_brush, quad_renderer, frame_arena, post_process, color_glyphs, mut diagnostics } = parts;
+    post_process.set_time(settings.time);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Render Encoder") });
+    if let Some(diagnostics) = diagnostics.as_deref_mut() {
+        diagnostics.begin_frame(device);
+    }
+
+    // Clear the scene to the theme's background
+    let scene_span = trace_span!("scene_pass").entered();
+    begin_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Scene);
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Scene Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: post_process.scene_view(),
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(BACKGROUND),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    });
+
+    frame_arena.begin_frame();
+    let mut ctx = RenderContext::new(
+        queue,
+        staging_belt,
+        glyph_brush,
+        quad_renderer,
+        frame_arena,
+        settings.width as f32,
+        settings.height as f32,
+    )
+    .with_scale_factor(settings.scale_factor)
+    .with_time(settings.time)
+    .with_glow_color(settings.glow_color);
+    if let Some(color_glyphs) = color_glyphs {
+        ctx = ctx.with_color_glyphs(color_glyphs);
+    }
+
+    // The scene glows; shapes and text are timed apart
+    scene(&mut ctx);
+    ctx.draw_queued_shapes(device, &mut encoder, post_process.scene_view());
+    end_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Scene);
+    begin_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Text);
+    ctx.draw_queued_text(device, &mut encoder, post_process.scene_view())?;
+    end_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Text);
+    drop(scene_span);
+
+    begin_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Bloom);
+    trace_span!("bloom").in_scope(|| post_process.apply_bloom(&mut encoder));
+    end_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Bloom);
+
+    // Overlays stay sharp on top of the bloom
+    let overlay_span = trace_span!("overlay_pass").entered();
+    overlay(&mut ctx);
+    if let Some(diagnostics) = diagnostics.as_deref() {
+        diagnostics.render(&mut ctx);
+    }
+    ctx.draw_queued(device, &mut encoder, post_process.bloom_view())?;
+    ctx.draw_glow_queued(device, &mut encoder, post_process.glow_view())?;
+    if let Some(diagnostics) = diagnostics.as_deref_mut() {
+        diagnostics.record_draws(ctx.stats(), post_process.memory_bytes());
+    }
+    drop(overlay_span);
+
+    begin_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Glow);
+    trace_span!("glow").in_scope(|| post_process.apply_glow(&mut encoder, output));
+    end_pass(diagnostics.as_deref(), &mut encoder, GpuPass::Glow);
+    if let Some(diagnostics) = diagnostics.as_deref() {
+        diagnostics.end_frame(&mut encoder);
+    }
+
+    // The belt is finished before the commands that use it are submitted
+    let _submit_span = trace_span!("submit").entered();
+    staging_belt.finish();
+    queue.submit(std::iter::once(encoder.finish()));
+    if let Some(diagnostics) = diagnostics {
+        diagnostics.submitted();
+    }
+    Ok(())
+}
+
+/// Mark where a pass starts, for the diagnostics overlay's GPU times
+fn begin_pass(diagnostics: Option<&Diagnostics>, encoder: &mut CommandEncoder, pass: GpuPass) {
+    if let Some(diagnostics) = diagnostics {
+        diagnostics.begin_pass(encoder, pass);
+    }
+}
+
+/// Mark where a pass ends
+fn end_pass(diagnostics: Option<&Diagnostics>, encoder: &mut CommandEncoder, pass: GpuPass) {
+    if let Some(diagnostics) = diagnostics {
+        diagnostics.end_pass(encoder, pass);
+    }
+}
@@ -0,0 +1,150 @@
+// Fuzzy matching for the search field
+//
+// The characters of each search term have to appear in order, but not next
+// to each other, e.g. "bgr" finds "Buy groceries". Like skim and fzf, a match
+// scores higher where its characters start words or run together, and lower
+// the more it skips between them, so the closest matches sort first.
+
+/// Score of each matched character
+const SCORE_MATCH: i32 = 16;
+/// Extra for a character that starts a word, e.g. after a space or at the start
+const BONUS_BOUNDARY: i32 = 8;
+/// Extra for an upper case letter after a lower case one, as in "camelCase"
+const BONUS_CAMEL: i32 = 6;
+/// Extra for a character right after the one matched before it
+const BONUS_CONSECUTIVE: i32 = 4;
+/// Cost of skipping characters between two matched ones
+const PENALTY_GAP_START: i32 = 3;
+/// Cost of each skipped character after the first
+const PENALTY_GAP_EXTENSION: i32 = 1;
+
+/// How well a search matched some text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// Byte offsets of the matched characters in the text, in order
+    pub positions: Vec<usize>,
+}
+
+/// Match a search against text, ignoring case
+///
+/// Every whitespace-separated term of the query has to match for the text to
+/// match; their scores add up. An empty query matches anything with no score.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let mut found = FuzzyMatch::default();
+    for term in query.split_whitespace() {
+        let term_match = match_term(term, text)?;
+        found.score += term_match.score;
+        found.positions.extend(term_match.positions);
+    }
+    found.positions.sort_unstable();
+    found.positions.dedup();
+    Some(found)
+}
+
+/// The best scoring way to match one term's characters in order
+fn match_term(term: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = term.chars().map(fold).collect();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let (m, n) = (query.len(), chars.len());
+    if m > n {
+        return None;
+    }
+    let bonuses: Vec<i32> = (0..n)
+        .map(|j| bonus(j.checked_sub(1).map(|previous| chars[previous].1), chars[j].1))
+        .collect();
+
+    // scores[i][j]: best score for the term's first i + 1 characters with the
+    // last at text character j; from[i][j]: where character i - 1 went then
+    let mut scores = vec![vec![None; n]; m];
+    let mut from = vec![vec![0; n]; m];
+    for i in 0..m {
+        // Best earlier match of character i - 1 at least one character back,
+        // less the cost of the gap up to here
+        let mut carry: Option<(i32, usize)> = None;
+        for j in i..n {
+            if i > 0 && j >= 2 {
+                let start = scores[i - 1][j - 2].map(|score: i32| (score - PENALTY_GAP_START, j - 2));
+                let extend = carry.map(|(score, at)| (score - PENALTY_GAP_EXTENSION, at));
+                carry = start.into_iter().chain(extend).max_by_key(|&(score, _)| score);
+            }
+            if fold(chars[j].1) != query[i] {
+                continue;
+            }
+            let score = SCORE_MATCH + bonuses[j];
+            if i == 0 {
+                scores[i][j] = Some(score);
+                continue;
+            }
+            let consecutive = scores[i - 1][j - 1].map(|previous| (previous + BONUS_CONSECUTIVE, j - 1));
+            if let Some((previous, at)) = consecutive.into_iter().chain(carry).max_by_key(|&(score, _)| score) {
+                scores[i][j] = Some(previous + score);
+                from[i][j] = at;
+            }
+        }
+    }
+
+    let (mut at, score) = scores[m - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by_key(|&(_, score)| score)?;
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = chars[at].0;
+        at = from[i][at];
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+/// A character compared without case
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Extra score for matching `c`, depending on the character before it
+fn bonus(previous: Option<char>, c: char) -> i32 {
+    match previous {
+        None => BONUS_BOUNDARY,
+        Some(previous) if !previous.is_alphanumeric() && c.is_alphanumeric() => BONUS_BOUNDARY,
+        Some(previous) if previous.is_lowercase() && c.is_uppercase() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, text: &str) -> i32 {
+        fuzzy_match(query, text).unwrap().score
+    }
+
+    #[test]
+    fn test_characters_match_in_order() {
+        assert_eq!(fuzzy_match("bgr", "Buy groceries").unwrap().positions, [0, 4, 5]);
+        assert_eq!(fuzzy_match("BGR", "buy groceries").unwrap().positions, [0, 4, 5]);
+        assert_eq!(fuzzy_match("rgb", "Buy groceries"), None);
+        assert_eq!(fuzzy_match("groceriess", "Buy groceries"), None);
+        // Positions are byte offsets
+        assert_eq!(fuzzy_match("kln", "Grüße aus Köln").unwrap().positions, [12, 15, 16]);
+        assert_eq!(fuzzy_match("", "anything"), Some(FuzzyMatch::default()));
+    }
+
+    #[test]
+    fn test_every_term_must_match() {
+        let found = fuzzy_match("gro buy", "Buy groceries").unwrap();
+        assert_eq!(found.positions, [0, 1, 2, 4, 5, 6]);
+        assert_eq!(found.score, score("gro", "Buy groceries") + score("buy", "Buy groceries"));
+        assert_eq!(fuzzy_match("gro milk", "Buy groceries"), None);
+    }
+
+    #[test]
+    fn test_word_starts_and_runs_score_higher() {
+        assert!(score("rep", "Write report") > score("rep", "Prepare slides"));
+        assert!(score("fix", "Fix the build") > score("fix", "Fill in the tax form"));
+        assert!(score("tb", "runTests build") > score("tb", "stubborn"));
+        // The best placement is found even when an earlier one exists
+        assert_eq!(fuzzy_match("rep", "Prepare report").unwrap().positions, [8, 9, 10]);
+    }
+}
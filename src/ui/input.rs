@@ -0,0 +1,863 @@
+// Input helpers shared by the event loop and widgets
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, Ime, MouseButton, MouseScrollDelta, WindowEvent};
+use std::collections::HashMap;
+use std::fmt;
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey};
+
+use crate::config::normalize_chord;
+
+/// Maximum time between presses for them to count as one multi-click, in seconds
+pub const MULTI_CLICK_INTERVAL: f64 = 0.4;
+
+/// Maximum pointer travel between presses of a multi-click, in pixels
+pub const MULTI_CLICK_DISTANCE: f32 = 4.0;
+
+/// Pointer travel before a held button turns into a drag, in pixels
+pub const DRAG_THRESHOLD: f32 = 3.0;
+
+/// Default delay before a held key starts repeating, in seconds
+pub const KEY_REPEAT_DELAY: f32 = 0.4;
+
+/// Default interval between key repeats, in seconds
+pub const KEY_REPEAT_INTERVAL: f32 = 0.035;
+
+/// Current wall-clock time in seconds
+///
+/// Uses chrono rather than `std::time::Instant`, which panics in the browser.
+pub fn now_secs() -> f64 {
+    chrono::Utc::now().timestamp_millis() as f64 / 1000.0
+}
+
+/// Turns a stream of mouse presses into click counts
+///
+/// Presses that follow each other quickly and close together count up
+/// (1 = single, 2 = double, 3 = triple click, ...); anything else starts over at 1.
+#[derive(Debug, Clone)]
+pub struct ClickTracker {
+    max_interval: f64,
+    max_distance: f32,
+    last_press: Option<(f32, f32, f64)>,
+    count: u32,
+}
+
+impl ClickTracker {
+    /// Create a tracker with the default thresholds
+    pub fn new() -> Self {
+        Self {
+            max_interval: MULTI_CLICK_INTERVAL,
+            max_distance: MULTI_CLICK_DISTANCE,
+            last_press: None,
+            count: 0,
+        }
+    }
+
+    /// Use custom time and distance thresholds
+    pub fn with_thresholds(mut self, max_interval: f64, max_distance: f32) -> Self {
+        self.max_interval = max_interval;
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Record a press at `(x, y)` happening at `time` seconds, returning its click count
+    pub fn press(&mut self, x: f32, y: f32, time: f64) -> u32 {
+        let continues = match self.last_press {
+            Some((last_x, last_y, last_time)) => {
+                let distance = ((x - last_x).powi(2) + (y - last_y).powi(2)).sqrt();
+                time - last_time <= self.max_interval && distance <= self.max_distance
+            }
+            None => false,
+        };
+
+        self.count = if continues { self.count + 1 } else { 1 };
+        self.last_press = Some((x, y, time));
+        self.count
+    }
+
+    /// Click count of the most recent press
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Forget the previous press, e.g. when the window loses focus
+    pub fn reset(&mut self) {
+        self.last_press = None;
+        self.count = 0;
+    }
+}
+
+impl Default for ClickTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a pointer gesture currently stands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerPhase {
+    /// No button held
+    Idle,
+    /// A button is held but the pointer hasn't moved far enough to drag
+    Pressed { button: MouseButton, origin: (f32, f32) },
+    /// A button is held and the pointer is being dragged
+    Dragging { button: MouseButton, origin: (f32, f32), last: (f32, f32) },
+}
+
+/// Small state machine telling presses apart from drags
+///
+/// Only one button is tracked at a time; presses of other buttons during a
+/// gesture are ignored until it is released.
+#[derive(Debug, Clone)]
+pub struct PointerState {
+    phase: PointerPhase,
+    drag_threshold: f32,
+}
+
+impl PointerState {
+    /// Create an idle pointer with the default drag threshold
+    pub fn new() -> Self {
+        Self {
+            phase: PointerPhase::Idle,
+            drag_threshold: DRAG_THRESHOLD,
+        }
+    }
+
+    /// Use a custom drag threshold
+    pub fn with_drag_threshold(mut self, drag_threshold: f32) -> Self {
+        self.drag_threshold = drag_threshold;
+        self
+    }
+
+    /// Current phase of the gesture
+    pub fn phase(&self) -> PointerPhase {
+        self.phase
+    }
+
+    /// Record a button press at `(x, y)`
+    pub fn press(&mut self, button: MouseButton, x: f32, y: f32) {
+        if self.phase == PointerPhase::Idle {
+            self.phase = PointerPhase::Pressed { button, origin: (x, y) };
+        }
+    }
+
+    /// Record pointer movement, returning the `(dx, dy)` moved since the last
+    /// call while dragging
+    pub fn move_to(&mut self, x: f32, y: f32) -> Option<(f32, f32)> {
+        match self.phase {
+            PointerPhase::Idle => None,
+            PointerPhase::Pressed { button, origin } => {
+                let distance = ((x - origin.0).powi(2) + (y - origin.1).powi(2)).sqrt();
+                if distance < self.drag_threshold {
+                    return None;
+                }
+                self.phase = PointerPhase::Dragging { button, origin, last: (x, y) };
+                Some((x - origin.0, y - origin.1))
+            }
+            PointerPhase::Dragging { button, origin, last } => {
+                self.phase = PointerPhase::Dragging { button, origin, last: (x, y) };
+                Some((x - last.0, y - last.1))
+            }
+        }
+    }
+
+    /// Record a button release, returning true if it ended a drag
+    pub fn release(&mut self, button: MouseButton) -> bool {
+        match self.phase {
+            PointerPhase::Pressed { button: held, .. } if held == button => {
+                self.phase = PointerPhase::Idle;
+                false
+            }
+            PointerPhase::Dragging { button: held, .. } if held == button => {
+                self.phase = PointerPhase::Idle;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `button` is currently dragging
+    pub fn is_dragging(&self, button: MouseButton) -> bool {
+        matches!(self.phase, PointerPhase::Dragging { button: held, .. } if held == button)
+    }
+
+    /// Whether `button` is currently held, dragging or not
+    pub fn is_held(&self, button: MouseButton) -> bool {
+        match self.phase {
+            PointerPhase::Idle => false,
+            PointerPhase::Pressed { button: held, .. } | PointerPhase::Dragging { button: held, .. } => held == button,
+        }
+    }
+
+    /// Abandon the current gesture, e.g. when the window loses focus
+    pub fn reset(&mut self) {
+        self.phase = PointerPhase::Idle;
+    }
+}
+
+impl Default for PointerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keys that repeat while held: cursor movement and deletion
+pub fn is_repeatable(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::ArrowUp
+            | KeyCode::ArrowDown
+            | KeyCode::ArrowLeft
+            | KeyCode::ArrowRight
+            | KeyCode::Backspace
+            | KeyCode::Delete
+    )
+}
+
+/// Generates repeats for a held key with our own timing
+///
+/// OS repeat events arrive at whatever rate the platform likes (and not at
+/// all in some browsers), so the event loop ignores them for repeatable keys
+/// and asks `tick` how many repeats are due instead.
+#[derive(Debug, Clone)]
+pub struct KeyRepeat {
+    delay: f32,
+    interval: f32,
+    held: Option<(KeyCode, f32)>,
+}
+
+impl KeyRepeat {
+    /// Create a repeater with the default timing
+    pub fn new() -> Self {
+        Self {
+            delay: KEY_REPEAT_DELAY,
+            interval: KEY_REPEAT_INTERVAL,
+            held: None,
+        }
+    }
+
+    /// Use custom timing, in seconds
+    pub fn with_timing(mut self, delay: f32, interval: f32) -> Self {
+        self.set_timing(delay, interval);
+        self
+    }
+
+    /// Change the timing, e.g. after the config was edited
+    pub fn set_timing(&mut self, delay: f32, interval: f32) {
+        self.delay = delay.max(0.0);
+        // A zero interval would repeat forever within one tick
+        self.interval = interval.max(0.001);
+    }
+
+    /// Record a key press; only repeatable keys are tracked, and any other
+    /// key stops the current repeat like it does in native text fields
+    pub fn press(&mut self, key: KeyCode) {
+        self.held = is_repeatable(key).then_some((key, self.delay));
+    }
+
+    /// Record a key release, stopping repeats of that key
+    pub fn release(&mut self, key: KeyCode) {
+        if self.held.is_some_and(|(held, _)| held == key) {
+            self.held = None;
+        }
+    }
+
+    /// The key currently repeating (or waiting to), if any
+    pub fn held(&self) -> Option<KeyCode> {
+        self.held.map(|(key, _)| key)
+    }
+
+    /// Advance time, returning the held key and how many repeats are due
+    pub fn tick(&mut self, delta_time: f32) -> Option<(KeyCode, u32)> {
+        let (key, remaining) = self.held.as_mut()?;
+        *remaining -= delta_time;
+
+        let mut count = 0;
+        while *remaining <= 0.0 {
+            *remaining += self.interval;
+            count += 1;
+        }
+        (count > 0).then_some((*key, count))
+    }
+
+    /// Stop repeating, e.g. when the window loses focus and the release will never arrive
+    pub fn reset(&mut self) {
+        self.held = None;
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Named keys that survive recording; other named keys are replayed as unidentified
+const NAMED_KEYS: &[NamedKey] = &[
+    NamedKey::Escape, NamedKey::Enter, NamedKey::Tab, NamedKey::Space,
+    NamedKey::Backspace, NamedKey::Delete, NamedKey::Insert,
+    NamedKey::ArrowUp, NamedKey::ArrowDown, NamedKey::ArrowLeft, NamedKey::ArrowRight,
+    NamedKey::Home, NamedKey::End, NamedKey::PageUp, NamedKey::PageDown,
+    NamedKey::Shift, NamedKey::Control, NamedKey::Alt, NamedKey::Super,
+    NamedKey::F1, NamedKey::F2, NamedKey::F3, NamedKey::F4, NamedKey::F5, NamedKey::F6,
+    NamedKey::F7, NamedKey::F8, NamedKey::F9, NamedKey::F10, NamedKey::F11, NamedKey::F12,
+];
+
+/// A logical key in a form that can be written to a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyName {
+    /// A `NamedKey` variant, e.g. "Enter"
+    Named(String),
+    /// The text the key produces, e.g. "a"
+    Character(String),
+}
+
+impl KeyName {
+    pub fn from_key(key: &Key) -> Option<Self> {
+        match key {
+            Key::Named(named) => Some(KeyName::Named(format!("{:?}", named))),
+            Key::Character(text) => Some(KeyName::Character(text.to_string())),
+            _ => None,
+        }
+    }
+
+    pub fn to_key(&self) -> Key {
+        match self {
+            KeyName::Named(name) => NAMED_KEYS
+                .iter()
+                .find(|named| format!("{:?}", named) == *name)
+                .map_or(Key::Unidentified(winit::keyboard::NativeKey::Unidentified), |named| Key::Named(*named)),
+            KeyName::Character(text) => Key::Character(text.as_str().into()),
+        }
+    }
+}
+
+/// Whether keys act on the app or type into a text field
+///
+/// Shown in the status bar; a vim-style modal keymap would add its own modes here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    /// Keys run their bound actions
+    Normal,
+    /// A text field has focus and takes the keys that type
+    Insert,
+}
+
+impl InputMode {
+    /// Message id of the mode's name
+    pub fn label_id(self) -> &'static str {
+        match self {
+            InputMode::Normal => "status-mode-normal",
+            InputMode::Insert => "status-mode-insert",
+        }
+    }
+}
+
+/// Text for the focused text field, typed or composed with an input method (IME)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TextEvent {
+    /// Text still being composed, shown at the cursor without being part of
+    /// the field yet; empty once composition ends. `cursor` is the input
+    /// method's cursor or highlighted part, as a byte range within it.
+    Preedit { text: String, cursor: Option<(usize, usize)> },
+    /// Finished text to insert at the cursor
+    Insert(String),
+}
+
+/// Window input the app reacts to, detached from winit so it can be recorded and replayed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    CursorMoved { x: f32, y: f32 },
+    /// The left or middle button
+    MouseButton { middle: bool, pressed: bool },
+    /// The right button, which opens context menus
+    RightButton { pressed: bool },
+    /// Wheel movement in lines, or in pixels from touchpads
+    MouseWheel { x: f32, y: f32, pixels: bool },
+    /// `text` is what the press typed, composed dead keys included; keys
+    /// taken by an input method type nothing and arrive as `Text` instead
+    Key {
+        key: KeyName,
+        pressed: bool,
+        repeat: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+    },
+    /// Composition from an input method
+    Text(TextEvent),
+    Modifiers { shift: bool, control: bool, alt: bool, logo: bool },
+    Focused(bool),
+}
+
+impl InputEvent {
+    /// Translate a window event, if it's input the app cares about
+    ///
+    /// Positions and pixel scrolls come out in logical pixels, like the
+    /// layout, so recordings replay the same on any display.
+    pub fn from_window_event(event: &WindowEvent, scale_factor: f64) -> Option<Self> {
+        Some(match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = position.to_logical::<f32>(scale_factor);
+                InputEvent::CursorMoved { x: position.x, y: position.y }
+            }
+            WindowEvent::MouseInput { state, button: MouseButton::Right, .. } => InputEvent::RightButton {
+                pressed: *state == ElementState::Pressed,
+            },
+            WindowEvent::MouseInput { state, button, .. } => InputEvent::MouseButton {
+                middle: match button {
+                    MouseButton::Left => false,
+                    MouseButton::Middle => true,
+                    _ => return None,
+                },
+                pressed: *state == ElementState::Pressed,
+            },
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => InputEvent::MouseWheel { x: *x, y: *y, pixels: false },
+                MouseScrollDelta::PixelDelta(position) => {
+                    let position = position.to_logical::<f32>(scale_factor);
+                    InputEvent::MouseWheel { x: position.x, y: position.y, pixels: true }
+                }
+            },
+            WindowEvent::KeyboardInput { event, .. } => InputEvent::Key {
+                key: KeyName::from_key(&event.logical_key)?,
+                pressed: event.state == ElementState::Pressed,
+                repeat: event.repeat,
+                text: event.text.as_ref().map(|text| text.to_string()),
+            },
+            WindowEvent::Ime(ime) => InputEvent::Text(match ime {
+                Ime::Preedit(text, cursor) => TextEvent::Preedit { text: text.clone(), cursor: *cursor },
+                Ime::Commit(text) => TextEvent::Insert(text.clone()),
+                // Whatever was being composed is dropped
+                Ime::Disabled => TextEvent::Preedit { text: String::new(), cursor: None },
+                Ime::Enabled => return None,
+            }),
+            WindowEvent::ModifiersChanged(modifiers) => {
+                let state = modifiers.state();
+                InputEvent::Modifiers {
+                    shift: state.shift_key(),
+                    control: state.control_key(),
+                    alt: state.alt_key(),
+                    logo: state.super_key(),
+                }
+            }
+            WindowEvent::Focused(focused) => InputEvent::Focused(*focused),
+            _ => return None,
+        })
+    }
+
+    /// The button of a `MouseButton` event
+    pub fn button(middle: bool) -> MouseButton {
+        if middle { MouseButton::Middle } else { MouseButton::Left }
+    }
+
+    /// The scroll delta of a `MouseWheel` event
+    pub fn scroll_delta(x: f32, y: f32, pixels: bool) -> MouseScrollDelta {
+        if pixels {
+            MouseScrollDelta::PixelDelta(PhysicalPosition::new(x as f64, y as f64))
+        } else {
+            MouseScrollDelta::LineDelta(x, y)
+        }
+    }
+
+    /// The modifier state of a `Modifiers` event
+    pub fn modifiers(shift: bool, control: bool, alt: bool, logo: bool) -> ModifiersState {
+        let mut state = ModifiersState::empty();
+        for (held, modifier) in [
+            (shift, ModifiersState::SHIFT),
+            (control, ModifiersState::CONTROL),
+            (alt, ModifiersState::ALT),
+            (logo, ModifiersState::SUPER),
+        ] {
+            state.set(modifier, held);
+        }
+        state
+    }
+}
+
+/// An input event with when it happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimedInput {
+    /// Frames rendered before the event arrived; replays deliver it before the next update
+    pub frame: u64,
+    /// Seconds since the recording started, for click counting
+    pub time: f64,
+    pub input: InputEvent,
+}
+
+/// The key code of a named key the widgets handle
+pub fn named_keycode(key: &Key) -> Option<KeyCode> {
+    match key {
+        Key::Named(named) => key_to_keycode(named),
+        _ => None,
+    }
+}
+
+/// Convert a named key to the key code widgets expect
+pub fn key_to_keycode(key: &NamedKey) -> Option<KeyCode> {
+    match key {
+        NamedKey::Escape => Some(KeyCode::Escape),
+        NamedKey::Enter => Some(KeyCode::Enter),
+        NamedKey::Delete => Some(KeyCode::Delete),
+        NamedKey::Backspace => Some(KeyCode::Backspace),
+        NamedKey::ArrowUp => Some(KeyCode::ArrowUp),
+        NamedKey::ArrowDown => Some(KeyCode::ArrowDown),
+        NamedKey::ArrowLeft => Some(KeyCode::ArrowLeft),
+        NamedKey::ArrowRight => Some(KeyCode::ArrowRight),
+        NamedKey::Tab => Some(KeyCode::Tab),
+        NamedKey::Space => Some(KeyCode::Space),
+        NamedKey::Home => Some(KeyCode::Home),
+        NamedKey::End => Some(KeyCode::End),
+        _ => None,
+    }
+}
+
+/// Something a key chord can be bound to in the `[keybinds]` config table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Save,
+    Settings,
+    Inspector,
+    Profiler,
+    /// Show frame times, GPU pass timings and draw counts
+    Diagnostics,
+    Undo,
+    Redo,
+    AddAndContinue,
+    FocusSearch,
+    SelectLeft,
+    SelectRight,
+    SelectHome,
+    SelectEnd,
+    ToggleSidebar,
+    /// Move the list's selection up a row
+    SelectPrevious,
+    /// Move the list's selection down a row
+    SelectNext,
+    /// Delete the selected task with its subtasks
+    DeleteTask,
+    /// Open the editor on the selected task
+    EditTask,
+    /// Scroll the list up by a page
+    ScrollPageUp,
+    /// Scroll the list down by a page
+    ScrollPageDown,
+    /// Scroll to the top of the list
+    ScrollTop,
+    /// Scroll to the bottom of the list
+    ScrollBottom,
+    /// Move keyboard focus to the next input, button or row
+    FocusNext,
+    /// Move keyboard focus back to the previous one
+    FocusPrevious,
+    /// Select all the text of the focused field
+    SelectAll,
+    /// Copy the selected text to the clipboard
+    Copy,
+    /// Copy the selected text to the clipboard and remove it
+    Cut,
+    /// Insert the clipboard's text, replacing the selection
+    Paste,
+    /// Move the text cursor back to the start of a word
+    WordLeft,
+    /// Move the text cursor on to the end of a word
+    WordRight,
+    /// Extend the text selection back to the start of a word
+    SelectWordLeft,
+    /// Extend the text selection on to the end of a word
+    SelectWordRight,
+    /// Switch to the next present mode (Fifo, Mailbox, Immediate)
+    CyclePresentMode,
+    /// Open the selected task's context menu
+    ContextMenu,
+    /// Sync with the CalDAV server now
+    Sync,
+    /// Open the list's archive of completed tasks
+    Archive,
+    /// Start a focus session on the selected task, or pause or resume the running one
+    FocusTimer,
+    /// Stop the focus session or break without logging it
+    StopFocusTimer,
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::Quit,
+        Action::Save,
+        Action::Settings,
+        Action::Inspector,
+        Action::Profiler,
+        Action::Diagnostics,
+        Action::Undo,
+        Action::Redo,
+        Action::AddAndContinue,
+        Action::FocusSearch,
+        Action::SelectLeft,
+        Action::SelectRight,
+        Action::SelectHome,
+        Action::SelectEnd,
+        Action::ToggleSidebar,
+        Action::SelectPrevious,
+        Action::SelectNext,
+        Action::DeleteTask,
+        Action::EditTask,
+        Action::ScrollPageUp,
+        Action::ScrollPageDown,
+        Action::ScrollTop,
+        Action::ScrollBottom,
+        Action::FocusNext,
+        Action::FocusPrevious,
+        Action::SelectAll,
+        Action::Copy,
+        Action::Cut,
+        Action::Paste,
+        Action::WordLeft,
+        Action::WordRight,
+        Action::SelectWordLeft,
+        Action::SelectWordRight,
+        Action::CyclePresentMode,
+        Action::ContextMenu,
+        Action::Sync,
+        Action::Archive,
+        Action::FocusTimer,
+        Action::StopFocusTimer,
+    ];
+
+    /// Name of the action in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Save => "save",
+            Action::Settings => "settings",
+            Action::Inspector => "inspector",
+            Action::Profiler => "profiler",
+            Action::Diagnostics => "diagnostics",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::AddAndContinue => "add_and_continue",
+            Action::FocusSearch => "focus_search",
+            Action::SelectLeft => "select_left",
+            Action::SelectRight => "select_right",
+            Action::SelectHome => "select_home",
+            Action::SelectEnd => "select_end",
+            Action::ToggleSidebar => "toggle_sidebar",
+            Action::SelectPrevious => "select_previous",
+            Action::SelectNext => "select_next",
+            Action::DeleteTask => "delete_task",
+            Action::EditTask => "edit_task",
+            Action::ScrollPageUp => "scroll_page_up",
+            Action::ScrollPageDown => "scroll_page_down",
+            Action::ScrollTop => "scroll_top",
+            Action::ScrollBottom => "scroll_bottom",
+            Action::FocusNext => "focus_next",
+            Action::FocusPrevious => "focus_previous",
+            Action::SelectAll => "select_all",
+            Action::Copy => "copy",
+            Action::Cut => "cut",
+            Action::Paste => "paste",
+            Action::WordLeft => "word_left",
+            Action::WordRight => "word_right",
+            Action::SelectWordLeft => "select_word_left",
+            Action::SelectWordRight => "select_word_right",
+            Action::CyclePresentMode => "cycle_present_mode",
+            Action::ContextMenu => "context_menu",
+            Action::Sync => "sync",
+            Action::Archive => "archive",
+            Action::FocusTimer => "focus_timer",
+            Action::StopFocusTimer => "stop_focus_timer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|action| action.name() == name)
+    }
+}
+
+/// Keys a chord can end in
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+    KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+    KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+    KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    KeyCode::Minus, KeyCode::Equal, KeyCode::Comma, KeyCode::Period, KeyCode::Slash,
+    KeyCode::Semicolon, KeyCode::Quote, KeyCode::BracketLeft, KeyCode::BracketRight,
+    KeyCode::Backslash, KeyCode::Backquote,
+    KeyCode::Escape, KeyCode::Enter, KeyCode::Tab, KeyCode::Space,
+    KeyCode::Backspace, KeyCode::Delete, KeyCode::Insert,
+    KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+];
+
+/// Modifiers in the order chords are written, with their names
+const CHORD_MODIFIERS: &[(ModifiersState, &str)] = &[
+    (ModifiersState::CONTROL, "Ctrl"),
+    (ModifiersState::ALT, "Alt"),
+    (ModifiersState::SHIFT, "Shift"),
+    (ModifiersState::SUPER, "Super"),
+];
+
+/// A key pressed with exactly these modifiers held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+impl KeyChord {
+    pub fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// Parse a chord as written in the config file, e.g. "Ctrl+Shift+Z", "F2" or "ArrowUp"
+    ///
+    /// Letters and digits may be written bare ("Z", "1") or by their key code ("KeyZ").
+    pub fn parse(text: &str) -> Option<Self> {
+        let normalized = normalize_chord(text)?;
+        let mut parts: Vec<&str> = normalized.split('+').collect();
+        let key = parts.pop()?;
+
+        let mut modifiers = ModifiersState::empty();
+        for part in parts {
+            let (modifier, _) = CHORD_MODIFIERS.iter().find(|(_, name)| name.eq_ignore_ascii_case(part))?;
+            modifiers |= *modifier;
+        }
+        let key = BINDABLE_KEYS.iter().copied().find(|code| {
+            let name = format!("{:?}", code).to_lowercase();
+            name == key || name.strip_prefix("key").or_else(|| name.strip_prefix("digit")) == Some(key)
+        })?;
+        Some(Self { modifiers, key })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in CHORD_MODIFIERS {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        let key = format!("{:?}", self.key);
+        let key = key.strip_prefix("Key").or_else(|| key.strip_prefix("Digit")).unwrap_or(&key);
+        f.write_str(key)
+    }
+}
+
+/// The key code a logical key is looked up by in a `Keymap`
+///
+/// Letters and digits map to the keys labelled with them, so bindings follow
+/// the keyboard layout rather than the key's position.
+pub fn binding_keycode(key: &Key) -> Option<KeyCode> {
+    match key {
+        Key::Named(named) => {
+            let name = format!("{:?}", named);
+            BINDABLE_KEYS.iter().copied().find(|code| format!("{:?}", code) == name)
+        }
+        Key::Character(text) => {
+            let mut chars = text.chars();
+            let c = chars.next()?.to_ascii_uppercase();
+            if chars.next().is_some() {
+                return None;
+            }
+            let name = match c {
+                'A'..='Z' => format!("Key{}", c),
+                '0'..='9' => format!("Digit{}", c),
+                '-' => "Minus".to_string(),
+                '=' => "Equal".to_string(),
+                ',' => "Comma".to_string(),
+                '.' => "Period".to_string(),
+                '/' => "Slash".to_string(),
+                ';' => "Semicolon".to_string(),
+                '\'' => "Quote".to_string(),
+                '[' => "BracketLeft".to_string(),
+                ']' => "BracketRight".to_string(),
+                '\\' => "Backslash".to_string(),
+                '`' => "Backquote".to_string(),
+                _ => return None,
+            };
+            BINDABLE_KEYS.iter().copied().find(|code| format!("{:?}", code) == name)
+        }
+        _ => None,
+    }
+}
+
+/// Which action each key chord triggers
+///
+/// Built from the `[keybinds]` config table, so every shortcut, list
+/// navigation included, can be rebound without recompiling.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Keymap {
+    bindings: HashMap<KeyChord, Action>,
+}
+
+impl Keymap {
+    /// A keymap without bindings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a keymap from action names and chords, skipping entries that don't parse
+    pub fn from_bindings<'a>(bindings: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut keymap = Self::new();
+        for (action, chord) in bindings {
+            if let (Some(action), Some(chord)) = (Action::from_name(action), KeyChord::parse(chord)) {
+                keymap.bind(chord, action);
+            }
+        }
+        keymap
+    }
+
+    /// Bind a chord to an action, returning the action it was bound to before
+    pub fn bind(&mut self, chord: KeyChord, action: Action) -> Option<Action> {
+        self.bindings.insert(chord, action)
+    }
+
+    /// The action a key pressed with these modifiers triggers
+    pub fn action(&self, modifiers: ModifiersState, key: KeyCode) -> Option<Action> {
+        self.bindings.get(&KeyChord::new(modifiers, key)).copied()
+    }
+
+    /// A chord bound to an action, e.g. to show it in a hint
+    pub fn chord_for(&self, action: Action) -> Option<KeyChord> {
+        self.bindings.iter().find(|(_, bound)| **bound == action).map(|(chord, _)| *chord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chords_parse_and_print() {
+        let chord = KeyChord::parse("shift+control+z").unwrap();
+        assert_eq!(chord, KeyChord::new(ModifiersState::CONTROL | ModifiersState::SHIFT, KeyCode::KeyZ));
+        assert_eq!(chord.to_string(), "Ctrl+Shift+Z");
+        assert_eq!(KeyChord::parse("Cmd+Digit1").unwrap().to_string(), "Super+1");
+        assert_eq!(KeyChord::parse("arrowup").unwrap().key, KeyCode::ArrowUp);
+        assert_eq!(KeyChord::parse("Ctrl+Nope"), None);
+        assert_eq!(KeyChord::parse("Hyper+F"), None);
+    }
+
+    #[test]
+    fn test_keys_map_to_their_labels() {
+        assert_eq!(binding_keycode(&Key::Character("z".into())), Some(KeyCode::KeyZ));
+        assert_eq!(binding_keycode(&Key::Character("7".into())), Some(KeyCode::Digit7));
+        assert_eq!(binding_keycode(&Key::Named(NamedKey::F9)), Some(KeyCode::F9));
+        assert_eq!(binding_keycode(&Key::Character("ß".into())), None);
+    }
+
+    #[test]
+    fn test_keymap_matches_exact_modifiers() {
+        let keymap = Keymap::from_bindings([("select_next", "J"), ("redo", "Ctrl+Shift+Z"), ("fly", "F")]);
+        assert_eq!(keymap.action(ModifiersState::empty(), KeyCode::KeyJ), Some(Action::SelectNext));
+        assert_eq!(keymap.action(ModifiersState::SHIFT, KeyCode::KeyJ), None);
+        assert_eq!(keymap.action(ModifiersState::CONTROL | ModifiersState::SHIFT, KeyCode::KeyZ), Some(Action::Redo));
+        assert_eq!(keymap.action(ModifiersState::empty(), KeyCode::KeyF), None);
+        assert_eq!(keymap.chord_for(Action::SelectNext).map(|chord| chord.to_string()).as_deref(), Some("J"));
+    }
+}
@@ -0,0 +1,253 @@
+// Debug inspector overlay
+//
+// Toggled with the `inspector` keybind. While enabled, the widget tree is
+// walked once per frame through `Widget::inspect`; the overlay then outlines
+// every widget, highlights hover and focus, shows the clip rects containers
+// push, and describes the widget under the pointer in a corner panel.
+
+use crate::i18n::{format_number, tr, tr_args};
+use crate::ui::{CyberpunkTheme, RenderContext, Widget};
+
+const PANEL_WIDTH: f32 = 300.0;
+const PANEL_MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 22.0;
+
+/// One widget's geometry and state as the inspector sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WidgetInfo {
+    /// Type name without its module path
+    pub name: &'static str,
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    /// Nesting level, 0 for the widgets the inspector was given
+    pub depth: usize,
+    pub hovered: bool,
+    pub focused: bool,
+    /// Rectangle (x, y, width, height) the widget clips its children to
+    pub clip: Option<(f32, f32, f32, f32)>,
+}
+
+impl WidgetInfo {
+    /// Describe a widget's bounds, with no hover, focus or clipping
+    pub fn of<W: Widget + ?Sized>(widget: &W, depth: usize) -> Self {
+        let name = std::any::type_name::<W>();
+        Self::new(name.rsplit("::").next().unwrap_or(name), widget.position(), widget.dimensions(), depth)
+    }
+
+    /// Describe something that isn't a `Widget`, such as an entity
+    pub fn new(name: &'static str, position: (f32, f32), size: (f32, f32), depth: usize) -> Self {
+        Self {
+            name,
+            position,
+            size,
+            depth,
+            hovered: false,
+            focused: false,
+            clip: None,
+        }
+    }
+
+    pub fn with_hovered(mut self, hovered: bool) -> Self {
+        self.hovered = hovered;
+        self
+    }
+
+    pub fn with_focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn with_clip(mut self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.clip = Some((x, y, width, height));
+        self
+    }
+
+    /// Check if point is inside the widget's bounds
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        let (widget_x, widget_y) = self.position;
+        let (width, height) = self.size;
+        x >= widget_x && x <= widget_x + width && y >= widget_y && y <= widget_y + height
+    }
+}
+
+/// Overlay that outlines widgets and describes the one under the pointer
+pub struct Inspector {
+    enabled: bool,
+    widgets: Vec<WidgetInfo>,
+    pointer: (f32, f32),
+    theme: CyberpunkTheme,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            widgets: Vec::new(),
+            pointer: (0.0, 0.0),
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Show the overlay, or hide it if it's already shown
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.widgets.clear();
+    }
+
+    /// Walk the widget tree, bottom-most root first
+    ///
+    /// Does nothing while disabled, so it can be called every frame.
+    pub fn collect(&mut self, roots: &[&dyn Widget], pointer: (f32, f32)) {
+        self.widgets.clear();
+        self.pointer = pointer;
+        if !self.enabled {
+            return;
+        }
+        for root in roots {
+            root.inspect(0, &mut self.widgets);
+        }
+    }
+
+    /// Every widget found by the last `collect`, parents before their children
+    pub fn widgets(&self) -> &[WidgetInfo] {
+        &self.widgets
+    }
+
+    /// The innermost widget under the pointer, preferring the one drawn last
+    pub fn under_cursor(&self) -> Option<&WidgetInfo> {
+        let (x, y) = self.pointer;
+        self.widgets
+            .iter()
+            .filter(|widget| widget.contains_point(x, y))
+            .max_by_key(|widget| widget.depth)
+    }
+
+    pub fn render(&self, ctx: &mut RenderContext) {
+        if !self.enabled {
+            return;
+        }
+
+        for widget in &self.widgets {
+            let (x, y) = widget.position;
+            let (width, height) = widget.size;
+            let color = if widget.focused {
+                self.theme.highlight()
+            } else if widget.hovered {
+                self.theme.neon_pink()
+            } else {
+                // Deeper widgets fade so nested outlines stay readable
+                let [r, g, b, _] = self.theme.cyan();
+                [r, g, b, (0.8 - widget.depth as f32 * 0.15).max(0.3)]
+            };
+            ctx.draw_outline(x, y, width, height, 1.0, color);
+
+            if let Some((clip_x, clip_y, clip_width, clip_height)) = widget.clip {
+                ctx.draw_outline(clip_x, clip_y, clip_width, clip_height, 2.0, self.theme.priority_medium());
+            }
+        }
+
+        let selected = self.under_cursor();
+        if let Some(widget) = selected {
+            let (x, y) = widget.position;
+            let (width, height) = widget.size;
+            ctx.draw_outline(x, y, width, height, 3.0, self.theme.neon_pink());
+        }
+
+        let lines = selected.map_or_else(|| vec![tr("inspector-nothing")], describe);
+        let panel_x = ctx.width - PANEL_WIDTH - PANEL_MARGIN;
+        let panel_height = LINE_HEIGHT * (lines.len() + 1) as f32 + 16.0;
+        ctx.draw_rect(panel_x, PANEL_MARGIN, PANEL_WIDTH, panel_height, self.theme.modal_background());
+        ctx.draw_outline(panel_x, PANEL_MARGIN, PANEL_WIDTH, panel_height, 1.0, self.theme.cyan());
+        ctx.draw_label(
+            "inspector-title",
+            panel_x + 10.0, PANEL_MARGIN + 8.0,
+            self.theme.text_size(),
+            self.theme.neon_pink(),
+        );
+        for (index, line) in lines.iter().enumerate() {
+            ctx.draw_text(
+                line,
+                panel_x + 10.0, PANEL_MARGIN + 8.0 + LINE_HEIGHT * (index + 1) as f32,
+                self.theme.small_text_size(),
+                self.theme.bright_text(),
+            );
+        }
+    }
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Panel lines for the widget under the pointer
+fn describe(widget: &WidgetInfo) -> Vec<String> {
+    let number = |value: f32| format_number(value as f64, 0);
+    let (x, y) = widget.position;
+    let (width, height) = widget.size;
+    let mut lines = vec![
+        widget.name.to_string(),
+        tr_args("inspector-position", &[("x", &number(x)), ("y", &number(y))]),
+        tr_args("inspector-size", &[("width", &number(width)), ("height", &number(height))]),
+        tr_args("inspector-depth", &[("depth", &widget.depth.to_string())]),
+    ];
+    if widget.hovered {
+        lines.push(tr("inspector-hovered"));
+    }
+    if widget.focused {
+        lines.push(tr("inspector-focused"));
+    }
+    if let Some((clip_x, clip_y, clip_width, clip_height)) = widget.clip {
+        lines.push(tr_args(
+            "inspector-clip",
+            &[("x", &number(clip_x)), ("y", &number(clip_y)), ("width", &number(clip_width)), ("height", &number(clip_height))],
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::UiDriver;
+
+    #[test]
+    fn test_collects_nothing_while_disabled() {
+        let (driver, _) = UiDriver::with_tasks(&["Alpha"]);
+        let mut inspector = Inspector::new();
+
+        inspector.collect(&[driver.widget()], (0.0, 0.0));
+        assert!(inspector.widgets().is_empty());
+        assert!(inspector.under_cursor().is_none());
+    }
+
+    #[test]
+    fn test_finds_the_innermost_widget_under_the_pointer() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Alpha", "Beta"]);
+        let mut inspector = Inspector::new();
+        inspector.toggle();
+
+        let checkbox = &driver.widget().item_widget(ids[1]).unwrap().checkbox_button;
+        let (x, y) = checkbox.position();
+        let (width, height) = checkbox.dimensions();
+        let center = (x + width / 2.0, y + height / 2.0);
+        driver.move_to(center.0, center.1);
+        inspector.collect(&[driver.widget()], center);
+
+        let widgets = inspector.widgets();
+        assert_eq!(widgets[0].name, "TodoListWidget");
+        assert!(widgets[0].clip.is_some());
+        assert_eq!(widgets.iter().filter(|widget| widget.name == "TodoItemWidget").count(), 2);
+
+        let found = inspector.under_cursor().unwrap();
+        assert_eq!(found.name, "Button");
+        assert_eq!(found.position, (x, y));
+        assert_eq!(found.depth, 2);
+        assert!(found.hovered);
+    }
+}
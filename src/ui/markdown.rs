@@ -0,0 +1,466 @@
+// Markdown in task descriptions
+//
+// A small subset, enough for notes: **bold**, *italic*, `code`, [links](url)
+// and bare web addresses, and bullet lists started with `-`, `*` or `+` and
+// nested by indenting two spaces. Every line of the text stays a line, since
+// people type notes rather than documents. The layout places runs of text in
+// lines no wider than the space given, on top of the render context's text
+// primitives; the details modal draws it and finds the links under clicks.
+// Titles aren't markdown, but web addresses in them are links all the same.
+
+use std::ops::Range;
+
+use crate::ui::text_metrics::text_width;
+use crate::ui::{CyberpunkTheme, RenderContext};
+
+/// Distance between the tops of two lines, relative to the text size
+const LINE_SPACING: f32 = 1.3;
+/// Indent of each level of a bullet list, relative to the text size
+const INDENT: f32 = 1.5;
+/// Links that are opened when clicked; others show as plain text
+const LINK_SCHEMES: [&str; 3] = ["https://", "http://", "mailto:"];
+/// Addresses in the text that become links without markup
+const BARE_SCHEMES: [&str; 2] = ["https://", "http://"];
+/// How far the second stroke of bold text is drawn to the right, relative to the text size
+const BOLD_OFFSET: f32 = 0.06;
+
+/// How a span of text is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    /// Index of the link in the document's `links`
+    pub link: Option<usize>,
+}
+
+/// Text in one style
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+/// A line of the text, which may be an item of a bullet list
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    /// Nesting level of a bullet list item, from 0; None for other lines
+    pub bullet: Option<usize>,
+    pub spans: Vec<Span>,
+}
+
+/// Markdown text, parsed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+    /// Addresses of the links, in the order they appear
+    pub links: Vec<String>,
+}
+
+/// A piece of text in one style placed by the layout, relative to the top left of the text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub style: Style,
+}
+
+/// A document placed in lines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layout {
+    pub runs: Vec<Run>,
+    /// Height of all the lines together
+    pub height: f32,
+    size: f32,
+}
+
+/// Add text to the spans, joining it to the last one if that has the same style
+fn push_span(spans: &mut Vec<Span>, text: &str, style: Style) {
+    if text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ => spans.push(Span { text: text.to_string(), style }),
+    }
+}
+
+/// The nesting level and text of a bullet list item, or None and the line as it is
+fn bullet_item(line: &str) -> (Option<usize>, &str) {
+    let content = line.trim_start();
+    let indent = line.len() - content.len();
+    ["- ", "* ", "+ "]
+        .into_iter()
+        .find_map(|marker| content.strip_prefix(marker))
+        .map_or((None, line), |item| (Some(indent / 2), item.trim_start()))
+}
+
+/// The text between `marker` at the start of `text` and the marker closing it,
+/// with the length of both markers and the text, if `text` starts with one
+///
+/// Emphasis needs text right inside its markers, and a single `*` or `_`
+/// isn't closed by a doubled one, so `*a **b** c*` is italic throughout. Code
+/// ends at the next backtick, whatever is inside.
+fn delimited<'t>(text: &'t str, marker: &str) -> Option<(&'t str, usize)> {
+    let inner = text.strip_prefix(marker)?;
+    if marker == "`" {
+        let end = inner.find('`').filter(|&end| end > 0)?;
+        return Some((&inner[..end], end + 2));
+    }
+    if inner.starts_with(char::is_whitespace) || inner.starts_with(marker) {
+        return None;
+    }
+
+    let single = marker.len() == 1;
+    let mut from = 0;
+    while let Some(found) = inner[from..].find(marker) {
+        let end = from + found;
+        if single && inner[end + 1..].starts_with(marker) {
+            from = end + 2;
+            continue;
+        }
+        if end > 0 && !inner[..end].ends_with(char::is_whitespace) {
+            return Some((&inner[..end], end + 2 * marker.len()));
+        }
+        from = end + marker.len();
+    }
+    None
+}
+
+/// Label, address and length of a `[label](address)` link at the start of `text`
+fn inline_link(text: &str) -> Option<(&str, &str, usize)> {
+    let rest = text.strip_prefix('[')?;
+    let close = rest.find("](")?;
+    let label = &rest[..close];
+    let target = &rest[close + 2..];
+    let end = target.find(')')?;
+    let url = target[..end].trim();
+    (!label.is_empty() && !url.is_empty() && !url.contains(char::is_whitespace))
+        .then_some((label, url, close + end + 4))
+}
+
+/// A web address at the start of `text`, without punctuation that ends the sentence it's in
+fn bare_url(text: &str) -> Option<&str> {
+    let scheme = BARE_SCHEMES.into_iter().find(|scheme| text.starts_with(scheme))?;
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    let url = text[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"']);
+    (url.len() > scheme.len()).then_some(url)
+}
+
+/// Byte ranges of the web addresses in plain text
+pub fn find_urls(text: &str) -> Vec<Range<usize>> {
+    let mut urls = Vec::new();
+    let mut at = 0;
+    while let Some(c) = text[at..].chars().next() {
+        let in_word = text[..at].chars().next_back().is_some_and(char::is_alphanumeric);
+        match bare_url(&text[at..]).filter(|_| !in_word) {
+            Some(url) => {
+                urls.push(at..at + url.len());
+                at += url.len();
+            }
+            None => at += c.len_utf8(),
+        }
+    }
+    urls
+}
+
+/// Addresses of a task's links, numbered as `UiEvent::LinkClicked` counts
+/// them: those in the title first, then the description's
+pub fn item_links(title: &str, description: Option<&str>) -> Vec<String> {
+    let mut links: Vec<String> = find_urls(title).into_iter().map(|url| title[url].to_string()).collect();
+    links.extend(description.map_or_else(Vec::new, |description| Document::parse(description).links));
+    links
+}
+
+impl Document {
+    /// Parse markdown text
+    pub fn parse(text: &str) -> Self {
+        let mut document = Self::default();
+        for line in text.lines() {
+            let (bullet, content) = bullet_item(line);
+            let mut spans = Vec::new();
+            document.parse_inline(content, Style::default(), &mut spans);
+            document.blocks.push(Block { bullet, spans });
+        }
+        document
+    }
+
+    /// The style of a link's label, pointing at its address if that's one to open
+    fn link_style(&mut self, url: &str, style: Style) -> Style {
+        let url_lower = url.to_ascii_lowercase();
+        if !LINK_SCHEMES.iter().any(|scheme| url_lower.starts_with(scheme)) {
+            return style;
+        }
+        self.links.push(url.to_string());
+        Style { link: Some(self.links.len() - 1), ..style }
+    }
+
+    /// Add the spans of a line's inline markup in `style`, and the links in it
+    fn parse_inline(&mut self, text: &str, style: Style, spans: &mut Vec<Span>) {
+        let mut at = 0;
+        'text: while let Some(c) = text[at..].chars().next() {
+            let rest = &text[at..];
+            let in_word = text[..at].chars().next_back().is_some_and(char::is_alphanumeric);
+
+            // A backslash keeps punctuation from being markup
+            if let Some(escaped) = rest.strip_prefix('\\').and_then(|rest| rest.chars().next()) {
+                if escaped.is_ascii_punctuation() {
+                    push_span(spans, &text[at + 1..at + 2], style);
+                    at += 2;
+                    continue;
+                }
+            }
+            if let Some((code, len)) = delimited(rest, "`") {
+                push_span(spans, code, Style { code: true, ..style });
+                at += len;
+                continue;
+            }
+            if let Some((label, url, len)) = inline_link(rest) {
+                let link_style = self.link_style(url, style);
+                self.parse_inline(label, link_style, spans);
+                at += len;
+                continue;
+            }
+            if let Some(url) = bare_url(rest).filter(|_| !in_word && style.link.is_none()) {
+                let link_style = self.link_style(url, style);
+                push_span(spans, url, link_style);
+                at += url.len();
+                continue;
+            }
+            for (marker, bold) in [("**", true), ("__", true), ("*", false), ("_", false)] {
+                // Underscores inside words, as in snake_case, are just underscores
+                if marker.starts_with('_') && in_word {
+                    continue;
+                }
+                if let Some((inner, len)) = delimited(rest, marker) {
+                    let emphasis = if bold { Style { bold: true, ..style } } else { Style { italic: true, ..style } };
+                    self.parse_inline(inner, emphasis, spans);
+                    at += len;
+                    continue 'text;
+                }
+            }
+
+            push_span(spans, &rest[..c.len_utf8()], style);
+            at += c.len_utf8();
+        }
+    }
+
+    /// Place the text in lines no wider than `max_width` at a text size
+    ///
+    /// Lines break between words where they can, and inside words too long
+    /// for a line of their own. Items of bullet lists wrap to their indent.
+    pub fn layout(&self, max_width: f32, size: f32) -> Layout {
+        let line_height = size * LINE_SPACING;
+        let mut layout = Layout { runs: Vec::new(), height: 0.0, size };
+        let mut y = 0.0;
+        for block in &self.blocks {
+            let left = match block.bullet {
+                Some(depth) => {
+                    let bullet_x = depth as f32 * size * INDENT;
+                    layout.place("•", (bullet_x, y), Style::default());
+                    bullet_x + size * INDENT
+                }
+                None => 0.0,
+            };
+
+            let mut x = left;
+            for span in &block.spans {
+                for piece in span.text.split_inclusive(' ') {
+                    let mut piece = piece;
+                    // Spaces aren't needed to fit, and don't start a wrapped line
+                    let fits = |x: f32, piece: &str| x + text_width(piece.trim_end(), size) <= max_width;
+                    if !fits(x, piece) && x > left {
+                        x = left;
+                        y += line_height;
+                        piece = piece.trim_start();
+                    }
+                    // Break words wider than a whole line wherever the line is full
+                    while !fits(x, piece) {
+                        let ends = piece.char_indices().map(|(byte, c)| byte + c.len_utf8());
+                        let end = ends
+                            .take_while(|&end| fits(x, &piece[..end]))
+                            .last()
+                            .unwrap_or_else(|| piece.chars().next().map_or(0, char::len_utf8));
+                        layout.place(&piece[..end], (x, y), span.style);
+                        piece = &piece[end..];
+                        x = left;
+                        y += line_height;
+                    }
+                    x += layout.place(piece, (x, y), span.style);
+                }
+            }
+            y += line_height;
+        }
+        layout.height = y;
+        layout
+    }
+}
+
+impl Layout {
+    /// Add text at a point, joined to the last run if it continues it, returning its width
+    fn place(&mut self, text: &str, (x, y): (f32, f32), style: Style) -> f32 {
+        if text.is_empty() {
+            return 0.0;
+        }
+        let width = text_width(text, self.size);
+        match self.runs.last_mut() {
+            Some(last) if last.style == style && last.y == y && last.x + last.width == x => {
+                last.text.push_str(text);
+                last.width += width;
+            }
+            _ => self.runs.push(Run { text: text.to_string(), x, y, width, style }),
+        }
+        width
+    }
+
+    /// Index of the link under a point, relative to the top left of the text
+    pub fn link_at(&self, x: f32, y: f32) -> Option<usize> {
+        let line_height = self.size * LINE_SPACING;
+        self.runs
+            .iter()
+            .find(|run| x >= run.x && x <= run.x + run.width && y >= run.y && y < run.y + line_height)
+            .and_then(|run| run.style.link)
+    }
+
+    /// Draw the text with its top left at `origin`
+    ///
+    /// With a single font, bold is drawn twice a little apart and italics
+    /// stand out by color. Code gets a background and links an underline.
+    pub fn render(&self, ctx: &mut RenderContext, origin: (f32, f32), color: [f32; 4], theme: &CyberpunkTheme) {
+        let size = self.size;
+        for run in &self.runs {
+            let (x, y) = (origin.0 + run.x, origin.1 + run.y);
+            let shown = run.text.trim_end();
+            let width = text_width(shown, size);
+            let run_color = match run.style {
+                Style { link: Some(_), .. } => theme.link(),
+                Style { code: true, .. } => theme.neon_pink(),
+                Style { italic: true, .. } => theme.purple(),
+                _ => color,
+            };
+
+            if run.style.code {
+                ctx.draw_rect(x - 2.0, y - 1.0, width + 4.0, size + 4.0, theme.filter_button_bg());
+            }
+            ctx.draw_text(shown, x, y, size, run_color);
+            if run.style.bold {
+                ctx.draw_text(shown, x + size * BOLD_OFFSET, y, size, run_color);
+            }
+            if run.style.link.is_some() {
+                let underline_y = y + size + 1.0;
+                ctx.draw_line(x, underline_y, x + width, underline_y, 1.0, run_color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(text: &str) -> Vec<(String, Style)> {
+        let document = Document::parse(text);
+        document.blocks[0].spans.iter().map(|span| (span.text.clone(), span.style)).collect()
+    }
+
+    fn plain(text: &str) -> (String, Style) {
+        (text.to_string(), Style::default())
+    }
+
+    const BOLD: Style = Style { bold: true, italic: false, code: false, link: None };
+    const ITALIC: Style = Style { bold: false, italic: true, code: false, link: None };
+    const CODE: Style = Style { bold: false, italic: false, code: true, link: None };
+
+    #[test]
+    fn test_emphasis_and_code() {
+        assert_eq!(spans("a **b** c"), [plain("a "), ("b".to_string(), BOLD), plain(" c")]);
+        assert_eq!(spans("_a_ __b__"), [("a".to_string(), ITALIC), plain(" "), ("b".to_string(), BOLD)]);
+        assert_eq!(
+            spans("*a **b** c*"),
+            [("a ".to_string(), ITALIC), ("b".to_string(), Style { bold: true, ..ITALIC }), (" c".to_string(), ITALIC)],
+        );
+        assert_eq!(spans("run `cargo **test**` now"), [plain("run "), ("cargo **test**".to_string(), CODE), plain(" now")]);
+
+        // Markers without text right inside them, or unclosed, are kept
+        assert_eq!(spans("2 * 3 * 4"), [plain("2 * 3 * 4")]);
+        assert_eq!(spans("**open"), [plain("**open")]);
+        assert_eq!(spans("snake_case_name"), [plain("snake_case_name")]);
+        assert_eq!(spans(r"\*not italic\*"), [plain("*not italic*")]);
+    }
+
+    #[test]
+    fn test_links() {
+        let document = Document::parse("See [the *docs*](https://example.com/docs) or https://example.org/a.\n[local](file:///etc/passwd)");
+        assert_eq!(document.links, ["https://example.com/docs", "https://example.org/a"]);
+        let link = |index| Style { link: Some(index), ..Style::default() };
+        assert_eq!(
+            document.blocks[0].spans,
+            [
+                Span { text: "See ".to_string(), style: Style::default() },
+                Span { text: "the ".to_string(), style: link(0) },
+                Span { text: "docs".to_string(), style: Style { italic: true, ..link(0) } },
+                Span { text: " or ".to_string(), style: Style::default() },
+                Span { text: "https://example.org/a".to_string(), style: link(1) },
+                Span { text: ".".to_string(), style: Style::default() },
+            ],
+        );
+        // Links to anything but web pages and mail show as their label
+        assert_eq!(document.blocks[1].spans, [Span { text: "local".to_string(), style: Style::default() }]);
+    }
+
+    #[test]
+    fn test_urls_in_plain_text() {
+        let title = "Read https://example.com/a?b=1, then http://x.org. Not xhttps://no or https://";
+        let urls: Vec<&str> = find_urls(title).into_iter().map(|url| &title[url]).collect();
+        assert_eq!(urls, ["https://example.com/a?b=1", "http://x.org"]);
+
+        let links = item_links("See https://a.example", Some("[b](https://b.example) *c*"));
+        assert_eq!(links, ["https://a.example", "https://b.example"]);
+        assert_eq!(item_links("No links", None), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_bullet_lists() {
+        let document = Document::parse("Steps:\n- one\n  * two\n+ three\n-not a bullet");
+        let bullets: Vec<Option<usize>> = document.blocks.iter().map(|block| block.bullet).collect();
+        assert_eq!(bullets, [None, Some(0), Some(1), Some(0), None]);
+        assert_eq!(document.blocks[2].spans[0].text, "two");
+    }
+
+    // Without a font set, every character at size 10 is 5 pixels wide and lines are 13 apart
+
+    #[test]
+    fn test_layout_wraps_words_and_bullets() {
+        let layout = Document::parse("one **two** three\n- four five six").layout(50.0, 10.0);
+        let runs: Vec<(&str, f32, f32)> = layout.runs.iter().map(|run| (run.text.as_str(), run.x, run.y)).collect();
+        assert_eq!(
+            runs,
+            [
+                ("one ", 0.0, 0.0),
+                ("two", 20.0, 0.0),
+                (" ", 35.0, 0.0),
+                ("three", 0.0, 13.0),
+                ("•", 0.0, 26.0),
+                ("four ", 15.0, 26.0),
+                ("five ", 15.0, 39.0),
+                ("six", 15.0, 52.0),
+            ],
+        );
+        assert_eq!(layout.height, 65.0);
+
+        // Words longer than a line break where it's full
+        let layout = Document::parse("abcdefghijklm").layout(50.0, 10.0);
+        let runs: Vec<&str> = layout.runs.iter().map(|run| run.text.as_str()).collect();
+        assert_eq!(runs, ["abcdefghij", "klm"]);
+    }
+
+    #[test]
+    fn test_links_under_a_point() {
+        let layout = Document::parse("go [here](https://example.com) now").layout(200.0, 10.0);
+        assert_eq!(layout.link_at(16.0, 5.0), Some(0));
+        assert_eq!(layout.link_at(5.0, 5.0), None);
+        assert_eq!(layout.link_at(16.0, 20.0), None);
+    }
+}
@@ -1,24 +1,69 @@
 // UI module for todo list application
 
+use winit::window::CursorIcon;
+
 // Re-export core types
 pub mod button;
 pub mod text_input;
+pub mod text_area; // Multi-line text fields
+pub mod edit_buffer; // Cursor, selection and composed text shared by the text fields
 pub mod panel;
 pub mod todo_item_widget;
 pub mod todo_list_widget;
 pub mod context;
+pub mod arena; // Buffers reused across frames
+pub mod ecs; // Entity/component storage for lightweight widgets
+pub mod confirm_dialog;
+pub mod context_menu; // Menus popped up on right-clicking
+pub mod event;
+pub mod input;
+pub mod focus; // Keyboard focus, tab order and the focus ring
+pub mod clipboard; // Copy and paste through the system clipboard
+pub mod text_metrics; // Text widths from the UI font
+pub mod fonts; // Bundled fonts and fallbacks for glyphs the UI font lacks
+pub mod color_glyphs; // Color emoji drawn from a texture atlas
+pub mod fuzzy; // Fuzzy matching for the search
+pub mod markdown; // Markdown in task descriptions
+pub mod scrollbar;
+pub mod settings_panel;
+pub mod toast;
 pub mod theme;
 pub mod renderer; // Post-processing renderer
+pub mod frame; // The passes every frame goes through, shared by the app, Runner and headless renders
 pub mod widgets;
+pub mod inspector; // Debug overlay outlining widgets
+pub mod diagnostics; // Frame times, GPU pass timings and draw counts
+pub mod damage; // Whether anything changed what's on screen
+#[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+pub mod profiler; // Flame view of profiler scopes
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing; // Headless input driver for widget tests
 
 // UI components: Widget trait implementations
 pub use button::Button;
 pub use text_input::TextInput;
+pub use text_area::TextArea;
 pub use panel::Panel;
 pub use todo_item_widget::TodoItemWidget;
 pub use todo_list_widget::TodoListWidget;
-pub use context::RenderContext;
+pub use context::{DrawStats, RenderContext, TextOverflow};
+pub use color_glyphs::ColorGlyphs;
+pub use arena::FrameArena;
+pub use confirm_dialog::{ConfirmDialog, DialogChoice};
+pub use event::{UiEvent, EventQueue};
+pub use input::{ClickTracker, KeyRepeat, PointerState};
+pub use focus::FocusManager;
+pub use clipboard::Clipboard;
+pub use scrollbar::Scrollbar;
+pub use settings_panel::{SettingsPanel, SettingsEvent, present_mode_name};
+pub use widgets::{ArchiveView, ArchiveEvent, EditTaskModal, EditTaskEvent, FocusRing, KanbanBoard, BoardEvent, WorkspaceSidebar, SidebarEvent, SidebarProject, StatusBar, TaskCounts, TimelineView, ViewSwitcher, ViewMode};
+pub use toast::ToastManager;
 pub use theme::CyberpunkTheme;
+pub use inspector::{Inspector, WidgetInfo};
+pub use diagnostics::{Diagnostics, GpuPass};
+pub use frame::{draw_frame, FrameParts, FrameSettings};
+#[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+pub use profiler::FlameView;
 pub use renderer::prelude::*; // Export the renderer types
 
 /// Trait all UI widgets must implement
@@ -48,6 +93,32 @@ pub trait Widget {
         
         x >= widget_x && x <= widget_x + width && y >= widget_y && y <= widget_y + height
     }
+    
+    /// Whether `update` has any work to do, such as a running animation or changed data
+    ///
+    /// Containers only call `update` on children that return true, so idle widgets
+    /// cost nothing per frame. Widgets that don't track this are always updated.
+    fn needs_update(&self) -> bool {
+        true
+    }
+    
+    /// Whether the widget's geometry changed since its layout was last computed
+    fn needs_layout(&self) -> bool {
+        false
+    }
+    
+    /// Mouse cursor to show while the pointer is at this point, if the widget cares
+    fn cursor_at(&self, _x: f32, _y: f32) -> Option<CursorIcon> {
+        None
+    }
+    
+    /// Describe this widget and its children to the debug inspector
+    ///
+    /// The default reports just the widget's own bounds; containers override
+    /// it to add their visible children one level deeper.
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth));
+    }
 }
 
 // Export public types in a prelude module for convenient imports
@@ -58,9 +129,28 @@ pub mod prelude {
     pub use super::Panel;
     pub use super::TodoItemWidget;
     pub use super::TodoListWidget;
-    pub use super::RenderContext;
+    pub use super::{RenderContext, FrameArena};
+    pub use super::{draw_frame, FrameParts, FrameSettings};
+    pub use super::ColorGlyphs;
+    pub use super::{ConfirmDialog, DialogChoice};
+    pub use super::UiEvent;
+    pub use super::{ClickTracker, KeyRepeat, PointerState};
+    pub use super::{SettingsPanel, SettingsEvent};
+    pub use super::{EditTaskModal, EditTaskEvent};
+    pub use super::{ArchiveView, ArchiveEvent};
+    pub use super::FocusRing;
+    pub use super::{KanbanBoard, BoardEvent};
+    pub use super::TimelineView;
+    pub use super::{ViewSwitcher, ViewMode};
+    pub use super::{WorkspaceSidebar, SidebarEvent, SidebarProject};
+    pub use super::{StatusBar, TaskCounts};
+    pub use super::ToastManager;
+    pub use super::Inspector;
+    pub use super::{Diagnostics, GpuPass};
+    #[cfg(all(feature = "profiling", not(target_arch = "wasm32")))]
+    pub use super::FlameView;
     pub use super::CyberpunkTheme;
     pub use super::widgets;
-    pub use super::BloomEffect;
-    pub use super::NeonGlowEffect;
+    pub use super::QuadRenderer;
+    pub use super::PostProcessStack;
 }
\ No newline at end of file
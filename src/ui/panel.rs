@@ -1,6 +1,6 @@
 use wgpu::Color;
 use std::sync::Arc;
-use crate::ui::{RenderContext, Widget};
+use crate::ui::{RenderContext, Widget, WidgetInfo, CyberpunkTheme, Quad};
 
 /// A basic panel widget that can contain other widgets
 pub struct Panel {
@@ -11,6 +11,7 @@ pub struct Panel {
     background_color: Color,
     border_color: Color,
     border_width: f32,
+    corner_radius: f32,
     children: Vec<Arc<dyn Widget + Send + Sync>>,
 }
 
@@ -24,6 +25,7 @@ impl Clone for Panel {
             background_color: self.background_color,
             border_color: self.border_color,
             border_width: self.border_width,
+            corner_radius: self.corner_radius,
             children: self.children.clone(),
         }
     }
@@ -50,6 +52,7 @@ impl Panel {
                 a: 1.0,
             },
             border_width: 2.0,
+            corner_radius: CyberpunkTheme::new().corner_radius(),
             children: Vec::new(),
         }
     }
@@ -72,6 +75,12 @@ impl Panel {
         self
     }
 
+    /// Set the corner radius; 0 gives square corners
+    pub fn with_corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
     /// Add a child widget to this panel
     pub fn add_child<W: Widget + Send + Sync + 'static>(&mut self, widget: W) {
         self.children.push(Arc::new(widget));
@@ -88,9 +97,25 @@ impl Widget for Panel {
         }
     }
 
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth));
+        for child in &self.children {
+            child.inspect(depth + 1, out);
+        }
+    }
+
     fn render(&self, ctx: &mut RenderContext) {
-        // TODO: Draw panel background and borders using a renderer
-        // For now, we can use placeholder logic
+        let [background, border] = [self.background_color, self.border_color]
+            .map(|color| [color.r as f32, color.g as f32, color.b as f32, color.a as f32]);
+        ctx.draw_quad(
+            Quad::rect(self.x, self.y, self.width, self.height, background)
+                .with_corner_radius(self.corner_radius)
+                .with_border(self.border_width, border),
+        );
         
         // Render all children
         for child_arc in &self.children {
@@ -121,7 +146,7 @@ impl Widget for Panel {
         // other patterns to allow updating children's positions
         
         // Log the position change for debugging
-        log::debug!("Panel moved by ({}, {})", dx, dy);
+        tracing::debug!("Panel moved by ({}, {})", dx, dy);
     }
 
     fn set_dimensions(&mut self, width: f32, height: f32) {
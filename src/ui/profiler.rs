@@ -0,0 +1,214 @@
+// In-app flame view (the `profiling` feature)
+//
+// A panel along the bottom of the window showing the scopes of the last
+// finished frame as a flame graph: one row per nesting level, each bar as wide
+// as its share of the frame, threads stacked below each other. Frames are
+// only kept while the view is shown, so a hidden view costs nothing.
+
+use puffin::{FrameData, GlobalFrameView, Reader, ScopeCollection, Stream};
+
+use crate::i18n::{format_number, tr_args};
+use crate::ui::{CyberpunkTheme, RenderContext};
+
+const PANEL_HEIGHT: f32 = 220.0;
+const PANEL_MARGIN: f32 = 12.0;
+const ROW_HEIGHT: f32 = 18.0;
+const HEADER_HEIGHT: f32 = 28.0;
+
+/// One scope of a frame, placed relative to the frame's start
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlameBar {
+    pub name: String,
+    pub thread: String,
+    /// Nesting level within the thread, 0 for top-level scopes
+    pub depth: usize,
+    /// Start and width as fractions of the whole frame
+    pub start: f32,
+    pub width: f32,
+    pub duration_ms: f32,
+}
+
+/// Flatten a frame's scopes into bars, threads in name order, parents before their children
+pub fn flame_bars(frame: &FrameData, scopes: &ScopeCollection) -> Vec<FlameBar> {
+    // Only fails to unpack when puffin is built with frame packing
+    let Some(unpacked) = frame.unpacked().ok() else {
+        return Vec::new();
+    };
+    let (frame_start, frame_end) = unpacked.meta.range_ns;
+    let frame_ns = (frame_end - frame_start).max(1) as f32;
+
+    let mut bars = Vec::new();
+    for (thread, stream) in &unpacked.thread_streams {
+        let top = Reader::from_start(&stream.stream).read_top_scopes().unwrap_or_default();
+        let mut pending: Vec<_> = top.into_iter().rev().map(|scope| (scope, 0)).collect();
+        while let Some((scope, depth)) = pending.pop() {
+            let name = scopes
+                .fetch_by_id(&scope.id)
+                .map_or_else(|| "?".to_string(), |details| details.name().to_string());
+            bars.push(FlameBar {
+                name,
+                thread: thread.name.clone(),
+                depth,
+                start: (scope.record.start_ns - frame_start) as f32 / frame_ns,
+                width: scope.record.duration_ns as f32 / frame_ns,
+                duration_ms: scope.record.duration_ns as f32 / 1_000_000.0,
+            });
+            pending.extend(children(&stream.stream, scope.child_begin_position).into_iter().rev().map(|child| (child, depth + 1)));
+        }
+    }
+    bars
+}
+
+fn children(stream: &Stream, offset: u64) -> Vec<puffin::Scope<'_>> {
+    Reader::with_offset(stream, offset)
+        .and_then(Reader::read_top_scopes)
+        .unwrap_or_default()
+}
+
+pub struct FlameView {
+    /// Subscribed to the global profiler while shown
+    frames: Option<GlobalFrameView>,
+    bars: Vec<FlameBar>,
+    frame_index: u64,
+    frame_ms: f32,
+    theme: CyberpunkTheme,
+}
+
+impl FlameView {
+    pub fn new() -> Self {
+        Self {
+            frames: None,
+            bars: Vec::new(),
+            frame_index: 0,
+            frame_ms: 0.0,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.frames.is_some()
+    }
+
+    /// Show the view, or hide it if it's already shown
+    pub fn toggle(&mut self) {
+        self.frames = match self.frames.take() {
+            Some(_) => None,
+            None => Some(GlobalFrameView::default()),
+        };
+        self.bars.clear();
+    }
+
+    /// Pick up the latest finished frame; does nothing while hidden
+    pub fn collect(&mut self) {
+        let Some(frames) = &self.frames else {
+            return;
+        };
+        let view = frames.lock();
+        let Some(frame) = view.latest_frame() else {
+            return;
+        };
+        if frame.meta().frame_index == self.frame_index && !self.bars.is_empty() {
+            return;
+        }
+        self.bars = flame_bars(&frame, view.scope_collection());
+        self.frame_index = frame.meta().frame_index;
+        let (start, end) = frame.meta().range_ns;
+        self.frame_ms = (end - start) as f32 / 1_000_000.0;
+    }
+
+    /// Bars of the last collected frame
+    pub fn bars(&self) -> &[FlameBar] {
+        &self.bars
+    }
+
+    pub fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        let x = PANEL_MARGIN;
+        let y = ctx.height - PANEL_HEIGHT - PANEL_MARGIN;
+        let width = ctx.width - PANEL_MARGIN * 2.0;
+        ctx.draw_rect(x, y, width, PANEL_HEIGHT, self.theme.modal_background());
+        ctx.draw_outline(x, y, width, PANEL_HEIGHT, 1.0, self.theme.cyan());
+        ctx.draw_text(
+            &tr_args(
+                "profiler-title",
+                &[("frame", &self.frame_index.to_string()), ("ms", &format_number(self.frame_ms as f64, 2))],
+            ),
+            x + 10.0, y + 6.0,
+            self.theme.small_text_size(),
+            self.theme.neon_pink(),
+        );
+
+        // Each thread gets as many rows as its deepest scope needs
+        let graph_x = x + 10.0;
+        let graph_width = width - 20.0;
+        let mut row_y = y + HEADER_HEIGHT;
+        let mut thread: Option<&str> = None;
+        let mut thread_rows = 0;
+        for bar in &self.bars {
+            if thread != Some(bar.thread.as_str()) {
+                row_y += thread_rows as f32 * ROW_HEIGHT;
+                thread_rows = 0;
+                thread = Some(bar.thread.as_str());
+            }
+            thread_rows = thread_rows.max(bar.depth + 1);
+
+            let bar_y = row_y + bar.depth as f32 * ROW_HEIGHT;
+            if bar_y + ROW_HEIGHT > y + PANEL_HEIGHT {
+                continue;
+            }
+            let bar_x = graph_x + bar.start * graph_width;
+            let bar_width = (bar.width * graph_width).max(1.0);
+            let [r, g, b, _] = if bar.depth % 2 == 0 { self.theme.cyan() } else { self.theme.neon_pink() };
+            ctx.draw_rect(bar_x, bar_y, bar_width, ROW_HEIGHT - 2.0, [r, g, b, 0.6]);
+
+            // Only label bars wide enough to hold the text
+            let label = format!("{} {}ms", bar.name, format_number(bar.duration_ms as f64, 2));
+            let size = self.theme.small_text_size() * 0.8;
+            if ctx.measure_text(&label, size).width + 6.0 <= bar_width {
+                ctx.draw_text(&label, bar_x + 3.0, bar_y + 1.0, size, self.theme.bright_text());
+            }
+        }
+    }
+}
+
+impl Default for FlameView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_scopes_become_deeper_bars() {
+        puffin::set_scopes_on(true);
+        let mut view = FlameView::new();
+        view.toggle();
+        {
+            puffin::profile_scope!("outer");
+            {
+                puffin::profile_scope!("inner");
+            }
+        }
+        puffin::GlobalProfiler::lock().new_frame();
+        view.collect();
+
+        // Other tests may record scopes in the same frame on their own threads
+        let thread = std::thread::current().name().unwrap().to_string();
+        let bars: Vec<_> = view.bars().iter().filter(|bar| bar.thread == thread).collect();
+        assert_eq!(bars.len(), 2);
+        assert_eq!((bars[0].name.as_str(), bars[0].depth), ("outer", 0));
+        assert_eq!((bars[1].name.as_str(), bars[1].depth), ("inner", 1));
+        assert!(bars[1].start >= bars[0].start);
+        assert!(bars[1].width <= bars[0].width);
+
+        view.toggle();
+        assert!(!view.is_visible());
+        assert!(view.bars().is_empty());
+    }
+}
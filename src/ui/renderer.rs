@@ -1,8 +1,10 @@
-// Post-processing renderer for bloom and glow effects
+// Quad renderer for UI shapes, and post-processing for bloom and glow effects
 use wgpu::*;
+use wgpu::util::StagingBelt;
 use std::sync::Arc;
 use bytemuck::{Pod, Zeroable};
 use super::CyberpunkTheme;
+use crate::profiling::profile_scope;
 
 // Define uniform buffer data structs with bytemuck
 #[repr(C)]
@@ -22,10 +24,428 @@ struct CompositeUniforms {
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct GlowUniforms {
-    color: [f32; 4],
     intensity: f32,
     size: f32,
-    _padding: [f32; 2], // Ensure 16-byte alignment
+    /// Animation time in seconds, and how far the glow's strength swings with it
+    time: f32,
+    pulse: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ScreenUniforms {
+    size: [f32; 2],
+    /// Physical pixels per logical pixel, which quads are given in
+    scale_factor: f32,
+    _padding: f32, // Ensure 16-byte alignment
+}
+
+/// How a fill blends from one color into another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gradient {
+    /// From `start` to `end` across the quad, heading `angle` radians from the
+    /// +x axis; 0 runs left to right, π/2 top to bottom
+    Linear { start: [f32; 4], end: [f32; 4], angle: f32 },
+    /// From `center` in the middle to `edge` at the middle of the sides
+    Radial { center: [f32; 4], edge: [f32; 4] },
+}
+
+impl Gradient {
+    /// A linear gradient from the top edge to the bottom one
+    pub fn vertical(top: [f32; 4], bottom: [f32; 4]) -> Self {
+        Gradient::Linear { start: top, end: bottom, angle: std::f32::consts::FRAC_PI_2 }
+    }
+
+    /// A linear gradient from the left edge to the right one
+    pub fn horizontal(left: [f32; 4], right: [f32; 4]) -> Self {
+        Gradient::Linear { start: left, end: right, angle: 0.0 }
+    }
+}
+
+/// One rectangle, line or circle for the quad renderer
+///
+/// Rectangles can have rounded corners, a border drawn inside their bounds,
+/// a gradient fill and soft edges; lines are rotated rectangles and circles
+/// fully rounded squares.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+pub struct Quad {
+    /// Center x, center y, half width, half height in pixels
+    rect: [f32; 4],
+    color: [f32; 4],
+    border_color: [f32; 4],
+    /// Corner radius, border width, rotation in radians, softness
+    params: [f32; 4],
+    /// Min x, min y, max x, max y of the area it may draw in
+    clip: [f32; 4],
+    /// Fill color at the end of the gradient; the same as `color` for flat fills
+    end_color: [f32; 4],
+    /// Direction x, direction y of a linear gradient, 1 if it's radial instead, unused
+    gradient: [f32; 4],
+}
+
+impl Quad {
+    /// A filled rectangle with its top-left corner at (x, y)
+    pub fn rect(x: f32, y: f32, width: f32, height: f32, color: [f32; 4]) -> Self {
+        Self {
+            rect: [x + width / 2.0, y + height / 2.0, width.max(0.0) / 2.0, height.max(0.0) / 2.0],
+            color,
+            border_color: color,
+            params: [0.0; 4],
+            clip: [f32::MIN, f32::MIN, f32::MAX, f32::MAX],
+            end_color: color,
+            gradient: [1.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    /// A line from (x1, y1) to (x2, y2), `thickness` wide
+    pub fn line(x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: [f32; 4]) -> Self {
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length = (dx * dx + dy * dy).sqrt();
+        let mut quad = Self::rect(0.0, 0.0, length, thickness, color);
+        quad.rect[0] = (x1 + x2) / 2.0;
+        quad.rect[1] = (y1 + y2) / 2.0;
+        quad.params[2] = dy.atan2(dx);
+        quad
+    }
+
+    /// A filled circle centered on (x, y)
+    pub fn circle(x: f32, y: f32, radius: f32, color: [f32; 4]) -> Self {
+        Self::rect(x - radius, y - radius, radius * 2.0, radius * 2.0, color).with_corner_radius(radius)
+    }
+
+    /// Round the corners; the radius is capped at half the shorter side
+    pub fn with_corner_radius(mut self, radius: f32) -> Self {
+        self.params[0] = radius.max(0.0);
+        self
+    }
+
+    /// Draw a border `width` pixels wide just inside the edge
+    pub fn with_border(mut self, width: f32, color: [f32; 4]) -> Self {
+        self.params[1] = width.max(0.0);
+        self.border_color = color;
+        self
+    }
+
+    /// Blur the edge over `softness` pixels either side of it, e.g. for a shadow
+    pub fn with_softness(mut self, softness: f32) -> Self {
+        self.params[3] = softness.max(0.0);
+        self
+    }
+
+    /// Fill with a gradient instead of the flat color; it follows the quad's rotation
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        match gradient {
+            Gradient::Linear { start, end, angle } => {
+                self.color = start;
+                self.end_color = end;
+                self.gradient = [angle.cos(), angle.sin(), 0.0, 0.0];
+            }
+            Gradient::Radial { center, edge } => {
+                self.color = center;
+                self.end_color = edge;
+                self.gradient = [0.0, 0.0, 1.0, 0.0];
+            }
+        }
+        self
+    }
+
+    /// Only draw the parts inside this rectangle
+    pub fn with_clip(mut self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.clip = [x, y, x + width, y + height];
+        self
+    }
+
+    /// Only draw the border, leaving the inside transparent
+    pub fn outline(x: f32, y: f32, width: f32, height: f32, thickness: f32, color: [f32; 4]) -> Self {
+        Self::rect(x, y, width, height, [0.0; 4]).with_border(thickness, color)
+    }
+
+    /// Whether drawing it would change any pixel
+    pub fn is_visible(&self) -> bool {
+        let [_, _, half_width, half_height] = self.rect;
+        let fill = self.color[3] > 0.0 || self.end_color[3] > 0.0;
+        let border = self.params[1] > 0.0 && self.border_color[3] > 0.0;
+        half_width > 0.0 && half_height > 0.0 && (fill || border)
+    }
+}
+
+/// Draws queued `Quad`s in a single instanced draw call
+///
+/// Quads are queued while widgets render and drawn, in the order they were
+/// queued, by `draw_queued`, the same way `wgpu_glyph` handles text. Quads
+/// that should glow have a queue of their own, drawn into the glow mask.
+pub struct QuadRenderer {
+    pipeline: RenderPipeline,
+    screen_buffer: Buffer,
+    bind_group: BindGroup,
+    instance_buffer: Buffer,
+    /// Quads the instance buffer has room for
+    capacity: usize,
+    queued: Vec<Quad>,
+    glow_queued: Vec<Quad>,
+    scale_factor: f32,
+}
+
+impl QuadRenderer {
+    /// Quads the instance buffer starts out with room for
+    const INITIAL_CAPACITY: usize = 256;
+
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let screen_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Quad Screen Uniforms"),
+            size: std::mem::size_of::<ScreenUniforms>() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Quad Shader"),
+            source: ShaderSource::Wgsl(include_str!("../shaders/quad.wgsl").into()),
+        });
+        
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Quad Bind Group Layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Quad Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: screen_buffer.as_entire_binding(),
+            }],
+        });
+        
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Quad Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Quad Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Quad>() as u64,
+                    step_mode: VertexStepMode::Instance,
+                    attributes: &vertex_attr_array![
+                        0 => Float32x4, 1 => Float32x4, 2 => Float32x4, 3 => Float32x4, 4 => Float32x4,
+                        5 => Float32x4, 6 => Float32x4,
+                    ],
+                }],
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+        
+        Self {
+            pipeline,
+            screen_buffer,
+            bind_group,
+            instance_buffer: Self::create_instance_buffer(device, Self::INITIAL_CAPACITY),
+            capacity: Self::INITIAL_CAPACITY,
+            queued: Vec::new(),
+            glow_queued: Vec::new(),
+            scale_factor: 1.0,
+        }
+    }
+    
+    /// Take quads in logical pixels, this many physical pixels each, from the next draw on
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+    
+    fn create_instance_buffer(device: &Device, capacity: usize) -> Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: Some("Quad Instances"),
+            size: (capacity * std::mem::size_of::<Quad>()) as u64,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+    
+    /// Queue a quad for the next `draw_queued`; invisible ones are dropped
+    pub fn queue(&mut self, quad: Quad) {
+        if quad.is_visible() {
+            self.queued.push(quad);
+        }
+    }
+    
+    /// Quads queued since the last draw
+    pub fn queued(&self) -> &[Quad] {
+        &self.queued
+    }
+    
+    /// Queue a quad for the next `draw_glow_queued`; invisible ones are dropped
+    pub fn queue_glow(&mut self, quad: Quad) {
+        if quad.is_visible() {
+            self.glow_queued.push(quad);
+        }
+    }
+    
+    /// Glowing quads queued since the last glow draw
+    pub fn queued_glow(&self) -> &[Quad] {
+        &self.glow_queued
+    }
+    
+    /// Drop the queued glowing quads, for a target without a glow pass
+    pub fn discard_glow(&mut self) {
+        self.glow_queued.clear();
+    }
+    
+    /// Clear the glow mask `target` and draw every queued glowing quad on it
+    ///
+    /// Cleared even without quads, so last frame's glow doesn't linger.
+    pub fn draw_glow_queued(
+        &mut self,
+        device: &Device,
+        staging_belt: &mut StagingBelt,
+        encoder: &mut CommandEncoder,
+        target: &TextureView,
+        width: u32,
+        height: u32,
+    ) {
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Glow Mask Clear"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::TRANSPARENT),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        
+        // The glow goes through the same draw as everything else, then the queues swap back
+        std::mem::swap(&mut self.queued, &mut self.glow_queued);
+        self.draw_queued(device, staging_belt, encoder, target, width, height);
+        std::mem::swap(&mut self.queued, &mut self.glow_queued);
+    }
+    
+    /// Draw every queued quad on top of `target`, `width` by `height`
+    /// physical pixels, and clear the queue
+    ///
+    /// Uploads go through the staging belt, so several draws into different
+    /// targets can share one encoder.
+    pub fn draw_queued(
+        &mut self,
+        device: &Device,
+        staging_belt: &mut StagingBelt,
+        encoder: &mut CommandEncoder,
+        target: &TextureView,
+        width: u32,
+        height: u32,
+    ) {
+        profile_scope!("quads");
+        if self.queued.is_empty() {
+            return;
+        }
+        
+        if self.queued.len() > self.capacity {
+            self.capacity = self.queued.len().next_power_of_two();
+            self.instance_buffer = Self::create_instance_buffer(device, self.capacity);
+        }
+        
+        let screen = ScreenUniforms { size: [width as f32, height as f32], scale_factor: self.scale_factor, _padding: 0.0 };
+        let screen_bytes: &[u8] = bytemuck::cast_slice(std::slice::from_ref(&screen));
+        let instance_bytes: &[u8] = bytemuck::cast_slice(&self.queued);
+        for (buffer, bytes) in [(&self.screen_buffer, screen_bytes), (&self.instance_buffer, instance_bytes)] {
+            // Neither is empty: the queue has quads and the uniforms are fixed size
+            let size = BufferSize::new(bytes.len() as u64).expect("upload is not empty");
+            staging_belt.write_buffer(encoder, buffer, 0, size, device).copy_from_slice(bytes);
+        }
+        
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Quad Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load, // Draw over what's there
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.instance_buffer.slice(..instance_bytes.len() as u64));
+        pass.draw(0..6, 0..self.queued.len() as u32); // Two triangles per quad
+        drop(pass);
+        
+        self.queued.clear();
+    }
+}
+
+/// Format of the bloom's half-resolution textures, whatever the output's
+const BLOOM_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
+/// Bytes a `width` by `height` texture of `format` takes, with no mipmaps
+fn texture_bytes(format: TextureFormat, width: u32, height: u32) -> u64 {
+    let texel = format.block_copy_size(None).unwrap_or(4) as u64;
+    width.max(1) as u64 * height.max(1) as u64 * texel
+}
+
+/// The half-resolution textures of the bloom passes, and the bind groups that
+/// read them and the scene
+///
+/// Made on resize rather than every frame: the scene view they're made for is
+/// the same from frame to frame until the size changes.
+struct BloomTargets {
+    bright_view: TextureView,
+    blur_h_view: TextureView,
+    blur_v_view: TextureView,
+    extract_bind_group: BindGroup,
+    blur_h_bind_group: BindGroup,
+    blur_v_bind_group: BindGroup,
+    composite_bind_group: BindGroup,
 }
 
 // BloomEffect handles the extraction, blur, and compositing for the bloom effect
@@ -40,16 +460,8 @@ pub struct BloomEffect {
     blur_v_pipeline: RenderPipeline,
     composite_pipeline: RenderPipeline,
     
-    // Bind groups for each stage
-    extract_bind_group: Option<BindGroup>,
-    blur_h_bind_group: Option<BindGroup>,
-    blur_v_bind_group: Option<BindGroup>,
-    composite_bind_group: Option<BindGroup>,
-    
-    // Intermediate textures
-    bright_texture: Option<Texture>,
-    blur_h_texture: Option<Texture>,
-    blur_v_texture: Option<Texture>,
+    // Intermediate textures and the bind groups for each stage, once sized
+    targets: Option<BloomTargets>,
     
     // Samplers
     sampler: Sampler,
@@ -145,14 +557,16 @@ impl BloomEffect {
             layout: Some(&extract_layout),
             vertex: VertexState {
                 module: &extract_shader,
-                entry_point: "vs_main",
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
                 buffers: &[],
             },
             fragment: Some(FragmentState {
                 module: &extract_shader,
-                entry_point: "fs_main",
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
                 targets: &[Some(ColorTargetState {
-                    format,
+                    format: BLOOM_FORMAT,
                     blend: Some(BlendState::ALPHA_BLENDING),
                     write_mask: ColorWrites::ALL,
                 })],
@@ -173,6 +587,7 @@ impl BloomEffect {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
         
         let blur_h_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
@@ -180,14 +595,16 @@ impl BloomEffect {
             layout: Some(&blur_layout),
             vertex: VertexState {
                 module: &blur_h_shader,
-                entry_point: "vs_main",
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
                 buffers: &[],
             },
             fragment: Some(FragmentState {
                 module: &blur_h_shader,
-                entry_point: "fs_main",
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
                 targets: &[Some(ColorTargetState {
-                    format,
+                    format: BLOOM_FORMAT,
                     blend: Some(BlendState::ALPHA_BLENDING),
                     write_mask: ColorWrites::ALL,
                 })],
@@ -208,6 +625,7 @@ impl BloomEffect {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
         
         let blur_v_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
@@ -215,14 +633,16 @@ impl BloomEffect {
             layout: Some(&blur_layout),
             vertex: VertexState {
                 module: &blur_v_shader,
-                entry_point: "vs_main",
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
                 buffers: &[],
             },
             fragment: Some(FragmentState {
                 module: &blur_v_shader,
-                entry_point: "fs_main",
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
                 targets: &[Some(ColorTargetState {
-                    format,
+                    format: BLOOM_FORMAT,
                     blend: Some(BlendState::ALPHA_BLENDING),
                     write_mask: ColorWrites::ALL,
                 })],
@@ -243,6 +663,7 @@ impl BloomEffect {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
         
         let composite_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
@@ -250,12 +671,14 @@ impl BloomEffect {
             layout: Some(&composite_layout),
             vertex: VertexState {
                 module: &composite_shader,
-                entry_point: "vs_main",
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
                 buffers: &[],
             },
             fragment: Some(FragmentState {
                 module: &composite_shader,
-                entry_point: "fs_main",
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
                 targets: &[Some(ColorTargetState {
                     format,
                     blend: Some(BlendState::ALPHA_BLENDING),
@@ -278,6 +701,7 @@ impl BloomEffect {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
         
         // Set default settings
@@ -315,13 +739,7 @@ impl BloomEffect {
             blur_h_pipeline,
             blur_v_pipeline,
             composite_pipeline,
-            extract_bind_group: None,
-            blur_h_bind_group: None,
-            blur_v_bind_group: None,
-            composite_bind_group: None,
-            bright_texture: None,
-            blur_h_texture: None,
-            blur_v_texture: None,
+            targets: None,
             sampler,
             extract_uniform_buffer,
             composite_uniform_buffer,
@@ -355,26 +773,6 @@ impl BloomEffect {
                 BindGroupLayoutEntry {
                     binding: 2,
                     visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::StorageTexture {
-                        access: StorageTextureAccess::WriteOnly,
-                        format: TextureFormat::Rgba8Unorm,
-                        view_dimension: TextureViewDimension::D2,
-                    },
-                    count: None,
-                },
-                BindGroupLayoutEntry {
-                    binding: 3,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                BindGroupLayoutEntry {
-                    binding: 4,
-                    visibility: ShaderStages::FRAGMENT,
                     ty: BindingType::Buffer {
                         ty: BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -407,16 +805,6 @@ impl BloomEffect {
                     ty: BindingType::Sampler(SamplerBindingType::Filtering),
                     count: None,
                 },
-                BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::StorageTexture {
-                        access: StorageTextureAccess::WriteOnly,
-                        format: TextureFormat::Rgba8Unorm,
-                        view_dimension: TextureViewDimension::D2,
-                    },
-                    count: None,
-                },
             ],
         })
     }
@@ -462,121 +850,38 @@ impl BloomEffect {
                     },
                     count: None,
                 },
-                BindGroupLayoutEntry {
-                    binding: 4,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
             ],
         })
     }
     
-    // Setup the bloom effect with the current screen size
-    pub fn resize(&mut self, width: u32, height: u32) {
-        // Create reduced resolution textures for the bloom effect
+    /// Size the bloom for a `width` by `height` scene, read from `input_view`
+    ///
+    /// The passes' textures and bind groups are made here, once per size, and
+    /// reused by every `apply` until the next resize.
+    pub fn resize(&mut self, width: u32, height: u32, input_view: &TextureView) {
         // Using half resolution for better performance
-        let bloom_width = width / 2;
-        let bloom_height = height / 2;
-        
-        // Create bright extraction texture (half res)
-        self.bright_texture = Some(self.device.create_texture(&TextureDescriptor {
-            label: Some("Bright Texture"),
-            size: Extent3d {
-                width: bloom_width,
-                height: bloom_height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: TextureDimension::D2,
-            format: TextureFormat::Rgba8Unorm,
-            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::STORAGE_BINDING | TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        }));
-        
-        // Create horizontal blur texture (half res)
-        self.blur_h_texture = Some(self.device.create_texture(&TextureDescriptor {
-            label: Some("Horizontal Blur Texture"),
-            size: Extent3d {
-                width: bloom_width,
-                height: bloom_height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: TextureDimension::D2,
-            format: TextureFormat::Rgba8Unorm,
-            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::STORAGE_BINDING | TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        }));
-        
-        // Create vertical blur texture (half res)
-        self.blur_v_texture = Some(self.device.create_texture(&TextureDescriptor {
-            label: Some("Vertical Blur Texture"),
-            size: Extent3d {
-                width: bloom_width,
-                height: bloom_height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: TextureDimension::D2,
-            format: TextureFormat::Rgba8Unorm,
-            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::STORAGE_BINDING | TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        }));
-    }
-    
-    // Update bloom settings
-    pub fn update_settings(&mut self, threshold: f32, intensity: f32, saturation: f32) {
-        self.threshold = threshold;
-        self.intensity = intensity;
-        self.saturation = saturation;
-        
-        // Update uniform buffers
-        let extract_uniforms = ExtractUniforms {
-            threshold,
-            intensity,
-        };
-        
-        let composite_uniforms = CompositeUniforms {
-            intensity,
-            saturation,
+        let bloom_width = (width / 2).max(1);
+        let bloom_height = (height / 2).max(1);
+        let create_view = |label| {
+            self.device.create_texture(&TextureDescriptor {
+                label: Some(label),
+                size: Extent3d {
+                    width: bloom_width,
+                    height: bloom_height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: BLOOM_FORMAT,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            }).create_view(&TextureViewDescriptor::default())
         };
+        let bright_view = create_view("Bright Texture");
+        let blur_h_view = create_view("Horizontal Blur Texture");
+        let blur_v_view = create_view("Vertical Blur Texture");
         
-        self.queue.write_buffer(
-            &self.extract_uniform_buffer,
-            0,
-            bytemuck::cast_slice(&[extract_uniforms]),
-        );
-        
-        self.queue.write_buffer(
-            &self.composite_uniform_buffer,
-            0,
-            bytemuck::cast_slice(&[composite_uniforms]),
-        );
-    }
-    
-    // Apply the bloom effect
-    pub fn apply(&self, encoder: &mut CommandEncoder, input_view: &TextureView, output_view: &TextureView) {
-        // Skip if not initialized
-        if self.bright_texture.is_none() 
-          || self.blur_h_texture.is_none() 
-          || self.blur_v_texture.is_none() {
-            return;
-        }
-        
-        // Get texture views
-        let bright_view = self.bright_texture.as_ref().unwrap().create_view(&TextureViewDescriptor::default());
-        let blur_h_view = self.blur_h_texture.as_ref().unwrap().create_view(&TextureViewDescriptor::default());
-        let blur_v_view = self.blur_v_texture.as_ref().unwrap().create_view(&TextureViewDescriptor::default());
-        
-        // Create bind groups if not already created
         let extract_bind_group = self.device.create_bind_group(&BindGroupDescriptor {
             label: Some("Extract Bind Group"),
             layout: &self.extract_pipeline.get_bind_group_layout(0),
@@ -591,56 +896,29 @@ impl BloomEffect {
                 },
                 BindGroupEntry {
                     binding: 2,
-                    resource: BindingResource::TextureView(&bright_view),
-                },
-                BindGroupEntry {
-                    binding: 3,
-                    resource: self.extract_uniform_buffer.as_entire_binding(),
-                },
-                BindGroupEntry {
-                    binding: 4,
                     resource: self.extract_uniform_buffer.as_entire_binding(),
                 },
             ],
         });
         
-        let blur_h_bind_group = self.device.create_bind_group(&BindGroupDescriptor {
-            label: Some("Horizontal Blur Bind Group"),
-            layout: &self.blur_h_pipeline.get_bind_group_layout(0),
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(&bright_view),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Sampler(&self.sampler),
-                },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::TextureView(&blur_h_view),
-                },
-            ],
-        });
-        
-        let blur_v_bind_group = self.device.create_bind_group(&BindGroupDescriptor {
-            label: Some("Vertical Blur Bind Group"),
-            layout: &self.blur_v_pipeline.get_bind_group_layout(0),
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(&blur_h_view),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Sampler(&self.sampler),
-                },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::TextureView(&blur_v_view),
-                },
-            ],
-        });
+        let blur_bind_group = |label, pipeline: &RenderPipeline, view| {
+            self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some(label),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            })
+        };
+        let blur_h_bind_group = blur_bind_group("Horizontal Blur Bind Group", &self.blur_h_pipeline, &bright_view);
+        let blur_v_bind_group = blur_bind_group("Vertical Blur Bind Group", &self.blur_v_pipeline, &blur_h_view);
         
         let composite_bind_group = self.device.create_bind_group(&BindGroupDescriptor {
             label: Some("Composite Bind Group"),
@@ -662,19 +940,64 @@ impl BloomEffect {
                     binding: 3,
                     resource: self.composite_uniform_buffer.as_entire_binding(),
                 },
-                BindGroupEntry {
-                    binding: 4,
-                    resource: self.composite_uniform_buffer.as_entire_binding(),
-                },
             ],
         });
         
+        self.targets = Some(BloomTargets {
+            bright_view,
+            blur_h_view,
+            blur_v_view,
+            extract_bind_group,
+            blur_h_bind_group,
+            blur_v_bind_group,
+            composite_bind_group,
+        });
+    }
+    
+    // Update bloom settings
+    pub fn update_settings(&mut self, threshold: f32, intensity: f32, saturation: f32) {
+        self.threshold = threshold;
+        self.intensity = intensity;
+        self.saturation = saturation;
+        
+        // Update uniform buffers
+        let extract_uniforms = ExtractUniforms {
+            threshold,
+            intensity,
+        };
+        
+        let composite_uniforms = CompositeUniforms {
+            intensity,
+            saturation,
+        };
+        
+        self.queue.write_buffer(
+            &self.extract_uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[extract_uniforms]),
+        );
+        
+        self.queue.write_buffer(
+            &self.composite_uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[composite_uniforms]),
+        );
+    }
+    
+    /// Bloom the scene given to `resize` into `output_view`
+    pub fn apply(&self, encoder: &mut CommandEncoder, output_view: &TextureView) {
+        profile_scope!("bloom");
+        // Skip if not sized yet
+        let Some(targets) = &self.targets else {
+            return;
+        };
+        
         // Step 1: Extract bright areas
         {
             let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("Bloom Extract Pass"),
                 color_attachments: &[Some(RenderPassColorAttachment {
-                    view: &bright_view,
+                    view: &targets.bright_view,
                     resolve_target: None,
                     ops: Operations {
                         load: LoadOp::Clear(Color::BLACK),
@@ -687,7 +1010,7 @@ impl BloomEffect {
             });
             
             pass.set_pipeline(&self.extract_pipeline);
-            pass.set_bind_group(0, &extract_bind_group, &[]);
+            pass.set_bind_group(0, &targets.extract_bind_group, &[]);
             pass.draw(0..3, 0..1); // Full-screen triangle
         }
         
@@ -696,7 +1019,7 @@ impl BloomEffect {
             let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("Horizontal Blur Pass"),
                 color_attachments: &[Some(RenderPassColorAttachment {
-                    view: &blur_h_view,
+                    view: &targets.blur_h_view,
                     resolve_target: None,
                     ops: Operations {
                         load: LoadOp::Clear(Color::BLACK),
@@ -709,7 +1032,7 @@ impl BloomEffect {
             });
             
             pass.set_pipeline(&self.blur_h_pipeline);
-            pass.set_bind_group(0, &blur_h_bind_group, &[]);
+            pass.set_bind_group(0, &targets.blur_h_bind_group, &[]);
             pass.draw(0..3, 0..1); // Full-screen triangle
         }
         
@@ -718,7 +1041,7 @@ impl BloomEffect {
             let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("Vertical Blur Pass"),
                 color_attachments: &[Some(RenderPassColorAttachment {
-                    view: &blur_v_view,
+                    view: &targets.blur_v_view,
                     resolve_target: None,
                     ops: Operations {
                         load: LoadOp::Clear(Color::BLACK),
@@ -731,7 +1054,7 @@ impl BloomEffect {
             });
             
             pass.set_pipeline(&self.blur_v_pipeline);
-            pass.set_bind_group(0, &blur_v_bind_group, &[]);
+            pass.set_bind_group(0, &targets.blur_v_bind_group, &[]);
             pass.draw(0..3, 0..1); // Full-screen triangle
         }
         
@@ -753,13 +1076,16 @@ impl BloomEffect {
             });
             
             pass.set_pipeline(&self.composite_pipeline);
-            pass.set_bind_group(0, &composite_bind_group, &[]);
+            pass.set_bind_group(0, &targets.composite_bind_group, &[]);
             pass.draw(0..3, 0..1); // Full-screen triangle
         }
     }
 }
 
 // NeonGlowEffect creates a vibrant glow around UI elements
+//
+// Only what widgets drew into the glow mask glows, each shape in its own color;
+// the pass spreads the mask into a halo and adds it to the frame.
 pub struct NeonGlowEffect {
     // Device and queue for operations
     device: Arc<Device>,
@@ -768,7 +1094,7 @@ pub struct NeonGlowEffect {
     // Render pipeline
     pipeline: RenderPipeline,
     
-    // Bind group
+    // Bind group reading the input and the glow mask, made when they change
     bind_group: Option<BindGroup>,
     
     // Sampler
@@ -778,9 +1104,10 @@ pub struct NeonGlowEffect {
     uniform_buffer: Buffer,
     
     // Settings
-    color: [f32; 4],
     intensity: f32,
     size: f32,
+    time: f32,
+    pulse: f32,
 }
 
 impl NeonGlowEffect {
@@ -844,20 +1171,10 @@ impl NeonGlowEffect {
                 BindGroupLayoutEntry {
                     binding: 3,
                     visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                BindGroupLayoutEntry {
-                    binding: 4,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
                     },
                     count: None,
                 },
@@ -877,12 +1194,14 @@ impl NeonGlowEffect {
             layout: Some(&pipeline_layout),
             vertex: VertexState {
                 module: &shader,
-                entry_point: "vs_main",
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
                 buffers: &[],
             },
             fragment: Some(FragmentState {
                 module: &shader,
-                entry_point: "fs_main",
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
                 targets: &[Some(ColorTargetState {
                     format,
                     blend: Some(BlendState::ALPHA_BLENDING),
@@ -905,54 +1224,62 @@ impl NeonGlowEffect {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
         
         // Default settings
-        let color = theme.cyan();
         let intensity = theme.glow_intensity();
         let size = 10.0;
         
-        // Write initial uniform data
-        let glow_uniforms = GlowUniforms {
-            color,
-            intensity,
-            size,
-            _padding: [0.0, 0.0], // Ensure 16-byte alignment
-        };
-        
-        queue.write_buffer(
-            &uniform_buffer,
-            0,
-            bytemuck::cast_slice(&[glow_uniforms]),
-        );
-        
-        Self {
+        let effect = Self {
             device,
             queue,
             pipeline,
             bind_group: None,
             sampler,
             uniform_buffer,
-            color,
             intensity,
             size,
-        }
+            time: 0.0,
+            pulse: 0.0,
+        };
+        
+        // Write initial uniform data
+        effect.write_uniforms();
+        effect
     }
     
-    // Update glow settings
-    pub fn update_settings(&mut self, color: [f32; 4], intensity: f32, size: f32) {
-        self.color = color;
+    // Update glow settings; the colors come from what's drawn into the glow mask
+    pub fn update_settings(&mut self, intensity: f32, size: f32) {
         self.intensity = intensity;
         self.size = size;
-        
-        // Update uniform buffer
+        self.write_uniforms();
+    }
+    
+    /// Glow as strongly as `theme` does by default, keeping the size
+    pub fn set_theme(&mut self, theme: &CyberpunkTheme) {
+        self.update_settings(theme.glow_intensity(), self.size);
+    }
+    
+    /// Make the glow swell and fade with time, by up to `pulse` of its intensity (0 holds it steady)
+    pub fn set_pulse(&mut self, pulse: f32) {
+        self.pulse = pulse;
+        self.write_uniforms();
+    }
+    
+    /// Set the animation time the pulse follows, in seconds
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+        self.write_uniforms();
+    }
+    
+    fn write_uniforms(&self) {
         let glow_uniforms = GlowUniforms {
-            color,
-            intensity,
-            size,
-            _padding: [0.0, 0.0],
+            intensity: self.intensity,
+            size: self.size,
+            time: self.time,
+            pulse: self.pulse,
         };
-        
         self.queue.write_buffer(
             &self.uniform_buffer,
             0,
@@ -960,10 +1287,10 @@ impl NeonGlowEffect {
         );
     }
     
-    // Apply the neon glow effect
-    pub fn apply(&self, encoder: &mut CommandEncoder, input_view: &TextureView, output_view: &TextureView) {
-        // Create bind group
-        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+    /// Read the frame from `input_view` and what glows from `mask_view` from
+    /// now on, e.g. after a resize replaced them
+    pub fn set_input(&mut self, input_view: &TextureView, mask_view: &TextureView) {
+        self.bind_group = Some(self.device.create_bind_group(&BindGroupDescriptor {
             label: Some("Neon Glow Bind Group"),
             layout: &self.pipeline.get_bind_group_layout(0),
             entries: &[
@@ -981,14 +1308,19 @@ impl NeonGlowEffect {
                 },
                 BindGroupEntry {
                     binding: 3,
-                    resource: self.uniform_buffer.as_entire_binding(),
-                },
-                BindGroupEntry {
-                    binding: 4,
-                    resource: self.uniform_buffer.as_entire_binding(),
+                    resource: BindingResource::TextureView(mask_view),
                 },
             ],
-        });
+        }));
+    }
+    
+    /// Draw the input given to `set_input` to `output_view`, with the mask's glow added
+    pub fn apply(&self, encoder: &mut CommandEncoder, output_view: &TextureView) {
+        profile_scope!("glow");
+        // Skip until there's an input
+        let Some(bind_group) = &self.bind_group else {
+            return;
+        };
         
         // Render pass
         let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
@@ -1007,13 +1339,227 @@ impl NeonGlowEffect {
         });
         
         pass.set_pipeline(&self.pipeline);
-        pass.set_bind_group(0, &bind_group, &[]);
+        pass.set_bind_group(0, bind_group, &[]);
         pass.draw(0..3, 0..1); // Full-screen triangle
     }
 }
 
+/// The full-size textures a frame is drawn into before it reaches the output:
+/// the scene the bloom reads, the bloom result the overlays go on, and the
+/// mask of what glows
+///
+/// Made once for a size and kept from frame to frame; resizing to another
+/// size replaces them.
+pub struct RenderTargets {
+    device: Arc<Device>,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    scene_view: TextureView,
+    bloom_view: TextureView,
+    glow_view: TextureView,
+}
+
+impl RenderTargets {
+    pub fn new(device: Arc<Device>, format: TextureFormat, width: u32, height: u32) -> Self {
+        let [scene_view, bloom_view, glow_view] = Self::create_views(&device, format, width, height);
+        Self { device, format, width, height, scene_view, bloom_view, glow_view }
+    }
+    
+    fn create_views(device: &Device, format: TextureFormat, width: u32, height: u32) -> [TextureView; 3] {
+        // The views keep their textures alive
+        let create_view = |label| {
+            device.create_texture(&TextureDescriptor {
+                label: Some(label),
+                size: Extent3d { width: width.max(1), height: height.max(1), depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            }).create_view(&TextureViewDescriptor::default())
+        };
+        ["Scene Buffer", "Bloom Buffer", "Glow Mask"].map(create_view)
+    }
+    
+    /// Follow the output's size, replacing the textures only if it changed
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) == self.size() {
+            return;
+        }
+        [self.scene_view, self.bloom_view, self.glow_view] = Self::create_views(&self.device, self.format, width, height);
+        self.width = width;
+        self.height = height;
+    }
+    
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+    
+    /// Bytes the three targets take on the GPU
+    pub fn memory_bytes(&self) -> u64 {
+        3 * texture_bytes(self.format, self.width, self.height)
+    }
+    
+    pub fn scene_view(&self) -> &TextureView {
+        &self.scene_view
+    }
+    
+    pub fn bloom_view(&self) -> &TextureView {
+        &self.bloom_view
+    }
+    
+    pub fn glow_view(&self) -> &TextureView {
+        &self.glow_view
+    }
+}
+
+/// The post-processing a frame goes through: bloom over the scene, then the
+/// neon glow on the way to the output
+///
+/// Owns the effects and the targets between them. A frame draws its scene
+/// into `scene_view`, calls `apply_bloom`, draws what should stay sharp into
+/// `bloom_view` and what should glow into `glow_view`, and ends with `apply_glow`.
+pub struct PostProcessStack {
+    targets: RenderTargets,
+    bloom_effect: BloomEffect,
+    neon_glow_effect: NeonGlowEffect,
+}
+
+impl PostProcessStack {
+    pub fn new(device: Arc<Device>, queue: Arc<Queue>, format: TextureFormat, theme: &CyberpunkTheme, width: u32, height: u32) -> Self {
+        let targets = RenderTargets::new(device.clone(), format, width, height);
+        let mut bloom_effect = BloomEffect::new(device.clone(), queue.clone(), format);
+        bloom_effect.resize(width, height, targets.scene_view());
+        let mut neon_glow_effect = NeonGlowEffect::new(device, queue, format, theme);
+        neon_glow_effect.set_input(targets.bloom_view(), targets.glow_view());
+        Self { targets, bloom_effect, neon_glow_effect }
+    }
+    
+    /// Follow the output's size; the same size keeps every texture and bind group
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) == self.targets.size() {
+            return;
+        }
+        self.targets.resize(width, height);
+        self.bloom_effect.resize(width, height, self.targets.scene_view());
+        self.neon_glow_effect.set_input(self.targets.bloom_view(), self.targets.glow_view());
+    }
+    
+    pub fn set_bloom(&mut self, threshold: f32, intensity: f32, saturation: f32) {
+        self.bloom_effect.update_settings(threshold, intensity, saturation);
+    }
+    
+    pub fn set_glow(&mut self, intensity: f32, size: f32) {
+        self.neon_glow_effect.update_settings(intensity, size);
+    }
+    
+    /// Use `theme`'s default glow, until `set_glow` picks another
+    pub fn set_theme(&mut self, theme: &CyberpunkTheme) {
+        self.neon_glow_effect.set_theme(theme);
+    }
+    
+    /// Make the glow swell and fade with time, by up to `pulse` of its intensity (0 holds it steady)
+    pub fn set_glow_pulse(&mut self, pulse: f32) {
+        self.neon_glow_effect.set_pulse(pulse);
+    }
+    
+    /// Set the animation time the glow's pulse follows, in seconds
+    pub fn set_time(&mut self, time: f32) {
+        self.neon_glow_effect.set_time(time);
+    }
+    
+    /// Bytes the targets and the bloom's half-resolution textures take on the GPU
+    pub fn memory_bytes(&self) -> u64 {
+        let (width, height) = self.targets.size();
+        self.targets.memory_bytes() + 3 * texture_bytes(BLOOM_FORMAT, width / 2, height / 2)
+    }
+    
+    /// Where the frame's scene is drawn, for the bloom to read
+    pub fn scene_view(&self) -> &TextureView {
+        self.targets.scene_view()
+    }
+    
+    /// The scene with its bloom, where overlays that should stay sharp are drawn
+    pub fn bloom_view(&self) -> &TextureView {
+        self.targets.bloom_view()
+    }
+    
+    /// The mask of what glows, cleared and drawn every frame before `apply_glow`
+    pub fn glow_view(&self) -> &TextureView {
+        self.targets.glow_view()
+    }
+    
+    /// Bloom the scene into the bloom target
+    pub fn apply_bloom(&self, encoder: &mut CommandEncoder) {
+        self.bloom_effect.apply(encoder, self.targets.bloom_view());
+    }
+    
+    /// Add the glow of what's in the glow mask to the bloom target on its way to `output`
+    pub fn apply_glow(&self, encoder: &mut CommandEncoder, output: &TextureView) {
+        self.neon_glow_effect.apply(encoder, output);
+    }
+}
+
 // Export the module in mod.rs
 pub mod prelude {
-    pub use super::BloomEffect;
-    pub use super::NeonGlowEffect;
-} 
\ No newline at end of file
+    pub use super::{Gradient, Quad, QuadRenderer};
+    pub use super::PostProcessStack;
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_is_stored_by_center() {
+        let quad = Quad::rect(10.0, 20.0, 100.0, 30.0, [1.0; 4]);
+        assert_eq!(quad.rect, [60.0, 35.0, 50.0, 15.0]);
+        assert!(quad.is_visible());
+        assert!(!Quad::rect(0.0, 0.0, 0.0, 10.0, [1.0; 4]).is_visible());
+        assert!(!Quad::rect(0.0, 0.0, 10.0, 10.0, [0.0; 4]).is_visible());
+        assert!(Quad::outline(0.0, 0.0, 10.0, 10.0, 1.0, [1.0; 4]).is_visible());
+    }
+
+    #[test]
+    fn test_lines_and_circles() {
+        let line = Quad::line(0.0, 0.0, 0.0, 40.0, 2.0, [1.0; 4]);
+        assert_eq!(&line.rect, &[0.0, 20.0, 20.0, 1.0]);
+        assert!((line.params[2] - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let circle = Quad::circle(50.0, 50.0, 8.0, [1.0; 4]);
+        assert_eq!(circle.rect, [50.0, 50.0, 8.0, 8.0]);
+        assert_eq!(circle.params[0], 8.0);
+    }
+
+    #[test]
+    fn test_gradients() {
+        let flat = Quad::rect(0.0, 0.0, 10.0, 10.0, [1.0; 4]);
+        assert_eq!(flat.end_color, flat.color);
+
+        let top = [1.0, 0.0, 1.0, 1.0];
+        let fading = Quad::rect(0.0, 0.0, 10.0, 10.0, [0.0; 4]).with_gradient(Gradient::vertical(top, [0.0; 4]));
+        assert_eq!((fading.color, fading.end_color), (top, [0.0; 4]));
+        assert!(fading.gradient[0].abs() < 1e-6 && fading.gradient[1] == 1.0);
+        assert!(fading.is_visible());
+
+        let glow = Quad::circle(0.0, 0.0, 4.0, [0.0; 4]).with_gradient(Gradient::Radial { center: top, edge: [0.0; 4] });
+        assert_eq!(glow.gradient, [0.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_texture_bytes() {
+        assert_eq!(texture_bytes(TextureFormat::Bgra8UnormSrgb, 1280, 720), 1280 * 720 * 4);
+        assert_eq!(texture_bytes(TextureFormat::Rgba16Float, 10, 10), 800);
+        // Zero-sized targets are made one pixel wide, so they're counted that way
+        assert_eq!(texture_bytes(BLOOM_FORMAT, 0, 0), 4);
+    }
+
+    #[test]
+    fn test_soft_edges() {
+        let shadow = Quad::rect(0.0, 0.0, 10.0, 10.0, [0.0, 0.0, 0.0, 0.5]).with_softness(12.0);
+        assert_eq!(shadow.params[3], 12.0);
+        assert_eq!(shadow.with_softness(-1.0).params[3], 0.0);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::ui::{damage, RenderContext, Widget, CyberpunkTheme};
+use winit::window::CursorIcon;
+
+/// Shortest the handle may get, so it stays grabbable in long lists
+const MIN_HANDLE_HEIGHT: f32 = 24.0;
+
+/// Delay before holding the track starts paging repeatedly, in seconds
+const PAGE_REPEAT_DELAY: f32 = 0.4;
+
+/// Interval between repeated pages while the track is held, in seconds
+const PAGE_REPEAT_INTERVAL: f32 = 0.08;
+
+/// How much wider the bar gets, to the left, while hovered or dragged
+const HOVER_WIDEN: f32 = 6.0;
+
+/// Seconds the bar takes to widen or narrow back
+const WIDEN_DURATION: f32 = 0.12;
+
+/// Which part of the scrollbar a point is over
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScrollbarPart {
+    Handle,
+    /// The track above the handle
+    TrackAbove,
+    /// The track below the handle
+    TrackBelow,
+}
+
+/// What the pointer is currently doing with the scrollbar
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScrollbarDrag {
+    Idle,
+    /// Dragging the handle; `grab_offset` is where on the handle it was grabbed
+    Handle { grab_offset: f32 },
+    /// Holding the track above (-1) or below (+1) the handle
+    Paging { direction: f32, pointer_y: f32, repeat_in: f32 },
+}
+
+/// A vertical scrollbar for a viewport over taller content
+///
+/// The owner keeps it in sync with `set_range`/`set_offset` and reads `offset()`
+/// back after forwarding input to it.
+#[derive(Clone)]
+pub struct Scrollbar {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    offset: f32,
+    max_scroll: f32,
+    viewport: f32,
+    drag: ScrollbarDrag,
+    /// Whether the pointer is over the bar
+    hovered: bool,
+    /// How far the bar has widened, from 0.0 to 1.0, easing toward 1.0 while hovered or dragged
+    widen: f32,
+    theme: CyberpunkTheme,
+}
+
+impl Scrollbar {
+    /// Create a scrollbar track at the given position
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            offset: 0.0,
+            max_scroll: 0.0,
+            viewport: height,
+            drag: ScrollbarDrag::Idle,
+            hovered: false,
+            widen: 0.0,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Update the scrollable range: the visible height and how far the content can scroll
+    pub fn set_range(&mut self, viewport: f32, max_scroll: f32) {
+        self.viewport = viewport.max(1.0);
+        self.max_scroll = max_scroll.max(0.0);
+        self.offset = self.offset.clamp(0.0, self.max_scroll);
+    }
+
+    /// Current scroll offset
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// Set the scroll offset, e.g. after scrolling with the wheel
+    pub fn set_offset(&mut self, offset: f32) {
+        self.offset = offset.clamp(0.0, self.max_scroll);
+    }
+
+    /// Whether there is anything to scroll
+    pub fn is_scrollable(&self) -> bool {
+        self.max_scroll > 0.0
+    }
+
+    /// Whether the scrollbar currently owns the pointer (dragging or paging)
+    pub fn is_active(&self) -> bool {
+        self.drag != ScrollbarDrag::Idle
+    }
+
+    /// Whether the handle is being dragged
+    pub fn is_dragging(&self) -> bool {
+        matches!(self.drag, ScrollbarDrag::Handle { .. })
+    }
+
+    /// Vertical position and height of the handle
+    fn handle_geometry(&self) -> (f32, f32) {
+        let visible_ratio = self.viewport / (self.viewport + self.max_scroll);
+        let handle_height = (self.height * visible_ratio).max(MIN_HANDLE_HEIGHT).min(self.height);
+        let travel = self.height - handle_height;
+        let progress = if self.max_scroll > 0.0 { self.offset / self.max_scroll } else { 0.0 };
+        (self.y + progress * travel, handle_height)
+    }
+
+    /// Bounds of the handle as drawn, widened while hovered or dragged
+    pub fn handle_bounds(&self) -> (f32, f32, f32, f32) {
+        let (handle_y, handle_height) = self.handle_geometry();
+        let width = self.width + HOVER_WIDEN * self.widen;
+        (self.x + self.width - width, handle_y, width, handle_height)
+    }
+
+    /// The part of the scrollbar under a point, if there's anything to scroll
+    fn part_at(&self, x: f32, y: f32) -> Option<ScrollbarPart> {
+        if !self.is_scrollable() || !self.contains_point(x, y) {
+            return None;
+        }
+        let (handle_y, handle_height) = self.handle_geometry();
+        Some(if y < handle_y {
+            ScrollbarPart::TrackAbove
+        } else if y <= handle_y + handle_height {
+            ScrollbarPart::Handle
+        } else {
+            ScrollbarPart::TrackBelow
+        })
+    }
+
+    /// Whether the bar is drawn widened, or on its way there
+    fn wants_widening(&self) -> bool {
+        self.hovered || self.is_active()
+    }
+
+    /// Scroll so the top of the handle sits at `handle_y`
+    fn move_handle_to(&mut self, handle_y: f32) {
+        let (_, handle_height) = self.handle_geometry();
+        let travel = self.height - handle_height;
+        if travel > 0.0 {
+            let progress = ((handle_y - self.y) / travel).clamp(0.0, 1.0);
+            self.offset = progress * self.max_scroll;
+        }
+    }
+
+    /// Scroll one viewport in `direction`, stopping once the handle reaches the pointer
+    fn page(&mut self, direction: f32, pointer_y: f32) {
+        let (handle_y, handle_height) = self.handle_geometry();
+        let reached = if direction < 0.0 { pointer_y >= handle_y } else { pointer_y <= handle_y + handle_height };
+        if !reached {
+            self.set_offset(self.offset + direction * self.viewport);
+        }
+    }
+
+    /// Handle mouse press; returns true if the scrollbar took it
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        let Some(part) = self.part_at(x, y) else {
+            return false;
+        };
+
+        let direction = match part {
+            ScrollbarPart::Handle => {
+                let (handle_y, _) = self.handle_geometry();
+                self.drag = ScrollbarDrag::Handle { grab_offset: y - handle_y };
+                return true;
+            }
+            ScrollbarPart::TrackAbove => -1.0,
+            ScrollbarPart::TrackBelow => 1.0,
+        };
+        self.page(direction, y);
+        self.drag = ScrollbarDrag::Paging { direction, pointer_y: y, repeat_in: PAGE_REPEAT_DELAY };
+        true
+    }
+
+    /// Handle pointer movement; returns true if the scrollbar owns the pointer
+    ///
+    /// Moving over the bar without a button held only widens it.
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let hovered = self.part_at(x, y).is_some();
+        damage::set(&mut self.hovered, hovered);
+        match &mut self.drag {
+            ScrollbarDrag::Idle => false,
+            ScrollbarDrag::Handle { grab_offset } => {
+                let handle_y = y - *grab_offset;
+                self.move_handle_to(handle_y);
+                true
+            }
+            ScrollbarDrag::Paging { pointer_y, .. } => {
+                *pointer_y = y;
+                true
+            }
+        }
+    }
+
+    /// Handle mouse release, ending any drag or paging
+    pub fn handle_mouse_up(&mut self) {
+        self.drag = ScrollbarDrag::Idle;
+    }
+}
+
+impl Widget for Scrollbar {
+    fn update(&mut self, delta_time: f32) {
+        let step = delta_time / WIDEN_DURATION;
+        self.widen = if self.wants_widening() { (self.widen + step).min(1.0) } else { (self.widen - step).max(0.0) };
+
+        // Keep paging while the track is held
+        if let ScrollbarDrag::Paging { direction, pointer_y, repeat_in } = self.drag {
+            let repeat_in = repeat_in - delta_time;
+            if repeat_in <= 0.0 {
+                self.page(direction, pointer_y);
+                self.drag = ScrollbarDrag::Paging { direction, pointer_y, repeat_in: PAGE_REPEAT_INTERVAL };
+            } else {
+                self.drag = ScrollbarDrag::Paging { direction, pointer_y, repeat_in };
+            }
+        }
+    }
+
+    fn needs_update(&self) -> bool {
+        let widening = if self.wants_widening() { self.widen < 1.0 } else { self.widen > 0.0 };
+        widening || matches!(self.drag, ScrollbarDrag::Paging { .. })
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_scrollable() {
+            return;
+        }
+
+        // Widening grows the bar to the left, away from the window edge
+        let (x, handle_y, width, handle_height) = self.handle_bounds();
+        ctx.draw_rect(
+            x, self.y,
+            width, self.height,
+            self.theme.get_scrollbar_bg_color(),
+        );
+
+        let handle_color = if self.is_dragging() {
+            self.theme.cyan()
+        } else {
+            self.theme.get_scrollbar_handle_color()
+        };
+        ctx.draw_rect(
+            x, handle_y,
+            width, handle_height,
+            handle_color,
+        );
+
+        // A neon glow around the handle, fading in as it widens
+        if self.widen > 0.0 {
+            let [r, g, b, _] = self.theme.cyan();
+            for (spread, alpha) in [(3.0, 0.15), (1.5, 0.35)] {
+                ctx.draw_outline(
+                    x - spread, handle_y - spread,
+                    width + spread * 2.0, handle_height + spread * 2.0,
+                    1.5,
+                    [r, g, b, alpha * self.widen],
+                );
+            }
+        }
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if self.is_dragging() {
+            return Some(CursorIcon::Grabbing);
+        }
+        self.part_at(x, y).map(|part| match part {
+            ScrollbarPart::Handle => CursorIcon::Grab,
+            ScrollbarPart::TrackAbove | ScrollbarPart::TrackBelow => CursorIcon::Default,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::testing::UiDriver;
+
+    #[test]
+    fn test_scrollbar_drags_pages_and_widens() {
+        let (mut driver, _) = UiDriver::with_numbered_tasks(40);
+        let handle = |driver: &UiDriver| driver.widget().scrollbar().handle_bounds();
+        let (x, y, width, height) = handle(&driver);
+        let (center_x, center_y) = (x + width / 2.0, y + height / 2.0);
+
+        // Hovering widens the bar to the left; moving off narrows it again
+        driver.move_to(center_x, center_y);
+        for _ in 0..10 {
+            driver.tick(0.016);
+        }
+        let (wide_x, _, wide_width, _) = handle(&driver);
+        assert!(wide_width > width && wide_x < x);
+        assert_eq!(wide_x + wide_width, x + width);
+        driver.move_to(center_x - 200.0, center_y);
+        for _ in 0..10 {
+            driver.tick(0.016);
+        }
+        assert_eq!(handle(&driver).2, width);
+
+        // Dragging the handle scrolls right away, without clicking the rows under the release
+        driver.drag((center_x, center_y), (center_x, center_y + 50.0));
+        let (offset, target) = driver.widget().scroll_position();
+        assert!(offset > 0.0);
+        assert_eq!(offset, target);
+        assert!((handle(&driver).1 - (y + 50.0)).abs() < 0.5);
+        assert!(driver.take_events().is_empty());
+
+        // Clicking the track below the handle pages down
+        let (_, handle_y, _, handle_height) = handle(&driver);
+        driver.click_at(center_x, handle_y + handle_height + 20.0);
+        assert!(driver.widget().scroll_position().0 > offset);
+        assert!(driver.take_events().is_empty());
+    }
+}
@@ -0,0 +1,507 @@
+use crate::config::{Config, PresentMode, RedrawMode, ARCHIVE_AFTER_PRESETS, AUTOSAVE_PRESETS, MAX_FPS_PRESETS, SCROLL_SPEED_PRESETS};
+use crate::i18n::{self, tr, tr_args, tr_count};
+use crate::ui::{damage, RenderContext, Widget, WidgetInfo, Button, CyberpunkTheme};
+use crate::ui::widgets::{FxEvent, FxPanel};
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+const HEADER_HEIGHT: f32 = 40.0;
+const ROW_HEIGHT: f32 = 40.0;
+const FOOTER_HEIGHT: f32 = 76.0;
+/// Width of the column of settings rows, left of the FX panel
+const ROWS_WIDTH: f32 = 480.0;
+
+/// Result of interacting with the SettingsPanel
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsEvent {
+    /// A setting was changed and should be applied right away
+    Changed(Config),
+    /// The user asked to write the settings to the config file
+    Saved(Config),
+    /// The panel was closed
+    Closed,
+}
+
+/// A single editable line in the panel
+#[derive(Debug, Clone, Copy)]
+enum SettingRow {
+    PresentMode,
+    MaxFps,
+    Redraw,
+    Bloom,
+    Glow,
+    Autosave,
+    ScrollSpeed,
+    NaturalScrolling,
+    FocusFollowsMouse,
+    AutoCompleteParents,
+    ArchiveAfter,
+}
+
+const ROWS: [SettingRow; 11] = [
+    SettingRow::PresentMode,
+    SettingRow::MaxFps,
+    SettingRow::Redraw,
+    SettingRow::Bloom,
+    SettingRow::Glow,
+    SettingRow::Autosave,
+    SettingRow::ScrollSpeed,
+    SettingRow::NaturalScrolling,
+    SettingRow::FocusFollowsMouse,
+    SettingRow::AutoCompleteParents,
+    SettingRow::ArchiveAfter,
+];
+
+/// The preset after `current`, wrapping around to the first
+fn next_preset<T: Copy + PartialOrd>(presets: &[T], current: T) -> T {
+    presets
+        .iter()
+        .copied()
+        .find(|&preset| preset > current)
+        .unwrap_or(presets[0])
+}
+
+impl SettingRow {
+    /// Message id of the row's label
+    fn label_id(self) -> &'static str {
+        match self {
+            SettingRow::PresentMode => "settings-present-mode",
+            SettingRow::MaxFps => "settings-max-fps",
+            SettingRow::Redraw => "settings-redraw",
+            SettingRow::Bloom => "settings-bloom",
+            SettingRow::Glow => "settings-glow",
+            SettingRow::Autosave => "settings-autosave",
+            SettingRow::ScrollSpeed => "settings-scroll-speed",
+            SettingRow::NaturalScrolling => "settings-natural-scrolling",
+            SettingRow::FocusFollowsMouse => "settings-focus-follows-mouse",
+            SettingRow::AutoCompleteParents => "settings-auto-complete-parents",
+            SettingRow::ArchiveAfter => "settings-archive-after",
+        }
+    }
+
+    fn value(self, config: &Config) -> String {
+        let on_off = |enabled: bool| tr(if enabled { "settings-on" } else { "settings-off" });
+        match self {
+            SettingRow::PresentMode => present_mode_name(config.display.present_mode),
+            SettingRow::MaxFps => match config.display.max_fps {
+                0 => tr("settings-off"),
+                fps => tr_args("settings-max-fps-value", &[("fps", &fps.to_string())]),
+            },
+            SettingRow::Redraw => tr(match config.display.redraw {
+                RedrawMode::OnDemand => "settings-redraw-on-demand",
+                RedrawMode::Continuous => "settings-redraw-continuous",
+            }),
+            SettingRow::Bloom => on_off(config.effects.bloom),
+            SettingRow::Glow => on_off(config.effects.glow),
+            SettingRow::Autosave => {
+                let secs = config.autosave_interval_secs;
+                let decimals = if secs.fract() == 0.0 { 0 } else { 1 };
+                tr_args("settings-autosave-value", &[("seconds", &i18n::format_number(secs as f64, decimals))])
+            }
+            SettingRow::ScrollSpeed => {
+                let factor = config.input.scroll_speed;
+                let decimals = if factor.fract() == 0.0 { 0 } else { 1 };
+                tr_args("settings-scroll-speed-value", &[("factor", &i18n::format_number(factor as f64, decimals))])
+            }
+            SettingRow::NaturalScrolling => on_off(config.input.natural_scrolling),
+            SettingRow::FocusFollowsMouse => on_off(config.input.focus_follows_mouse),
+            SettingRow::AutoCompleteParents => on_off(config.auto_complete_parents),
+            SettingRow::ArchiveAfter => match config.archive_after_days {
+                0 => tr("settings-off"),
+                days => tr_count("settings-archive-after-value", "days", days as usize),
+            },
+        }
+    }
+
+    /// Toggle the setting, or step to the next preset for numeric values
+    fn advance(self, config: &mut Config) {
+        match self {
+            SettingRow::PresentMode => config.display.present_mode = config.display.present_mode.next(),
+            SettingRow::MaxFps => config.display.max_fps = next_preset(MAX_FPS_PRESETS, config.display.max_fps),
+            SettingRow::Redraw => {
+                config.display.redraw = match config.display.redraw {
+                    RedrawMode::OnDemand => RedrawMode::Continuous,
+                    RedrawMode::Continuous => RedrawMode::OnDemand,
+                };
+            }
+            SettingRow::Bloom => config.effects.bloom = !config.effects.bloom,
+            SettingRow::Glow => config.effects.glow = !config.effects.glow,
+            SettingRow::Autosave => {
+                config.autosave_interval_secs = next_preset(AUTOSAVE_PRESETS, config.autosave_interval_secs);
+            }
+            SettingRow::ScrollSpeed => {
+                config.input.scroll_speed = next_preset(SCROLL_SPEED_PRESETS, config.input.scroll_speed);
+            }
+            SettingRow::NaturalScrolling => config.input.natural_scrolling = !config.input.natural_scrolling,
+            SettingRow::FocusFollowsMouse => config.input.focus_follows_mouse = !config.input.focus_follows_mouse,
+            SettingRow::AutoCompleteParents => config.auto_complete_parents = !config.auto_complete_parents,
+            SettingRow::ArchiveAfter => {
+                config.archive_after_days = next_preset(ARCHIVE_AFTER_PRESETS, config.archive_after_days);
+            }
+        }
+    }
+}
+
+/// Translated name of a present mode, as shown in the panel and its toasts
+pub fn present_mode_name(mode: PresentMode) -> String {
+    tr(&format!("settings-present-mode-{}", mode.name()))
+}
+
+/// Convert [f32; 4] RGBA values to wgpu::Color
+fn to_color(rgba: [f32; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: rgba[0] as f64,
+        g: rgba[1] as f64,
+        b: rgba[2] as f64,
+        a: rgba[3] as f64,
+    }
+}
+
+/// Modal panel for editing the user configuration
+///
+/// The effect strengths have sliders in a collapsible FX panel along the right
+/// edge; like the rows, they change the config as they move.
+pub struct SettingsPanel {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    config: Config,
+    hovered_row: Option<usize>,
+    fx_panel: FxPanel,
+    screen_width: f32,
+    screen_height: f32,
+    save_button: Button,
+    close_button: Button,
+    is_visible: bool,
+    theme: CyberpunkTheme,
+}
+
+impl SettingsPanel {
+    /// Create a hidden panel; call `show` to open it
+    pub fn new() -> Self {
+        let theme = CyberpunkTheme::new();
+
+        let save_button = Button::new(0.0, 0.0, 120.0, 36.0, tr("settings-save"))
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.neon_pink()));
+        let close_button = Button::new(0.0, 0.0, 120.0, 36.0, tr("settings-close"))
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.filter_button_bg()));
+
+        let height = HEADER_HEIGHT + 20.0 + ROW_HEIGHT * ROWS.len() as f32 + FOOTER_HEIGHT;
+        let fx_panel = FxPanel::new(ROWS_WIDTH, HEADER_HEIGHT, height - HEADER_HEIGHT - FOOTER_HEIGHT);
+
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: ROWS_WIDTH + fx_panel.width(),
+            height,
+            config: Config::default(),
+            hovered_row: None,
+            fx_panel,
+            screen_width: 0.0,
+            screen_height: 0.0,
+            save_button,
+            close_button,
+            is_visible: false,
+            theme,
+        }
+    }
+
+    /// Check if the panel is currently shown
+    pub fn is_visible(&self) -> bool {
+        self.is_visible
+    }
+
+    /// Open the panel with a copy of the current config
+    pub fn show(&mut self, config: &Config, screen_width: f32, screen_height: f32) {
+        self.config = config.clone();
+        self.fx_panel.load(&config.effects);
+        self.is_visible = true;
+        self.layout(screen_width, screen_height);
+    }
+
+    /// Hide the panel
+    pub fn hide(&mut self) {
+        self.is_visible = false;
+        self.hovered_row = None;
+        self.fx_panel.handle_mouse_up();
+    }
+
+    /// Re-center the panel, e.g. after a window resize
+    pub fn layout(&mut self, screen_width: f32, screen_height: f32) {
+        self.screen_width = screen_width;
+        self.screen_height = screen_height;
+        let fx_width = self.fx_panel.width();
+        self.width = (screen_width - fx_width).clamp(0.0, ROWS_WIDTH) + fx_width;
+        self.x = (screen_width - self.width) / 2.0;
+        self.y = (screen_height - self.height) / 2.0;
+        self.fx_panel.set_position(self.x + self.rows_width(), self.y + HEADER_HEIGHT);
+
+        let button_y = self.y + self.height - 56.0;
+        self.close_button.set_position(self.x + self.width - 260.0, button_y);
+        self.save_button.set_position(self.x + self.width - 130.0, button_y);
+    }
+
+    /// Width of the settings rows, which the FX panel doesn't cover
+    fn rows_width(&self) -> f32 {
+        self.width - self.fx_panel.width()
+    }
+
+    /// Top edge of a settings row
+    fn row_y(&self, index: usize) -> f32 {
+        self.y + HEADER_HEIGHT + 20.0 + index as f32 * ROW_HEIGHT
+    }
+
+    /// Find the settings row under a point
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        if x < self.x || x > self.x + self.rows_width() {
+            return None;
+        }
+        (0..ROWS.len()).find(|&index| {
+            let row_y = self.row_y(index);
+            y >= row_y && y < row_y + ROW_HEIGHT
+        })
+    }
+
+    /// Turn an FX panel event into the panel's own
+    fn fx_event(&mut self, event: Option<FxEvent>) -> Option<SettingsEvent> {
+        match event? {
+            FxEvent::Changed => Some(SettingsEvent::Changed(self.config.clone())),
+            FxEvent::Toggled(_) => {
+                self.layout(self.screen_width, self.screen_height);
+                None
+            }
+        }
+    }
+
+    /// Handle mouse movement for hover states and dragging the FX sliders
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) -> Option<SettingsEvent> {
+        let hovered_row = self.row_at(x, y);
+        damage::set(&mut self.hovered_row, hovered_row);
+        self.save_button.handle_mouse_move(x, y);
+        self.close_button.handle_mouse_move(x, y);
+        let event = self.fx_panel.handle_mouse_move(x, y, &mut self.config.effects);
+        self.fx_event(event)
+    }
+
+    /// Handle mouse button press, returning a change if it grabbed an FX slider
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> Option<SettingsEvent> {
+        if !self.is_visible {
+            return None;
+        }
+        self.save_button.handle_mouse_down(x, y);
+        self.close_button.handle_mouse_down(x, y);
+        let event = self.fx_panel.handle_mouse_down(x, y, &mut self.config.effects);
+        self.fx_event(event)
+    }
+
+    /// Handle mouse button release, returning what the click did
+    pub fn handle_mouse_up(&mut self, x: f32, y: f32) -> Option<SettingsEvent> {
+        if !self.is_visible {
+            return None;
+        }
+
+        // Letting go of a slider doesn't click whatever the drag ended on
+        let event = if self.fx_panel.is_dragging() {
+            self.fx_panel.handle_mouse_up();
+            None
+        } else if self.save_button.contains_point(x, y) {
+            Some(SettingsEvent::Saved(self.config.clone()))
+        } else if self.close_button.contains_point(x, y) {
+            self.hide();
+            Some(SettingsEvent::Closed)
+        } else if let Some(index) = self.row_at(x, y) {
+            ROWS[index].advance(&mut self.config);
+            Some(SettingsEvent::Changed(self.config.clone()))
+        } else {
+            None
+        };
+
+        self.save_button.handle_mouse_up(x, y);
+        self.close_button.handle_mouse_up(x, y);
+        event
+    }
+
+    /// Handle keyboard input: Enter saves, Escape closes
+    pub fn handle_key_press(&mut self, key: KeyCode) -> Option<SettingsEvent> {
+        if !self.is_visible {
+            return None;
+        }
+
+        match key {
+            KeyCode::Enter => Some(SettingsEvent::Saved(self.config.clone())),
+            KeyCode::Escape => {
+                self.hide();
+                Some(SettingsEvent::Closed)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for SettingsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for SettingsPanel {
+    fn update(&mut self, delta_time: f32) {
+        for button in [&mut self.save_button, &mut self.close_button] {
+            if button.needs_update() {
+                button.update(delta_time);
+            }
+        }
+    }
+
+    fn needs_update(&self) -> bool {
+        self.is_visible && (self.save_button.needs_update() || self.close_button.needs_update())
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        if self.is_visible {
+            out.push(WidgetInfo::of(self, depth));
+            self.fx_panel.inspect(depth + 1, out);
+            self.save_button.inspect(depth + 1, out);
+            self.close_button.inspect(depth + 1, out);
+        }
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_visible {
+            return;
+        }
+
+        ctx.draw_rect(0.0, 0.0, ctx.width, ctx.height, self.theme.get_modal_overlay_color());
+        ctx.draw_modal_frame(&self.theme, self.x, self.y, self.width, self.height, HEADER_HEIGHT);
+
+        ctx.draw_label(
+            "settings-title",
+            self.x + 20.0, self.y + 8.0,
+            24.0,
+            self.theme.get_modal_text_color(),
+        );
+
+        let rows_width = self.rows_width();
+        for (index, row) in ROWS.iter().enumerate() {
+            let row_y = self.row_y(index);
+            if self.hovered_row == Some(index) {
+                ctx.draw_rect(self.x, row_y, rows_width, ROW_HEIGHT, self.theme.item_hover_bg());
+            }
+
+            ctx.draw_label(
+                row.label_id(),
+                self.x + 20.0, row_y + 10.0,
+                self.theme.text_size(),
+                self.theme.get_modal_text_color(),
+            );
+
+            let value = row.value(&self.config);
+            let value_width = ctx.measure_text(&value, self.theme.text_size()).width;
+            ctx.draw_text(
+                &value,
+                self.x + rows_width - 20.0 - value_width, row_y + 10.0,
+                self.theme.text_size(),
+                self.theme.cyan(),
+            );
+        }
+
+        self.fx_panel.render(ctx);
+        self.close_button.render(ctx);
+        self.save_button.render(ctx);
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        let dx = x - self.x;
+        let dy = y - self.y;
+        self.x = x;
+        self.y = y;
+
+        let (save_x, save_y) = self.save_button.position();
+        self.save_button.set_position(save_x + dx, save_y + dy);
+        let (close_x, close_y) = self.close_button.position();
+        self.close_button.set_position(close_x + dx, close_y + dy);
+        let (fx_x, fx_y) = self.fx_panel.position();
+        self.fx_panel.set_position(fx_x + dx, fx_y + dy);
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if !self.is_visible {
+            return None;
+        }
+        if self.row_at(x, y).is_some() {
+            return Some(CursorIcon::Pointer);
+        }
+        self.fx_panel
+            .cursor_at(x, y)
+            .or_else(|| self.save_button.cursor_at(x, y))
+            .or_else(|| self.close_button.cursor_at(x, y))
+            .or(Some(CursorIcon::Default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::VIEWPORT;
+
+    #[test]
+    fn test_fx_sliders_change_effects_live() {
+        let config = Config::default();
+        let mut panel = SettingsPanel::new();
+        let (width, height) = VIEWPORT;
+        panel.show(&config, width, height);
+
+        // Collapsed, the FX header sits right of the 480px rows; expanding widens the panel
+        assert_eq!(panel.dimensions().0, 480.0 + 64.0);
+        assert_eq!(panel.handle_mouse_down(740.0, 170.0), None);
+        assert_eq!(panel.dimensions(), (480.0 + 280.0, 576.0));
+        let (panel_x, panel_y) = panel.position();
+        let slider_x = panel_x + 480.0 + 16.0;
+        // Below the panel's header and the FX header, 48px rows end in their slider
+        let slider_y = |row: f32| panel_y + 40.0 + 40.0 + 48.0 * row + 33.0;
+
+        // Pressing halfway along the threshold slider sets it there, dragging follows
+        let Some(SettingsEvent::Changed(changed)) = panel.handle_mouse_down(slider_x + 124.0, slider_y(0.0)) else {
+            panic!("pressing a slider should change the config");
+        };
+        assert_eq!(changed.effects.bloom_threshold, 0.5);
+        let Some(SettingsEvent::Changed(changed)) = panel.handle_mouse_move(slider_x + 500.0, slider_y(0.0)) else {
+            panic!("dragging a slider should change the config");
+        };
+        assert_eq!(changed.effects.bloom_threshold, 1.0);
+        assert_eq!(changed.effects.bloom_intensity, config.effects.bloom_intensity);
+
+        // Letting go over a settings row doesn't click it
+        assert_eq!(panel.handle_mouse_up(panel_x + 20.0, panel_y + 70.0), None);
+        assert_eq!(panel.handle_mouse_move(slider_x, slider_y(0.0)), None);
+
+        // The glow hue starts at the theme's cyan; the left end is red
+        let Some(SettingsEvent::Changed(changed)) = panel.handle_mouse_down(slider_x, slider_y(3.0)) else {
+            panic!("pressing a slider should change the config");
+        };
+        assert_eq!(changed.effects.glow_color(), [1.0, 0.0, 0.0, 1.0]);
+        panel.handle_mouse_up(slider_x, slider_y(3.0));
+
+        let Some(SettingsEvent::Saved(saved)) = panel.handle_key_press(KeyCode::Enter) else {
+            panic!("Enter should save");
+        };
+        assert_eq!((saved.effects.bloom_threshold, saved.effects.glow_hue), (1.0, 0.0));
+        assert_eq!(saved.display.present_mode, PresentMode::Fifo);
+    }
+}
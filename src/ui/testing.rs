@@ -0,0 +1,312 @@
+// Headless UI test driver
+//
+// Drives a `TodoListWidget` with synthetic mouse and keyboard input the way the
+// event loop in main.rs does, without a window or GPU. Input handling never
+// touches the `RenderContext` (it wraps a wgpu queue and glyph brush), so the
+// driver doesn't render at all and passes the viewport size where the widget
+// asks for it. Tests then assert on the todo list and on the emitted events.
+
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use winit::keyboard::{Key, KeyCode, ModifiersState};
+
+use crate::config::{Config, InputConfig};
+use crate::core::prelude::{Dispatcher, TodoItem, TodoList};
+use crate::ui::input::{named_keycode, Action, ClickTracker, InputEvent, Keymap, TextEvent, TimedInput};
+use crate::ui::{Button, Clipboard, TodoListWidget, UiEvent, Widget};
+
+/// Window size the driver lays the list out in, like a freshly opened window
+pub const VIEWPORT: (f32, f32) = (1024.0, 768.0);
+
+/// A todo list widget with synthetic input
+pub struct UiDriver {
+    widget: TodoListWidget,
+    todo_list: Arc<Mutex<TodoList>>,
+    /// The default key bindings, which the app checks before passing keys on
+    keymap: Keymap,
+    /// Stands in for the system clipboard, which tests leave alone
+    clipboard: Clipboard,
+    events: Vec<UiEvent>,
+}
+
+impl UiDriver {
+    /// Show `todo_list` where main.rs puts it with the default layout, leaving out the sidebar
+    pub fn new(todo_list: TodoList) -> Self {
+        let todo_list = Arc::new(Mutex::new(todo_list));
+        let (width, height) = VIEWPORT;
+        let widget = TodoListWidget::new(50.0, 100.0, width - 100.0, height - 200.0, Dispatcher::new(todo_list.clone()));
+        Self {
+            widget,
+            todo_list,
+            keymap: Config::default().keymap(),
+            clipboard: Clipboard::in_memory(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Show a list of root tasks, returning their ids in order
+    pub fn with_tasks(titles: &[&str]) -> (Self, Vec<Uuid>) {
+        let mut todo_list = TodoList::new("Test");
+        let ids = titles.iter().map(|title| todo_list.create_item(title)).collect();
+        (Self::new(todo_list), ids)
+    }
+
+    /// Show `count` root tasks named "Task 0", "Task 1" and so on, enough to scroll
+    pub fn with_numbered_tasks(count: usize) -> (Self, Vec<Uuid>) {
+        let titles: Vec<String> = (0..count).map(|n| format!("Task {}", n)).collect();
+        let titles: Vec<&str> = titles.iter().map(String::as_str).collect();
+        Self::with_tasks(&titles)
+    }
+
+    pub fn widget(&self) -> &TodoListWidget {
+        &self.widget
+    }
+
+    pub fn widget_mut(&mut self) -> &mut TodoListWidget {
+        &mut self.widget
+    }
+
+    /// The list the widget edits, shared the way the app shares it
+    pub fn todo_list(&self) -> &Arc<Mutex<TodoList>> {
+        &self.todo_list
+    }
+
+    /// The stand-in clipboard that copy, cut and paste use
+    pub fn clipboard(&mut self) -> &mut Clipboard {
+        &mut self.clipboard
+    }
+
+    /// Bind keys differently, as a user's config would
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    /// Current state of a task in the list
+    pub fn task(&self, id: Uuid) -> Option<TodoItem> {
+        self.todo_list.lock().unwrap().get_item(id).cloned()
+    }
+
+    /// Move the pointer
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        self.widget.handle_mouse_move(x, y);
+        self.collect_events();
+    }
+
+    /// Press and release the left button at a point
+    pub fn click_at(&mut self, x: f32, y: f32) {
+        self.press_and_release(x, y, 1);
+    }
+
+    /// Press the right button at a point
+    pub fn right_click_at(&mut self, x: f32, y: f32) {
+        let (width, height) = VIEWPORT;
+        self.widget.handle_mouse_move(x, y);
+        self.widget.handle_right_click(x, y, width, height);
+        self.collect_events();
+    }
+
+    /// Press the left button at `from`, move to `to` and release there
+    pub fn drag(&mut self, from: (f32, f32), to: (f32, f32)) {
+        let (width, height) = VIEWPORT;
+        self.widget.handle_mouse_move(from.0, from.1);
+        self.widget.handle_mouse_down(from.0, from.1, 1, width, height);
+        self.widget.handle_mouse_move(to.0, to.1);
+        self.widget.handle_mouse_up(to.0, to.1);
+        self.collect_events();
+    }
+
+    /// Click twice at a point, as the ClickTracker reports a double click
+    pub fn double_click_at(&mut self, x: f32, y: f32) {
+        self.press_and_release(x, y, 1);
+        self.press_and_release(x, y, 2);
+    }
+
+    /// Click an item's row, past its checkbox, while holding modifiers
+    pub fn click_row_with(&mut self, modifiers: ModifiersState, id: Uuid) {
+        let (x, y) = self.widget.item_widget(id).expect("item is not shown").position();
+        self.widget.set_modifiers(modifiers);
+        self.click_at(x + 100.0, y + 10.0);
+        self.widget.set_modifiers(ModifiersState::empty());
+    }
+
+    /// Click an item's checkbox
+    pub fn click_checkbox(&mut self, id: Uuid) {
+        let (x, y) = self.item_button(id, |widget| &widget.checkbox_button);
+        self.click_at(x, y);
+    }
+
+    /// Click the ▶/▼ toggle of an item with children
+    pub fn click_collapse_toggle(&mut self, id: Uuid) {
+        let widget = self.widget.item_widget(id).expect("item is not shown");
+        let (x, y, width, height) = widget.disclosure_bounds().expect("item has no children");
+        self.click_at(x + width / 2.0, y + height / 2.0);
+    }
+
+    /// Click an item's edit button
+    pub fn click_edit(&mut self, id: Uuid) {
+        let (x, y) = self.item_button(id, |widget| &widget.edit_button);
+        self.click_at(x, y);
+    }
+
+    /// Click an item's delete button
+    pub fn click_delete(&mut self, id: Uuid) {
+        let (x, y) = self.item_button(id, |widget| &widget.delete_button);
+        self.click_at(x, y);
+    }
+
+    /// Type text into the focused field
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.widget.handle_char_input(c);
+        }
+        self.collect_events();
+    }
+
+    /// Press a key without modifiers
+    pub fn press(&mut self, key: KeyCode) {
+        self.press_with(ModifiersState::empty(), key);
+    }
+
+    /// Press a key while holding modifiers
+    pub fn press_with(&mut self, modifiers: ModifiersState, key: KeyCode) {
+        self.key_down(modifiers, key);
+        self.collect_events();
+    }
+
+    /// Run the key's bound action, or hand the key to the list if there's none or it didn't apply
+    fn key_down(&mut self, modifiers: ModifiersState, key: KeyCode) {
+        let handled = self.keymap
+            .action(modifiers, key)
+            .is_some_and(|action| match action {
+                Action::Copy | Action::Cut | Action::Paste => self.widget.handle_clipboard(action, &mut self.clipboard),
+                _ => self.widget.handle_action(action),
+            });
+        if !handled {
+            self.widget.handle_key_press(key);
+        }
+    }
+
+    /// Advance time, updating the widget if it has work to do
+    pub fn tick(&mut self, delta_time: f32) {
+        if self.widget.needs_update() {
+            self.widget.update(delta_time);
+        }
+        self.collect_events();
+    }
+
+    /// Feed a recording to the list the way the app does, ticking once per frame
+    ///
+    /// Only input the list itself handles is replayed; global shortcuts and
+    /// modals belong to the app. Click counts use the recorded times.
+    pub fn replay(&mut self, inputs: &[TimedInput]) {
+        let (width, height) = VIEWPORT;
+        let mut clicks = ClickTracker::new();
+        let mut pointer = (0.0, 0.0);
+        let mut frame = inputs.first().map_or(0, |timed| timed.frame);
+        for timed in inputs {
+            while frame < timed.frame {
+                self.tick(0.016);
+                frame += 1;
+            }
+            match timed.input {
+                InputEvent::CursorMoved { x, y } => {
+                    pointer = (x, y);
+                    self.widget.handle_mouse_move(x, y);
+                }
+                InputEvent::MouseButton { middle: false, pressed: true } => {
+                    let click_count = clicks.press(pointer.0, pointer.1, timed.time);
+                    self.widget.handle_mouse_down(pointer.0, pointer.1, click_count, width, height);
+                }
+                InputEvent::MouseButton { middle: false, pressed: false } => {
+                    self.widget.handle_mouse_up(pointer.0, pointer.1);
+                }
+                InputEvent::RightButton { pressed: true } => {
+                    self.widget.handle_right_click(pointer.0, pointer.1, width, height);
+                }
+                InputEvent::MouseWheel { x, y, pixels } => {
+                    let delta = InputEvent::scroll_delta(x, y, pixels);
+                    self.widget.handle_mouse_wheel(InputConfig::default().scroll_pixels(&delta));
+                }
+                InputEvent::Key { ref key, pressed: true, ref text, .. } => match key.to_key() {
+                    Key::Character(_) => {
+                        if let Some(text) = text {
+                            self.widget.handle_text(&TextEvent::Insert(text.clone()));
+                        }
+                    }
+                    key => {
+                        if let Some(code) = named_keycode(&key) {
+                            self.key_down(ModifiersState::empty(), code);
+                        }
+                    }
+                },
+                InputEvent::Text(ref event) => self.widget.handle_text(event),
+                InputEvent::Modifiers { shift, control, alt, logo } => {
+                    self.widget.set_modifiers(InputEvent::modifiers(shift, control, alt, logo));
+                }
+                InputEvent::Focused(false) => clicks.reset(),
+                _ => {}
+            }
+            self.collect_events();
+        }
+        self.tick(0.016);
+    }
+
+    /// Events emitted since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<UiEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn press_and_release(&mut self, x: f32, y: f32, click_count: u32) {
+        let (width, height) = VIEWPORT;
+        self.widget.handle_mouse_move(x, y);
+        self.widget.handle_mouse_down(x, y, click_count, width, height);
+        self.widget.handle_mouse_up(x, y);
+        self.collect_events();
+    }
+
+    /// Center of one of a shown item's buttons
+    fn item_button(&self, id: Uuid, button: impl Fn(&crate::ui::TodoItemWidget) -> &Button) -> (f32, f32) {
+        let widget = self.widget.item_widget(id).expect("item is not shown");
+        let (x, y) = button(widget).position();
+        let (width, height) = button(widget).dimensions();
+        (x + width / 2.0, y + height / 2.0)
+    }
+
+    fn collect_events(&mut self) {
+        self.events.extend(self.widget.take_events());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbered_tasks_show_in_order() {
+        let (driver, ids) = UiDriver::with_numbered_tasks(3);
+        assert_eq!(driver.widget().shown_items(), ids);
+        assert_eq!(driver.task(ids[2]).unwrap().title(), "Task 2");
+    }
+
+    #[test]
+    fn test_replaying_a_recording() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Alpha", "Beta"]);
+        let (x, y) = driver.widget().item_widget(ids[1]).unwrap().position();
+        let named = |name: &str| crate::ui::input::KeyName::Named(name.to_string());
+        let timed = |frame, time, input| TimedInput { frame, time, input };
+
+        // Two presses in quick succession make a double click; keys come later
+        let recording = vec![
+            timed(0, 0.0, InputEvent::CursorMoved { x: x + 200.0, y: y + 10.0 }),
+            timed(1, 0.1, InputEvent::MouseButton { middle: false, pressed: true }),
+            timed(1, 0.15, InputEvent::MouseButton { middle: false, pressed: false }),
+            timed(2, 0.2, InputEvent::MouseButton { middle: false, pressed: true }),
+            timed(2, 0.25, InputEvent::MouseButton { middle: false, pressed: false }),
+            timed(60, 1.0, InputEvent::Key { key: named("ArrowUp"), pressed: true, repeat: false, text: None }),
+        ];
+        driver.replay(&recording);
+
+        assert!(driver.widget().item_widget(ids[1]).unwrap().is_expanded());
+        assert_eq!(driver.widget().selected_item(), Some(driver.widget().shown_items()[0]));
+    }
+}
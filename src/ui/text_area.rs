@@ -0,0 +1,687 @@
+use wgpu::Color;
+use crate::ui::{Clipboard, Quad, RenderContext, Widget, WidgetInfo};
+use crate::ui::edit_buffer::{previous_boundary, EditBuffer};
+use crate::ui::input::{Action, TextEvent};
+use crate::ui::text_metrics::{text_width, wrap};
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+/// Padding around the text, inside the field's bounds
+const TEXT_PADDING: f32 = 5.0;
+
+/// Size of the text, and of the placeholder
+const TEXT_SIZE: f32 = 16.0;
+
+/// Distance between the tops of two lines
+const LINE_HEIGHT: f32 = 20.0;
+
+/// Width of the area an input method places its candidates next to
+const CARET_WIDTH: f32 = 2.0;
+
+/// Width of the bar showing which part of text taller than the field is in view
+const SCROLL_INDICATOR_WIDTH: f32 = 3.0;
+
+/// Text as the field holds it: line breaks as `\n`, tabs as spaces and no
+/// other control characters
+fn clean(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .chars()
+        .map(|c| match c {
+            '\r' => '\n',
+            '\t' => ' ',
+            c => c,
+        })
+        .filter(|&c| c == '\n' || !c.is_control())
+        .collect()
+}
+
+/// A multi-line text field
+///
+/// Lines longer than the field wrap at its width, and text taller than it
+/// scrolls to keep the cursor in view. Enter starts a new line, so owners
+/// submit with another key.
+pub struct TextArea {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    buffer: EditBuffer,
+    placeholder: String,
+    text_color: Color,
+    placeholder_color: Color,
+    is_focused: bool,
+    /// Distance from the start of the line that Up and Down keep to, so
+    /// passing through a shorter line doesn't lose the column
+    goal_x: Option<f32>,
+    /// Index of the top line shown
+    scroll: usize,
+    cursor_blink_time: f32,
+    cursor_visible: bool,
+}
+
+impl TextArea {
+    /// Create a new text area
+    pub fn new(x: f32, y: f32, width: f32, height: f32, placeholder: impl Into<String>) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            buffer: EditBuffer::default(),
+            placeholder: placeholder.into(),
+            text_color: Color {
+                r: 0.0,
+                g: 0.9,
+                b: 0.9,
+                a: 1.0,
+            },
+            placeholder_color: Color {
+                r: 0.4,
+                g: 0.4,
+                b: 0.4,
+                a: 1.0,
+            },
+            is_focused: false,
+            goal_x: None,
+            scroll: 0,
+            cursor_blink_time: 0.0,
+            cursor_visible: true,
+        }
+    }
+
+    /// Set the text color
+    pub fn with_text_color(mut self, color: Color) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    /// Set the placeholder color
+    pub fn with_placeholder_color(mut self, color: Color) -> Self {
+        self.placeholder_color = color;
+        self
+    }
+
+    /// Get the current text
+    pub fn text(&self) -> &str {
+        self.buffer.text()
+    }
+
+    /// Set the text, scrolled to the top
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.buffer.set_text(clean(&text.into()));
+        self.goal_x = None;
+        self.scroll = 0;
+    }
+
+    /// Byte range of the selected text, if anything is selected
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        self.buffer.selection()
+    }
+
+    /// The selected text, if anything is selected
+    pub fn selected_text(&self) -> Option<&str> {
+        self.buffer.selected_text()
+    }
+
+    /// Select the whole text
+    pub fn select_all(&mut self) {
+        self.buffer.select_all();
+        self.scroll_to_cursor();
+    }
+
+    /// Width lines wrap at
+    fn wrap_width(&self) -> f32 {
+        (self.width - 2.0 * TEXT_PADDING - SCROLL_INDICATOR_WIDTH).max(1.0)
+    }
+
+    /// Byte ranges of the lines shown, after wrapping at the field's width
+    ///
+    /// A wrapped line keeps the spaces it was broken at, so every offset
+    /// but those of line breaks falls inside a line.
+    fn lines(&self) -> Vec<(usize, usize)> {
+        if self.buffer.text().is_empty() {
+            return vec![(0, 0)];
+        }
+
+        // `wrap` hands back slices of the text, which give their offsets away
+        let base = self.buffer.text().as_ptr() as usize;
+        let mut lines: Vec<(usize, usize)> = wrap(self.buffer.text(), self.wrap_width(), TEXT_SIZE)
+            .into_iter()
+            .map(|line| {
+                let start = line.as_ptr() as usize - base;
+                (start, start + line.len())
+            })
+            .collect();
+        // Text ending in a line break has an empty line after it
+        if self.buffer.text().ends_with('\n') {
+            lines.push((self.buffer.text().len(), self.buffer.text().len()));
+        }
+        for index in 1..lines.len() {
+            let (end, next) = (lines[index - 1].1, lines[index].0);
+            if !self.buffer.text()[end..next].contains('\n') {
+                lines[index - 1].1 = next;
+            }
+        }
+        lines
+    }
+
+    /// Index of the line a byte offset is on
+    fn line_of(lines: &[(usize, usize)], offset: usize) -> usize {
+        lines.partition_point(|&(start, _)| start <= offset).saturating_sub(1)
+    }
+
+    /// Last offset the cursor can take on a line
+    ///
+    /// The end of a wrapped line is the start of the next, where the cursor
+    /// would show instead, so it stops a character short.
+    fn line_end(&self, lines: &[(usize, usize)], line: usize) -> usize {
+        let (start, end) = lines[line];
+        let wrapped = lines.get(line + 1).is_some_and(|&(next, _)| next == end);
+        if wrapped && end > start { previous_boundary(self.buffer.text(), end) } else { end }
+    }
+
+    /// Number of whole lines that fit in the field
+    fn visible_lines(&self) -> usize {
+        (((self.height - 2.0 * TEXT_PADDING) / LINE_HEIGHT).floor() as usize).max(1)
+    }
+
+    fn max_scroll(&self, lines: &[(usize, usize)]) -> usize {
+        lines.len().saturating_sub(self.visible_lines())
+    }
+
+    /// Scroll as little as needed to bring the cursor's line into view
+    fn scroll_to_cursor(&mut self) {
+        let lines = self.lines();
+        let line = Self::line_of(&lines, self.buffer.cursor());
+        let visible = self.visible_lines();
+        if line < self.scroll {
+            self.scroll = line;
+        } else if line >= self.scroll + visible {
+            self.scroll = line + 1 - visible;
+        }
+        self.scroll = self.scroll.min(self.max_scroll(&lines));
+        self.cursor_visible = true;
+        self.cursor_blink_time = 0.0;
+    }
+
+    /// Top edge of a line, which may be outside the field when scrolled away
+    fn line_y(&self, line: usize) -> f32 {
+        self.y + TEXT_PADDING + (line as f32 - self.scroll as f32) * LINE_HEIGHT
+    }
+
+    /// Distance from the start of a line to a byte offset on it, counting
+    /// the composed text shown at the cursor
+    fn offset_x(&self, line_start: usize, offset: usize) -> f32 {
+        let x = text_width(&self.buffer.text()[line_start..offset], TEXT_SIZE);
+        if offset > self.buffer.cursor() && line_start <= self.buffer.cursor() {
+            x + text_width(self.buffer.preedit(), TEXT_SIZE)
+        } else {
+            x
+        }
+    }
+
+    /// Byte offset on a line nearest to a distance from its start
+    fn offset_in_line(&self, lines: &[(usize, usize)], line: usize, target: f32) -> usize {
+        let start = lines[line].0;
+        let end = self.line_end(lines, line);
+        let mut left = 0.0;
+        for (byte, c) in self.buffer.text()[start..end].char_indices() {
+            let right = text_width(&self.buffer.text()[start..start + byte + c.len_utf8()], TEXT_SIZE);
+            if target < (left + right) / 2.0 {
+                return start + byte;
+            }
+            left = right;
+        }
+        end
+    }
+
+    /// Byte offset nearest to a point on screen, taking the first or last
+    /// line shown for points above or below them
+    fn offset_at(&self, x: f32, y: f32) -> usize {
+        let lines = self.lines();
+        let row = ((y - self.y - TEXT_PADDING) / LINE_HEIGHT).floor().max(0.0) as usize;
+        let line = (self.scroll + row.min(self.visible_lines() - 1)).min(lines.len() - 1);
+        self.offset_in_line(&lines, line, x - self.x - TEXT_PADDING)
+    }
+
+    /// Offset the cursor lands on moving `count` lines down (or up, if
+    /// negative), keeping to the column it started in
+    fn offset_lines_away(&mut self, count: isize) -> usize {
+        let lines = self.lines();
+        let line = Self::line_of(&lines, self.buffer.cursor());
+        let goal_x = match self.goal_x {
+            Some(goal_x) => goal_x,
+            None => text_width(&self.buffer.text()[lines[line].0..self.buffer.cursor()], TEXT_SIZE),
+        };
+        self.goal_x = Some(goal_x);
+
+        // Moving up from the first line goes to the start, down from the last to the end
+        match line.checked_add_signed(count) {
+            None => 0,
+            Some(target) if target >= lines.len() => self.buffer.text().len(),
+            Some(target) => self.offset_in_line(&lines, target, goal_x),
+        }
+    }
+
+    /// Move the cursor to an offset, selecting from where it was when
+    /// `extend` is set
+    fn move_cursor(&mut self, offset: usize, extend: bool) {
+        self.buffer.move_cursor(offset, extend);
+        self.scroll_to_cursor();
+    }
+
+    /// Offset a cursor movement key leads to, or None for other keys
+    ///
+    /// Up and Down keep the remembered column; other movements forget it.
+    fn offset_for_key(&mut self, key: KeyCode) -> Option<usize> {
+        let page = self.visible_lines() as isize;
+        let vertical = match key {
+            KeyCode::ArrowUp => Some(-1),
+            KeyCode::ArrowDown => Some(1),
+            KeyCode::PageUp => Some(-page),
+            KeyCode::PageDown => Some(page),
+            _ => None,
+        };
+        if let Some(count) = vertical {
+            return Some(self.offset_lines_away(count));
+        }
+
+        self.goal_x = None;
+        let lines = self.lines();
+        let line = Self::line_of(&lines, self.buffer.cursor());
+        match key {
+            KeyCode::ArrowLeft => Some(self.buffer.char_boundary(false)),
+            KeyCode::ArrowRight => Some(self.buffer.char_boundary(true)),
+            KeyCode::Home => Some(lines[line].0),
+            KeyCode::End => Some(self.line_end(&lines, line)),
+            _ => None,
+        }
+    }
+
+    /// Move the cursor like the arrows, Home and End would, keeping the
+    /// other end of the selection where it is
+    ///
+    /// Home and End go to the start and end of the cursor's line.
+    pub fn extend_selection(&mut self, key: KeyCode) {
+        if !self.is_focused {
+            return;
+        }
+        if let Some(offset) = self.offset_for_key(key) {
+            self.move_cursor(offset, true);
+        }
+    }
+
+    /// Move the cursor a word back or on (Ctrl+Arrow), extending the selection
+    /// when `extend` is set (Ctrl+Shift+Arrow)
+    pub fn move_word(&mut self, forward: bool, extend: bool) {
+        if !self.is_focused {
+            return;
+        }
+        self.goal_x = None;
+        self.move_cursor(self.buffer.word_boundary(forward), extend);
+    }
+
+    /// Carry out a cursor or selection action, returning whether it applied
+    ///
+    /// Clipboard actions go through `handle_clipboard` instead.
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        match action {
+            Action::SelectLeft => self.extend_selection(KeyCode::ArrowLeft),
+            Action::SelectRight => self.extend_selection(KeyCode::ArrowRight),
+            Action::SelectHome => self.extend_selection(KeyCode::Home),
+            Action::SelectEnd => self.extend_selection(KeyCode::End),
+            Action::SelectAll => self.select_all(),
+            Action::WordLeft => self.move_word(false, false),
+            Action::WordRight => self.move_word(true, false),
+            Action::SelectWordLeft => self.move_word(false, true),
+            Action::SelectWordRight => self.move_word(true, true),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Copy, cut or paste through the clipboard, returning whether the action applied
+    ///
+    /// Copy and cut need a selection; paste needs text on the clipboard.
+    pub fn handle_clipboard(&mut self, action: Action, clipboard: &mut Clipboard) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        match action {
+            Action::Copy => self.buffer.copy(clipboard),
+            Action::Cut => {
+                let cut = self.buffer.cut(clipboard);
+                if cut {
+                    self.goal_x = None;
+                    self.scroll_to_cursor();
+                }
+                cut
+            }
+            Action::Paste => match clipboard.text() {
+                Some(text) => {
+                    self.insert_text(&text);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Take text typed or composed with an input method
+    ///
+    /// Text being composed is only shown at the cursor; the field changes
+    /// once the input method inserts the finished text.
+    pub fn handle_text(&mut self, event: &TextEvent) {
+        if !self.is_focused {
+            return;
+        }
+        match event {
+            TextEvent::Preedit { text, cursor } => {
+                self.buffer.set_preedit(text, *cursor);
+                self.cursor_visible = true;
+                self.cursor_blink_time = 0.0;
+            }
+            TextEvent::Insert(text) => {
+                self.buffer.clear_preedit();
+                self.insert_text(text);
+            }
+        }
+    }
+
+    /// Insert text at the cursor, replacing the selection
+    ///
+    /// Line breaks are kept, tabs become spaces and other control characters
+    /// are dropped.
+    pub fn insert_text(&mut self, text: &str) {
+        if !self.is_focused {
+            return;
+        }
+
+        if self.buffer.insert(&clean(text)) {
+            self.goal_x = None;
+            self.scroll_to_cursor();
+        }
+    }
+
+    /// Bounds of the text cursor while focused, where an input method shows its candidates
+    pub fn caret_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        if !self.is_focused {
+            return None;
+        }
+        let lines = self.lines();
+        let line = Self::line_of(&lines, self.buffer.cursor());
+        let x = self.x + TEXT_PADDING + self.offset_x(lines[line].0, self.buffer.cursor());
+        Some((x, self.line_y(line), CARET_WIDTH, LINE_HEIGHT))
+    }
+
+    /// Set the focus state, putting the cursor at the end of the text when focused
+    pub fn set_focused(&mut self, focused: bool) {
+        self.is_focused = focused;
+        self.buffer.clear_selection();
+        self.goal_x = None;
+        self.buffer.clear_preedit();
+        if focused {
+            self.buffer.move_cursor(self.buffer.text().len(), false);
+            self.scroll_to_cursor();
+        }
+    }
+
+    /// Check if a point is inside the text area
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+
+    /// Handle mouse click: a single click places the cursor,
+    /// a double click selects a word and a triple click selects everything
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32, click_count: u32) {
+        self.is_focused = self.contains_point(x, y);
+        self.goal_x = None;
+        if !self.is_focused {
+            self.buffer.clear_selection();
+            self.buffer.clear_preedit();
+            return;
+        }
+
+        self.buffer.move_cursor(self.offset_at(x, y), false);
+        match click_count {
+            0 | 1 => {}
+            2 => self.buffer.select_word_at(self.buffer.cursor()),
+            _ => self.buffer.select_all(),
+        }
+        self.scroll_to_cursor();
+    }
+
+    /// Scroll by a number of pixels, a line at a time, leaving the cursor where it is
+    pub fn handle_mouse_wheel(&mut self, delta: f32) {
+        let lines = (delta / LINE_HEIGHT).round() as isize;
+        let lines = if lines == 0 && delta != 0.0 { delta.signum() as isize } else { lines };
+        self.scroll = self.scroll.saturating_add_signed(lines).min(self.max_scroll(&self.lines()));
+    }
+
+    /// Handle character input
+    pub fn handle_char_input(&mut self, c: char) {
+        if !self.is_focused || c.is_control() {
+            return;
+        }
+        self.insert_text(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Handle keyboard input: Enter starts a new line, and the arrows,
+    /// Home, End and the page keys move the cursor
+    pub fn handle_key_press(&mut self, key: KeyCode) {
+        if !self.is_focused {
+            return;
+        }
+
+        match key {
+            KeyCode::Backspace | KeyCode::Delete => {
+                if self.buffer.delete(key == KeyCode::Delete) {
+                    self.goal_x = None;
+                    self.scroll_to_cursor();
+                }
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter => self.insert_text("\n"),
+            KeyCode::Escape => {
+                self.is_focused = false;
+                self.buffer.clear_preedit();
+            }
+            key => {
+                if let Some(offset) = self.offset_for_key(key) {
+                    self.move_cursor(offset, false);
+                }
+            }
+        }
+    }
+}
+
+impl Widget for TextArea {
+    fn update(&mut self, delta_time: f32) {
+        // Update cursor blink
+        if self.is_focused {
+            self.cursor_blink_time += delta_time;
+            if self.cursor_blink_time >= 0.5 {
+                self.cursor_blink_time = 0.0;
+                self.cursor_visible = !self.cursor_visible;
+            }
+        } else {
+            self.cursor_visible = false;
+        }
+    }
+
+    fn needs_update(&self) -> bool {
+        // Blinking while focused, or hiding the cursor after losing focus
+        self.is_focused || self.cursor_visible
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth).with_focused(self.is_focused));
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let text_x = self.x + TEXT_PADDING;
+        let text_offset = (LINE_HEIGHT - TEXT_SIZE) / 2.0;
+        let placeholder_color = [
+            self.placeholder_color.r as f32,
+            self.placeholder_color.g as f32,
+            self.placeholder_color.b as f32,
+            self.placeholder_color.a as f32,
+        ];
+        let text_color = [
+            self.text_color.r as f32,
+            self.text_color.g as f32,
+            self.text_color.b as f32,
+            self.text_color.a as f32,
+        ];
+        let faded = [text_color[0], text_color[1], text_color[2], 0.3];
+
+        // The focused field's border glows in the user's glow color
+        if self.is_focused {
+            ctx.draw_glow(Quad::outline(self.x, self.y, self.width, self.height, 2.0, ctx.glow_color()));
+        }
+
+        if self.buffer.text().is_empty() && self.buffer.preedit().is_empty() {
+            ctx.draw_text(&self.placeholder, text_x, self.line_y(0) + text_offset, TEXT_SIZE, placeholder_color);
+        }
+
+        let lines = self.lines();
+        let cursor_line = Self::line_of(&lines, self.buffer.cursor());
+        let visible = self.scroll..(self.scroll + self.visible_lines()).min(lines.len());
+        for line in visible.clone() {
+            let (start, end) = lines[line];
+            let line_y = self.line_y(line);
+
+            // Highlight the selection behind the text, a little past the end
+            // of lines whose line break is selected too
+            if let Some((from, to)) = self.selection() {
+                let (from, to) = (from.clamp(start, end), to.clamp(start, end));
+                let break_selected = self.selection().is_some_and(|(first, last)| first <= end && last > end)
+                    && self.buffer.text()[end..].starts_with('\n');
+                if from < to || break_selected {
+                    let from_x = self.offset_x(start, from);
+                    let mut width = self.offset_x(start, to) - from_x;
+                    if break_selected {
+                        width += TEXT_SIZE * 0.5;
+                    }
+                    ctx.draw_rect(text_x + from_x, line_y, width, LINE_HEIGHT, faded);
+                }
+            }
+
+            // Text being composed is spliced in at the cursor and underlined until it's committed
+            if line == cursor_line && !self.buffer.preedit().is_empty() {
+                let mut shown = self.buffer.text()[start..end].to_string();
+                shown.insert_str(self.buffer.cursor() - start, self.buffer.preedit());
+                ctx.draw_text(&shown, text_x, line_y + text_offset, TEXT_SIZE, text_color);
+
+                let preedit_x = text_x + self.offset_x(start, self.buffer.cursor());
+                let end_x = preedit_x + ctx.measure_text(self.buffer.preedit(), TEXT_SIZE).width;
+                let underline_y = line_y + LINE_HEIGHT - 2.0;
+                ctx.draw_line(preedit_x, underline_y, end_x, underline_y, 1.0, text_color);
+            } else {
+                ctx.draw_text(&self.buffer.text()[start..end], text_x, line_y + text_offset, TEXT_SIZE, text_color);
+            }
+        }
+
+        // Draw cursor if focused, visible and on a line in view
+        if self.is_focused && self.cursor_visible && visible.contains(&cursor_line) {
+            // While composing, the cursor follows the input method's cursor
+            let cursor_x = text_x
+                + self.offset_x(lines[cursor_line].0, self.buffer.cursor())
+                + ctx.measure_text(&self.buffer.preedit()[..self.buffer.preedit_cursor()], TEXT_SIZE).width;
+            ctx.draw_text("|", cursor_x, self.line_y(cursor_line) + text_offset, TEXT_SIZE, text_color);
+        }
+
+        // A thin bar on the right shows which part of longer text is in view
+        if lines.len() > self.visible_lines() {
+            let track = self.height - 2.0 * TEXT_PADDING;
+            let bar_height = track * self.visible_lines() as f32 / lines.len() as f32;
+            let bar_y = self.y + TEXT_PADDING + track * self.scroll as f32 / lines.len() as f32;
+            ctx.draw_rect(self.x + self.width - SCROLL_INDICATOR_WIDTH - 2.0, bar_y, SCROLL_INDICATOR_WIDTH, bar_height, faded);
+        }
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+        // The lines wrap anew at another width
+        self.scroll = self.scroll.min(self.max_scroll(&self.lines()));
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.contains_point(x, y).then_some(CursorIcon::Text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_area_wraps_and_moves_across_lines() {
+        // Without a font every character is 8 pixels wide: 16 fit on a line and 4 lines show
+        let mut area = TextArea::new(0.0, 0.0, 143.0, 90.0, "");
+        let caret = |area: &TextArea| area.caret_bounds().map(|(x, y, _, _)| (x, y));
+        area.set_focused(true);
+        area.handle_text(&TextEvent::Insert("long first line".to_string()));
+        area.handle_key_press(KeyCode::Enter);
+        area.handle_text(&TextEvent::Insert("ab\r\nanother\tlong one".to_string()));
+        assert_eq!(area.text(), "long first line\nab\nanother long one");
+
+        // Up and Down keep to the column through a shorter line
+        area.handle_key_press(KeyCode::Home);
+        for _ in 0..10 {
+            area.handle_key_press(KeyCode::ArrowRight);
+        }
+        assert_eq!(caret(&area), Some((85.0, 45.0)));
+        area.handle_key_press(KeyCode::ArrowUp);
+        assert_eq!(caret(&area), Some((21.0, 25.0)));
+        area.handle_key_press(KeyCode::ArrowUp);
+        assert_eq!(caret(&area), Some((85.0, 5.0)));
+        area.handle_key_press(KeyCode::ArrowDown);
+        area.handle_key_press(KeyCode::ArrowDown);
+        assert_eq!(caret(&area), Some((85.0, 45.0)));
+        // Past the last line is the end, before the first the start
+        area.handle_key_press(KeyCode::ArrowDown);
+        assert_eq!(caret(&area), Some((133.0, 45.0)));
+        area.handle_key_press(KeyCode::PageUp);
+        assert_eq!(caret(&area), Some((5.0, 5.0)));
+
+        // Long lines wrap at a space, and End stops before it
+        area.set_text("one two three four five six");
+        area.set_focused(true);
+        area.handle_key_press(KeyCode::PageUp);
+        area.handle_key_press(KeyCode::End);
+        assert_eq!(caret(&area), Some((109.0, 5.0)));
+        area.extend_selection(KeyCode::ArrowDown);
+        assert_eq!(area.selected_text(), Some(" four five six"));
+
+        // Text taller than the area scrolls to keep the cursor in view
+        area.set_text("1\n2\n3\n4\n5\n6");
+        area.set_focused(true);
+        assert_eq!(caret(&area), Some((13.0, 65.0)));
+        area.handle_key_press(KeyCode::PageUp);
+        assert_eq!(caret(&area), Some((13.0, 5.0)));
+        // The wheel scrolls without moving the cursor
+        area.handle_mouse_wheel(100.0);
+        assert_eq!(caret(&area), Some((13.0, -15.0)));
+        area.handle_mouse_down(20.0, 30.0, 1);
+        area.handle_char_input('!');
+        assert_eq!(area.text(), "1\n2\n3\n4!\n5\n6");
+    }
+}
@@ -1,6 +1,22 @@
 use wgpu::Color;
-use crate::ui::{RenderContext, Widget};
+use crate::ui::{Clipboard, Quad, RenderContext, Widget, WidgetInfo};
+use crate::ui::edit_buffer::{truncate_to, EditBuffer};
+use crate::ui::input::{Action, TextEvent};
+use crate::ui::text_metrics::text_width;
 use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+/// Horizontal padding before the first character
+const TEXT_PADDING: f32 = 5.0;
+
+/// Size of the text, and of the placeholder
+const TEXT_SIZE: f32 = 16.0;
+
+/// Width of the area an input method places its candidates next to
+const CARET_WIDTH: f32 = 2.0;
+
+/// Called with the input's text when it changes or is submitted
+type TextCallback = Box<dyn Fn(&str)>;
 
 /// A text input widget
 pub struct TextInput {
@@ -8,7 +24,7 @@ pub struct TextInput {
     y: f32,
     width: f32,
     height: f32,
-    text: String,
+    buffer: EditBuffer,
     placeholder: String,
     background_color: Color,
     text_color: Color,
@@ -16,12 +32,11 @@ pub struct TextInput {
     border_color: Color,
     border_width: f32,
     is_focused: bool,
-    cursor_position: usize,
     cursor_blink_time: f32,
     cursor_visible: bool,
     max_length: Option<usize>,
-    on_change: Option<Box<dyn Fn(&str)>>,
-    on_submit: Option<Box<dyn Fn(&str)>>,
+    on_change: Option<TextCallback>,
+    on_submit: Option<TextCallback>,
 }
 
 impl TextInput {
@@ -32,7 +47,7 @@ impl TextInput {
             y,
             width,
             height,
-            text: String::new(),
+            buffer: EditBuffer::default(),
             placeholder: placeholder.into(),
             background_color: Color {
                 r: 0.1,
@@ -60,7 +75,6 @@ impl TextInput {
             },
             border_width: 1.0,
             is_focused: false,
-            cursor_position: 0,
             cursor_blink_time: 0.0,
             cursor_visible: true,
             max_length: None,
@@ -119,21 +133,205 @@ impl TextInput {
 
     /// Get the current text
     pub fn text(&self) -> &str {
-        &self.text
+        self.buffer.text()
     }
 
     /// Set the text
     pub fn set_text(&mut self, text: impl Into<String>) {
-        self.text = text.into();
+        let mut text = text.into();
         if let Some(max_length) = self.max_length {
-            if self.text.len() > max_length {
-                self.text.truncate(max_length);
+            let end = truncate_to(&text, max_length).len();
+            text.truncate(end);
+        }
+        self.buffer.set_text(text);
+        self.notify_change();
+    }
+
+    /// Byte range of the selected text, if anything is selected
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        self.buffer.selection()
+    }
+
+    /// The selected text, if anything is selected
+    pub fn selected_text(&self) -> Option<&str> {
+        self.buffer.selected_text()
+    }
+
+    /// Select the whole text
+    pub fn select_all(&mut self) {
+        self.buffer.select_all();
+    }
+
+    /// Select the word (or run of spaces/punctuation) containing the byte offset
+    pub fn select_word_at(&mut self, offset: usize) {
+        self.buffer.select_word_at(offset);
+    }
+
+    /// Move the cursor like ArrowLeft/ArrowRight/Home/End would, keeping the
+    /// other end of the selection where it is
+    pub fn extend_selection(&mut self, key: KeyCode) {
+        if !self.is_focused {
+            return;
+        }
+        if let Some(offset) = self.offset_for_key(key) {
+            self.buffer.move_cursor(offset, true);
+        }
+    }
+
+    /// Offset ArrowLeft/ArrowRight/Home/End lead to, or None for other keys
+    fn offset_for_key(&self, key: KeyCode) -> Option<usize> {
+        match key {
+            KeyCode::ArrowLeft => Some(self.buffer.char_boundary(false)),
+            KeyCode::ArrowRight => Some(self.buffer.char_boundary(true)),
+            KeyCode::Home => Some(0),
+            KeyCode::End => Some(self.buffer.text().len()),
+            _ => None,
+        }
+    }
+
+    /// Move the cursor a word back or on (Ctrl+Arrow), extending the selection
+    /// when `extend` is set (Ctrl+Shift+Arrow)
+    pub fn move_word(&mut self, forward: bool, extend: bool) {
+        if !self.is_focused {
+            return;
+        }
+        self.buffer.move_cursor(self.buffer.word_boundary(forward), extend);
+    }
+
+    /// Carry out a cursor or selection action, returning whether it applied
+    ///
+    /// Clipboard actions go through `handle_clipboard` instead.
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        match action {
+            Action::SelectLeft => self.extend_selection(KeyCode::ArrowLeft),
+            Action::SelectRight => self.extend_selection(KeyCode::ArrowRight),
+            Action::SelectHome => self.extend_selection(KeyCode::Home),
+            Action::SelectEnd => self.extend_selection(KeyCode::End),
+            Action::SelectAll => self.select_all(),
+            Action::WordLeft => self.move_word(false, false),
+            Action::WordRight => self.move_word(true, false),
+            Action::SelectWordLeft => self.move_word(false, true),
+            Action::SelectWordRight => self.move_word(true, true),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Copy, cut or paste through the clipboard, returning whether the action applied
+    ///
+    /// Copy and cut need a selection; paste needs text on the clipboard.
+    pub fn handle_clipboard(&mut self, action: Action, clipboard: &mut Clipboard) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        match action {
+            Action::Copy => self.buffer.copy(clipboard),
+            Action::Cut => {
+                let cut = self.buffer.cut(clipboard);
+                if cut {
+                    self.notify_change();
+                }
+                cut
+            }
+            Action::Paste => match clipboard.text() {
+                Some(text) => {
+                    self.insert_text(&text);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Take text typed or composed with an input method
+    ///
+    /// Text being composed is only shown at the cursor; the field changes
+    /// once the input method inserts the finished text.
+    pub fn handle_text(&mut self, event: &TextEvent) {
+        if !self.is_focused {
+            return;
+        }
+        match event {
+            TextEvent::Preedit { text, cursor } => {
+                self.buffer.set_preedit(text, *cursor);
+                self.cursor_visible = true;
+                self.cursor_blink_time = 0.0;
+            }
+            TextEvent::Insert(text) => {
+                self.buffer.clear_preedit();
+                self.insert_text(text);
             }
         }
-        self.cursor_position = self.text.len();
+    }
+
+    /// Insert text at the cursor, replacing the selection
+    ///
+    /// Line breaks and tabs become spaces and other control characters are
+    /// dropped, since the field holds a single line. Text past the maximum
+    /// length is cut off.
+    pub fn insert_text(&mut self, text: &str) {
+        if !self.is_focused {
+            return;
+        }
+
+        let text: String = text
+            .lines()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .filter(|c| !c.is_control())
+            .collect();
+        let deleted = self.buffer.delete_selection();
+        let room = self.max_length.map_or(text.len(), |max_length| max_length.saturating_sub(self.buffer.text().len()));
+        if self.buffer.insert(truncate_to(&text, room)) || deleted {
+            self.notify_change();
+        }
+    }
+
+    fn notify_change(&self) {
         if let Some(on_change) = &self.on_change {
-            on_change(&self.text);
+            on_change(self.buffer.text());
+        }
+    }
+
+    /// Distance from the start of the text to a byte offset into it, counting
+    /// the composed text shown at the cursor
+    fn offset_x(&self, offset: usize) -> f32 {
+        let x = text_width(&self.buffer.text()[..offset], TEXT_SIZE);
+        if offset > self.buffer.cursor() {
+            x + text_width(self.buffer.preedit(), TEXT_SIZE)
+        } else {
+            x
+        }
+    }
+
+    /// Bounds of the text cursor while focused, where an input method shows its candidates
+    pub fn caret_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        if !self.is_focused {
+            return None;
+        }
+        let x = self.x + TEXT_PADDING + self.offset_x(self.buffer.cursor());
+        Some((x, self.y, CARET_WIDTH, self.height))
+    }
+
+    /// Byte offset of the character boundary nearest to a screen x coordinate
+    fn offset_at(&self, x: f32) -> usize {
+        let text = self.buffer.text();
+        let target = x - self.x - TEXT_PADDING;
+        let mut left = 0.0;
+        for (byte, c) in text.char_indices() {
+            let right = text_width(&text[..byte + c.len_utf8()], TEXT_SIZE);
+            if target < (left + right) / 2.0 {
+                return byte;
+            }
+            left = right;
         }
+        text.len()
     }
 
     /// Get the focus state
@@ -144,8 +342,10 @@ impl TextInput {
     /// Set the focus state
     pub fn set_focused(&mut self, focused: bool) {
         self.is_focused = focused;
+        self.buffer.clear_selection();
+        self.buffer.clear_preedit();
         if focused {
-            self.cursor_position = self.text.len();
+            self.buffer.move_cursor(self.buffer.text().len(), false);
             self.cursor_visible = true;
             self.cursor_blink_time = 0.0;
         }
@@ -156,41 +356,32 @@ impl TextInput {
         x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
     }
 
-    /// Handle mouse click
-    pub fn handle_mouse_down(&mut self, x: f32, y: f32) {
+    /// Handle mouse click: a single click places the cursor,
+    /// a double click selects a word and a triple click selects everything
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32, click_count: u32) {
         self.is_focused = self.contains_point(x, y);
-        // TODO: Position cursor based on click position within text
-        if self.is_focused {
-            self.cursor_position = self.text.len();
-        }
-    }
-
-    /// Handle character input
-    pub fn handle_char_input(&mut self, c: char) {
         if !self.is_focused {
+            self.buffer.clear_selection();
+            self.buffer.clear_preedit();
             return;
         }
 
-        // Ignore control characters
-        if c.is_control() {
-            return;
-        }
-
-        // Check max length
-        if let Some(max_length) = self.max_length {
-            if self.text.len() >= max_length {
-                return;
-            }
+        self.cursor_visible = true;
+        self.cursor_blink_time = 0.0;
+        self.buffer.move_cursor(self.offset_at(x), false);
+        match click_count {
+            0 | 1 => {}
+            2 => self.select_word_at(self.buffer.cursor()),
+            _ => self.select_all(),
         }
+    }
 
-        // Insert character at cursor position
-        self.text.insert(self.cursor_position, c);
-        self.cursor_position += 1;
-
-        // Trigger on_change
-        if let Some(on_change) = &self.on_change {
-            on_change(&self.text);
+    /// Handle character input; typing replaces the selection
+    pub fn handle_char_input(&mut self, c: char) {
+        if !self.is_focused || c.is_control() {
+            return;
         }
+        self.insert_text(c.encode_utf8(&mut [0; 4]));
     }
 
     /// Handle keyboard input
@@ -200,48 +391,25 @@ impl TextInput {
         }
 
         match key {
-            KeyCode::Backspace => {
-                if self.cursor_position > 0 {
-                    self.text.remove(self.cursor_position - 1);
-                    self.cursor_position -= 1;
-                    if let Some(on_change) = &self.on_change {
-                        on_change(&self.text);
-                    }
-                }
-            }
-            KeyCode::Delete => {
-                if self.cursor_position < self.text.len() {
-                    self.text.remove(self.cursor_position);
-                    if let Some(on_change) = &self.on_change {
-                        on_change(&self.text);
-                    }
-                }
-            }
-            KeyCode::ArrowLeft => {
-                if self.cursor_position > 0 {
-                    self.cursor_position -= 1;
-                }
-            }
-            KeyCode::ArrowRight => {
-                if self.cursor_position < self.text.len() {
-                    self.cursor_position += 1;
+            KeyCode::Backspace | KeyCode::Delete => {
+                if self.buffer.delete(key == KeyCode::Delete) {
+                    self.notify_change();
                 }
             }
-            KeyCode::Home => {
-                self.cursor_position = 0;
-            }
-            KeyCode::End => {
-                self.cursor_position = self.text.len();
-            }
             KeyCode::Enter => {
                 if let Some(on_submit) = &self.on_submit {
-                    on_submit(&self.text);
+                    on_submit(self.buffer.text());
                 }
             }
             KeyCode::Escape => {
                 self.is_focused = false;
+                self.buffer.clear_preedit();
+            }
+            key => {
+                if let Some(offset) = self.offset_for_key(key) {
+                    self.buffer.move_cursor(offset, false);
+                }
             }
-            _ => {}
         }
     }
 }
@@ -254,7 +422,7 @@ impl Clone for TextInput {
             y: self.y,
             width: self.width,
             height: self.height,
-            text: self.text.clone(),
+            buffer: self.buffer.clone(),
             placeholder: self.placeholder.clone(),
             background_color: self.background_color,
             text_color: self.text_color,
@@ -262,7 +430,6 @@ impl Clone for TextInput {
             border_color: self.border_color,
             border_width: self.border_width,
             is_focused: self.is_focused,
-            cursor_position: self.cursor_position,
             cursor_blink_time: self.cursor_blink_time,
             cursor_visible: self.cursor_visible,
             max_length: self.max_length,
@@ -286,12 +453,21 @@ impl Widget for TextInput {
         }
     }
 
+    fn needs_update(&self) -> bool {
+        // Blinking while focused, or hiding the cursor after losing focus
+        self.is_focused || self.cursor_visible
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth).with_focused(self.is_focused));
+    }
+
     fn render(&self, ctx: &mut RenderContext) {
         // TODO: Draw text input background and border
         // For now, just draw the text/placeholder and cursor
 
         // Calculate text position
-        let text_x = self.x + 5.0;  // Small padding
+        let text_x = self.x + TEXT_PADDING;
         let text_y = self.y + (self.height / 2.0) - 8.0;  // Rough vertical centering
 
         // Convert wgpu::Color to [f32; 4] array
@@ -309,18 +485,44 @@ impl Widget for TextInput {
             self.text_color.a as f32,
         ];
 
-        // Draw the text or placeholder
-        if self.text.is_empty() {
-            ctx.draw_text(&self.placeholder, text_x, text_y, 16.0, placeholder_color_array);
+        // The focused field's border glows in the user's glow color
+        if self.is_focused {
+            ctx.draw_glow(Quad::outline(self.x, self.y, self.width, self.height, 2.0, ctx.glow_color()));
+        }
+
+        // Highlight the selection behind the text
+        if let Some((start, end)) = self.selection() {
+            let start_x = self.offset_x(start);
+            ctx.draw_rect(
+                text_x + start_x, self.y + 4.0,
+                self.offset_x(end) - start_x, self.height - 8.0,
+                [text_color_array[0], text_color_array[1], text_color_array[2], 0.3],
+            );
+        }
+
+        // Draw the text or placeholder, with any text being composed spliced in at the cursor
+        let (text, preedit) = (self.buffer.text(), self.buffer.preedit());
+        let preedit_x = text_x + self.offset_x(self.buffer.cursor());
+        if text.is_empty() && preedit.is_empty() {
+            ctx.draw_text(&self.placeholder, text_x, text_y, TEXT_SIZE, placeholder_color_array);
+        } else if preedit.is_empty() {
+            ctx.draw_text(text, text_x, text_y, TEXT_SIZE, text_color_array);
         } else {
-            ctx.draw_text(&self.text, text_x, text_y, 16.0, text_color_array);
+            let mut shown = text.to_string();
+            shown.insert_str(self.buffer.cursor(), preedit);
+            ctx.draw_text(&shown, text_x, text_y, TEXT_SIZE, text_color_array);
+
+            // Composed text is underlined until it's committed
+            let end_x = preedit_x + ctx.measure_text(preedit, TEXT_SIZE).width;
+            let underline_y = self.y + self.height - 6.0;
+            ctx.draw_line(preedit_x, underline_y, end_x, underline_y, 1.0, text_color_array);
         }
 
         // Draw cursor if focused and visible
         if self.is_focused && self.cursor_visible {
-            // Calculate cursor position (assume monospace font with 8px width)
-            let cursor_x = text_x + (self.cursor_position as f32 * 8.0);
-            ctx.draw_text("|", cursor_x, text_y, 16.0, text_color_array);
+            // While composing, the cursor follows the input method's cursor
+            let cursor_x = preedit_x + ctx.measure_text(&preedit[..self.buffer.preedit_cursor()], TEXT_SIZE).width;
+            ctx.draw_text("|", cursor_x, text_y, TEXT_SIZE, text_color_array);
         }
     }
 
@@ -341,4 +543,89 @@ impl Widget for TextInput {
         self.width = width;
         self.height = height;
     }
-} 
\ No newline at end of file
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.contains_point(x, y).then_some(CursorIcon::Text)
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::input::{InputEvent, TextEvent, TimedInput};
+    use crate::ui::testing::UiDriver;
+    use crate::ui::UiEvent;
+    use winit::keyboard::{KeyCode, ModifiersState};
+
+    #[test]
+    fn test_editing_text_with_the_clipboard() {
+        let (mut driver, _) = UiDriver::with_tasks(&[]);
+        let ctrl_shift = ModifiersState::CONTROL | ModifiersState::SHIFT;
+
+        // Cut the last word and paste it back after typing in front of it
+        driver.widget_mut().add_and_continue();
+        driver.type_text("Buy milk");
+        driver.press_with(ctrl_shift, KeyCode::ArrowLeft);
+        driver.press_with(ModifiersState::CONTROL, KeyCode::KeyX);
+        assert_eq!(driver.clipboard().text().as_deref(), Some("milk"));
+        driver.type_text("oat ");
+        driver.press_with(ModifiersState::CONTROL, KeyCode::KeyV);
+        driver.press(KeyCode::Enter);
+
+        // Pasted lines are joined into one, and editing steps over whole characters
+        driver.clipboard().set_text("Crème\nbrûlée\n");
+        driver.widget_mut().add_and_continue();
+        driver.press_with(ModifiersState::CONTROL, KeyCode::KeyA);
+        driver.press_with(ModifiersState::CONTROL, KeyCode::KeyV);
+        driver.press(KeyCode::Backspace);
+        driver.press_with(ModifiersState::CONTROL, KeyCode::ArrowLeft);
+        for _ in 0..3 {
+            driver.press(KeyCode::ArrowLeft);
+        }
+        driver.press(KeyCode::Backspace);
+        driver.type_text("è");
+        driver.press_with(ModifiersState::CONTROL, KeyCode::KeyA);
+        driver.press_with(ModifiersState::CONTROL, KeyCode::KeyC);
+        driver.press(KeyCode::Enter);
+
+        let titles: Vec<String> = driver.take_events().iter()
+            .filter_map(|event| match event {
+                UiEvent::TaskAdded { id } => driver.task(*id).map(|task| task.title().to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(titles, ["Buy oat milk", "Crème brûlé"]);
+        assert_eq!(driver.clipboard().text().as_deref(), Some("Crème brûlé"));
+    }
+
+    #[test]
+    fn test_composed_and_multibyte_text() {
+        let (mut driver, _) = UiDriver::with_tasks(&[]);
+        driver.widget_mut().add_and_continue();
+        let caret = driver.widget().caret_bounds().unwrap();
+
+        // Nothing is typed while an input method composes, only once it commits
+        let text = |text: &str| InputEvent::Text(TextEvent::Insert(text.to_string()));
+        let preedit = |text: &str| InputEvent::Text(TextEvent::Preedit { text: text.to_string(), cursor: None });
+        let key = |text: &str| InputEvent::Key {
+            key: crate::ui::input::KeyName::Character(text.to_string()),
+            pressed: true,
+            repeat: false,
+            text: Some(text.to_string()),
+        };
+        let recording: Vec<TimedInput> = [preedit("にほん"), preedit("日本"), text("日本"), key("é"), key("🍵")]
+            .into_iter()
+            .map(|input| TimedInput { frame: 0, time: 0.0, input })
+            .collect();
+        driver.replay(&recording[..2]);
+        assert_eq!(driver.widget().caret_bounds(), Some(caret));
+        driver.replay(&recording[2..]);
+        assert!(driver.widget().caret_bounds().unwrap().0 > caret.0);
+        driver.press(KeyCode::Enter);
+
+        let events = driver.take_events();
+        let [UiEvent::TaskAdded { id }] = events[..] else {
+            panic!("expected one added task, got {:?}", events);
+        };
+        assert_eq!(driver.task(id).unwrap().title(), "日本é🍵");
+    }
+}
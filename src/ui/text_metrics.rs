@@ -0,0 +1,169 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use wgpu_glyph::ab_glyph::{Font, FontArc, GlyphId, ScaleFont};
+
+use crate::ui::fonts;
+
+/// Advance widths of the UI fonts' glyphs, cached per text size
+///
+/// Widgets lay out text and hit-test clicks outside the render pass, so the
+/// metrics are shared process-wide rather than kept on the `RenderContext`.
+/// Until the app has loaded its fonts (and in headless tests) widths are
+/// estimated instead.
+pub struct TextMetrics {
+    /// The UI font, then its fallbacks, as the glyph brush has them
+    fonts: Vec<FontArc>,
+    /// Advance of each character measured so far, keyed by the size's bits
+    advances: HashMap<u32, HashMap<char, f32>>,
+}
+
+impl TextMetrics {
+    pub fn new(fonts: Vec<FontArc>) -> Self {
+        Self { fonts, advances: HashMap::new() }
+    }
+
+    /// Width of a line of text at a size, kerning included
+    ///
+    /// Each character is measured in the font that draws it; kerning only
+    /// applies between neighbours in the same font.
+    pub fn width(&mut self, text: &str, size: f32) -> f32 {
+        let advances = self.advances.entry(size.to_bits()).or_default();
+        let mut width = 0.0;
+        let mut previous: Option<(usize, GlyphId)> = None;
+        for c in text.chars() {
+            let font = fonts::font_for(&self.fonts, c);
+            let scaled = self.fonts[font].as_scaled(size);
+            let id = scaled.glyph_id(c);
+            width += *advances.entry(c).or_insert_with(|| scaled.h_advance(id));
+            if let Some((previous_font, previous)) = previous {
+                if previous_font == font {
+                    width += scaled.kern(previous, id);
+                }
+            }
+            previous = Some((font, id));
+        }
+        width
+    }
+}
+
+fn metrics() -> &'static RwLock<Option<TextMetrics>> {
+    static METRICS: OnceLock<RwLock<Option<TextMetrics>>> = OnceLock::new();
+    METRICS.get_or_init(|| RwLock::new(None))
+}
+
+/// Measure text with these fonts from now on; the app calls it with the ones it draws with
+pub fn set_fonts(fonts: Vec<FontArc>) {
+    *metrics().write().unwrap_or_else(|e| e.into_inner()) = Some(TextMetrics::new(fonts));
+}
+
+/// Width of a line of text at a size, in pixels
+pub fn text_width(text: &str, size: f32) -> f32 {
+    match metrics().write().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        Some(metrics) => metrics.width(text, size),
+        None => estimate_width(text, size),
+    }
+}
+
+/// Width of a line of text assuming every character is half as wide as the text is tall
+fn estimate_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * 0.5
+}
+
+/// Byte length of the longest start of `text` no wider than `max_width`
+fn fitting_len(text: &str, max_width: f32, size: f32) -> usize {
+    let ends: Vec<usize> = text.char_indices().map(|(byte, c)| byte + c.len_utf8()).collect();
+    let fitting = ends.partition_point(|&end| text_width(&text[..end], size) <= max_width);
+    fitting.checked_sub(1).map_or(0, |last| ends[last])
+}
+
+/// The text cut short with "…" so it fits in `max_width`, or unchanged if it already fits
+pub fn truncate(text: &str, max_width: f32, size: f32) -> Cow<'_, str> {
+    if text_width(text, size) <= max_width {
+        return Cow::Borrowed(text);
+    }
+    let end = fitting_len(text, max_width - text_width("…", size), size);
+    Cow::Owned(format!("{}…", text[..end].trim_end()))
+}
+
+/// Break text into lines no wider than `max_width`
+///
+/// Lines break at spaces where they can, and inside words too long for a
+/// line of their own. Line breaks in the text are kept.
+pub fn wrap(text: &str, max_width: f32, size: f32) -> Vec<&str> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut rest = paragraph;
+        while text_width(rest, size) > max_width {
+            // At least one character per line, however narrow the bounds
+            let fits = fitting_len(rest, max_width, size)
+                .max(rest.chars().next().map_or(0, char::len_utf8));
+            let end = if rest[fits..].starts_with(char::is_whitespace) {
+                fits
+            } else {
+                rest[..fits].rfind(char::is_whitespace).filter(|&space| space > 0).unwrap_or(fits)
+            };
+            lines.push(rest[..end].trim_end());
+            rest = rest[end..].trim_start();
+        }
+        if !rest.is_empty() || paragraph.is_empty() {
+            lines.push(rest);
+        }
+    }
+    if lines.is_empty() {
+        lines.push("");
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widths_follow_the_font() {
+        let font = FontArc::try_from_vec(std::fs::read("fonts/Inconsolata-Regular.ttf").unwrap()).unwrap();
+        let advance = font.as_scaled(20.0).h_advance(font.glyph_id('m'));
+        let mut metrics = TextMetrics::new(vec![font]);
+
+        assert_eq!(metrics.width("", 20.0), 0.0);
+        assert_eq!(metrics.width("m", 20.0), advance);
+        // The UI font is monospaced, so longer text grows by whole advances
+        assert!((metrics.width("mmmm", 20.0) - advance * 4.0).abs() < 0.01);
+        assert!((metrics.width("m", 40.0) - advance * 2.0).abs() < 0.01);
+        assert_eq!(metrics.advances.len(), 2);
+    }
+
+    #[test]
+    fn test_fallback_glyphs_are_measured_in_their_font() {
+        let fonts = fonts::bundled();
+        let pencil = fonts[1].as_scaled(20.0).h_advance(fonts[1].glyph_id('✎'));
+        let m = fonts[0].as_scaled(20.0).h_advance(fonts[0].glyph_id('m'));
+        let mut metrics = TextMetrics::new(fonts);
+
+        assert_eq!(metrics.width("✎", 20.0), pencil);
+        assert!((metrics.width("m✎m", 20.0) - (pencil + m * 2.0)).abs() < 0.01);
+    }
+
+    // Without a font set, every character at size 10 is 5 pixels wide
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate("Short", 30.0, 10.0), "Short");
+        assert_eq!(truncate("Hello world", 30.0, 10.0), "Hello…");
+        // Spaces before the ellipsis are dropped
+        assert_eq!(truncate("Hello world", 35.0, 10.0), "Hello…");
+        assert_eq!(truncate("Grüße aus Köln", 40.0, 10.0), "Grüße a…");
+        assert_eq!(truncate("Hello", 4.0, 10.0), "…");
+    }
+
+    #[test]
+    fn test_wrap_at_spaces() {
+        assert_eq!(wrap("one two three", 40.0, 10.0), ["one two", "three"]);
+        assert_eq!(wrap("one  two", 20.0, 10.0), ["one", "two"]);
+        assert_eq!(wrap("abcdefghij", 40.0, 10.0), ["abcdefgh", "ij"]);
+        assert_eq!(wrap("first\n\nthird line", 40.0, 10.0), ["first", "", "third", "line"]);
+        assert_eq!(wrap("", 40.0, 10.0), [""]);
+        assert_eq!(wrap("wide", 1.0, 10.0), ["w", "i", "d", "e"]);
+    }
+}
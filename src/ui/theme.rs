@@ -1,3 +1,6 @@
+use crate::core::prelude::Priority;
+use crate::ui::Gradient;
+
 /// CyberpunkTheme encapsulates the visual styling for the UI
 #[derive(Debug, Clone)]
 pub struct CyberpunkTheme {
@@ -10,6 +13,14 @@ impl CyberpunkTheme {
         Self {}
     }
     
+    /// The theme a config names, one of `config::THEMES`
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "cyberpunk" => Some(Self::new()),
+            _ => None,
+        }
+    }
+    
     /// Get neon pink as [r, g, b, a]
     pub fn neon_pink(&self) -> [f32; 4] {
         [1.0, 0.255, 0.639, 1.0] // #FF41A3
@@ -70,6 +81,11 @@ impl CyberpunkTheme {
         [0.3, 1.0, 0.5, 1.0] // Green-ish success
     }
     
+    /// Get link color as [r, g, b, a]
+    pub fn link(&self) -> [f32; 4] {
+        [0.35, 0.8, 1.0, 1.0] // Sky blue, apart from the cyan of tags
+    }
+    
     /// Get default text size
     pub fn text_size(&self) -> f32 {
         18.0 // Increased from 16.0
@@ -182,11 +198,35 @@ impl CyberpunkTheme {
         [0.0, 0.0, 0.0, 0.5] // Semi-transparent shadow
     }
     
+    /// Get how far shadows fall below and to the right of what casts them
+    pub fn shadow_offset(&self) -> (f32, f32) {
+        (4.0, 6.0)
+    }
+    
+    /// Get how many pixels shadow edges blur over
+    pub fn shadow_softness(&self) -> f32 {
+        10.0
+    }
+    
     /// Get modal warning color (for overdue tasks, etc.)
     pub fn modal_warning(&self) -> [f32; 4] {
         [1.0, 0.5, 0.2, 1.0] // Orange-ish warning
     }
     
+    // Gradients
+    
+    /// Get the synthwave gradient across modal headers, magenta-purple into the header blue
+    pub fn header_gradient(&self) -> Gradient {
+        Gradient::horizontal([0.3, 0.06, 0.4, 1.0], self.get_modal_header_color())
+    }
+    
+    /// Get the gradient down a priority stripe, from the priority's color into neon pink
+    pub fn priority_gradient(&self, color: [f32; 4]) -> Gradient {
+        let pink = self.neon_pink();
+        let bottom = std::array::from_fn(|channel| color[channel] + (pink[channel] - color[channel]) * 0.6);
+        Gradient::vertical(color, bottom)
+    }
+    
     // Priority colors
     
     /// Priority colors for Critical priority
@@ -209,11 +249,40 @@ impl CyberpunkTheme {
         [0.3, 0.8, 0.3, 1.0] // Green
     }
     
+    /// Get the color a task of a priority is drawn in when it has no project
+    pub fn priority_accent(&self, priority: Priority) -> [f32; 4] {
+        match priority {
+            Priority::High => self.priority_high(),
+            Priority::Medium => self.priority_medium(),
+            Priority::Low => self.priority_low(),
+        }
+    }
+    
     /// Priority colors for None priority
     pub fn priority_none(&self) -> [f32; 4] {
         [0.4, 0.4, 0.4, 0.7] // Grey
     }
     
+    // Project colors
+    
+    /// Accent colors new projects take in turn, as red, green and blue
+    pub fn project_palette(&self) -> [[u8; 3]; 6] {
+        [
+            [255, 42, 109],  // Neon pink
+            [5, 217, 232],   // Cyan
+            [170, 90, 255],  // Violet
+            [255, 140, 40],  // Orange
+            [60, 220, 120],  // Green
+            [250, 220, 60],  // Yellow
+        ]
+    }
+    
+    /// Get a project's accent color as [r, g, b, a]
+    pub fn project_accent(&self, color: [u8; 3]) -> [f32; 4] {
+        let [r, g, b] = color.map(|channel| channel as f32 / 255.0);
+        [r, g, b, 1.0]
+    }
+    
     // Todo item specific colors
     
     /// Checkbox border color
@@ -266,6 +335,11 @@ impl CyberpunkTheme {
         [0.15, 0.15, 0.25, 0.5] // Slightly brighter when hovered
     }
     
+    /// Background of tasks picked for bulk actions
+    pub fn item_marked_bg(&self) -> [f32; 4] {
+        [0.35, 0.12, 0.4, 0.6] // Translucent purple
+    }
+    
     /// Task title text color when normal
     pub fn text_normal(&self) -> [f32; 4] {
         [0.9, 0.9, 1.0, 1.0] // Nearly white
@@ -0,0 +1,139 @@
+use crate::ui::{RenderContext, Widget, CyberpunkTheme};
+
+/// Seconds a toast stays on screen
+const TOAST_DURATION: f32 = 5.0;
+
+/// Seconds spent fading out at the end
+const TOAST_FADE: f32 = 0.5;
+
+const TOAST_WIDTH: f32 = 420.0;
+const TOAST_HEIGHT: f32 = 44.0;
+const TOAST_SPACING: f32 = 8.0;
+const TOAST_MARGIN: f32 = 20.0;
+
+/// What kind of message a toast carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Error,
+}
+
+struct Toast {
+    message: String,
+    kind: ToastKind,
+    remaining: f32,
+}
+
+/// Short-lived notifications stacked in the bottom-right corner
+pub struct ToastManager {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    toasts: Vec<Toast>,
+    theme: CyberpunkTheme,
+}
+
+impl ToastManager {
+    /// Create an empty toast stack covering a screen of the given size
+    pub fn new(screen_width: f32, screen_height: f32) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: screen_width,
+            height: screen_height,
+            toasts: Vec::new(),
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Show an informational message
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(message, ToastKind::Info);
+    }
+
+    /// Show an error message
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(message, ToastKind::Error);
+    }
+
+    /// Show a message of the given kind
+    pub fn push(&mut self, message: impl Into<String>, kind: ToastKind) {
+        self.toasts.push(Toast {
+            message: message.into(),
+            kind,
+            remaining: TOAST_DURATION,
+        });
+    }
+
+    /// Check if any toasts are showing
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+}
+
+impl Widget for ToastManager {
+    fn update(&mut self, delta_time: f32) {
+        for toast in &mut self.toasts {
+            toast.remaining -= delta_time;
+        }
+        self.toasts.retain(|toast| toast.remaining > 0.0);
+    }
+
+    fn needs_update(&self) -> bool {
+        !self.toasts.is_empty()
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let toast_width = TOAST_WIDTH.min(self.width - TOAST_MARGIN * 2.0);
+        let x = self.x + self.width - toast_width - TOAST_MARGIN;
+        let mut y = self.y + self.height - TOAST_MARGIN - TOAST_HEIGHT;
+
+        // Newest toast at the bottom, older ones stacked above it
+        for toast in self.toasts.iter().rev() {
+            let alpha = (toast.remaining / TOAST_FADE).min(1.0);
+            let accent = match toast.kind {
+                ToastKind::Info => self.theme.cyan(),
+                ToastKind::Error => self.theme.danger(),
+            };
+            let mut background = self.theme.panel_background();
+            background[3] *= alpha;
+            let mut text_color = self.theme.bright_text();
+            text_color[3] *= alpha;
+            let mut accent_color = accent;
+            accent_color[3] *= alpha;
+
+            ctx.draw_rect(x, y, toast_width, TOAST_HEIGHT, background);
+            ctx.draw_rect(x, y, 4.0, TOAST_HEIGHT, accent_color);
+            ctx.draw_text(
+                &toast.message,
+                x + 16.0, y + 12.0,
+                self.theme.small_text_size(),
+                text_color,
+            );
+
+            y -= TOAST_HEIGHT + TOAST_SPACING;
+            if y < self.y {
+                break;
+            }
+        }
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+}
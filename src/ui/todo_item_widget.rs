@@ -1,19 +1,85 @@
 use wgpu::Color;
-use std::sync::Arc;
-use crate::ui::{RenderContext, Widget, Button, Panel};
-use crate::core::prelude::{TodoItem, Status, Priority};
+use crate::ui::{RenderContext, TextOverflow, Widget, WidgetInfo, Button, Panel, Quad, UiEvent, EventQueue};
+use crate::ui::context::TextSize;
+use crate::ui::damage;
+use crate::ui::markdown::{self, Document};
+use crate::ui::text_metrics;
+use crate::core::prelude::{TodoItem, TodoList, Status, Priority};
+use crate::i18n::{self, tr, tr_args};
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use winit::window::CursorIcon;
 use crate::ui::CyberpunkTheme;
 
+/// Size of the text on tag chips, and the chips' height and inner margin
+const TAG_TEXT_SIZE: f32 = 14.0;
+const TAG_CHIP_HEIGHT: f32 = 20.0;
+const TAG_CHIP_PADDING: f32 = 6.0;
+
+/// Width of the ▶/▼ toggle before the checkbox, kept free on rows without children too
+const DISCLOSURE_WIDTH: f32 = 16.0;
+
+/// Size of the done/total count on parents' rows, and the width of the bar after it
+const PROGRESS_TEXT_SIZE: f32 = 14.0;
+const PROGRESS_BAR_WIDTH: f32 = 28.0;
+
+/// Seconds a row takes to slide in or out when its parent is expanded or collapsed
+const REVEAL_DURATION: f32 = 0.15;
+
+/// Size of the title's text on the row
+const TITLE_TEXT_SIZE: f32 = 24.0;
+
+/// Size of the text of the description in the details modal, and of the attachments' names
+const DESCRIPTION_TEXT_SIZE: f32 = 16.0;
+/// Space between the names of attachments in the details modal
+const ATTACHMENT_GAP: f32 = 16.0;
+
+/// Position and size of the details modal in a window of the given size
+fn modal_bounds(ctx_width: f32, ctx_height: f32) -> (f32, f32, f32, f32) {
+    let modal_width = ctx_width.min(600.0);
+    let modal_height = ctx_height.min(400.0);
+    ((ctx_width - modal_width) / 2.0, (ctx_height - modal_height) / 2.0, modal_width, modal_height)
+}
+
+/// Width of the chip showing a tag, `#` included
+pub(crate) fn tag_chip_width(tag: &str) -> f32 {
+    TextSize::measure(&format!("#{}", tag), TAG_TEXT_SIZE).width + TAG_CHIP_PADDING * 2.0
+}
+
 /// A widget for displaying and interacting with a TodoItem
+///
+/// The widget only knows its item's id and reads the item from the shared list
+/// whenever it draws or is clicked, so edits made anywhere show up right away.
 pub struct TodoItemWidget {
     x: f32,
     y: f32,
     width: f32,
     height: f32,
-    pub todo_item: TodoItem,
+    id: Uuid,
+    todo_list: Arc<Mutex<TodoList>>,
     is_expanded: bool,
     is_hovered: bool,
+    is_selected: bool,
+    /// Picked for bulk actions, with Ctrl+click and the like
+    is_marked: bool,
+    hovered_tag: Option<usize>,
+    /// Index of the link in the title under the mouse
+    hovered_link: Option<usize>,
     hierarchy_level: usize,  // 0 for root items, 1+ for nested items
+    /// None for items without children, else whether their rows are hidden
+    children_collapsed: Option<bool>,
+    /// For items with children, how many of them are completed, and how many there are
+    child_progress: Option<(usize, usize)>,
+    /// Accent color of the item's project, drawn where its priority's would be
+    accent: Option<[f32; 4]>,
+    /// Byte offsets of the title's characters the search matched
+    title_matches: Vec<usize>,
+    
+    // How much of the row shows, from 0.0 to 1.0, easing toward all of it
+    // (`revealing`) or none while it slides in or out under its parent
+    reveal: f32,
+    revealing: bool,
     
     // UI components
     pub checkbox_button: Button,
@@ -21,10 +87,8 @@ pub struct TodoItemWidget {
     pub delete_button: Button,
     panel: Panel,
     
-    // Callbacks
-    pub on_status_change: Option<Arc<dyn Fn(Status) + Send + Sync>>,
-    pub on_edit: Option<Arc<dyn Fn() + Send + Sync>>,
-    pub on_delete: Option<Arc<dyn Fn() + Send + Sync>>,
+    // Events emitted by clicks, drained by the owning list
+    events: EventQueue,
     
     // Theme
     theme: CyberpunkTheme,
@@ -32,84 +96,59 @@ pub struct TodoItemWidget {
     // Close button bounds for modal (x, y, width, height)
     close_button_bounds: Option<(f32, f32, f32, f32)>,
     is_close_button_hovered: bool,
+    
+    // Geometry or expansion changed; close button bounds are recomputed on the next update
+    needs_layout: bool,
 }
 
 // Manual implementation of Clone for TodoItemWidget
 impl Clone for TodoItemWidget {
     fn clone(&self) -> Self {
-        let mut clone = Self {
+        Self {
             x: self.x,
             y: self.y,
             width: self.width,
             height: self.height,
-            todo_item: self.todo_item.clone(),
+            id: self.id,
+            todo_list: self.todo_list.clone(),
             is_expanded: self.is_expanded,
             is_hovered: self.is_hovered,
+            is_selected: self.is_selected,
+            is_marked: self.is_marked,
+            hovered_tag: self.hovered_tag,
+            hovered_link: self.hovered_link,
             hierarchy_level: self.hierarchy_level,
+            children_collapsed: self.children_collapsed,
+            child_progress: self.child_progress,
+            accent: self.accent,
+            title_matches: self.title_matches.clone(),
+            reveal: self.reveal,
+            revealing: self.revealing,
             checkbox_button: self.checkbox_button.clone(),
             edit_button: self.edit_button.clone(),
             delete_button: self.delete_button.clone(),
             panel: self.panel.clone(),
-            on_status_change: None, // Cannot clone function pointers easily
-            on_edit: None,          // Cannot clone function pointers easily
-            on_delete: None,        // Cannot clone function pointers easily
-            theme: CyberpunkTheme::new(), // Theme is stateless, just create a new one
-            close_button_bounds: self.close_button_bounds.clone(),
+            events: EventQueue::new(), // Pending events belong to the original
+            theme: self.theme.clone(),
+            close_button_bounds: self.close_button_bounds,
             is_close_button_hovered: self.is_close_button_hovered,
-        };
-        
-        // Manually clone the function pointers by wrapping them
-        if let Some(f) = &self.on_status_change {
-            let f_clone = f.clone();
-            clone.on_status_change = Some(f_clone);
-        }
-        
-        if let Some(f) = &self.on_edit {
-            let f_clone = f.clone();
-            clone.on_edit = Some(f_clone);
+            needs_layout: self.needs_layout,
         }
-        
-        if let Some(f) = &self.on_delete {
-            let f_clone = f.clone();
-            clone.on_delete = Some(f_clone);
-        }
-        
-        clone
     }
 }
 
 impl TodoItemWidget {
-    /// Create a new TodoItemWidget
-    pub fn new(x: f32, y: f32, width: f32, todo_item: TodoItem) -> Self {
+    /// Create a new TodoItemWidget showing the item `id` of `todo_list`
+    pub fn new(x: f32, y: f32, width: f32, id: Uuid, todo_list: Arc<Mutex<TodoList>>) -> Self {
         let theme = CyberpunkTheme::new();
         let item_height = theme.todo_item_height(); // Use theme value instead of hardcoded
         
         // Create panel with theme values
-        let panel_bg = match todo_item.priority() {
-            Priority::High => Color {
-                r: 0.18,
-                g: 0.12,
-                b: 0.14,
-                a: 0.85,
-            },
-            Priority::Medium => Color {
-                r: 0.16,
-                g: 0.14,
-                b: 0.12,
-                a: 0.85,
-            },
-            Priority::Low => Color {
-                r: 0.12,
-                g: 0.16,
-                b: 0.12,
-                a: 0.85,
-            },
-            _ => Color {
-                r: 0.12,
-                g: 0.12,
-                b: 0.16,
-                a: 0.85,
-            }
+        let panel_bg = Color {
+            r: 0.12,
+            g: 0.12,
+            b: 0.16,
+            a: 0.85,
         };
         
         let panel = Panel::new(x, y, width, item_height)
@@ -118,13 +157,13 @@ impl TodoItemWidget {
         // Calculate button size based on theme values
         let button_size = item_height * 0.5;
         
-        // Create the checkbox button
+        // Create the checkbox button; the check mark is drawn from the item's status
         let checkbox_button = Button::new(
-            x + 10.0,
+            x + 10.0 + DISCLOSURE_WIDTH,
             y + (item_height - button_size) / 2.0,
             button_size, 
             button_size, 
-            if todo_item.is_completed() { "✓" } else { " " }
+            " "
         ).with_text_color(Color {
             r: 0.0,
             g: 0.9,
@@ -164,26 +203,53 @@ impl TodoItemWidget {
             y,
             width,
             height: item_height,
-            todo_item,
+            id,
+            todo_list,
             is_expanded: false,
             is_hovered: false,
+            is_selected: false,
+            is_marked: false,
+            hovered_tag: None,
+            hovered_link: None,
             hierarchy_level: 0,
+            children_collapsed: None,
+            child_progress: None,
+            accent: None,
+            title_matches: Vec::new(),
+            reveal: 1.0,
+            revealing: true,
             checkbox_button,
             edit_button,
             delete_button,
             panel,
-            on_status_change: None,
-            on_edit: None,
-            on_delete: None,
+            events: EventQueue::new(),
             theme,
             close_button_bounds: None,
             is_close_button_hovered: false,
+            needs_layout: true,
         }
     }
     
+    /// Draw the row and its details in `theme` instead of the default one
+    pub fn with_theme(mut self, theme: CyberpunkTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+    
     /// Set the hierarchy level for this item
     pub fn with_hierarchy_level(mut self, level: usize) -> Self {
+        self.set_hierarchy_level(level);
+        self
+    }
+    
+    /// Indent the row for an item `level` deep in the hierarchy
+    pub fn set_hierarchy_level(&mut self, level: usize) {
+        if level == self.hierarchy_level {
+            return;
+        }
         self.hierarchy_level = level;
+        let (checkbox_x, checkbox_y) = (self.checkbox_x(), self.checkbox_button.position().1);
+        self.checkbox_button.set_position(checkbox_x, checkbox_y);
         
         // Adjust the panel style based on the hierarchy level
         if level > 0 {
@@ -198,28 +264,107 @@ impl TodoItemWidget {
                 b: b as f64,
                 a: 0.9 + (level as f32 * 0.02).min(0.1) as f64,
             };
-            self.panel = self.panel.with_background_color(bg_color);
+            self.panel = self.panel.clone().with_background_color(bg_color);
         }
-        
-        self
     }
     
-    /// Set callback for when status changes
-    pub fn with_on_status_change<F: Fn(Status) + Send + Sync + 'static>(mut self, callback: F) -> Self {
-        self.on_status_change = Some(Arc::new(callback));
-        self
+    pub fn hierarchy_level(&self) -> usize {
+        self.hierarchy_level
     }
     
-    /// Set callback for when edit button is clicked
-    pub fn with_on_edit<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
-        self.on_edit = Some(Arc::new(callback));
-        self
+    /// Show the ▶/▼ toggle for an item with children, collapsed or not, or hide it (None)
+    pub fn set_children_collapsed(&mut self, collapsed: Option<bool>) {
+        self.children_collapsed = collapsed;
     }
     
-    /// Set callback for when delete button is clicked
-    pub fn with_on_delete<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
-        self.on_delete = Some(Arc::new(callback));
-        self
+    /// Show how many of the item's children are completed, out of how many, or nothing (None)
+    pub fn set_child_progress(&mut self, progress: Option<(usize, usize)>) {
+        self.child_progress = progress;
+    }
+    
+    /// How many of the item's children are completed, out of how many, for items with children
+    pub fn child_progress(&self) -> Option<(usize, usize)> {
+        self.child_progress
+    }
+    
+    /// Text of the subtask count, e.g. "2/5"
+    fn progress_text(&self) -> Option<String> {
+        self.child_progress.map(|(done, total)| format!("{}/{}", done, total))
+    }
+    
+    /// Color the stripe and glow with the accent of the item's project, or its priority's color (None)
+    pub fn set_accent(&mut self, accent: Option<[f32; 4]>) {
+        self.accent = accent;
+    }
+    
+    /// Highlight the title's characters at these byte offsets, where the search matched
+    pub fn set_title_matches(&mut self, positions: Vec<usize>) {
+        self.title_matches = positions;
+    }
+    
+    /// Bounds of the ▶/▼ toggle, on rows of items with children
+    pub fn disclosure_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        self.children_collapsed?;
+        Some((self.indent_x(), self.y + (self.height - 20.0) / 2.0, DISCLOSURE_WIDTH, 20.0))
+    }
+    
+    fn disclosure_contains(&self, x: f32, y: f32) -> bool {
+        self.contains_point(x, y) && self.disclosure_bounds()
+            .is_some_and(|(bx, by, bw, bh)| x >= bx && x <= bx + bw && y >= by && y <= by + bh)
+    }
+    
+    /// Slide the row in under its parent, from nothing if `from_hidden`, else from where it is
+    pub fn slide_in(&mut self, from_hidden: bool) {
+        if from_hidden {
+            self.reveal = 0.0;
+        }
+        self.revealing = true;
+    }
+    
+    /// Slide the row out under its parent; the list drops it once it's gone
+    pub fn slide_out(&mut self) {
+        self.revealing = false;
+    }
+    
+    /// Whether the row is sliding out, or already hidden
+    pub fn is_sliding_out(&self) -> bool {
+        !self.revealing
+    }
+    
+    /// Whether the row is sliding in or out
+    pub fn is_sliding(&self) -> bool {
+        if self.revealing { self.reveal < 1.0 } else { self.reveal > 0.0 }
+    }
+    
+    /// Whether the row slid out all the way
+    pub fn is_hidden(&self) -> bool {
+        !self.revealing && self.reveal <= 0.0
+    }
+    
+    /// How much of the row shows while it slides in or out, from 0.0 to 1.0
+    pub fn reveal(&self) -> f32 {
+        self.reveal
+    }
+    
+    /// Height of the part of the row that shows, at its bottom
+    fn visible_height(&self) -> f32 {
+        self.height * self.reveal
+    }
+    
+    /// Id of the item shown
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+    
+    /// Run `f` on the current version of the item, if it still exists
+    fn with_item<R>(&self, f: impl FnOnce(&TodoItem) -> R) -> Option<R> {
+        let todo_list = self.todo_list.lock().ok()?;
+        todo_list.get_item(self.id).map(f)
+    }
+    
+    /// Take the events emitted since the last call
+    pub fn take_events(&mut self) -> Vec<UiEvent> {
+        self.events.drain()
     }
     
     /// Check if the widget is currently expanded
@@ -230,19 +375,151 @@ impl TodoItemWidget {
     /// Toggle expanded state
     pub fn toggle_expanded(&mut self) {
         self.is_expanded = !self.is_expanded;
+        self.needs_layout = true;
+    }
+    
+    /// Open or close the details modal
+    pub fn set_expanded(&mut self, expanded: bool) {
+        self.is_expanded = expanded;
+        self.needs_layout = true;
+    }
+    
+    /// Check if the mouse is over the row
+    pub fn is_hovered(&self) -> bool {
+        self.is_hovered
+    }
+    
+    /// Check if the item has the keyboard selection
+    pub fn is_selected(&self) -> bool {
+        self.is_selected
+    }
+    
+    /// Mark the item as (not) having the keyboard selection
+    pub fn set_selected(&mut self, selected: bool) {
+        self.is_selected = selected;
+    }
+    
+    /// Check if the item is picked for bulk actions
+    pub fn is_marked(&self) -> bool {
+        self.is_marked
+    }
+    
+    /// Highlight the item as (not) picked for bulk actions
+    pub fn set_marked(&mut self, marked: bool) {
+        self.is_marked = marked;
+    }
+    
+    /// Left edge of the ▶/▼ toggle, after the indent
+    fn indent_x(&self) -> f32 {
+        self.x + 10.0 + (self.hierarchy_level as f32 * 15.0)
+    }
+    
+    /// Left edge of the checkbox, after the ▶/▼ toggle
+    fn checkbox_x(&self) -> f32 {
+        self.indent_x() + DISCLOSURE_WIDTH
+    }
+    
+    /// Left edge of the title, after the indent and the checkbox
+    fn title_x(&self) -> f32 {
+        self.checkbox_x() + 30.0
+    }
+    
+    /// Right edge of the room for the title and tags, before the subtask count, the due date and the buttons
+    fn content_limit(&self, todo_item: &TodoItem) -> f32 {
+        let due_width = todo_item.due_date()
+            .map_or(0.0, |due| TextSize::measure(&i18n::format_date(due), 16.0).width + 32.0);
+        let progress_width = self.progress_text().map_or(0.0, |text| {
+            TextSize::measure(&text, PROGRESS_TEXT_SIZE).width + PROGRESS_BAR_WIDTH + 18.0
+        });
+        self.x + self.width - 100.0 - due_width - progress_width
+    }
+    
+    /// Widest the title is drawn; longer titles end in an ellipsis
+    fn title_max_width(&self, todo_item: &TodoItem) -> f32 {
+        (self.content_limit(todo_item) - self.title_x()).max(0.0)
+    }
+    
+    /// Left edge and width of each tag chip, in the row after the title
+    ///
+    /// Chips that would run into the due date or the buttons are left out.
+    fn tag_chips(&self, todo_item: &TodoItem) -> Vec<(f32, f32)> {
+        let limit = self.content_limit(todo_item);
+        let title_width = TextSize::measure(todo_item.title(), TITLE_TEXT_SIZE).width.min(self.title_max_width(todo_item));
+        let mut x = self.title_x() + title_width + 12.0;
+        let mut chips = Vec::new();
+        for tag in todo_item.tags() {
+            let width = tag_chip_width(tag);
+            if x + width > limit {
+                break;
+            }
+            chips.push((x, width));
+            x += width + 6.0;
+        }
+        chips
+    }
+    
+    /// Index of the tag whose chip is under a point
+    fn tag_at(&self, x: f32, y: f32) -> Option<usize> {
+        let chip_y = self.y + (self.height - TAG_CHIP_HEIGHT) / 2.0;
+        if self.is_expanded || y < chip_y || y > chip_y + TAG_CHIP_HEIGHT {
+            return None;
+        }
+        self.with_item(|todo_item| {
+            self.tag_chips(todo_item).iter().position(|&(chip_x, width)| x >= chip_x && x <= chip_x + width)
+        }).flatten()
+    }
+    
+    /// Top of the title's text on the row
+    fn title_y(&self) -> f32 {
+        self.y + (self.height - TITLE_TEXT_SIZE) / 2.0 - 2.0
+    }
+    
+    /// Byte range, left edge and width of each web address in the title, as far as it shows
+    fn title_links(&self, todo_item: &TodoItem) -> Vec<(std::ops::Range<usize>, f32, f32)> {
+        let title = todo_item.title();
+        let shown = text_metrics::truncate(title, self.title_max_width(todo_item), TITLE_TEXT_SIZE);
+        // Up to the ellipsis, the shown title is the title
+        let kept = match shown {
+            Cow::Borrowed(_) => title.len(),
+            Cow::Owned(shown) => shown.len() - "…".len(),
+        };
+        let title_x = self.title_x();
+        markdown::find_urls(title)
+            .into_iter()
+            .take_while(|url| url.start < kept)
+            .map(|url| {
+                let left = title_x + text_metrics::text_width(&title[..url.start], TITLE_TEXT_SIZE);
+                let width = text_metrics::text_width(&title[url.start..url.end.min(kept)], TITLE_TEXT_SIZE);
+                (url, left, width)
+            })
+            .collect()
+    }
+    
+    /// Index of the link in the title under a point
+    fn link_at(&self, x: f32, y: f32) -> Option<usize> {
+        let title_y = self.title_y();
+        if self.is_expanded || y < title_y || y > title_y + TITLE_TEXT_SIZE + 4.0 {
+            return None;
+        }
+        self.with_item(|todo_item| {
+            self.title_links(todo_item).iter().position(|&(_, left, width)| x >= left && x <= left + width)
+        }).flatten()
     }
     
     /// Handle mouse move event
     pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
         // Update hover state
-        self.is_hovered = self.contains_point(x, y);
+        let hovered = self.contains_point(x, y);
+        damage::set(&mut self.is_hovered, hovered);
+        let hovered_tag = self.tag_at(x, y);
+        damage::set(&mut self.hovered_tag, hovered_tag);
+        let hovered_link = self.link_at(x, y);
+        damage::set(&mut self.hovered_link, hovered_link);
         
         // Check if hovering over the close button
-        if let Some((bx, by, bw, bh)) = self.close_button_bounds {
-            self.is_close_button_hovered = x >= bx && x <= bx + bw && y >= by && y <= by + bh;
-        } else {
-            self.is_close_button_hovered = false;
-        }
+        let close_hovered = self.close_button_bounds
+            .is_some_and(|(bx, by, bw, bh)| x >= bx && x <= bx + bw && y >= by && y <= by + bh);
+        damage::set(&mut self.is_close_button_hovered, close_hovered);
         
         // Update other button states
         self.checkbox_button.handle_mouse_move(x, y);
@@ -251,18 +528,22 @@ impl TodoItemWidget {
     }
     
     /// Handle mouse down event
-    pub fn handle_mouse_down(&mut self, x: f32, y: f32) {
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32, click_count: u32) {
         // Propagate to child buttons
         self.checkbox_button.handle_mouse_down(x, y);
         self.edit_button.handle_mouse_down(x, y);
         self.delete_button.handle_mouse_down(x, y);
         
-        // Toggle expanded state when clicking on the main item area
+        // Toggle expanded state when double-clicking on the main item area
         // (but not on the buttons)
-        if self.is_hovered && 
+        if click_count == 2 &&
+           self.contains_point(x, y) && 
            !self.checkbox_button.contains_point(x, y) &&
            !self.edit_button.contains_point(x, y) &&
-           !self.delete_button.contains_point(x, y) {
+           !self.delete_button.contains_point(x, y) &&
+           !self.disclosure_contains(x, y) &&
+           self.tag_at(x, y).is_none() &&
+           self.link_at(x, y).is_none() {
             self.toggle_expanded();
         }
     }
@@ -283,64 +564,56 @@ impl TodoItemWidget {
         let checkbox_clicked = self.checkbox_button.contains_point(x, y);
         let edit_clicked = self.edit_button.contains_point(x, y);
         let delete_clicked = self.delete_button.contains_point(x, y);
+        let tag_clicked = self.tag_at(x, y);
+        let link_clicked = self.link_at(x, y);
+        let disclosure_clicked = self.disclosure_contains(x, y);
         
         // Propagate to child buttons
         self.checkbox_button.handle_mouse_up(x, y);
         self.edit_button.handle_mouse_up(x, y);
         self.delete_button.handle_mouse_up(x, y);
         
-        // Handle checkbox click
+        // Handle checkbox click: toggle completion; the list applies the change
         if checkbox_clicked {
-            // Toggle completion status
-            if self.todo_item.is_completed() {
+            if let Some(completed) = self.with_item(TodoItem::is_completed) {
                 // Mark as not started (opposite of completed)
-                self.todo_item.set_status(Status::NotStarted);
-                self.checkbox_button = Button::new(
-                    self.checkbox_button.position().0,
-                    self.checkbox_button.position().1,
-                    20.0,
-                    20.0,
-                    " "
-                );
-            } else {
-                self.todo_item.mark_completed();
-                self.checkbox_button = Button::new(
-                    self.checkbox_button.position().0,
-                    self.checkbox_button.position().1,
-                    20.0,
-                    20.0,
-                    "✓"
-                );
-            }
-            
-            // Trigger callback
-            if let Some(on_status_change) = &self.on_status_change {
-                on_status_change(self.todo_item.status());
+                let status = if completed { Status::NotStarted } else { Status::Completed };
+                self.events.push(UiEvent::StatusChanged { id: self.id, status });
             }
         }
         
         // Handle edit click
         if edit_clicked {
-            if let Some(on_edit) = &self.on_edit {
-                on_edit();
-            }
+            self.events.push(UiEvent::EditRequested { id: self.id });
         }
         
         // Handle delete click
         if delete_clicked {
-            if let Some(on_delete) = &self.on_delete {
-                on_delete();
-            }
+            self.events.push(UiEvent::DeleteRequested { id: self.id });
+        }
+        
+        // Handle tag click; the list filters by it
+        if let Some(index) = tag_clicked {
+            self.events.push(UiEvent::TagClicked { id: self.id, index });
+        }
+        
+        // Handle link click; the owner opens it
+        if let Some(index) = link_clicked {
+            self.events.push(UiEvent::LinkClicked { id: self.id, index });
+        }
+        
+        // Handle the ▶/▼ toggle; the list hides or shows the children
+        if let (true, Some(collapsed)) = (disclosure_clicked, self.children_collapsed) {
+            self.events.push(UiEvent::CollapseToggled { id: self.id, collapsed: !collapsed });
         }
     }
     
     /// Get a color based on priority
-    fn priority_color(&self) -> Color {
-        match self.todo_item.priority() {
-            Priority::High => Color { r: 1.0, g: 0.3, b: 0.3, a: 1.0 }, // Red for high
-            Priority::Medium => Color { r: 1.0, g: 0.8, b: 0.0, a: 1.0 }, // Yellow for medium
-            Priority::Low => Color { r: 0.3, g: 0.8, b: 0.3, a: 1.0 }, // Green for low
-            _ => Color { r: 0.5, g: 0.5, b: 0.5, a: 0.5 }, // Grey for none
+    fn priority_color(&self, todo_item: &TodoItem) -> [f32; 4] {
+        match todo_item.priority() {
+            Priority::High => [1.0, 0.3, 0.3, 1.0],   // Red for high
+            Priority::Medium => [1.0, 0.8, 0.0, 1.0], // Yellow for medium
+            Priority::Low => [0.3, 0.8, 0.3, 1.0],    // Green for low
         }
     }
 
@@ -364,30 +637,77 @@ impl TodoItemWidget {
 
     /// Render only the base widget (first pass)
     pub fn render_base(&self, ctx: &mut RenderContext) {
+        self.with_item(|todo_item| self.draw_base(todo_item, ctx));
+    }
+    
+    /// Draw the row for the current version of the item
+    ///
+    /// For owners that already hold the list's lock, e.g. to draw many rows at once.
+    pub fn draw_base(&self, todo_item: &TodoItem, ctx: &mut RenderContext) {
         // Skip rendering the expanded view in the base pass
         if self.is_expanded {
             return;
         }
+        
+        // A row sliding in or out only shows its bottom part, below the row above
+        let clipped = self.reveal < 1.0;
+        if clipped {
+            let hidden = self.height - self.visible_height();
+            ctx.push_clip_rect(self.x, self.y + hidden, self.width, self.visible_height());
+        }
+        self.draw_row(todo_item, ctx);
+        if clipped {
+            ctx.pop_clip_rect();
+        }
+    }
+    
+    fn draw_row(&self, todo_item: &TodoItem, ctx: &mut RenderContext) {
+        // Items in a project show its accent instead of the priority's color
+        let accent = self.accent.unwrap_or_else(|| self.priority_color(todo_item));
 
-        // Get color as [f32; 4] (fix the type issue)
-        let priority_color = match self.todo_item.priority() {
-            Priority::High => [1.0, 0.3, 0.3, 1.0],    // Red
-            Priority::Medium => [1.0, 0.8, 0.0, 1.0],  // Yellow/gold
-            Priority::Low => [0.3, 0.8, 0.3, 1.0],     // Green
+        // Draw the card background, tinted when picked for bulk actions, lighter when hovered or selected
+        let background = if self.is_marked {
+            self.theme.item_marked_bg()
+        } else if self.is_hovered || self.is_selected {
+            self.theme.item_hover_bg()
+        } else {
+            self.theme.get_card_background_color()
         };
-
-        // Draw the card background
+        if self.is_hovered {
+            ctx.draw_shadow(&self.theme, self.x, self.y, self.width, self.height);
+            // The hovered card's border glows in its accent
+            ctx.draw_glow(Quad::outline(self.x, self.y, self.width, self.height, self.theme.border_width(), accent));
+        }
         ctx.draw_rect(
             self.x, self.y,
             self.width, self.height,
-            self.theme.get_card_background_color(),
+            background,
         );
+        
+        // Outline rows that are past their due date
+        if todo_item.is_overdue() {
+            ctx.draw_outline(
+                self.x, self.y,
+                self.width, self.height,
+                1.0,
+                self.theme.get_overdue_color(),
+            );
+        }
+        
+        // Underline the keyboard selection
+        if self.is_selected {
+            ctx.draw_rect(
+                self.x, self.y + self.height - 2.0,
+                self.width, 2.0,
+                self.theme.cyan(),
+            );
+        }
 
-        // Draw priority indicator
-        ctx.draw_rect(
+        // Draw the priority or project stripe
+        ctx.draw_rect_gradient(
             self.x, self.y,
             5.0, self.height,
-            priority_color,
+            self.theme.priority_gradient(accent),
         );
 
         // Draw hierarchy indent if needed
@@ -399,13 +719,16 @@ impl TodoItemWidget {
             );
         }
 
-        // Draw checkbox
-        self.checkbox_button.render(ctx);
+        // Draw the toggle for the children
+        if let Some((toggle_x, toggle_y, _, _)) = self.disclosure_bounds() {
+            let symbol = if self.children_collapsed == Some(true) { "▶" } else { "▼" };
+            ctx.draw_text(symbol, toggle_x + 2.0, toggle_y + 2.0, 14.0, self.theme.get_expand_button_color());
+        }
 
-        // Draw checkbox
-        let checkbox_x = self.x + 10.0 + (self.hierarchy_level as f32 * 15.0);
+        // Draw checkbox; the button only takes the clicks
+        let checkbox_x = self.checkbox_x();
         let checkbox_y = self.y + (self.height - 20.0) / 2.0;
-        let checkbox_color = match self.todo_item.status() {
+        let checkbox_color = match todo_item.status() {
             Status::Completed => self.theme.get_checkbox_checked_color(),
             _ => self.theme.get_checkbox_unchecked_color(),
         };
@@ -416,7 +739,7 @@ impl TodoItemWidget {
             checkbox_color,
         );
 
-        if self.todo_item.status() == Status::Completed {
+        if todo_item.status() == Status::Completed {
             // Draw checkmark
             ctx.draw_text(
                 "✓",
@@ -426,21 +749,51 @@ impl TodoItemWidget {
             );
         }
 
-        // Draw title
-        let title_x = checkbox_x + 30.0;
-        let title_y = self.y + (self.height - 24.0) / 2.0 - 2.0;
-        let title_color = if self.todo_item.status() == Status::Completed {
+        // Draw title, with what the search matched highlighted and web addresses as links
+        let title_x = self.title_x();
+        let title_y = self.title_y();
+        let title_color = if todo_item.status() == Status::Completed {
             self.theme.get_completed_text_color()
         } else {
             self.theme.get_text_color()
         };
+        let links = self.title_links(todo_item);
 
-        ctx.draw_text(
-            &self.todo_item.title(),
-            title_x, title_y,
-            24.0,
+        ctx.draw_text_colored(
+            todo_item.title(),
+            (title_x, title_y),
+            self.title_max_width(todo_item),
+            TITLE_TEXT_SIZE,
             title_color,
+            |byte| {
+                if self.title_matches.binary_search(&byte).is_ok() {
+                    Some(self.theme.highlight())
+                } else {
+                    links.iter().any(|(url, _, _)| url.contains(&byte)).then(|| self.theme.link())
+                }
+            },
         );
+        for (index, (_, left, width)) in links.iter().enumerate() {
+            let thickness = if self.hovered_link == Some(index) { 2.0 } else { 1.0 };
+            ctx.draw_line(*left, title_y + TITLE_TEXT_SIZE + 1.0, left + width, title_y + TITLE_TEXT_SIZE + 1.0, thickness, self.theme.link());
+        }
+
+        // Draw tag chips after the title
+        let chip_y = self.y + (self.height - TAG_CHIP_HEIGHT) / 2.0;
+        for (index, ((chip_x, chip_width), tag)) in self.tag_chips(todo_item).into_iter().zip(todo_item.tags()).enumerate() {
+            let background = if self.hovered_tag == Some(index) {
+                self.theme.filter_button_selected_bg()
+            } else {
+                self.theme.filter_button_bg()
+            };
+            ctx.draw_rect(chip_x, chip_y, chip_width, TAG_CHIP_HEIGHT, background);
+            ctx.draw_text(
+                &format!("#{}", tag),
+                chip_x + TAG_CHIP_PADDING, chip_y + (TAG_CHIP_HEIGHT - TAG_TEXT_SIZE) / 2.0,
+                TAG_TEXT_SIZE,
+                self.theme.cyan(),
+            );
+        }
 
         // Draw delete button
         let delete_btn_x = self.x + self.width - 30.0;
@@ -473,28 +826,49 @@ impl TodoItemWidget {
             self.theme.get_expand_button_color(),
         );
 
+        // Draw the subtask count and a bar filled as far as they're done, before the due date
+        if let (Some((done, total)), Some(text)) = (self.child_progress, self.progress_text()) {
+            let progress_x = self.content_limit(todo_item) + 6.0;
+            let color = if done == total {
+                self.theme.get_checkbox_checked_color()
+            } else {
+                self.theme.get_due_date_color()
+            };
+            ctx.draw_text(
+                &text,
+                progress_x, self.y + (self.height - PROGRESS_TEXT_SIZE) / 2.0 - 1.0,
+                PROGRESS_TEXT_SIZE,
+                color,
+            );
+            let bar_x = progress_x + ctx.measure_text(&text, PROGRESS_TEXT_SIZE).width + 6.0;
+            let bar_y = self.y + (self.height - 4.0) / 2.0;
+            ctx.draw_rect(bar_x, bar_y, PROGRESS_BAR_WIDTH, 4.0, self.theme.get_hierarchy_indent_color());
+            ctx.draw_rect(bar_x, bar_y, PROGRESS_BAR_WIDTH * done as f32 / total as f32, 4.0, color);
+        }
+
         // Draw due date if exists
-        if let Some(due_date) = self.todo_item.due_date() {
-            let date_str = time_to_string(due_date);
-            let is_overdue = self.todo_item.is_overdue();
+        if let Some(due_date) = todo_item.due_date() {
+            let date_str = i18n::format_date(due_date);
+            let is_overdue = todo_item.is_overdue();
             let date_color = if is_overdue {
                 self.theme.get_overdue_color()
             } else {
                 self.theme.get_due_date_color()
             };
 
-            // Due date icon
+            // Date text, ending just before the expand button
+            let date_x = expand_btn_x - 10.0 - ctx.measure_text(&date_str, 16.0).width;
             ctx.draw_text(
-                "🕒",
-                expand_btn_x - 50.0, expand_btn_y - 2.0,
+                &date_str,
+                date_x, expand_btn_y,
                 16.0,
                 date_color,
             );
 
-            // Date text
+            // Due date icon
             ctx.draw_text(
-                &date_str,
-                expand_btn_x - 30.0, expand_btn_y,
+                "🕒",
+                date_x - 22.0, expand_btn_y - 2.0,
                 16.0,
                 date_color,
             );
@@ -503,6 +877,13 @@ impl TodoItemWidget {
 
     /// Render modal for expanded view (second pass)
     pub fn render_modal(&self, ctx: &mut RenderContext) {
+        if self.is_expanded {
+            self.with_item(|todo_item| self.draw_modal(todo_item, ctx));
+        }
+    }
+    
+    /// Draw the details modal for the current version of the item
+    pub fn draw_modal(&self, todo_item: &TodoItem, ctx: &mut RenderContext) {
         if !self.is_expanded {
             return;
         }
@@ -514,13 +895,10 @@ impl TodoItemWidget {
             self.theme.get_modal_overlay_color(),
         );
 
-        // Calculate modal dimensions
-        let modal_width = ctx.width.min(600.0);
-        let modal_height = ctx.height.min(400.0);
-        let modal_x = (ctx.width - modal_width) / 2.0;
-        let modal_y = (ctx.height - modal_height) / 2.0;
+        let (modal_x, modal_y, modal_width, modal_height) = modal_bounds(ctx.width, ctx.height);
 
-        // Draw modal background
+        // Draw modal background over its shadow
+        ctx.draw_shadow(&self.theme, modal_x, modal_y, modal_width, modal_height);
         ctx.draw_rect(
             modal_x, modal_y,
             modal_width, modal_height,
@@ -528,18 +906,20 @@ impl TodoItemWidget {
         );
 
         // Draw modal header
-        ctx.draw_rect(
+        ctx.draw_rect_gradient(
             modal_x, modal_y,
             modal_width, 40.0,
-            self.theme.get_modal_header_color(),
+            self.theme.header_gradient(),
         );
 
-        // Draw title
-        ctx.draw_text(
-            &self.todo_item.title(),
-            modal_x + 20.0, modal_y + 8.0,
+        // Draw title, leaving room for the close button
+        ctx.draw_text_bounded(
+            todo_item.title(),
+            (modal_x + 20.0, modal_y + 8.0),
+            modal_width - 60.0,
             24.0,
             self.theme.get_modal_text_color(),
+            TextOverflow::Ellipsis,
         );
 
         // Draw close button
@@ -555,7 +935,7 @@ impl TodoItemWidget {
 
         // Draw status
         ctx.draw_text(
-            &format!("Status: {:?}", self.todo_item.status()),
+            &tr_args("item-status", &[("status", &i18n::status_label(todo_item.status()))]),
             modal_x + 20.0, content_y,
             18.0,
             self.theme.get_modal_text_color(),
@@ -563,25 +943,28 @@ impl TodoItemWidget {
 
         // Draw priority
         ctx.draw_text(
-            &format!("Priority: {:?}", self.todo_item.priority()),
+            &tr_args("item-priority", &[("priority", &i18n::priority_label(todo_item.priority()))]),
             modal_x + 20.0, content_y + 30.0,
             18.0,
             self.theme.get_modal_text_color(),
         );
 
-        // Draw created date
-        let created_str = time_to_string(self.todo_item.created_at());
-        ctx.draw_text(
-            &format!("Created: {}", created_str),
-            modal_x + 20.0, content_y + 60.0,
-            18.0,
-            self.theme.get_modal_text_color(),
-        );
+        // Draw when it was created, last changed and completed
+        let mut line_y = content_y + 60.0;
+        for (message, timestamp) in Self::modal_times(todo_item) {
+            ctx.draw_text(
+                &tr_args(message, &[("date", &i18n::format_datetime(timestamp))]),
+                modal_x + 20.0, line_y,
+                18.0,
+                self.theme.get_modal_text_color(),
+            );
+            line_y += 30.0;
+        }
 
         // Draw due date if exists
-        if let Some(due_date) = self.todo_item.due_date() {
-            let date_str = time_to_string(due_date);
-            let is_overdue = self.todo_item.is_overdue();
+        if let Some(due_date) = todo_item.due_date() {
+            let date_str = i18n::format_date(due_date);
+            let is_overdue = todo_item.is_overdue();
             let date_color = if is_overdue {
                 self.theme.get_overdue_color()
             } else {
@@ -589,37 +972,56 @@ impl TodoItemWidget {
             };
 
             ctx.draw_text(
-                &format!("Due: {}", date_str),
-                modal_x + 20.0, content_y + 90.0,
+                &tr_args("item-due", &[("date", &date_str)]),
+                modal_x + 20.0, line_y,
                 18.0,
                 date_color,
             );
+            line_y += 30.0;
+        }
+
+        // Draw attachments' names in a row, as links
+        if !todo_item.attachments().is_empty() {
+            ctx.draw_label(
+                "item-attachments",
+                modal_x + 20.0, line_y,
+                18.0,
+                self.theme.get_modal_text_color(),
+            );
+            let name_y = line_y + 18.0 - DESCRIPTION_TEXT_SIZE;
+            for ((left, width), attachment) in Self::modal_attachments(todo_item, modal_x, modal_width).into_iter().zip(todo_item.attachments()) {
+                ctx.draw_text(&attachment.name(), left, name_y, DESCRIPTION_TEXT_SIZE, self.theme.link());
+                let underline_y = name_y + DESCRIPTION_TEXT_SIZE + 1.0;
+                ctx.draw_line(left, underline_y, left + width, underline_y, 1.0, self.theme.link());
+            }
+            line_y += 30.0;
         }
 
         // Draw description
-        ctx.draw_text(
-            "Description:",
-            modal_x + 20.0, content_y + 130.0,
+        ctx.draw_label(
+            "item-description",
+            modal_x + 20.0, line_y + 10.0,
             18.0,
             self.theme.get_modal_text_color(),
         );
 
-        let description = if let Some(desc) = self.todo_item.description() {
-            if desc.is_empty() {
-                "No description".to_string()
-            } else {
-                desc.to_string()
+        match todo_item.description().filter(|desc| !desc.is_empty()) {
+            // Long descriptions wrap, and lines past the bottom of the modal are cut off
+            Some(desc) => {
+                let desc_y = line_y + 35.0;
+                ctx.push_clip_rect(modal_x, desc_y, modal_width, modal_y + modal_height - 10.0 - desc_y);
+                Document::parse(desc)
+                    .layout(modal_width - 40.0, DESCRIPTION_TEXT_SIZE)
+                    .render(ctx, (modal_x + 20.0, desc_y), self.theme.get_modal_text_color(), &self.theme);
+                ctx.pop_clip_rect();
             }
-        } else {
-            "No description".to_string()
-        };
-
-        ctx.draw_text(
-            &description,
-            modal_x + 20.0, content_y + 155.0,
-            16.0,
-            self.theme.get_modal_text_color(),
-        );
+            None => ctx.draw_label(
+                "item-no-description",
+                modal_x + 20.0, line_y + 35.0,
+                DESCRIPTION_TEXT_SIZE,
+                self.theme.get_modal_text_color(),
+            ),
+        }
     }
 
     /// Handle mouse down event on the modal
@@ -628,18 +1030,10 @@ impl TodoItemWidget {
             return false;
         }
 
-        // Calculate modal dimensions and position
-        let modal_width = ctx_width * 0.6;
-        let modal_height = ctx_height * 0.7;
-        let modal_x = (ctx_width - modal_width) / 2.0;
-        let modal_y = (ctx_height - modal_height) / 2.0;
+        let (modal_x, modal_y, modal_width, modal_height) = modal_bounds(ctx_width, ctx_height);
 
         // Check if close button was clicked
-        let close_btn_x = modal_x + modal_width - 30.0;
-        let close_btn_y = modal_y + 8.0;
-        
-        if x >= close_btn_x - 10.0 && x <= close_btn_x + 20.0 &&
-           y >= close_btn_y - 10.0 && y <= close_btn_y + 24.0 {
+        if self.modal_close_button_contains(x, y, ctx_width, ctx_height) {
             self.is_expanded = false;
             return true;
         }
@@ -652,7 +1046,112 @@ impl TodoItemWidget {
 
         // If clicked outside modal, close it
         self.is_expanded = false;
-        return true;
+        true
+    }
+    
+    /// Check if a point is on the modal's close button
+    fn modal_close_button_contains(&self, x: f32, y: f32, ctx_width: f32, ctx_height: f32) -> bool {
+        let (modal_x, modal_y, modal_width, _) = modal_bounds(ctx_width, ctx_height);
+        let close_btn_x = modal_x + modal_width - 30.0;
+        let close_btn_y = modal_y + 8.0;
+        
+        x >= close_btn_x - 10.0 && x <= close_btn_x + 20.0 &&
+        y >= close_btn_y - 10.0 && y <= close_btn_y + 24.0
+    }
+    
+    /// The times shown in the details modal: created, last changed and completed
+    fn modal_times(todo_item: &TodoItem) -> impl Iterator<Item = (&'static str, u64)> {
+        [
+            Some(("item-created", todo_item.created_at())),
+            Some(("item-updated", todo_item.updated_at())),
+            todo_item.completed_at().map(|completed| ("item-completed", completed)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Top of the line under the status, priority, times and due date in the details modal
+    fn modal_lines_end(todo_item: &TodoItem, modal_y: f32) -> f32 {
+        let lines = 2 + Self::modal_times(todo_item).count() + usize::from(todo_item.due_date().is_some());
+        modal_y + 60.0 + lines as f32 * 30.0
+    }
+
+    /// Left edge and width of each attachment's name in the details modal
+    ///
+    /// Names that would run past the modal's edge are left out.
+    fn modal_attachments(todo_item: &TodoItem, modal_x: f32, modal_width: f32) -> Vec<(f32, f32)> {
+        let right = modal_x + modal_width - 20.0;
+        let mut left = modal_x + 20.0 + TextSize::measure(&tr("item-attachments"), 18.0).width + 10.0;
+        let mut names = Vec::new();
+        for attachment in todo_item.attachments() {
+            let width = TextSize::measure(&attachment.name(), DESCRIPTION_TEXT_SIZE).width;
+            if left + width > right {
+                break;
+            }
+            names.push((left, width));
+            left += width + ATTACHMENT_GAP;
+        }
+        names
+    }
+
+    /// Index of the attachment whose name is under a point on the expanded modal
+    pub fn modal_attachment_at(&self, x: f32, y: f32, ctx_width: f32, ctx_height: f32) -> Option<usize> {
+        if !self.is_expanded {
+            return None;
+        }
+        let (modal_x, modal_y, modal_width, _) = modal_bounds(ctx_width, ctx_height);
+        self.with_item(|todo_item| {
+            let row_y = Self::modal_lines_end(todo_item, modal_y);
+            if y < row_y - 4.0 || y > row_y + 24.0 {
+                return None;
+            }
+            Self::modal_attachments(todo_item, modal_x, modal_width)
+                .iter()
+                .position(|&(left, width)| x >= left && x <= left + width)
+        })
+        .flatten()
+    }
+
+    /// Index of the link in the description under a point on the expanded modal
+    ///
+    /// Links are counted as in `markdown::item_links`, after the title's.
+    pub fn modal_link_at(&self, x: f32, y: f32, ctx_width: f32, ctx_height: f32) -> Option<usize> {
+        if !self.is_expanded {
+            return None;
+        }
+        let (modal_x, modal_y, modal_width, modal_height) = modal_bounds(ctx_width, ctx_height);
+        self.with_item(|todo_item| {
+            let desc = todo_item.description().filter(|desc| !desc.is_empty())?;
+            // Same place as drawn: below the status, priority, times, due date and attachments
+            let attachments_height = if todo_item.attachments().is_empty() { 0.0 } else { 30.0 };
+            let desc_y = Self::modal_lines_end(todo_item, modal_y) + attachments_height + 35.0;
+            // Lines cut off at the bottom of the modal can't be clicked
+            if y > modal_y + modal_height - 10.0 {
+                return None;
+            }
+            // The title's links come first
+            let title_links = markdown::find_urls(todo_item.title()).len();
+            Document::parse(desc)
+                .layout(modal_width - 40.0, DESCRIPTION_TEXT_SIZE)
+                .link_at(x - (modal_x + 20.0), y - desc_y)
+                .map(|index| title_links + index)
+        })
+        .flatten()
+    }
+
+    /// Mouse cursor over the expanded modal: a pointer on the close button and links
+    pub fn modal_cursor_at(&self, x: f32, y: f32, ctx_width: f32, ctx_height: f32) -> Option<CursorIcon> {
+        if !self.is_expanded {
+            return None;
+        }
+        if self.modal_close_button_contains(x, y, ctx_width, ctx_height)
+            || self.modal_link_at(x, y, ctx_width, ctx_height).is_some()
+            || self.modal_attachment_at(x, y, ctx_width, ctx_height).is_some()
+        {
+            Some(CursorIcon::Pointer)
+        } else {
+            Some(CursorIcon::Default)
+        }
     }
     
     /// Check if a point is inside the modal
@@ -661,11 +1160,7 @@ impl TodoItemWidget {
             return false;
         }
 
-        // Calculate modal dimensions and position
-        let modal_width = ctx_width * 0.6;
-        let modal_height = ctx_height * 0.7;
-        let modal_x = (ctx_width - modal_width) / 2.0;
-        let modal_y = (ctx_height - modal_height) / 2.0;
+        let (modal_x, modal_y, modal_width, modal_height) = modal_bounds(ctx_width, ctx_height);
 
         // Check if point is inside modal
         x >= modal_x && x <= modal_x + modal_width &&
@@ -684,22 +1179,47 @@ impl TodoItemWidget {
 }
 
 // Helper function to convert a timestamp to a string
-fn time_to_string(timestamp: u64) -> String {
-    // Basic formatting, could be improved with proper date/time library
-    format!("{}", timestamp)
-}
-
 impl Widget for TodoItemWidget {
-    fn update(&mut self, _delta_time: f32) {
-        // Update child components
-        self.checkbox_button.update(_delta_time);
-        self.edit_button.update(_delta_time);
-        self.delete_button.update(_delta_time);
+    fn update(&mut self, delta_time: f32) {
+        // Slide in or out
+        let step = delta_time / REVEAL_DURATION;
+        if self.revealing {
+            self.reveal = (self.reveal + step).min(1.0);
+        } else {
+            self.reveal = (self.reveal - step).max(0.0);
+        }
         
-        // Update close button bounds if expanded
-        if self.is_expanded {
+        // Update child components that have something to do
+        for button in [&mut self.checkbox_button, &mut self.edit_button, &mut self.delete_button] {
+            if button.needs_update() {
+                button.update(delta_time);
+            }
+        }
+        
+        // Update close button bounds if expanded and moved since the last update
+        if self.needs_layout && self.is_expanded {
             self.update_close_button_bounds();
         }
+        self.needs_layout = false;
+    }
+    
+    fn needs_update(&self) -> bool {
+        self.needs_layout
+            || self.is_sliding()
+            || self.checkbox_button.needs_update()
+            || self.edit_button.needs_update()
+            || self.delete_button.needs_update()
+    }
+    
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth).with_hovered(self.is_hovered).with_focused(self.is_selected));
+        for button in [&self.checkbox_button, &self.edit_button, &self.delete_button] {
+            button.inspect(depth + 1, out);
+        }
+    }
+    
+    fn needs_layout(&self) -> bool {
+        self.needs_layout
     }
     
     fn render(&self, ctx: &mut RenderContext) {
@@ -733,11 +1253,14 @@ impl Widget for TodoItemWidget {
         
         let (panel_x, panel_y) = self.panel.position();
         self.panel.set_position(panel_x + dx, panel_y + dy);
+        
+        self.needs_layout |= dx != 0.0 || dy != 0.0;
     }
     
     fn set_dimensions(&mut self, width: f32, height: f32) {
         self.width = width;
         self.height = height;
+        self.needs_layout = true;
         
         // Update panel dimensions
         self.panel.set_dimensions(width, height);
@@ -746,7 +1269,7 @@ impl Widget for TodoItemWidget {
         let button_size = height * 0.5;
         
         self.checkbox_button.set_position(
-            self.x + 10.0,
+            self.checkbox_x(),
             self.y + (height - button_size) / 2.0
         );
         
@@ -761,7 +1284,130 @@ impl Widget for TodoItemWidget {
         );
     }
     
+    /// Only the part of a sliding row that shows is hit
     fn contains_point(&self, x: f32, y: f32) -> bool {
-        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+        let top = self.y + self.height - self.visible_height();
+        x >= self.x && x <= self.x + self.width && y >= top && y <= self.y + self.height
     }
-} 
\ No newline at end of file
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.checkbox_button
+            .cursor_at(x, y)
+            .or_else(|| self.edit_button.cursor_at(x, y))
+            .or_else(|| self.delete_button.cursor_at(x, y))
+            .or_else(|| self.tag_at(x, y).map(|_| CursorIcon::Pointer))
+            .or_else(|| self.link_at(x, y).map(|_| CursorIcon::Pointer))
+            .or_else(|| self.disclosure_contains(x, y).then_some(CursorIcon::Pointer))
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use crate::core::prelude::{Dispatcher, Status, TodoList};
+    use crate::ui::testing::UiDriver;
+    use crate::ui::{UiEvent, Widget};
+
+    #[test]
+    fn test_checkbox_toggles_status() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Write tests", "Ship"]);
+
+        driver.click_checkbox(ids[0]);
+        assert_eq!(driver.task(ids[0]).unwrap().status(), Status::Completed);
+        assert_eq!(driver.take_events(), vec![UiEvent::StatusChanged { id: ids[0], status: Status::Completed }]);
+
+        driver.click_checkbox(ids[0]);
+        assert_eq!(driver.task(ids[0]).unwrap().status(), Status::NotStarted);
+        assert_eq!(driver.task(ids[1]).unwrap().status(), Status::NotStarted);
+        assert_eq!(driver.take_events(), vec![UiEvent::StatusChanged { id: ids[0], status: Status::NotStarted }]);
+    }
+
+    #[test]
+    fn test_delete_and_edit_buttons() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Keep", "Drop"]);
+
+        driver.click_edit(ids[0]);
+        assert_eq!(driver.take_events(), vec![UiEvent::EditRequested { id: ids[0] }]);
+
+        driver.click_delete(ids[1]);
+        assert!(driver.task(ids[1]).is_none());
+        assert_eq!(driver.widget().shown_items(), vec![ids[0]]);
+        assert_eq!(driver.take_events(), vec![UiEvent::DeleteRequested { id: ids[1] }]);
+    }
+
+    #[test]
+    fn test_double_click_opens_details() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Alpha"]);
+        let (x, y) = driver.widget().item_widget(ids[0]).unwrap().position();
+
+        driver.double_click_at(x + 200.0, y + 10.0);
+        driver.tick(0.016);
+        assert!(driver.widget().item_widget(ids[0]).unwrap().is_expanded());
+        assert_eq!(driver.widget().selected_item(), Some(ids[0]));
+    }
+
+    #[test]
+    fn test_clicking_a_link_in_the_details() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Alpha"]);
+        driver.todo_list().lock().unwrap().get_item_mut(ids[0]).unwrap()
+            .set_description(Some("[docs](https://example.com) and **more**"));
+        let (x, y) = driver.widget().item_widget(ids[0]).unwrap().position();
+        driver.double_click_at(x + 200.0, y + 10.0);
+        driver.tick(0.016);
+        driver.take_events();
+
+        // The 600x400 modal is centered; the description starts below the
+        // status, priority and two times, 20 pixels in
+        let (desc_x, desc_y) = (212.0 + 20.0, 184.0 + 60.0 + 4.0 * 30.0 + 35.0);
+        driver.click_at(desc_x + 60.0, desc_y + 5.0);
+        assert!(driver.take_events().is_empty());
+        driver.click_at(desc_x + 10.0, desc_y + 5.0);
+        assert_eq!(driver.take_events(), vec![UiEvent::LinkClicked { id: ids[0], index: 0 }]);
+        assert!(driver.widget().item_widget(ids[0]).unwrap().is_expanded());
+    }
+
+    #[test]
+    fn test_clicking_a_link_in_a_title() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Read https://example.com today"]);
+        let row = driver.widget().item_widget(ids[0]).unwrap();
+        let (x, y) = row.position();
+        // The title starts 56 pixels in; "Read " takes 60 of them
+        let link_x = x + 56.0 + 60.0 + 30.0;
+        assert_eq!(row.cursor_at(link_x, y + 20.0), Some(winit::window::CursorIcon::Pointer));
+        assert_eq!(row.cursor_at(x + 56.0 + 20.0, y + 20.0), None);
+
+        // Double-clicking a link opens it rather than the details
+        driver.double_click_at(link_x, y + 20.0);
+        driver.tick(0.016);
+        assert!(!driver.widget().item_widget(ids[0]).unwrap().is_expanded());
+        let links = vec![UiEvent::LinkClicked { id: ids[0], index: 0 }];
+        assert_eq!(driver.take_events(), [links.clone(), links].concat());
+    }
+
+    #[test]
+    fn test_parents_count_and_follow_their_subtasks() {
+        let mut todo_list = TodoList::new("Test");
+        let parent = todo_list.create_item("Move out");
+        let boxes = todo_list.create_item("Pack boxes");
+        let keys = todo_list.create_item("Return keys");
+        todo_list.move_item(boxes, Some(parent)).unwrap();
+        todo_list.move_item(keys, Some(parent)).unwrap();
+        let mut driver = UiDriver::new(todo_list);
+        let progress = |driver: &UiDriver| driver.widget().item_widget(parent).unwrap().child_progress();
+
+        assert_eq!(progress(&driver), Some((0, 2)));
+        assert_eq!(driver.widget().item_widget(boxes).unwrap().child_progress(), None);
+        driver.click_checkbox(boxes);
+        assert_eq!(progress(&driver), Some((1, 2)));
+        assert_eq!(driver.task(parent).unwrap().status(), Status::NotStarted);
+
+        // With the option on, the last subtask completes the parent and reopening one reopens it
+        let commands = Dispatcher::new(driver.todo_list().clone());
+        commands.set_auto_complete_parents(true);
+        driver.widget_mut().set_dispatcher(commands);
+        driver.click_checkbox(keys);
+        assert_eq!(progress(&driver), Some((2, 2)));
+        assert_eq!(driver.task(parent).unwrap().status(), Status::Completed);
+        driver.click_checkbox(boxes);
+        assert_eq!(driver.task(parent).unwrap().status(), Status::NotStarted);
+    }
+}
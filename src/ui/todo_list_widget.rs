@@ -1,9 +1,24 @@
-use crate::ui::{RenderContext, Widget, Button, Panel, TextInput, CyberpunkTheme};
-use crate::ui::todo_item_widget::TodoItemWidget;
-use crate::core::prelude::{TodoList, TodoItem, Status, Priority};
+use crate::ui::{RenderContext, Widget, WidgetInfo, Button, Panel, TextInput, Scrollbar, CyberpunkTheme, UiEvent, EventQueue, FocusManager, Clipboard};
+use crate::ui::context_menu::{ContextMenu, MenuItem};
+use crate::ui::fuzzy::{fuzzy_match, FuzzyMatch};
+use crate::ui::{damage, event};
+use crate::ui::input::{Action, TextEvent};
+use crate::ui::ecs::{self, Bounds, Entity, Interactive, Position, Row, Style, Text, World};
+use crate::session::Session;
+use crate::layout::FilterControl;
+use crate::profiling::profile_scope;
+use crate::ui::todo_item_widget::{self, TodoItemWidget};
+use crate::core::parse::quick_add;
+use crate::core::prelude::{BulkEdit, Command, Dispatcher, ListEvent, TodoList, TodoItem, Status, Priority, SortKey, SortOrder};
+use crate::i18n::{self, tr};
 use uuid::Uuid;
+use winit::keyboard::ModifiersState;
+use winit::window::CursorIcon;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::mpsc::Receiver;
 
 /// Filter settings for displaying todo items
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -23,7 +38,7 @@ pub enum PriorityFilter {
 }
 
 /// Type of filter applied
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum FilterType {
     None,
     Title,
@@ -33,6 +48,34 @@ pub enum FilterType {
     Combined,
 }
 
+/// Extra filter supplied by the application, e.g. from a script; items it
+/// returns false for are hidden
+pub type ItemFilter = Rc<dyn Fn(&TodoItem) -> bool>;
+
+/// Height of the controls above the item area
+const CONTROLS_HEIGHT: f32 = 50.0;
+
+/// Height of the row of tag chips below the controls, shown while any item has tags
+const TAG_ROW_HEIGHT: f32 = 30.0;
+const TAG_CHIP_HEIGHT: f32 = 22.0;
+
+/// Distance between the tops of neighbouring rows
+const ROW_HEIGHT: f32 = 40.0;
+
+/// Rows given a widget beyond each edge of the viewport, so a short scroll doesn't create any
+const OVERSCAN_ROWS: usize = 3;
+
+/// Width of the scrollbar and its gap to the right edge
+const SCROLLBAR_WIDTH: f32 = 8.0;
+const SCROLLBAR_MARGIN: f32 = 5.0;
+
+/// How quickly the offset closes in on the scroll target, per second; higher is snappier
+const SCROLL_SMOOTHING: f32 = 18.0;
+/// How quickly a fling after panning slows down, per second
+const FLING_FRICTION: f32 = 4.0;
+/// Slowest fling speed, in pixels per second, below which a fling stops
+const MIN_FLING_SPEED: f32 = 30.0;
+
 /// Convert [f32; 4] RGBA values to wgpu::Color
 fn to_color(rgba: [f32; 4]) -> wgpu::Color {
     wgpu::Color {
@@ -43,27 +86,225 @@ fn to_color(rgba: [f32; 4]) -> wgpu::Color {
     }
 }
 
+/// The row of filter controls above the items: the search text, the field,
+/// status and priority selectors and the sort dropdown with its direction
+/// toggle, each an entity laid out by `ecs::layout`
+///
+/// Below them, a second row holds a chip for every tag in the list.
+#[derive(Clone)]
+struct FilterControls {
+    world: World,
+    row: Entity,
+    search: Entity,
+    field: Entity,
+    status: Entity,
+    priority: Entity,
+    sort: Entity,
+    sort_direction: Entity,
+    tag_row: Entity,
+    tag_chips: Vec<(Entity, String)>,
+}
+
+impl FilterControls {
+    /// Create the controls for a list whose top-left corner is at `(x, y)`
+    fn new(x: f32, y: f32, theme: &CyberpunkTheme) -> Self {
+        let mut world = World::new();
+        let mut control = |width: f32, text_inset: f32| {
+            let entity = world.spawn();
+            world.bounds.insert(entity, Bounds { width, height: 30.0 });
+            world.styles.insert(entity, Style {
+                background: theme.get_background_color(),
+                hover_background: None,
+                text_color: theme.get_text_color(),
+                text_size: theme.small_text_size(),
+                padding: (text_inset, 5.0),
+            });
+            world.interactive.insert(entity, Interactive::default());
+            entity
+        };
+        let search = control(150.0, 5.0);
+        let field = control(120.0, 10.0);
+        let status = control(120.0, 10.0);
+        let priority = control(120.0, 10.0);
+        let sort = control(130.0, 10.0);
+        let sort_direction = control(30.0, 10.0);
+        
+        let row = world.spawn();
+        world.rows.insert(row, Row { gap: 10.0, children: vec![search, field, status, priority, sort, sort_direction] });
+        let tag_row = world.spawn();
+        world.rows.insert(tag_row, Row { gap: 6.0, children: Vec::new() });
+        
+        let mut controls = Self { world, row, search, field, status, priority, sort, sort_direction, tag_row, tag_chips: Vec::new() };
+        controls.move_to(x, y);
+        controls
+    }
+    
+    /// Follow the list's top-left corner
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.world.positions.insert(self.row, Position { x: x + 10.0, y: y + 10.0 });
+        self.world.positions.insert(self.tag_row, Position { x: x + 10.0, y: y + CONTROLS_HEIGHT });
+        ecs::layout(&mut self.world);
+    }
+    
+    /// Whether there's a chip for each of these tags and no others
+    fn shows_tags(&self, tags: &[String]) -> bool {
+        self.tag_chips.len() == tags.len()
+            && self.tag_chips.iter().zip(tags).all(|((_, shown), tag)| shown == tag)
+    }
+    
+    /// Show a chip for each tag, highlighting the one being filtered by
+    fn set_tags(&mut self, tags: &[String], active: Option<&str>, theme: &CyberpunkTheme) {
+        if !self.shows_tags(tags) {
+            for (chip, _) in self.tag_chips.drain(..) {
+                self.world.despawn(chip);
+            }
+            for tag in tags {
+                let chip = self.world.spawn();
+                self.world.bounds.insert(chip, Bounds { width: todo_item_widget::tag_chip_width(tag), height: TAG_CHIP_HEIGHT });
+                self.world.texts.insert(chip, Text::Plain(format!("#{}", tag)));
+                self.world.interactive.insert(chip, Interactive::default());
+                self.tag_chips.push((chip, tag.clone()));
+            }
+            if let Some(row) = self.world.rows.get_mut(self.tag_row) {
+                row.children = self.tag_chips.iter().map(|(chip, _)| *chip).collect();
+            }
+            ecs::layout(&mut self.world);
+        }
+        
+        for (chip, tag) in &self.tag_chips {
+            let selected = active == Some(tag.as_str());
+            self.world.styles.insert(*chip, Style {
+                background: if selected { theme.filter_button_selected_bg() } else { theme.filter_button_bg() },
+                hover_background: Some(theme.item_hover_bg()),
+                text_color: if selected { theme.bright_text() } else { theme.cyan() },
+                text_size: theme.small_text_size(),
+                padding: (6.0, (TAG_CHIP_HEIGHT - theme.small_text_size()) / 2.0),
+            });
+        }
+    }
+    
+    /// The tag of a chip
+    fn tag_of(&self, chip: Entity) -> Option<&str> {
+        self.tag_chips.iter().find(|(entity, _)| *entity == chip).map(|(_, tag)| tag.as_str())
+    }
+    
+    /// Show these controls in this order; the others lose their position, so
+    /// they are neither drawn nor clickable
+    fn arrange(&mut self, shown: &[FilterControl]) {
+        let entities = |control: &FilterControl| match control {
+            FilterControl::Search => vec![self.search],
+            FilterControl::Field => vec![self.field],
+            FilterControl::Status => vec![self.status],
+            FilterControl::Priority => vec![self.priority],
+            FilterControl::Sort => vec![self.sort, self.sort_direction],
+        };
+        let children: Vec<Entity> = shown.iter().flat_map(entities).collect();
+        for control in [self.search, self.field, self.status, self.priority, self.sort, self.sort_direction] {
+            if !children.contains(&control) {
+                self.world.positions.remove(control);
+            }
+        }
+        if let Some(row) = self.world.rows.get_mut(self.row) {
+            row.children = children;
+        }
+        ecs::layout(&mut self.world);
+    }
+}
+
+/// What can have keyboard focus in the list, in tab order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListFocus {
+    TitleInput,
+    AddButton,
+    SearchInput,
+    /// A filter control or tag chip
+    Filter(Entity),
+    Item(Uuid),
+}
+
+/// What the context menu of a task can do to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemAction {
+    /// Mark it done, or not done again if it was
+    ToggleComplete,
+    Edit,
+    AddSubtask,
+    SetPriority(Priority),
+    CopyTitle,
+    /// Push its due date back, shown for open tasks with one
+    Snooze,
+    /// Start a focus session on it, shown for open tasks
+    Focus,
+    Delete,
+    /// Change all the tasks picked for bulk actions; moving them under a task
+    /// is offered on the tasks that aren't picked
+    Bulk(BulkEdit),
+    /// Sort the list by this; the same menu drops down from the sort control
+    Sort(SortKey),
+}
+
+/// A row of the list: the item it shows, and how
+///
+/// Every row has one, shown or scrolled away; only rows near the viewport get
+/// a `TodoItemWidget`, made from this when they scroll into view.
+#[derive(Debug, Clone)]
+struct ListRow {
+    id: Uuid,
+    depth: usize,
+    /// For items with children, whether those are hidden
+    children_collapsed: Option<bool>,
+    child_progress: Option<(usize, usize)>,
+    /// Accent color of the item's project
+    accent: Option<[f32; 4]>,
+    /// Positions of the search's matches in the title
+    title_matches: Vec<usize>,
+}
+
+impl ListRow {
+    /// Show the row on its widget
+    fn apply_to(&self, widget: &mut TodoItemWidget) {
+        widget.set_hierarchy_level(self.depth);
+        widget.set_children_collapsed(self.children_collapsed);
+        widget.set_child_progress(self.child_progress);
+        widget.set_accent(self.accent);
+        widget.set_title_matches(self.title_matches.clone());
+    }
+}
+
 /// A widget for displaying and managing a TodoList
 pub struct TodoListWidget {
     x: f32,
     y: f32,
     width: f32,
     height: f32,
-    todo_list: Arc<Mutex<TodoList>>,
+    /// Changes to the list go through here
+    commands: Dispatcher,
+    /// Changes to the list, made here or anywhere else, that the rows don't show yet
+    list_events: Receiver<ListEvent>,
     
     // UI components
     panel: Panel,
     add_button: Button,
     title_input: TextInput,
     filter_buttons: Vec<Button>,
+    filter_controls: FilterControls,
     search_input: TextInput,
     
-    // Scrolling
+    // Scrolling; the offset eases toward the target in `update`
     scroll_offset: f32,
+    scroll_target: f32,
     max_scroll: f32,
+    scrollbar: Scrollbar,
+    /// Set while the list is panned by hand, with the distance moved since the last update
+    panning: Option<f32>,
+    /// Recent panning speed in pixels per second, carried on as a fling on release
+    pan_velocity: f32,
+    fling_velocity: f32,
     
-    // Todo item widgets
-    todo_item_widgets: Vec<Arc<Mutex<TodoItemWidget>>>,
+    // Every filtered row in display order, and widgets for those near the
+    // viewport and those with their details modal open
+    rows: Vec<ListRow>,
+    todo_item_widgets: Vec<TodoItemWidget>,
     
     // Filter state
     show_completed: bool,
@@ -71,31 +312,66 @@ pub struct TodoListWidget {
     filter_status: Option<Status>,
     search_text: String,
     
-    // Callbacks
-    on_item_status_change: Option<Arc<dyn Fn(TodoItem) + Send + Sync>>,
-    on_item_edit: Option<Arc<dyn Fn(TodoItem) + Send + Sync>>,
-    on_item_delete: Option<Arc<dyn Fn(TodoItem) + Send + Sync>>,
+    // Events for the application, drained with take_events
+    events: EventQueue,
     
     // Theme
     theme: CyberpunkTheme,
     
-    // Track which item has a modal open (if any)
-    modal_open_index: Option<usize>,
+    // Click count of the last mouse press (1 = single, 2 = double, ...)
+    last_click_count: u32,
+    
+    // Keyboard selection, kept by id so it survives regenerating the item widgets
+    selected_item: Option<Uuid>,
+    
+    // Tasks picked with Ctrl+click, Shift+click or Ctrl+A, which bulk actions change;
+    // the keyboard selection is where Shift+click ranges start
+    multi_selection: HashSet<Uuid>,
+    /// Modifier keys held, which turn clicks on rows into picking them
+    modifiers: ModifiersState,
+    /// Set when a press picked a row, so its release doesn't click the row's buttons
+    selection_press: bool,
+    
+    // Keyboard focus; a focused row is also the selected one
+    focus: FocusManager<ListFocus>,
+    
+    // Focus-follows-mouse: selection tracks the hovered row and the search box
+    // takes focus after the pointer rests on it for `hover_focus_delay` seconds
+    focus_follows_mouse: bool,
+    hover_focus_delay: f32,
+    search_hover_time: Option<f32>,
+    
+    // Items whose details modal is open, kept by id so it survives regenerating the item widgets
+    expanded_items: HashSet<Uuid>,
+    
+    // Items whose children are hidden
+    collapsed_items: HashSet<Uuid>,
+    
+    // The open context menu and the item it's for
+    context_menu: ContextMenu<ItemAction>,
+    menu_item: Option<Uuid>,
+    /// Set when a press went to the context menu, so its release doesn't reach what's under it
+    menu_press: bool,
     
     // New fields
-    expanded_items: Vec<usize>, // Track expanded item indices
-    visible_items: Vec<usize>,
     filter_value: String,
     filter_type: FilterType,
     status_filter: Option<Status>,
     priority_filter: Option<Priority>,
+    tag_filter: Option<String>,
+    /// Only items in this project are shown
+    project_filter: Option<Uuid>,
+    custom_filter: Option<ItemFilter>,
 }
 
 impl TodoListWidget {
-    /// Create a new TodoListWidget with the given todo list and position
-    pub fn new(x: f32, y: f32, width: f32, height: f32, todo_list: Arc<Mutex<TodoList>>) -> Self {
-        let theme = CyberpunkTheme::new();
-        
+    /// Create a new TodoListWidget showing the dispatcher's list at the given position
+    pub fn new(x: f32, y: f32, width: f32, height: f32, commands: Dispatcher) -> Self {
+        Self::themed(x, y, width, height, commands, CyberpunkTheme::new())
+    }
+    
+    /// `new`, drawing the list and its rows in `theme`
+    pub fn themed(x: f32, y: f32, width: f32, height: f32, commands: Dispatcher, theme: CyberpunkTheme) -> Self {
         // Create panel
         let panel = Panel::new(x, y, width, height)
             .with_background_color(to_color(theme.panel_background()))
@@ -110,7 +386,7 @@ impl TodoListWidget {
             y + button_padding,
             button_width,
             button_height,
-            "Add Task"
+            tr("add-task")
         ).with_text_color(to_color(theme.bright_text()))
          .with_background_color(to_color(theme.neon_pink()));
         
@@ -121,13 +397,13 @@ impl TodoListWidget {
             y + button_padding,
             input_width,
             button_height,
-            "New task..."
+            tr("new-task-placeholder")
         ).with_text_color(to_color(theme.bright_text()))
          .with_background_color(to_color(theme.background()))
          .with_border_color(to_color(theme.border()));
         
         // Create filter buttons
-        let filter_buttons = Self::create_filter_buttons(x, y, width, &theme);
+        let filter_buttons = Self::create_filter_buttons(x, y, &theme);
         
         // Create search input
         let search_input_width = 200.0;
@@ -136,44 +412,61 @@ impl TodoListWidget {
             y + button_padding * 2.0 + button_height,
             search_input_width,
             button_height,
-            "Search..."
+            tr("search-placeholder")
         ).with_text_color(to_color(theme.bright_text()))
          .with_background_color(to_color(theme.background()))
          .with_border_color(to_color(theme.border()));
         
-        // Calculate the appropriate area for todo items
-        let top_controls_height = button_height + button_padding * 2.0; // Add button + title input
-        let filter_controls_height = button_height + button_padding; // Filter controls
-        
+        let list_events = commands.lock().subscribe();
         let mut widget = Self {
             x,
             y,
             width,
             height,
-            todo_list,
+            commands,
+            list_events,
             panel,
             add_button,
             title_input,
             filter_buttons,
+            filter_controls: FilterControls::new(x, y, &theme),
             search_input,
             scroll_offset: 0.0,
+            scroll_target: 0.0,
             max_scroll: 0.0,
+            scrollbar: Self::create_scrollbar(x, y, width, height),
+            panning: None,
+            pan_velocity: 0.0,
+            fling_velocity: 0.0,
+            rows: Vec::new(),
             todo_item_widgets: Vec::new(),
             show_completed: true,
             filter_priority: None,
             filter_status: None,
             search_text: String::new(),
-            on_item_status_change: None,
-            on_item_edit: None,
-            on_item_delete: None,
+            events: EventQueue::new(),
             theme,
-            modal_open_index: None,
-            expanded_items: Vec::new(),
-            visible_items: Vec::new(),
+            last_click_count: 1,
+            selected_item: None,
+            multi_selection: HashSet::new(),
+            modifiers: ModifiersState::empty(),
+            selection_press: false,
+            focus: FocusManager::new(),
+            focus_follows_mouse: false,
+            hover_focus_delay: 0.5,
+            search_hover_time: None,
+            expanded_items: HashSet::new(),
+            collapsed_items: HashSet::new(),
+            context_menu: ContextMenu::new(),
+            menu_item: None,
+            menu_press: false,
             filter_value: String::new(),
             filter_type: FilterType::None,
             status_filter: None,
             priority_filter: None,
+            tag_filter: None,
+            project_filter: None,
+            custom_filter: None,
         };
         
         // Generate initial todo item widgets
@@ -184,115 +477,282 @@ impl TodoListWidget {
     
     /// Get the todo list
     pub fn todo_list(&self) -> Arc<Mutex<TodoList>> {
-        self.todo_list.clone()
+        self.commands.list().clone()
     }
     
-    /// Set a new todo_list
-    pub fn set_todo_list(&mut self, todo_list: Arc<Mutex<TodoList>>) {
-        self.todo_list = todo_list;
+    /// Show another list, changed through its own dispatcher
+    pub fn set_dispatcher(&mut self, commands: Dispatcher) {
+        self.list_events = commands.lock().subscribe();
+        self.commands = commands;
         
         // Reset filters and search
         self.show_completed = true;
         self.filter_priority = None;
         self.filter_status = None;
-        self.search_text = String::new();
-        self.search_input.set_text("Search...");
+        self.tag_filter = None;
+        self.project_filter = None;
+        self.clear_search();
+        self.multi_selection.clear();
         
-        // Regenerate todo item widgets
+        // Regenerate todo item widgets; the old ones read from the previous list
+        self.todo_item_widgets.clear();
+        self.update_todo_items();
+    }
+    
+    /// Set or clear the application supplied filter, applied on top of the built-in ones
+    pub fn set_custom_filter(&mut self, filter: Option<ItemFilter>) {
+        self.custom_filter = filter;
+        self.update_todo_items();
+    }
+    
+    /// The tag items must have to be shown, if any
+    pub fn tag_filter(&self) -> Option<&str> {
+        self.tag_filter.as_deref()
+    }
+    
+    /// The filters narrowing the list, in words, e.g. for the status bar
+    pub fn filter_summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.is_searching() {
+            parts.push(i18n::tr_args("status-filter-search", &[("query", self.filter_value.trim())]));
+        }
+        if let Some(status) = self.status_filter {
+            parts.push(i18n::status_label(status));
+        }
+        if let Some(priority) = self.priority_filter {
+            parts.push(i18n::tr_args("item-priority", &[("priority", &i18n::priority_label(priority))]));
+        }
+        if let Some(tag) = &self.tag_filter {
+            parts.push(format!("#{}", tag));
+        }
+        if let Some(project) = self.project_filter.and_then(|id| Some(self.commands.lock().project(id)?.name().to_string())) {
+            parts.push(project);
+        }
+        if self.custom_filter.is_some() {
+            parts.push(tr("status-filter-script"));
+        }
+        if parts.is_empty() {
+            return tr("status-filter-none");
+        }
+        i18n::tr_args("status-filter", &[("filter", &parts.join(" · "))])
+    }
+    
+    /// Show only items with a tag, or clear the tag filter
+    pub fn set_tag_filter(&mut self, tag: Option<String>) {
+        self.tag_filter = tag;
+        self.update_todo_items();
+    }
+    
+    /// The project items must be in to be shown, if any
+    pub fn project_filter(&self) -> Option<Uuid> {
+        self.project_filter
+    }
+    
+    /// Show only items in a project, or clear the project filter
+    pub fn set_project_filter(&mut self, project_id: Option<Uuid>) {
+        self.project_filter = project_id;
         self.update_todo_items();
     }
     
+    /// Snapshot of the view state worth restoring on the next launch
+    pub fn session(&self) -> Session {
+        Session {
+            scroll_offset: self.scroll_offset,
+            filter_type: self.filter_type,
+            filter_value: self.filter_value.clone(),
+            status_filter: self.status_filter,
+            priority_filter: self.priority_filter,
+            tag_filter: self.tag_filter.clone(),
+            project_filter: self.project_filter,
+            selected_item: self.selected_item,
+            expanded_items: self.rows.iter()
+                .map(|row| row.id)
+                .filter(|id| self.expanded_items.contains(id))
+                .collect(),
+            collapsed_items: {
+                let mut collapsed: Vec<Uuid> = self.collapsed_items.iter().copied().collect();
+                collapsed.sort();
+                collapsed
+            },
+            // The app fills in which view was shown
+            ..Session::default()
+        }
+    }
+    
+    /// Restore a saved view state; items that no longer exist are ignored
+    pub fn restore_session(&mut self, session: &Session) {
+        self.filter_type = session.filter_type;
+        self.filter_value = session.filter_value.clone();
+        if !self.filter_value.is_empty() {
+            self.search_text = self.filter_value.clone();
+            self.search_input.set_text(self.filter_value.as_str());
+        }
+        self.status_filter = session.status_filter;
+        self.priority_filter = session.priority_filter;
+        self.tag_filter = session.tag_filter.clone();
+        self.project_filter = session.project_filter;
+        self.selected_item = session.selected_item;
+        self.expanded_items = session.expanded_items.iter().copied().collect();
+        self.collapsed_items = session.collapsed_items.iter().copied().collect();
+        
+        // Rows that stay take the session's selection and expansion
+        for widget in &mut self.todo_item_widgets {
+            widget.set_selected(self.selected_item == Some(widget.id()));
+            widget.set_expanded(self.expanded_items.contains(&widget.id()));
+        }
+        self.setup_todo_item_widgets();
+        self.scroll_to(session.scroll_offset);
+    }
+    
+    /// Show the changes made to the list since the rows were last built, e.g.
+    /// by undo, a script or sync
+    ///
+    /// Rows read their item when drawn, so edits only need what's derived from
+    /// elsewhere in the list brought up to date: the subtask counts on parents
+    /// and the project accents. Anything that may add, remove or reorder rows,
+    /// or recolor a project, rebuilds them, once for all the changes.
+    pub fn refresh(&mut self) {
+        let mut updated = Vec::new();
+        for event in self.list_events.try_iter() {
+            match event {
+                ListEvent::ItemUpdated { id } => updated.push(id),
+                ListEvent::ItemAdded { .. } | ListEvent::ItemRemoved { .. } | ListEvent::ItemMoved { .. }
+                | ListEvent::ProjectsChanged | ListEvent::Reset => {
+                    return self.setup_todo_item_widgets();
+                }
+            }
+        }
+        if updated.is_empty() {
+            return;
+        }
+        
+        // Filters and sorting may hide or move an edited item
+        if self.is_filtered() || !self.sort_order().is_manual() {
+            return self.setup_todo_item_widgets();
+        }
+        let (progress, accents, tags) = {
+            let todo_list = self.commands.lock();
+            let parents: HashSet<Uuid> = updated.iter()
+                .filter_map(|&id| todo_list.get_item(id)?.parent_id())
+                .collect();
+            let progress: Vec<(Uuid, Option<(usize, usize)>)> = parents.into_iter()
+                .map(|parent| (parent, todo_list.child_progress(parent)))
+                .collect();
+            let accents: Vec<(Uuid, Option<[f32; 4]>)> = updated.iter()
+                .map(|&id| (id, self.project_accent(&todo_list, id)))
+                .collect();
+            (progress, accents, todo_list.all_tags())
+        };
+        // New or gone tags change the chips, and with them where the rows start
+        if !self.filter_controls.shows_tags(&tags) {
+            return self.setup_todo_item_widgets();
+        }
+        for (parent, counts) in progress {
+            if let Some(row) = self.rows.iter_mut().find(|row| row.id == parent) {
+                row.child_progress = counts;
+            }
+            if let Some(widget) = self.todo_item_widgets.iter_mut().find(|widget| widget.id() == parent) {
+                widget.set_child_progress(counts);
+            }
+        }
+        for (id, accent) in accents {
+            if let Some(row) = self.rows.iter_mut().find(|row| row.id == id) {
+                row.accent = accent;
+            }
+            if let Some(widget) = self.todo_item_widgets.iter_mut().find(|widget| widget.id() == id) {
+                widget.set_accent(accent);
+            }
+        }
+    }
+    
     /// Create filter buttons with proper layout
-    fn create_filter_buttons(x: f32, y: f32, width: f32, theme: &CyberpunkTheme) -> Vec<Button> {
+    fn create_filter_buttons(x: f32, y: f32, theme: &CyberpunkTheme) -> Vec<Button> {
         let button_height = 30.0;
         let button_padding = 10.0;
         let button_width = 140.0;
         let button_margin = 5.0;
         let button_y = y + button_padding * 2.0 + button_height;
-        let mut buttons = Vec::new();
-        
-        // All button
-        buttons.push(
+        vec![
+            // All button, selected to start with
             Button::new(
                 x + button_padding,
                 button_y,
                 button_width,
                 button_height,
-                "All Tasks"
+                tr("filter-all-tasks")
             ).with_text_color(to_color(theme.bright_text()))
-             .with_background_color(to_color(theme.filter_button_selected_bg())) // Start with "All" selected
-        );
-        
-        // Active button
-        buttons.push(
+             .with_background_color(to_color(theme.filter_button_selected_bg())),
+            // Active button
             Button::new(
                 x + button_padding + button_width + button_margin,
                 button_y,
                 button_width,
                 button_height,
-                "Active"
+                tr("filter-active")
             ).with_text_color(to_color(theme.bright_text()))
-             .with_background_color(to_color(theme.filter_button_bg()))
-        );
-        
-        // Completed button
-        buttons.push(
+             .with_background_color(to_color(theme.filter_button_bg())),
+            // Completed button
             Button::new(
                 x + button_padding + (button_width + button_margin) * 2.0,
                 button_y,
                 button_width,
                 button_height,
-                "Completed"
+                tr("filter-completed")
             ).with_text_color(to_color(theme.bright_text()))
-             .with_background_color(to_color(theme.filter_button_bg()))
-        );
-        
-        buttons
+             .with_background_color(to_color(theme.filter_button_bg())),
+        ]
     }
     
     /// Update the todo item widgets based on current state and filters
     fn update_todo_items(&mut self) {
-        // Clear current todo item widgets
-        self.todo_item_widgets.clear();
-        
-        // Get filtered items
-        let items = {
-            let todo_list = self.todo_list.lock().unwrap();
-            self.filter_items(&todo_list.all_items())
-        };
-        
-        // Calculate the appropriate area for todo items
-        let top_controls_height = 30.0 + 10.0 * 2.0; // Add button + title input
-        let filter_controls_height = 30.0 + 10.0; // Filter controls
-        let visible_area_y = self.y + top_controls_height + filter_controls_height;
-        let visible_area_height = self.height - top_controls_height - filter_controls_height;
-        
         // Generate todo item widgets with hierarchy
         self.setup_todo_item_widgets();
     }
     
-    /// Filter todo items based on current filter settings
-    fn filter_items(&self, items: &Vec<&TodoItem>) -> Vec<TodoItem> {
+    /// Whether the list shows search results rather than the hierarchy
+    fn is_searching(&self) -> bool {
+        !self.filter_value.trim().is_empty()
+    }
+    
+    /// Whether anything narrows down the items shown
+    fn is_filtered(&self) -> bool {
+        self.is_searching()
+            || self.status_filter.is_some()
+            || self.priority_filter.is_some()
+            || self.tag_filter.is_some()
+            || self.project_filter.is_some()
+            || self.custom_filter.is_some()
+    }
+    
+    /// How well an item matches the search in the chosen field, or None if it doesn't
+    ///
+    /// The match's positions are in the title, and empty if only the description matched.
+    fn search_match(&self, item: &TodoItem) -> Option<FuzzyMatch> {
+        if !self.is_searching() {
+            return Some(FuzzyMatch::default());
+        }
+        let query = self.filter_value.as_str();
+        let title = || fuzzy_match(query, item.title());
+        let description = || item.description()
+            .and_then(|description| fuzzy_match(query, description))
+            .map(|found| FuzzyMatch { positions: Vec::new(), ..found });
+        match self.filter_type {
+            FilterType::Title => title(),
+            FilterType::Description => description(),
+            // Any field scores as the better one, highlighting the title if it matched
+            _ => match (title(), description()) {
+                (Some(title), Some(description)) => Some(FuzzyMatch { score: title.score.max(description.score), ..title }),
+                (title, description) => title.or(description),
+            },
+        }
+    }
+    
+    /// The items that pass the current filter settings, with how well they match the search
+    fn filter_items<'a>(&self, items: &[&'a TodoItem]) -> Vec<(&'a TodoItem, FuzzyMatch)> {
         items.iter()
-            .filter(|item| {
+            .filter_map(|&item| {
                 // Text filter
-                let text_match = if !self.filter_value.is_empty() {
-                    let search_text = self.filter_value.to_lowercase();
-                    
-                    match self.filter_type {
-                        FilterType::Title => item.title().to_lowercase().contains(&search_text),
-                        FilterType::Description => {
-                            if let Some(desc) = item.description() {
-                                desc.to_lowercase().contains(&search_text)
-                            } else {
-                                false
-                            }
-                        },
-                        _ => true
-                    }
-                } else {
-                    true
-                };
+                let found = self.search_match(item)?;
                 
                 // Status filter
                 let status_match = match self.status_filter {
@@ -310,319 +770,821 @@ impl TodoListWidget {
                     None => true,
                 };
                 
-                text_match && status_match && priority_match
+                // Tag filter
+                let tag_match = match &self.tag_filter {
+                    Some(tag) => item.has_tag(tag),
+                    None => true,
+                };
+                
+                // Project filter
+                let project_match = self.project_filter.is_none() || item.project_id() == self.project_filter;
+                
+                // Custom filter
+                let custom_match = match &self.custom_filter {
+                    Some(filter) => filter(item),
+                    None => true,
+                };
+                
+                (status_match && priority_match && tag_match && project_match && custom_match).then_some((item, found))
             })
-            .map(|&item| item.clone())
             .collect()
     }
     
-    /// Set up callbacks for a TodoItem widget
-    fn setup_todo_item_callbacks(&self, widget: Arc<Mutex<TodoItemWidget>>, item: TodoItem) {
-        let todo_list_clone = self.todo_list.clone();
-        let item_id = item.id();
-        
-        // --- Create status change callback --- 
-        let status_callback = {
-            let list_for_status = todo_list_clone.clone(); // Clone Arc for this closure
-            let on_status_change = self.on_item_status_change.clone();
-            let item_for_status = item.clone();
-            Arc::new(move |status: Status| {
-                if let Ok(mut todo_list) = list_for_status.lock() { // Use the cloned Arc
-                    if let Some(item) = todo_list.get_item_mut(item_id) {
-                        item.set_status(status);
-                        
-                        // Call external callback if provided
-                        if let Some(callback) = &on_status_change {
-                            callback(item.clone());
-                        }
-                    }
-                }
-            })
-        };
-        
-        // --- Create edit callback --- 
-        let edit_callback = {
-            // No need to clone todo_list here
-            let on_item_edit = self.on_item_edit.clone();
-            let item_for_edit = item.clone();
-            Arc::new(move || {
-                if let Some(callback) = &on_item_edit {
-                    callback(item_for_edit.clone());
-                }
-            })
-        };
-        
-        // --- Create delete callback --- 
-        let delete_callback = {
-            let list_for_delete = todo_list_clone.clone(); // Clone Arc again for this closure
-            let on_item_delete = self.on_item_delete.clone();
-            let item_for_delete = item.clone(); 
-            Arc::new(move || {
-                if let Ok(mut todo_list) = list_for_delete.lock() { // Use the cloned Arc
-                    todo_list.remove_item(item_id);
-                    
-                    // Call external callback if provided
-                    if let Some(callback) = &on_item_delete {
-                        callback(item_for_delete.clone());
-                    }
-                }
-            })
-        };
-        
-        // --- Set callbacks on the widget --- 
-        if let Ok(mut widget_guard) = widget.lock() {
-            // Clone the widget data to modify it, as `with_on_*` consumes self
-            let mut temp_widget = (*widget_guard).clone();
-
-            let status_cb = status_callback.clone();
-            temp_widget = temp_widget.with_on_status_change(move |status| {
-                status_cb(status);
-            });
-            
-            let edit_cb = edit_callback.clone();
-            temp_widget = temp_widget.with_on_edit(move || {
-                edit_cb();
-            });
-            
-            let delete_cb = delete_callback.clone();
-            temp_widget = temp_widget.with_on_delete(move || {
-                delete_cb();
-            });
-            
-            // Assign the modified widget back to the MutexGuard
-            *widget_guard = temp_widget;
+    /// Rows to show in hierarchy order: the items that pass the filters, less the children of collapsed ones
+    ///
+    /// Each row comes with its depth and, for items with children, whether they're collapsed.
+    fn visible_rows(&self, hierarchy: &[(&TodoItem, usize)], shown: &HashSet<Uuid>) -> Vec<(Uuid, usize, Option<bool>)> {
+        let mut rows = Vec::new();
+        let mut collapsed_depth: Option<usize> = None;
+        for (index, &(item, depth)) in hierarchy.iter().enumerate() {
+            if collapsed_depth.is_some_and(|collapsed| depth > collapsed) {
+                continue;
+            }
+            collapsed_depth = None;
+            if !shown.contains(&item.id()) {
+                continue;
+            }
+            let has_children = hierarchy.get(index + 1).is_some_and(|&(_, next)| next > depth);
+            let collapsed = self.collapsed_items.contains(&item.id());
+            if has_children && collapsed {
+                collapsed_depth = Some(depth);
+            }
+            rows.push((item.id(), depth, has_children.then_some(collapsed)));
         }
+        rows
     }
-
+    
+    /// Accent color of the project an item is in, if it's in one
+    fn project_accent(&self, todo_list: &TodoList, id: Uuid) -> Option<[f32; 4]> {
+        let project = todo_list.project(todo_list.get_item(id)?.project_id()?)?;
+        Some(self.theme.project_accent(project.color()))
+    }
+    
     /// Set up todo item widgets based on the filtered and visible items
     fn setup_todo_item_widgets(&mut self) {
-        // Get filtered items first, releasing the lock on todo_list immediately
-        let filtered_items = {
-            let todo_list_guard = match self.todo_list.lock() {
+        profile_scope!("list_layout");
+        
+        // Get filtered ids first, releasing the lock on todo_list immediately
+        let mut title_matches: HashMap<Uuid, Vec<usize>> = HashMap::new();
+        let (rows, tags, mut progress, mut accents, sort_order) = {
+            let todo_list_guard = match self.commands.list().lock() {
                 Ok(guard) => guard,
                 Err(_) => {
                     // Log error or handle appropriately
                     return; 
                 }
             };
-            self.filter_items(&todo_list_guard.all_items())
+            // Building from the list as it is shows every change made so far
+            self.list_events.try_iter().for_each(drop);
+            
+            // A tag nothing has any more can't be filtered by, or cleared from its chip
+            let tags = todo_list_guard.all_tags();
+            if self.tag_filter.as_ref().is_some_and(|tag| !tags.contains(tag)) {
+                self.tag_filter = None;
+            }
+            // Likewise a project that's been removed
+            if self.project_filter.is_some_and(|id| todo_list_guard.project(id).is_none()) {
+                self.project_filter = None;
+            }
+            self.collapsed_items.retain(|&id| todo_list_guard.get_item(id).is_some());
+            let mut matches = self.filter_items(&todo_list_guard.all_items());
+            let rows = if self.is_searching() {
+                // Best matches first, in a flat list since a match's parent may not match
+                matches.sort_by(|(a, a_match), (b, b_match)| {
+                    b_match.score.cmp(&a_match.score).then_with(|| a.title().cmp(b.title()))
+                });
+                matches.into_iter()
+                    .map(|(item, found)| {
+                        title_matches.insert(item.id(), found.positions);
+                        (item.id(), 0, None)
+                    })
+                    .collect()
+            } else {
+                let shown: HashSet<Uuid> = matches.into_iter().map(|(item, _)| item.id()).collect();
+                self.visible_rows(&todo_list_guard.sorted_view(), &shown)
+            };
+            let progress: HashMap<Uuid, (usize, usize)> = rows.iter()
+                .filter_map(|&(id, _, _)| Some((id, todo_list_guard.child_progress(id)?)))
+                .collect();
+            let accents: HashMap<Uuid, [f32; 4]> = rows.iter()
+                .filter_map(|&(id, _, _)| Some((id, self.project_accent(&todo_list_guard, id)?)))
+                .collect();
+            (rows, tags, progress, accents, todo_list_guard.sort_order())
             // Lock is released here
         };
+        self.filter_controls.set_tags(&tags, self.tag_filter.as_deref(), &self.theme);
+        self.sync_filter_controls(sort_order);
+        self.layout_scrollbar();
 
-        // Preserve expansion state *before* clearing widgets
-        let expanded_item_ids: Vec<Uuid> = self.expanded_items.iter()
-            .filter_map(|&idx| {
-                if idx < self.todo_item_widgets.len() {
-                    if let Ok(widget) = self.todo_item_widgets[idx].lock() {
-                        return Some(widget.todo_item.id());
-                    }
-                }
-                None
+        self.rows = rows.into_iter()
+            .map(|(id, depth, children_collapsed)| ListRow {
+                id,
+                depth,
+                children_collapsed,
+                child_progress: progress.remove(&id),
+                accent: accents.remove(&id),
+                title_matches: title_matches.remove(&id).unwrap_or_default(),
             })
             .collect();
-
-        // Clear existing widgets and state
-        self.todo_item_widgets.clear();
-        self.visible_items.clear();
-        self.expanded_items.clear();
-        
-        // Calculate starting position for items
-        let items_start_y = self.y + 50.0; // Below filter controls
-        let item_height = 40.0; // Standard height for todo items
-        let mut current_y = items_start_y - self.scroll_offset; // Apply initial scroll offset
-
-        // Create widgets for each filtered item
-        for (i, item) in filtered_items.into_iter().enumerate() {
-            let todo_item_widget = TodoItemWidget::new(
-                self.x, // Position relative to parent TodoListWidget X
-                current_y, // Set the calculated Y position
-                self.width, 
-                item.clone()
-            );
-            
-            let widget_arc = Arc::new(Mutex::new(todo_item_widget));
-            
-            // Set up callbacks (this function handles its own locking)
-            self.setup_todo_item_callbacks(widget_arc.clone(), item.clone());
-            
-            self.todo_item_widgets.push(widget_arc);
-            self.visible_items.push(i);
-            
-            // Restore expansion state using the preserved IDs
-            if expanded_item_ids.contains(&item.id()) {
-                self.expanded_items.push(i);
+        
+        // Keep the widgets of rows that are still shown, so their hover, expansion
+        // and animation state survive; rows read their item when drawn, so edits
+        // need no new widgets either
+        self.sync_expanded_items();
+        let shown: HashMap<Uuid, &ListRow> = self.rows.iter().map(|row| (row.id, row)).collect();
+        self.todo_item_widgets.retain(|widget| shown.contains_key(&widget.id()));
+        for widget in &mut self.todo_item_widgets {
+            shown[&widget.id()].apply_to(widget);
+            if widget.dimensions().0 != self.width {
+                widget.set_dimensions(self.width, widget.dimensions().1);
+            }
+        }
+        
+        // Drop the selection and open modals of items that were filtered out or deleted
+        self.multi_selection.retain(|id| shown.contains_key(id));
+        self.expanded_items.retain(|id| shown.contains_key(id));
+        if !self.selected_item.is_some_and(|id| shown.contains_key(&id)) {
+            self.selected_item = None;
+            if let Some(ListFocus::Item(_)) = self.focus.focused() {
+                self.focus.set_focus(None);
             }
-            
-            // Update Y for the next item
-            current_y += item_height; 
         }
         
         // Calculate max scroll after all modifications to self are done
         self.calculate_max_scroll();
+        self.layout_rows();
     }
     
-    /// Render the filter controls
-    fn render_filter_controls(&self, ctx: &mut RenderContext) {
-        // Filter controls at the top
-        let filter_y = self.y + 10.0;
-        
-        // Draw search box
-        ctx.draw_rect(
-            self.x + 10.0, filter_y,
-            150.0, 30.0,
-            self.theme.get_background_color(),
-        );
-        
-        // Text input placeholder or value
-        let search_text = if self.filter_value.is_empty() { "Search..." } else { &self.filter_value };
-        ctx.draw_text(
-            search_text,
-            self.x + 15.0, filter_y + 5.0,
-            self.theme.small_text_size(),
-            self.theme.get_text_color(),
-        );
+    /// Give the rows near the viewport a widget and stack them down from the
+    /// scroll offset, each taking room for as much of it as shows
+    ///
+    /// Rows further away have no widget, so a long list costs no more per frame
+    /// than a short one. The rows above the first widget are all full height.
+    fn layout_rows(&mut self) {
+        let (items_y, items_height) = self.items_area();
+        let first = ((self.scroll_offset / ROW_HEIGHT) as usize).saturating_sub(OVERSCAN_ROWS).min(self.rows.len());
+        let bottom = items_y + items_height + OVERSCAN_ROWS as f32 * ROW_HEIGHT;
+        let mut existing: HashMap<Uuid, TodoItemWidget> = self.todo_item_widgets
+            .drain(..)
+            .map(|widget| (widget.id(), widget))
+            .collect();
         
-        // Draw filter type dropdown
-        let filter_type_x = self.x + 170.0;
-        ctx.draw_rect(
-            filter_type_x, filter_y,
-            120.0, 30.0,
-            self.theme.get_background_color(),
-        );
+        let mut y_position = items_y - self.scroll_offset + first as f32 * ROW_HEIGHT;
+        let mut end = first;
+        while end < self.rows.len() && y_position < bottom {
+            let row = &self.rows[end];
+            let mut widget = existing.remove(&row.id).unwrap_or_else(|| self.row_widget(row));
+            // A row sliding in or out comes from under the row above
+            let room = ROW_HEIGHT * widget.reveal();
+            widget.set_position(self.x, y_position + room - ROW_HEIGHT);
+            y_position += room;
+            self.todo_item_widgets.push(widget);
+            end += 1;
+        }
         
-        // Filter type text
-        let filter_type_text = match self.filter_type {
-            FilterType::Title => "Title",
-            FilterType::Description => "Description",
-            _ => "All Fields",
+        // A row that slid out of view is done sliding out; an open modal keeps
+        // its row's widget, parked where the row is
+        let mut slid_out = HashSet::new();
+        for (id, mut widget) in existing {
+            if widget.is_sliding_out() {
+                slid_out.insert(id);
+            } else if let Some(index) = self.rows.iter().position(|row| row.id == id).filter(|_| widget.is_expanded()) {
+                let y = if index < first {
+                    items_y - self.scroll_offset + index as f32 * ROW_HEIGHT
+                } else {
+                    y_position + (index - end) as f32 * ROW_HEIGHT
+                };
+                widget.set_position(self.x, y);
+                self.todo_item_widgets.push(widget);
+            }
+        }
+        if !slid_out.is_empty() {
+            self.rows.retain(|row| !slid_out.contains(&row.id));
+            self.calculate_max_scroll();
+            self.layout_rows();
+        }
+    }
+    
+    /// A widget for a row scrolling into view
+    fn row_widget(&self, row: &ListRow) -> TodoItemWidget {
+        let mut widget = TodoItemWidget::new(
+            self.x, // Position relative to parent TodoListWidget X
+            self.y + self.controls_height(),
+            self.width,
+            row.id,
+            self.commands.list().clone(),
+        )
+        .with_theme(self.theme.clone());
+        row.apply_to(&mut widget);
+        widget.set_selected(self.selected_item == Some(row.id));
+        widget.set_expanded(self.expanded_items.contains(&row.id));
+        widget.set_marked(self.multi_selection.contains(&row.id));
+        widget
+    }
+    
+    /// Hide (`collapsed`) or show the children of an item, sliding their rows out or in
+    pub fn set_collapsed(&mut self, id: Uuid, collapsed: bool) {
+        let changed = if collapsed {
+            self.collapsed_items.insert(id)
+        } else {
+            self.collapsed_items.remove(&id)
         };
+        if !changed {
+            return;
+        }
         
-        ctx.draw_text(
-            filter_type_text,
-            filter_type_x + 10.0, filter_y + 5.0,
-            self.theme.small_text_size(),
-            self.theme.get_text_color(),
-        );
-        
-        // Status filter
-        let status_x = self.x + 300.0;
-        ctx.draw_rect(
-            status_x, filter_y,
-            120.0, 30.0,
-            self.theme.get_background_color(),
-        );
-        
-        // Status text
-        let status_text = match self.status_filter {
-            Some(Status::NotStarted) => "Not Started",
-            Some(Status::InProgress) => "In Progress",
-            Some(Status::Completed) => "Completed",
-            None => "All Status",
+        if collapsed {
+            // The rows in view stay until they've slid out, the others go at once;
+            // a selected one passes the selection to the item
+            let children: HashSet<Uuid> = self.rows[self.descendant_rows(id)].iter().map(|row| row.id).collect();
+            if let Some(row) = self.rows.iter_mut().find(|row| row.id == id) {
+                row.children_collapsed = Some(true);
+            }
+            if let Some(widget) = self.todo_item_widgets.iter_mut().find(|widget| widget.id() == id) {
+                widget.set_children_collapsed(Some(true));
+            }
+            let selected_child = self.selected_item.is_some_and(|selected| children.contains(&selected));
+            for widget in self.todo_item_widgets.iter_mut().filter(|widget| children.contains(&widget.id())) {
+                widget.slide_out();
+            }
+            let sliding: HashSet<Uuid> = self.todo_item_widgets.iter().map(TodoItemWidget::id).collect();
+            self.rows.retain(|row| !children.contains(&row.id) || sliding.contains(&row.id));
+            self.calculate_max_scroll();
+            self.layout_rows();
+            if selected_child {
+                self.select_item(Some(id));
+            }
+        } else {
+            let shown: HashSet<Uuid> = self.todo_item_widgets.iter().map(|widget| widget.id()).collect();
+            self.update_todo_items();
+            let children: HashSet<Uuid> = self.rows[self.descendant_rows(id)].iter().map(|row| row.id).collect();
+            for widget in self.todo_item_widgets.iter_mut().filter(|widget| children.contains(&widget.id())) {
+                widget.slide_in(!shown.contains(&widget.id()));
+            }
+            self.layout_rows();
+        }
+    }
+    
+    /// Whether the children of an item are hidden
+    pub fn is_collapsed(&self, id: Uuid) -> bool {
+        self.collapsed_items.contains(&id)
+    }
+    
+    /// Indices of the rows below an item's own that are nested under it
+    fn descendant_rows(&self, id: Uuid) -> std::ops::Range<usize> {
+        let Some(index) = self.rows.iter().position(|row| row.id == id) else {
+            return 0..0;
         };
-        
-        ctx.draw_text(
-            status_text,
-            status_x + 10.0, filter_y + 5.0,
-            self.theme.small_text_size(),
-            self.theme.get_text_color(),
-        );
-        
-        // Priority filter
-        let priority_x = self.x + 430.0;
-        ctx.draw_rect(
-            priority_x, filter_y,
-            120.0, 30.0,
-            self.theme.get_background_color(),
-        );
-        
-        // Priority text
-        let priority_text = match self.priority_filter {
-            Some(Priority::Low) => "Low",
-            Some(Priority::Medium) => "Medium",
-            Some(Priority::High) => "High",
-            None => "All Priority",
+        let level = self.rows[index].depth;
+        let count = self.rows[index + 1..].iter()
+            .take_while(|row| row.depth > level)
+            .count();
+        index + 1..index + 1 + count
+    }
+    
+    /// Show the current filter settings and the list's sort order on the filter controls
+    fn sync_filter_controls(&mut self, sort_order: SortOrder) {
+        let search = if self.filter_value.is_empty() {
+            Text::Label("search-placeholder")
+        } else {
+            Text::Plain(self.filter_value.clone())
         };
-        
-        ctx.draw_text(
-            priority_text,
-            priority_x + 10.0, filter_y + 5.0,
-            self.theme.small_text_size(),
-            self.theme.get_text_color(),
-        );
+        let field = match self.filter_type {
+            FilterType::Title => "filter-field-title",
+            FilterType::Description => "filter-field-description",
+            _ => "filter-field-all",
+        };
+        let status = self.status_filter.map_or("filter-status-all", i18n::status_label_id);
+        let priority = self.priority_filter.map_or("filter-priority-all", i18n::priority_label_id);
+        
+        let controls = &mut self.filter_controls;
+        controls.world.texts.insert(controls.search, search);
+        controls.world.texts.insert(controls.field, Text::Label(field));
+        controls.world.texts.insert(controls.status, Text::Label(status));
+        controls.world.texts.insert(controls.priority, Text::Label(priority));
+        controls.world.texts.insert(controls.sort, Text::Label(i18n::sort_label_id(sort_order.key)));
+        let direction = if sort_order.descending { "↓" } else { "↑" };
+        controls.world.texts.insert(controls.sort_direction, Text::Plain(direction.to_string()));
     }
     
-    /// Handle mouse wheel for scrolling
-    pub fn handle_mouse_wheel(&mut self, delta: f32) {
-        // Update scroll offset with the mouse wheel delta
-        self.scroll_offset = (self.scroll_offset + delta * 20.0)
-            .max(0.0)
-            .min(self.max_scroll);
-        
-        // Update positions of todo item widgets based on new scroll offset
-        let top_controls_height = 50.0; // Height of the filter controls area
-        let visible_area_y = self.y + top_controls_height;
-        
-        // Reposition all visible todo item widgets based on scroll offset
-        let mut y_position = visible_area_y - self.scroll_offset;
-        let item_height = 40.0; // Standard height for todo items
-        
-        for &item_idx in &self.visible_items {
-            if item_idx < self.todo_item_widgets.len() {
-                if let Ok(mut widget) = self.todo_item_widgets[item_idx].lock() {
-                    widget.set_position(self.x, y_position);
-                    y_position += item_height;
-                }
-            }
+    /// Height of the filter controls, tag chips included
+    fn controls_height(&self) -> f32 {
+        if self.filter_controls.tag_chips.is_empty() {
+            CONTROLS_HEIGHT
+        } else {
+            CONTROLS_HEIGHT + TAG_ROW_HEIGHT
         }
     }
     
-    /// Set a callback for when an item's status changes
-    pub fn with_on_status_change<F>(mut self, callback: F) -> Self
-    where
-        F: Fn(TodoItem) + Send + Sync + 'static,
-    {
-        self.on_item_status_change = Some(Arc::new(callback));
-        self
+    /// Fit the scrollbar to the item area, which shrinks while tag chips are shown
+    fn layout_scrollbar(&mut self) {
+        let controls_height = self.controls_height();
+        self.scrollbar.set_position(self.x + self.width - SCROLLBAR_WIDTH - SCROLLBAR_MARGIN, self.y + controls_height);
+        self.scrollbar.set_dimensions(SCROLLBAR_WIDTH, (self.height - controls_height).max(0.0));
     }
     
-    /// Set a callback for when an item is edited
-    pub fn with_on_edit<F>(mut self, callback: F) -> Self
-    where
-        F: Fn(TodoItem) + Send + Sync + 'static,
-    {
-        self.on_item_edit = Some(Arc::new(callback));
-        self
+    /// Create the scrollbar along the right edge of the item area
+    fn create_scrollbar(x: f32, y: f32, width: f32, height: f32) -> Scrollbar {
+        Scrollbar::new(
+            x + width - SCROLLBAR_WIDTH - SCROLLBAR_MARGIN,
+            y + CONTROLS_HEIGHT,
+            SCROLLBAR_WIDTH,
+            (height - CONTROLS_HEIGHT).max(0.0),
+        )
     }
     
-    /// Set a callback for when an item is deleted
-    pub fn with_on_delete<F>(mut self, callback: F) -> Self
-    where
-        F: Fn(TodoItem) + Send + Sync + 'static,
-    {
-        self.on_item_delete = Some(Arc::new(callback));
-        self
+    /// Handle mouse wheel for scrolling, by a number of pixels (positive scrolls down)
+    ///
+    /// The list glides there over the next frames; further notches add to the distance.
+    pub fn handle_mouse_wheel(&mut self, delta: f32) {
+        // The menu would be left pointing at another row
+        self.close_context_menu();
+        self.scroll_smoothly_to(self.scroll_target + delta);
     }
     
-    /// Handle mouse movement for hover effects
-    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
-        // Handle mouse movement in filter buttons
-        for button in &mut self.filter_buttons {
-            if button.contains_point(x, y) {
-                button.handle_mouse_move(x, y);
-            }
+    /// Scroll the list by a number of pixels, e.g. while panning with the middle button
+    ///
+    /// The list follows the pointer exactly; `end_pan` lets it coast on.
+    pub fn pan_by(&mut self, dy: f32) {
+        *self.panning.get_or_insert(0.0) += dy;
+        self.scroll_to(self.scroll_offset + dy);
+    }
+    
+    /// Release the list after panning, flinging it on at the speed it was moving
+    pub fn end_pan(&mut self) {
+        if self.panning.take().is_some() && self.pan_velocity.abs() >= MIN_FLING_SPEED {
+            self.fling_velocity = self.pan_velocity;
+        }
+        self.pan_velocity = 0.0;
+    }
+    
+    /// Scroll a page up (negative) or down, keeping one row of overlap
+    pub fn scroll_page(&mut self, direction: f32) {
+        let item_height = ROW_HEIGHT;
+        let page = (self.height - self.controls_height() - item_height).max(item_height);
+        self.scroll_smoothly_to(self.scroll_target + direction * page);
+    }
+    
+    /// Glide to an absolute offset over the next frames
+    pub fn scroll_smoothly_to(&mut self, offset: f32) {
+        self.fling_velocity = 0.0;
+        self.scroll_target = offset.max(0.0).min(self.max_scroll);
+    }
+    
+    /// Current scroll offset in pixels, and where it's heading
+    pub fn scroll_position(&self) -> (f32, f32) {
+        (self.scroll_offset, self.scroll_target)
+    }
+    
+    /// Advance the scroll animation: panning speed, flings and easing toward the target
+    fn update_scroll(&mut self, delta_time: f32) {
+        if delta_time <= 0.0 {
+            return;
+        }
+        if let Some(moved) = self.panning.as_mut() {
+            // Average over a couple of frames so a single jittery one doesn't decide the fling
+            self.pan_velocity = (self.pan_velocity + *moved / delta_time) / 2.0;
+            *moved = 0.0;
+            return;
+        }
+        
+        if self.fling_velocity != 0.0 {
+            let target = self.scroll_target + self.fling_velocity * delta_time;
+            self.scroll_target = target.max(0.0).min(self.max_scroll);
+            self.fling_velocity *= (-FLING_FRICTION * delta_time).exp();
+            if self.fling_velocity.abs() < MIN_FLING_SPEED || self.scroll_target != target {
+                self.fling_velocity = 0.0;
+            }
+        }
+        
+        let gap = self.scroll_target - self.scroll_offset;
+        if gap.abs() < 0.5 {
+            if gap != 0.0 {
+                self.set_scroll_offset(self.scroll_target);
+            }
+        } else {
+            let eased = gap * (1.0 - (-SCROLL_SMOOTHING * delta_time).exp());
+            self.set_scroll_offset(self.scroll_offset + eased);
+        }
+    }
+    
+    /// How the list being shown is sorted
+    pub fn sort_order(&self) -> SortOrder {
+        self.commands.lock().sort_order()
+    }
+    
+    /// Sort the list being shown; the order is kept with the list, so each
+    /// list of a workspace keeps its own
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        if order == self.sort_order() {
+            return;
+        }
+        self.commands.lock().set_sort_order(order);
+        self.events.push(UiEvent::SortChanged { order });
+        self.setup_todo_item_widgets();
+    }
+    
+    /// Choose which filter controls the toolbar shows, left to right
+    pub fn set_toolbar(&mut self, controls: &[FilterControl]) {
+        self.filter_controls.arrange(controls);
+    }
+    
+    /// Enable or disable focus-follows-mouse, with the delay before hovering focuses the search box
+    pub fn set_focus_follows_mouse(&mut self, enabled: bool, delay: f32) {
+        self.focus_follows_mouse = enabled;
+        self.hover_focus_delay = delay;
+        self.search_hover_time = None;
+    }
+    
+    /// The scrollbar along the item area
+    pub fn scrollbar(&self) -> &Scrollbar {
+        &self.scrollbar
+    }
+    
+    /// Id of the item with the keyboard selection
+    pub fn selected_item(&self) -> Option<Uuid> {
+        self.selected_item
+    }
+    
+    /// Ids of the items shown, in display order
+    pub fn shown_items(&self) -> Vec<Uuid> {
+        self.shown_rows().map(|row| row.id).collect()
+    }
+    
+    /// Rows in display order, less those sliding out under a collapsed parent
+    fn shown_rows(&self) -> impl Iterator<Item = &ListRow> {
+        let sliding_out: HashSet<Uuid> = self.todo_item_widgets.iter()
+            .filter(|widget| widget.is_sliding_out())
+            .map(TodoItemWidget::id)
+            .collect();
+        self.rows.iter().filter(move |row| !sliding_out.contains(&row.id))
+    }
+    
+    /// Widget of a shown item, while its row is near enough the viewport to have one
+    pub fn item_widget(&self, id: Uuid) -> Option<&TodoItemWidget> {
+        self.todo_item_widgets.iter().find(|widget| widget.id() == id)
+    }
+    
+    /// Move the keyboard selection to an item (or clear it)
+    ///
+    /// If a row has keyboard focus, focus moves along with the selection.
+    pub fn select_item(&mut self, id: Option<Uuid>) {
+        self.selected_item = id;
+        for widget in &mut self.todo_item_widgets {
+            let selected = Some(widget.id()) == id;
+            widget.set_selected(selected);
+        }
+        if let Some(ListFocus::Item(_)) = self.focus.focused() {
+            self.focus.set_focus(id.map(ListFocus::Item));
+        }
+    }
+    
+    /// Tasks picked for bulk actions, in display order
+    pub fn multi_selection(&self) -> Vec<Uuid> {
+        self.shown_rows()
+            .map(|row| row.id)
+            .filter(|id| self.multi_selection.contains(id))
+            .collect()
+    }
+    
+    /// Pick these tasks for bulk actions, instead of the ones picked before
+    fn set_multi_selection(&mut self, ids: HashSet<Uuid>) {
+        for widget in &mut self.todo_item_widgets {
+            widget.set_marked(ids.contains(&widget.id()));
+        }
+        self.multi_selection = ids;
+    }
+    
+    /// Pick every task shown (Ctrl+A)
+    pub fn select_all(&mut self) {
+        let ids = self.shown_items().into_iter().collect();
+        self.set_multi_selection(ids);
+    }
+    
+    /// Tell the list which modifier keys are held, for Ctrl+click and Shift+click
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+    
+    /// Pick or unpick a clicked row, returning false for clicks without Ctrl or Shift
+    ///
+    /// Ctrl+click (Cmd+click) adds a row to the picked tasks, along with the
+    /// selected one, or takes it out again. Shift+click picks the rows from the
+    /// selected one to the clicked one, leaving the selection where it is.
+    fn pick_row(&mut self, id: Uuid) -> bool {
+        let toggle = (self.modifiers.control_key() || self.modifiers.super_key()) && !self.modifiers.alt_key();
+        if self.modifiers.shift_key() {
+            let ids = self.shown_items();
+            let end = ids.iter().position(|&row| row == id);
+            let start = self.selected_item.and_then(|anchor| ids.iter().position(|&row| row == anchor)).or(end);
+            let (Some(start), Some(end)) = (start, end) else {
+                return false;
+            };
+            let range = ids[start.min(end)..=start.max(end)].iter().copied();
+            let picked = if toggle { self.multi_selection.iter().copied().chain(range).collect() } else { range.collect() };
+            self.set_multi_selection(picked);
+            if self.selected_item.is_none() {
+                self.select_item(Some(id));
+            }
+        } else if toggle {
+            let mut picked = self.multi_selection.clone();
+            if picked.is_empty() {
+                picked.extend(self.selected_item);
+            }
+            if !picked.insert(id) {
+                picked.remove(&id);
+            }
+            self.set_multi_selection(picked);
+            self.select_item(Some(id));
+        } else {
+            return false;
+        }
+        true
+    }
+    
+    /// Make a change to all the picked tasks as one step of the undo history
+    fn apply_bulk_edit(&mut self, edit: BulkEdit) -> bool {
+        let ids = self.multi_selection();
+        if ids.is_empty() {
+            return false;
+        }
+        let command = self.commands.lock().batch(&ids, edit);
+        match command.and_then(|command| self.commands.execute(command)) {
+            Ok(true) => {
+                self.events.push(UiEvent::BulkEdited { edit, count: ids.len() });
+                self.refresh();
+                true
+            }
+            Ok(false) => false,
+            Err(e) => {
+                tracing::error!("{}", e);
+                false
+            }
+        }
+    }
+    
+    /// Move keyboard focus, keeping the text inputs' carets and the selection in step
+    fn set_focus(&mut self, target: Option<ListFocus>) {
+        self.focus.set_focus(target);
+        self.title_input.set_focused(target == Some(ListFocus::TitleInput));
+        self.search_input.set_focused(target == Some(ListFocus::SearchInput));
+        if let Some(ListFocus::Item(id)) = target {
+            self.select_item(Some(id));
+            if let Some(index) = self.rows.iter().position(|row| row.id == id) {
+                self.scroll_row_into_view(index);
+            }
+        }
+    }
+    
+    /// Everything that can take focus, in tab order: the inputs and button,
+    /// the shown filter controls and tag chips, then the rows
+    fn focus_order(&self) -> Vec<ListFocus> {
+        let world = &self.filter_controls.world;
+        let filters = [self.filter_controls.row, self.filter_controls.tag_row]
+            .into_iter()
+            .filter_map(|row| world.rows.get(row))
+            .flat_map(|row| row.children.iter().copied())
+            .filter(|&entity| world.positions.contains(entity))
+            .map(ListFocus::Filter);
+        [ListFocus::TitleInput, ListFocus::AddButton, ListFocus::SearchInput]
+            .into_iter()
+            .chain(filters)
+            .chain(self.shown_rows().map(|row| ListFocus::Item(row.id)))
+            .collect()
+    }
+    
+    /// Tab to the next focusable element, or back to the previous one
+    pub fn cycle_focus(&mut self, backward: bool) {
+        let order = self.focus_order();
+        let target = self.focus.cycle(&order, backward);
+        self.set_focus(target);
+    }
+    
+    /// Press the focused button or filter control, returning whether one had focus
+    fn activate_focused(&mut self) -> bool {
+        match self.focus.focused() {
+            Some(ListFocus::AddButton) => {
+                if self.add_task_from_input() {
+                    self.title_input.set_text(tr("new-task-placeholder"));
+                }
+            }
+            Some(ListFocus::Filter(entity)) => {
+                self.activate_filter_control(entity);
+            }
+            _ => return false,
+        }
+        true
+    }
+    
+    /// Bounds of a focusable element, for the focus ring
+    fn focus_bounds(&self, target: ListFocus) -> Option<(f32, f32, f32, f32)> {
+        let bounds = |widget: &dyn Widget| {
+            let (x, y) = widget.position();
+            let (width, height) = widget.dimensions();
+            (x, y, width, height)
+        };
+        match target {
+            ListFocus::TitleInput => Some(bounds(&self.title_input)),
+            ListFocus::AddButton => Some(bounds(&self.add_button)),
+            ListFocus::SearchInput => Some(bounds(&self.search_input)),
+            ListFocus::Filter(entity) => {
+                let world = &self.filter_controls.world;
+                let position = world.positions.get(entity)?;
+                let size = world.bounds.get(entity)?;
+                Some((position.x, position.y, size.width, size.height))
+            }
+            ListFocus::Item(id) => {
+                // Inside the row, so the clip on the items area doesn't cut the ring
+                let (x, y, width, height) = bounds(self.item_widget(id)?);
+                Some((x + 8.0, y + 6.0, width - 16.0 - SCROLLBAR_WIDTH - SCROLLBAR_MARGIN, height - 12.0))
+            }
+        }
+    }
+    
+    /// Record which items have their modal open after widgets toggled themselves
+    fn sync_expanded_items(&mut self) {
+        for widget in &self.todo_item_widgets {
+            if widget.is_expanded() {
+                self.expanded_items.insert(widget.id());
+            } else {
+                self.expanded_items.remove(&widget.id());
+            }
+        }
+    }
+    
+    /// Move the selection `step` rows up (negative) or down, scrolling it into view
+    fn select_relative(&mut self, step: isize) {
+        let ids = self.shown_items();
+        if ids.is_empty() {
+            return;
+        }
+        
+        let row = match ids.iter().position(|&id| Some(id) == self.selected_item) {
+            Some(row) => (row as isize + step).clamp(0, ids.len() as isize - 1) as usize,
+            None if step < 0 => ids.len() - 1,
+            None => 0,
+        };
+        self.set_multi_selection(HashSet::new());
+        self.select_item(Some(ids[row]));
+        self.scroll_row_into_view(row);
+    }
+    
+    /// Scroll just enough to show the whole row
+    fn scroll_row_into_view(&mut self, row: usize) {
+        let item_height = ROW_HEIGHT;
+        let row_top = row as f32 * item_height;
+        let viewport = self.height - self.controls_height();
+        if row_top < self.scroll_target {
+            self.scroll_smoothly_to(row_top);
+        } else if row_top + item_height > self.scroll_target + viewport {
+            self.scroll_smoothly_to(row_top + item_height - viewport);
+        }
+    }
+    
+    /// Whether the scrollbar is being dragged or its track is held
+    pub fn is_scrolling(&self) -> bool {
+        self.scrollbar.is_active()
+    }
+    
+    /// Jump to an absolute offset at once, stopping any scroll animation
+    pub fn scroll_to(&mut self, offset: f32) {
+        self.fling_velocity = 0.0;
+        self.scroll_target = offset.max(0.0).min(self.max_scroll);
+        self.set_scroll_offset(self.scroll_target);
+    }
+    
+    /// Move the rows to an offset, keeping the scrollbar in sync
+    fn set_scroll_offset(&mut self, offset: f32) {
+        self.scroll_offset = offset.max(0.0).min(self.max_scroll);
+        self.scrollbar.set_offset(self.scroll_offset);
+        
+        // Reposition all visible todo item widgets based on scroll offset
+        self.layout_rows();
+    }
+    
+    /// Take the events emitted since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<UiEvent> {
+        self.events.drain()
+    }
+    
+    /// Apply the events emitted by item widgets to the list and pass them on
+    fn process_item_events(&mut self) {
+        let item_events: Vec<UiEvent> = self.todo_item_widgets.iter_mut()
+            .flat_map(|widget| widget.take_events())
+            .collect();
+        if item_events.is_empty() {
+            return;
         }
         
+        let mut refilter = false;
+        for item_event in &item_events {
+            event::reduce(&self.commands, item_event);
+            
+            // A clicked ▶/▼ slides the item's children out or in; rebuilding now would cut that short
+            if let UiEvent::CollapseToggled { id, collapsed } = *item_event {
+                self.set_collapsed(id, collapsed);
+            }
+            
+            // A clicked tag chip narrows the list to that tag
+            if let UiEvent::TagClicked { id, index } = *item_event {
+                let tag = self.commands.lock().get_item(id).and_then(|item| item.tags().get(index).cloned());
+                if tag.is_some() {
+                    self.tag_filter = tag;
+                    refilter = true;
+                }
+            }
+        }
+        
+        // The list reported what changed; a new tag filter needs the rows rebuilt anyway
+        if refilter {
+            self.update_todo_items();
+        } else {
+            self.refresh();
+        }
+        
+        for item_event in item_events {
+            self.events.push(item_event);
+        }
+    }
+    
+    /// Handle mouse movement for hover effects
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        // An open context menu covers what's under it
+        if self.context_menu.is_visible() {
+            self.context_menu.handle_mouse_move(x, y);
+            return;
+        }
+        
+        // A dragged scrollbar owns the pointer until release
+        if self.scrollbar.handle_mouse_move(x, y) {
+            let offset = self.scrollbar.offset();
+            self.scroll_to(offset);
+            return;
+        }
+        
+        // Handle mouse movement in filter buttons
+        for button in &mut self.filter_buttons {
+            if button.contains_point(x, y) {
+                button.handle_mouse_move(x, y);
+            }
+        }
+        ecs::hover(&mut self.filter_controls.world, x, y);
+        
         // Handle mouse movement in add button
         if self.add_button.contains_point(x, y) {
             self.add_button.handle_mouse_move(x, y);
         }
         
+        // Update item hover states; rows scrolled under the controls can't be hovered
+        let in_items_area = self.contains_point(x, y) && y >= self.y + self.controls_height();
+        let (hover_x, hover_y) = if in_items_area { (x, y) } else { (f32::MIN, f32::MIN) };
+        let mut hovered_item = None;
+        for widget in &mut self.todo_item_widgets {
+            widget.handle_mouse_move(hover_x, hover_y);
+            if widget.contains_point(hover_x, hover_y) {
+                hovered_item = Some(widget.id());
+            }
+        }
+        
+        if self.focus_follows_mouse {
+            if hovered_item.is_some() && hovered_item != self.selected_item {
+                self.select_item(hovered_item);
+                damage::invalidate();
+            }
+            
+            // Start the search focus timer, unless the user is busy typing a new task
+            let wants_search = self.search_input.contains_point(x, y)
+                && !self.search_input.is_focused()
+                && !self.title_input.is_focused();
+            if !wants_search {
+                self.search_hover_time = None;
+            } else if self.search_hover_time.is_none() {
+                self.search_hover_time = Some(0.0);
+            }
+        }
+        
         // No handle_mouse_move method in TextInput, so we'll skip these
         // Handle mouse movement in title input and search input
     }
     
     /// Handle mouse button up
     pub fn handle_mouse_up(&mut self, x: f32, y: f32) {
+        if std::mem::take(&mut self.menu_press) {
+            if let Some(action) = self.context_menu.handle_mouse_up(x, y) {
+                self.run_menu_action(action);
+            }
+            return;
+        }
+        if std::mem::take(&mut self.selection_press) {
+            return;
+        }
+        
+        // Releasing a scrollbar drag shouldn't also click whatever is under the pointer
+        if self.scrollbar.is_active() {
+            self.scrollbar.handle_mouse_up();
+            return;
+        }
+        
         // Handle mouse up in filter buttons
         for button in &mut self.filter_buttons {
             button.handle_mouse_up(x, y);
@@ -633,24 +1595,22 @@ impl TodoListWidget {
         
         // Handle mouse up in title input
         if self.title_input.contains_point(x, y) {
-            self.title_input.handle_mouse_down(x, y);
-            self.title_input.set_focused(true);
-            self.search_input.set_focused(false);
+            self.set_focus(Some(ListFocus::TitleInput));
+            self.title_input.handle_mouse_down(x, y, self.last_click_count);
         }
         
         // Handle mouse up in search input
         if self.search_input.contains_point(x, y) {
-            self.search_input.handle_mouse_down(x, y);
-            self.search_input.set_focused(true);
-            self.title_input.set_focused(false);
+            self.set_focus(Some(ListFocus::SearchInput));
+            self.search_input.handle_mouse_down(x, y, self.last_click_count);
         }
         
         // Handle mouse up in todo item widgets
         for widget in &mut self.todo_item_widgets {
-            if let Ok(mut widget) = widget.lock() {
-                widget.handle_mouse_up(x, y);
-            }
+            widget.handle_mouse_up(x, y);
         }
+        self.sync_expanded_items();
+        self.process_item_events();
     }
     
     /// Handle character input for text fields
@@ -665,41 +1625,42 @@ impl TodoListWidget {
             self.search_input.handle_char_input(c);
             
             // Update the search text and regenerate widgets
-            self.search_text = self.search_input.text().to_string();
-            if self.search_text == "Search..." {
-                self.search_text = String::new();
-            }
-            
-            self.update_todo_items();
+            self.refresh_search();
         }
     }
     
     /// Handle keyboard input
     pub fn handle_key_press(&mut self, key_code: winit::keyboard::KeyCode) {
+        if self.context_menu.is_visible() {
+            if let Some(action) = self.context_menu.handle_key_press(key_code) {
+                self.run_menu_action(action);
+            } else if !self.context_menu.is_visible() {
+                self.menu_item = None;
+            }
+            return;
+        }
+        
+        // Without a focused text field, keys only act through the keymap (see `handle_action`)
+        if !self.is_editing_text() {
+            return;
+        }
+        
         // Handle keyboard input in title input
         if self.title_input.is_focused() {
             match key_code {
                 winit::keyboard::KeyCode::Escape => {
                     // Clear focus
-                    self.title_input.set_focused(false);
+                    self.set_focus(None);
                 },
                 winit::keyboard::KeyCode::Enter => {
                     // Add a new task if Enter is pressed
-                    let title = self.title_input.text().trim();
-                    if !title.is_empty() && title != "New task..." {
-                        if let Ok(mut todo_list) = self.todo_list.lock() {
-                            todo_list.create_item(title);
-                        }
-                        
+                    if self.add_task_from_input() {
                         // Clear the input field
-                        self.title_input.set_text("New task...");
-                        
-                        // Regenerate todo item widgets
-                        self.update_todo_items();
+                        self.title_input.set_text(tr("new-task-placeholder"));
                     }
                     
                     // Clear focus
-                    self.title_input.set_focused(false);
+                    self.set_focus(None);
                 },
                 _ => {
                     // Let the text input handle other keys
@@ -713,9 +1674,8 @@ impl TodoListWidget {
             match key_code {
                 winit::keyboard::KeyCode::Escape => {
                     // Clear focus and search
-                    self.search_input.set_focused(false);
-                    self.search_input.set_text("Search...");
-                    self.search_text = String::new();
+                    self.set_focus(None);
+                    self.clear_search();
                     
                     // Regenerate todo item widgets with no search filter
                     self.update_todo_items();
@@ -729,12 +1689,7 @@ impl TodoListWidget {
                         winit::keyboard::KeyCode::Backspace
                         | winit::keyboard::KeyCode::Delete => {
                             // Update search text after handling key press
-                            self.search_text = self.search_input.text().to_string();
-                            if self.search_text == "Search..." {
-                                self.search_text = String::new();
-                            }
-                            
-                            self.update_todo_items();
+                            self.refresh_search();
                         },
                         _ => {}
                     }
@@ -743,46 +1698,447 @@ impl TodoListWidget {
         }
     }
 
+    /// Create a task from the title input, returning whether one was added
+    ///
+    /// Dates, priorities, tags and recurrence typed with the title are set on
+    /// the task and left out of its title.
+    fn add_task_from_input(&mut self) -> bool {
+        let title = self.title_input.text().trim();
+        if title.is_empty() || title == tr("new-task-placeholder") {
+            return false;
+        }
+        
+        let item = quick_add::parse(title, &chrono::Local::now()).into_item();
+        let id = item.id();
+        match self.commands.execute(Command::AddItem { item }) {
+            Ok(_) => self.events.push(UiEvent::TaskAdded { id }),
+            Err(e) => tracing::error!("{}", e),
+        }
+        self.refresh();
+        true
+    }
+    
+    /// Add the typed task and keep the title input focused for the next one
+    ///
+    /// Focuses the title input first if something else had focus.
+    pub fn add_and_continue(&mut self) {
+        if !self.title_input.is_focused() {
+            self.set_focus(Some(ListFocus::TitleInput));
+            return;
+        }
+        
+        if self.add_task_from_input() {
+            self.title_input.set_text("");
+        }
+    }
+    
+    /// Whether a text field has keyboard focus, so keys should type rather than act on the list
+    pub fn is_editing_text(&self) -> bool {
+        self.title_input.is_focused() || self.search_input.is_focused()
+    }
+    
+    /// Carry out a key-bound action, returning whether it applied here
+    ///
+    /// Actions on the selected task are skipped while a text field has focus,
+    /// so their keys (arrows, Delete, Enter) keep editing the text.
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        // An open context menu takes the keys themselves, in `handle_key_press`
+        if self.context_menu.is_visible() {
+            return false;
+        }
+        
+        // Cursor and selection actions of the focused text field
+        if self.focused_input_mut().is_some_and(|input| input.handle_action(action)) {
+            return true;
+        }
+        match action {
+            Action::FocusNext => self.cycle_focus(false),
+            Action::FocusPrevious => self.cycle_focus(true),
+            Action::AddAndContinue => self.add_and_continue(),
+            Action::FocusSearch => self.focus_search(),
+            Action::SelectPrevious | Action::SelectNext | Action::DeleteTask | Action::EditTask | Action::ContextMenu
+            | Action::ScrollPageUp | Action::ScrollPageDown | Action::ScrollTop | Action::ScrollBottom | Action::SelectAll
+                if self.is_editing_text() => return false,
+            Action::SelectPrevious => self.select_relative(-1),
+            Action::SelectNext => self.select_relative(1),
+            Action::SelectAll => self.select_all(),
+            Action::DeleteTask if !self.multi_selection.is_empty() => return self.apply_bulk_edit(BulkEdit::Delete),
+            Action::DeleteTask => return self.delete_selected(),
+            Action::ContextMenu => return self.open_selected_menu(),
+            Action::ScrollPageUp => self.scroll_page(-1.0),
+            Action::ScrollPageDown => self.scroll_page(1.0),
+            Action::ScrollTop => self.scroll_smoothly_to(0.0),
+            Action::ScrollBottom => self.scroll_smoothly_to(self.max_scroll),
+            Action::EditTask => {
+                if self.activate_focused() {
+                    return true;
+                }
+                let Some(id) = self.selected_item else {
+                    return false;
+                };
+                self.events.push(UiEvent::EditRequested { id });
+            }
+            _ => return false,
+        }
+        true
+    }
+    
+    /// Delete the selected task, moving the selection to the row that takes its place
+    fn delete_selected(&mut self) -> bool {
+        let Some(id) = self.selected_item else {
+            return false;
+        };
+        let index = self.rows.iter().position(|row| row.id == id).unwrap_or(0);
+        let delete = UiEvent::DeleteRequested { id };
+        if !event::reduce(&self.commands, &delete) {
+            return false;
+        }
+        self.events.push(delete);
+        self.refresh();
+        let next = self.rows.get(index).or(self.rows.last()).map(|row| row.id);
+        self.select_item(next);
+        true
+    }
+    
+    /// Whether a task's context menu is open, taking the keys and clicks
+    pub fn is_menu_open(&self) -> bool {
+        self.context_menu.is_visible()
+    }
+    
+    /// Open the context menu of the task under `(x, y)`, e.g. on a right-click
+    ///
+    /// Returns false, closing any open menu, if there's no task there.
+    pub fn handle_right_click(&mut self, x: f32, y: f32, ctx_width: f32, ctx_height: f32) -> bool {
+        // Expanded item modals cover the rows
+        if self.todo_item_widgets.iter().any(|widget| {
+            widget.is_expanded() && widget.modal_contains_point(x, y, ctx_width, ctx_height)
+        }) {
+            return true;
+        }
+        
+        // Rows scrolled under the controls can't be clicked
+        let (items_y, _) = self.items_area();
+        let clicked_item = self.todo_item_widgets.iter()
+            .find(|widget| self.contains_point(x, y) && y >= items_y && widget.contains_point(x, y))
+            .map(|widget| widget.id());
+        let Some(id) = clicked_item else {
+            self.close_context_menu();
+            return false;
+        };
+        self.set_focus(None);
+        self.select_item(Some(id));
+        self.open_context_menu(id, (x, y), (ctx_width, ctx_height))
+    }
+    
+    /// Open the selected task's context menu under its row, for the keyboard
+    fn open_selected_menu(&mut self) -> bool {
+        let Some(widget) = self.selected_item.and_then(|id| self.item_widget(id)) else {
+            return false;
+        };
+        let id = widget.id();
+        let (x, y) = widget.position();
+        let (_, height) = widget.dimensions();
+        let bounds = (self.x + self.width, self.y + self.height);
+        self.open_context_menu(id, (x + 40.0, y + height), bounds)
+    }
+    
+    /// Show the actions for a task, keeping the menu within `bounds`
+    ///
+    /// On one of several picked tasks, the actions are for all of them.
+    fn open_context_menu(&mut self, id: Uuid, position: (f32, f32), bounds: (f32, f32)) -> bool {
+        let picked = self.multi_selection.len();
+        let items = {
+            let todo_list = self.commands.lock();
+            let Some(item) = todo_list.get_item(id) else {
+                return false;
+            };
+            if picked > 1 && self.multi_selection.contains(&id) {
+                Self::bulk_menu_items(picked)
+            } else {
+                let mut items = Self::menu_items(item);
+                if picked > 0 && !self.multi_selection.contains(&id) {
+                    let label = i18n::tr_args("menu-bulk-move-here", &[("count", &picked.to_string())]);
+                    items.insert(0, MenuItem::new(ItemAction::Bulk(BulkEdit::MoveUnder(Some(id))), label));
+                }
+                items
+            }
+        };
+        self.context_menu.show(items, position, bounds);
+        self.menu_item = Some(id);
+        true
+    }
+    
+    /// The actions for a single task
+    fn menu_items(item: &TodoItem) -> Vec<MenuItem<ItemAction>> {
+        let complete = if item.is_completed() { tr("menu-reopen") } else { tr("menu-complete") };
+        let mut items = vec![
+            MenuItem::new(ItemAction::ToggleComplete, complete),
+            MenuItem::new(ItemAction::Edit, tr("menu-edit")),
+            MenuItem::new(ItemAction::AddSubtask, tr("menu-add-subtask")),
+        ];
+        for priority in [Priority::High, Priority::Medium, Priority::Low] {
+            let label = i18n::tr_args("item-priority", &[("priority", &i18n::priority_label(priority))]);
+            items.push(MenuItem::new(ItemAction::SetPriority(priority), label).with_checked(item.priority() == priority));
+        }
+        items.push(MenuItem::new(ItemAction::CopyTitle, tr("menu-copy-title")));
+        if item.due_date().is_some() && !item.is_completed() {
+            items.push(MenuItem::new(ItemAction::Snooze, tr("menu-snooze")));
+        }
+        if !item.is_completed() {
+            items.push(MenuItem::new(ItemAction::Focus, tr("menu-focus")));
+        }
+        items.push(MenuItem::new(ItemAction::Delete, tr("menu-delete")));
+        items
+    }
+    
+    /// The actions for `count` picked tasks
+    fn bulk_menu_items(count: usize) -> Vec<MenuItem<ItemAction>> {
+        let count = count.to_string();
+        let mut items = vec![MenuItem::new(
+            ItemAction::Bulk(BulkEdit::Complete),
+            i18n::tr_args("menu-bulk-complete", &[("count", &count)]),
+        )];
+        for priority in [Priority::High, Priority::Medium, Priority::Low] {
+            let label = i18n::tr_args("item-priority", &[("priority", &i18n::priority_label(priority))]);
+            items.push(MenuItem::new(ItemAction::Bulk(BulkEdit::SetPriority(priority)), label));
+        }
+        items.push(MenuItem::new(ItemAction::Bulk(BulkEdit::MoveUnder(None)), tr("menu-bulk-move-top")));
+        items.push(MenuItem::new(
+            ItemAction::Bulk(BulkEdit::Delete),
+            i18n::tr_args("menu-bulk-delete", &[("count", &count)]),
+        ));
+        items
+    }
+    
+    /// Drop the sort options down from the sort control, the current one checked
+    fn open_sort_menu(&mut self) {
+        let world = &self.filter_controls.world;
+        let (Some(position), Some(size)) = (world.positions.get(self.filter_controls.sort), world.bounds.get(self.filter_controls.sort)) else {
+            return;
+        };
+        let anchor = (position.x, position.y + size.height);
+        let current = self.sort_order().key;
+        let items = [SortKey::Manual, SortKey::Priority, SortKey::DueDate, SortKey::Created, SortKey::Alphabetical]
+            .into_iter()
+            .map(|key| MenuItem::new(ItemAction::Sort(key), tr(i18n::sort_label_id(key))).with_checked(key == current))
+            .collect();
+        self.context_menu.show(items, anchor, (self.x + self.width, self.y + self.height));
+        self.menu_item = None;
+    }
+    
+    fn close_context_menu(&mut self) {
+        self.context_menu.hide();
+        self.menu_item = None;
+    }
+    
+    /// Carry out what was picked in the context menu
+    fn run_menu_action(&mut self, action: ItemAction) {
+        self.context_menu.hide();
+        let id = match (action, self.menu_item.take()) {
+            (ItemAction::Sort(key), _) => {
+                self.set_sort_order(SortOrder { key, ..self.sort_order() });
+                return;
+            }
+            (_, Some(id)) => id,
+            (_, None) => return,
+        };
+        let event = match action {
+            ItemAction::ToggleComplete => {
+                let Some(completed) = self.commands.lock().get_item(id).map(TodoItem::is_completed) else {
+                    return;
+                };
+                let status = if completed { Status::NotStarted } else { Status::Completed };
+                UiEvent::StatusChanged { id, status }
+            }
+            ItemAction::SetPriority(priority) => UiEvent::PriorityChanged { id, priority },
+            ItemAction::Edit => UiEvent::EditRequested { id },
+            ItemAction::CopyTitle => UiEvent::CopyTitleRequested { id },
+            ItemAction::Snooze => UiEvent::SnoozeRequested { id },
+            ItemAction::Focus => UiEvent::FocusRequested { id },
+            ItemAction::AddSubtask => {
+                self.add_subtask(id);
+                return;
+            }
+            ItemAction::Delete => {
+                self.select_item(Some(id));
+                self.delete_selected();
+                return;
+            }
+            ItemAction::Bulk(edit) => {
+                self.apply_bulk_edit(edit);
+                return;
+            }
+            ItemAction::Sort(_) => return,
+        };
+        if event::reduce(&self.commands, &event) {
+            self.refresh();
+        }
+        self.events.push(event);
+    }
+    
+    /// Add a subtask under an item and open the editor on it, to name it
+    fn add_subtask(&mut self, parent: Uuid) {
+        let item = TodoItem::new(&tr("menu-new-subtask")).with_parent(parent);
+        let id = item.id();
+        if let Err(e) = self.commands.execute(Command::AddItem { item }) {
+            tracing::error!("{}", e);
+            return;
+        }
+        self.events.push(UiEvent::TaskAdded { id });
+        
+        // Show it under its parent
+        self.collapsed_items.remove(&parent);
+        self.update_todo_items();
+        self.select_item(Some(id));
+        self.events.push(UiEvent::EditRequested { id });
+    }
+    
+    /// Move keyboard focus to the search field, selecting what's already there
+    pub fn focus_search(&mut self) {
+        if self.search_input.text() == tr("search-placeholder") {
+            self.search_input.set_text("");
+        }
+        self.set_focus(Some(ListFocus::SearchInput));
+        self.search_input.select_all();
+    }
+    
+    /// The text field with keyboard focus, if any
+    fn focused_input_mut(&mut self) -> Option<&mut TextInput> {
+        if self.title_input.is_focused() {
+            Some(&mut self.title_input)
+        } else if self.search_input.is_focused() {
+            Some(&mut self.search_input)
+        } else {
+            None
+        }
+    }
+    
+    /// Copy, cut or paste in the focused text field, returning whether the action applied
+    pub fn handle_clipboard(&mut self, action: Action, clipboard: &mut Clipboard) -> bool {
+        let applied = self.focused_input_mut().is_some_and(|input| input.handle_clipboard(action, clipboard));
+        if applied && self.search_input.is_focused() {
+            self.refresh_search();
+        }
+        applied
+    }
+    
+    /// Type or compose text in the focused text field
+    pub fn handle_text(&mut self, event: &TextEvent) {
+        let Some(input) = self.focused_input_mut() else {
+            return;
+        };
+        input.handle_text(event);
+        if self.search_input.is_focused() {
+            self.refresh_search();
+        }
+    }
+    
+    /// Bounds of the focused text field's cursor, for placing an input method's candidates
+    pub fn caret_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        self.title_input.caret_bounds().or_else(|| self.search_input.caret_bounds())
+    }
+    
+    /// Filter the list by what is typed in the search field
+    fn refresh_search(&mut self) {
+        self.search_text = self.search_input.text().to_string();
+        if self.search_text == tr("search-placeholder") {
+            self.search_text = String::new();
+        }
+        self.filter_value = self.search_text.clone();
+        
+        self.update_todo_items();
+    }
+    
+    /// Empty the search field and show everything the other filters let through
+    fn clear_search(&mut self) {
+        self.search_text = String::new();
+        self.filter_value = String::new();
+        self.search_input.set_text(tr("search-placeholder"));
+    }
+    
     /// Handle mouse down event - use one implementation with context dimensions
-    pub fn handle_mouse_down(&mut self, x: f32, y: f32, ctx_width: f32, ctx_height: f32) -> bool {
+    ///
+    /// `click_count` comes from the input layer's ClickTracker (2 = double click, ...).
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32, click_count: u32, ctx_width: f32, ctx_height: f32) -> bool {
+        // Text inputs react on release, so remember what kind of click this was
+        self.last_click_count = click_count;
+        
+        // An open context menu takes the press; one outside it just closes the menu
+        if self.context_menu.is_visible() {
+            if !self.context_menu.contains_point(x, y) {
+                self.close_context_menu();
+            }
+            self.menu_press = true;
+            return true;
+        }
+        
+        // The scrollbar sits above the items
+        if self.scrollbar.handle_mouse_down(x, y) {
+            let offset = self.scrollbar.offset();
+            self.scroll_to(offset);
+            return true;
+        }
+        
         // Check if we clicked on any expanded modals first
-        for (i, widget) in self.todo_item_widgets.iter().enumerate() {
-            if let Ok(widget_mut) = widget.lock() { // Changed to immutable lock as we only read state
-                // Check if click is in a modal
-                if self.expanded_items.contains(&i) && 
-                   widget_mut.modal_contains_point(x, y, ctx_width, ctx_height) {
-                    // If click is inside an expanded modal, consume the event but don't change state here
-                    return true; 
-                }
+        if let Some(widget) = self.todo_item_widgets.iter().find(|widget| {
+            widget.is_expanded() && widget.modal_contains_point(x, y, ctx_width, ctx_height)
+        }) {
+            // If click is inside an expanded modal, consume the event; the owner opens links and attachments
+            if let Some(index) = widget.modal_link_at(x, y, ctx_width, ctx_height) {
+                self.events.push(UiEvent::LinkClicked { id: widget.id(), index });
+            } else if let Some(index) = widget.modal_attachment_at(x, y, ctx_width, ctx_height) {
+                self.events.push(UiEvent::AttachmentClicked { id: widget.id(), index });
             }
+            return true;
         }
         
         // If not in a modal, check regular widgets
-        for (i, widget) in self.todo_item_widgets.iter().enumerate() {
-            if let Ok(mut widget_mut) = widget.lock() {
-                if widget_mut.contains_point(x, y) {
-                    widget_mut.handle_mouse_down(x, y); // Call handle_mouse_down, ignore return value
-                    let is_expanded_now = widget_mut.is_expanded(); // Use getter
-                    
-                    // Check if the item was expanded *after* handling the click
-                    if is_expanded_now {
-                        if !self.expanded_items.contains(&i) {
-                            self.expanded_items.push(i);
-                        }
-                    } else {
-                        self.expanded_items.retain(|&idx| idx != i);
-                    }
-                    return true; // Indicate the event was handled by this widget
-                }
+        let clicked_item = self.todo_item_widgets.iter()
+            .find(|widget| widget.contains_point(x, y))
+            .map(TodoItemWidget::id);
+        
+        // Ctrl+click and Shift+click pick the row rather than click it
+        if let Some(id) = clicked_item {
+            if self.pick_row(id) {
+                self.set_focus(None);
+                self.selection_press = true;
+                return true;
             }
         }
         
+        // Clicking a row may toggle its modal, and also selects it; the focus ring is for the keyboard
+        if let Some(id) = clicked_item {
+            if let Some(widget) = self.todo_item_widgets.iter_mut().find(|widget| widget.id() == id) {
+                widget.handle_mouse_down(x, y, click_count);
+            }
+            self.set_focus(None);
+            self.sync_expanded_items();
+            self.set_multi_selection(HashSet::new());
+            self.select_item(clicked_item);
+            return true; // Indicate the event was handled by this widget
+        }
+        
         // Check filter controls
         self.handle_filter_controls_click(x, y)
     }
     
+    /// Mouse cursor over an expanded item's modal, which covers everything else
+    pub fn modal_cursor_at(&self, x: f32, y: f32, ctx_width: f32, ctx_height: f32) -> Option<CursorIcon> {
+        self.todo_item_widgets
+            .iter()
+            .filter(|widget| widget.is_expanded())
+            .find(|widget| widget.modal_contains_point(x, y, ctx_width, ctx_height))
+            .and_then(|widget| widget.modal_cursor_at(x, y, ctx_width, ctx_height))
+    }
+
+    /// Top and height of the area the rows scroll in, below the filter controls
+    fn items_area(&self) -> (f32, f32) {
+        (self.y + self.controls_height(), self.height - self.controls_height())
+    }
+
     /// Render base widgets (first pass rendering)
     pub fn render_base(&self, ctx: &mut RenderContext) {
+        profile_scope!("list_render");
         // Draw background
         ctx.draw_rect(
             self.x, self.y,
@@ -791,68 +2147,47 @@ impl TodoListWidget {
         );
         
         // Render filter controls at top
-        self.render_filter_controls(ctx);
-        
-        // Calculate areas for todo items
-        let items_y = self.y + 50.0; // Below filter controls
-        let items_height = self.height - 50.0;
+        ecs::draw(&self.filter_controls.world, ctx);
         
         // Create clipping rectangle for todo items area
+        let (items_y, items_height) = self.items_area();
         ctx.push_clip_rect(self.x, items_y, self.width, items_height);
         
-        // Render visible todo items
-        for &widget_idx in &self.visible_items {
-            if widget_idx < self.todo_item_widgets.len() {
-                let widget = &self.todo_item_widgets[widget_idx];
-                if let Ok(widget) = widget.lock() {
-                    widget.render_base(ctx);
+        // Render visible todo items, locking the list once for all rows
+        if let Ok(todo_list) = self.commands.list().lock() {
+            for widget in &self.todo_item_widgets {
+                if let Some(item) = todo_list.get_item(widget.id()) {
+                    widget.draw_base(item, ctx);
                 }
             }
         }
         
-        // Render scrollbar if needed
-        if self.max_scroll > 0.0 {
-            let scrollbar_width = 8.0;
-            let scrollbar_x = self.x + self.width - scrollbar_width - 5.0;
-            let scrollbar_y = items_y;
-            let scrollbar_height = items_height;
-            
-            // Draw scrollbar background
-            ctx.draw_rect(
-                scrollbar_x, scrollbar_y,
-                scrollbar_width, scrollbar_height,
-                self.theme.get_scrollbar_bg_color(),
-            );
-            
-            // Calculate handle position and size
-            let visible_ratio = items_height / (items_height + self.max_scroll);
-            let handle_height = items_height * visible_ratio;
-            let handle_y = scrollbar_y + (self.scroll_offset / self.max_scroll) * (items_height - handle_height);
-            
-            // Draw scrollbar handle
-            ctx.draw_rect(
-                scrollbar_x, handle_y,
-                scrollbar_width, handle_height,
-                self.theme.get_scrollbar_handle_color(),
-            );
-        }
+        self.scrollbar.render(ctx);
         
         // Remove clipping rectangle
         ctx.pop_clip_rect();
+        
+        if let Some(focused) = self.focus.focused() {
+            if let Some((x, y, width, height)) = self.focus_bounds(focused) {
+                let clip = matches!(focused, ListFocus::Item(_));
+                if clip {
+                    ctx.push_clip_rect(self.x, items_y, self.width, items_height);
+                }
+                self.focus.draw_ring(ctx, x, y, width, height);
+                if clip {
+                    ctx.pop_clip_rect();
+                }
+            }
+        }
     }
     
     /// Render modals (second pass rendering)
     pub fn render_modals(&self, ctx: &mut RenderContext) {
         // Render expanded item modals (second pass)
-        for &widget_idx in &self.expanded_items {
-            if widget_idx < self.todo_item_widgets.len() {
-                let widget = &self.todo_item_widgets[widget_idx];
-                // Lock the widget before calling render_modal
-                if let Ok(widget) = widget.lock() {
-                    widget.render_modal(ctx);
-                }
-            }
+        for widget in self.todo_item_widgets.iter().filter(|widget| widget.is_expanded()) {
+            widget.render_modal(ctx);
         }
+        self.context_menu.render(ctx);
     }
 
     /// Render the widget
@@ -861,29 +2196,40 @@ impl TodoListWidget {
         self.render_modals(ctx);
     }
 
-    /// Get the theme's hierarchy indent value (since it's missing from the theme)
-    fn get_hierarchy_indent(&self) -> f32 {
-        15.0 // Default indent value for hierarchy levels
-    }
-
     /// Calculate the maximum scroll value based on the number of items
     fn calculate_max_scroll(&mut self) {
-        let items_height = self.visible_items.len() as f32 * 40.0; // 40.0 is the standard item height
-        let visible_area_height = self.height - 50.0; // Subtract height of filter controls
+        // Only rows with a widget can be partly slid in or out
+        let sliding: f32 = self.todo_item_widgets.iter().map(|widget| 1.0 - widget.reveal()).sum();
+        let items_height = ROW_HEIGHT * (self.rows.len() as f32 - sliding);
+        let visible_area_height = self.height - self.controls_height(); // Subtract height of filter controls
         
         self.max_scroll = (items_height - visible_area_height).max(0.0);
         self.scroll_offset = self.scroll_offset.min(self.max_scroll);
+        self.scroll_target = self.scroll_target.min(self.max_scroll);
+        self.scrollbar.set_range(visible_area_height, self.max_scroll);
+        self.scrollbar.set_offset(self.scroll_offset);
     }
 
     /// Handle clicks on filter controls
     fn handle_filter_controls_click(&mut self, x: f32, y: f32) -> bool {
-        // Status dropdown
-        let status_dropdown_width = 120.0;
-        let status_dropdown_x = self.x + 300.0;  // Match values from render_filter_controls
-        let status_dropdown_y = self.y + 10.0;   // Match values from render_filter_controls
-        
-        if x >= status_dropdown_x && x <= status_dropdown_x + status_dropdown_width &&
-           y >= status_dropdown_y && y <= status_dropdown_y + 30.0 {
+        let Some(hit) = ecs::hit_test(&self.filter_controls.world, x, y) else {
+            return false;
+        };
+        self.activate_filter_control(hit);
+        true
+    }
+    
+    /// Step the filter control or toggle the tag chip, from a click or the keyboard
+    fn activate_filter_control(&mut self, hit: Entity) {
+        let controls = &self.filter_controls;
+        if hit == controls.sort {
+            self.open_sort_menu();
+            return;
+        } else if hit == controls.sort_direction {
+            let order = self.sort_order();
+            self.set_sort_order(SortOrder { descending: !order.descending, ..order });
+            return;
+        } else if hit == controls.status {
             // Cycle through status options
             self.status_filter = match self.status_filter {
                 None => Some(Status::NotStarted),
@@ -891,39 +2237,14 @@ impl TodoListWidget {
                 Some(Status::InProgress) => Some(Status::Completed),
                 Some(Status::Completed) => None,
             };
-            
-            // Update todo item widgets
-            self.setup_todo_item_widgets();
-            return true;
-        }
-        
-        // Filter type dropdown
-        let filter_dropdown_width = 120.0;
-        let filter_dropdown_x = self.x + 170.0;  // Match values from render_filter_controls
-        let filter_dropdown_y = status_dropdown_y;
-        
-        if x >= filter_dropdown_x && x <= filter_dropdown_x + filter_dropdown_width &&
-           y >= filter_dropdown_y && y <= filter_dropdown_y + 30.0 {
+        } else if hit == controls.field {
             // Cycle through filter type options
             self.filter_type = match self.filter_type {
                 FilterType::None => FilterType::Title,
                 FilterType::Title => FilterType::Description,
-                FilterType::Description => FilterType::None,
                 _ => FilterType::None,
             };
-            
-            // Update todo item widgets
-            self.setup_todo_item_widgets();
-            return true;
-        }
-        
-        // Priority dropdown
-        let priority_dropdown_width = 120.0;
-        let priority_dropdown_x = self.x + 430.0;  // Match values from render_filter_controls
-        let priority_dropdown_y = status_dropdown_y;
-        
-        if x >= priority_dropdown_x && x <= priority_dropdown_x + priority_dropdown_width &&
-           y >= priority_dropdown_y && y <= priority_dropdown_y + 30.0 {
+        } else if hit == controls.priority {
             // Cycle through priority options
             self.priority_filter = match self.priority_filter {
                 None => Some(Priority::Low),
@@ -931,49 +2252,103 @@ impl TodoListWidget {
                 Some(Priority::Medium) => Some(Priority::High),
                 Some(Priority::High) => None,
             };
-            
-            // Update todo item widgets
-            self.setup_todo_item_widgets();
-            return true;
-        }
-        
-        // Search box
-        let search_box_width = 150.0;
-        let search_box_x = self.x + 10.0;  // Match values from render_filter_controls
-        let search_box_y = status_dropdown_y;
-        
-        if x >= search_box_x && x <= search_box_x + search_box_width &&
-           y >= search_box_y && y <= search_box_y + 30.0 {
+        } else if let Some(tag) = controls.tag_of(hit) {
+            // Clicking the active tag again shows everything
+            self.tag_filter = match self.tag_filter.as_deref() {
+                Some(active) if active == tag => None,
+                _ => Some(tag.to_string()),
+            };
+        } else if hit == controls.search {
             // Toggle search input active state (in a real app, this would open a text input)
             // Here we'll just clear the search text to demonstrate
-            if !self.filter_value.is_empty() {
-                self.filter_value = String::new();
-                self.setup_todo_item_widgets();
+            if self.filter_value.is_empty() {
+                return;
             }
-            return true;
+            self.clear_search();
         }
         
-        false
+        // Update todo item widgets
+        self.setup_todo_item_widgets();
     }
 }
 
 impl Widget for TodoListWidget {
     fn update(&mut self, delta_time: f32) {
-        // Update child components
-        self.panel.update(delta_time);
-        self.add_button.update(delta_time);
-        self.title_input.update(delta_time);
-        self.search_input.update(delta_time);
+        profile_scope!("list_update");
+        // Catch up with changes made to the list since the last frame
+        self.refresh();
         
-        for button in &mut self.filter_buttons {
+        // Update child components that have something to do
+        if self.panel.needs_update() {
+            self.panel.update(delta_time);
+        }
+        if self.add_button.needs_update() {
+            self.add_button.update(delta_time);
+        }
+        if self.title_input.needs_update() {
+            self.title_input.update(delta_time);
+        }
+        if self.search_input.needs_update() {
+            self.search_input.update(delta_time);
+        }
+        
+        // Focus the search box once the pointer has rested on it long enough
+        if let Some(elapsed) = self.search_hover_time.as_mut() {
+            *elapsed += delta_time;
+            if *elapsed >= self.hover_focus_delay {
+                self.search_hover_time = None;
+                self.focus_search();
+            }
+        }
+        
+        for button in self.filter_buttons.iter_mut().filter(|button| button.needs_update()) {
             button.update(delta_time);
         }
         
-        for widget in &mut self.todo_item_widgets {
-            if let Ok(mut widget) = widget.lock() {
-                widget.update(delta_time);
+        self.update_scroll(delta_time);
+        self.focus.update(delta_time);
+        
+        // Holding the scrollbar track keeps paging
+        if self.scrollbar.needs_update() {
+            self.scrollbar.update(delta_time);
+            let offset = self.scrollbar.offset();
+            if offset != self.scroll_offset {
+                self.scroll_to(offset);
             }
         }
+        
+        // Most rows are idle; only touch the ones that moved or are animating
+        let sliding = self.todo_item_widgets.iter().any(TodoItemWidget::is_sliding);
+        for widget in self.todo_item_widgets.iter_mut().filter(|widget| widget.needs_update()) {
+            widget.update(delta_time);
+        }
+        
+        // Rows sliding in or out push the ones below; those that slid out are gone
+        if sliding {
+            let gone: HashSet<Uuid> = self.todo_item_widgets.iter()
+                .filter(|widget| widget.is_hidden())
+                .map(TodoItemWidget::id)
+                .collect();
+            self.todo_item_widgets.retain(|widget| !gone.contains(&widget.id()));
+            self.rows.retain(|row| !gone.contains(&row.id));
+            self.calculate_max_scroll();
+            self.layout_rows();
+        }
+    }
+    
+    fn needs_update(&self) -> bool {
+        let scrolling = self.panning.is_some() || self.fling_velocity != 0.0 || self.scroll_target != self.scroll_offset;
+        // The focus ring pulses for a moment after focus moves
+        scrolling
+            || self.focus.is_pulsing()
+            || self.search_hover_time.is_some()
+            || self.panel.needs_update()
+            || self.add_button.needs_update()
+            || self.title_input.needs_update()
+            || self.search_input.needs_update()
+            || self.scrollbar.needs_update()
+            || self.filter_buttons.iter().any(|button| button.needs_update())
+            || self.todo_item_widgets.iter().any(|widget| widget.needs_update())
     }
     
     fn render(&self, ctx: &mut RenderContext) {
@@ -981,6 +2356,32 @@ impl Widget for TodoListWidget {
         self.render_modals(ctx);
     }
     
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        let (items_y, items_height) = self.items_area();
+        out.push(WidgetInfo::of(self, depth).with_clip(self.x, items_y, self.width, items_height));
+        
+        self.panel.inspect(depth + 1, out);
+        self.title_input.inspect(depth + 1, out);
+        self.add_button.inspect(depth + 1, out);
+        self.search_input.inspect(depth + 1, out);
+        for button in &self.filter_buttons {
+            button.inspect(depth + 1, out);
+        }
+        ecs::inspect(&self.filter_controls.world, depth + 1, out);
+        
+        // Rows scrolled out of the clip rect aren't drawn, so leave them out
+        let visible = |widget: &&TodoItemWidget| {
+            let (_, y) = widget.position();
+            let (_, height) = widget.dimensions();
+            y + height > items_y && y < items_y + items_height
+        };
+        for widget in self.todo_item_widgets.iter().filter(visible) {
+            widget.inspect(depth + 1, out);
+        }
+        self.scrollbar.inspect(depth + 1, out);
+        self.context_menu.inspect(depth + 1, out);
+    }
+    
     fn position(&self) -> (f32, f32) {
         (self.x, self.y)
     }
@@ -1010,16 +2411,18 @@ impl Widget for TodoListWidget {
             let (btn_x, btn_y) = button.position();
             button.set_position(btn_x + dx, btn_y + dy);
         }
+        self.filter_controls.move_to(x, y);
         
         let (search_x, search_y) = self.search_input.position();
         self.search_input.set_position(search_x + dx, search_y + dy);
         
+        let (scrollbar_x, scrollbar_y) = self.scrollbar.position();
+        self.scrollbar.set_position(scrollbar_x + dx, scrollbar_y + dy);
+        
         // Update positions of todo item widgets
         for widget in &mut self.todo_item_widgets {
-            if let Ok(mut widget) = widget.lock() {
-                let (widget_x, widget_y) = widget.position();
-                widget.set_position(widget_x + dx, widget_y + dy);
-            }
+            let (widget_x, widget_y) = widget.position();
+            widget.set_position(widget_x + dx, widget_y + dy);
         }
     }
     
@@ -1048,7 +2451,7 @@ impl Widget for TodoListWidget {
         self.title_input.set_dimensions(input_width, button_height);
         
         // Reposition filter buttons
-        let new_filter_buttons = Self::create_filter_buttons(self.x, self.y, width, &self.theme);
+        let new_filter_buttons = Self::create_filter_buttons(self.x, self.y, &self.theme);
         self.filter_buttons = new_filter_buttons;
         
         // Reposition search input
@@ -1058,65 +2461,595 @@ impl Widget for TodoListWidget {
             self.y + button_padding * 2.0 + button_height
         );
         
-        // Regenerate todo item widgets
+        // Regenerate todo item widgets, which also moves the scrollbar along the right edge
         self.update_todo_items();
     }
     
     fn contains_point(&self, x: f32, y: f32) -> bool {
         x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
     }
+    
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if let Some(icon) = self.context_menu.cursor_at(x, y) {
+            return Some(icon);
+        }
+        
+        // Keep the grabbing hand for the whole drag, even off the scrollbar
+        if let Some(icon) = self.scrollbar.cursor_at(x, y) {
+            return Some(icon);
+        }
+        
+        let controls = self.title_input.cursor_at(x, y)
+            .or_else(|| self.search_input.cursor_at(x, y))
+            .or_else(|| self.add_button.cursor_at(x, y))
+            .or_else(|| self.filter_buttons.iter().find_map(|button| button.cursor_at(x, y)))
+            .or_else(|| ecs::cursor_at(&self.filter_controls.world, x, y));
+        if controls.is_some() {
+            return controls;
+        }
+        
+        // Items scrolled under the controls are clipped away
+        if y < self.y + self.controls_height() {
+            return None;
+        }
+        self.todo_item_widgets
+            .iter()
+            .find_map(|widget| widget.cursor_at(x, y))
+    }
 }
 
 impl Clone for TodoListWidget {
     fn clone(&self) -> Self {
         // Create a new instance with the same properties
-        let mut clone = Self {
+        Self {
             x: self.x,
             y: self.y,
             width: self.width,
             height: self.height,
-            todo_list: self.todo_list.clone(),
+            commands: self.commands.clone(),
+            list_events: self.commands.lock().subscribe(),
             panel: self.panel.clone(),
             add_button: self.add_button.clone(),
             title_input: self.title_input.clone(),
             filter_buttons: self.filter_buttons.clone(),
+            filter_controls: self.filter_controls.clone(),
             search_input: self.search_input.clone(),
             scroll_offset: self.scroll_offset,
+            scroll_target: self.scroll_target,
             max_scroll: self.max_scroll,
-            todo_item_widgets: Vec::new(), // Will be regenerated
+            scrollbar: self.scrollbar.clone(),
+            panning: None, // A pan in progress belongs to the original
+            pan_velocity: 0.0,
+            fling_velocity: self.fling_velocity,
+            rows: self.rows.clone(),
+            todo_item_widgets: self.todo_item_widgets.clone(),
             show_completed: self.show_completed,
             filter_priority: self.filter_priority,
             filter_status: self.filter_status,
             search_text: self.search_text.clone(),
-            on_item_status_change: None, // Will be manually cloned
-            on_item_edit: None, // Will be manually cloned
-            on_item_delete: None, // Will be manually cloned
-            theme: CyberpunkTheme::new(), // Theme is stateless, just create a new one
-            modal_open_index: None, // Will be manually cloned
-            expanded_items: self.expanded_items.clone(), // Will be manually cloned
-            visible_items: self.visible_items.clone(),
+            events: EventQueue::new(), // Pending events belong to the original
+            theme: self.theme.clone(),
+            last_click_count: self.last_click_count,
+            selected_item: self.selected_item,
+            multi_selection: self.multi_selection.clone(),
+            modifiers: self.modifiers,
+            selection_press: false,
+            focus: self.focus.clone(),
+            focus_follows_mouse: self.focus_follows_mouse,
+            hover_focus_delay: self.hover_focus_delay,
+            search_hover_time: None,
+            expanded_items: self.expanded_items.clone(),
+            collapsed_items: self.collapsed_items.clone(),
+            context_menu: self.context_menu.clone(),
+            menu_item: self.menu_item,
+            menu_press: false,
             filter_value: self.filter_value.clone(),
             filter_type: self.filter_type,
             status_filter: self.status_filter,
             priority_filter: self.priority_filter,
+            tag_filter: self.tag_filter.clone(),
+            project_filter: self.project_filter,
+            custom_filter: self.custom_filter.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::core::prelude::{Dispatcher, Priority, RecurrenceRule, Status, TodoItem, TodoList};
+    use crate::ui::testing::{UiDriver, VIEWPORT};
+    use crate::ui::{UiEvent, Widget};
+    use winit::keyboard::{KeyCode, ModifiersState};
+
+    #[test]
+    fn test_rows_see_edits_made_elsewhere() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Write tests"]);
+
+        // Completed behind the widget's back, e.g. by a script, without a refresh
+        driver.todo_list().lock().unwrap().get_item_mut(ids[0]).unwrap().mark_completed();
+
+        driver.click_checkbox(ids[0]);
+        assert_eq!(driver.task(ids[0]).unwrap().status(), Status::NotStarted);
+        assert_eq!(driver.take_events(), vec![UiEvent::StatusChanged { id: ids[0], status: Status::NotStarted }]);
+    }
+
+    #[test]
+    fn test_rows_follow_changes_made_elsewhere() {
+        let mut todo_list = TodoList::new("Test");
+        let trip = todo_list.create_item("Trip");
+        let socks = todo_list.add_item(TodoItem::new("Pack socks").with_parent(trip));
+        let mut driver = UiDriver::new(todo_list);
+        let shared = driver.todo_list().clone();
+
+        // An edit only updates what the other rows show of it
+        shared.lock().unwrap().get_item_mut(socks).unwrap().mark_completed();
+        driver.widget_mut().refresh();
+        assert_eq!(driver.widget().item_widget(trip).unwrap().child_progress(), Some((1, 1)));
+
+        // Added, moved and removed items change the rows
+        let tickets = shared.lock().unwrap().create_item("Buy tickets");
+        driver.widget_mut().refresh();
+        assert_eq!(driver.widget().shown_items().len(), 3);
+        shared.lock().unwrap().move_item(tickets, Some(trip)).unwrap();
+        shared.lock().unwrap().remove_item(socks);
+        driver.widget_mut().refresh();
+        assert_eq!(driver.widget().shown_items(), vec![trip, tickets]);
+        assert_eq!(driver.widget().item_widget(trip).unwrap().child_progress(), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_typing_adds_a_task() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["First"]);
+
+        // Ctrl+Enter focuses the title input
+        driver.widget_mut().add_and_continue();
+        driver.type_text("Second");
+        driver.press(KeyCode::Enter);
+
+        let events = driver.take_events();
+        let [UiEvent::TaskAdded { id }] = events[..] else {
+            panic!("expected one added task, got {:?}", events);
         };
-        
-        // Manually clone callback Arc pointers
-        if let Some(cb) = &self.on_item_status_change {
-            clone.on_item_status_change = Some(cb.clone());
+        assert_eq!(driver.task(id).unwrap().title(), "Second");
+        let shown = driver.widget().shown_items();
+        assert!(shown.len() == 2 && shown.contains(&ids[0]) && shown.contains(&id));
+    }
+
+    #[test]
+    fn test_typed_tokens_set_the_new_task() {
+        let (mut driver, _) = UiDriver::with_tasks(&[]);
+        driver.widget_mut().add_and_continue();
+        driver.type_text("Water plants tomorrow 8am !high #garden every 3 days");
+        driver.press(KeyCode::Enter);
+
+        let events = driver.take_events();
+        let [UiEvent::TaskAdded { id }] = events[..] else {
+            panic!("expected one added task, got {:?}", events);
+        };
+        let task = driver.task(id).unwrap();
+        assert_eq!(task.title(), "Water plants");
+        assert_eq!(task.priority(), Priority::High);
+        assert_eq!(task.tags(), ["garden"]);
+        assert_eq!(task.recurrence(), Some(RecurrenceRule::EveryNDays(3)));
+        let due = crate::i18n::local_date(task.due_date().unwrap()).unwrap();
+        assert_eq!(due, chrono::Local::now().date_naive().succ_opt().unwrap());
+    }
+
+    #[test]
+    fn test_arrow_keys_move_the_selection() {
+        let (mut driver, _) = UiDriver::with_tasks(&["Alpha", "Beta", "Gamma"]);
+        let rows = driver.widget().shown_items();
+
+        driver.press(KeyCode::ArrowDown);
+        driver.press(KeyCode::ArrowDown);
+        assert_eq!(driver.widget().selected_item(), Some(rows[1]));
+        driver.press(KeyCode::ArrowUp);
+        driver.press(KeyCode::ArrowUp);
+        assert_eq!(driver.widget().selected_item(), Some(rows[0]));
+        assert!(driver.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_bound_keys_act_on_the_selection() {
+        let (mut driver, _) = UiDriver::with_tasks(&["Alpha", "Beta", "Gamma"]);
+        let rows = driver.widget().shown_items();
+
+        driver.press(KeyCode::ArrowDown);
+        driver.press(KeyCode::Enter);
+        assert_eq!(driver.take_events(), vec![UiEvent::EditRequested { id: rows[0] }]);
+
+        // Deleting selects the row that moves up into its place
+        driver.press(KeyCode::Delete);
+        assert!(driver.task(rows[0]).is_none());
+        assert_eq!(driver.widget().selected_item(), Some(rows[1]));
+        assert_eq!(driver.take_events(), vec![UiEvent::DeleteRequested { id: rows[0] }]);
+
+        // While a text field has focus, the keys edit the text instead
+        driver.widget_mut().focus_search();
+        driver.press(KeyCode::Delete);
+        assert_eq!(driver.widget().shown_items().len(), 2);
+
+        // Rebound navigation leaves the arrows alone
+        let (config, _) = Config::from_toml("[keybinds]\nselect_next = \"J\"\n");
+        driver.set_keymap(config.keymap());
+        driver.press(KeyCode::Escape);
+        driver.press(KeyCode::ArrowDown);
+        assert_eq!(driver.widget().selected_item(), Some(rows[1]));
+        driver.press(KeyCode::KeyJ);
+        assert_eq!(driver.widget().selected_item(), Some(rows[2]));
+    }
+
+    #[test]
+    fn test_scrolling_glides_to_its_target() {
+        let (mut driver, _) = UiDriver::with_numbered_tasks(40);
+
+        // The wheel sets where to go; the offset catches up over a few frames
+        driver.widget_mut().handle_mouse_wheel(100.0);
+        assert_eq!(driver.widget().scroll_position(), (0.0, 100.0));
+        driver.tick(0.016);
+        let (offset, _) = driver.widget().scroll_position();
+        assert!(offset > 0.0 && offset < 100.0);
+        for _ in 0..60 {
+            driver.tick(0.016);
         }
-        
-        if let Some(cb) = &self.on_item_edit {
-            clone.on_item_edit = Some(cb.clone());
+        assert_eq!(driver.widget().scroll_position(), (100.0, 100.0));
+
+        // Paging keeps a row of overlap, and End stops at the last row
+        driver.press(KeyCode::PageDown);
+        let (_, target) = driver.widget().scroll_position();
+        assert!(target > 100.0 && target < 100.0 + VIEWPORT.1);
+        driver.press(KeyCode::End);
+        driver.press(KeyCode::Home);
+        assert_eq!(driver.widget().scroll_position().1, 0.0);
+        driver.press(KeyCode::End);
+        for _ in 0..60 {
+            driver.tick(0.016);
         }
-        
-        if let Some(cb) = &self.on_item_delete {
-            clone.on_item_delete = Some(cb.clone());
+        let (offset, target) = driver.widget().scroll_position();
+        assert_eq!(offset, target);
+        assert!(offset > 0.0);
+
+        // A pan follows the pointer at once, then coasts on after release
+        driver.widget_mut().scroll_to(0.0);
+        driver.widget_mut().pan_by(20.0);
+        driver.tick(0.016);
+        driver.widget_mut().pan_by(20.0);
+        driver.tick(0.016);
+        assert_eq!(driver.widget().scroll_position().0, 40.0);
+        driver.widget_mut().end_pan();
+        for _ in 0..10 {
+            driver.tick(0.016);
         }
-        
-        // Regenerate todo item widgets
-        clone.update_todo_items();
-        
-        clone
+        assert!(driver.widget().scroll_position().0 > 40.0);
+    }
+
+    #[test]
+    fn test_long_lists_only_build_rows_near_the_view() {
+        let (mut driver, ids) = UiDriver::with_numbered_tasks(2000);
+        assert_eq!(driver.widget().shown_items(), ids);
+        assert!(driver.widget().item_widget(ids[0]).is_some());
+        assert!(driver.widget().item_widget(ids[100]).is_none());
+
+        // Selecting the last row from the keyboard scrolls it into view, with a widget
+        driver.press(KeyCode::ArrowUp);
+        assert_eq!(driver.widget().selected_item(), Some(ids[1999]));
+        for _ in 0..60 {
+            driver.tick(1.0 / 60.0);
+        }
+        let row = driver.widget().item_widget(ids[1999]).unwrap();
+        assert!(row.is_selected());
+        assert!(driver.widget().item_widget(ids[0]).is_none());
+
+        // Rows scrolled away keep what they show
+        driver.widget_mut().scroll_to(0.0);
+        assert!(!driver.widget().item_widget(ids[0]).unwrap().is_selected());
+        driver.widget_mut().scroll_to(f32::MAX);
+        assert!(driver.widget().item_widget(ids[1999]).unwrap().is_selected());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_tab_moves_focus_through_the_list() {
+        let (mut driver, _) = UiDriver::with_tasks(&["Alpha", "Beta"]);
+        let ids = driver.widget().shown_items();
+
+        // Shift+Tab from nothing wraps around to the last row, which it selects
+        driver.press_with(ModifiersState::SHIFT, KeyCode::Tab);
+        assert_eq!(driver.widget().selected_item(), Some(ids[1]));
+        driver.press_with(ModifiersState::SHIFT, KeyCode::Tab);
+        assert_eq!(driver.widget().selected_item(), Some(ids[0]));
+
+        // Tab wraps to the title input, and on to the add button, which Enter presses
+        driver.press(KeyCode::Tab);
+        driver.press(KeyCode::Tab);
+        assert!(driver.widget().is_editing_text());
+        driver.type_text("Milk");
+        driver.press(KeyCode::Tab);
+        assert!(!driver.widget().is_editing_text());
+        driver.press(KeyCode::Enter);
+        assert_eq!(driver.widget().shown_items().len(), 3);
+        assert!(matches!(driver.take_events().as_slice(), [UiEvent::TaskAdded { .. }]));
+
+        // Focus moves on from the search input without typing a tab into it
+        driver.press(KeyCode::Tab);
+        assert!(driver.widget().is_editing_text());
+        driver.press(KeyCode::Tab);
+        assert!(!driver.widget().is_editing_text());
+
+        // Clicking a row takes the focus ring off the inputs
+        driver.widget_mut().focus_search();
+        let (x, y) = driver.widget().item_widget(ids[1]).unwrap().position();
+        driver.click_at(x + 200.0, y + 10.0);
+        assert!(!driver.widget().is_editing_text());
+        assert_eq!(driver.widget().selected_item(), Some(ids[1]));
+    }
+
+    #[test]
+    fn test_filter_controls_cycle() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Alpha", "Beta"]);
+        driver.click_checkbox(ids[0]);
+
+        // The status selector is the third control in the row above the items
+        driver.click_at(50.0 + 310.0, 100.0 + 25.0);
+        assert_eq!(driver.widget().session().status_filter, Some(Status::NotStarted));
+        assert_eq!(driver.widget().shown_items(), vec![ids[1]]);
+    }
+
+    #[test]
+    fn test_search_matches_fuzzily_best_first() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Prepare slides", "Buy groceries", "Write report"]);
+
+        driver.widget_mut().focus_search();
+        driver.type_text("rep");
+        assert_eq!(driver.widget().shown_items(), vec![ids[2], ids[0]]);
+        driver.type_text("t");
+        assert_eq!(driver.widget().shown_items(), vec![ids[2]]);
+        assert_eq!(driver.widget().session().filter_value, "rept");
+
+        // Escape clears the search and brings everything back
+        driver.press(KeyCode::Escape);
+        assert_eq!(driver.widget().shown_items().len(), 3);
+        assert_eq!(driver.widget().session().filter_value, "");
+    }
+
+    #[test]
+    fn test_rows_survive_filtering_and_restoring() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Prepare slides", "Buy groceries"]);
+        let (x, y) = driver.widget().item_widget(ids[0]).unwrap().position();
+        driver.move_to(x + 100.0, y + 10.0);
+        assert!(driver.widget().item_widget(ids[0]).unwrap().is_hovered());
+
+        // The row that's still shown is the same row, hover and all
+        driver.widget_mut().focus_search();
+        driver.type_text("slides");
+        assert_eq!(driver.widget().shown_items(), vec![ids[0]]);
+        assert!(driver.widget().item_widget(ids[0]).unwrap().is_hovered());
+
+        // Restoring a session updates the rows it keeps
+        let mut session = driver.widget().session();
+        session.selected_item = Some(ids[0]);
+        session.expanded_items = vec![ids[0]];
+        driver.widget_mut().restore_session(&session);
+        let row = driver.widget().item_widget(ids[0]).unwrap();
+        assert!(row.is_hovered() && row.is_selected() && row.is_expanded());
+    }
+
+    #[test]
+    fn test_bulk_actions_on_picked_tasks() {
+        use crate::core::prelude::BulkEdit;
+
+        let (mut driver, _) = UiDriver::with_tasks(&["Alpha", "Beta", "Gamma", "Delta"]);
+        // In the order they're shown
+        let [alpha, beta, gamma, delta] = driver.widget().shown_items()[..] else { unreachable!() };
+        let marked = |driver: &UiDriver, id| driver.widget().item_widget(id).unwrap().is_marked();
+
+        // Ctrl+click picks the selected task and the clicked one, and unpicks again
+        driver.click_row_with(ModifiersState::empty(), alpha);
+        driver.click_row_with(ModifiersState::CONTROL, gamma);
+        assert_eq!(driver.widget().multi_selection(), vec![alpha, gamma]);
+        assert!(marked(&driver, alpha) && !marked(&driver, beta));
+        driver.click_row_with(ModifiersState::CONTROL, alpha);
+        assert_eq!(driver.widget().multi_selection(), vec![gamma]);
+        assert!(driver.take_events().is_empty());
+
+        // Shift+click picks a range from the selected task; a plain click drops it
+        driver.click_row_with(ModifiersState::empty(), beta);
+        assert!(driver.widget().multi_selection().is_empty());
+        driver.click_row_with(ModifiersState::SHIFT, delta);
+        assert_eq!(driver.widget().multi_selection(), vec![beta, gamma, delta]);
+        assert_eq!(driver.widget().selected_item(), Some(beta));
+
+        // The menu of a picked task acts on all of them, undone in one step
+        let (row_x, row_y) = driver.widget().item_widget(gamma).unwrap().position();
+        let (x, y) = (row_x + 100.0, row_y + 10.0);
+        driver.right_click_at(x, y);
+        driver.click_at(x + 50.0, y + 20.0);
+        assert!([beta, gamma, delta].iter().all(|&id| driver.task(id).unwrap().is_completed()));
+        assert!(!driver.task(alpha).unwrap().is_completed());
+        assert_eq!(driver.take_events(), vec![UiEvent::BulkEdited { edit: BulkEdit::Complete, count: 3 }]);
+        Dispatcher::new(driver.todo_list().clone()).undo().unwrap();
+        assert!(driver.todo_list().lock().unwrap().completed_items().is_empty());
+
+        // Another task's menu moves the picked ones under it
+        let (row_x, row_y) = driver.widget().item_widget(alpha).unwrap().position();
+        let (x, y) = (row_x + 100.0, row_y + 10.0);
+        driver.right_click_at(x, y);
+        driver.click_at(x + 50.0, y + 20.0);
+        assert!([beta, gamma, delta].iter().all(|&id| driver.task(id).unwrap().parent_id() == Some(alpha)));
+        assert_eq!(driver.take_events(), vec![UiEvent::BulkEdited { edit: BulkEdit::MoveUnder(Some(alpha)), count: 3 }]);
+
+        // Ctrl+A picks everything shown, and Delete removes it all
+        driver.press_with(ModifiersState::CONTROL, KeyCode::KeyA);
+        assert_eq!(driver.widget().multi_selection().len(), 4);
+        assert_eq!(driver.widget().multi_selection(), driver.widget().shown_items());
+        driver.press(KeyCode::Delete);
+        assert!(driver.todo_list().lock().unwrap().is_empty());
+        assert!(driver.widget().multi_selection().is_empty());
+    }
+
+    #[test]
+    fn test_toolbar_follows_the_layout() {
+        use crate::layout::FilterControl;
+        let (mut driver, ids) = UiDriver::with_tasks(&["Alpha", "Beta"]);
+        driver.click_checkbox(ids[0]);
+
+        // With only the priority and status selectors, status moves to second place
+        driver.widget_mut().set_toolbar(&[FilterControl::Priority, FilterControl::Status]);
+        driver.click_at(50.0 + 180.0, 100.0 + 25.0);
+        assert_eq!(driver.widget().session().status_filter, Some(Status::NotStarted));
+
+        // Where the hidden controls were is now empty
+        driver.click_at(50.0 + 310.0, 100.0 + 25.0);
+        assert_eq!(driver.widget().session().status_filter, Some(Status::NotStarted));
+        assert_eq!(driver.widget().shown_items(), vec![ids[1]]);
+    }
+
+    #[test]
+    fn test_sort_control_orders_the_list() {
+        use crate::core::prelude::{SortKey, SortOrder};
+        let mut todo_list = TodoList::new("Test");
+        let later = todo_list.add_item(TodoItem::new("Later").with_due_date(2_000));
+        let soon = todo_list.add_item(TodoItem::new("Soon").with_due_date(1_000));
+        let undated = todo_list.create_item("Undated");
+        let mut driver = UiDriver::new(todo_list);
+
+        // The sort dropdown follows the priority selector; its third option is the due date
+        driver.click_at(50.0 + 620.0, 100.0 + 25.0);
+        assert!(driver.widget().is_menu_open());
+        for _ in 0..3 {
+            driver.press(KeyCode::ArrowDown);
+        }
+        driver.press(KeyCode::Enter);
+        assert!(!driver.widget().is_menu_open());
+        assert_eq!(driver.widget().shown_items(), vec![soon, later, undated]);
+        let order = SortOrder { key: SortKey::DueDate, descending: false };
+        assert_eq!(driver.take_events(), vec![UiEvent::SortChanged { order }]);
+
+        // The arrow next to it flips the direction, keeping undated tasks last
+        driver.click_at(50.0 + 715.0, 100.0 + 25.0);
+        assert!(driver.widget().sort_order().descending);
+        assert_eq!(driver.widget().shown_items(), vec![later, soon, undated]);
+
+        // The order belongs to the list, so a fresh widget on it sorts the same way
+        let restored = UiDriver::new(driver.widget().todo_list().lock().unwrap().clone());
+        assert_eq!(restored.widget().shown_items(), vec![later, soon, undated]);
+    }
+
+    #[test]
+    fn test_tag_chips_filter_the_list() {
+        let mut todo_list = TodoList::new("Test");
+        let alpha = todo_list.add_item(TodoItem::new("Alpha").with_tag("work"));
+        let beta = todo_list.add_item(TodoItem::new("Beta").with_tag("home"));
+        let gamma = todo_list.create_item("Gamma");
+        let mut driver = UiDriver::new(todo_list);
+
+        // The tag row pushes the items down
+        let top = |driver: &UiDriver| driver.widget().shown_items().iter()
+            .map(|&id| driver.widget().item_widget(id).unwrap().position().1)
+            .fold(f32::MAX, f32::min);
+        assert_eq!(top(&driver), 100.0 + 80.0);
+
+        // Chips for "home" then "work", below the filter controls
+        driver.click_at(50.0 + 30.0, 100.0 + 60.0);
+        assert_eq!(driver.widget().shown_items(), vec![beta]);
+        driver.click_at(50.0 + 80.0, 100.0 + 60.0);
+        assert_eq!(driver.widget().shown_items(), vec![alpha]);
+        assert_eq!(driver.widget().session().tag_filter.as_deref(), Some("work"));
+
+        // Clicking the active tag again shows everything
+        driver.click_at(50.0 + 80.0, 100.0 + 60.0);
+        assert_eq!(driver.widget().shown_items().len(), 3);
+
+        // So does a chip on a row, right after its title
+        let (_, y) = driver.widget().item_widget(beta).unwrap().position();
+        driver.click_at(50.0 + 126.0, y + 20.0);
+        assert_eq!(driver.widget().tag_filter(), Some("home"));
+        assert_eq!(driver.widget().shown_items(), vec![beta]);
+        assert_eq!(driver.take_events(), vec![UiEvent::TagClicked { id: beta, index: 0 }]);
+
+        // Without tags, the row disappears and a filter on a vanished tag is dropped
+        driver.todo_list().lock().unwrap().delete_tag("home");
+        driver.todo_list().lock().unwrap().delete_tag("work");
+        driver.widget_mut().refresh();
+        assert_eq!(driver.widget().tag_filter(), None);
+        assert!(driver.widget().shown_items().contains(&gamma));
+        assert_eq!(top(&driver), 100.0 + 50.0);
+    }
+
+    #[test]
+    fn test_projects_group_and_filter_tasks() {
+        use crate::core::prelude::{Command, Project};
+        use crate::ui::{EditTaskEvent, EditTaskModal};
+        let (mut driver, ids) = UiDriver::with_tasks(&["Weed", "Report"]);
+        let dispatcher = Dispatcher::new(driver.todo_list().clone());
+        let garden = Project::new("Garden", [60, 220, 120]);
+        dispatcher.execute(Command::PutProject { project: garden.clone() }).unwrap();
+
+        // The editor offers the list's projects after no project
+        let mut modal = EditTaskModal::new(dispatcher.clone());
+        let (width, height) = VIEWPORT;
+        assert!(modal.show(ids[0], width, height));
+        for _ in 0..8 {
+            modal.handle_key_press(KeyCode::Tab);
+        }
+        modal.handle_key_press(KeyCode::ArrowDown);
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), Some(EditTaskEvent::Saved(ids[0])));
+        assert_eq!(driver.task(ids[0]).unwrap().project_id(), Some(garden.id()));
+
+        driver.widget_mut().set_project_filter(Some(garden.id()));
+        assert_eq!(driver.widget().shown_items(), [ids[0]]);
+        assert!(driver.widget().filter_summary().contains("Garden"));
+        assert_eq!(driver.widget().session().project_filter, Some(garden.id()));
+
+        // A removed project can't be filtered by any more
+        dispatcher.execute(Command::RemoveProject { id: garden.id() }).unwrap();
+        driver.widget_mut().refresh();
+        assert_eq!(driver.widget().project_filter(), None);
+        assert_eq!(driver.widget().shown_items().len(), 2);
+    }
+
+    #[test]
+    fn test_collapsing_a_subtree() {
+        use crate::session::Session;
+
+        let mut todo_list = TodoList::new("Test");
+        let parent = todo_list.create_item("Parent");
+        let child = todo_list.create_item("Child");
+        let grandchild = todo_list.create_item("Grandchild");
+        let other = todo_list.create_item("Other");
+        todo_list.move_item(child, Some(parent)).unwrap();
+        todo_list.move_item(grandchild, Some(child)).unwrap();
+        let mut driver = UiDriver::new(todo_list);
+
+        // Children follow their parent, indented
+        let rows = driver.widget().shown_items();
+        let row = |id| rows.iter().position(|&row| row == id).unwrap();
+        assert_eq!((row(child), row(grandchild)), (row(parent) + 1, row(parent) + 2));
+        assert_eq!(driver.widget().item_widget(grandchild).unwrap().hierarchy_level(), 2);
+        assert!(driver.widget().item_widget(other).unwrap().disclosure_bounds().is_none());
+
+        // Collapsed rows slide out before they're dropped
+        driver.click_collapse_toggle(parent);
+        assert_eq!(driver.take_events(), vec![UiEvent::CollapseToggled { id: parent, collapsed: true }]);
+        assert_eq!(driver.widget().shown_items().len(), 2);
+        driver.tick(0.075);
+        assert!((driver.widget().item_widget(child).unwrap().reveal() - 0.5).abs() < 0.01);
+        driver.tick(0.1);
+        assert!(driver.widget().item_widget(child).is_none());
+        assert_eq!(driver.widget().session().collapsed_items, vec![parent]);
+
+        // Expanded again, they slide back in where they were
+        driver.click_collapse_toggle(parent);
+        assert_eq!(driver.widget().item_widget(grandchild).unwrap().reveal(), 0.0);
+        driver.tick(0.2);
+        assert_eq!(driver.widget().shown_items(), rows);
+        assert_eq!(driver.widget().item_widget(grandchild).unwrap().reveal(), 1.0);
+
+        // The collapse state comes back with the session
+        driver.widget_mut().set_collapsed(child, true);
+        let mut restored = UiDriver::new(driver.todo_list().lock().unwrap().clone());
+        restored.widget_mut().restore_session(&Session { collapsed_items: vec![child], ..Session::default() });
+        assert!(restored.widget().item_widget(child).is_some());
+        assert!(restored.widget().item_widget(grandchild).is_none());
+    }
+}
@@ -0,0 +1,401 @@
+use uuid::Uuid;
+use crate::core::prelude::TodoList;
+use crate::i18n::{self, tr};
+use crate::ui::{damage, RenderContext, TextOverflow, Widget, WidgetInfo, Button, CyberpunkTheme};
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+const HEADER_HEIGHT: f32 = 40.0;
+const ROW_HEIGHT: f32 = 32.0;
+const FOOTER_HEIGHT: f32 = 76.0;
+/// Rows shown at once; more scroll
+const VISIBLE_ROWS: usize = 10;
+const WIDTH: f32 = 560.0;
+/// Width of the restore link at the end of each row
+const RESTORE_WIDTH: f32 = 96.0;
+/// Width of the completion date before it
+const DATE_WIDTH: f32 = 110.0;
+/// Indent per level of an archived subtask
+const INDENT: f32 = 16.0;
+
+/// Convert [f32; 4] RGBA values to wgpu::Color
+fn to_color(rgba: [f32; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: rgba[0] as f64,
+        g: rgba[1] as f64,
+        b: rgba[2] as f64,
+        a: rgba[3] as f64,
+    }
+}
+
+/// What a click or key press in the archive view asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveEvent {
+    /// Bring this archived task back into the list, with its archived subtasks
+    Restore(Uuid),
+    /// Archive the list's completed tasks now
+    ArchiveCompleted,
+    /// The view was closed
+    Closed,
+}
+
+/// An archived task as the view shows it
+struct ArchivedRow {
+    id: Uuid,
+    title: String,
+    depth: usize,
+    completed: String,
+}
+
+/// Modal listing a list's archived tasks, each with a link to restore it
+///
+/// The view only shows a copy of the archive; its owner makes the changes it
+/// asks for and hands it the list again with `set_list`.
+pub struct ArchiveView {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rows: Vec<ArchivedRow>,
+    /// Index of the top row shown
+    scroll: usize,
+    hovered_restore: Option<usize>,
+    archive_button: Button,
+    close_button: Button,
+    is_visible: bool,
+    theme: CyberpunkTheme,
+}
+
+impl ArchiveView {
+    /// Create a hidden view; call `show` to open it
+    pub fn new() -> Self {
+        let theme = CyberpunkTheme::new();
+
+        let archive_button = Button::new(0.0, 0.0, 200.0, 36.0, tr("archive-completed"))
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.neon_pink()));
+        let close_button = Button::new(0.0, 0.0, 120.0, 36.0, tr("archive-close"))
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.filter_button_bg()));
+
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: WIDTH,
+            height: HEADER_HEIGHT + ROW_HEIGHT * VISIBLE_ROWS as f32 + FOOTER_HEIGHT,
+            rows: Vec::new(),
+            scroll: 0,
+            hovered_restore: None,
+            archive_button,
+            close_button,
+            is_visible: false,
+            theme,
+        }
+    }
+
+    /// Check if the view is currently shown
+    pub fn is_visible(&self) -> bool {
+        self.is_visible
+    }
+
+    /// Open the view on a list's archive
+    pub fn show(&mut self, list: &TodoList, screen_width: f32, screen_height: f32) {
+        self.scroll = 0;
+        self.set_list(list);
+        self.is_visible = true;
+        self.layout(screen_width, screen_height);
+    }
+
+    /// Show the list's archive as it is now, keeping the scroll position where it can
+    pub fn set_list(&mut self, list: &TodoList) {
+        self.rows = list
+            .archived_items()
+            .into_iter()
+            .map(|(item, depth)| ArchivedRow {
+                id: item.id(),
+                title: item.title().to_string(),
+                depth,
+                completed: item.completed_at().map(i18n::format_date).unwrap_or_default(),
+            })
+            .collect();
+        self.scroll = self.scroll.min(self.max_scroll());
+        self.hovered_restore = None;
+    }
+
+    /// Number of archived tasks shown
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Hide the view
+    pub fn hide(&mut self) {
+        self.is_visible = false;
+        self.hovered_restore = None;
+    }
+
+    /// Re-center the view, e.g. after a window resize
+    pub fn layout(&mut self, screen_width: f32, screen_height: f32) {
+        self.width = screen_width.min(WIDTH);
+        self.x = (screen_width - self.width) / 2.0;
+        self.y = (screen_height - self.height) / 2.0;
+
+        let button_y = self.y + self.height - 56.0;
+        self.close_button.set_position(self.x + self.width - 130.0, button_y);
+        self.archive_button.set_position(self.x + self.width - 340.0, button_y);
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.rows.len().saturating_sub(VISIBLE_ROWS)
+    }
+
+    /// Top edge of the slot a shown row is drawn in
+    fn slot_y(&self, slot: usize) -> f32 {
+        self.y + HEADER_HEIGHT + slot as f32 * ROW_HEIGHT
+    }
+
+    /// Index of the row whose restore link is under a point
+    fn restore_at(&self, x: f32, y: f32) -> Option<usize> {
+        let restore_x = self.x + self.width - RESTORE_WIDTH;
+        if x < restore_x || x > self.x + self.width || y < self.slot_y(0) {
+            return None;
+        }
+        let slot = ((y - self.slot_y(0)) / ROW_HEIGHT) as usize;
+        let index = self.scroll + slot;
+        (slot < VISIBLE_ROWS && index < self.rows.len()).then_some(index)
+    }
+
+    /// Scroll by a number of pixels, a row at a time
+    pub fn handle_mouse_wheel(&mut self, delta: f32) {
+        let rows = (delta / ROW_HEIGHT).round() as isize;
+        let rows = if rows == 0 && delta != 0.0 { delta.signum() as isize } else { rows };
+        self.scroll = self.scroll.saturating_add_signed(rows).min(self.max_scroll());
+        self.hovered_restore = None;
+    }
+
+    /// Handle mouse movement for hover states
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        let hovered_restore = self.restore_at(x, y);
+        damage::set(&mut self.hovered_restore, hovered_restore);
+        self.archive_button.handle_mouse_move(x, y);
+        self.close_button.handle_mouse_move(x, y);
+    }
+
+    /// Handle mouse button press
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) {
+        if !self.is_visible {
+            return;
+        }
+        self.archive_button.handle_mouse_down(x, y);
+        self.close_button.handle_mouse_down(x, y);
+    }
+
+    /// Handle mouse button release, returning what the click asks for
+    pub fn handle_mouse_up(&mut self, x: f32, y: f32) -> Option<ArchiveEvent> {
+        if !self.is_visible {
+            return None;
+        }
+
+        let event = if self.archive_button.contains_point(x, y) {
+            Some(ArchiveEvent::ArchiveCompleted)
+        } else if self.close_button.contains_point(x, y) {
+            self.hide();
+            Some(ArchiveEvent::Closed)
+        } else {
+            self.restore_at(x, y).map(|index| ArchiveEvent::Restore(self.rows[index].id))
+        };
+
+        self.archive_button.handle_mouse_up(x, y);
+        self.close_button.handle_mouse_up(x, y);
+        event
+    }
+
+    /// Handle keyboard input: arrows and page keys scroll, Escape closes
+    pub fn handle_key_press(&mut self, key: KeyCode) -> Option<ArchiveEvent> {
+        if !self.is_visible {
+            return None;
+        }
+
+        let page = VISIBLE_ROWS as isize;
+        let rows = match key {
+            KeyCode::Escape => {
+                self.hide();
+                return Some(ArchiveEvent::Closed);
+            }
+            KeyCode::ArrowUp => -1,
+            KeyCode::ArrowDown => 1,
+            KeyCode::PageUp => -page,
+            KeyCode::PageDown => page,
+            KeyCode::Home => -(self.rows.len() as isize),
+            KeyCode::End => self.rows.len() as isize,
+            _ => return None,
+        };
+        self.scroll = self.scroll.saturating_add_signed(rows).min(self.max_scroll());
+        None
+    }
+}
+
+impl Default for ArchiveView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for ArchiveView {
+    fn update(&mut self, delta_time: f32) {
+        for button in [&mut self.archive_button, &mut self.close_button] {
+            if button.needs_update() {
+                button.update(delta_time);
+            }
+        }
+    }
+
+    fn needs_update(&self) -> bool {
+        self.is_visible && (self.archive_button.needs_update() || self.close_button.needs_update())
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        if self.is_visible {
+            out.push(WidgetInfo::of(self, depth));
+            self.archive_button.inspect(depth + 1, out);
+            self.close_button.inspect(depth + 1, out);
+        }
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_visible {
+            return;
+        }
+
+        ctx.draw_rect(0.0, 0.0, ctx.width, ctx.height, self.theme.get_modal_overlay_color());
+        ctx.draw_modal_frame(&self.theme, self.x, self.y, self.width, self.height, HEADER_HEIGHT);
+
+        ctx.draw_label(
+            "archive-title",
+            self.x + 20.0, self.y + 8.0,
+            24.0,
+            self.theme.get_modal_text_color(),
+        );
+
+        let text_size = self.theme.text_size();
+        if self.rows.is_empty() {
+            ctx.draw_label(
+                "archive-empty",
+                self.x + 20.0, self.slot_y(0) + (ROW_HEIGHT - text_size) / 2.0,
+                text_size,
+                self.theme.muted_text(),
+            );
+        }
+
+        let restore_label = tr("archive-restore");
+        let restore_width = ctx.measure_text(&restore_label, text_size).width;
+        for (slot, (index, row)) in self.rows.iter().enumerate().skip(self.scroll).take(VISIBLE_ROWS).enumerate() {
+            let row_y = self.slot_y(slot);
+            let text_y = row_y + (ROW_HEIGHT - text_size) / 2.0;
+            let restore_x = self.x + self.width - RESTORE_WIDTH;
+            if self.hovered_restore == Some(index) {
+                ctx.draw_rect(restore_x, row_y, RESTORE_WIDTH, ROW_HEIGHT, self.theme.item_hover_bg());
+            }
+
+            let title_x = self.x + 20.0 + row.depth as f32 * INDENT;
+            let title_width = (restore_x - DATE_WIDTH - title_x - 8.0).max(0.0);
+            ctx.draw_text_bounded(&row.title, (title_x, text_y), title_width, text_size, self.theme.bright_text(), TextOverflow::Ellipsis);
+            ctx.draw_text(&row.completed, restore_x - DATE_WIDTH, text_y, text_size * 0.85, self.theme.muted_text());
+            ctx.draw_text(
+                &restore_label,
+                restore_x + (RESTORE_WIDTH - restore_width) / 2.0, text_y,
+                text_size,
+                self.theme.cyan(),
+            );
+        }
+
+        self.archive_button.render(ctx);
+        self.close_button.render(ctx);
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        let dx = x - self.x;
+        let dy = y - self.y;
+        self.x = x;
+        self.y = y;
+
+        for button in [&mut self.archive_button, &mut self.close_button] {
+            let (button_x, button_y) = button.position();
+            button.set_position(button_x + dx, button_y + dy);
+        }
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if !self.is_visible {
+            return None;
+        }
+        if self.restore_at(x, y).is_some() {
+            return Some(CursorIcon::Pointer);
+        }
+        self.archive_button
+            .cursor_at(x, y)
+            .or_else(|| self.close_button.cursor_at(x, y))
+            .or(Some(CursorIcon::Default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::{Command, Dispatcher, Status, TodoItem};
+    use crate::ui::testing::VIEWPORT;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_archive_view_restores_and_scrolls() {
+        let mut list = TodoList::new("Archive");
+        let ids: Vec<Uuid> = (0..12)
+            .map(|n| list.add_item(TodoItem::new(&format!("Done {}", n)).with_status(Status::Completed)))
+            .collect();
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(list)));
+        dispatcher.execute(Command::Batch(ids.iter().map(|&id| Command::Archive { id }).collect())).unwrap();
+
+        let mut view = ArchiveView::new();
+        let (width, height) = VIEWPORT;
+        view.show(&dispatcher.lock(), width, height);
+        assert_eq!(view.len(), 12);
+        let (x, y) = view.position();
+        let (view_width, view_height) = view.dimensions();
+        // Below the 40px header, 32px rows end in their restore link
+        let (link_x, second_row_y) = (x + view_width - 40.0, y + 40.0 + 32.0 + 16.0);
+
+        // Only the link restores a row
+        assert_eq!(view.handle_mouse_up(link_x, second_row_y), Some(ArchiveEvent::Restore(ids[1])));
+        assert_eq!(view.handle_mouse_up(x + 40.0, second_row_y), None);
+
+        // Ten rows are shown; scrolling stops once the last one is
+        view.handle_mouse_wheel(32.0 * 5.0);
+        assert_eq!(view.handle_mouse_up(link_x, second_row_y), Some(ArchiveEvent::Restore(ids[3])));
+        dispatcher.execute(Command::Restore { id: ids[3] }).unwrap();
+        view.set_list(&dispatcher.lock());
+        assert_eq!(view.len(), 11);
+        assert!(dispatcher.lock().get_item(ids[3]).is_some());
+        assert_eq!(view.handle_mouse_up(link_x, second_row_y), Some(ArchiveEvent::Restore(ids[2])));
+
+        let footer_y = y + view_height - 38.0;
+        assert_eq!(view.handle_mouse_up(x + view_width - 240.0, footer_y), Some(ArchiveEvent::ArchiveCompleted));
+        assert_eq!(view.handle_key_press(KeyCode::Escape), Some(ArchiveEvent::Closed));
+        assert!(!view.is_visible());
+    }
+}
@@ -0,0 +1,304 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+use crate::i18n::{self, tr};
+use crate::ui::{damage, RenderContext, Widget, CyberpunkTheme};
+
+const CELL_SIZE: f32 = 36.0;
+const PADDING: f32 = 8.0;
+const HEADER_HEIGHT: f32 = 36.0;
+const WEEKDAY_HEIGHT: f32 = 24.0;
+const FOOTER_HEIGHT: f32 = 32.0;
+/// Weeks shown at once, enough for any month
+const WEEKS: usize = 6;
+
+/// Result of interacting with the DatePicker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DatePickerEvent {
+    /// A day was chosen, as the Unix timestamp of its local midnight
+    Picked(u64),
+    /// The date was removed
+    Cleared,
+}
+
+/// A month calendar that pops up to choose a day
+///
+/// Shows one month at a time, weeks starting on Monday, with arrows to page
+/// through the months and a footer to jump to today or clear the date. The
+/// keyboard moves a highlighted day: arrows by a day or a week, Page Up and
+/// Page Down by a month.
+pub struct DatePicker {
+    x: f32,
+    y: f32,
+    /// First of the month on show
+    month: NaiveDate,
+    /// The highlighted day, moved by the keyboard and the pointer
+    cursor: NaiveDate,
+    /// The day the picker was opened with
+    selected: Option<NaiveDate>,
+    is_open: bool,
+    theme: CyberpunkTheme,
+}
+
+fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+impl DatePicker {
+    /// Create a closed picker; call `open` to show it
+    pub fn new() -> Self {
+        let today = today();
+        Self {
+            x: 0.0,
+            y: 0.0,
+            month: first_of_month(today),
+            cursor: today,
+            selected: None,
+            is_open: false,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    /// Show the calendar with its top-left corner at a point, on the month of `selected` or of today
+    pub fn open(&mut self, x: f32, y: f32, selected: Option<u64>) {
+        self.x = x;
+        self.y = y;
+        self.selected = selected.and_then(i18n::local_date);
+        self.cursor = self.selected.unwrap_or_else(today);
+        self.month = first_of_month(self.cursor);
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    /// Size of the open calendar
+    pub fn size() -> (f32, f32) {
+        (
+            CELL_SIZE * 7.0 + PADDING * 2.0,
+            HEADER_HEIGHT + WEEKDAY_HEIGHT + CELL_SIZE * WEEKS as f32 + FOOTER_HEIGHT + PADDING,
+        )
+    }
+
+    /// The Monday on or before the first of the month, where the grid starts
+    fn grid_start(&self) -> NaiveDate {
+        let offset = self.month.weekday().num_days_from_monday();
+        self.month - Days::new(offset as u64)
+    }
+
+    /// Top-left corner of a day's cell
+    fn cell_position(&self, index: usize) -> (f32, f32) {
+        (
+            self.x + PADDING + (index % 7) as f32 * CELL_SIZE,
+            self.y + HEADER_HEIGHT + WEEKDAY_HEIGHT + (index / 7) as f32 * CELL_SIZE,
+        )
+    }
+
+    /// The day whose cell holds a point
+    fn day_at(&self, x: f32, y: f32) -> Option<NaiveDate> {
+        let column = ((x - self.x - PADDING) / CELL_SIZE).floor();
+        let row = ((y - self.y - HEADER_HEIGHT - WEEKDAY_HEIGHT) / CELL_SIZE).floor();
+        if !(0.0..7.0).contains(&column) || !(0.0..WEEKS as f32).contains(&row) {
+            return None;
+        }
+        self.grid_start().checked_add_days(Days::new((row * 7.0 + column) as u64))
+    }
+
+    /// Show another month, keeping the highlighted day in it
+    fn show_month(&mut self, month: Option<NaiveDate>) {
+        if let Some(month) = month {
+            self.month = first_of_month(month);
+            let day = self.cursor.day();
+            self.cursor = (0..4)
+                .find_map(|back| self.month.with_day(day.saturating_sub(back)))
+                .unwrap_or(self.month);
+        }
+    }
+
+    /// Move the highlighted day, following it into other months
+    fn move_cursor(&mut self, day: Option<NaiveDate>) {
+        if let Some(day) = day {
+            self.cursor = day;
+            self.month = first_of_month(day);
+        }
+    }
+
+    fn pick(&mut self, day: NaiveDate) -> Option<DatePickerEvent> {
+        self.close();
+        i18n::local_midnight(day).map(DatePickerEvent::Picked)
+    }
+
+    /// Highlight the day under the pointer
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        if !self.is_open {
+            return;
+        }
+        if let Some(day) = self.day_at(x, y).filter(|day| first_of_month(*day) == self.month) {
+            damage::set(&mut self.cursor, day);
+        }
+    }
+
+    /// Handle a click on the calendar, returning the chosen date if one was picked
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> Option<DatePickerEvent> {
+        if !self.is_open || !self.contains_point(x, y) {
+            return None;
+        }
+        let (width, height) = Self::size();
+
+        if y < self.y + HEADER_HEIGHT {
+            if x < self.x + CELL_SIZE + PADDING {
+                self.show_month(self.month.checked_sub_months(Months::new(1)));
+            } else if x > self.x + width - CELL_SIZE - PADDING {
+                self.show_month(self.month.checked_add_months(Months::new(1)));
+            }
+            None
+        } else if y > self.y + height - FOOTER_HEIGHT - PADDING {
+            if x < self.x + width / 2.0 {
+                self.pick(today())
+            } else {
+                self.close();
+                Some(DatePickerEvent::Cleared)
+            }
+        } else {
+            let day = self.day_at(x, y)?;
+            self.pick(day)
+        }
+    }
+
+    /// Handle keyboard input while open: arrows and Page Up/Down move, Enter picks,
+    /// Delete clears and Escape closes without changing anything
+    pub fn handle_key_press(&mut self, key: KeyCode) -> Option<DatePickerEvent> {
+        if !self.is_open {
+            return None;
+        }
+        match key {
+            KeyCode::ArrowLeft => self.move_cursor(self.cursor.checked_sub_days(Days::new(1))),
+            KeyCode::ArrowRight => self.move_cursor(self.cursor.checked_add_days(Days::new(1))),
+            KeyCode::ArrowUp => self.move_cursor(self.cursor.checked_sub_days(Days::new(7))),
+            KeyCode::ArrowDown => self.move_cursor(self.cursor.checked_add_days(Days::new(7))),
+            KeyCode::PageUp => self.show_month(self.month.checked_sub_months(Months::new(1))),
+            KeyCode::PageDown => self.show_month(self.month.checked_add_months(Months::new(1))),
+            KeyCode::Home => self.move_cursor(Some(today())),
+            KeyCode::Enter | KeyCode::Space => return self.pick(self.cursor),
+            KeyCode::Delete | KeyCode::Backspace => {
+                self.close();
+                return Some(DatePickerEvent::Cleared);
+            }
+            KeyCode::Escape => self.close(),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl Default for DatePicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for DatePicker {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_open {
+            return;
+        }
+        let (width, height) = Self::size();
+        let text_size = self.theme.text_size();
+        let small_size = self.theme.small_text_size();
+
+        ctx.draw_rect(self.x, self.y, width, height, self.theme.get_modal_bg_color());
+        ctx.draw_outline(self.x, self.y, width, height, 1.0, self.theme.cyan());
+
+        // Month and year between the paging arrows
+        let title = format!("{} {}", i18n::month_name(self.month.month()), self.month.year());
+        let title_width = ctx.measure_text(&title, text_size).width;
+        let header_y = self.y + (HEADER_HEIGHT - text_size) / 2.0;
+        ctx.draw_text(&title, self.x + (width - title_width) / 2.0, header_y, text_size, self.theme.bright_text());
+        ctx.draw_text("‹", self.x + PADDING + 12.0, header_y, text_size, self.theme.cyan());
+        ctx.draw_text("›", self.x + width - PADDING - 20.0, header_y, text_size, self.theme.cyan());
+
+        for (column, name) in i18n::weekday_names().iter().enumerate() {
+            ctx.draw_text(
+                name,
+                self.x + PADDING + column as f32 * CELL_SIZE + 8.0, self.y + HEADER_HEIGHT,
+                small_size,
+                self.theme.muted_text(),
+            );
+        }
+
+        let today = today();
+        let mut day = self.grid_start();
+        for index in 0..WEEKS * 7 {
+            let (cell_x, cell_y) = self.cell_position(index);
+            if Some(day) == self.selected {
+                ctx.draw_rect(cell_x + 2.0, cell_y + 2.0, CELL_SIZE - 4.0, CELL_SIZE - 4.0, self.theme.neon_pink());
+            }
+            if day == self.cursor {
+                ctx.draw_outline(cell_x + 2.0, cell_y + 2.0, CELL_SIZE - 4.0, CELL_SIZE - 4.0, 1.0, self.theme.cyan());
+            }
+            let color = if first_of_month(day) != self.month {
+                self.theme.muted_text()
+            } else if day == today {
+                self.theme.cyan()
+            } else {
+                self.theme.bright_text()
+            };
+            let label = day.day().to_string();
+            let label_width = ctx.measure_text(&label, small_size).width;
+            ctx.draw_text(
+                &label,
+                cell_x + (CELL_SIZE - label_width) / 2.0, cell_y + (CELL_SIZE - small_size) / 2.0,
+                small_size,
+                color,
+            );
+            day = day.succ_opt().unwrap_or(day);
+        }
+
+        let footer_y = self.y + height - FOOTER_HEIGHT - PADDING + (FOOTER_HEIGHT - small_size) / 2.0;
+        ctx.draw_text(&tr("date-picker-today"), self.x + PADDING + 8.0, footer_y, small_size, self.theme.cyan());
+        let clear = tr("date-picker-clear");
+        let clear_width = ctx.measure_text(&clear, small_size).width;
+        ctx.draw_text(&clear, self.x + width - PADDING - 8.0 - clear_width, footer_y, small_size, self.theme.danger());
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        Self::size()
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// The calendar's size is fixed
+    fn set_dimensions(&mut self, _width: f32, _height: f32) {}
+
+    fn contains_point(&self, x: f32, y: f32) -> bool {
+        let (width, height) = Self::size();
+        self.is_open && x >= self.x && x <= self.x + width && y >= self.y && y <= self.y + height
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.contains_point(x, y).then_some(CursorIcon::Pointer)
+    }
+}
@@ -0,0 +1,211 @@
+use crate::ui::{damage, RenderContext, Widget, CyberpunkTheme};
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+/// A closed box showing the chosen option, which opens into a list of all options
+///
+/// The open list hangs below the box and overlaps whatever is there, so owners
+/// render it with `render_options` after everything else.
+pub struct Dropdown<T> {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    options: Vec<(T, String)>,
+    selected: usize,
+    hovered: Option<usize>,
+    is_open: bool,
+    is_focused: bool,
+    theme: CyberpunkTheme,
+}
+
+impl<T: Copy + PartialEq> Dropdown<T> {
+    /// Create a closed dropdown with the first option chosen
+    pub fn new(x: f32, y: f32, width: f32, height: f32, options: Vec<(T, String)>) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            options,
+            selected: 0,
+            hovered: None,
+            is_open: false,
+            is_focused: false,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Offer other options, choosing the first
+    pub fn set_options(&mut self, options: Vec<(T, String)>) {
+        self.options = options;
+        self.selected = 0;
+        self.close();
+    }
+
+    /// The chosen option
+    pub fn selected(&self) -> T {
+        self.options[self.selected].0
+    }
+
+    /// Choose an option; values that aren't among the options are ignored
+    pub fn set_selected(&mut self, value: T) {
+        if let Some(index) = self.options.iter().position(|(option, _)| *option == value) {
+            self.selected = index;
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.hovered = None;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.is_focused
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.is_focused = focused;
+        if !focused {
+            self.close();
+        }
+    }
+
+    /// Top edge of an option in the open list
+    fn option_y(&self, index: usize) -> f32 {
+        self.y + self.height * (index + 1) as f32
+    }
+
+    /// The option under a point while open
+    fn option_at(&self, x: f32, y: f32) -> Option<usize> {
+        if !self.is_open || x < self.x || x > self.x + self.width {
+            return None;
+        }
+        (0..self.options.len()).find(|&index| {
+            let option_y = self.option_y(index);
+            y >= option_y && y < option_y + self.height
+        })
+    }
+
+    /// Whether a point is on the box or, while open, on the list
+    pub fn hit(&self, x: f32, y: f32) -> bool {
+        self.contains_point(x, y) || self.option_at(x, y).is_some()
+    }
+
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        let hovered = self.option_at(x, y);
+        damage::set(&mut self.hovered, hovered);
+    }
+
+    /// Open or close on a click on the box, choose on a click on an option
+    ///
+    /// Returns whether the click landed on the dropdown; it takes focus if so.
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if let Some(index) = self.option_at(x, y) {
+            self.selected = index;
+            self.close();
+        } else if self.contains_point(x, y) {
+            self.is_open = !self.is_open;
+        } else {
+            self.set_focused(false);
+            return false;
+        }
+        self.is_focused = true;
+        true
+    }
+
+    /// Arrow keys step through the options, Space and Enter open and close the list
+    ///
+    /// Returns whether the key was used.
+    pub fn handle_key_press(&mut self, key: KeyCode) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        match key {
+            KeyCode::ArrowUp => self.selected = self.selected.saturating_sub(1),
+            KeyCode::ArrowDown => self.selected = (self.selected + 1).min(self.options.len() - 1),
+            KeyCode::Space => self.is_open = !self.is_open,
+            KeyCode::Enter | KeyCode::Escape if self.is_open => self.close(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draw the open list of options
+    pub fn render_options(&self, ctx: &mut RenderContext) {
+        if !self.is_open {
+            return;
+        }
+        let list_y = self.option_y(0);
+        let list_height = self.height * self.options.len() as f32;
+        ctx.draw_rect(self.x, list_y, self.width, list_height, self.theme.get_modal_bg_color());
+        ctx.draw_outline(self.x, list_y, self.width, list_height, 1.0, self.theme.cyan());
+
+        for (index, (_, label)) in self.options.iter().enumerate() {
+            let option_y = self.option_y(index);
+            if self.hovered == Some(index) || (self.hovered.is_none() && index == self.selected) {
+                ctx.draw_rect(self.x + 1.0, option_y, self.width - 2.0, self.height, self.theme.item_hover_bg());
+            }
+            ctx.draw_text(
+                label,
+                self.x + 8.0, option_y + (self.height - self.theme.text_size()) / 2.0,
+                self.theme.text_size(),
+                self.theme.get_modal_text_color(),
+            );
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> Widget for Dropdown<T> {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let border = if self.is_focused { self.theme.neon_pink() } else { self.theme.cyan() };
+        ctx.draw_rect(self.x, self.y, self.width, self.height, self.theme.filter_button_bg());
+        ctx.draw_outline(self.x, self.y, self.width, self.height, 1.0, border);
+
+        let text_y = self.y + (self.height - self.theme.text_size()) / 2.0;
+        ctx.draw_text(
+            &self.options[self.selected].1,
+            self.x + 8.0, text_y,
+            self.theme.text_size(),
+            self.theme.bright_text(),
+        );
+        ctx.draw_text(
+            if self.is_open { "▴" } else { "▾" },
+            self.x + self.width - 20.0, text_y,
+            self.theme.text_size(),
+            self.theme.cyan(),
+        );
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.hit(x, y).then_some(CursorIcon::Pointer)
+    }
+}
@@ -0,0 +1,1197 @@
+use uuid::Uuid;
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+use crate::core::prelude::{Attachment, Command, Dispatcher, Priority, RecurrenceRule, Reminder, Status};
+use crate::i18n::{self, tr, tr_args};
+use crate::ui::{Button, Clipboard, CyberpunkTheme, FocusManager, RenderContext, TextArea, TextInput, Widget, WidgetInfo};
+use crate::ui::input::{Action, TextEvent};
+use crate::ui::text_metrics::text_width;
+use super::{DatePicker, DatePickerEvent, Dropdown};
+
+const HEADER_HEIGHT: f32 = 40.0;
+const ROW_HEIGHT: f32 = 48.0;
+const FIELD_HEIGHT: f32 = 34.0;
+/// Height of the description's field, four lines of text
+const DESCRIPTION_HEIGHT: f32 = 90.0;
+const LABEL_WIDTH: f32 = 130.0;
+const ERROR_HEIGHT: f32 = 24.0;
+const FOOTER_HEIGHT: f32 = 76.0;
+/// Width of the button opening the calendar, right of the due date
+const CALENDAR_BUTTON_WIDTH: f32 = 40.0;
+/// Height of the strip of attachment chips under their field, and of the chips in it
+const ATTACHMENT_STRIP_HEIGHT: f32 = 28.0;
+const ATTACHMENT_CHIP_HEIGHT: f32 = 22.0;
+const ATTACHMENT_TEXT_SIZE: f32 = 14.0;
+/// Whether this build can pick files to attach with the system's file dialog
+const FILE_PICKER: bool = cfg!(all(feature = "file-picker", not(target_arch = "wasm32")));
+
+/// Choices offered for how a task repeats, besides never
+const RECURRENCE_CHOICES: [RecurrenceRule; 6] = [
+    RecurrenceRule::Daily,
+    RecurrenceRule::EveryNDays(2),
+    RecurrenceRule::EveryNDays(3),
+    RecurrenceRule::Weekly,
+    RecurrenceRule::EveryNDays(14),
+    RecurrenceRule::Monthly,
+];
+
+/// Lead times offered for a task's reminder, in minutes before it's due
+const REMINDER_CHOICES: [u32; 6] = [0, 5, 15, 30, 60, 24 * 60];
+
+/// Result of interacting with the EditTaskModal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditTaskEvent {
+    /// The changes were written to the task and the modal closed
+    Saved(Uuid),
+    /// The modal closed without changing anything
+    Cancelled,
+    /// The button for attaching files was clicked; the owner opens the file
+    /// dialog and hands the files picked to `add_files`
+    PickFiles(Uuid),
+}
+
+/// The editable fields, in tab order
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Title,
+    Description,
+    Status,
+    Priority,
+    DueDate,
+    Recurrence,
+    Reminder,
+    Tags,
+    Project,
+    Attachments,
+}
+
+const FIELDS: [Field; 10] = [
+    Field::Title, Field::Description, Field::Status, Field::Priority, Field::DueDate, Field::Recurrence, Field::Reminder, Field::Tags,
+    Field::Project, Field::Attachments,
+];
+
+impl Field {
+    /// Message id of the field's label
+    fn label_id(self) -> &'static str {
+        match self {
+            Field::Title => "edit-field-title",
+            Field::Description => "edit-field-description",
+            Field::Status => "edit-field-status",
+            Field::Priority => "edit-field-priority",
+            Field::DueDate => "edit-field-due",
+            Field::Recurrence => "edit-field-repeat",
+            Field::Reminder => "edit-field-reminder",
+            Field::Tags => "edit-field-tags",
+            Field::Project => "edit-field-project",
+            Field::Attachments => "edit-field-attachments",
+        }
+    }
+
+    fn index(self) -> usize {
+        FIELDS.iter().position(|&field| field == self).unwrap_or(0)
+    }
+
+    /// Height of the field's input, the description's holding several lines
+    fn height(self) -> f32 {
+        match self {
+            Field::Description => DESCRIPTION_HEIGHT,
+            _ => FIELD_HEIGHT,
+        }
+    }
+
+    /// Height of the field's row, its input with the usual space around it,
+    /// and the attachments' chips under their input
+    fn row_height(self) -> f32 {
+        let below = if self == Field::Attachments { ATTACHMENT_STRIP_HEIGHT } else { 0.0 };
+        self.height() + ROW_HEIGHT - FIELD_HEIGHT + below
+    }
+}
+
+/// Convert [f32; 4] RGBA values to wgpu::Color
+fn to_color(rgba: [f32; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: rgba[0] as f64,
+        g: rgba[1] as f64,
+        b: rgba[2] as f64,
+        a: rgba[3] as f64,
+    }
+}
+
+/// Modal form for changing a task's title, description, status, priority, due
+/// date, recurrence, reminder, tags, project and attachments
+///
+/// Saving runs all changed fields as one batch through the dispatcher, so a
+/// single undo reverts the whole edit.
+pub struct EditTaskModal {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    /// Height of the window, to keep the calendar on screen
+    screen_height: f32,
+    commands: Dispatcher,
+    /// The task being edited, while shown
+    item_id: Option<Uuid>,
+    title_input: TextInput,
+    description_input: TextArea,
+    status: Dropdown<Status>,
+    priority: Dropdown<Priority>,
+    due_input: TextInput,
+    due_picker: DatePicker,
+    /// The due date as loaded, kept as is unless its text is changed
+    original_due: (Option<u64>, String),
+    recurrence: Dropdown<Option<RecurrenceRule>>,
+    reminder: Dropdown<Reminder>,
+    /// Comma separated tags
+    tags_input: TextInput,
+    /// None, or one of the list's projects
+    project: Dropdown<Option<Uuid>>,
+    /// A link or file path to attach, added on Enter
+    attachment_input: TextInput,
+    /// The attachments as they'll be saved
+    attachments: Vec<Attachment>,
+    focus: FocusManager<Field>,
+    /// Why the last save was refused
+    error: Option<String>,
+    save_button: Button,
+    cancel_button: Button,
+    theme: CyberpunkTheme,
+}
+
+impl EditTaskModal {
+    /// Create a hidden modal editing tasks of the dispatcher's list; call `show` to open it
+    pub fn new(commands: Dispatcher) -> Self {
+        let theme = CyberpunkTheme::new();
+        let text_input = |placeholder: String| {
+            TextInput::new(0.0, 0.0, 0.0, FIELD_HEIGHT, placeholder)
+                .with_text_color(to_color(theme.bright_text()))
+                .with_placeholder_color(to_color(theme.muted_text()))
+        };
+        let statuses = [Status::NotStarted, Status::InProgress, Status::Completed]
+            .map(|status| (status, i18n::status_label(status)));
+        let priorities = [Priority::Low, Priority::Medium, Priority::High]
+            .map(|priority| (priority, i18n::priority_label(priority)));
+
+        let save_button = Button::new(0.0, 0.0, 120.0, 36.0, tr("edit-save"))
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.neon_pink()));
+        let cancel_button = Button::new(0.0, 0.0, 120.0, 36.0, tr("edit-cancel"))
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.filter_button_bg()));
+
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 560.0,
+            height: HEADER_HEIGHT + 20.0 + FIELDS.iter().map(|field| field.row_height()).sum::<f32>() + ERROR_HEIGHT + FOOTER_HEIGHT,
+            screen_height: 0.0,
+            commands,
+            item_id: None,
+            title_input: text_input(String::new()).with_max_length(200),
+            description_input: TextArea::new(0.0, 0.0, 0.0, DESCRIPTION_HEIGHT, tr("item-no-description"))
+                .with_text_color(to_color(theme.bright_text()))
+                .with_placeholder_color(to_color(theme.muted_text())),
+            status: Dropdown::new(0.0, 0.0, 0.0, FIELD_HEIGHT, statuses.to_vec()),
+            priority: Dropdown::new(0.0, 0.0, 0.0, FIELD_HEIGHT, priorities.to_vec()),
+            due_input: text_input(tr("edit-due-placeholder")),
+            due_picker: DatePicker::new(),
+            original_due: (None, String::new()),
+            recurrence: Dropdown::new(0.0, 0.0, 0.0, FIELD_HEIGHT, Self::recurrence_options(None)),
+            reminder: Dropdown::new(0.0, 0.0, 0.0, FIELD_HEIGHT, Self::reminder_options(Reminder::Default)),
+            tags_input: text_input(tr("edit-tags-placeholder")),
+            project: Dropdown::new(0.0, 0.0, 0.0, FIELD_HEIGHT, vec![(None, tr("edit-no-project"))]),
+            attachment_input: text_input(tr("edit-attachment-placeholder")),
+            attachments: Vec::new(),
+            focus: FocusManager::new(),
+            error: None,
+            save_button,
+            cancel_button,
+            theme,
+        }
+    }
+
+    /// Never, the usual intervals, and `current` if it's an interval not among them
+    fn recurrence_options(current: Option<RecurrenceRule>) -> Vec<(Option<RecurrenceRule>, String)> {
+        let mut rules: Vec<Option<RecurrenceRule>> = std::iter::once(None)
+            .chain(RECURRENCE_CHOICES.into_iter().map(Some))
+            .collect();
+        if !rules.contains(&current) {
+            rules.push(current);
+        }
+        rules.into_iter().map(|rule| (rule, i18n::recurrence_label(rule))).collect()
+    }
+
+    /// The app's default, the usual lead times, off, and `current` if it's a lead time not among them
+    fn reminder_options(current: Reminder) -> Vec<(Reminder, String)> {
+        let mut reminders: Vec<Reminder> = std::iter::once(Reminder::Default)
+            .chain(REMINDER_CHOICES.into_iter().map(Reminder::MinutesBefore))
+            .chain(std::iter::once(Reminder::Off))
+            .collect();
+        if !reminders.contains(&current) {
+            reminders.push(current);
+        }
+        reminders.into_iter().map(|reminder| (reminder, i18n::reminder_label(reminder))).collect()
+    }
+
+    /// Edit tasks of another list from now on, closing the modal if it was open
+    pub fn set_dispatcher(&mut self, commands: Dispatcher) {
+        self.hide();
+        self.commands = commands;
+    }
+
+    /// Check if the modal is currently shown
+    pub fn is_visible(&self) -> bool {
+        self.item_id.is_some()
+    }
+
+    /// The task being edited, while shown
+    pub fn item_id(&self) -> Option<Uuid> {
+        self.item_id
+    }
+
+    /// Open the modal on a task, filled in with its current values
+    ///
+    /// Returns false, leaving the modal closed, if the task doesn't exist.
+    pub fn show(&mut self, id: Uuid, screen_width: f32, screen_height: f32) -> bool {
+        let Some(item) = self.commands.lock().get_item(id).cloned() else {
+            return false;
+        };
+        self.title_input.set_text(item.title());
+        self.description_input.set_text(item.description().unwrap_or_default());
+        self.status.set_selected(item.status());
+        self.priority.set_selected(item.priority());
+        let due_text = item.due_date().map(i18n::format_date).unwrap_or_default();
+        self.due_input.set_text(due_text.clone());
+        self.original_due = (item.due_date(), due_text);
+        self.recurrence.set_options(Self::recurrence_options(item.recurrence()));
+        self.recurrence.set_selected(item.recurrence());
+        self.reminder.set_options(Self::reminder_options(item.reminder()));
+        self.reminder.set_selected(item.reminder());
+        self.tags_input.set_text(item.tags().join(", "));
+        let projects = self.commands.lock().projects().iter()
+            .map(|project| (Some(project.id()), project.name().to_string()))
+            .collect::<Vec<_>>();
+        self.project.set_options(std::iter::once((None, tr("edit-no-project"))).chain(projects).collect());
+        self.project.set_selected(item.project_id());
+        self.attachment_input.set_text(String::new());
+        self.attachments = item.attachments().to_vec();
+        self.error = None;
+        self.item_id = Some(id);
+        self.layout(screen_width, screen_height);
+        self.set_focus(Field::Title);
+        true
+    }
+
+    /// Hide the modal, discarding unsaved changes
+    pub fn hide(&mut self) {
+        self.item_id = None;
+        self.error = None;
+        self.status.close();
+        self.priority.close();
+        self.recurrence.close();
+        self.reminder.close();
+        self.project.close();
+        self.due_picker.close();
+    }
+
+    /// Re-center the modal, e.g. after a window resize
+    pub fn layout(&mut self, screen_width: f32, screen_height: f32) {
+        self.screen_height = screen_height;
+        self.width = screen_width.min(560.0);
+        self.x = (screen_width - self.width) / 2.0;
+        self.y = ((screen_height - self.height) / 2.0).max(0.0);
+
+        let field_x = self.x + 20.0 + LABEL_WIDTH;
+        let field_width = self.width - LABEL_WIDTH - 40.0;
+        for field in FIELDS {
+            let field_y = self.row_y(field) + (ROW_HEIGHT - FIELD_HEIGHT) / 2.0;
+            let widget = self.field_widget_mut(field);
+            widget.set_position(field_x, field_y);
+            widget.set_dimensions(field_width, field.height());
+        }
+        self.due_input.set_dimensions(field_width - CALENDAR_BUTTON_WIDTH - 8.0, FIELD_HEIGHT);
+        if FILE_PICKER {
+            self.attachment_input.set_dimensions(field_width - CALENDAR_BUTTON_WIDTH - 8.0, FIELD_HEIGHT);
+        }
+        self.due_picker.close();
+
+        let button_y = self.y + self.height - 56.0;
+        self.cancel_button.set_position(self.x + self.width - 260.0, button_y);
+        self.save_button.set_position(self.x + self.width - 130.0, button_y);
+    }
+
+    /// Top edge of a field's row
+    fn row_y(&self, field: Field) -> f32 {
+        let above: f32 = FIELDS[..field.index()].iter().map(|field| field.row_height()).sum();
+        self.y + HEADER_HEIGHT + 20.0 + above
+    }
+
+    fn field_widget(&self, field: Field) -> &dyn Widget {
+        match field {
+            Field::Title => &self.title_input,
+            Field::Description => &self.description_input,
+            Field::Status => &self.status,
+            Field::Priority => &self.priority,
+            Field::DueDate => &self.due_input,
+            Field::Recurrence => &self.recurrence,
+            Field::Reminder => &self.reminder,
+            Field::Tags => &self.tags_input,
+            Field::Project => &self.project,
+            Field::Attachments => &self.attachment_input,
+        }
+    }
+
+    fn field_widget_mut(&mut self, field: Field) -> &mut dyn Widget {
+        match field {
+            Field::Title => &mut self.title_input,
+            Field::Description => &mut self.description_input,
+            Field::Status => &mut self.status,
+            Field::Priority => &mut self.priority,
+            Field::DueDate => &mut self.due_input,
+            Field::Recurrence => &mut self.recurrence,
+            Field::Reminder => &mut self.reminder,
+            Field::Tags => &mut self.tags_input,
+            Field::Project => &mut self.project,
+            Field::Attachments => &mut self.attachment_input,
+        }
+    }
+
+    /// The field with keyboard focus
+    fn focused_field(&self) -> Field {
+        self.focus.focused().unwrap_or(Field::Title)
+    }
+
+    /// The focused field if it takes a line of text; the description goes
+    /// through `description_focused` instead
+    fn focused_input(&mut self) -> Option<&mut TextInput> {
+        match self.focused_field() {
+            Field::Title => Some(&mut self.title_input),
+            Field::DueDate => Some(&mut self.due_input),
+            Field::Tags => Some(&mut self.tags_input),
+            Field::Attachments => Some(&mut self.attachment_input),
+            Field::Description | Field::Status | Field::Priority | Field::Recurrence | Field::Reminder | Field::Project => None,
+        }
+    }
+
+    fn description_focused(&self) -> bool {
+        self.focused_field() == Field::Description
+    }
+
+    /// Move keyboard focus to a field
+    fn set_focus(&mut self, field: Field) {
+        self.focus.set_focus(Some(field));
+        self.title_input.set_focused(field == Field::Title);
+        self.description_input.set_focused(field == Field::Description);
+        self.status.set_focused(field == Field::Status);
+        self.priority.set_focused(field == Field::Priority);
+        self.due_input.set_focused(field == Field::DueDate);
+        self.recurrence.set_focused(field == Field::Recurrence);
+        self.reminder.set_focused(field == Field::Reminder);
+        self.tags_input.set_focused(field == Field::Tags);
+        self.project.set_focused(field == Field::Project);
+        self.attachment_input.set_focused(field == Field::Attachments);
+    }
+
+    /// Move focus to the next field (Tab), or back to the previous one (Shift+Tab)
+    pub fn cycle_focus(&mut self, backward: bool) {
+        if !self.is_visible() || self.due_picker.is_open() {
+            return;
+        }
+        self.status.close();
+        self.priority.close();
+        self.recurrence.close();
+        self.reminder.close();
+        self.project.close();
+        if let Some(field) = self.focus.cycle(&FIELDS, backward) {
+            self.set_focus(field);
+        }
+    }
+
+    /// Bounds of the button next to the due date that opens the calendar
+    fn calendar_button(&self) -> (f32, f32, f32, f32) {
+        let (x, y) = self.due_input.position();
+        let (width, height) = self.due_input.dimensions();
+        (x + width + 8.0, y, CALENDAR_BUTTON_WIDTH, height)
+    }
+
+    fn calendar_button_contains(&self, x: f32, y: f32) -> bool {
+        let (button_x, button_y, width, height) = self.calendar_button();
+        x >= button_x && x <= button_x + width && y >= button_y && y <= button_y + height
+    }
+
+    /// Bounds of the button right of the attachment field that opens the file dialog
+    fn file_button(&self) -> (f32, f32, f32, f32) {
+        let (x, y) = self.attachment_input.position();
+        let (width, height) = self.attachment_input.dimensions();
+        (x + width + 8.0, y, CALENDAR_BUTTON_WIDTH, height)
+    }
+
+    fn file_button_contains(&self, x: f32, y: f32) -> bool {
+        let (button_x, button_y, width, height) = self.file_button();
+        FILE_PICKER && x >= button_x && x <= button_x + width && y >= button_y && y <= button_y + height
+    }
+
+    /// Left edge, top and width of each attachment's chip, under the attachment field
+    ///
+    /// Chips that would run past the field are left out.
+    fn attachment_chips(&self) -> Vec<(f32, f32, f32)> {
+        let (field_x, field_y) = self.attachment_input.position();
+        let right = self.x + self.width - 20.0;
+        let chip_y = field_y + FIELD_HEIGHT + (ATTACHMENT_STRIP_HEIGHT - ATTACHMENT_CHIP_HEIGHT) / 2.0 + 4.0;
+        let mut x = field_x;
+        let mut chips = Vec::new();
+        for attachment in &self.attachments {
+            let width = text_width(&Self::chip_label(attachment), ATTACHMENT_TEXT_SIZE) + 16.0;
+            if x + width > right {
+                break;
+            }
+            chips.push((x, chip_y, width));
+            x += width + 6.0;
+        }
+        chips
+    }
+
+    /// Text of an attachment's chip: its name and the × that removes it
+    fn chip_label(attachment: &Attachment) -> String {
+        format!("{} ×", attachment.name())
+    }
+
+    /// Index of the attachment whose chip is under a point
+    fn attachment_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.attachment_chips().iter().position(|&(chip_x, chip_y, width)| {
+            x >= chip_x && x <= chip_x + width && y >= chip_y && y <= chip_y + ATTACHMENT_CHIP_HEIGHT
+        })
+    }
+
+    /// Attach the link or path typed in the attachment field, returning whether there was one
+    fn add_typed_attachment(&mut self) -> bool {
+        let Some(attachment) = Attachment::parse(self.attachment_input.text()) else {
+            return false;
+        };
+        if !self.attachments.contains(&attachment) {
+            self.attachments.push(attachment);
+        }
+        self.attachment_input.set_text(String::new());
+        true
+    }
+
+    /// Attach files picked in the file dialog, if the modal is still editing task `id`
+    #[cfg(all(feature = "file-picker", not(target_arch = "wasm32")))]
+    pub fn add_files(&mut self, id: Uuid, paths: Vec<std::path::PathBuf>) {
+        if self.item_id != Some(id) {
+            return;
+        }
+        for attachment in paths.into_iter().map(Attachment::File) {
+            if !self.attachments.contains(&attachment) {
+                self.attachments.push(attachment);
+            }
+        }
+    }
+
+    /// Open the calendar under the due date, or above it if it wouldn't fit below
+    fn open_calendar(&mut self) {
+        self.set_focus(Field::DueDate);
+        let (x, y) = self.due_input.position();
+        let (_, picker_height) = DatePicker::size();
+        let below = y + FIELD_HEIGHT + 4.0;
+        let picker_y = if below + picker_height <= self.screen_height { below } else { (y - picker_height - 4.0).max(0.0) };
+        let current = i18n::parse_date(self.due_input.text()).or(self.original_due.0);
+        self.due_picker.open(x, picker_y, current);
+    }
+
+    /// Put a date chosen in the calendar into the due date field
+    fn apply_picked(&mut self, event: DatePickerEvent) {
+        let text = match event {
+            DatePickerEvent::Picked(timestamp) => i18n::format_date(timestamp),
+            DatePickerEvent::Cleared => String::new(),
+        };
+        self.due_input.set_text(text);
+        self.set_focus(Field::DueDate);
+    }
+
+    /// The field under a point, with open option lists on top of the fields below them
+    fn field_at(&self, x: f32, y: f32) -> Option<Field> {
+        if self.status.is_open() && self.status.hit(x, y) {
+            return Some(Field::Status);
+        }
+        if self.priority.is_open() && self.priority.hit(x, y) {
+            return Some(Field::Priority);
+        }
+        if self.recurrence.is_open() && self.recurrence.hit(x, y) {
+            return Some(Field::Recurrence);
+        }
+        if self.reminder.is_open() && self.reminder.hit(x, y) {
+            return Some(Field::Reminder);
+        }
+        if self.project.is_open() && self.project.hit(x, y) {
+            return Some(Field::Project);
+        }
+        FIELDS.into_iter().find(|&field| self.field_widget(field).contains_point(x, y))
+    }
+
+    /// Handle mouse movement for hover states
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        self.due_picker.handle_mouse_move(x, y);
+        self.status.handle_mouse_move(x, y);
+        self.priority.handle_mouse_move(x, y);
+        self.recurrence.handle_mouse_move(x, y);
+        self.reminder.handle_mouse_move(x, y);
+        self.project.handle_mouse_move(x, y);
+        self.save_button.handle_mouse_move(x, y);
+        self.cancel_button.handle_mouse_move(x, y);
+    }
+
+    /// Handle mouse button press, focusing the field under the pointer
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32, click_count: u32) {
+        if !self.is_visible() {
+            return;
+        }
+
+        // The open calendar lies on top of everything
+        if self.due_picker.contains_point(x, y) {
+            if let Some(event) = self.due_picker.handle_mouse_down(x, y) {
+                self.apply_picked(event);
+            }
+            return;
+        }
+        if self.calendar_button_contains(x, y) {
+            if self.due_picker.is_open() {
+                self.due_picker.close();
+            } else {
+                self.open_calendar();
+            }
+            return;
+        }
+        self.due_picker.close();
+
+        // Clicking an attachment's chip takes it off, unless an open list lies on top
+        if let (None, Some(index)) = (self.field_at(x, y), self.attachment_at(x, y)) {
+            self.attachments.remove(index);
+            return;
+        }
+
+        match self.field_at(x, y) {
+            Some(field) => {
+                if field != self.focused_field() {
+                    self.set_focus(field);
+                }
+                match field {
+                    Field::Status => {
+                        self.status.handle_mouse_down(x, y);
+                    }
+                    Field::Priority => {
+                        self.priority.handle_mouse_down(x, y);
+                    }
+                    Field::Recurrence => {
+                        self.recurrence.handle_mouse_down(x, y);
+                    }
+                    Field::Reminder => {
+                        self.reminder.handle_mouse_down(x, y);
+                    }
+                    Field::Project => {
+                        self.project.handle_mouse_down(x, y);
+                    }
+                    Field::Description => self.description_input.handle_mouse_down(x, y, click_count),
+                    _ => {
+                        if let Some(input) = self.focused_input() {
+                            input.handle_mouse_down(x, y, click_count);
+                        }
+                    }
+                }
+            }
+            None => {
+                self.status.close();
+                self.priority.close();
+                self.recurrence.close();
+                self.reminder.close();
+                self.project.close();
+                self.save_button.handle_mouse_down(x, y);
+                self.cancel_button.handle_mouse_down(x, y);
+            }
+        }
+    }
+
+    /// Handle mouse button release, returning what the click did
+    pub fn handle_mouse_up(&mut self, x: f32, y: f32) -> Option<EditTaskEvent> {
+        if !self.is_visible() {
+            return None;
+        }
+
+        let event = if self.save_button.contains_point(x, y) {
+            self.save()
+        } else if let Some(id) = self.item_id.filter(|_| self.file_button_contains(x, y)) {
+            Some(EditTaskEvent::PickFiles(id))
+        } else if self.cancel_button.contains_point(x, y) {
+            self.hide();
+            Some(EditTaskEvent::Cancelled)
+        } else {
+            None
+        };
+
+        self.save_button.handle_mouse_up(x, y);
+        self.cancel_button.handle_mouse_up(x, y);
+        event
+    }
+
+    /// Scroll the description when the pointer is over it
+    pub fn handle_mouse_wheel(&mut self, x: f32, y: f32, delta: f32) {
+        if self.is_visible() && !self.due_picker.is_open() && self.description_input.contains_point(x, y) {
+            self.description_input.handle_mouse_wheel(delta);
+        }
+    }
+
+    /// Carry out a cursor or selection action in the focused text field, returning whether it applied
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        if !self.is_visible() || self.due_picker.is_open() {
+            return false;
+        }
+        if self.description_focused() {
+            return self.description_input.handle_action(action);
+        }
+        self.focused_input().is_some_and(|input| input.handle_action(action))
+    }
+
+    /// Copy, cut or paste in the focused text field, returning whether the action applied
+    pub fn handle_clipboard(&mut self, action: Action, clipboard: &mut Clipboard) -> bool {
+        if !self.is_visible() || self.due_picker.is_open() {
+            return false;
+        }
+        if self.description_focused() {
+            return self.description_input.handle_clipboard(action, clipboard);
+        }
+        self.focused_input().is_some_and(|input| input.handle_clipboard(action, clipboard))
+    }
+
+    /// Type or compose text in the focused text field
+    pub fn handle_text(&mut self, event: &TextEvent) {
+        if !self.is_visible() || self.due_picker.is_open() {
+            return;
+        }
+        if self.description_focused() {
+            self.description_input.handle_text(event);
+        } else if let Some(input) = self.focused_input() {
+            input.handle_text(event);
+        }
+    }
+
+    /// Bounds of the focused text field's cursor, for placing an input method's candidates
+    pub fn caret_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        if !self.is_visible() || self.due_picker.is_open() {
+            return None;
+        }
+        [&self.title_input, &self.due_input, &self.tags_input, &self.attachment_input]
+            .into_iter()
+            .find_map(TextInput::caret_bounds)
+            .or_else(|| self.description_input.caret_bounds())
+    }
+
+    /// Type into the focused text field
+    pub fn handle_char_input(&mut self, c: char) {
+        if self.description_focused() {
+            self.description_input.handle_char_input(c);
+        } else if let Some(input) = self.focused_input() {
+            input.handle_char_input(c);
+        }
+    }
+
+    /// Handle keyboard input: Tab moves to the next field, Enter saves, Escape cancels
+    ///
+    /// In the description Enter starts a new line instead; `save` is bound to
+    /// Ctrl+Enter for saving from there. In the attachment field Enter attaches
+    /// what was typed, and saves once it's empty. Arrow Down on the due date
+    /// opens the calendar, which then takes the keys until a day is picked or
+    /// it's closed with Escape.
+    pub fn handle_key_press(&mut self, key: KeyCode) -> Option<EditTaskEvent> {
+        if !self.is_visible() {
+            return None;
+        }
+
+        if self.due_picker.is_open() {
+            if let Some(event) = self.due_picker.handle_key_press(key) {
+                self.apply_picked(event);
+            }
+            return None;
+        }
+        if self.focused_field() == Field::DueDate && key == KeyCode::ArrowDown {
+            self.open_calendar();
+            return None;
+        }
+
+        // Dropdowns use the arrows, and Enter and Escape while open
+        let used = match self.focused_field() {
+            Field::Status => self.status.handle_key_press(key),
+            Field::Priority => self.priority.handle_key_press(key),
+            Field::Recurrence => self.recurrence.handle_key_press(key),
+            Field::Reminder => self.reminder.handle_key_press(key),
+            Field::Project => self.project.handle_key_press(key),
+            _ => false,
+        };
+        if used {
+            return None;
+        }
+
+        match key {
+            KeyCode::Tab => {
+                self.cycle_focus(false);
+                None
+            }
+            KeyCode::Enter if self.focused_field() == Field::Attachments && self.add_typed_attachment() => None,
+            KeyCode::Enter if !self.description_focused() => self.save(),
+            KeyCode::Escape => {
+                self.hide();
+                Some(EditTaskEvent::Cancelled)
+            }
+            // Space arrives as a named key rather than a character
+            KeyCode::Space => {
+                self.handle_char_input(' ');
+                None
+            }
+            _ => {
+                if self.description_focused() {
+                    self.description_input.handle_key_press(key);
+                } else if let Some(input) = self.focused_input() {
+                    input.handle_key_press(key);
+                }
+                None
+            }
+        }
+    }
+
+    /// Write the form to the task, or keep the modal open with an error
+    pub fn save(&mut self) -> Option<EditTaskEvent> {
+        let id = self.item_id?;
+
+        let title = self.title_input.text().trim().to_string();
+        if title.is_empty() {
+            self.error = Some(tr("edit-empty-title"));
+            self.set_focus(Field::Title);
+            return None;
+        }
+
+        let due_text = self.due_input.text().trim();
+        let due_date = if due_text == self.original_due.1 {
+            self.original_due.0
+        } else if due_text.is_empty() {
+            None
+        } else if let Some(timestamp) = i18n::parse_date(due_text) {
+            Some(timestamp)
+        } else {
+            self.error = Some(tr_args("edit-invalid-date", &[("text", due_text)]));
+            self.set_focus(Field::DueDate);
+            return None;
+        };
+
+        let description = self.description_input.text().trim();
+        let description = (!description.is_empty()).then(|| description.to_string());
+        let tags = self.tags_input.text().split(',').map(str::to_string).collect();
+        // A link or path typed but not yet added goes with the rest
+        self.add_typed_attachment();
+        let attachments = self.attachments.clone();
+
+        // The status goes last, so completing a recurring task copies the edited fields
+        let edit = Command::Batch(vec![
+            Command::SetTitle { id, title },
+            Command::SetDescription { id, description },
+            Command::SetPriority { id, priority: self.priority.selected() },
+            Command::SetDueDate { id, due_date },
+            Command::SetRecurrence { id, recurrence: self.recurrence.selected() },
+            Command::SetReminder { id, reminder: self.reminder.selected() },
+            Command::SetTags { id, tags },
+            Command::SetProject { id, project_id: self.project.selected() },
+            Command::SetAttachments { id, attachments },
+            Command::SetStatus { id, status: self.status.selected() },
+        ]);
+        match self.commands.execute(edit) {
+            Ok(_) => {
+                self.hide();
+                Some(EditTaskEvent::Saved(id))
+            }
+            Err(e) => {
+                tracing::error!("Couldn't save task {}: {}", id, e);
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
+impl Widget for EditTaskModal {
+    fn update(&mut self, delta_time: f32) {
+        for input in [&mut self.title_input, &mut self.due_input, &mut self.tags_input, &mut self.attachment_input] {
+            if input.needs_update() {
+                input.update(delta_time);
+            }
+        }
+        if self.description_input.needs_update() {
+            self.description_input.update(delta_time);
+        }
+        for button in [&mut self.save_button, &mut self.cancel_button] {
+            if button.needs_update() {
+                button.update(delta_time);
+            }
+        }
+        self.focus.update(delta_time);
+    }
+
+    fn needs_update(&self) -> bool {
+        self.is_visible()
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        if self.is_visible() {
+            out.push(WidgetInfo::of(self, depth));
+            for field in FIELDS {
+                self.field_widget(field).inspect(depth + 1, out);
+            }
+            self.cancel_button.inspect(depth + 1, out);
+            self.save_button.inspect(depth + 1, out);
+        }
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        ctx.draw_rect(0.0, 0.0, ctx.width, ctx.height, self.theme.get_modal_overlay_color());
+        ctx.draw_modal_frame(&self.theme, self.x, self.y, self.width, self.height, HEADER_HEIGHT);
+
+        ctx.draw_label(
+            "edit-title",
+            self.x + 20.0, self.y + 8.0,
+            24.0,
+            self.theme.get_modal_text_color(),
+        );
+
+        for field in FIELDS {
+            let row_y = self.row_y(field);
+            ctx.draw_label(
+                field.label_id(),
+                self.x + 20.0, row_y + (ROW_HEIGHT - self.theme.text_size()) / 2.0,
+                self.theme.text_size(),
+                self.theme.get_modal_text_color(),
+            );
+
+            // Text inputs don't draw their own box
+            let widget = self.field_widget(field);
+            if matches!(field, Field::Title | Field::Description | Field::DueDate | Field::Tags | Field::Attachments) {
+                let (x, y) = widget.position();
+                let (width, height) = widget.dimensions();
+                let border = if field == self.focused_field() { self.theme.neon_pink() } else { self.theme.cyan() };
+                ctx.draw_rect(x, y, width, height, self.theme.background());
+                ctx.draw_outline(x, y, width, height, 1.0, border);
+            }
+            widget.render(ctx);
+        }
+
+        let (x, y) = self.field_widget(self.focused_field()).position();
+        let (width, height) = self.field_widget(self.focused_field()).dimensions();
+        self.focus.draw_ring(ctx, x, y, width, height);
+
+        let (button_x, button_y, button_width, button_height) = self.calendar_button();
+        ctx.draw_rect(button_x, button_y, button_width, button_height, self.theme.filter_button_bg());
+        ctx.draw_outline(button_x, button_y, button_width, button_height, 1.0, self.theme.cyan());
+        ctx.draw_text(
+            "📅",
+            button_x + 10.0, button_y + (button_height - self.theme.text_size()) / 2.0,
+            self.theme.text_size(),
+            self.theme.cyan(),
+        );
+
+        if FILE_PICKER {
+            let (button_x, button_y, button_width, button_height) = self.file_button();
+            ctx.draw_rect(button_x, button_y, button_width, button_height, self.theme.filter_button_bg());
+            ctx.draw_outline(button_x, button_y, button_width, button_height, 1.0, self.theme.cyan());
+            ctx.draw_text(
+                "📎",
+                button_x + 10.0, button_y + (button_height - self.theme.text_size()) / 2.0,
+                self.theme.text_size(),
+                self.theme.cyan(),
+            );
+        }
+
+        for ((chip_x, chip_y, chip_width), attachment) in self.attachment_chips().into_iter().zip(&self.attachments) {
+            ctx.draw_rect(chip_x, chip_y, chip_width, ATTACHMENT_CHIP_HEIGHT, self.theme.filter_button_bg());
+            ctx.draw_text(
+                &Self::chip_label(attachment),
+                chip_x + 8.0, chip_y + (ATTACHMENT_CHIP_HEIGHT - ATTACHMENT_TEXT_SIZE) / 2.0,
+                ATTACHMENT_TEXT_SIZE,
+                self.theme.link(),
+            );
+        }
+
+        if let Some(error) = &self.error {
+            ctx.draw_text(
+                error,
+                self.x + 20.0, self.y + self.height - FOOTER_HEIGHT - ERROR_HEIGHT + 2.0,
+                self.theme.small_text_size(),
+                self.theme.danger(),
+            );
+        }
+
+        self.cancel_button.render(ctx);
+        self.save_button.render(ctx);
+
+        // Open lists cover the fields below them
+        self.status.render_options(ctx);
+        self.priority.render_options(ctx);
+        self.recurrence.render_options(ctx);
+        self.reminder.render_options(ctx);
+        self.project.render_options(ctx);
+        self.due_picker.render(ctx);
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        let dx = x - self.x;
+        let dy = y - self.y;
+        self.x = x;
+        self.y = y;
+
+        for field in FIELDS {
+            let widget = self.field_widget_mut(field);
+            let (field_x, field_y) = widget.position();
+            widget.set_position(field_x + dx, field_y + dy);
+        }
+        for button in [&mut self.save_button, &mut self.cancel_button] {
+            let (button_x, button_y) = button.position();
+            button.set_position(button_x + dx, button_y + dy);
+        }
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if !self.is_visible() {
+            return None;
+        }
+        if self.due_picker.contains_point(x, y)
+            || self.calendar_button_contains(x, y)
+            || self.file_button_contains(x, y)
+            || self.attachment_at(x, y).is_some()
+        {
+            return Some(CursorIcon::Pointer);
+        }
+        self.field_at(x, y)
+            .and_then(|field| self.field_widget(field).cursor_at(x, y))
+            .or_else(|| self.save_button.cursor_at(x, y))
+            .or_else(|| self.cancel_button.cursor_at(x, y))
+            .or(Some(CursorIcon::Default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::{TodoItem, TodoList};
+    use crate::ui::testing::{UiDriver, VIEWPORT};
+    use crate::ui::UiEvent;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_edit_modal_takes_a_multi_line_description() {
+        let mut todo_list = TodoList::new("Test");
+        let id = todo_list.create_item("Draft");
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(todo_list)));
+        let mut modal = EditTaskModal::new(dispatcher.clone());
+        let (width, height) = VIEWPORT;
+        assert!(modal.show(id, width, height));
+
+        // Enter starts a new line in the description instead of saving
+        modal.handle_key_press(KeyCode::Tab);
+        "First".chars().for_each(|c| modal.handle_char_input(c));
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), None);
+        assert!(modal.is_visible());
+        modal.handle_text(&TextEvent::Insert("second line".to_string()));
+        assert_eq!(modal.save(), Some(EditTaskEvent::Saved(id)));
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().description(), Some("First\nsecond line"));
+
+        // Loaded again with its lines, and Enter saves from the other fields
+        assert!(modal.show(id, width, height));
+        modal.handle_key_press(KeyCode::Tab);
+        modal.handle_key_press(KeyCode::ArrowUp);
+        modal.handle_key_press(KeyCode::End);
+        modal.handle_char_input('!');
+        modal.cycle_focus(true);
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), Some(EditTaskEvent::Saved(id)));
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().description(), Some("First!\nsecond line"));
+    }
+
+    #[test]
+    fn test_completing_a_recurring_task_in_the_editor() {
+        let mut todo_list = TodoList::new("Test");
+        let id = todo_list.add_item(TodoItem::new("Review").with_recurrence(RecurrenceRule::EveryNDays(10)));
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(todo_list)));
+        let mut modal = EditTaskModal::new(dispatcher.clone());
+        let (width, height) = VIEWPORT;
+
+        // An interval that isn't one of the usual ones is offered as it is
+        assert!(modal.show(id, width, height));
+        modal.handle_key_press(KeyCode::Tab);
+        modal.handle_key_press(KeyCode::Tab);
+        modal.handle_key_press(KeyCode::ArrowDown);
+        modal.handle_key_press(KeyCode::ArrowDown);
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), Some(EditTaskEvent::Saved(id)));
+
+        let list = dispatcher.lock();
+        let next = list.all_items().into_iter().find(|item| item.id() != id).expect("the next occurrence");
+        assert_eq!(list.get_item(id).unwrap().status(), Status::Completed);
+        assert_eq!(list.get_item(id).unwrap().recurrence(), Some(RecurrenceRule::EveryNDays(10)));
+        assert_eq!(next.title(), "Review");
+        assert_eq!(next.status(), Status::NotStarted);
+        assert_eq!(next.recurrence(), Some(RecurrenceRule::EveryNDays(10)));
+    }
+
+    #[test]
+    fn test_edit_modal_picks_a_due_date() {
+        use chrono::NaiveDate;
+        use crate::i18n::local_midnight;
+        let day = |month, day| local_midnight(NaiveDate::from_ymd_opt(2025, month, day).unwrap());
+
+        let mut todo_list = TodoList::new("Test");
+        let id = todo_list.add_item(TodoItem::new("Report").with_due_date(day(2, 10).unwrap()));
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(todo_list)));
+        let mut modal = EditTaskModal::new(dispatcher.clone());
+        let (width, height) = VIEWPORT;
+
+        // Arrow Down on the due date opens the calendar on that day
+        let open_calendar = |modal: &mut EditTaskModal| {
+            assert!(modal.show(id, width, height));
+            for _ in 0..4 {
+                modal.handle_key_press(KeyCode::Tab);
+            }
+            modal.handle_key_press(KeyCode::ArrowDown);
+        };
+        open_calendar(&mut modal);
+        modal.handle_key_press(KeyCode::ArrowRight);
+        modal.handle_key_press(KeyCode::PageDown);
+        modal.handle_key_press(KeyCode::Enter);
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), Some(EditTaskEvent::Saved(id)));
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().due_date(), day(3, 11));
+
+        // Delete in the calendar removes the date
+        open_calendar(&mut modal);
+        modal.handle_key_press(KeyCode::Delete);
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), Some(EditTaskEvent::Saved(id)));
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().due_date(), None);
+    }
+
+    #[test]
+    fn test_edit_modal_saves_every_field() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Draft", "Other"]);
+        let mut modal = EditTaskModal::new(Dispatcher::new(driver.todo_list().clone()));
+        let (width, height) = VIEWPORT;
+
+        driver.click_edit(ids[0]);
+        let Some(UiEvent::EditRequested { id }) = driver.take_events().pop() else {
+            panic!("the edit button should request an edit");
+        };
+        assert!(modal.show(id, width, height));
+
+        // The title is focused with its cursor at the end
+        for _ in 0.."Draft".len() {
+            modal.handle_key_press(KeyCode::Backspace);
+        }
+        "Final".chars().for_each(|c| modal.handle_char_input(c));
+        modal.handle_key_press(KeyCode::Tab);
+        "Two".chars().for_each(|c| modal.handle_char_input(c));
+        modal.handle_key_press(KeyCode::Space);
+        "words".chars().for_each(|c| modal.handle_char_input(c));
+
+        // Status, then priority, step through their options with the arrows
+        modal.handle_key_press(KeyCode::Tab);
+        modal.handle_key_press(KeyCode::ArrowDown);
+        modal.handle_key_press(KeyCode::Tab);
+        modal.handle_key_press(KeyCode::ArrowDown);
+        modal.handle_key_press(KeyCode::Tab);
+
+        // A date that doesn't parse keeps the modal open
+        "soon".chars().for_each(|c| modal.handle_char_input(c));
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), None);
+        assert!(modal.is_visible());
+        for _ in 0.."soon".len() {
+            modal.handle_key_press(KeyCode::Backspace);
+        }
+        let due = crate::i18n::format_date(1_750_000_000);
+        due.chars().for_each(|c| modal.handle_char_input(c));
+        modal.handle_key_press(KeyCode::Tab);
+        modal.handle_key_press(KeyCode::ArrowDown);
+        modal.handle_key_press(KeyCode::Tab);
+        modal.handle_key_press(KeyCode::ArrowDown);
+        modal.handle_key_press(KeyCode::ArrowDown);
+        modal.handle_key_press(KeyCode::Tab);
+        "work,".chars().for_each(|c| modal.handle_char_input(c));
+        modal.handle_key_press(KeyCode::Space);
+        "#errands".chars().for_each(|c| modal.handle_char_input(c));
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), Some(EditTaskEvent::Saved(ids[0])));
+        assert!(!modal.is_visible());
+
+        let task = driver.task(ids[0]).unwrap();
+        assert_eq!(task.title(), "Final");
+        assert_eq!(task.description(), Some("Two words"));
+        assert_eq!(task.status(), Status::InProgress);
+        assert_eq!(task.priority(), Priority::High);
+        assert_eq!(task.due_date(), crate::i18n::parse_date(&due));
+        assert_eq!(task.recurrence(), Some(RecurrenceRule::Daily));
+        assert_eq!(task.reminder(), Reminder::MinutesBefore(5));
+        assert_eq!(task.tags(), ["work", "errands"]);
+        assert_eq!(driver.task(ids[1]).unwrap().title(), "Other");
+
+        // Cancelling leaves the task alone
+        assert!(modal.show(ids[0], width, height));
+        modal.handle_key_press(KeyCode::Backspace);
+        assert_eq!(modal.handle_key_press(KeyCode::Escape), Some(EditTaskEvent::Cancelled));
+        assert_eq!(driver.task(ids[0]).unwrap().title(), "Final");
+    }
+
+    #[test]
+    fn test_attaching_a_link_and_opening_it() {
+        use crate::core::prelude::Attachment;
+        use crate::ui::{EditTaskEvent, EditTaskModal};
+        let (mut driver, ids) = UiDriver::with_tasks(&["Report"]);
+        let mut modal = EditTaskModal::new(Dispatcher::new(driver.todo_list().clone()));
+        let (width, height) = VIEWPORT;
+        assert!(modal.show(ids[0], width, height));
+
+        // Enter in the attachments field adds what was typed, then saves once it's empty
+        for _ in 0..9 {
+            modal.handle_key_press(KeyCode::Tab);
+        }
+        "https://example.com/spec".chars().for_each(|c| modal.handle_char_input(c));
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), None);
+        assert!(modal.is_visible());
+        "notes/report.md".chars().for_each(|c| modal.handle_char_input(c));
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), None);
+        assert_eq!(modal.handle_key_press(KeyCode::Enter), Some(EditTaskEvent::Saved(ids[0])));
+        let task = driver.task(ids[0]).unwrap();
+        assert_eq!(task.attachments(), [
+            Attachment::Url("https://example.com/spec".to_string()),
+            Attachment::File("notes/report.md".into()),
+        ]);
+
+        let (x, y) = driver.widget().item_widget(ids[0]).unwrap().position();
+        driver.double_click_at(x + 200.0, y + 10.0);
+        driver.tick(0.016);
+        driver.take_events();
+
+        // The names follow the "Attachments:" label, below the status, priority and two times
+        let (names_x, row_y) = (212.0 + 20.0 + 108.0 + 10.0, 184.0 + 60.0 + 4.0 * 30.0);
+        driver.click_at(names_x + 10.0, row_y + 5.0);
+        assert_eq!(driver.take_events(), vec![UiEvent::AttachmentClicked { id: ids[0], index: 0 }]);
+        // The address shows without its scheme, then comes a 16 pixel gap
+        driver.click_at(names_x + "example.com/spec".len() as f32 * 8.0 + 16.0 + 10.0, row_y + 5.0);
+        assert_eq!(driver.take_events(), vec![UiEvent::AttachmentClicked { id: ids[0], index: 1 }]);
+        assert!(driver.widget().item_widget(ids[0]).unwrap().is_expanded());
+    }
+}
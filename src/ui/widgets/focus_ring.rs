@@ -0,0 +1,189 @@
+use crate::i18n::tr;
+use crate::pomodoro::{self, FocusTimer, Phase};
+use crate::ui::{RenderContext, TextOverflow, Widget, CyberpunkTheme};
+use winit::window::CursorIcon;
+
+const WIDTH: f32 = 280.0;
+const HEIGHT: f32 = 72.0;
+const RING_RADIUS: f32 = 28.0;
+const RING_THICKNESS: f32 = 4.0;
+/// Space between the ring and the task's title
+const GAP: f32 = 14.0;
+
+/// What the ring shows of the timer, so a new frame is only needed when it changes
+#[derive(Debug, Clone, PartialEq)]
+struct Shown {
+    title: String,
+    phase: Phase,
+    paused: bool,
+    remaining: String,
+    fraction_left: f32,
+}
+
+/// The running focus session or break in the header: a ring emptying as the
+/// countdown runs, the time left in it and the task beside it
+///
+/// The owner hands it the timer with `set_timer` as time goes by; clicking the
+/// ring asks to pause or resume.
+pub struct FocusRing {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    shown: Option<Shown>,
+    theme: CyberpunkTheme,
+}
+
+impl FocusRing {
+    pub fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: WIDTH,
+            height: HEIGHT,
+            shown: None,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Width and height the ring takes in the header
+    pub fn size() -> (f32, f32) {
+        (WIDTH, HEIGHT)
+    }
+
+    /// Check if a session or break is shown
+    pub fn is_visible(&self) -> bool {
+        self.shown.is_some()
+    }
+
+    /// Show the timer as it stands at `now`, or hide the ring without one
+    ///
+    /// Returns whether what's shown changed other than the ring's sweep, i.e.
+    /// the countdown moved on to another second or the timer started, stopped
+    /// or was paused.
+    pub fn set_timer(&mut self, timer: Option<&FocusTimer>, now: f64) -> bool {
+        let shown = timer.map(|timer| Shown {
+            title: timer.title().to_string(),
+            phase: timer.phase(),
+            paused: timer.is_paused(),
+            remaining: pomodoro::format_remaining(timer.remaining(now)),
+            fraction_left: timer.fraction_left(now),
+        });
+        let changed = match (&self.shown, &shown) {
+            (Some(old), Some(new)) => {
+                (&old.title, old.phase, old.paused, &old.remaining) != (&new.title, new.phase, new.paused, &new.remaining)
+            }
+            (old, new) => old.is_some() != new.is_some(),
+        };
+        self.shown = shown;
+        changed
+    }
+
+    fn ring_center(&self) -> (f32, f32) {
+        (self.x + RING_RADIUS + RING_THICKNESS, self.y + self.height / 2.0)
+    }
+
+    /// Handle mouse button release, returning whether the click asks to pause or resume
+    pub fn handle_mouse_up(&self, x: f32, y: f32) -> bool {
+        self.is_visible() && self.contains_point(x, y)
+    }
+}
+
+impl Default for FocusRing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for FocusRing {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let Some(shown) = &self.shown else {
+            return;
+        };
+
+        let color = match (shown.paused, shown.phase) {
+            (true, _) => self.theme.muted_text(),
+            (false, Phase::Focus) => self.theme.neon_pink(),
+            (false, Phase::Break) => self.theme.cyan(),
+        };
+        let center = self.ring_center();
+        ctx.draw_arc(center, RING_RADIUS, (0.0, std::f32::consts::TAU), RING_THICKNESS, self.theme.border());
+        // The countdown's share left, clockwise from twelve o'clock
+        let sweep = std::f32::consts::TAU * shown.fraction_left;
+        if sweep > 0.0 {
+            ctx.draw_arc(center, RING_RADIUS, (-std::f32::consts::FRAC_PI_2, sweep), RING_THICKNESS, color);
+        }
+
+        let time_size = 18.0;
+        let time_width = ctx.measure_text(&shown.remaining, time_size).width;
+        ctx.draw_text(&shown.remaining, center.0 - time_width / 2.0, center.1 - time_size / 2.0, time_size, self.theme.bright_text());
+
+        let text_size = self.theme.text_size();
+        let text_x = center.0 + RING_RADIUS + GAP;
+        let text_width = (self.x + self.width - text_x).max(0.0);
+        let state = match (shown.paused, shown.phase) {
+            (true, _) => "focus-paused",
+            (false, Phase::Focus) => "focus-session",
+            (false, Phase::Break) => "focus-break",
+        };
+        ctx.draw_text_bounded(&tr(state), (text_x, center.1 - text_size - 2.0), text_width, text_size * 0.85, color, TextOverflow::Ellipsis);
+        ctx.draw_text_bounded(&shown.title, (text_x, center.1 + 2.0), text_width, text_size, self.theme.bright_text(), TextOverflow::Ellipsis);
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        (self.is_visible() && self.contains_point(x, y)).then_some(CursorIcon::Pointer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_focus_ring_follows_the_timer() {
+        let mut ring = FocusRing::new();
+        ring.set_position(700.0, 14.0);
+        assert!(!ring.set_timer(None, 0.0));
+        assert!(!ring.handle_mouse_up(720.0, 50.0));
+
+        // A new frame is wanted when another second is shown, not in between
+        let mut timer = FocusTimer::start(Uuid::new_v4(), "Write report", 25, 1000.0);
+        assert!(ring.set_timer(Some(&timer), 1000.0));
+        assert!(!ring.set_timer(Some(&timer), 1000.4));
+        assert!(ring.set_timer(Some(&timer), 1001.2));
+        timer.pause(1001.5);
+        assert!(ring.set_timer(Some(&timer), 1001.5));
+        assert!(!ring.set_timer(Some(&timer), 1100.0));
+
+        assert!(ring.handle_mouse_up(720.0, 50.0));
+        assert_eq!(ring.cursor_at(720.0, 50.0), Some(CursorIcon::Pointer));
+        assert!(!ring.handle_mouse_up(600.0, 50.0));
+        assert!(ring.set_timer(None, 1100.0));
+        assert_eq!(ring.cursor_at(720.0, 50.0), None);
+    }
+}
@@ -0,0 +1,311 @@
+use crate::config::EffectsConfig;
+use crate::i18n::{self, tr};
+use crate::ui::{damage, RenderContext, Widget, WidgetInfo, CyberpunkTheme};
+use super::Slider;
+use winit::window::CursorIcon;
+
+/// Width of the panel while it shows its sliders
+pub const EXPANDED_WIDTH: f32 = 280.0;
+/// Width of the panel folded down to its header
+pub const COLLAPSED_WIDTH: f32 = 64.0;
+
+const HEADER_HEIGHT: f32 = 40.0;
+const ROW_HEIGHT: f32 = 48.0;
+const PADDING: f32 = 16.0;
+const SLIDER_HEIGHT: f32 = 14.0;
+const SWATCH_SIZE: f32 = 14.0;
+
+/// What a click or drag on the panel did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FxEvent {
+    /// A slider moved and the effects it edits were changed
+    Changed,
+    /// The panel was collapsed (true) or expanded (false); the layout around it changes
+    Toggled(bool),
+}
+
+/// An effect setting with a slider of its own
+#[derive(Debug, Clone, Copy)]
+enum FxSetting {
+    BloomThreshold,
+    BloomIntensity,
+    BloomSaturation,
+    GlowHue,
+    GlowSize,
+}
+
+const SETTINGS: [FxSetting; 5] = [
+    FxSetting::BloomThreshold,
+    FxSetting::BloomIntensity,
+    FxSetting::BloomSaturation,
+    FxSetting::GlowHue,
+    FxSetting::GlowSize,
+];
+
+impl FxSetting {
+    /// Message id of the slider's label
+    fn label_id(self) -> &'static str {
+        match self {
+            FxSetting::BloomThreshold => "fx-bloom-threshold",
+            FxSetting::BloomIntensity => "fx-bloom-intensity",
+            FxSetting::BloomSaturation => "fx-bloom-saturation",
+            FxSetting::GlowHue => "fx-glow-color",
+            FxSetting::GlowSize => "fx-glow-size",
+        }
+    }
+
+    /// Minimum, maximum and step of the slider; the maximums are those the config accepts
+    fn range(self) -> (f32, f32, f32) {
+        match self {
+            FxSetting::BloomThreshold => (0.0, 1.0, 0.05),
+            FxSetting::BloomIntensity | FxSetting::BloomSaturation => (0.0, 4.0, 0.05),
+            FxSetting::GlowHue => (0.0, 360.0, 1.0),
+            FxSetting::GlowSize => (0.0, 64.0, 0.5),
+        }
+    }
+
+    /// Decimals the value is shown with
+    fn decimals(self) -> usize {
+        match self {
+            FxSetting::GlowHue => 0,
+            FxSetting::GlowSize => 1,
+            _ => 2,
+        }
+    }
+
+    fn get(self, effects: &EffectsConfig) -> f32 {
+        match self {
+            FxSetting::BloomThreshold => effects.bloom_threshold,
+            FxSetting::BloomIntensity => effects.bloom_intensity,
+            FxSetting::BloomSaturation => effects.bloom_saturation,
+            FxSetting::GlowHue => effects.glow_hue,
+            FxSetting::GlowSize => effects.glow_size,
+        }
+    }
+
+    fn set(self, effects: &mut EffectsConfig, value: f32) {
+        match self {
+            FxSetting::BloomThreshold => effects.bloom_threshold = value,
+            FxSetting::BloomIntensity => effects.bloom_intensity = value,
+            FxSetting::BloomSaturation => effects.bloom_saturation = value,
+            FxSetting::GlowHue => effects.glow_hue = value,
+            FxSetting::GlowSize => effects.glow_size = value,
+        }
+    }
+}
+
+/// Collapsible column of sliders for the bloom and glow settings
+///
+/// The panel doesn't own the settings: the owner loads them with `load` and
+/// passes them to the mouse handlers, which write the one a slider changed.
+/// Collapsed, it is just the header that expands it again.
+pub struct FxPanel {
+    x: f32,
+    y: f32,
+    height: f32,
+    collapsed: bool,
+    sliders: Vec<Slider>,
+    header_hovered: bool,
+    theme: CyberpunkTheme,
+}
+
+impl FxPanel {
+    /// Create a collapsed panel with the default settings
+    pub fn new(x: f32, y: f32, height: f32) -> Self {
+        let sliders = SETTINGS
+            .iter()
+            .map(|setting| {
+                let (min, max, step) = setting.range();
+                Slider::new(0.0, 0.0, EXPANDED_WIDTH - 2.0 * PADDING, SLIDER_HEIGHT, min, max, step)
+            })
+            .collect();
+
+        let mut panel = Self {
+            x,
+            y,
+            height,
+            collapsed: true,
+            sliders,
+            header_hovered: false,
+            theme: CyberpunkTheme::new(),
+        };
+        panel.load(&EffectsConfig::default());
+        panel.place_sliders();
+        panel
+    }
+
+    /// Show these settings on the sliders
+    pub fn load(&mut self, effects: &EffectsConfig) {
+        for (slider, setting) in self.sliders.iter_mut().zip(SETTINGS) {
+            slider.set_value(setting.get(effects));
+        }
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+        self.header_hovered = false;
+        for slider in &mut self.sliders {
+            slider.handle_mouse_up();
+        }
+    }
+
+    /// Width taken up in the current state
+    pub fn width(&self) -> f32 {
+        if self.collapsed { COLLAPSED_WIDTH } else { EXPANDED_WIDTH }
+    }
+
+    /// Top edge of the row of the setting at an index
+    fn row_y(&self, index: usize) -> f32 {
+        self.y + HEADER_HEIGHT + ROW_HEIGHT * index as f32
+    }
+
+    fn place_sliders(&mut self) {
+        for index in 0..self.sliders.len() {
+            let slider_y = self.row_y(index) + ROW_HEIGHT - SLIDER_HEIGHT - 8.0;
+            self.sliders[index].set_position(self.x + PADDING, slider_y);
+        }
+    }
+
+    fn header_contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.x + self.width() && y >= self.y && y < self.y + HEADER_HEIGHT
+    }
+
+    /// Drag the slider that was grabbed, writing its setting
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32, effects: &mut EffectsConfig) -> Option<FxEvent> {
+        let hovered = self.header_contains(x, y);
+        damage::set(&mut self.header_hovered, hovered);
+        if self.collapsed {
+            return None;
+        }
+        let mut changed = false;
+        for (slider, setting) in self.sliders.iter_mut().zip(SETTINGS) {
+            if slider.handle_mouse_move(x, y) {
+                setting.set(effects, slider.value());
+                changed = true;
+            }
+        }
+        changed.then_some(FxEvent::Changed)
+    }
+
+    /// Toggle on a click on the header, grab a slider on a press over it
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32, effects: &mut EffectsConfig) -> Option<FxEvent> {
+        if self.header_contains(x, y) {
+            self.set_collapsed(!self.collapsed);
+            return Some(FxEvent::Toggled(self.collapsed));
+        }
+        if self.collapsed {
+            return None;
+        }
+        for (slider, setting) in self.sliders.iter_mut().zip(SETTINGS) {
+            if slider.handle_mouse_down(x, y) {
+                setting.set(effects, slider.value());
+                return Some(FxEvent::Changed);
+            }
+        }
+        None
+    }
+
+    /// Let go of the slider being dragged
+    pub fn handle_mouse_up(&mut self) {
+        for slider in &mut self.sliders {
+            slider.handle_mouse_up();
+        }
+    }
+
+    /// Whether a slider is being dragged
+    pub fn is_dragging(&self) -> bool {
+        self.sliders.iter().any(Slider::is_dragging)
+    }
+}
+
+impl Widget for FxPanel {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth));
+        if !self.collapsed {
+            for slider in &self.sliders {
+                slider.inspect(depth + 1, out);
+            }
+        }
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let width = self.width();
+        ctx.draw_rect(self.x, self.y, width, self.height, self.theme.panel_background());
+        ctx.draw_line(self.x, self.y, self.x, self.y + self.height, 1.0, self.theme.border());
+
+        let text_size = self.theme.text_size();
+        if self.header_hovered {
+            ctx.draw_rect(self.x, self.y, width, HEADER_HEIGHT, self.theme.item_hover_bg());
+        }
+        let arrow = if self.collapsed { "»" } else { "«" };
+        ctx.draw_text(
+            &format!("{} {}", tr("fx-title"), arrow),
+            self.x + PADDING, self.y + (HEADER_HEIGHT - text_size) / 2.0,
+            text_size,
+            self.theme.neon_pink(),
+        );
+        if self.collapsed {
+            return;
+        }
+
+        for (index, (slider, setting)) in self.sliders.iter().zip(SETTINGS).enumerate() {
+            let row_y = self.row_y(index);
+            ctx.draw_label(
+                setting.label_id(),
+                self.x + PADDING, row_y + 4.0,
+                text_size,
+                self.theme.get_modal_text_color(),
+            );
+
+            let value = i18n::format_number(slider.value() as f64, setting.decimals());
+            let mut value_x = self.x + width - PADDING - ctx.measure_text(&value, text_size).width;
+            ctx.draw_text(&value, value_x, row_y + 4.0, text_size, self.theme.cyan());
+            if let FxSetting::GlowHue = setting {
+                let glow = EffectsConfig { glow_hue: slider.value(), ..EffectsConfig::default() };
+                value_x -= SWATCH_SIZE + 8.0;
+                ctx.draw_rect(value_x, row_y + 6.0, SWATCH_SIZE, SWATCH_SIZE, glow.glow_color());
+            }
+
+            slider.render(ctx);
+        }
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width(), self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+        self.place_sliders();
+    }
+
+    /// The width follows the collapsed state; only the height is taken
+    fn set_dimensions(&mut self, _width: f32, height: f32) {
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if self.header_contains(x, y) {
+            return Some(CursorIcon::Pointer);
+        }
+        if self.collapsed {
+            return None;
+        }
+        self.sliders.iter().find_map(|slider| slider.cursor_at(x, y))
+    }
+}
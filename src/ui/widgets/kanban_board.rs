@@ -0,0 +1,434 @@
+use uuid::Uuid;
+use crate::core::prelude::{Status, TodoList};
+use crate::i18n::{self, tr};
+use crate::ui::{damage, PointerState, RenderContext, TextOverflow, Widget, CyberpunkTheme, Quad};
+use crate::ui::text_metrics::text_width;
+use winit::event::MouseButton;
+use winit::window::CursorIcon;
+
+const HEADER_HEIGHT: f32 = 40.0;
+const CARD_HEIGHT: f32 = 56.0;
+const CARD_GAP: f32 = 8.0;
+/// Space between the columns
+const COLUMN_GAP: f32 = 16.0;
+/// Space around the cards inside a column
+const PADDING: f32 = 10.0;
+/// Width of the accent stripe down a card's left edge
+const STRIPE_WIDTH: f32 = 4.0;
+
+/// The statuses the columns hold, left to right
+const COLUMNS: [Status; 3] = [Status::NotStarted, Status::InProgress, Status::Completed];
+
+/// What a click or drag on the board asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardEvent {
+    /// A card was dropped on another column, so its task takes that column's status
+    Moved { id: Uuid, status: Status },
+    /// A card was clicked without dragging it
+    Opened(Uuid),
+}
+
+/// A task as its card shows it
+struct Card {
+    id: Uuid,
+    title: String,
+    accent: [f32; 4],
+    due: Option<String>,
+    overdue: bool,
+}
+
+/// The card under the pointer since the button went down on it
+struct Held {
+    id: Uuid,
+    column: usize,
+    /// Pointer position from the card's top left corner, kept while dragging
+    grab: (f32, f32),
+    pointer: (f32, f32),
+}
+
+/// The list laid out as a column per status, with cards dragged between them
+///
+/// Subtasks get cards of their own, in the list's sort order. The board only
+/// shows a copy of the list; its owner makes the changes it asks for and
+/// hands it the list again with `set_list`.
+pub struct KanbanBoard {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    columns: [Vec<Card>; 3],
+    /// Pixels each column is scrolled down by
+    scroll: [f32; 3],
+    pointer: PointerState,
+    held: Option<Held>,
+    hovered: Option<Uuid>,
+    theme: CyberpunkTheme,
+}
+
+impl KanbanBoard {
+    pub fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 0.0,
+            columns: Default::default(),
+            scroll: [0.0; 3],
+            pointer: PointerState::new(),
+            held: None,
+            hovered: None,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Show the list as it is now, only the tasks in `project` if one is given
+    ///
+    /// Cards take their project's accent color, or their priority's without one.
+    pub fn set_list(&mut self, list: &TodoList, project: Option<Uuid>) {
+        let mut columns: [Vec<Card>; 3] = Default::default();
+        for (item, _) in list.sorted_view() {
+            if project.is_some() && item.project_id() != project {
+                continue;
+            }
+            let accent = item.project_id()
+                .and_then(|id| list.project(id))
+                .map(|project| self.theme.project_accent(project.color()))
+                .unwrap_or_else(|| self.theme.priority_accent(item.priority()));
+            columns[Self::column_of(item.status())].push(Card {
+                id: item.id(),
+                title: item.title().to_string(),
+                accent,
+                due: item.due_date().map(i18n::format_date),
+                overdue: item.is_overdue(),
+            });
+        }
+        self.columns = columns;
+        for column in 0..COLUMNS.len() {
+            self.scroll[column] = self.scroll[column].min(self.max_scroll(column));
+        }
+
+        // A card deleted while held can't be dropped anywhere
+        if self.held.as_ref().is_some_and(|held| self.position_of(held.id).is_none()) {
+            self.cancel_drag();
+        }
+    }
+
+    fn column_of(status: Status) -> usize {
+        COLUMNS.iter().position(|&column| column == status).unwrap_or(0)
+    }
+
+    /// Column and index of a task's card
+    fn position_of(&self, id: Uuid) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(column, cards)| {
+            cards.iter().position(|card| card.id == id).map(|index| (column, index))
+        })
+    }
+
+    fn column_width(&self) -> f32 {
+        ((self.width - COLUMN_GAP * (COLUMNS.len() - 1) as f32) / COLUMNS.len() as f32).max(0.0)
+    }
+
+    fn column_x(&self, column: usize) -> f32 {
+        self.x + column as f32 * (self.column_width() + COLUMN_GAP)
+    }
+
+    fn column_at(&self, x: f32, y: f32) -> Option<usize> {
+        if !self.contains_point(x, y) {
+            return None;
+        }
+        (0..COLUMNS.len()).find(|&column| {
+            let column_x = self.column_x(column);
+            x >= column_x && x <= column_x + self.column_width()
+        })
+    }
+
+    /// Top left corner of a card, scrolled with its column
+    fn card_position(&self, column: usize, index: usize) -> (f32, f32) {
+        (
+            self.column_x(column) + PADDING,
+            self.y + HEADER_HEIGHT + PADDING + index as f32 * (CARD_HEIGHT + CARD_GAP) - self.scroll[column],
+        )
+    }
+
+    fn card_width(&self) -> f32 {
+        (self.column_width() - PADDING * 2.0).max(0.0)
+    }
+
+    /// Column and index of the card under a point
+    fn card_at(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let column = self.column_at(x, y)?;
+        if y < self.y + HEADER_HEIGHT {
+            return None;
+        }
+        let (card_x, first_y) = self.card_position(column, 0);
+        if x < card_x || x > card_x + self.card_width() || y < first_y {
+            return None;
+        }
+        let stride = CARD_HEIGHT + CARD_GAP;
+        let index = ((y - first_y) / stride) as usize;
+        let inside = y - first_y - index as f32 * stride <= CARD_HEIGHT;
+        (inside && index < self.columns[column].len()).then_some((column, index))
+    }
+
+    fn max_scroll(&self, column: usize) -> f32 {
+        let cards = self.columns[column].len() as f32;
+        let content = PADDING * 2.0 + cards * (CARD_HEIGHT + CARD_GAP) - CARD_GAP;
+        (content - (self.height - HEADER_HEIGHT)).max(0.0)
+    }
+
+    /// Whether a card is being dragged
+    pub fn is_dragging(&self) -> bool {
+        self.held.is_some() && self.pointer.is_dragging(MouseButton::Left)
+    }
+
+    /// Drop the card being dragged back where it was, e.g. when the window loses focus
+    pub fn cancel_drag(&mut self) {
+        self.pointer.reset();
+        self.held = None;
+    }
+
+    /// Column the dragged card would land in if dropped now, other than its own
+    fn drop_column(&self) -> Option<usize> {
+        let held = self.held.as_ref().filter(|_| self.is_dragging())?;
+        self.column_at(held.pointer.0, held.pointer.1).filter(|&column| column != held.column)
+    }
+
+    /// Scroll the column under the pointer by a number of pixels
+    pub fn handle_mouse_wheel(&mut self, x: f32, y: f32, delta: f32) {
+        if let Some(column) = self.column_at(x, y) {
+            self.scroll[column] = (self.scroll[column] + delta).clamp(0.0, self.max_scroll(column));
+        }
+    }
+
+    /// Handle mouse movement for hover states and dragging
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        self.pointer.move_to(x, y);
+        if let Some(held) = self.held.as_mut() {
+            held.pointer = (x, y);
+        }
+        let hovered = self.card_at(x, y).map(|(column, index)| self.columns[column][index].id);
+        damage::set(&mut self.hovered, hovered);
+    }
+
+    /// Handle mouse button press, returning whether it picked up a card
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        let Some((column, index)) = self.card_at(x, y) else {
+            return false;
+        };
+        let (card_x, card_y) = self.card_position(column, index);
+        self.pointer.press(MouseButton::Left, x, y);
+        self.held = Some(Held {
+            id: self.columns[column][index].id,
+            column,
+            grab: (x - card_x, y - card_y),
+            pointer: (x, y),
+        });
+        true
+    }
+
+    /// Handle mouse button release, returning what the click or drop asks for
+    ///
+    /// A card dropped on another column moves there at once, so it doesn't
+    /// jump back until its owner has changed the task.
+    pub fn handle_mouse_up(&mut self, x: f32, y: f32) -> Option<BoardEvent> {
+        let dragged = self.pointer.release(MouseButton::Left);
+        let held = self.held.take()?;
+        if !dragged {
+            return Some(BoardEvent::Opened(held.id));
+        }
+
+        let column = self.column_at(x, y).filter(|&column| column != held.column)?;
+        let (from, index) = self.position_of(held.id)?;
+        let card = self.columns[from].remove(index);
+        self.columns[column].push(card);
+        Some(BoardEvent::Moved { id: held.id, status: COLUMNS[column] })
+    }
+
+    fn column_color(&self, status: Status) -> [f32; 4] {
+        match status {
+            Status::NotStarted => self.theme.neon_pink(),
+            Status::InProgress => self.theme.cyan(),
+            Status::Completed => self.theme.purple(),
+        }
+    }
+
+    fn draw_card(&self, ctx: &mut RenderContext, card: &Card, (x, y): (f32, f32), completed: bool, lifted: bool) {
+        let width = self.card_width();
+        if lifted {
+            ctx.draw_shadow(&self.theme, x, y, width, CARD_HEIGHT);
+            ctx.draw_glow(Quad::outline(x, y, width, CARD_HEIGHT, self.theme.border_width(), card.accent));
+        }
+        let background = if lifted || self.hovered == Some(card.id) {
+            self.theme.item_hover_bg()
+        } else {
+            self.theme.get_card_background_color()
+        };
+        ctx.draw_rect(x, y, width, CARD_HEIGHT, background);
+        ctx.draw_rect(x, y, STRIPE_WIDTH, CARD_HEIGHT, card.accent);
+        if card.overdue {
+            ctx.draw_outline(x, y, width, CARD_HEIGHT, 1.0, self.theme.get_overdue_color());
+        }
+
+        let text_x = x + STRIPE_WIDTH + 10.0;
+        let text_width = (width - STRIPE_WIDTH - 20.0).max(0.0);
+        let title_color = if completed { self.theme.text_completed() } else { self.theme.bright_text() };
+        ctx.draw_text_bounded(&card.title, (text_x, y + 8.0), text_width, self.theme.text_size(), title_color, TextOverflow::Ellipsis);
+        if let Some(due) = &card.due {
+            let size = self.theme.small_text_size();
+            let color = if card.overdue { self.theme.get_overdue_color() } else { self.theme.get_due_date_color() };
+            ctx.draw_text(due, text_x, y + CARD_HEIGHT - size - 8.0, size, color);
+        }
+    }
+}
+
+impl Default for KanbanBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for KanbanBoard {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let column_width = self.column_width();
+        let text_size = self.theme.text_size();
+        let dragging = self.held.as_ref().filter(|_| self.is_dragging());
+        let target = self.drop_column();
+
+        for (column, &status) in COLUMNS.iter().enumerate() {
+            let column_x = self.column_x(column);
+            let color = self.column_color(status);
+            ctx.draw_shadow(&self.theme, column_x, self.y, column_width, self.height);
+            ctx.draw_rect(column_x, self.y, column_width, self.height, self.theme.panel_background());
+            ctx.draw_rect(column_x, self.y, column_width, 3.0, color);
+            ctx.draw_glow(Quad::rect(column_x, self.y, column_width, 3.0, color));
+            if target == Some(column) {
+                ctx.draw_outline(column_x, self.y, column_width, self.height, 2.0, color);
+                ctx.draw_glow(Quad::outline(column_x, self.y, column_width, self.height, 2.0, color));
+            }
+
+            let header_text_y = self.y + (HEADER_HEIGHT - text_size) / 2.0;
+            ctx.draw_text(&i18n::status_label(status), column_x + PADDING, header_text_y, text_size, color);
+            let count = self.columns[column].len().to_string();
+            let count_x = column_x + column_width - PADDING - text_width(&count, text_size);
+            ctx.draw_text(&count, count_x, header_text_y, text_size, self.theme.muted_text());
+
+            ctx.push_clip_rect(column_x, self.y + HEADER_HEIGHT, column_width, (self.height - HEADER_HEIGHT).max(0.0));
+            if self.columns[column].is_empty() {
+                let (text_x, text_y) = self.card_position(column, 0);
+                ctx.draw_text(&tr("board-empty"), text_x, text_y + (CARD_HEIGHT - text_size) / 2.0, text_size, self.theme.muted_text());
+            }
+            for (index, card) in self.columns[column].iter().enumerate() {
+                let (card_x, card_y) = self.card_position(column, index);
+                if dragging.is_some_and(|held| held.id == card.id) {
+                    // The gap the dragged card left
+                    ctx.draw_outline(card_x, card_y, self.card_width(), CARD_HEIGHT, 1.0, self.theme.border());
+                } else {
+                    self.draw_card(ctx, card, (card_x, card_y), status == Status::Completed, false);
+                }
+            }
+            ctx.pop_clip_rect();
+        }
+
+        // The dragged card follows the pointer over the columns
+        if let Some(held) = dragging {
+            if let Some((column, index)) = self.position_of(held.id) {
+                let position = (held.pointer.0 - held.grab.0, held.pointer.1 - held.grab.1);
+                self.draw_card(ctx, &self.columns[column][index], position, COLUMNS[column] == Status::Completed, true);
+            }
+        }
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+        for column in 0..COLUMNS.len() {
+            self.scroll[column] = self.scroll[column].min(self.max_scroll(column));
+        }
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        if self.is_dragging() {
+            Some(CursorIcon::Grabbing)
+        } else {
+            self.card_at(x, y).map(|_| CursorIcon::Grab)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::{Command, Dispatcher, Project};
+    use crate::ui::{ViewMode, ViewSwitcher};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_board_moves_cards_between_statuses() {
+        let mut todo_list = TodoList::new("Test");
+        let ids = [todo_list.create_item("Draft"), todo_list.create_item("Review")];
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(todo_list)));
+
+        let mut switcher = ViewSwitcher::new();
+        assert_eq!(switcher.handle_mouse_down(120.0, 10.0), Some(ViewMode::Board));
+        assert_eq!(switcher.handle_mouse_down(120.0, 10.0), None);
+        assert_eq!(switcher.mode(), ViewMode::Board);
+
+        // Three 280 wide columns; the first card of each is 56 high at y = 150
+        let mut board = KanbanBoard::new();
+        board.set_position(50.0, 100.0);
+        board.set_dimensions(872.0, 568.0);
+        board.set_list(&dispatcher.lock(), None);
+
+        // A click without moving opens the task
+        assert!(board.handle_mouse_down(100.0, 170.0));
+        assert_eq!(board.handle_mouse_up(100.0, 170.0), Some(BoardEvent::Opened(ids[0])));
+
+        // Dragging it into the next column asks for that column's status
+        assert!(board.handle_mouse_down(100.0, 170.0));
+        board.handle_mouse_move(200.0, 170.0);
+        board.handle_mouse_move(400.0, 200.0);
+        assert!(board.is_dragging());
+        let event = board.handle_mouse_up(400.0, 200.0);
+        assert_eq!(event, Some(BoardEvent::Moved { id: ids[0], status: Status::InProgress }));
+        dispatcher.execute(Command::SetStatus { id: ids[0], status: Status::InProgress }).unwrap();
+        board.set_list(&dispatcher.lock(), None);
+        assert!(board.handle_mouse_down(400.0, 170.0));
+        assert_eq!(board.handle_mouse_up(400.0, 170.0), Some(BoardEvent::Opened(ids[0])));
+
+        // Dropping a card in its own column or off the board changes nothing
+        for (x, y) in [(120.0, 400.0), (40.0, 700.0)] {
+            assert!(board.handle_mouse_down(100.0, 170.0));
+            board.handle_mouse_move(x, y);
+            assert_eq!(board.handle_mouse_up(x, y), None);
+            assert!(!board.is_dragging());
+        }
+
+        // With a project picked, only its tasks get cards
+        let project = Project::new("Launch", [5, 217, 232]);
+        dispatcher.execute(Command::PutProject { project: project.clone() }).unwrap();
+        dispatcher.execute(Command::SetProject { id: ids[1], project_id: Some(project.id()) }).unwrap();
+        board.set_list(&dispatcher.lock(), Some(project.id()));
+        assert!(!board.handle_mouse_down(400.0, 170.0));
+        assert!(board.handle_mouse_down(100.0, 170.0));
+        assert_eq!(board.handle_mouse_up(100.0, 170.0), Some(BoardEvent::Opened(ids[1])));
+    }
+}
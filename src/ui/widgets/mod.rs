@@ -1,4 +1,34 @@
 // Re-export widgets
 pub use super::panel::Panel;
 
-// This module contains specialized versions of the basic widgets 
\ No newline at end of file
+// This module contains specialized versions of the basic widgets
+pub mod archive_view;
+pub mod date_picker;
+pub mod dropdown;
+pub mod edit_task_modal;
+pub mod focus_ring;
+pub mod fx_panel;
+pub mod kanban_board;
+// The popup the tray and the hotkey open to add a task (quick-add feature)
+#[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+pub mod quick_add;
+pub mod sidebar;
+pub mod slider;
+pub mod status_bar;
+pub mod timeline_view;
+pub mod view_switcher;
+
+pub use archive_view::{ArchiveView, ArchiveEvent};
+pub use date_picker::{DatePicker, DatePickerEvent};
+pub use dropdown::Dropdown;
+pub use edit_task_modal::{EditTaskModal, EditTaskEvent};
+pub use focus_ring::FocusRing;
+pub use fx_panel::{FxPanel, FxEvent};
+pub use kanban_board::{KanbanBoard, BoardEvent};
+#[cfg(all(feature = "quick-add", not(target_arch = "wasm32")))]
+pub use quick_add::{QuickAddPopup, QuickAddEvent};
+pub use sidebar::{WorkspaceSidebar, SidebarEvent, SidebarProject};
+pub use slider::Slider;
+pub use status_bar::{StatusBar, TaskCounts};
+pub use timeline_view::TimelineView;
+pub use view_switcher::{ViewSwitcher, ViewMode};
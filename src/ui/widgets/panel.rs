@@ -1,4 +1,4 @@
-use crate::ui::{Widget, context::RenderContext, theme::CyberpunkTheme};
+use crate::ui::{Widget, WidgetInfo, context::RenderContext, theme::CyberpunkTheme};
 use wgpu_glyph::{Section, Text};
 
 /// A panel widget that serves as a container for other widgets
@@ -41,11 +41,22 @@ impl Panel {
 impl Widget for Panel {
     fn update(&mut self, _delta_time: f32) {
         // Update all child widgets
-        for child in &mut self.children {
+        for child in self.children.iter_mut().filter(|child| child.needs_update()) {
             child.update(_delta_time);
         }
     }
 
+    fn needs_update(&self) -> bool {
+        self.children.iter().any(|child| child.needs_update())
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth));
+        for child in &self.children {
+            child.inspect(depth + 1, out);
+        }
+    }
+
     fn render(&self, context: &mut RenderContext) {
         // TODO: In a real implementation, we would draw the panel background
         // For now, we'll just handle the text rendering since we don't have a drawing API yet
@@ -0,0 +1,208 @@
+use crate::i18n::{tr, tr_args};
+use crate::ui::{Clipboard, RenderContext, TextInput, Widget, WidgetInfo, CyberpunkTheme};
+use crate::ui::input::{Action, TextEvent};
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+const PADDING: f32 = 16.0;
+const HEADER_HEIGHT: f32 = 36.0;
+const INPUT_HEIGHT: f32 = 36.0;
+
+fn to_color(rgba: [f32; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: rgba[0] as f64,
+        g: rgba[1] as f64,
+        b: rgba[2] as f64,
+        a: rgba[3] as f64,
+    }
+}
+
+/// What a key press in the popup did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuickAddEvent {
+    /// Enter was pressed on a title, which the popup has cleared again
+    Submitted(String),
+    /// Escape was pressed; the popup should go away
+    Cancelled,
+}
+
+/// A title field and a hint, filling the small window the tray's quick add and the hotkey open
+///
+/// The popup only collects the title; its owner adds the task and closes the window.
+pub struct QuickAddPopup {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    input: TextInput,
+    hint: String,
+    theme: CyberpunkTheme,
+}
+
+impl QuickAddPopup {
+    pub fn new(width: f32, height: f32) -> Self {
+        let theme = CyberpunkTheme::new();
+        let input = TextInput::new(0.0, 0.0, 0.0, INPUT_HEIGHT, tr("new-task-placeholder"))
+            .with_text_color(to_color(theme.bright_text()))
+            .with_background_color(to_color(theme.background()))
+            .with_border_color(to_color(theme.border()));
+        let mut popup = Self {
+            x: 0.0,
+            y: 0.0,
+            width,
+            height,
+            input,
+            hint: String::new(),
+            theme,
+        };
+        popup.place_input();
+        popup.input.set_focused(true);
+        popup
+    }
+
+    /// Name the list tasks are added to in the hint
+    pub fn set_list_name(&mut self, name: &str) {
+        self.hint = tr_args("quick-add-hint", &[("list", name)]);
+    }
+
+    /// Empty the field and focus it, for the next time the popup opens
+    pub fn reset(&mut self) {
+        self.input.set_text("");
+        self.input.set_focused(true);
+    }
+
+    fn place_input(&mut self) {
+        self.input.set_position(self.x + PADDING, self.y + HEADER_HEIGHT);
+        self.input.set_dimensions((self.width - 2.0 * PADDING).max(0.0), INPUT_HEIGHT);
+    }
+
+    /// Type or compose text in the field
+    pub fn handle_text(&mut self, event: &TextEvent) {
+        self.input.handle_text(event);
+    }
+
+    /// Select, move by word and the like in the field, returning whether the action applied
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        self.input.handle_action(action)
+    }
+
+    /// Copy, cut or paste in the field, returning whether the action applied
+    pub fn handle_clipboard(&mut self, action: Action, clipboard: &mut Clipboard) -> bool {
+        self.input.handle_clipboard(action, clipboard)
+    }
+
+    /// Bounds of the field's cursor, for placing an input method's candidates
+    pub fn caret_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        self.input.caret_bounds()
+    }
+
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32, click_count: u32) {
+        // Clicking beside the field doesn't take its focus, there's nothing else to focus
+        if self.input.contains_point(x, y) {
+            self.input.handle_mouse_down(x, y, click_count);
+        }
+    }
+
+    /// Handle keyboard input: Enter submits the title, Escape cancels
+    ///
+    /// Enter on an empty field does nothing.
+    pub fn handle_key_press(&mut self, key: KeyCode) -> Option<QuickAddEvent> {
+        match key {
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                let title = self.input.text().trim().to_string();
+                if title.is_empty() {
+                    return None;
+                }
+                self.reset();
+                Some(QuickAddEvent::Submitted(title))
+            }
+            KeyCode::Escape => Some(QuickAddEvent::Cancelled),
+            key => {
+                self.input.handle_key_press(key);
+                None
+            }
+        }
+    }
+}
+
+impl Widget for QuickAddPopup {
+    fn update(&mut self, delta_time: f32) {
+        self.input.update(delta_time);
+    }
+
+    fn needs_update(&self) -> bool {
+        self.input.needs_update()
+    }
+
+    fn inspect(&self, depth: usize, out: &mut Vec<WidgetInfo>) {
+        out.push(WidgetInfo::of(self, depth));
+        self.input.inspect(depth + 1, out);
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        ctx.draw_rect(self.x, self.y, self.width, self.height, self.theme.panel_background());
+        ctx.draw_outline(self.x, self.y, self.width, self.height, self.theme.border_width(), self.theme.neon_pink());
+
+        let text_size = self.theme.text_size();
+        ctx.draw_label(
+            "quick-add-title",
+            self.x + PADDING, self.y + (HEADER_HEIGHT - text_size) / 2.0,
+            text_size,
+            self.theme.neon_pink(),
+        );
+        self.input.render(ctx);
+        ctx.draw_text(
+            &self.hint,
+            self.x + PADDING, self.y + HEADER_HEIGHT + INPUT_HEIGHT + 8.0,
+            text_size * 0.85,
+            self.theme.muted_text(),
+        );
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+        self.place_input();
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+        self.place_input();
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.input.cursor_at(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quick_add_submits_a_trimmed_title() {
+        // The field is focused from the start; Enter on nothing does nothing
+        let mut popup = QuickAddPopup::new(420.0, 120.0);
+        assert_eq!(popup.handle_key_press(KeyCode::Enter), None);
+        assert!(popup.caret_bounds().is_some());
+
+        popup.handle_text(&TextEvent::Insert("  Buy milk ".to_string()));
+        assert_eq!(popup.handle_key_press(KeyCode::Enter), Some(QuickAddEvent::Submitted("Buy milk".to_string())));
+        // Emptied for the next task
+        assert_eq!(popup.handle_key_press(KeyCode::Enter), None);
+
+        popup.handle_text(&TextEvent::Insert("Drafts".to_string()));
+        popup.handle_key_press(KeyCode::Backspace);
+        assert_eq!(popup.handle_key_press(KeyCode::Escape), Some(QuickAddEvent::Cancelled));
+        assert_eq!(popup.handle_key_press(KeyCode::Enter), Some(QuickAddEvent::Submitted("Draft".to_string())));
+    }
+}
@@ -0,0 +1,344 @@
+use crate::i18n::tr;
+use crate::ui::{damage, RenderContext, Widget, CyberpunkTheme};
+use crate::ui::text_metrics::text_width;
+use winit::window::CursorIcon;
+
+/// Width of the sidebar while it shows the list names
+pub const EXPANDED_WIDTH: f32 = 200.0;
+/// Width of the sidebar folded down to its toggle
+pub const COLLAPSED_WIDTH: f32 = 36.0;
+
+const HEADER_HEIGHT: f32 = 40.0;
+const ROW_HEIGHT: f32 = 32.0;
+/// Side of a project's color swatch, and how far in from the left its clicks reach
+const SWATCH_SIZE: f32 = 12.0;
+const SWATCH_HIT_WIDTH: f32 = 30.0;
+
+/// What a click on the sidebar asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidebarEvent {
+    /// Show the list at this index
+    Selected(usize),
+    /// Create a new list
+    AddRequested,
+    /// Show only the tasks of the active list's project at this index, or all of them again
+    ProjectSelected(usize),
+    /// Give the project at this index the next accent color
+    RecolorRequested(usize),
+    /// Create a new project in the active list
+    AddProjectRequested,
+    /// The sidebar was collapsed (true) or expanded (false); the layout around it changes
+    Toggled(bool),
+}
+
+/// Something on the sidebar a click can land on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Toggle,
+    List(usize),
+    Add,
+    Project(usize),
+    Swatch(usize),
+    AddProject,
+}
+
+/// A project as the sidebar shows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SidebarProject {
+    pub name: String,
+    pub accent: [f32; 4],
+    /// How many of its tasks are still open
+    pub open: usize,
+}
+
+/// The workspace's lists down the left edge of the window, one row each, and
+/// under them the active list's projects with their open task counts
+///
+/// The sidebar only shows names; the owner keeps it in sync with `set_lists`
+/// and `set_projects`, swaps the list view when a list is picked and filters
+/// it when a project is. Collapsed, it is just the toggle that expands it again.
+pub struct WorkspaceSidebar {
+    x: f32,
+    y: f32,
+    height: f32,
+    names: Vec<String>,
+    active: usize,
+    projects: Vec<SidebarProject>,
+    /// The project the list is filtered to
+    selected_project: Option<usize>,
+    collapsed: bool,
+    hovered: Option<Part>,
+    theme: CyberpunkTheme,
+}
+
+impl WorkspaceSidebar {
+    /// Create an expanded sidebar without lists
+    pub fn new(x: f32, y: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            height,
+            names: Vec::new(),
+            active: 0,
+            projects: Vec::new(),
+            selected_project: None,
+            collapsed: false,
+            hovered: None,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Show these list names, highlighting the active one
+    pub fn set_lists(&mut self, names: Vec<String>, active: usize) {
+        self.names = names;
+        self.active = active;
+        self.hovered = None;
+    }
+
+    /// Show these projects, highlighting the one the list is filtered to
+    pub fn set_projects(&mut self, projects: Vec<SidebarProject>, selected: Option<usize>) {
+        if projects.len() != self.projects.len() {
+            self.hovered = None;
+        }
+        self.projects = projects;
+        self.selected_project = selected;
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+        self.hovered = None;
+    }
+
+    /// Width taken up in the current state
+    pub fn width(&self) -> f32 {
+        if self.collapsed { COLLAPSED_WIDTH } else { EXPANDED_WIDTH }
+    }
+
+    /// Top edge of the row at an index, counting down from the first list
+    fn row_y(&self, index: usize) -> f32 {
+        self.y + HEADER_HEIGHT + ROW_HEIGHT * index as f32
+    }
+
+    /// Row index of the projects' heading, under the lists and their add row
+    fn projects_row(&self) -> usize {
+        self.names.len() + 1
+    }
+
+    fn part_at(&self, x: f32, y: f32) -> Option<Part> {
+        if !self.contains_point(x, y) {
+            return None;
+        }
+        if y < self.y + HEADER_HEIGHT {
+            return (x >= self.x + self.width() - COLLAPSED_WIDTH).then_some(Part::Toggle);
+        }
+        if self.collapsed {
+            return None;
+        }
+        let row = ((y - self.y - HEADER_HEIGHT) / ROW_HEIGHT) as usize;
+        let first_project = self.projects_row() + 1;
+        if row < self.names.len() {
+            Some(Part::List(row))
+        } else if row == self.names.len() {
+            Some(Part::Add)
+        } else if (first_project..first_project + self.projects.len()).contains(&row) {
+            let index = row - first_project;
+            Some(if x < self.x + SWATCH_HIT_WIDTH { Part::Swatch(index) } else { Part::Project(index) })
+        } else {
+            (row == first_project + self.projects.len()).then_some(Part::AddProject)
+        }
+    }
+
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        let hovered = self.part_at(x, y);
+        damage::set(&mut self.hovered, hovered);
+    }
+
+    /// Returns what the click asks for, if it landed on a toggle or row
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> Option<SidebarEvent> {
+        match self.part_at(x, y)? {
+            Part::Toggle => {
+                self.set_collapsed(!self.collapsed);
+                Some(SidebarEvent::Toggled(self.collapsed))
+            }
+            Part::List(index) => Some(SidebarEvent::Selected(index)),
+            Part::Add => Some(SidebarEvent::AddRequested),
+            Part::Project(index) => Some(SidebarEvent::ProjectSelected(index)),
+            Part::Swatch(index) => Some(SidebarEvent::RecolorRequested(index)),
+            Part::AddProject => Some(SidebarEvent::AddProjectRequested),
+        }
+    }
+}
+
+impl Widget for WorkspaceSidebar {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let width = self.width();
+        ctx.draw_shadow(&self.theme, self.x, self.y, width, self.height);
+        ctx.draw_rect(self.x, self.y, width, self.height, self.theme.panel_background());
+        ctx.draw_line(self.x + width, self.y, self.x + width, self.y + self.height, 1.0, self.theme.border());
+
+        let text_size = self.theme.text_size();
+        let header_text_y = self.y + (HEADER_HEIGHT - text_size) / 2.0;
+        let toggle_x = self.x + width - COLLAPSED_WIDTH;
+        if self.hovered == Some(Part::Toggle) {
+            ctx.draw_rect(toggle_x, self.y, COLLAPSED_WIDTH, HEADER_HEIGHT, self.theme.item_hover_bg());
+        }
+        ctx.draw_text(
+            if self.collapsed { "»" } else { "«" },
+            toggle_x + 12.0, header_text_y,
+            text_size,
+            self.theme.cyan(),
+        );
+        if self.collapsed {
+            return;
+        }
+        ctx.draw_text(&tr("sidebar-title"), self.x + 12.0, header_text_y, text_size, self.theme.neon_pink());
+
+        ctx.push_clip_rect(self.x, self.y, width, self.height);
+        for (index, name) in self.names.iter().enumerate() {
+            let row_y = self.row_y(index);
+            if index == self.active {
+                ctx.draw_rect(self.x, row_y, width, ROW_HEIGHT, self.theme.filter_button_selected_bg());
+                ctx.draw_rect(self.x, row_y, 3.0, ROW_HEIGHT, self.theme.neon_pink());
+            } else if self.hovered == Some(Part::List(index)) {
+                ctx.draw_rect(self.x, row_y, width, ROW_HEIGHT, self.theme.item_hover_bg());
+            }
+            ctx.draw_text(
+                name,
+                self.x + 12.0, row_y + (ROW_HEIGHT - text_size) / 2.0,
+                text_size,
+                self.theme.bright_text(),
+            );
+        }
+
+        let add_y = self.row_y(self.names.len());
+        if self.hovered == Some(Part::Add) {
+            ctx.draw_rect(self.x, add_y, width, ROW_HEIGHT, self.theme.item_hover_bg());
+        }
+        ctx.draw_text(
+            &tr("sidebar-new-list"),
+            self.x + 12.0, add_y + (ROW_HEIGHT - text_size) / 2.0,
+            text_size,
+            self.theme.muted_text(),
+        );
+
+        let heading_y = self.row_y(self.projects_row());
+        ctx.draw_line(self.x + 12.0, heading_y + 4.0, self.x + width - 12.0, heading_y + 4.0, 1.0, self.theme.border());
+        ctx.draw_text(
+            &tr("sidebar-projects"),
+            self.x + 12.0, heading_y + (ROW_HEIGHT - text_size) / 2.0 + 2.0,
+            text_size,
+            self.theme.neon_pink(),
+        );
+        for (index, project) in self.projects.iter().enumerate() {
+            let row_y = self.row_y(self.projects_row() + 1 + index);
+            if self.selected_project == Some(index) {
+                ctx.draw_rect(self.x, row_y, width, ROW_HEIGHT, self.theme.filter_button_selected_bg());
+                ctx.draw_rect(self.x, row_y, 3.0, ROW_HEIGHT, project.accent);
+            } else if matches!(self.hovered, Some(Part::Project(hovered) | Part::Swatch(hovered)) if hovered == index) {
+                ctx.draw_rect(self.x, row_y, width, ROW_HEIGHT, self.theme.item_hover_bg());
+            }
+            let swatch_y = row_y + (ROW_HEIGHT - SWATCH_SIZE) / 2.0;
+            ctx.draw_rect(self.x + 12.0, swatch_y, SWATCH_SIZE, SWATCH_SIZE, project.accent);
+            if self.hovered == Some(Part::Swatch(index)) {
+                ctx.draw_outline(self.x + 10.0, swatch_y - 2.0, SWATCH_SIZE + 4.0, SWATCH_SIZE + 4.0, 1.0, self.theme.bright_text());
+            }
+            let text_y = row_y + (ROW_HEIGHT - text_size) / 2.0;
+            let count = project.open.to_string();
+            let count_x = self.x + width - 12.0 - text_width(&count, text_size);
+            ctx.draw_text(&count, count_x, text_y, text_size, self.theme.muted_text());
+            ctx.draw_text(&project.name, self.x + SWATCH_HIT_WIDTH + 2.0, text_y, text_size, self.theme.bright_text());
+        }
+
+        let add_project_y = self.row_y(self.projects_row() + 1 + self.projects.len());
+        if self.hovered == Some(Part::AddProject) {
+            ctx.draw_rect(self.x, add_project_y, width, ROW_HEIGHT, self.theme.item_hover_bg());
+        }
+        ctx.draw_text(
+            &tr("sidebar-new-project"),
+            self.x + 12.0, add_project_y + (ROW_HEIGHT - text_size) / 2.0,
+            text_size,
+            self.theme.muted_text(),
+        );
+        ctx.pop_clip_rect();
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width(), self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// The width follows the collapsed state; only the height is taken
+    fn set_dimensions(&mut self, _width: f32, height: f32) {
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.part_at(x, y).map(|_| CursorIcon::Pointer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::{Dispatcher, Workspace};
+    use crate::ui::testing::UiDriver;
+
+    #[test]
+    fn test_sidebar_switches_lists() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Inbox task"]);
+        let mut workspace = Workspace::new(driver.todo_list().lock().unwrap().clone());
+        let work = workspace.add_list("Work").unwrap();
+        workspace.list(work).unwrap().lock().unwrap().create_item("Work task");
+
+        // Header at the top, then one 32px row per list and the "new list" row
+        let mut sidebar = WorkspaceSidebar::new(0.0, 100.0, 568.0);
+        sidebar.set_lists(workspace.names(), workspace.active());
+        assert_eq!(sidebar.handle_mouse_down(20.0, 100.0 + 40.0 + 32.0 + 10.0), Some(SidebarEvent::Selected(work)));
+        assert_eq!(sidebar.handle_mouse_down(20.0, 100.0 + 40.0 + 64.0 + 10.0), Some(SidebarEvent::AddRequested));
+        assert_eq!(sidebar.handle_mouse_down(20.0, 100.0 + 40.0 + 96.0 + 10.0), None);
+
+        // The owner swaps the list the widget shows and edits
+        workspace.set_active(work).unwrap();
+        driver.widget_mut().set_dispatcher(Dispatcher::new(workspace.active_list().clone()));
+        assert!(driver.widget().item_widget(ids[0]).is_none());
+        assert_eq!(driver.widget().shown_items().len(), 1);
+
+        // Collapsed, only the toggle is left
+        assert_eq!(sidebar.handle_mouse_down(190.0, 110.0), Some(SidebarEvent::Toggled(true)));
+        assert_eq!(sidebar.width(), COLLAPSED_WIDTH);
+        assert_eq!(sidebar.handle_mouse_down(20.0, 100.0 + 40.0 + 10.0), None);
+        assert_eq!(sidebar.handle_mouse_down(20.0, 110.0), Some(SidebarEvent::Toggled(false)));
+    }
+
+    #[test]
+    fn test_sidebar_lists_projects() {
+        // The projects follow the lists, their add row and a heading
+        let mut sidebar = WorkspaceSidebar::new(0.0, 100.0, 568.0);
+        sidebar.set_lists(vec!["Tasks".to_string()], 0);
+        sidebar.set_projects(vec![SidebarProject { name: "Garden".to_string(), accent: [0.2, 0.9, 0.5, 1.0], open: 1 }], None);
+        let row = |index: f32| 100.0 + 40.0 + 32.0 * index + 10.0;
+        assert_eq!(sidebar.handle_mouse_down(100.0, row(2.0)), None);
+        assert_eq!(sidebar.handle_mouse_down(100.0, row(3.0)), Some(SidebarEvent::ProjectSelected(0)));
+        assert_eq!(sidebar.handle_mouse_down(16.0, row(3.0)), Some(SidebarEvent::RecolorRequested(0)));
+        assert_eq!(sidebar.handle_mouse_down(100.0, row(4.0)), Some(SidebarEvent::AddProjectRequested));
+        assert_eq!(sidebar.handle_mouse_down(100.0, row(5.0)), None);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::ui::{damage, RenderContext, Widget, CyberpunkTheme};
+use winit::window::CursorIcon;
+
+const TRACK_HEIGHT: f32 = 4.0;
+const KNOB_WIDTH: f32 = 10.0;
+
+/// A horizontal track with a knob, for picking a number in a range
+///
+/// Pressing on the track jumps the knob there and drags it until the button is
+/// released. Values snap to multiples of `step` above `min`.
+pub struct Slider {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+    is_hovered: bool,
+    is_dragging: bool,
+    theme: CyberpunkTheme,
+}
+
+impl Slider {
+    /// Create a slider from `min` to `max`, set to `min`
+    pub fn new(x: f32, y: f32, width: f32, height: f32, min: f32, max: f32, step: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            min,
+            max,
+            step,
+            value: min,
+            is_hovered: false,
+            is_dragging: false,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Move the knob, clamping to the range and snapping to the step
+    pub fn set_value(&mut self, value: f32) {
+        // Dividing by the steps per unit keeps e.g. 0.35 as close as f32 gets, unlike 7.0 * 0.05
+        let steps_per_unit = 1.0 / self.step;
+        let steps = ((value - self.min) * steps_per_unit).round();
+        self.value = (self.min + steps / steps_per_unit).clamp(self.min, self.max);
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.is_dragging
+    }
+
+    /// The value the knob takes at an x position
+    fn value_at(&self, x: f32) -> f32 {
+        let fraction = ((x - self.x) / self.width).clamp(0.0, 1.0);
+        self.min + fraction * (self.max - self.min)
+    }
+
+    /// Share of the track left of the knob
+    fn fraction(&self) -> f32 {
+        (self.value - self.min) / (self.max - self.min)
+    }
+
+    /// Start dragging on a press over the slider
+    ///
+    /// Returns whether the value changed.
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if !self.contains_point(x, y) {
+            return false;
+        }
+        self.is_dragging = true;
+        let old_value = self.value;
+        self.set_value(self.value_at(x));
+        self.value != old_value
+    }
+
+    /// Follow the pointer while dragging
+    ///
+    /// Returns whether the value changed.
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let hovered = self.contains_point(x, y);
+        damage::set(&mut self.is_hovered, hovered);
+        if !self.is_dragging {
+            return false;
+        }
+        let old_value = self.value;
+        self.set_value(self.value_at(x));
+        self.value != old_value
+    }
+
+    /// Stop dragging, returning whether a drag ended
+    pub fn handle_mouse_up(&mut self) -> bool {
+        std::mem::take(&mut self.is_dragging)
+    }
+}
+
+impl Widget for Slider {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let track_y = self.y + (self.height - TRACK_HEIGHT) / 2.0;
+        let filled = self.width * self.fraction();
+        ctx.draw_rect(self.x, track_y, self.width, TRACK_HEIGHT, self.theme.filter_button_bg());
+        ctx.draw_rect(self.x, track_y, filled, TRACK_HEIGHT, self.theme.cyan());
+
+        let knob_color = if self.is_hovered || self.is_dragging { self.theme.bright_text() } else { self.theme.neon_pink() };
+        ctx.draw_rect(self.x + filled - KNOB_WIDTH / 2.0, self.y, KNOB_WIDTH, self.height, knob_color);
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.contains_point(x, y).then_some(CursorIcon::Pointer)
+    }
+}
@@ -0,0 +1,237 @@
+use crate::core::prelude::{Status, TodoList};
+use crate::core::sync::SyncStatus;
+use crate::i18n::{format_datetime, tr, tr_args};
+use crate::ui::input::InputMode;
+use crate::ui::text_metrics::text_width;
+use crate::ui::{RenderContext, TextOverflow, Widget, CyberpunkTheme};
+
+/// Space between the bar's sections, and from its ends
+const SPACING: f32 = 16.0;
+
+/// How many tasks a list has, by state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskCounts {
+    pub total: usize,
+    /// Not completed yet
+    pub active: usize,
+    pub completed: usize,
+    /// Active and past their due date
+    pub overdue: usize,
+}
+
+impl TaskCounts {
+    /// Count the tasks of a list, subtasks included
+    pub fn of(list: &TodoList) -> Self {
+        let mut counts = Self::default();
+        for item in list.all_items() {
+            counts.total += 1;
+            if item.status() == Status::Completed {
+                counts.completed += 1;
+            } else {
+                counts.active += 1;
+            }
+            if item.is_overdue() {
+                counts.overdue += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// A strip along the bottom of the window with the list's task counts, the
+/// active filter, the input mode, how syncing went and the frame rate
+///
+/// The owner feeds it fresh values every frame; the texts are only rebuilt
+/// when they change.
+pub struct StatusBar {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    text_size: f32,
+    counts: TaskCounts,
+    counts_text: String,
+    overdue_text: String,
+    filter_text: String,
+    mode: InputMode,
+    mode_text: String,
+    /// How the last sync went; nothing is shown while syncing is off
+    sync: Option<SyncStatus>,
+    sync_text: String,
+    /// Frame rate, rounded to whole frames
+    fps: u32,
+    fps_text: String,
+    theme: CyberpunkTheme,
+}
+
+impl StatusBar {
+    pub fn new(height: f32, text_size: f32) -> Self {
+        let mut status_bar = Self {
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height,
+            text_size,
+            counts: TaskCounts::default(),
+            counts_text: String::new(),
+            overdue_text: String::new(),
+            filter_text: String::new(),
+            mode: InputMode::Normal,
+            mode_text: String::new(),
+            sync: None,
+            sync_text: String::new(),
+            fps: 0,
+            fps_text: String::new(),
+            theme: CyberpunkTheme::new(),
+        };
+        status_bar.refresh_texts();
+        status_bar
+    }
+
+    /// Text size of the bar's sections
+    pub fn set_text_size(&mut self, text_size: f32) {
+        self.text_size = text_size;
+    }
+
+    pub fn set_counts(&mut self, counts: TaskCounts) {
+        if counts != self.counts {
+            self.counts = counts;
+            self.refresh_texts();
+        }
+    }
+
+    /// Describe the active filter, e.g. from `TodoListWidget::filter_summary`
+    pub fn set_filter(&mut self, summary: &str) {
+        if self.filter_text != summary {
+            self.filter_text = summary.to_string();
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: InputMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.mode_text = tr(mode.label_id());
+        }
+    }
+
+    pub fn set_sync(&mut self, sync: Option<&SyncStatus>) {
+        if sync != self.sync.as_ref() {
+            self.sync = sync.cloned();
+            self.refresh_texts();
+        }
+    }
+
+    pub fn set_fps(&mut self, fps: f32) {
+        let fps = fps.round() as u32;
+        if fps != self.fps {
+            self.fps = fps;
+            self.refresh_texts();
+        }
+    }
+
+    /// Rebuild the texts from the current values
+    fn refresh_texts(&mut self) {
+        self.mode_text = tr(self.mode.label_id());
+        let counts = self.counts;
+        self.counts_text = tr_args("status-counts", &[
+            ("total", &counts.total.to_string()),
+            ("active", &counts.active.to_string()),
+            ("completed", &counts.completed.to_string()),
+        ]);
+        self.overdue_text = tr_args("status-overdue", &[("overdue", &counts.overdue.to_string())]);
+        self.sync_text = match &self.sync {
+            None => String::new(),
+            Some(SyncStatus::Syncing) => tr("status-sync-syncing"),
+            Some(SyncStatus::Synced { at }) => tr_args("status-sync-synced", &[("time", &format_datetime(*at))]),
+            Some(SyncStatus::Failed { .. }) => tr("status-sync-failed"),
+        };
+        self.fps_text = tr_args("status-fps", &[("fps", &self.fps.to_string())]);
+    }
+}
+
+impl Widget for StatusBar {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        ctx.draw_rect(self.x, self.y, self.width, self.height, self.theme.panel_background());
+        ctx.draw_line(self.x, self.y, self.x + self.width, self.y, 1.0, self.theme.border());
+
+        let size = self.text_size;
+        let text_y = self.y + (self.height - size) / 2.0 - 2.0;
+
+        // The mode as a chip, like vim's: cyan while keys act, pink while they type
+        let mode_width = text_width(&self.mode_text, size) + SPACING;
+        let mode_color = match self.mode {
+            InputMode::Normal => self.theme.cyan(),
+            InputMode::Insert => self.theme.neon_pink(),
+        };
+        ctx.draw_rect(self.x, self.y + 1.0, mode_width, self.height - 1.0, mode_color);
+        ctx.draw_text(&self.mode_text, self.x + SPACING / 2.0, text_y, size, self.theme.background());
+
+        let mut x = self.x + mode_width + SPACING;
+        ctx.draw_text(&self.counts_text, x, text_y, size, self.theme.bright_text());
+        x += text_width(&self.counts_text, size) + SPACING;
+        let overdue_color = if self.counts.overdue > 0 { self.theme.danger() } else { self.theme.muted_text() };
+        ctx.draw_text(&self.overdue_text, x, text_y, size, overdue_color);
+        x += text_width(&self.overdue_text, size) + SPACING;
+
+        // The filter takes what's left between the counts and the sync state and frame rate
+        let fps_x = self.x + self.width - SPACING - text_width(&self.fps_text, size);
+        ctx.draw_text(&self.fps_text, fps_x, text_y, size, self.theme.muted_text());
+        let mut right = fps_x;
+        if !self.sync_text.is_empty() {
+            right -= SPACING + text_width(&self.sync_text, size);
+            let sync_color = match self.sync {
+                Some(SyncStatus::Failed { .. }) => self.theme.danger(),
+                Some(SyncStatus::Syncing) => self.theme.cyan(),
+                _ => self.theme.muted_text(),
+            };
+            ctx.draw_text(&self.sync_text, right, text_y, size, sync_color);
+        }
+        let filter_width = right - SPACING - x;
+        if filter_width > 0.0 {
+            ctx.draw_text_bounded(&self.filter_text, (x, text_y), filter_width, size, self.theme.highlight(), TextOverflow::Ellipsis);
+        }
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::UiDriver;
+
+    #[test]
+    fn test_status_bar_counts_and_filter() {
+        let (mut driver, ids) = UiDriver::with_tasks(&["Write report", "Buy groceries"]);
+        driver.click_checkbox(ids[0]);
+        let counts = TaskCounts::of(&driver.widget().todo_list().lock().unwrap());
+        assert_eq!(counts, TaskCounts { total: 2, active: 1, completed: 1, overdue: 0 });
+
+        assert_eq!(driver.widget().filter_summary(), "No filter");
+        driver.widget_mut().focus_search();
+        driver.type_text("rep");
+        assert_eq!(driver.widget().filter_summary(), "Filter: Search \"rep\"");
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::core::prelude::{TodoItem, TodoList};
+use crate::i18n;
+use crate::ui::{damage, Gradient, Quad, RenderContext, TextOverflow, Widget, CyberpunkTheme};
+use crate::ui::text_metrics::text_width;
+use winit::keyboard::ModifiersState;
+use winit::window::CursorIcon;
+
+const AXIS_HEIGHT: f32 = 32.0;
+const ROW_HEIGHT: f32 = 28.0;
+/// Width of the titles column left of the bars
+const LABEL_WIDTH: f32 = 220.0;
+/// Indent per level of a subtask's title
+const INDENT: f32 = 16.0;
+const BAR_HEIGHT: f32 = 12.0;
+/// Thickness of the bracket under a parent spanning its subtasks
+const SPAN_HEIGHT: f32 = 3.0;
+const ZOOM_BUTTON_SIZE: f32 = 24.0;
+/// Widths of a day, in pixels, the zoom stays between
+const MIN_DAY_WIDTH: f32 = 2.0;
+const MAX_DAY_WIDTH: f32 = 160.0;
+const DEFAULT_DAY_WIDTH: f32 = 24.0;
+/// Zoom factor of one click of the buttons or the wheel
+const ZOOM_STEP: f32 = 1.25;
+/// Least room between the dates along the axis
+const MIN_TICK_SPACING: f32 = 64.0;
+/// Days between the dates along the axis, the fewest that leave them room
+const TICK_STEPS: [u64; 6] = [1, 2, 7, 14, 28, 91];
+const DAY: u64 = 24 * 60 * 60;
+
+/// A task on the timeline
+struct TimelineRow {
+    id: Uuid,
+    title: String,
+    depth: usize,
+    /// From creation to the due date, for a task with one
+    bar: Option<(u64, u64)>,
+    /// From the earliest start to the latest due date of the task and its subtasks, for a parent
+    span: Option<(u64, u64)>,
+    accent: [f32; 4],
+    completed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    ZoomOut,
+    ZoomIn,
+    Row(usize),
+}
+
+/// The earliest start and the latest end of two stretches of time
+fn join(a: (u64, u64), b: (u64, u64)) -> (u64, u64) {
+    (a.0.min(b.0), a.1.max(b.1))
+}
+
+/// The list's dated tasks as bars along a time axis, nested like the list
+///
+/// Each task with a due date gets a bar from its creation to the due date;
+/// parents of dated subtasks also get a bracket spanning them all. Tasks
+/// without dates of their own or below them are left out. The wheel scrolls
+/// the rows, along the axis with Shift held and zooms around the pointer with
+/// Ctrl held. The view only shows a copy of the list; its owner hands it the
+/// list again with `set_list`.
+pub struct TimelineView {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rows: Vec<TimelineRow>,
+    /// Local midnight of the first day on the axis
+    origin: u64,
+    /// Days from the origin to the latest due date
+    days: f32,
+    /// Where the line marking the present goes
+    now: u64,
+    day_width: f32,
+    scroll_x: f32,
+    scroll_y: f32,
+    hovered: Option<Part>,
+    theme: CyberpunkTheme,
+}
+
+impl TimelineView {
+    pub fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 0.0,
+            rows: Vec::new(),
+            origin: 0,
+            days: 0.0,
+            now: 0,
+            day_width: DEFAULT_DAY_WIDTH,
+            scroll_x: 0.0,
+            scroll_y: 0.0,
+            hovered: None,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Show the list as it is at `now`, only the tasks in `project` if one is given
+    ///
+    /// Parents outside the project still show as brackets over its tasks below them.
+    pub fn set_list(&mut self, list: &TodoList, project: Option<Uuid>, now: u64) {
+        let own_bar = |item: &TodoItem| {
+            item.due_date()
+                .filter(|_| project.is_none() || item.project_id() == project)
+                .map(|due| (item.created_at().min(due), due))
+        };
+
+        // Subtasks come after their parent, so going backwards their extents are known first
+        let view = list.hierarchical_view();
+        let mut extents: HashMap<Uuid, (u64, u64)> = HashMap::new();
+        let mut rows = Vec::new();
+        for &(item, depth) in view.iter().rev() {
+            let bar = own_bar(item);
+            let children = list.child_ids(item.id()).iter()
+                .filter_map(|id| extents.get(id).copied())
+                .reduce(join);
+            let Some(extent) = bar.into_iter().chain(children).reduce(join) else {
+                continue;
+            };
+            extents.insert(item.id(), extent);
+            rows.push(TimelineRow {
+                id: item.id(),
+                title: item.title().to_string(),
+                depth,
+                bar,
+                span: children.map(|_| extent),
+                accent: item.project_id()
+                    .and_then(|id| list.project(id))
+                    .map(|project| self.theme.project_accent(project.color()))
+                    .unwrap_or_else(|| self.theme.priority_accent(item.priority())),
+                completed: item.is_completed(),
+            });
+        }
+        rows.reverse();
+
+        let (start, end) = rows.iter()
+            .filter(|row| row.depth == 0)
+            .map(|row| extents[&row.id])
+            .reduce(join)
+            .unwrap_or((now, now));
+        self.origin = i18n::local_date(start)
+            .and_then(i18n::local_midnight)
+            .unwrap_or(start - start % DAY);
+        self.days = (end.saturating_sub(self.origin)) as f32 / DAY as f32;
+        self.now = now;
+        self.rows = rows;
+        self.scroll_by(0.0, 0.0);
+    }
+
+    fn chart_x(&self) -> f32 {
+        self.x + LABEL_WIDTH
+    }
+
+    fn chart_width(&self) -> f32 {
+        (self.width - LABEL_WIDTH).max(0.0)
+    }
+
+    fn rows_y(&self) -> f32 {
+        self.y + AXIS_HEIGHT
+    }
+
+    /// Where a moment falls on the axis
+    fn time_x(&self, time: u64) -> f32 {
+        let days = (time as i64 - self.origin as i64) as f32 / DAY as f32;
+        self.chart_x() + days * self.day_width - self.scroll_x
+    }
+
+    fn row_y(&self, index: usize) -> f32 {
+        self.rows_y() + index as f32 * ROW_HEIGHT - self.scroll_y
+    }
+
+    fn zoom_button_x(&self, part: Part) -> f32 {
+        let offset = if part == Part::ZoomIn { ZOOM_BUTTON_SIZE + 6.0 } else { 0.0 };
+        self.x + 10.0 + offset
+    }
+
+    fn max_scroll_x(&self) -> f32 {
+        // A couple of days to spare after the last due date
+        ((self.days + 2.0) * self.day_width - self.chart_width()).max(0.0)
+    }
+
+    fn max_scroll_y(&self) -> f32 {
+        (self.rows.len() as f32 * ROW_HEIGHT - (self.height - AXIS_HEIGHT)).max(0.0)
+    }
+
+    fn part_at(&self, x: f32, y: f32) -> Option<Part> {
+        if !self.contains_point(x, y) {
+            return None;
+        }
+        if y < self.rows_y() {
+            let button_y = self.y + (AXIS_HEIGHT - ZOOM_BUTTON_SIZE) / 2.0;
+            return [Part::ZoomOut, Part::ZoomIn].into_iter().find(|&part| {
+                let button_x = self.zoom_button_x(part);
+                x >= button_x && x <= button_x + ZOOM_BUTTON_SIZE && y >= button_y && y <= button_y + ZOOM_BUTTON_SIZE
+            });
+        }
+        let index = ((y - self.rows_y() + self.scroll_y) / ROW_HEIGHT) as usize;
+        (index < self.rows.len()).then_some(Part::Row(index))
+    }
+
+    /// Scroll along the axis and down the rows by a number of pixels
+    pub fn scroll_by(&mut self, dx: f32, dy: f32) {
+        self.scroll_x = (self.scroll_x + dx).clamp(0.0, self.max_scroll_x());
+        self.scroll_y = (self.scroll_y + dy).clamp(0.0, self.max_scroll_y());
+    }
+
+    /// Zoom the axis in by a number of steps, out for negative ones, keeping
+    /// the moment under `x` in place
+    pub fn zoom_at(&mut self, x: f32, steps: f32) {
+        let anchor = (x - self.chart_x()).clamp(0.0, self.chart_width());
+        let days = (anchor + self.scroll_x) / self.day_width;
+        self.day_width = (self.day_width * ZOOM_STEP.powf(steps)).clamp(MIN_DAY_WIDTH, MAX_DAY_WIDTH);
+        self.scroll_x = (days * self.day_width - anchor).clamp(0.0, self.max_scroll_x());
+    }
+
+    /// Scroll by a number of pixels: down the rows, along the axis with Shift
+    /// held, or zoom around the pointer with Ctrl held
+    pub fn handle_mouse_wheel(&mut self, x: f32, delta: f32, modifiers: ModifiersState) {
+        if modifiers.control_key() || modifiers.super_key() {
+            self.zoom_at(x, -delta.signum());
+        } else if modifiers.shift_key() {
+            self.scroll_by(delta, 0.0);
+        } else {
+            self.scroll_by(0.0, delta);
+        }
+    }
+
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        let hovered = self.part_at(x, y);
+        damage::set(&mut self.hovered, hovered);
+    }
+
+    /// Handle mouse button press, returning the task whose row was clicked
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> Option<Uuid> {
+        let center = self.chart_x() + self.chart_width() / 2.0;
+        match self.part_at(x, y)? {
+            Part::ZoomOut => self.zoom_at(center, -1.0),
+            Part::ZoomIn => self.zoom_at(center, 1.0),
+            Part::Row(index) => return Some(self.rows[index].id),
+        }
+        None
+    }
+
+    /// Dates along the axis, with a faint line down through the rows at each
+    fn render_axis(&self, ctx: &mut RenderContext) {
+        let step = TICK_STEPS.iter()
+            .copied()
+            .find(|&step| step as f32 * self.day_width >= MIN_TICK_SPACING)
+            .unwrap_or(TICK_STEPS[TICK_STEPS.len() - 1]);
+        let size = self.theme.small_text_size();
+        let [r, g, b, _] = self.theme.border();
+        let grid = [r, g, b, 0.35];
+        let right = self.chart_x() + self.chart_width();
+
+        let mut day = (self.scroll_x / self.day_width) as u64 / step * step;
+        loop {
+            let time = self.origin + day * DAY;
+            let tick_x = self.time_x(time);
+            if tick_x > right {
+                break;
+            }
+            ctx.draw_line(tick_x, self.rows_y(), tick_x, self.y + self.height, 1.0, grid);
+            ctx.draw_text(&i18n::format_day(time), tick_x + 4.0, self.y + (AXIS_HEIGHT - size) / 2.0, size, self.theme.muted_text());
+            day += step;
+        }
+
+        let today_x = self.time_x(self.now);
+        if today_x >= self.chart_x() && today_x <= right {
+            let quad = Quad::rect(today_x - 1.0, self.rows_y(), 2.0, self.height - AXIS_HEIGHT, self.theme.neon_pink());
+            ctx.draw_quad(quad);
+            ctx.draw_glow(quad);
+        }
+    }
+
+    fn render_bars(&self, ctx: &mut RenderContext, index: usize, row: &TimelineRow) {
+        let row_y = self.row_y(index);
+        let accent = if row.completed { [row.accent[0], row.accent[1], row.accent[2], 0.45] } else { row.accent };
+        if let Some((start, end)) = row.span {
+            let start_x = self.time_x(start);
+            let end_x = self.time_x(end).max(start_x + 2.0);
+            let bracket_y = row_y + ROW_HEIGHT - 6.0;
+            ctx.draw_rect(start_x, bracket_y, end_x - start_x, SPAN_HEIGHT, accent);
+            ctx.draw_rect(start_x, bracket_y - 4.0, 2.0, SPAN_HEIGHT + 4.0, accent);
+            ctx.draw_rect(end_x - 2.0, bracket_y - 4.0, 2.0, SPAN_HEIGHT + 4.0, accent);
+        }
+        if let Some((start, end)) = row.bar {
+            let start_x = self.time_x(start);
+            let end_x = self.time_x(end).max(start_x + BAR_HEIGHT);
+            // Parents' bars sit higher to leave room for the bracket
+            let lift = if row.span.is_some() { 4.0 } else { 0.0 };
+            let bar_y = row_y + (ROW_HEIGHT - BAR_HEIGHT) / 2.0 - lift;
+            let [r, g, b, a] = accent;
+            let quad = Quad::rect(start_x, bar_y, end_x - start_x, BAR_HEIGHT, accent)
+                .with_corner_radius(BAR_HEIGHT / 2.0)
+                .with_gradient(Gradient::horizontal([r * 0.5, g * 0.5, b * 0.5, a], accent));
+            ctx.draw_quad(quad);
+            if self.hovered == Some(Part::Row(index)) {
+                ctx.draw_glow(quad);
+            }
+        }
+    }
+}
+
+impl Default for TimelineView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for TimelineView {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        ctx.draw_shadow(&self.theme, self.x, self.y, self.width, self.height);
+        ctx.draw_rect(self.x, self.y, self.width, self.height, self.theme.panel_background());
+
+        let text_size = self.theme.text_size();
+        let button_y = self.y + (AXIS_HEIGHT - ZOOM_BUTTON_SIZE) / 2.0;
+        for (part, label) in [(Part::ZoomOut, "−"), (Part::ZoomIn, "+")] {
+            let button_x = self.zoom_button_x(part);
+            let background = if self.hovered == Some(part) { self.theme.item_hover_bg() } else { self.theme.filter_button_bg() };
+            ctx.draw_rect(button_x, button_y, ZOOM_BUTTON_SIZE, ZOOM_BUTTON_SIZE, background);
+            ctx.draw_text(
+                label,
+                button_x + (ZOOM_BUTTON_SIZE - text_width(label, text_size)) / 2.0,
+                button_y + (ZOOM_BUTTON_SIZE - text_size) / 2.0,
+                text_size,
+                self.theme.cyan(),
+            );
+        }
+        ctx.draw_line(self.x, self.rows_y(), self.x + self.width, self.rows_y(), 1.0, self.theme.border());
+        ctx.draw_line(self.chart_x(), self.y, self.chart_x(), self.y + self.height, 1.0, self.theme.border());
+
+        if self.rows.is_empty() {
+            ctx.draw_label("timeline-empty", self.chart_x() + 20.0, self.rows_y() + 20.0, text_size, self.theme.muted_text());
+        }
+
+        ctx.push_clip_rect(self.chart_x(), self.y, self.chart_width(), self.height);
+        self.render_axis(ctx);
+        ctx.pop_clip_rect();
+
+        let rows_height = (self.height - AXIS_HEIGHT).max(0.0);
+        let first = (self.scroll_y / ROW_HEIGHT) as usize;
+        let shown = (rows_height / ROW_HEIGHT).ceil() as usize + 1;
+        ctx.push_clip_rect(self.x, self.rows_y(), self.width, rows_height);
+        for (index, row) in self.rows.iter().enumerate().skip(first).take(shown) {
+            let row_y = self.row_y(index);
+            if self.hovered == Some(Part::Row(index)) {
+                ctx.draw_rect(self.x, row_y, self.width, ROW_HEIGHT, self.theme.item_hover_bg());
+            }
+            let title_x = self.x + 10.0 + row.depth as f32 * INDENT;
+            let title_width = (self.chart_x() - title_x - 8.0).max(0.0);
+            let color = if row.completed { self.theme.text_completed() } else { self.theme.bright_text() };
+            ctx.draw_text_bounded(&row.title, (title_x, row_y + (ROW_HEIGHT - text_size) / 2.0), title_width, text_size, color, TextOverflow::Ellipsis);
+        }
+        ctx.pop_clip_rect();
+
+        ctx.push_clip_rect(self.chart_x(), self.rows_y(), self.chart_width(), rows_height);
+        for (index, row) in self.rows.iter().enumerate().skip(first).take(shown) {
+            self.render_bars(ctx, index, row);
+        }
+        ctx.pop_clip_rect();
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_dimensions(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+        self.scroll_by(0.0, 0.0);
+    }
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.part_at(x, y).map(|_| CursorIcon::Pointer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prelude::Project;
+
+    #[test]
+    fn test_timeline_shows_dated_tasks_nested() {
+        const DAY: u64 = 24 * 60 * 60;
+        let now = crate::ui::input::now_secs() as u64;
+        let mut list = TodoList::new("Plans");
+        let launch = list.create_item("Launch");
+        let design = list.add_item(TodoItem::new("Design").with_parent(launch).with_due_date(now + 3 * DAY));
+        let project = Project::new("Ops", [255, 140, 40]);
+        list.put_project(project.clone());
+        let build = list.add_item(TodoItem::new("Build").with_parent(launch).with_due_date(now + 10 * DAY).with_project(project.id()));
+        list.create_item("Someday");
+        let taxes = list.add_item(TodoItem::new("Taxes").with_due_date(now + 2 * DAY));
+
+        let mut timeline = TimelineView::new();
+        timeline.set_position(50.0, 100.0);
+        timeline.set_dimensions(872.0, 568.0);
+        timeline.set_list(&list, None, now);
+
+        // Rows 28 high under a 32 high axis; the undated task with no dated subtasks is left out
+        let row = |index: f32| 100.0 + 32.0 + 28.0 * index + 10.0;
+        let clicked: Vec<_> = (0..5).map(|index| timeline.handle_mouse_down(400.0, row(index as f32))).collect();
+        assert_eq!(clicked, [Some(launch), Some(design), Some(build), Some(taxes), None]);
+
+        // The zoom buttons and scrolling past the ends don't pick a row
+        assert_eq!(timeline.handle_mouse_down(70.0, 115.0), None);
+        assert_eq!(timeline.handle_mouse_down(100.0, 115.0), None);
+        timeline.handle_mouse_wheel(400.0, 500.0, ModifiersState::empty());
+        timeline.handle_mouse_wheel(400.0, -120.0, ModifiersState::CONTROL);
+        assert_eq!(timeline.handle_mouse_down(400.0, row(0.0)), Some(launch));
+
+        // A project keeps its tasks and the parents above them
+        timeline.set_list(&list, Some(project.id()), now);
+        assert_eq!(timeline.handle_mouse_down(400.0, row(0.0)), Some(launch));
+        assert_eq!(timeline.handle_mouse_down(400.0, row(1.0)), Some(build));
+        assert_eq!(timeline.handle_mouse_down(400.0, row(2.0)), None);
+    }
+}
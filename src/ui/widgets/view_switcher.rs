@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use crate::i18n::tr;
+use crate::ui::{damage, RenderContext, Widget, CyberpunkTheme};
+use crate::ui::text_metrics::text_width;
+use winit::window::CursorIcon;
+
+const SEGMENT_WIDTH: f32 = 80.0;
+const HEIGHT: f32 = 32.0;
+
+/// How the active list is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    /// Rows, nested under their parents
+    #[default]
+    List,
+    /// A column per status, with cards dragged between them
+    Board,
+    /// Bars along a time axis for the tasks with due dates
+    Timeline,
+}
+
+impl ViewMode {
+    const ALL: [ViewMode; 3] = [ViewMode::List, ViewMode::Board, ViewMode::Timeline];
+
+    fn label_id(self) -> &'static str {
+        match self {
+            ViewMode::List => "view-list",
+            ViewMode::Board => "view-board",
+            ViewMode::Timeline => "view-timeline",
+        }
+    }
+}
+
+/// Segments in the header switching between the list, the board and the timeline
+pub struct ViewSwitcher {
+    x: f32,
+    y: f32,
+    mode: ViewMode,
+    hovered: Option<ViewMode>,
+    theme: CyberpunkTheme,
+}
+
+impl ViewSwitcher {
+    pub fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            mode: ViewMode::List,
+            hovered: None,
+            theme: CyberpunkTheme::new(),
+        }
+    }
+
+    /// Width and height the switcher takes in the header
+    pub fn size() -> (f32, f32) {
+        (SEGMENT_WIDTH * ViewMode::ALL.len() as f32, HEIGHT)
+    }
+
+    /// The view shown
+    pub fn mode(&self) -> ViewMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ViewMode) {
+        self.mode = mode;
+    }
+
+    /// The segment under a point
+    fn segment_at(&self, x: f32, y: f32) -> Option<ViewMode> {
+        if !self.contains_point(x, y) {
+            return None;
+        }
+        let index = ((x - self.x) / SEGMENT_WIDTH) as usize;
+        ViewMode::ALL.get(index).copied()
+    }
+
+    pub fn handle_mouse_move(&mut self, x: f32, y: f32) {
+        let hovered = self.segment_at(x, y);
+        damage::set(&mut self.hovered, hovered);
+    }
+
+    /// Switch to the clicked view, returning it if it wasn't shown already
+    pub fn handle_mouse_down(&mut self, x: f32, y: f32) -> Option<ViewMode> {
+        let mode = self.segment_at(x, y).filter(|&mode| mode != self.mode)?;
+        self.mode = mode;
+        Some(mode)
+    }
+}
+
+impl Default for ViewSwitcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for ViewSwitcher {
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn needs_update(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ctx: &mut RenderContext) {
+        let (width, height) = Self::size();
+        let text_size = self.theme.text_size();
+        for (index, mode) in ViewMode::ALL.into_iter().enumerate() {
+            let segment_x = self.x + index as f32 * SEGMENT_WIDTH;
+            let background = if mode == self.mode {
+                self.theme.filter_button_selected_bg()
+            } else if self.hovered == Some(mode) {
+                self.theme.item_hover_bg()
+            } else {
+                self.theme.filter_button_bg()
+            };
+            ctx.draw_rect(segment_x, self.y, SEGMENT_WIDTH, height, background);
+            if mode == self.mode {
+                ctx.draw_rect(segment_x, self.y + height - 2.0, SEGMENT_WIDTH, 2.0, self.theme.cyan());
+            }
+
+            let label = tr(mode.label_id());
+            let color = if mode == self.mode { self.theme.bright_text() } else { self.theme.muted_text() };
+            ctx.draw_text(
+                &label,
+                segment_x + (SEGMENT_WIDTH - text_width(&label, text_size)) / 2.0,
+                self.y + (height - text_size) / 2.0,
+                text_size,
+                color,
+            );
+        }
+        ctx.draw_outline(self.x, self.y, width, height, 1.0, self.theme.border());
+    }
+
+    fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn dimensions(&self) -> (f32, f32) {
+        Self::size()
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// The size is fixed
+    fn set_dimensions(&mut self, _width: f32, _height: f32) {}
+
+    fn cursor_at(&self, x: f32, y: f32) -> Option<CursorIcon> {
+        self.segment_at(x, y).map(|_| CursorIcon::Pointer)
+    }
+}
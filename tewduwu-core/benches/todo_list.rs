@@ -0,0 +1,96 @@
+// Benchmarks for TodoList operations on large lists
+//
+// Run with `cargo bench -p tewduwu-core`. Record a baseline before a
+// performance change with `-- --save-baseline before` and compare against it
+// afterwards with `-- --baseline before`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use tewduwu_core::prelude::*;
+use uuid::Uuid;
+
+/// Roots, children per root and grandchildren per child: 100 + 1000 + 9000 items
+const SHAPE: (usize, usize, usize) = (100, 10, 9);
+
+/// A three-level list with mixed statuses and priorities, returning it with
+/// every id in creation order
+fn large_list() -> (TodoList, Vec<Uuid>) {
+    let (roots, children, grandchildren) = SHAPE;
+    let mut list = TodoList::new("Benchmark");
+    let mut ids = Vec::new();
+
+    let add = |list: &mut TodoList, ids: &mut Vec<Uuid>, parent: Option<Uuid>| {
+        let n = ids.len();
+        let mut item = TodoItem::new(&format!("Task {} needs doing", n))
+            .with_priority([Priority::Low, Priority::Medium, Priority::High][n % 3])
+            .with_status([Status::NotStarted, Status::InProgress, Status::Completed][n / 3 % 3]);
+        if let Some(parent) = parent {
+            item = item.with_parent(parent);
+        }
+        let id = list.add_item(item);
+        ids.push(id);
+        id
+    };
+
+    for _ in 0..roots {
+        let root = add(&mut list, &mut ids, None);
+        for _ in 0..children {
+            let child = add(&mut list, &mut ids, Some(root));
+            for _ in 0..grandchildren {
+                add(&mut list, &mut ids, Some(child));
+            }
+        }
+    }
+    (list, ids)
+}
+
+/// Deterministic pseudo-random indexes, so every run does the same work
+fn indexes(len: usize, count: usize, seed: u64) -> Vec<usize> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % len as u64) as usize
+        })
+        .collect()
+}
+
+fn filtering(c: &mut Criterion) {
+    let (list, _) = large_list();
+    let mut group = c.benchmark_group("filter_10k");
+    group.bench_function("completed", |b| b.iter(|| black_box(list.completed_items().len())));
+    group.bench_function("priority", |b| b.iter(|| black_box(list.items_by_priority(Priority::High).len())));
+    group.bench_function("title_contains", |b| {
+        b.iter(|| black_box(list.filter_items(|item| item.title().to_lowercase().contains("99")).len()))
+    });
+    group.finish();
+}
+
+fn hierarchy(c: &mut Criterion) {
+    let (list, _) = large_list();
+    c.bench_function("hierarchical_view_10k", |b| b.iter(|| black_box(list.hierarchical_view().len())));
+}
+
+fn moves(c: &mut Criterion) {
+    let (list, ids) = large_list();
+    let items = indexes(ids.len(), 1000, 0x2545_f491_4f6c_dd1d);
+    let parents = indexes(ids.len(), 1000, 0x9e37_79b9_7f4a_7c15);
+
+    // Every fifth move goes to the root; moves under a descendant are rejected
+    // after walking its ancestors
+    c.bench_function("move_item_storm_1k", |b| {
+        b.iter_batched_ref(
+            || list.clone(),
+            |list| {
+                for (&item, &parent) in items.iter().zip(&parents) {
+                    let _ = list.move_item(ids[item], Some(ids[parent]).filter(|_| parent % 5 != 0));
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, filtering, hierarchy, moves);
+criterion_main!(benches);
@@ -0,0 +1,692 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use uuid::Uuid;
+use super::todo_item::{clean_tags, unique_attachments, TodoItem, Status, Priority, RecurrenceRule, Reminder, Attachment};
+use super::todo_list::TodoList;
+use super::project::Project;
+use super::events::ListEvent;
+
+/// A change to a todo list
+///
+/// Every edit made by the UI, scripts or plugins is expressed as a command and
+/// run through a `Dispatcher`, so there is one place that sees all of them.
+/// Applying a command yields the command that reverts it.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum Command {
+    /// Add an item; its parent, if any, must exist
+    AddItem { item: TodoItem },
+    /// Remove an item along with its descendants
+    RemoveItem { id: Uuid },
+    SetTitle { id: Uuid, title: String },
+    SetDescription { id: Uuid, description: Option<String> },
+    SetStatus { id: Uuid, status: Status },
+    SetPriority { id: Uuid, priority: Priority },
+    SetDueDate { id: Uuid, due_date: Option<u64> },
+    /// Replace an item's tags
+    SetTags { id: Uuid, tags: Vec<String> },
+    /// Replace the files and web pages an item refers to
+    SetAttachments { id: Uuid, attachments: Vec<Attachment> },
+    SetRecurrence { id: Uuid, recurrence: Option<RecurrenceRule> },
+    SetReminder { id: Uuid, reminder: Reminder },
+    /// Put an item in a project of the list, or take it out of its project with `None`
+    SetProject { id: Uuid, project_id: Option<Uuid> },
+    /// Add a project, or rename or recolor the one with its id
+    PutProject { project: Project },
+    /// Remove a project, taking its items out of it
+    RemoveProject { id: Uuid },
+    /// Set one of an item's metadata values, or remove it with `None`
+    SetMetadata { id: Uuid, key: String, value: Option<String> },
+    /// Give an item a new parent, or make it a root item
+    Move { id: Uuid, parent_id: Option<Uuid> },
+    /// Mark several items completed at once
+    BulkComplete { ids: Vec<Uuid> },
+    /// Swap an item for another version of it, e.g. one pulled by sync, which
+    /// may have moved to another parent
+    ReplaceItem { item: TodoItem },
+    /// Replace the whole list, e.g. with one restored from the recovery journal
+    ReplaceList { list: TodoList },
+    /// Move an item and its descendants into the list's archive
+    Archive { id: Uuid },
+    /// Bring an archived item and its archived descendants back
+    Restore { id: Uuid },
+    /// Several commands applied in order, as one change
+    Batch(Vec<Command>),
+}
+
+/// A change made to several items at once, turned into a command by `TodoList::batch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkEdit {
+    Complete,
+    SetPriority(Priority),
+    /// Remove the items along with their descendants
+    Delete,
+    /// Give the items a new parent, or make them root items
+    MoveUnder(Option<Uuid>),
+}
+
+impl Command {
+    /// Short name for logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::AddItem { .. } => "add item",
+            Command::RemoveItem { .. } => "remove item",
+            Command::SetTitle { .. } => "set title",
+            Command::SetDescription { .. } => "set description",
+            Command::SetStatus { .. } => "set status",
+            Command::SetPriority { .. } => "set priority",
+            Command::SetDueDate { .. } => "set due date",
+            Command::SetTags { .. } => "set tags",
+            Command::SetAttachments { .. } => "set attachments",
+            Command::SetRecurrence { .. } => "set recurrence",
+            Command::SetReminder { .. } => "set reminder",
+            Command::SetProject { .. } => "set project",
+            Command::PutProject { .. } => "put project",
+            Command::RemoveProject { .. } => "remove project",
+            Command::SetMetadata { .. } => "set metadata",
+            Command::Move { .. } => "move",
+            Command::BulkComplete { .. } => "complete items",
+            Command::ReplaceItem { .. } => "replace item",
+            Command::ReplaceList { .. } => "replace list",
+            Command::Archive { .. } => "archive",
+            Command::Restore { .. } => "restore",
+            Command::Batch(_) => "batch",
+        }
+    }
+
+    /// Apply the command to a list
+    ///
+    /// Returns the command that undoes it, or `None` if the list was already in
+    /// the requested state. A command that fails leaves the list unchanged; a
+    /// batch is all or nothing, so when one of its commands fails the ones
+    /// already applied are undone before the error is returned.
+    pub fn apply(self, list: &mut TodoList) -> Result<Option<Command>, String> {
+        match self {
+            Command::AddItem { item } => {
+                let id = item.id();
+                if list.get_item(id).is_some() {
+                    return Err(format!("Item with ID {} already exists", id));
+                }
+                if let Some(parent_id) = item.parent_id() {
+                    if list.get_item(parent_id).is_none() {
+                        return Err(format!("Parent item with ID {} not found", parent_id));
+                    }
+                }
+                list.add_item(item);
+                Ok(Some(Command::RemoveItem { id }))
+            }
+            Command::RemoveItem { id } => {
+                // Parents first, so re-adding them in order restores the subtree
+                let mut removed = Vec::new();
+                collect_subtree(list, id, &mut removed);
+                if removed.is_empty() {
+                    return Err(format!("Item with ID {} not found", id));
+                }
+                list.remove_item(id);
+                Ok(Some(Command::Batch(removed.into_iter().map(|item| Command::AddItem { item }).collect())))
+            }
+            Command::SetTitle { id, title } => {
+                let item = item_mut(list, id)?;
+                if item.title() == title {
+                    return Ok(None);
+                }
+                let old = item.title().to_string();
+                item.set_title(&title);
+                Ok(Some(Command::SetTitle { id, title: old }))
+            }
+            Command::SetDescription { id, description } => {
+                let item = item_mut(list, id)?;
+                if item.description() == description.as_deref() {
+                    return Ok(None);
+                }
+                let old = item.description().map(str::to_string);
+                item.set_description(description.as_deref());
+                Ok(Some(Command::SetDescription { id, description: old }))
+            }
+            Command::SetStatus { id, status } => {
+                let item = item_mut(list, id)?;
+                if item.status() == status {
+                    return Ok(None);
+                }
+                let old = item.status();
+                item.set_status(status);
+                let undo = Command::SetStatus { id, status: old };
+                
+                // Completing a recurring task brings back its next occurrence, once
+                let next = match item.next_occurrence() {
+                    Some(next) if status == Status::Completed
+                        && !list.all_items().iter().any(|other| other.previous_occurrence() == Some(id)) => next,
+                    _ => return Ok(Some(undo)),
+                };
+                let next_id = next.id();
+                list.add_item(next);
+                // Redo re-adds the occurrence before completing, so it isn't spawned twice
+                Ok(Some(Command::Batch(vec![undo, Command::RemoveItem { id: next_id }])))
+            }
+            Command::SetPriority { id, priority } => {
+                let item = item_mut(list, id)?;
+                if item.priority() == priority {
+                    return Ok(None);
+                }
+                let old = item.priority();
+                item.set_priority(priority);
+                Ok(Some(Command::SetPriority { id, priority: old }))
+            }
+            Command::SetDueDate { id, due_date } => {
+                let item = item_mut(list, id)?;
+                if item.due_date() == due_date {
+                    return Ok(None);
+                }
+                let old = item.due_date();
+                item.set_due_date(due_date);
+                Ok(Some(Command::SetDueDate { id, due_date: old }))
+            }
+            Command::SetTags { id, tags } => {
+                let item = item_mut(list, id)?;
+                let tags = clean_tags(tags);
+                if item.tags() == tags.as_slice() {
+                    return Ok(None);
+                }
+                let old = item.tags().to_vec();
+                item.set_tags(tags);
+                Ok(Some(Command::SetTags { id, tags: old }))
+            }
+            Command::SetAttachments { id, attachments } => {
+                let item = item_mut(list, id)?;
+                let attachments = unique_attachments(attachments);
+                if item.attachments() == attachments.as_slice() {
+                    return Ok(None);
+                }
+                let old = item.attachments().to_vec();
+                item.set_attachments(attachments);
+                Ok(Some(Command::SetAttachments { id, attachments: old }))
+            }
+            Command::SetRecurrence { id, recurrence } => {
+                let item = item_mut(list, id)?;
+                if item.recurrence() == recurrence {
+                    return Ok(None);
+                }
+                let old = item.recurrence();
+                item.set_recurrence(recurrence);
+                Ok(Some(Command::SetRecurrence { id, recurrence: old }))
+            }
+            Command::SetReminder { id, reminder } => {
+                let item = item_mut(list, id)?;
+                if item.reminder() == reminder {
+                    return Ok(None);
+                }
+                let old = item.reminder();
+                item.set_reminder(reminder);
+                Ok(Some(Command::SetReminder { id, reminder: old }))
+            }
+            Command::SetProject { id, project_id } => {
+                if let Some(project_id) = project_id.filter(|&project_id| list.project(project_id).is_none()) {
+                    return Err(format!("Project with ID {} not found", project_id));
+                }
+                let item = item_mut(list, id)?;
+                if item.project_id() == project_id {
+                    return Ok(None);
+                }
+                let old = item.project_id();
+                item.set_project_id(project_id);
+                Ok(Some(Command::SetProject { id, project_id: old }))
+            }
+            Command::PutProject { project } => {
+                if project.name().is_empty() {
+                    return Err("A project needs a name".to_string());
+                }
+                let id = project.id();
+                if list.project(id) == Some(&project) {
+                    return Ok(None);
+                }
+                Ok(Some(match list.put_project(project) {
+                    Some(old) => Command::PutProject { project: old },
+                    None => Command::RemoveProject { id },
+                }))
+            }
+            Command::RemoveProject { id } => {
+                let project = list.project(id).cloned().ok_or_else(|| format!("Project with ID {} not found", id))?;
+                // The project comes back before its items go back into it
+                let mut undo = vec![Command::PutProject { project }];
+                for item_id in list.items_by_project(Some(id)).iter().map(|item| item.id()).collect::<Vec<_>>() {
+                    item_mut(list, item_id)?.set_project_id(None);
+                    undo.push(Command::SetProject { id: item_id, project_id: Some(id) });
+                }
+                list.remove_project(id);
+                Ok(Some(Command::Batch(undo)))
+            }
+            Command::SetMetadata { id, key, value } => {
+                let item = item_mut(list, id)?;
+                let old = item.metadata().get(&key).cloned();
+                if old == value {
+                    return Ok(None);
+                }
+                match &value {
+                    Some(value) => item.set_metadata(&key, value),
+                    None => {
+                        item.remove_metadata(&key);
+                    }
+                }
+                Ok(Some(Command::SetMetadata { id, key, value: old }))
+            }
+            Command::Move { id, parent_id } => {
+                let old = item_mut(list, id)?.parent_id();
+                if old == parent_id {
+                    return Ok(None);
+                }
+                list.move_item(id, parent_id)?;
+                Ok(Some(Command::Move { id, parent_id: old }))
+            }
+            Command::BulkComplete { ids } => {
+                if let Some(missing) = ids.iter().find(|id| list.get_item(**id).is_none()) {
+                    return Err(format!("Item with ID {} not found", missing));
+                }
+                let commands = ids.into_iter().map(|id| Command::SetStatus { id, status: Status::Completed }).collect();
+                Command::Batch(commands).apply(list)
+            }
+            Command::ReplaceItem { item } => {
+                let id = item.id();
+                let old = item_mut(list, id)?.clone();
+                if old == item {
+                    return Ok(None);
+                }
+                if item.parent_id() != old.parent_id() {
+                    list.move_item(id, item.parent_id())?;
+                }
+                // Verbatim, down to when it last changed
+                *item_mut(list, id)? = item;
+                Ok(Some(Command::ReplaceItem { item: old }))
+            }
+            Command::ReplaceList { list: mut new_list } => {
+                // The history describes the list being edited, not a particular version of it
+                *new_list.history_mut() = std::mem::take(list.history_mut());
+                // Subscribers too, and they're told to look at everything again
+                *new_list.subscribers_mut() = std::mem::take(list.subscribers_mut());
+                let old = std::mem::replace(list, new_list);
+                list.subscribers_mut().send(ListEvent::Reset);
+                Ok(Some(Command::ReplaceList { list: old }))
+            }
+            Command::Archive { id } => {
+                list.archive_item(id)?;
+                Ok(Some(Command::Restore { id }))
+            }
+            Command::Restore { id } => {
+                list.restore_item(id)?;
+                Ok(Some(Command::Archive { id }))
+            }
+            Command::Batch(commands) => {
+                let mut undo: Vec<Command> = Vec::new();
+                for command in commands {
+                    match command.apply(list) {
+                        Ok(applied) => undo.extend(applied),
+                        Err(e) => {
+                            // All or nothing: take back what already went through
+                            for command in undo.into_iter().rev() {
+                                let _ = command.apply(list);
+                            }
+                            return Err(e);
+                        }
+                    }
+                }
+                if undo.is_empty() {
+                    return Ok(None);
+                }
+                undo.reverse();
+                Ok(Some(Command::Batch(undo)))
+            }
+        }
+    }
+}
+
+impl Command {
+    /// Items whose status the command sets, batches included
+    fn status_targets(&self) -> Vec<Uuid> {
+        match self {
+            Command::SetStatus { id, .. } => vec![*id],
+            Command::Batch(commands) => commands.iter().flat_map(Command::status_targets).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn item_mut(list: &mut TodoList, id: Uuid) -> Result<&mut TodoItem, String> {
+    list.get_item_mut(id).ok_or_else(|| format!("Item with ID {} not found", id))
+}
+
+/// Clone an item and its descendants, parents before their children
+fn collect_subtree(list: &TodoList, id: Uuid, out: &mut Vec<TodoItem>) {
+    if let Some(item) = list.get_item(id) {
+        out.push(item.clone());
+        for child_id in list.child_ids(id) {
+            collect_subtree(list, child_id, out);
+        }
+    }
+}
+
+/// A command that changed the list, with the command that reverts it
+#[derive(Debug, Clone)]
+pub struct Applied {
+    pub command: Command,
+    pub undo: Command,
+}
+
+/// Runs commands against a shared todo list and records the ones that applied
+///
+/// Cheap to clone; clones share the list and the record. Applied commands go
+/// into the list's undo history, and whoever owns the application drains the
+/// record with `take_applied`, e.g. once per frame, to feed sync or an audit
+/// log. Undo and redo show up in the record like any other change.
+#[derive(Debug, Clone)]
+pub struct Dispatcher {
+    list: Arc<Mutex<TodoList>>,
+    applied: Arc<Mutex<Vec<Applied>>>,
+    /// Whether parents are completed along with their last open child, and reopened with any
+    auto_complete_parents: Arc<AtomicBool>,
+}
+
+impl Dispatcher {
+    /// Create a dispatcher for a shared list
+    pub fn new(list: Arc<Mutex<TodoList>>) -> Self {
+        Self {
+            list,
+            applied: Arc::new(Mutex::new(Vec::new())),
+            auto_complete_parents: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Complete parents when all their children are completed, and reopen them
+    /// when one is reopened; applies to every clone of the dispatcher
+    pub fn set_auto_complete_parents(&self, enabled: bool) {
+        self.auto_complete_parents.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether parents follow their children's completion
+    pub fn auto_completes_parents(&self) -> bool {
+        self.auto_complete_parents.load(Ordering::Relaxed)
+    }
+
+    /// The list the commands change, for reading
+    pub fn list(&self) -> &Arc<Mutex<TodoList>> {
+        &self.list
+    }
+
+    /// Lock the list and apply a command, returning whether the list changed
+    pub fn execute(&self, command: Command) -> Result<bool, String> {
+        let mut list = self.lock();
+        self.execute_on(&mut list, command)
+    }
+
+    /// Apply a command to the dispatcher's list when the caller already holds its lock
+    pub fn execute_on(&self, list: &mut TodoList, command: Command) -> Result<bool, String> {
+        let recorded = command.clone();
+        match command.apply(list)? {
+            Some(undo) => {
+                let mut applied = Applied { command: recorded, undo };
+                if self.auto_completes_parents() {
+                    applied = update_parents(list, applied)?;
+                }
+                list.history_mut().record(applied.clone());
+                self.applied.lock().unwrap().push(applied);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Revert the list's most recent change, returning whether there was one
+    pub fn undo(&self) -> Result<bool, String> {
+        let undone = self.lock().undo()?;
+        Ok(self.record(undone))
+    }
+
+    /// Make the most recently undone change again, returning whether there was one
+    pub fn redo(&self) -> Result<bool, String> {
+        let redone = self.lock().redo()?;
+        Ok(self.record(redone))
+    }
+
+    fn record(&self, applied: Option<Applied>) -> bool {
+        match applied {
+            Some(applied) => {
+                self.applied.lock().unwrap().push(applied);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Lock the list
+    pub fn lock(&self) -> MutexGuard<'_, TodoList> {
+        self.list.lock().unwrap()
+    }
+
+    /// Take the commands applied since the last call, oldest first
+    pub fn take_applied(&self) -> Vec<Applied> {
+        std::mem::take(&mut *self.applied.lock().unwrap())
+    }
+}
+
+/// Bring the ancestors of the items an applied command set the status of in
+/// line with their children, as part of the same change
+///
+/// The parents' changes are added to the command and its undo, so undo and
+/// redo take them back and make them again with the rest.
+fn update_parents(list: &mut TodoList, applied: Applied) -> Result<Applied, String> {
+    let mut pending = applied.command.status_targets();
+    let mut commands = vec![applied.command];
+    let mut undo = vec![applied.undo];
+    while let Some(id) = pending.pop() {
+        let Some((parent, status)) = list.parent_status_update(id) else {
+            continue;
+        };
+        let command = Command::SetStatus { id: parent, status };
+        if let Some(reverse) = command.clone().apply(list)? {
+            commands.push(command);
+            undo.push(reverse);
+            pending.push(parent);
+        }
+    }
+    if commands.len() == 1 {
+        return Ok(Applied { command: commands.remove(0), undo: undo.remove(0) });
+    }
+    undo.reverse();
+    Ok(Applied { command: Command::Batch(commands), undo: Command::Batch(undo) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parents(list: &TodoList) -> Vec<(Uuid, Option<Uuid>, Status, String)> {
+        let mut items: Vec<_> = list.all_items()
+            .into_iter()
+            .map(|item| (item.id(), item.parent_id(), item.status(), item.title().to_string()))
+            .collect();
+        items.sort_by_key(|(id, ..)| *id);
+        items
+    }
+
+    #[test]
+    fn test_undo_restores_the_list() {
+        let mut list = TodoList::new("Commands");
+        let parent = list.create_item("Parent");
+        let child = list.add_item(TodoItem::new("Child").with_parent(parent));
+        let other = list.create_item("Other");
+        let before = parents(&list);
+
+        let commands = vec![
+            Command::SetTitle { id: other, title: "Renamed".to_string() },
+            Command::SetTags { id: other, tags: vec!["work".to_string()] },
+            Command::SetAttachments { id: other, attachments: vec![Attachment::Url("https://example.com".to_string())] },
+            Command::SetReminder { id: other, reminder: Reminder::MinutesBefore(30) },
+            Command::SetMetadata { id: other, key: "context".to_string(), value: Some("office".to_string()) },
+            Command::BulkComplete { ids: vec![child, other] },
+            Command::Move { id: other, parent_id: Some(child) },
+            Command::RemoveItem { id: parent },
+            Command::AddItem { item: TodoItem::new("New") },
+        ];
+        let mut undo = Vec::new();
+        for command in commands {
+            undo.push(command.apply(&mut list).unwrap().expect("every command changes the list"));
+        }
+        assert_eq!(list.len(), 1);
+
+        for command in undo.into_iter().rev() {
+            command.apply(&mut list).unwrap();
+        }
+        assert_eq!(parents(&list), before);
+        list.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_and_redundant_commands() {
+        let mut list = TodoList::new("Commands");
+        let id = list.create_item("Task");
+
+        assert!(Command::SetStatus { id, status: Status::NotStarted }.apply(&mut list).unwrap().is_none());
+        assert!(Command::SetMetadata { id, key: "context".to_string(), value: None }.apply(&mut list).unwrap().is_none());
+        assert!(Command::RemoveItem { id: Uuid::new_v4() }.apply(&mut list).is_err());
+        assert!(Command::Move { id, parent_id: Some(id) }.apply(&mut list).is_err());
+        assert!(Command::BulkComplete { ids: vec![id, Uuid::new_v4()] }.apply(&mut list).is_err());
+        assert_eq!(list.get_item(id).unwrap().status(), Status::NotStarted);
+        assert!(Command::ReplaceItem { item: TodoItem::new("Elsewhere") }.apply(&mut list).is_err());
+
+        // Tags and attachments that come out the same, once cleaned, don't count as a change
+        Command::SetTags { id, tags: vec!["work".to_string()] }.apply(&mut list).unwrap();
+        Command::SetAttachments { id, attachments: vec![Attachment::Url("https://example.com".to_string())] }.apply(&mut list).unwrap();
+        let revision = list.get_item(id).unwrap().revision();
+        let tags = vec![" #work".to_string(), "work".to_string(), "".to_string()];
+        assert!(Command::SetTags { id, tags }.apply(&mut list).unwrap().is_none());
+        let attachments = vec![Attachment::Url("https://example.com".to_string()); 2];
+        assert!(Command::SetAttachments { id, attachments }.apply(&mut list).unwrap().is_none());
+        assert_eq!(list.get_item(id).unwrap().revision(), revision);
+
+        // A batch that fails partway leaves the list as it was
+        let batch = Command::Batch(vec![
+            Command::SetTitle { id, title: "Renamed".to_string() },
+            Command::RemoveItem { id: Uuid::new_v4() },
+        ]);
+        assert!(batch.apply(&mut list).is_err());
+        assert_eq!(list.get_item(id).unwrap().title(), "Task");
+    }
+
+    #[test]
+    fn test_projects() {
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("Commands"))));
+        let project = Project::new("Garden", [5, 217, 232]);
+        let (project_id, item) = (project.id(), TodoItem::new("Weed"));
+        let id = item.id();
+        dispatcher.execute(Command::AddItem { item }).unwrap();
+        assert!(dispatcher.execute(Command::SetProject { id, project_id: Some(project_id) }).is_err());
+        assert!(dispatcher.execute(Command::PutProject { project: Project::new(" ", [0, 0, 0]) }).is_err());
+
+        dispatcher.execute(Command::PutProject { project: project.clone() }).unwrap();
+        assert!(!dispatcher.execute(Command::PutProject { project: project.clone() }).unwrap());
+        dispatcher.execute(Command::SetProject { id, project_id: Some(project_id) }).unwrap();
+        let mut renamed = project.clone();
+        renamed.set_name("Yard");
+        dispatcher.execute(Command::PutProject { project: renamed }).unwrap();
+        assert_eq!(dispatcher.lock().project(project_id).unwrap().name(), "Yard");
+
+        // Removing a project takes its items out of it, and undo puts them back
+        dispatcher.execute(Command::RemoveProject { id: project_id }).unwrap();
+        assert!(dispatcher.lock().projects().is_empty());
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().project_id(), None);
+        dispatcher.undo().unwrap();
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().project_id(), Some(project_id));
+        dispatcher.undo().unwrap();
+        assert_eq!(dispatcher.lock().projects(), [project]);
+    }
+
+    #[test]
+    fn test_dispatcher_records_changes() {
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("Commands"))));
+        let item = TodoItem::new("Task");
+        let id = item.id();
+
+        assert!(dispatcher.execute(Command::AddItem { item }).unwrap());
+        assert!(!dispatcher.execute(Command::SetStatus { id, status: Status::NotStarted }).unwrap());
+        assert!(dispatcher.clone().execute(Command::SetStatus { id, status: Status::Completed }).unwrap());
+
+        let applied = dispatcher.take_applied();
+        let names: Vec<&str> = applied.iter().map(|applied| applied.command.name()).collect();
+        assert_eq!(names, vec!["add item", "set status"]);
+        assert!(matches!(applied[1].undo, Command::SetStatus { status: Status::NotStarted, .. }));
+        assert!(dispatcher.take_applied().is_empty());
+    }
+
+    #[test]
+    fn test_completing_a_recurring_task_spawns_the_next() {
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("Commands"))));
+        let item = TodoItem::new("Water plants");
+        let id = item.id();
+        dispatcher.execute(Command::AddItem { item }).unwrap();
+        dispatcher.execute(Command::SetRecurrence { id, recurrence: Some(RecurrenceRule::Daily) }).unwrap();
+
+        let spawned = |dispatcher: &Dispatcher| -> Vec<Uuid> {
+            dispatcher.lock().all_items().iter()
+                .filter(|item| item.previous_occurrence() == Some(id))
+                .map(|item| item.id())
+                .collect()
+        };
+        dispatcher.execute(Command::SetStatus { id, status: Status::Completed }).unwrap();
+        let next = spawned(&dispatcher);
+        assert_eq!(next.len(), 1);
+        assert_eq!(dispatcher.lock().get_item(next[0]).unwrap().recurrence(), Some(RecurrenceRule::Daily));
+
+        // Undo takes the occurrence back; redo restores the same one
+        dispatcher.undo().unwrap();
+        assert!(spawned(&dispatcher).is_empty());
+        assert_eq!(dispatcher.lock().len(), 1);
+        dispatcher.redo().unwrap();
+        assert_eq!(spawned(&dispatcher), next);
+
+        // Reopening and completing again doesn't add a second occurrence
+        dispatcher.execute(Command::SetStatus { id, status: Status::InProgress }).unwrap();
+        dispatcher.execute(Command::SetStatus { id, status: Status::Completed }).unwrap();
+        assert_eq!(spawned(&dispatcher), next);
+        dispatcher.undo().unwrap();
+        dispatcher.undo().unwrap();
+        dispatcher.undo().unwrap();
+        assert_eq!(dispatcher.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_parents_follow_their_children() {
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("Commands"))));
+        let status = |id: Uuid| dispatcher.lock().get_item(id).unwrap().status();
+        // Trip > Packing > Socks, and Trip > Tickets
+        let trip = TodoItem::new("Trip");
+        let packing = TodoItem::new("Packing").with_parent(trip.id());
+        let socks = TodoItem::new("Socks").with_parent(packing.id());
+        let tickets = TodoItem::new("Tickets").with_parent(trip.id());
+        let ids = [&trip, &packing, &socks, &tickets].map(TodoItem::id);
+        let items = [trip, packing, socks, tickets].map(|item| Command::AddItem { item });
+        dispatcher.execute(Command::Batch(items.to_vec())).unwrap();
+        let [trip, packing_id, socks, tickets] = ids;
+
+        // Off by default
+        dispatcher.execute(Command::SetStatus { id: tickets, status: Status::Completed }).unwrap();
+        dispatcher.execute(Command::SetStatus { id: socks, status: Status::Completed }).unwrap();
+        assert_eq!(status(packing_id), Status::NotStarted);
+        dispatcher.undo().unwrap();
+
+        // The last open child completes its parent, and that one its own
+        dispatcher.clone().set_auto_complete_parents(true);
+        assert!(dispatcher.auto_completes_parents());
+        dispatcher.execute(Command::SetStatus { id: socks, status: Status::Completed }).unwrap();
+        assert_eq!((status(packing_id), status(trip)), (Status::Completed, Status::Completed));
+        assert_eq!(dispatcher.lock().child_progress(trip), Some((2, 2)));
+
+        // Reopening a child reopens them, and undo takes it all back in one step
+        dispatcher.execute(Command::SetStatus { id: socks, status: Status::InProgress }).unwrap();
+        assert_eq!((status(packing_id), status(trip)), (Status::NotStarted, Status::NotStarted));
+        assert_eq!(dispatcher.lock().child_progress(trip), Some((1, 2)));
+        dispatcher.undo().unwrap();
+        assert_eq!((status(socks), status(packing_id), status(trip)), (Status::Completed, Status::Completed, Status::Completed));
+        dispatcher.undo().unwrap();
+        assert_eq!((status(socks), status(packing_id), status(trip)), (Status::NotStarted, Status::NotStarted, Status::NotStarted));
+        dispatcher.redo().unwrap();
+        assert_eq!(status(trip), Status::Completed);
+        assert_eq!(dispatcher.lock().child_progress(socks), None);
+    }
+}
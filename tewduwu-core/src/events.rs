@@ -0,0 +1,68 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use uuid::Uuid;
+
+/// A change to the items of a list, sent to everyone who subscribed to it
+///
+/// Events say which item changed, not how; receivers look at the list for the
+/// rest. They arrive in the order the changes were made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEvent {
+    ItemAdded { id: Uuid },
+    /// The item may have changed; this is sent whenever it's borrowed mutably
+    ItemUpdated { id: Uuid },
+    /// The item is gone; removing an item sends this for its descendants too
+    ItemRemoved { id: Uuid },
+    /// The item has another parent, or another place among its siblings
+    ItemMoved { id: Uuid, parent_id: Option<Uuid> },
+    /// A project was added, removed, renamed or recolored
+    ProjectsChanged,
+    /// Anything may have changed, e.g. the whole list was replaced
+    Reset,
+}
+
+/// The senders of a list's subscribers
+///
+/// A clone of a list starts without subscribers: copies such as save snapshots
+/// aren't what anyone subscribed to.
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers {
+    senders: Vec<Sender<ListEvent>>,
+}
+
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Subscribers {
+    /// Add a subscriber, returning where its events arrive
+    pub(crate) fn subscribe(&mut self) -> Receiver<ListEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.senders.push(sender);
+        receiver
+    }
+
+    /// Send an event to every subscriber, forgetting those that dropped their receiver
+    pub(crate) fn send(&mut self, event: ListEvent) {
+        self.senders.retain(|sender| sender.send(event).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_receivers_unsubscribe() {
+        let mut subscribers = Subscribers::default();
+        let kept = subscribers.subscribe();
+        let dropped = subscribers.subscribe();
+        drop(dropped);
+
+        subscribers.send(ListEvent::Reset);
+        assert_eq!(subscribers.senders.len(), 1);
+        assert_eq!(kept.try_recv(), Ok(ListEvent::Reset));
+        assert!(subscribers.clone().senders.is_empty());
+    }
+}
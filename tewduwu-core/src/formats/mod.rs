@@ -0,0 +1,3 @@
+//! Other todo apps' data formats, for bringing tasks in and taking them back out
+
+pub mod taskwarrior;
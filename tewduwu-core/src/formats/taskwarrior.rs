@@ -0,0 +1,280 @@
+use std::collections::{HashMap, HashSet};
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use crate::todo_item::{TodoItem, Status, Priority, RecurrenceRule};
+use crate::todo_list::TodoList;
+
+/// How Taskwarrior writes dates: UTC, without separators
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Metadata key the Taskwarrior project is kept under
+pub const PROJECT_KEY: &str = "project";
+
+/// One task as `task export` writes it and `task import` reads it
+///
+/// Only the attributes tewduwu has a use for; anything else is ignored on import.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Task {
+    uuid: Uuid,
+    description: String,
+    /// pending, completed, deleted, waiting or recurring
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    /// When the task was completed or deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    /// Set while the task is being worked on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    /// H, M or L
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recur: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+    /// The parent task, which Taskwarrior has no notion of; it keeps unknown
+    /// attributes like this one, so subtasks survive a round trip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tewduwu_parent: Option<Uuid>,
+}
+
+/// A note added to a task, which is where longer text goes in Taskwarrior
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Annotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    description: String,
+}
+
+/// Read the JSON array `task export` writes
+///
+/// Deleted tasks and the templates of recurring ones are left out; each
+/// occurrence is imported with the template's rule. Tasks keep their ids, and
+/// come parents first, so they can be added to a list in order.
+pub fn from_json(json: &str) -> Result<Vec<TodoItem>, String> {
+    let tasks: Vec<Task> = serde_json::from_str(json).map_err(|e| format!("Failed to parse Taskwarrior tasks: {}", e))?;
+    let tasks: Vec<Task> = tasks
+        .into_iter()
+        .filter(|task| !matches!(task.status.as_str(), "deleted" | "recurring"))
+        .collect();
+    let ids: HashSet<Uuid> = tasks.iter().map(|task| task.uuid).collect();
+    let mut items: Vec<TodoItem> = tasks.into_iter().map(|task| to_item(task, &ids)).collect();
+
+    // Parents before their children
+    let parents: HashMap<Uuid, Option<Uuid>> = items.iter().map(|item| (item.id(), item.parent_id())).collect();
+    let depth = |mut id: Uuid| {
+        let mut depth = 0;
+        while let Some(Some(parent)) = parents.get(&id) {
+            depth += 1;
+            id = *parent;
+            // A cycle would never end; it can only come from an edited file
+            if depth > parents.len() {
+                break;
+            }
+        }
+        depth
+    };
+    items.sort_by_cached_key(|item| depth(item.id()));
+    Ok(items)
+}
+
+/// Write a list's tasks as JSON that `task import` reads
+pub fn to_json(list: &TodoList) -> Result<String, String> {
+    let tasks: Vec<Task> = list.all_items().into_iter().map(to_task).collect();
+    serde_json::to_string_pretty(&tasks).map_err(|e| format!("Failed to serialize Taskwarrior tasks: {}", e))
+}
+
+/// Convert a task, dropping a parent that isn't among `ids`
+fn to_item(task: Task, ids: &HashSet<Uuid>) -> TodoItem {
+    let status = match task.status.as_str() {
+        "completed" => Status::Completed,
+        _ if task.start.is_some() => Status::InProgress,
+        _ => Status::NotStarted,
+    };
+    let priority = match task.priority.as_deref() {
+        Some("H") => Priority::High,
+        Some("L") => Priority::Low,
+        _ => Priority::Medium,
+    };
+    let mut item = TodoItem::new(&task.description)
+        .with_id(task.uuid)
+        .with_status(status)
+        .with_priority(priority);
+    if let Some(entry) = task.entry.as_deref().and_then(parse_date) {
+        item = item.with_created_at(entry);
+    }
+    if let Some(end) = task.end.as_deref().and_then(parse_date) {
+        item = item.with_completed_at(end);
+    }
+    if let Some(due) = task.due.as_deref().and_then(parse_date) {
+        item = item.with_due_date(due);
+    }
+    if let Some(recurrence) = task.recur.as_deref().and_then(parse_recurrence) {
+        item = item.with_recurrence(recurrence);
+    }
+    if let Some(parent) = task.tewduwu_parent.filter(|parent| ids.contains(parent)) {
+        item = item.with_parent(parent);
+    }
+    if let Some(project) = &task.project {
+        item.set_metadata(PROJECT_KEY, project);
+    }
+    for tag in &task.tags {
+        item.add_tag(tag);
+    }
+    let notes: Vec<&str> = task.annotations.iter().map(|note| note.description.as_str()).collect();
+    if !notes.is_empty() {
+        item.set_description(Some(&notes.join("\n")));
+    }
+    // Last, as building the item counts as changing it
+    let modified = task.modified.as_deref().and_then(parse_date).unwrap_or_else(|| item.updated_at());
+    item.with_updated_at(modified)
+}
+
+fn to_task(item: &TodoItem) -> Task {
+    let (status, start) = match item.status() {
+        Status::Completed => ("completed", None),
+        // When work started isn't recorded; the task's creation stands in for it
+        Status::InProgress => ("pending", Some(format_date(item.created_at()))),
+        Status::NotStarted => ("pending", None),
+    };
+    let priority = match item.priority() {
+        Priority::High => "H",
+        Priority::Medium => "M",
+        Priority::Low => "L",
+    };
+    let annotations = item
+        .description()
+        .map(|description| vec![Annotation { entry: Some(format_date(item.created_at())), description: description.to_string() }])
+        .unwrap_or_default();
+    Task {
+        uuid: item.id(),
+        description: item.title().to_string(),
+        status: status.to_string(),
+        entry: Some(format_date(item.created_at())),
+        modified: Some(format_date(item.updated_at())),
+        end: item.completed_at().map(format_date),
+        start,
+        due: item.due_date().map(format_date),
+        priority: Some(priority.to_string()),
+        project: item.metadata().get(PROJECT_KEY).cloned(),
+        tags: item.tags().to_vec(),
+        // Taskwarrior only repeats tasks that are due
+        recur: item.recurrence().filter(|_| item.due_date().is_some()).map(format_recurrence),
+        annotations,
+        tewduwu_parent: item.parent_id(),
+    }
+}
+
+fn parse_date(date: &str) -> Option<u64> {
+    let date = NaiveDateTime::parse_from_str(date, DATE_FORMAT).ok()?;
+    u64::try_from(date.and_utc().timestamp()).ok()
+}
+
+fn format_date(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .unwrap_or_default()
+        .format(DATE_FORMAT)
+        .to_string()
+}
+
+/// Read the periods tewduwu can repeat by, e.g. `weekly` or `3d`
+fn parse_recurrence(recur: &str) -> Option<RecurrenceRule> {
+    match recur {
+        "daily" | "day" | "1d" => return Some(RecurrenceRule::Daily),
+        "weekly" | "week" | "1w" | "7d" => return Some(RecurrenceRule::Weekly),
+        "monthly" | "month" | "1mo" => return Some(RecurrenceRule::Monthly),
+        _ => {}
+    }
+    let days = recur.strip_suffix("days").or_else(|| recur.strip_suffix('d'))?;
+    days.trim().parse().ok().filter(|&days| days > 0).map(RecurrenceRule::EveryNDays)
+}
+
+fn format_recurrence(recurrence: RecurrenceRule) -> String {
+    match recurrence {
+        RecurrenceRule::Daily => "daily".to_string(),
+        RecurrenceRule::Weekly => "weekly".to_string(),
+        RecurrenceRule::Monthly => "monthly".to_string(),
+        RecurrenceRule::EveryNDays(days) => format!("{}d", days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trimmed from the output of `task export`
+    const EXPORT: &str = r#"[
+        {"id":1,"description":"Buy milk","entry":"20240301T090000Z","modified":"20240301T090000Z","priority":"H","project":"home","status":"pending","tags":["errands"],"due":"20240305T170000Z","uuid":"5f4c2a8e-1f2b-4c3d-9e8f-0a1b2c3d4e5f","urgency":8.2,"annotations":[{"entry":"20240301T091000Z","description":"The oat one"}]},
+        {"id":0,"description":"File taxes","end":"20240302T100000Z","entry":"20240201T080000Z","status":"completed","uuid":"6a5d3b9f-2a3c-4d4e-8f9a-1b2c3d4e5f60","urgency":0},
+        {"id":2,"description":"Write report","entry":"20240301T100000Z","start":"20240301T110000Z","status":"pending","priority":"L","uuid":"7b6e4c0a-3b4d-4e5f-9a0b-2c3d4e5f6071","urgency":1.5},
+        {"id":0,"description":"Old idea","entry":"20240101T080000Z","status":"deleted","uuid":"8c7f5d1b-4c5e-4f6a-8b1c-3d4e5f607182","urgency":0},
+        {"id":3,"description":"Water plants","due":"20240304T080000Z","entry":"20240301T080000Z","parent":"9d806e2c-5d6f-4a7b-9c2d-4e5f60718293","recur":"weekly","status":"pending","uuid":"ae917f3d-6e7a-4b8c-8d3e-5f6071829304","urgency":5}
+    ]"#;
+
+    #[test]
+    fn test_import_maps_attributes() {
+        let items = from_json(EXPORT).unwrap();
+        let titles: Vec<&str> = items.iter().map(TodoItem::title).collect();
+        assert_eq!(titles, ["Buy milk", "File taxes", "Write report", "Water plants"]);
+
+        let milk = &items[0];
+        assert_eq!(milk.id().to_string(), "5f4c2a8e-1f2b-4c3d-9e8f-0a1b2c3d4e5f");
+        assert_eq!(milk.priority(), Priority::High);
+        assert_eq!(milk.status(), Status::NotStarted);
+        assert_eq!(milk.tags(), ["errands"]);
+        assert_eq!(milk.metadata().get(PROJECT_KEY).map(String::as_str), Some("home"));
+        assert_eq!(milk.description(), Some("The oat one"));
+        assert_eq!(milk.created_at(), 1_709_283_600);
+        assert_eq!(milk.due_date(), Some(1_709_658_000));
+
+        assert_eq!(items[1].status(), Status::Completed);
+        assert_eq!(items[1].completed_at(), Some(1_709_373_600));
+        assert_eq!(milk.completed_at(), None);
+        assert_eq!(items[1].priority(), Priority::Medium);
+        assert_eq!(items[2].status(), Status::InProgress);
+        assert_eq!(items[2].priority(), Priority::Low);
+        assert_eq!(items[3].recurrence(), Some(RecurrenceRule::Weekly));
+        // Taskwarrior's parent is a recurrence template, not a parent task
+        assert_eq!(items[3].parent_id(), None);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_subtasks() {
+        let mut list = TodoList::new("Round trip");
+        let parent = list.add_item(
+            TodoItem::new("Move house")
+                .with_priority(Priority::High)
+                .with_due_date(1_710_000_000)
+                .with_recurrence(RecurrenceRule::EveryNDays(3))
+                .with_description("Before April"),
+        );
+        let child = list.add_item(TodoItem::new("Pack books").with_parent(parent).with_status(Status::InProgress).with_tag("boxes"));
+        list.add_item(TodoItem::new("Label boxes").with_parent(child).with_status(Status::Completed));
+
+        let items = from_json(&to_json(&list).unwrap()).unwrap();
+        assert_eq!(items[0].id(), parent);
+        assert_eq!(items[1].id(), child);
+        for item in &items {
+            assert_eq!(Some(item), list.get_item(item.id()));
+        }
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert!(from_json("{}").is_err());
+        assert!(from_json(r#"[{"description":"No uuid","status":"pending"}]"#).is_err());
+        assert_eq!(parse_recurrence("3days"), Some(RecurrenceRule::EveryNDays(3)));
+        assert_eq!(parse_recurrence("quarterly"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+}
@@ -0,0 +1,167 @@
+use super::command::Applied;
+
+/// Undo and redo stacks of the commands applied to a list
+///
+/// Each entry is a command that changed the list together with the command
+/// that reverts it. Undoing an entry applies its revert command, and the
+/// result goes onto the redo stack the same way, so undo and redo are mirror
+/// images of each other. Recording a new change drops everything that could be
+/// redone.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: Vec<Applied>,
+    redo: Vec<Applied>,
+    limit: usize,
+}
+
+impl History {
+    /// How many changes are kept by default
+    pub const DEFAULT_LIMIT: usize = 100;
+
+    pub fn new() -> Self {
+        Self::with_limit(Self::DEFAULT_LIMIT)
+    }
+
+    /// Keep at most `limit` changes, forgetting the oldest first
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Remember a change made by the user
+    pub fn record(&mut self, applied: Applied) {
+        self.redo.clear();
+        push_limited(&mut self.undo, applied, self.limit);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Name of the change `undo` would revert, for menus and status messages
+    pub fn undo_name(&self) -> Option<&'static str> {
+        self.undo.last().map(|applied| applied.command.name())
+    }
+
+    /// Name of the change `redo` would make again
+    pub fn redo_name(&self) -> Option<&'static str> {
+        self.redo.last().map(|applied| applied.command.name())
+    }
+
+    /// Forget all changes
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub(crate) fn pop_undo(&mut self) -> Option<Applied> {
+        self.undo.pop()
+    }
+
+    pub(crate) fn pop_redo(&mut self) -> Option<Applied> {
+        self.redo.pop()
+    }
+
+    /// Remember an undone change so it can be redone
+    pub(crate) fn push_redo(&mut self, applied: Applied) {
+        push_limited(&mut self.redo, applied, self.limit);
+    }
+
+    /// Remember a redone change without dropping the rest of the redo stack
+    pub(crate) fn push_undo(&mut self, applied: Applied) {
+        push_limited(&mut self.undo, applied, self.limit);
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn push_limited(stack: &mut Vec<Applied>, applied: Applied, limit: usize) {
+    stack.push(applied);
+    if stack.len() > limit {
+        let excess = stack.len() - limit;
+        stack.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use crate::{Command, Dispatcher, Status, TodoItem, TodoList};
+    use super::*;
+
+    #[test]
+    fn test_undo_restores_a_removed_subtree() {
+        let mut list = TodoList::new("History");
+        let parent = list.create_item("Parent");
+        let child = list.add_item(TodoItem::new("Child").with_parent(parent));
+        list.add_item(TodoItem::new("Grandchild").with_parent(child));
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(list)));
+
+        assert!(dispatcher.execute(Command::RemoveItem { id: parent }).unwrap());
+        assert!(dispatcher.lock().is_empty());
+
+        assert!(dispatcher.undo().unwrap());
+        assert_eq!(dispatcher.lock().len(), 3);
+        assert_eq!(dispatcher.lock().get_item(child).unwrap().parent_id(), Some(parent));
+        dispatcher.lock().check_invariants().unwrap();
+
+        assert!(dispatcher.redo().unwrap());
+        assert!(dispatcher.lock().is_empty());
+        assert!(!dispatcher.redo().unwrap());
+
+        // Undo and redo are reported like any other change
+        let names: Vec<&str> = dispatcher.take_applied().iter().map(|applied| applied.command.name()).collect();
+        assert_eq!(names, vec!["remove item", "batch", "batch"]);
+    }
+
+    #[test]
+    fn test_new_changes_drop_redo() {
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(TodoList::new("History"))));
+        let item = TodoItem::new("Task");
+        let id = item.id();
+        dispatcher.execute(Command::AddItem { item }).unwrap();
+        dispatcher.execute(Command::SetStatus { id, status: Status::Completed }).unwrap();
+        dispatcher.execute(Command::SetTitle { id, title: "Renamed".to_string() }).unwrap();
+
+        assert!(dispatcher.undo().unwrap());
+        assert!(dispatcher.undo().unwrap());
+        assert_eq!(dispatcher.lock().get_item(id).unwrap().status(), Status::NotStarted);
+        assert_eq!(dispatcher.lock().history().redo_name(), Some("set status"));
+
+        dispatcher.execute(Command::Move { id, parent_id: None }).unwrap();
+        dispatcher.execute(Command::SetPriority { id, priority: crate::Priority::High }).unwrap();
+        assert!(!dispatcher.lock().history().can_redo());
+        assert_eq!(dispatcher.lock().history().undo_name(), Some("set priority"));
+
+        // Undoing everything leaves an empty list and nothing more to undo
+        while dispatcher.undo().unwrap() {}
+        assert!(dispatcher.lock().is_empty());
+        assert!(!dispatcher.lock().history().can_undo());
+    }
+
+    #[test]
+    fn test_history_forgets_the_oldest_changes() {
+        let mut history = History::with_limit(2);
+        for title in ["a", "b", "c"] {
+            let id = uuid::Uuid::new_v4();
+            history.record(crate::Applied {
+                command: Command::SetTitle { id, title: title.to_string() },
+                undo: Command::SetTitle { id, title: String::new() },
+            });
+        }
+        assert!(history.pop_undo().is_some());
+        assert!(history.pop_undo().is_some());
+        assert!(history.pop_undo().is_none());
+    }
+}
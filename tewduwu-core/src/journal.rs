@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+use super::storage;
+use super::workspace::Workspace;
+
+/// Crash-recovery journal for unsaved changes
+///
+/// While the app runs, snapshots of the workspace are written here periodically.
+/// A successful regular save clears the journal, so finding one at startup
+/// means the previous session ended without saving (crash, kill, power loss).
+pub struct Journal {
+    /// Where snapshots are written
+    path: PathBuf,
+
+    /// JSON of the last snapshot written, used to skip redundant writes
+    last_snapshot: Option<String>,
+}
+
+impl Journal {
+    /// Create a journal stored next to the given data file
+    pub fn for_data_path(data_path: &Path) -> Self {
+        Self::at_path(data_path.with_extension("journal"))
+    }
+
+    /// Create a journal at a specific path
+    pub fn at_path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            last_snapshot: None,
+        }
+    }
+
+    /// Write a snapshot of the workspace if it changed since the last one
+    ///
+    /// Returns true if a snapshot was written.
+    pub fn snapshot(&mut self, workspace: &Workspace) -> Result<bool, String> {
+        let json = storage::workspace_to_json(workspace)?;
+        if self.last_snapshot.as_deref() == Some(json.as_str()) {
+            return Ok(false);
+        }
+
+        storage::write_text(&self.path, &json)?;
+        self.last_snapshot = Some(json);
+        Ok(true)
+    }
+
+    /// Remember the workspace as already persisted, so identical snapshots are skipped
+    pub fn mark_saved(&mut self, workspace: &Workspace) {
+        self.last_snapshot = storage::workspace_to_json(workspace).ok();
+    }
+
+    /// Like `mark_saved`, for a workspace that was already serialized, e.g. by a background save
+    pub fn mark_saved_json(&mut self, json: String) {
+        self.last_snapshot = Some(json);
+    }
+
+    /// Load the journaled workspace left behind by an unclean shutdown, if any
+    pub fn recover(&self) -> Result<Option<Workspace>, String> {
+        storage::load_workspace(&self.path)
+    }
+
+    /// Remove the journal, typically after a successful regular save
+    pub fn clear(&self) -> Result<(), String> {
+        storage::remove(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo_list::TodoList;
+
+    fn temp_journal() -> Journal {
+        Journal::at_path(std::env::temp_dir().join(format!("tewduwu-test-{}.journal", uuid::Uuid::new_v4())))
+    }
+
+    #[test]
+    fn test_snapshot_and_recover() {
+        let mut journal = temp_journal();
+        let mut list = TodoList::new("Journal Test");
+        list.create_item("Unsaved task");
+        let workspace = Workspace::new(list);
+
+        assert!(journal.snapshot(&workspace).unwrap());
+        let recovered = journal.recover().unwrap().expect("journal should exist");
+        assert_eq!(recovered.item_count(), 1);
+
+        journal.clear().unwrap();
+        assert!(journal.recover().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unchanged_list_is_not_rewritten() {
+        let mut journal = temp_journal();
+        let mut list = TodoList::new("Journal Test");
+        list.create_item("Task");
+        let mut workspace = Workspace::new(list);
+
+        journal.mark_saved(&workspace);
+        assert!(!journal.snapshot(&workspace).unwrap());
+        assert!(journal.recover().unwrap().is_none());
+
+        workspace.add_list("Another list").unwrap();
+        assert!(journal.snapshot(&workspace).unwrap());
+        journal.clear().unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+//! Data model and persistence for tewduwu
+//!
+//! Todo items, the todo list with its hierarchy and filtering, workspaces of
+//! several lists, the commands that change a list and their undo history, JSON
+//! storage, the crash-recovery journal, other apps' formats, quick-add parsing
+//! and syncing with servers. Nothing here depends on wgpu or winit, so other
+//! frontends can build on just this crate.
+
+mod todo_item;
+mod todo_list;
+mod command;
+mod workspace;
+mod project;
+mod events;
+pub mod history;
+pub mod storage;
+pub mod journal;
+pub mod formats;
+pub mod parse;
+pub mod sync;
+
+pub use todo_item::{TodoItem, Status, Priority, RecurrenceRule, Reminder, Attachment, FOCUS_LOG_KEY};
+pub use todo_list::{TodoList, SortKey, SortOrder};
+pub use workspace::Workspace;
+pub use project::Project;
+pub use command::{Command, BulkEdit, Dispatcher, Applied};
+pub use events::ListEvent;
+pub use history::History;
+
+/// The core module contains the data structures for the todo list.
+/// This includes the TodoItem and TodoList structures, as well as
+/// supporting enums like Status and Priority.
+pub mod prelude {
+    pub use super::{TodoItem, TodoList, SortKey, SortOrder, ListEvent, Workspace, Project, Status, Priority, RecurrenceRule, Reminder, Attachment, Command, BulkEdit, Dispatcher};
+} 
\ No newline at end of file
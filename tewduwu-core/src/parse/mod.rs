@@ -0,0 +1,3 @@
+//! Reading tasks out of the text people type
+
+pub mod quick_add;
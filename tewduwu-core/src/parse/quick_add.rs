@@ -0,0 +1,386 @@
+//! Due dates, priorities, tags and recurrence typed along with a task's title
+//!
+//! `Buy milk tomorrow 5pm !high #errands @store every friday` adds "Buy milk"
+//! with all of that set. Words are read case-insensitively:
+//!
+//! - dates: `today`, `tonight`, `tomorrow`, a weekday (`friday`, `on friday`,
+//!   `next friday`), `next week`, `next month`, `in 3 days` (or weeks, months)
+//! - times: `5pm`, `5:30pm`, `5 pm`, `17:00`, `noon`, optionally after `at`
+//! - priorities: `!high`, `!medium`, `!low`, or `!h`, `!m`, `!l`
+//! - tags: `#projectx` and `@home`, without their sign
+//! - recurrence: `every day`, `every week`, `every month`, `every other day`,
+//!   `every 3 days`, `every 2 weeks`, `every friday`
+//!
+//! A date without a time is due at the start of that day, and a time without
+//! a date is the next time the clock shows it. Words that aren't any of these
+//! make up the title; a line of nothing else is taken as the title as it is.
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveTime, TimeZone, Weekday};
+use crate::todo_item::{TodoItem, Priority, RecurrenceRule};
+
+/// What a quick-add line says about the task to add
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuickAdd {
+    /// The words left once the others were read
+    pub title: String,
+    pub due_date: Option<u64>,
+    pub priority: Option<Priority>,
+    pub tags: Vec<String>,
+    pub recurrence: Option<RecurrenceRule>,
+}
+
+impl QuickAdd {
+    /// A new task with everything the line set
+    pub fn into_item(self) -> TodoItem {
+        let mut item = TodoItem::new(&self.title);
+        if let Some(due_date) = self.due_date {
+            item = item.with_due_date(due_date);
+        }
+        if let Some(priority) = self.priority {
+            item = item.with_priority(priority);
+        }
+        for tag in &self.tags {
+            item = item.with_tag(tag);
+        }
+        if let Some(recurrence) = self.recurrence {
+            item = item.with_recurrence(recurrence);
+        }
+        item
+    }
+}
+
+/// One thing read from the line
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Date(NaiveDate),
+    Time(NaiveTime),
+    /// `tonight`: a day with a time of its own
+    DateTime(NaiveDate, NaiveTime),
+    Priority(Priority),
+    Tag(String),
+    /// The rule, and the day it starts on for `every friday`
+    Recurrence(RecurrenceRule, Option<NaiveDate>),
+}
+
+/// Read a quick-add line typed at `now`, whose time zone dates are read in
+pub fn parse<Tz: TimeZone>(text: &str, now: &DateTime<Tz>) -> QuickAdd {
+    let today = now.date_naive();
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    let mut parsed = QuickAdd::default();
+    let mut title = Vec::new();
+    let (mut date, mut time, mut start) = (None, None, None);
+    let mut read_any = false;
+    let mut index = 0;
+    while index < words.len() {
+        let Some((used, token)) = read_token(&words[index..], today) else {
+            title.push(words[index]);
+            index += 1;
+            continue;
+        };
+        match token {
+            Token::Date(day) => date = Some(day),
+            Token::Time(clock) => time = Some(clock),
+            Token::DateTime(day, clock) => (date, time) = (Some(day), Some(clock)),
+            Token::Priority(priority) => parsed.priority = Some(priority),
+            Token::Tag(tag) => {
+                if !parsed.tags.contains(&tag) {
+                    parsed.tags.push(tag);
+                }
+            }
+            Token::Recurrence(rule, day) => (parsed.recurrence, start) = (Some(rule), day),
+        }
+        read_any = true;
+        index += used;
+    }
+
+    if title.is_empty() || !read_any {
+        return QuickAdd { title: text.trim().to_string(), ..QuickAdd::default() };
+    }
+    parsed.title = title.join(" ");
+
+    let due = match (date.or(start), time) {
+        (Some(day), time) => Some(day.and_time(time.unwrap_or(NaiveTime::MIN))),
+        // Today if that time is still to come, tomorrow otherwise
+        (None, Some(time)) if time > now.time() => Some(today.and_time(time)),
+        (None, Some(time)) => today.succ_opt().map(|tomorrow| tomorrow.and_time(time)),
+        (None, None) => None,
+    };
+    parsed.due_date = due
+        .and_then(|due| now.timezone().from_local_datetime(&due).earliest())
+        .and_then(|due| u64::try_from(due.timestamp()).ok());
+    parsed
+}
+
+/// Read the token the words start with, returning how many words it took
+fn read_token(words: &[&str], today: NaiveDate) -> Option<(usize, Token)> {
+    let word = |index: usize| words.get(index).map(|word| word.to_lowercase()).unwrap_or_default();
+    let first = word(0);
+
+    if let Some(name) = first.strip_prefix('!') {
+        let priority = match name {
+            "high" | "h" => Priority::High,
+            "medium" | "med" | "m" => Priority::Medium,
+            "low" | "l" => Priority::Low,
+            _ => return None,
+        };
+        return Some((1, Token::Priority(priority)));
+    }
+    if let Some(tag) = words[0].strip_prefix(['#', '@']) {
+        let is_tag = tag.chars().next().is_some_and(char::is_alphanumeric)
+            && tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '/');
+        return is_tag.then(|| (1, Token::Tag(tag.to_string())));
+    }
+
+    let date = |used: usize, day: Option<NaiveDate>| day.map(|day| (used, Token::Date(day)));
+    match first.as_str() {
+        "today" => return date(1, Some(today)),
+        "tomorrow" => return date(1, today.succ_opt()),
+        "tonight" => return NaiveTime::from_hms_opt(20, 0, 0).map(|time| (1, Token::DateTime(today, time))),
+        "on" => return weekday(&word(1)).and_then(|weekday| date(2, upcoming(today, weekday))),
+        "next" => {
+            let day = match word(1).as_str() {
+                "week" => today.checked_add_days(Days::new(7)),
+                "month" => today.checked_add_months(Months::new(1)),
+                name => weekday(name).and_then(|weekday| next(today, weekday)),
+            };
+            return date(2, day);
+        }
+        "in" => {
+            let count: u32 = word(1).parse().ok().filter(|&count| count > 0)?;
+            let day = match word(2).trim_end_matches('s') {
+                "day" => today.checked_add_days(Days::new(count.into())),
+                "week" => today.checked_add_days(Days::new(u64::from(count) * 7)),
+                "month" => today.checked_add_months(Months::new(count)),
+                _ => None,
+            };
+            return date(3, day);
+        }
+        "at" => return read_time(&words[1..]).map(|(used, time)| (used + 1, Token::Time(time))),
+        "every" => return read_recurrence(&words[1..], today).map(|(used, token)| (used + 1, token)),
+        _ => {}
+    }
+
+    if let Some(weekday) = weekday(&first) {
+        return date(1, upcoming(today, weekday));
+    }
+    read_time(words).map(|(used, time)| (used, Token::Time(time)))
+}
+
+/// Read the rest of `every ...`
+fn read_recurrence(words: &[&str], today: NaiveDate) -> Option<(usize, Token)> {
+    let word = |index: usize| words.get(index).map(|word| word.to_lowercase()).unwrap_or_default();
+    let rule = |used: usize, rule: RecurrenceRule| Some((used, Token::Recurrence(rule, None)));
+    let first = word(0);
+    match first.as_str() {
+        "day" => return rule(1, RecurrenceRule::Daily),
+        "week" => return rule(1, RecurrenceRule::Weekly),
+        "month" => return rule(1, RecurrenceRule::Monthly),
+        "other" => {
+            return match word(1).as_str() {
+                "day" => rule(2, RecurrenceRule::EveryNDays(2)),
+                "week" => rule(2, RecurrenceRule::EveryNDays(14)),
+                _ => None,
+            }
+        }
+        _ => {}
+    }
+    if let Some(weekday) = weekday(&first) {
+        return Some((1, Token::Recurrence(RecurrenceRule::Weekly, upcoming(today, weekday))));
+    }
+
+    let count: u32 = first.parse().ok().filter(|&count| count > 0)?;
+    match (word(1).trim_end_matches('s'), count) {
+        ("day", 1) => rule(2, RecurrenceRule::Daily),
+        ("day", days) => rule(2, RecurrenceRule::EveryNDays(days)),
+        ("week", 1) => rule(2, RecurrenceRule::Weekly),
+        ("week", weeks) => rule(2, RecurrenceRule::EveryNDays(weeks.checked_mul(7)?)),
+        ("month", 1) => rule(2, RecurrenceRule::Monthly),
+        _ => None,
+    }
+}
+
+/// Read a time of day such as `5pm`, `5 pm`, `5:30pm`, `17:00` or `noon`
+fn read_time(words: &[&str]) -> Option<(usize, NaiveTime)> {
+    let first = words.first()?.to_lowercase();
+    if first == "noon" {
+        return Some((1, NaiveTime::from_hms_opt(12, 0, 0)?));
+    }
+
+    let (clock, meridiem, used) = if let Some(clock) = first.strip_suffix("am") {
+        (clock, Some(false), 1)
+    } else if let Some(clock) = first.strip_suffix("pm") {
+        (clock, Some(true), 1)
+    } else {
+        match words.get(1).map(|word| word.to_lowercase()).as_deref() {
+            Some("am") => (first.as_str(), Some(false), 2),
+            Some("pm") => (first.as_str(), Some(true), 2),
+            _ => (first.as_str(), None, 1),
+        }
+    };
+
+    let (hours, minutes) = match clock.split_once(':') {
+        Some((hours, minutes)) if minutes.len() == 2 => (hours, minutes.parse().ok()?),
+        Some(_) => return None,
+        // A bare number is only a time with am or pm
+        None if meridiem.is_some() => (clock, 0),
+        None => return None,
+    };
+    if hours.is_empty() || hours.len() > 2 {
+        return None;
+    }
+    let hours: u32 = hours.parse().ok()?;
+    let hours = match meridiem {
+        Some(pm) if (1..=12).contains(&hours) => hours % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hours,
+    };
+    NaiveTime::from_hms_opt(hours, minutes, 0).map(|time| (used, time))
+}
+
+fn weekday(name: &str) -> Option<Weekday> {
+    let weekday = match name {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+/// The first day from today on that falls on `weekday`
+fn upcoming(today: NaiveDate, weekday: Weekday) -> Option<NaiveDate> {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today.checked_add_days(Days::new(ahead.into()))
+}
+
+/// The first day after today that falls on `weekday`
+fn next(today: NaiveDate, weekday: Weekday) -> Option<NaiveDate> {
+    upcoming(today.succ_opt()?, weekday)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    /// Wednesday, 5 March 2025, 10:00 in UTC+1
+    fn now() -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(3600).unwrap().with_ymd_and_hms(2025, 3, 5, 10, 0, 0).unwrap()
+    }
+
+    /// Unix time of a day of March 2025 at a time, in UTC+1
+    fn march(day: u32, hour: u32, minute: u32) -> u64 {
+        FixedOffset::east_opt(3600).unwrap().with_ymd_and_hms(2025, 3, day, hour, minute, 0).unwrap().timestamp() as u64
+    }
+
+    fn due(text: &str) -> Option<u64> {
+        let parsed = parse(text, &now());
+        assert_eq!(parsed.title, "Call", "{:?}", text);
+        parsed.due_date
+    }
+
+    #[test]
+    fn test_reads_everything_and_leaves_the_title() {
+        let parsed = parse("Buy  milk tomorrow 5pm !high #errands @store every friday", &now());
+        assert_eq!(parsed, QuickAdd {
+            title: "Buy milk".to_string(),
+            due_date: Some(march(6, 17, 0)),
+            priority: Some(Priority::High),
+            tags: vec!["errands".to_string(), "store".to_string()],
+            recurrence: Some(RecurrenceRule::Weekly),
+        });
+
+        let item = parsed.into_item();
+        assert_eq!(item.title(), "Buy milk");
+        assert_eq!(item.due_date(), Some(march(6, 17, 0)));
+        assert_eq!(item.priority(), Priority::High);
+        assert_eq!(item.tags(), ["errands", "store"]);
+        assert_eq!(item.recurrence(), Some(RecurrenceRule::Weekly));
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(due("Call today"), Some(march(5, 0, 0)));
+        assert_eq!(due("Call tonight"), Some(march(5, 20, 0)));
+        assert_eq!(due("Call Tomorrow"), Some(march(6, 0, 0)));
+        // Today is a Wednesday
+        assert_eq!(due("Call wednesday"), Some(march(5, 0, 0)));
+        assert_eq!(due("Call next wednesday"), Some(march(12, 0, 0)));
+        assert_eq!(due("Call on friday"), Some(march(7, 0, 0)));
+        assert_eq!(due("Call monday"), Some(march(10, 0, 0)));
+        assert_eq!(due("Call next week"), Some(march(12, 0, 0)));
+        assert_eq!(due("Call in 3 days"), Some(march(8, 0, 0)));
+        assert_eq!(due("Call in 2 weeks"), Some(march(19, 0, 0)));
+        assert_eq!(parse("Call next month", &now()).due_date, parse("Call in 1 month", &now()).due_date);
+    }
+
+    #[test]
+    fn test_due_today_is_not_overdue() {
+        // Due at the start of the day, which has already begun
+        let item = parse("Call today", &now()).into_item();
+        assert!(!item.is_overdue_at(&now()));
+        assert!(parse("Call today", &(now() - Days::new(1))).into_item().is_overdue_at(&now()));
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(due("Call friday at 5:30pm"), Some(march(7, 17, 30)));
+        assert_eq!(due("Call friday 9 am"), Some(march(7, 9, 0)));
+        assert_eq!(due("Call tomorrow 12am"), Some(march(6, 0, 0)));
+        assert_eq!(due("Call tomorrow noon"), Some(march(6, 12, 0)));
+        // Still to come today, or already past and so tomorrow
+        assert_eq!(due("Call 17:00"), Some(march(5, 17, 0)));
+        assert_eq!(due("Call at 9am"), Some(march(6, 9, 0)));
+
+        // Numbers and the like that aren't times stay in the title
+        for text in ["Read 5 chapters", "Buy 13pm paint", "Fix 1:5 ratio", "Meet at the station"] {
+            assert_eq!(parse(text, &now()), QuickAdd { title: text.to_string(), ..QuickAdd::default() });
+        }
+    }
+
+    #[test]
+    fn test_priorities_and_tags() {
+        let parsed = parse("Pay rent !L #home/bills @Home #home/bills", &now());
+        assert_eq!(parsed.title, "Pay rent");
+        assert_eq!(parsed.priority, Some(Priority::Low));
+        assert_eq!(parsed.tags, ["home/bills", "Home"]);
+
+        // Unknown priorities, lone signs and addresses aren't tokens
+        let text = "Ask !boss about # and me@example.com";
+        assert_eq!(parse(text, &now()).title, text);
+    }
+
+    #[test]
+    fn test_recurrence() {
+        let recurrence = |text: &str| {
+            let parsed = parse(text, &now());
+            assert_eq!(parsed.title, "Water plants", "{:?}", text);
+            (parsed.recurrence, parsed.due_date)
+        };
+        assert_eq!(recurrence("Water plants every day"), (Some(RecurrenceRule::Daily), None));
+        assert_eq!(recurrence("Water plants every week"), (Some(RecurrenceRule::Weekly), None));
+        assert_eq!(recurrence("Water plants every month"), (Some(RecurrenceRule::Monthly), None));
+        assert_eq!(recurrence("Water plants every other day"), (Some(RecurrenceRule::EveryNDays(2)), None));
+        assert_eq!(recurrence("Water plants every 3 days"), (Some(RecurrenceRule::EveryNDays(3)), None));
+        assert_eq!(recurrence("Water plants every 2 weeks"), (Some(RecurrenceRule::EveryNDays(14)), None));
+        assert_eq!(recurrence("Water plants every 1 week"), (Some(RecurrenceRule::Weekly), None));
+        // A weekday starts it on the next one, unless a date says otherwise
+        assert_eq!(recurrence("Water plants every friday"), (Some(RecurrenceRule::Weekly), Some(march(7, 0, 0))));
+        assert_eq!(recurrence("Water plants every friday 8am"), (Some(RecurrenceRule::Weekly), Some(march(7, 8, 0))));
+        assert_eq!(recurrence("Water plants every friday in 2 weeks"), (Some(RecurrenceRule::Weekly), Some(march(19, 0, 0))));
+
+        assert_eq!(parse("Water plants every 2 months", &now()).title, "Water plants every 2 months");
+    }
+
+    #[test]
+    fn test_a_line_of_only_tokens_is_the_title() {
+        assert_eq!(parse(" tomorrow ", &now()), QuickAdd { title: "tomorrow".to_string(), ..QuickAdd::default() });
+        assert_eq!(parse("#idea !high", &now()).title, "#idea !high");
+        assert_eq!(parse("", &now()), QuickAdd::default());
+    }
+}
@@ -0,0 +1,89 @@
+use uuid::Uuid;
+
+/// A group of tasks in a list, with a name and an accent color
+///
+/// Items refer to their project by id; the list keeps the projects.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Project {
+    id: Uuid,
+    name: String,
+    /// Red, green and blue, each 0 to 255
+    color: [u8; 3],
+}
+
+impl Project {
+    /// Create a project with a fresh id
+    pub fn new(name: &str, color: [u8; 3]) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.trim().to_string(),
+            color,
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The accent color as red, green and blue
+    pub fn color(&self) -> [u8; 3] {
+        self.color
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.trim().to_string();
+    }
+
+    pub fn set_color(&mut self, color: [u8; 3]) {
+        self.color = color;
+    }
+
+    /// The accent color as `#rrggbb`
+    pub fn hex_color(&self) -> String {
+        let [r, g, b] = self.color;
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// Read a color written as `#rrggbb` or `#rgb`, the `#` optional
+    pub fn parse_color(text: &str) -> Option<[u8; 3]> {
+        let hex = text.trim().trim_start_matches('#');
+        if !hex.is_ascii() {
+            return None;
+        }
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        match hex.len() {
+            6 => Some([channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?]),
+            3 => {
+                let short = |index: usize| channel(&hex[index..index + 1]).map(|value| value * 17);
+                Some([short(0)?, short(1)?, short(2)?])
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(Project::parse_color("#ff2a6d"), Some([255, 42, 109]));
+        assert_eq!(Project::parse_color(" 05D9E8 "), Some([5, 217, 232]));
+        assert_eq!(Project::parse_color("#f80"), Some([255, 136, 0]));
+        assert_eq!(Project::parse_color("#ff2a6"), None);
+        assert_eq!(Project::parse_color("#ggg"), None);
+        assert_eq!(Project::parse_color("#ééé"), None);
+
+        let mut project = Project::new("  Garden ", [255, 136, 0]);
+        assert_eq!((project.name(), project.hex_color().as_str()), ("Garden", "#ff8800"));
+        project.set_color([5, 217, 232]);
+        project.set_name("Yard ");
+        assert_eq!(project.name(), "Yard");
+        assert_eq!(project.hex_color(), "#05d9e8");
+    }
+}
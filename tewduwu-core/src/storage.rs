@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+use super::todo_list::TodoList;
+use super::workspace::Workspace;
+
+/// Default location of the data file
+///
+/// In the browser there is no filesystem; paths are used as localStorage keys instead.
+pub fn default_data_path() -> PathBuf {
+    PathBuf::from("tewduwu.json")
+}
+
+/// Serialize a TodoList to a JSON string
+pub fn to_json(list: &TodoList) -> Result<String, String> {
+    serde_json::to_string_pretty(list).map_err(|e| format!("Failed to serialize todo list: {}", e))
+}
+
+/// Deserialize a TodoList from a JSON string
+pub fn from_json(json: &str) -> Result<TodoList, String> {
+    serde_json::from_str(json).map_err(|e| format!("Failed to parse todo list: {}", e))
+}
+
+/// Save a TodoList as JSON to the given path
+pub fn save_to_path(list: &TodoList, path: &Path) -> Result<(), String> {
+    let json = to_json(list)?;
+    write_text(path, &json)
+}
+
+/// Load a TodoList from the given path
+///
+/// Returns `Ok(None)` if nothing has been saved there yet.
+pub fn load_from_path(path: &Path) -> Result<Option<TodoList>, String> {
+    match read_text(path)? {
+        Some(json) => from_json(&json).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Serialize a Workspace to a JSON string
+pub fn workspace_to_json(workspace: &Workspace) -> Result<String, String> {
+    serde_json::to_string_pretty(workspace).map_err(|e| format!("Failed to serialize workspace: {}", e))
+}
+
+/// Deserialize a Workspace from a JSON string, which may also hold a single list
+pub fn workspace_from_json(json: &str) -> Result<Workspace, String> {
+    serde_json::from_str(json).map_err(|e| format!("Failed to parse workspace: {}", e))
+}
+
+/// Save a Workspace as JSON to the given path
+pub fn save_workspace(workspace: &Workspace, path: &Path) -> Result<(), String> {
+    let json = workspace_to_json(workspace)?;
+    write_text(path, &json)
+}
+
+/// Load a Workspace from the given path, wrapping a single saved list in one
+///
+/// Returns `Ok(None)` if nothing has been saved there yet.
+pub fn load_workspace(path: &Path) -> Result<Option<Workspace>, String> {
+    match read_text(path)? {
+        Some(json) => workspace_from_json(&json).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Write text to a path, replacing it atomically on native platforms
+/// so a crash mid-write never leaves a truncated file behind
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_text(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    std::fs::write(&tmp_path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Read text from a path, returning `Ok(None)` if it doesn't exist
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_text(path: &Path) -> Result<Option<String>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Remove the file at a path; a missing file is not an error
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(path: &Path) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {}", path.display(), e)),
+    }
+}
+
+/// localStorage key standing in for a path in the browser
+#[cfg(target_arch = "wasm32")]
+fn local_storage_key(path: &Path) -> String {
+    format!("tewduwu:{}", path.display())
+}
+
+/// Get the browser's localStorage, if available
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or_else(|| "No browser window available".to_string())?
+        .local_storage()
+        .map_err(|_| "Access to localStorage was denied".to_string())?
+        .ok_or_else(|| "localStorage is not available".to_string())
+}
+
+/// Write text into the localStorage entry for a path
+#[cfg(target_arch = "wasm32")]
+pub fn write_text(path: &Path, contents: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(&local_storage_key(path), contents)
+        .map_err(|_| "Failed to write to localStorage (quota exceeded?)".to_string())
+}
+
+/// Read text from the localStorage entry for a path
+#[cfg(target_arch = "wasm32")]
+pub fn read_text(path: &Path) -> Result<Option<String>, String> {
+    local_storage()?
+        .get_item(&local_storage_key(path))
+        .map_err(|_| "Failed to read from localStorage".to_string())
+}
+
+/// Remove the localStorage entry for a path
+#[cfg(target_arch = "wasm32")]
+pub fn remove(path: &Path) -> Result<(), String> {
+    local_storage()?
+        .remove_item(&local_storage_key(path))
+        .map_err(|_| "Failed to remove from localStorage".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut list = TodoList::new("Round Trip");
+        let parent_id = list.create_item("Parent");
+        let child_id = list.create_item("Child");
+        list.move_item(child_id, Some(parent_id)).unwrap();
+
+        let json = to_json(&list).unwrap();
+        let loaded = from_json(&json).unwrap();
+
+        assert_eq!(loaded.name(), "Round Trip");
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.child_ids(parent_id).contains(&child_id));
+    }
+
+    #[test]
+    fn test_save_and_load_path() {
+        let path = std::env::temp_dir().join(format!("tewduwu-test-{}.json", uuid::Uuid::new_v4()));
+        let mut list = TodoList::new("Saved");
+        list.create_item("Task");
+
+        save_to_path(&list, &path).unwrap();
+        let loaded = load_from_path(&path).unwrap().expect("file should exist");
+        assert_eq!(loaded.len(), 1);
+
+        remove(&path).unwrap();
+        assert!(load_from_path(&path).unwrap().is_none());
+    }
+}
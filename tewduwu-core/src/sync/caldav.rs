@@ -0,0 +1,771 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use chrono::{NaiveDate, NaiveDateTime};
+use uuid::Uuid;
+use crate::command::Command;
+use crate::todo_item::{TodoItem, Status, Priority, RecurrenceRule, Attachment};
+use crate::todo_list::TodoList;
+
+/// Metadata key holding a task's UID on the server when it isn't a UUID,
+/// e.g. for tasks created in other apps
+pub const UID_KEY: &str = "caldav-uid";
+
+/// How iCalendar writes UTC times
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Longest line iCalendar allows, in bytes; longer ones are folded
+const MAX_LINE: usize = 75;
+
+/// Where a task lives on the server and which version of it that is
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Resource {
+    pub href: String,
+    pub etag: String,
+}
+
+/// A calendar object as the server sent it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteObject {
+    pub resource: Resource,
+    /// iCalendar text holding a VTODO
+    pub data: String,
+}
+
+/// A task as it was at the end of the last sync
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncedTask {
+    pub resource: Resource,
+    /// The local item's `updated_at` then
+    pub modified: u64,
+    /// The local item's revision then
+    #[serde(default)]
+    pub revision: u64,
+}
+
+/// What the last sync left each side at, kept with the list it describes
+///
+/// Against it, a sync tells a task changed on one side from one changed on
+/// both, and one deleted on one side from one added on the other.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncState {
+    tasks: BTreeMap<Uuid, SyncedTask>,
+}
+
+impl SyncState {
+    /// Whether nothing has been synced yet
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+}
+
+/// A CalDAV task collection, e.g. a Nextcloud Tasks list
+///
+/// The engine only needs these three requests, so tests can stand in for a server.
+pub trait CalDavServer {
+    /// Every VTODO in the collection
+    fn fetch_all(&self) -> impl Future<Output = Result<Vec<RemoteObject>, String>> + Send;
+
+    /// Create a task, or overwrite the version `existing` names, returning where it went
+    fn put(&self, uid: &str, data: &str, existing: Option<&Resource>) -> impl Future<Output = Result<Resource, String>> + Send;
+
+    /// Delete the version of a task `resource` names
+    fn delete(&self, resource: &Resource) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+/// What a sync did, and what's left to do locally
+#[derive(Debug, Clone, Default)]
+pub struct SyncOutcome {
+    /// Changes pulled from the server, to run on the list in order, e.g. as a batch
+    pub commands: Vec<Command>,
+    /// The state to keep once the commands ran
+    pub state: SyncState,
+    pub pushed: usize,
+    pub pulled: usize,
+    /// Tasks changed on both sides; the one changed last won
+    pub conflicts: usize,
+    /// Objects on the server that aren't tasks tewduwu can read
+    pub skipped: usize,
+}
+
+/// Bring a list and a server collection in step
+///
+/// Local changes are pushed right away; changes from the server come back as
+/// commands, so they go through the list's dispatcher and can be undone like
+/// any other edit. A task changed on both sides since the last sync takes the
+/// version modified last; one deleted on one side is deleted on the other,
+/// unless the other side changed it since, in which case it's brought back,
+/// as a root task if its parent is gone.
+pub async fn sync<S: CalDavServer>(list: &TodoList, state: &SyncState, server: &S) -> Result<SyncOutcome, String> {
+    let mut outcome = SyncOutcome::default();
+    let mut remote = HashMap::new();
+    for object in server.fetch_all().await? {
+        match from_vtodo(&object.data) {
+            Ok(item) => {
+                remote.insert(item.id(), (object.resource, item));
+            }
+            Err(_) => outcome.skipped += 1,
+        }
+    }
+
+    let ids: BTreeSet<Uuid> = list
+        .all_items()
+        .iter()
+        .map(|item| item.id())
+        .chain(remote.keys().copied())
+        .chain(state.tasks.keys().copied())
+        .collect();
+    let mut pulled = Vec::new();
+    let mut removed = HashSet::new();
+    for id in ids {
+        let local = list.get_item(id);
+        let remote = remote.get(&id);
+        let synced = state.tasks.get(&id);
+        let local_changed = match (local, synced) {
+            (Some(item), Some(synced)) => item.revision() != synced.revision || item.updated_at() != synced.modified,
+            (local, _) => local.is_some(),
+        };
+        let remote_changed = match (remote, synced) {
+            (Some((resource, _)), Some(synced)) => resource.etag != synced.resource.etag,
+            (remote, _) => remote.is_some(),
+        };
+
+        let push = match (local, remote) {
+            (Some(item), Some((_, remote_item))) => match (local_changed, remote_changed) {
+                (false, false) => {
+                    outcome.state.tasks.extend(synced.map(|synced| (id, synced.clone())));
+                    continue;
+                }
+                (true, true) => {
+                    outcome.conflicts += 1;
+                    item.updated_at() >= remote_item.updated_at()
+                }
+                (local_changed, _) => local_changed,
+            },
+            // Deleted on the server; brought back if changed here since
+            (Some(_), None) if synced.is_some() && !local_changed => {
+                removed.insert(id);
+                continue;
+            }
+            (Some(_), None) => true,
+            // Deleted here; brought back if changed on the server since
+            (None, Some((resource, _))) if synced.is_some() && !remote_changed => {
+                server.delete(resource).await?;
+                continue;
+            }
+            (None, Some(_)) => false,
+            (None, None) => continue,
+        };
+
+        if push {
+            let item = local.expect("only local tasks are pushed");
+            let resource = server.put(&uid(item), &to_vtodo(item), remote.map(|(resource, _)| resource)).await?;
+            outcome.state.tasks.insert(id, SyncedTask { resource, modified: item.updated_at(), revision: item.revision() });
+            outcome.pushed += 1;
+        } else {
+            let (resource, item) = remote.expect("only remote tasks are pulled");
+            outcome.state.tasks.insert(id, SyncedTask { resource: resource.clone(), modified: item.updated_at(), revision: item.revision() });
+            pulled.push(item.clone());
+            outcome.pulled += 1;
+        }
+    }
+
+    outcome.commands = pull_commands(list, pulled, &removed);
+    Ok(outcome)
+}
+
+/// Commands that add or replace the pulled tasks and remove the ones deleted
+/// on the server, in an order the list accepts
+fn pull_commands(list: &TodoList, pulled: Vec<TodoItem>, removed: &HashSet<Uuid>) -> Vec<Command> {
+    let is_removed = |mut id: Uuid| loop {
+        if removed.contains(&id) {
+            return true;
+        }
+        match list.get_item(id).and_then(TodoItem::parent_id) {
+            Some(parent) => id = parent,
+            None => return false,
+        }
+    };
+    let added: HashSet<Uuid> = pulled.iter().map(TodoItem::id).filter(|id| list.get_item(*id).is_none()).collect();
+    let kept = |id: Uuid| added.contains(&id) || (list.get_item(id).is_some() && !is_removed(id));
+
+    let (mut adds, mut replaces) = (Vec::new(), Vec::new());
+    for mut item in pulled {
+        // A parent that won't be there makes it a root task, without counting as a change
+        if item.parent_id().is_some_and(|parent| !kept(parent)) {
+            let modified = item.updated_at();
+            item.set_parent_id(None);
+            item = item.with_updated_at(modified);
+        }
+        if added.contains(&item.id()) {
+            adds.push(item);
+        } else {
+            replaces.push(item);
+        }
+    }
+
+    // New tasks after their new parents
+    let mut commands = Vec::new();
+    let mut placed: HashSet<Uuid> = HashSet::new();
+    while !adds.is_empty() {
+        let (ready, waiting): (Vec<TodoItem>, Vec<TodoItem>) = adds
+            .into_iter()
+            .partition(|item| item.parent_id().is_none_or(|parent| !added.contains(&parent) || placed.contains(&parent)));
+        if ready.is_empty() {
+            // Parents that point at each other; the server's data is at fault
+            commands.extend(waiting.into_iter().map(|mut item| {
+                let modified = item.updated_at();
+                item.set_parent_id(None);
+                Command::AddItem { item: item.with_updated_at(modified) }
+            }));
+            break;
+        }
+        placed.extend(ready.iter().map(TodoItem::id));
+        commands.extend(ready.into_iter().map(|item| Command::AddItem { item }));
+        adds = waiting;
+    }
+    // Subtasks staying here, e.g. changed since, would go along with a removed
+    // parent, so they become root tasks first; pulled ones already are
+    let replaced: HashSet<Uuid> = replaces.iter().map(TodoItem::id).collect();
+    let mut orphans: Vec<Uuid> = list
+        .all_items()
+        .iter()
+        .filter(|item| !removed.contains(&item.id()) && !replaced.contains(&item.id()))
+        .filter(|item| item.parent_id().is_some_and(|parent| removed.contains(&parent)))
+        .map(|item| item.id())
+        .collect();
+    orphans.sort();
+    commands.extend(replaces.into_iter().map(|item| Command::ReplaceItem { item }));
+    commands.extend(orphans.into_iter().map(|id| Command::Move { id, parent_id: None }));
+    // Removing a task takes its subtasks along, so those aren't removed on their own
+    let mut removed: Vec<Uuid> = removed
+        .iter()
+        .copied()
+        .filter(|id| list.get_item(*id).and_then(TodoItem::parent_id).is_none_or(|parent| !is_removed(parent)))
+        .collect();
+    removed.sort();
+    commands.extend(removed.into_iter().map(|id| Command::RemoveItem { id }));
+    commands
+}
+
+/// The UID a task has on the server
+///
+/// A kept UID only counts for the task it came with, not for the next
+/// occurrences of a repeating one spawned while they still copied it.
+fn uid(item: &TodoItem) -> String {
+    item.metadata()
+        .get(UID_KEY)
+        .filter(|uid| id_for_uid(uid) == item.id())
+        .cloned()
+        .unwrap_or_else(|| item.id().to_string())
+}
+
+/// The id of the task with a UID; UIDs that aren't UUIDs always map to the same one
+fn id_for_uid(uid: &str) -> Uuid {
+    Uuid::parse_str(uid).unwrap_or_else(|_| Uuid::new_v5(&Uuid::NAMESPACE_URL, uid.as_bytes()))
+}
+
+/// Write a task as an iCalendar object holding one VTODO
+pub fn to_vtodo(item: &TodoItem) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//tewduwu//tewduwu-neon//EN".to_string(),
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", escape(&uid(item))),
+        format!("DTSTAMP:{}", format_date(item.updated_at())),
+        format!("CREATED:{}", format_date(item.created_at())),
+        format!("LAST-MODIFIED:{}", format_date(item.updated_at())),
+        format!("SUMMARY:{}", escape(item.title())),
+    ];
+    if let Some(description) = item.description() {
+        lines.push(format!("DESCRIPTION:{}", escape(description)));
+    }
+    lines.push(format!("STATUS:{}", match item.status() {
+        Status::NotStarted => "NEEDS-ACTION",
+        Status::InProgress => "IN-PROCESS",
+        Status::Completed => "COMPLETED",
+    }));
+    if let Some(completed) = item.completed_at() {
+        lines.push(format!("COMPLETED:{}", format_date(completed)));
+    }
+    lines.push(format!("PRIORITY:{}", match item.priority() {
+        Priority::High => 1,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }));
+    if let Some(due) = item.due_date() {
+        lines.push(format!("DUE:{}", format_date(due)));
+    }
+    if !item.tags().is_empty() {
+        let tags: Vec<String> = item.tags().iter().map(|tag| escape(tag)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if let Some(parent) = item.parent_id() {
+        lines.push(format!("RELATED-TO;RELTYPE=PARENT:{}", parent));
+    }
+    // Files go as `file://` addresses, which only open on computers that have them
+    for attachment in item.attachments() {
+        lines.push(match attachment {
+            Attachment::File(path) => format!("ATTACH:file://{}", path.to_string_lossy()),
+            Attachment::Url(url) => format!("ATTACH:{}", url),
+        });
+    }
+    if let Some(recurrence) = item.recurrence() {
+        lines.push(format!("RRULE:{}", match recurrence {
+            RecurrenceRule::Daily => "FREQ=DAILY".to_string(),
+            RecurrenceRule::Weekly => "FREQ=WEEKLY".to_string(),
+            RecurrenceRule::Monthly => "FREQ=MONTHLY".to_string(),
+            RecurrenceRule::EveryNDays(days) => format!("FREQ=DAILY;INTERVAL={}", days),
+        }));
+    }
+    lines.extend(["END:VTODO".to_string(), "END:VCALENDAR".to_string()]);
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Read the VTODO of an iCalendar object
+///
+/// Properties tewduwu has no use for, and nested components such as alarms,
+/// are skipped. A UID that isn't a UUID is kept in the metadata, so the task
+/// goes back to the server under the same one.
+pub fn from_vtodo(text: &str) -> Result<TodoItem, String> {
+    let mut properties: Vec<(String, String, String)> = Vec::new();
+    let mut depth = 0;
+    let mut in_todo = false;
+    for line in unfold(text) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VTODO") if !in_todo => in_todo = true,
+            ("BEGIN", _) if in_todo => depth += 1,
+            ("END", "VTODO") if in_todo && depth == 0 => break,
+            ("END", _) if in_todo => depth -= 1,
+            _ if in_todo && depth == 0 => properties.push((name, params, value)),
+            _ => {}
+        }
+    }
+    if !in_todo {
+        return Err("No VTODO in the calendar object".to_string());
+    }
+    let property = |wanted: &str| properties.iter().find(|(name, _, _)| name == wanted).map(|(_, _, value)| value.as_str());
+    let uid = property("UID").ok_or("The VTODO has no UID")?;
+
+    let status = match property("STATUS") {
+        Some("COMPLETED" | "CANCELLED") => Status::Completed,
+        Some("IN-PROCESS") => Status::InProgress,
+        _ => Status::NotStarted,
+    };
+    let priority = match property("PRIORITY").and_then(|priority| priority.trim().parse::<u8>().ok()) {
+        Some(1..=4) => Priority::High,
+        Some(6..=9) => Priority::Low,
+        _ => Priority::Medium,
+    };
+    let mut item = TodoItem::new(&unescape(property("SUMMARY").unwrap_or_default()))
+        .with_id(id_for_uid(uid))
+        .with_status(status)
+        .with_priority(priority);
+    if Uuid::parse_str(uid).is_err() {
+        item.set_metadata(UID_KEY, uid);
+    }
+    if let Some(description) = property("DESCRIPTION") {
+        item.set_description(Some(&unescape(description)));
+    }
+    if let Some(created) = property("CREATED").and_then(parse_date) {
+        item = item.with_created_at(created);
+    }
+    if let Some(completed) = property("COMPLETED").and_then(parse_date) {
+        item = item.with_completed_at(completed);
+    }
+    if let Some(due) = property("DUE").and_then(parse_date) {
+        item = item.with_due_date(due);
+    }
+    if let Some(recurrence) = property("RRULE").and_then(parse_rule) {
+        item = item.with_recurrence(recurrence);
+    }
+    let parent = properties.iter().find(|(name, params, _)| {
+        name == "RELATED-TO" && !params.to_ascii_uppercase().contains("RELTYPE=") || params.to_ascii_uppercase().contains("RELTYPE=PARENT")
+    });
+    if let Some((_, _, parent)) = parent {
+        item = item.with_parent(id_for_uid(parent.trim()));
+    }
+    for (_, _, categories) in properties.iter().filter(|(name, _, _)| name == "CATEGORIES") {
+        for tag in split_unescaped(categories, ',') {
+            item.add_tag(&unescape(&tag));
+        }
+    }
+    // Files sent inline, rather than by address, are left on the server
+    let attachments = properties.iter().filter(|(name, params, _)| {
+        name == "ATTACH" && !params.to_ascii_uppercase().contains("VALUE=BINARY")
+    });
+    for (_, _, address) in attachments {
+        if let Some(attachment) = Attachment::parse(address) {
+            item.add_attachment(attachment);
+        }
+    }
+    // Last, as building the item counts as changing it
+    let modified = property("LAST-MODIFIED").or(property("DTSTAMP")).and_then(parse_date);
+    let modified = modified.unwrap_or_else(|| item.updated_at());
+    Ok(item.with_updated_at(modified))
+}
+
+/// Join folded lines back up; a line starting with a space or tab continues the one before
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)) {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Break a line into 75 byte pieces, each after the first starting with a space, ending in CRLF
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 4);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Split `NAME;PARAM=x:value` into its name, upper case, its parameters and its value
+fn split_property(line: &str) -> Option<(String, String, String)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find(|&(_, c)| {
+        quoted ^= c == '"';
+        c == ':' && !quoted
+    })?.0;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.trim().to_ascii_uppercase(), params.to_string(), value.to_string()))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(other) => unescaped.push(other),
+                None => unescaped.push('\\'),
+            },
+            (c, false) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Split at separators that aren't escaped with a backslash
+fn split_unescaped(text: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in text.chars() {
+        if c == separator && !escaped {
+            parts.push(String::new());
+            continue;
+        }
+        escaped = c == '\\' && !escaped;
+        parts.last_mut().expect("starts with one part").push(c);
+    }
+    parts
+}
+
+/// Read a UTC or floating date-time, or a date as its midnight; time zones are taken as UTC
+fn parse_date(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let date = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d").map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default()))
+        .ok()?;
+    u64::try_from(date.and_utc().timestamp()).ok()
+}
+
+fn format_date(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .unwrap_or_default()
+        .format(DATE_TIME_FORMAT)
+        .to_string()
+}
+
+/// The repeats tewduwu has among an RRULE's, e.g. `FREQ=WEEKLY;INTERVAL=2` is every 14 days
+fn parse_rule(rule: &str) -> Option<RecurrenceRule> {
+    let parts: HashMap<String, &str> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.to_ascii_uppercase(), value))
+        .collect();
+    let interval: u32 = parts.get("INTERVAL").map_or(Some(1), |interval| interval.parse().ok())?;
+    match (parts.get("FREQ").map(|freq| freq.to_ascii_uppercase()).as_deref(), interval) {
+        (_, 0) => None,
+        (Some("DAILY"), 1) => Some(RecurrenceRule::Daily),
+        (Some("DAILY"), days) => Some(RecurrenceRule::EveryNDays(days)),
+        (Some("WEEKLY"), 1) => Some(RecurrenceRule::Weekly),
+        (Some("WEEKLY"), weeks) => Some(RecurrenceRule::EveryNDays(weeks * 7)),
+        (Some("MONTHLY"), 1) => Some(RecurrenceRule::Monthly),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// A collection kept in memory
+    #[derive(Default)]
+    struct FakeServer {
+        objects: Mutex<BTreeMap<String, RemoteObject>>,
+        version: Mutex<u32>,
+    }
+
+    impl FakeServer {
+        fn next_etag(&self) -> String {
+            let mut version = self.version.lock().unwrap();
+            *version += 1;
+            format!("\"{}\"", version)
+        }
+
+        /// Change a task on the server, as another app would
+        fn edit(&self, id: Uuid, change: impl FnOnce(&mut TodoItem)) {
+            let href = format!("/tasks/{}.ics", id);
+            let etag = self.next_etag();
+            let mut objects = self.objects.lock().unwrap();
+            let object = objects.get_mut(&href).unwrap();
+            let mut item = from_vtodo(&object.data).unwrap();
+            change(&mut item);
+            object.data = to_vtodo(&item);
+            object.resource.etag = etag;
+        }
+
+        fn titles(&self) -> Vec<String> {
+            let objects = self.objects.lock().unwrap();
+            let mut titles: Vec<String> = objects.values().map(|object| from_vtodo(&object.data).unwrap().title().to_string()).collect();
+            titles.sort();
+            titles
+        }
+    }
+
+    impl CalDavServer for FakeServer {
+        async fn fetch_all(&self) -> Result<Vec<RemoteObject>, String> {
+            Ok(self.objects.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn put(&self, uid: &str, data: &str, existing: Option<&Resource>) -> Result<Resource, String> {
+            let href = existing.map_or_else(|| format!("/tasks/{}.ics", uid), |existing| existing.href.clone());
+            let resource = Resource { href: href.clone(), etag: self.next_etag() };
+            self.objects.lock().unwrap().insert(href, RemoteObject { resource: resource.clone(), data: data.to_string() });
+            Ok(resource)
+        }
+
+        async fn delete(&self, resource: &Resource) -> Result<(), String> {
+            self.objects.lock().unwrap().remove(&resource.href);
+            Ok(())
+        }
+    }
+
+    /// Drive a future that never waits, as the fake server's don't
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = std::task::Waker::noop();
+        let mut context = std::task::Context::from_waker(waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    /// Sync and run the pulled changes, as the app does
+    fn sync_list(list: &mut TodoList, server: &FakeServer) -> SyncOutcome {
+        let state = list.sync_state().clone();
+        let outcome = block_on(sync(list, &state, server)).unwrap();
+        Command::Batch(outcome.commands.clone()).apply(list).unwrap();
+        list.set_sync_state(outcome.state.clone());
+        list.check_invariants().unwrap();
+        outcome
+    }
+
+    fn titles(list: &TodoList) -> Vec<String> {
+        let mut titles: Vec<String> = list.all_items().iter().map(|item| item.title().to_string()).collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn test_vtodo_round_trip() {
+        let parent = Uuid::new_v4();
+        let item = TodoItem::new("Buy milk; eggs, bread \\ more")
+            .with_description("Line one\nLine two, with a very long tail that goes well past seventy-five bytes so it has to be folded")
+            .with_priority(Priority::High)
+            .with_status(Status::InProgress)
+            .with_due_date(1_709_658_000)
+            .with_recurrence(RecurrenceRule::EveryNDays(3))
+            .with_tag("errands")
+            .with_tag("a,b")
+            .with_attachment(Attachment::File("/home/me/list.txt".into()))
+            .with_attachment(Attachment::Url("https://example.com/shop".to_string()))
+            .with_parent(parent);
+        let text = to_vtodo(&item);
+        assert!(text.lines().all(|line| line.len() <= MAX_LINE + 1));
+        assert_eq!(from_vtodo(&text).unwrap(), item);
+    }
+
+    #[test]
+    fn test_reads_other_apps_tasks() {
+        let text = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:20240301-abc@example.com\r\nSUMMARY:Call the ba\r\n nk\r\n\
+                    STATUS:NEEDS-ACTION\r\nPRIORITY:3\r\nDUE;VALUE=DATE:20240305\r\nCATEGORIES:Work,Phone\r\n\
+                    RRULE:FREQ=WEEKLY;INTERVAL=2\r\nLAST-MODIFIED:20240301T090000Z\r\n\
+                    ATTACH;FMTTYPE=text/plain:https://example.com/agenda.txt\r\nATTACH;ENCODING=BASE64;VALUE=BINARY:aGk=\r\n\
+                    BEGIN:VALARM\r\nSUMMARY:Not the title\r\nEND:VALARM\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let item = from_vtodo(text).unwrap();
+        assert_eq!(item.title(), "Call the bank");
+        assert_eq!(item.priority(), Priority::High);
+        assert_eq!(item.due_date(), Some(1_709_596_800));
+        assert_eq!(item.tags(), ["Work", "Phone"]);
+        assert_eq!(item.recurrence(), Some(RecurrenceRule::EveryNDays(14)));
+        assert_eq!(item.attachments(), [Attachment::Url("https://example.com/agenda.txt".to_string())]);
+        assert_eq!(item.updated_at(), 1_709_283_600.max(item.created_at()));
+        // The same UID always maps to the same task, and goes back out unchanged
+        assert_eq!(item.id(), from_vtodo(text).unwrap().id());
+        assert!(to_vtodo(&item).contains("UID:20240301-abc@example.com\r\n"));
+        let next = item.next_occurrence().unwrap();
+        assert!(to_vtodo(&next).contains(&format!("UID:{}\r\n", next.id())));
+        assert!(from_vtodo("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n").is_err());
+    }
+
+    #[test]
+    fn test_changes_flow_both_ways() {
+        let server = FakeServer::default();
+        let mut list = TodoList::new("Sync");
+        let milk = list.add_item(TodoItem::new("Buy milk"));
+        let call = list.add_item(TodoItem::new("Call mom"));
+        let outcome = sync_list(&mut list, &server);
+        assert_eq!((outcome.pushed, outcome.pulled), (2, 0));
+        assert_eq!(server.titles(), ["Buy milk", "Call mom"]);
+
+        // Nothing changed, nothing to do
+        let outcome = sync_list(&mut list, &server);
+        assert_eq!((outcome.pushed, outcome.pulled), (0, 0));
+        assert!(outcome.commands.is_empty());
+
+        // Completed elsewhere, renamed here
+        server.edit(milk, |item| item.set_status(Status::Completed));
+        list.get_item_mut(call).unwrap().set_title("Call dad");
+        let outcome = sync_list(&mut list, &server);
+        assert_eq!((outcome.pushed, outcome.pulled), (1, 1));
+        assert!(list.get_item(milk).unwrap().is_completed());
+        assert_eq!(server.titles(), ["Buy milk", "Call dad"]);
+
+        // Added on the server
+        let mut other = TodoList::new("Other app");
+        let subtask = TodoItem::new("Whole milk").with_parent(milk);
+        other.add_item(subtask.clone());
+        block_on(server.put(&subtask.id().to_string(), &to_vtodo(&subtask), None)).unwrap();
+        sync_list(&mut list, &server);
+        assert_eq!(list.get_item(subtask.id()).unwrap().parent_id(), Some(milk));
+    }
+
+    #[test]
+    fn test_deletions_and_conflicts() {
+        let server = FakeServer::default();
+        let mut list = TodoList::new("Sync");
+        let keep = list.add_item(TodoItem::new("Keep"));
+        let gone_there = list.add_item(TodoItem::new("Deleted on the server"));
+        let gone_here = list.add_item(TodoItem::new("Deleted here"));
+        sync_list(&mut list, &server);
+
+        let resource = server.objects.lock().unwrap().values().find(|object| object.data.contains(&gone_there.to_string())).unwrap().resource.clone();
+        block_on(server.delete(&resource)).unwrap();
+        list.remove_item(gone_here);
+        sync_list(&mut list, &server);
+        assert_eq!(titles(&list), ["Keep"]);
+        assert_eq!(server.titles(), ["Keep"]);
+        assert_eq!(list.sync_state().len(), 1);
+
+        // Changed on both sides: the later change wins
+        list.get_item_mut(keep).unwrap().set_title("Keep here");
+        let later = list.get_item(keep).unwrap().updated_at() + 60;
+        server.edit(keep, |item| {
+            item.set_title("Keep there");
+            *item = item.clone().with_updated_at(later);
+        });
+        let outcome = sync_list(&mut list, &server);
+        assert_eq!(outcome.conflicts, 1);
+        assert_eq!(titles(&list), ["Keep there"]);
+
+        // Undoing the pulled change pushes the undone version
+        let undo = Command::ReplaceItem { item: TodoItem::new("Keep").with_id(keep) };
+        undo.apply(&mut list).unwrap();
+        sync_list(&mut list, &server);
+        assert_eq!(server.titles(), ["Keep"]);
+    }
+
+    #[test]
+    fn test_subtask_changed_here_outlives_parent_deleted_there() {
+        let server = FakeServer::default();
+        let mut list = TodoList::new("Sync");
+        let parent = list.add_item(TodoItem::new("Groceries"));
+        let child = list.add_item(TodoItem::new("Milk").with_parent(parent));
+        let other = list.add_item(TodoItem::new("Eggs").with_parent(parent));
+        sync_list(&mut list, &server);
+
+        // The server deletes the parent and one subtask, the other is edited here
+        for id in [parent, other] {
+            let href = format!("/tasks/{}.ics", id);
+            server.objects.lock().unwrap().remove(&href);
+        }
+        list.get_item_mut(child).unwrap().set_title("Oat milk");
+        sync_list(&mut list, &server);
+        assert_eq!(titles(&list), ["Oat milk"]);
+        assert_eq!(list.get_item(child).unwrap().parent_id(), None);
+
+        // And it stays on both sides, now as a root task
+        sync_list(&mut list, &server);
+        sync_list(&mut list, &server);
+        assert_eq!(titles(&list), ["Oat milk"]);
+        assert_eq!(server.titles(), ["Oat milk"]);
+        let object = server.objects.lock().unwrap().values().next().unwrap().data.clone();
+        assert!(!object.contains("RELATED-TO"));
+    }
+
+    #[test]
+    fn test_many_edits_here_dont_beat_a_later_one_there() {
+        let server = FakeServer::default();
+        let mut list = TodoList::new("Sync");
+        let task = list.add_item(TodoItem::new("Draft"));
+        sync_list(&mut list, &server);
+
+        for n in 0..50 {
+            list.get_item_mut(task).unwrap().set_title(&format!("Draft {}", n));
+        }
+        let later = crate::todo_item::now_timestamp() + 1;
+        server.edit(task, |item| {
+            item.set_title("Final");
+            *item = item.clone().with_updated_at(later);
+        });
+        let outcome = sync_list(&mut list, &server);
+        assert_eq!(outcome.conflicts, 1);
+        assert_eq!(titles(&list), ["Final"]);
+        assert_eq!(server.titles(), ["Final"]);
+    }
+}
@@ -0,0 +1,18 @@
+//! Keeping lists in step with task servers
+//!
+//! The engines here decide what to send and what to apply; the requests
+//! themselves are left to the frontend, behind a trait, so this crate stays
+//! free of HTTP clients and async runtimes.
+
+pub mod caldav;
+
+/// How syncing with a server went, for the UI to show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncStatus {
+    /// A sync is running
+    Syncing,
+    /// The last sync went through, at this Unix time
+    Synced { at: u64 },
+    /// The last sync failed
+    Failed { error: String },
+}
@@ -0,0 +1,1028 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, TimeZone};
+use std::fmt;
+use uuid::Uuid;
+
+/// Current Unix timestamp in seconds
+///
+/// Uses chrono rather than `std::time::SystemTime`, which panics on
+/// wasm32-unknown-unknown.
+pub(crate) fn now_timestamp() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+/// Metadata key of a task's log of finished focus sessions, kept by the app's focus timer
+pub const FOCUS_LOG_KEY: &str = "focus_sessions";
+
+/// Metadata about one occurrence of a repeating task, which the next starts without
+const OCCURRENCE_METADATA: [&str; 2] = [FOCUS_LOG_KEY, crate::sync::caldav::UID_KEY];
+
+/// Priority levels for todo items
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::Low => write!(f, "Low"),
+            Priority::Medium => write!(f, "Medium"),
+            Priority::High => write!(f, "High"),
+        }
+    }
+}
+
+/// Status of a todo item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Status {
+    #[default]
+    NotStarted,
+    InProgress,
+    Completed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::NotStarted => write!(f, "Not Started"),
+            Status::InProgress => write!(f, "In Progress"),
+            Status::Completed => write!(f, "Completed"),
+        }
+    }
+}
+
+/// How often a task comes back once it's completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum RecurrenceRule {
+    Daily,
+    Weekly,
+    /// Same day of the month, or the month's last day if it's shorter
+    Monthly,
+    EveryNDays(u32),
+}
+
+impl RecurrenceRule {
+    /// The due date following `due`, one interval later on the calendar of `zone`
+    ///
+    /// The date moves and the time of day stays, across daylight saving time
+    /// changes too. Monthly ones fall on `day` of the month, `due`'s own day
+    /// if `None`, or the month's last day if it's shorter.
+    pub fn next_after<Tz: TimeZone>(self, due: u64, day: Option<u32>, zone: &Tz) -> u64 {
+        let Some(local) = DateTime::from_timestamp(due as i64, 0).map(|due| due.with_timezone(zone).naive_local()) else {
+            return due + self.approximate_days() * DAY;
+        };
+        let date = match self {
+            RecurrenceRule::Monthly => day_of_next_month(local.date(), day.unwrap_or(local.day())),
+            _ => local.date().checked_add_days(Days::new(self.approximate_days())),
+        };
+        let next = date.map(|date| date.and_time(local.time())).and_then(|next| {
+            // A time the clocks skip is an hour later that day
+            zone.from_local_datetime(&next)
+                .earliest()
+                .or_else(|| zone.from_local_datetime(&(next + chrono::TimeDelta::hours(1))).earliest())
+        });
+        next.and_then(|next| u64::try_from(next.timestamp()).ok())
+            .unwrap_or(due + self.approximate_days() * DAY)
+    }
+
+    /// Due date of the occurrence after one due at `due`, at least an interval after `now`
+    ///
+    /// Occurrences that would already be overdue are skipped, so a daily task
+    /// finished three days late comes back tomorrow rather than three times.
+    /// Without a due date, the interval counts from `now`.
+    pub fn next_due<Tz: TimeZone>(self, due: Option<u64>, day: Option<u32>, now: u64, zone: &Tz) -> u64 {
+        let mut next = self.next_after(due.unwrap_or(now), day, zone);
+        while next <= now {
+            next = self.next_after(next, day, zone);
+        }
+        next
+    }
+
+    /// Days from one occurrence to the next, taking months as 30 days
+    fn approximate_days(self) -> u64 {
+        match self {
+            RecurrenceRule::Daily => 1,
+            RecurrenceRule::Weekly => 7,
+            RecurrenceRule::Monthly => 30,
+            RecurrenceRule::EveryNDays(days) => u64::from(days.max(1)),
+        }
+    }
+}
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// `day` of the month after `date`'s, or that month's last day if it's shorter
+fn day_of_next_month(date: NaiveDate, day: u32) -> Option<NaiveDate> {
+    let first = date.with_day(1)?.checked_add_months(Months::new(1))?;
+    (1..=day.min(31)).rev().find_map(|day| first.with_day(day))
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecurrenceRule::Daily => write!(f, "Daily"),
+            RecurrenceRule::Weekly => write!(f, "Weekly"),
+            RecurrenceRule::Monthly => write!(f, "Monthly"),
+            RecurrenceRule::EveryNDays(days) => write!(f, "Every {} days", days),
+        }
+    }
+}
+
+/// When to be reminded of a task before it's due
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Reminder {
+    /// As far ahead as the app is set to remind of every task
+    #[default]
+    Default,
+    /// This many minutes before it's due
+    MinutesBefore(u32),
+    /// Not at all, not even once it's overdue
+    Off,
+}
+
+/// A file or web page a task refers to
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Attachment {
+    /// A file, by its path on this computer
+    File(std::path::PathBuf),
+    /// Anything with an address, such as a web page
+    Url(String),
+}
+
+impl Attachment {
+    /// An address if the text starts with a scheme such as `https:`, else a
+    /// file path; `file://` addresses are file paths too
+    ///
+    /// Returns `None` for blank text. A single letter before the colon is a
+    /// Windows drive rather than a scheme.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        if let Some(path) = text.strip_prefix("file://") {
+            return Some(Attachment::File(path.into()));
+        }
+        let scheme = text.split_once(':').map_or("", |(scheme, _)| scheme);
+        let is_scheme = scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+        Some(if is_scheme { Attachment::Url(text.to_string()) } else { Attachment::File(text.into()) })
+    }
+
+    /// Short name to show: the file's name, or the address without its scheme
+    pub fn name(&self) -> String {
+        match self {
+            Attachment::File(path) => path
+                .file_name()
+                .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy())
+                .into_owned(),
+            Attachment::Url(url) => {
+                let rest = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+                rest.trim_end_matches('/').to_string()
+            }
+        }
+    }
+
+    /// What to hand the system to open it: the path or the address
+    pub fn target(&self) -> String {
+        match self {
+            Attachment::File(path) => path.to_string_lossy().into_owned(),
+            Attachment::Url(url) => url.clone(),
+        }
+    }
+}
+
+/// A TodoItem represents a single task in the todo list
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TodoItem {
+    /// Unique identifier for the item
+    id: Uuid,
+    
+    /// Title of the todo item
+    title: String,
+    
+    /// Optional detailed description
+    description: Option<String>,
+    
+    /// Current status of the item
+    status: Status,
+    
+    /// Priority level of the item
+    priority: Priority,
+    
+    /// Unix timestamp of when the item was created
+    created_at: u64,
+    
+    /// Unix timestamp of when the item is due, if any
+    due_date: Option<u64>,
+    
+    /// Unix timestamp of the last change, which sync compares to tell the newer side
+    #[serde(default, alias = "modified_at")]
+    updated_at: u64,
+    
+    /// Count of changes made here, which sync compares to tell whether the item
+    /// changed since it last saw it, even within the same second
+    #[serde(default)]
+    revision: u64,
+    
+    /// Unix timestamp of when the item was last completed, while it is
+    #[serde(default)]
+    completed_at: Option<u64>,
+    
+    /// Parent item ID for hierarchical structure
+    parent_id: Option<Uuid>,
+    
+    /// Additional metadata as key-value pairs
+    #[serde(default)]
+    metadata: std::collections::HashMap<String, String>,
+    
+    /// Labels such as a project or context, in the order they were added
+    #[serde(default)]
+    tags: Vec<String>,
+    
+    /// How the task repeats, if it does
+    #[serde(default)]
+    recurrence: Option<RecurrenceRule>,
+    
+    /// The completed occurrence this one was spawned from, for recurring tasks
+    #[serde(default)]
+    previous_occurrence: Option<Uuid>,
+    
+    /// Day of the month a repeating task started on, which monthly ones keep
+    /// falling on after a shorter month moved one earlier
+    #[serde(default)]
+    recurrence_day: Option<u32>,
+    
+    /// When to be reminded of it, if it has a due date
+    #[serde(default)]
+    reminder: Reminder,
+    
+    /// Files and web pages it refers to, in the order they were added
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+    
+    /// The project of its list it belongs to, if any
+    #[serde(default)]
+    project_id: Option<Uuid>,
+}
+
+/// Trim a tag and drop a leading `#`, or `None` if nothing is left
+fn clean_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').trim();
+    (!tag.is_empty()).then(|| tag.to_string())
+}
+
+/// Clean tags as an item keeps them: blank ones and repeats dropped, in order
+pub(crate) fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.iter().filter_map(|tag| clean_tag(tag)) {
+        if !cleaned.contains(&tag) {
+            cleaned.push(tag);
+        }
+    }
+    cleaned
+}
+
+/// Attachments as an item keeps them: repeats dropped, in order
+pub(crate) fn unique_attachments(attachments: Vec<Attachment>) -> Vec<Attachment> {
+    let mut unique: Vec<Attachment> = Vec::with_capacity(attachments.len());
+    for attachment in attachments {
+        if !unique.contains(&attachment) {
+            unique.push(attachment);
+        }
+    }
+    unique
+}
+
+impl TodoItem {
+    /// Create a new TodoItem with the given title
+    pub fn new(title: &str) -> Self {
+        let now = now_timestamp();
+            
+        TodoItem {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: None,
+            status: Status::default(),
+            priority: Priority::default(),
+            created_at: now,
+            due_date: None,
+            updated_at: now,
+            revision: 0,
+            completed_at: None,
+            parent_id: None,
+            metadata: std::collections::HashMap::new(),
+            tags: Vec::new(),
+            recurrence: None,
+            previous_occurrence: None,
+            recurrence_day: None,
+            reminder: Reminder::default(),
+            attachments: Vec::new(),
+            project_id: None,
+        }
+    }
+    
+    // --- Getters ---
+    
+    /// Get the item's unique ID
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+    
+    /// Get the item's title
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    
+    /// Get the item's description, if any
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    
+    /// Get the item's status
+    pub fn status(&self) -> Status {
+        self.status
+    }
+    
+    /// Get the item's priority
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+    
+    /// Get the item's creation timestamp
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+    
+    /// Get the item's due date, if any
+    pub fn due_date(&self) -> Option<u64> {
+        self.due_date
+    }
+    
+    /// Get when the item last changed; items saved before changes were timed report their creation
+    pub fn updated_at(&self) -> u64 {
+        self.updated_at.max(self.created_at)
+    }
+    
+    /// Get how many changes were made to the item here, since it was created
+    /// or came from another app
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+    
+    /// Get when the item was completed, if it is
+    ///
+    /// Items completed before this was recorded report when they last changed.
+    pub fn completed_at(&self) -> Option<u64> {
+        self.is_completed().then(|| self.completed_at.unwrap_or_else(|| self.updated_at()))
+    }
+    
+    /// Get the item's parent ID, if any
+    pub fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+    
+    /// Get a reference to the item's metadata
+    pub fn metadata(&self) -> &std::collections::HashMap<String, String> {
+        &self.metadata
+    }
+    
+    /// Get the item's tags
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+    
+    /// Check if the item has a tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own == tag)
+    }
+    
+    /// Get how the item repeats, if it does
+    pub fn recurrence(&self) -> Option<RecurrenceRule> {
+        self.recurrence
+    }
+    
+    /// Get when to be reminded of the item
+    pub fn reminder(&self) -> Reminder {
+        self.reminder
+    }
+    
+    /// Get the files and web pages the item refers to
+    pub fn attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+    
+    /// Get the ID of the project the item belongs to, if any
+    pub fn project_id(&self) -> Option<Uuid> {
+        self.project_id
+    }
+    
+    /// Get the ID of the occurrence this item was spawned from, if any
+    pub fn previous_occurrence(&self) -> Option<Uuid> {
+        self.previous_occurrence
+    }
+    
+    // --- Setters ---
+    //
+    // Each one counts as one change to the item, for sync.
+    
+    /// Record that the item changed just now
+    ///
+    /// The time is the clock's, never later, so it compares fairly with changes
+    /// made elsewhere; the revision tells apart changes within the same second.
+    fn touch(&mut self) {
+        self.updated_at = now_timestamp();
+        self.revision += 1;
+    }
+    
+    /// Set the item's title
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+        self.touch();
+    }
+    
+    /// Set the item's description
+    pub fn set_description(&mut self, description: Option<&str>) {
+        self.description = description.map(|s| s.to_string());
+        self.touch();
+    }
+    
+    /// Set the item's status, noting when it's completed
+    pub fn set_status(&mut self, status: Status) {
+        self.touch();
+        self.record_completion(status);
+        self.status = status;
+    }
+    
+    /// Note the time for an item becoming completed, or forget it when it's reopened
+    ///
+    /// Call before changing the status; completing a completed item keeps its time.
+    fn record_completion(&mut self, status: Status) {
+        self.completed_at = match status {
+            Status::Completed if self.is_completed() => self.completed_at,
+            Status::Completed => Some(self.updated_at()),
+            _ => None,
+        };
+    }
+    
+    /// Set the item's priority
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+        self.touch();
+    }
+    
+    /// Set the item's due date
+    pub fn set_due_date(&mut self, due_date: Option<u64>) {
+        self.due_date = due_date;
+        // A date picked by hand is where the repeats start from
+        self.recurrence_day = None;
+        self.touch();
+    }
+    
+    /// Set the item's parent ID
+    pub fn set_parent_id(&mut self, parent_id: Option<Uuid>) {
+        self.parent_id = parent_id;
+        self.touch();
+    }
+    
+    /// Add or update a metadata value
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self.touch();
+    }
+    
+    /// Remove a metadata value
+    pub fn remove_metadata(&mut self, key: &str) -> Option<String> {
+        let removed = self.metadata.remove(key);
+        if removed.is_some() {
+            self.touch();
+        }
+        removed
+    }
+    
+    /// Replace the item's tags; blank ones and repeats are dropped
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = clean_tags(tags);
+        self.touch();
+    }
+    
+    /// Add a tag, returning whether the item didn't have it yet
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let added = self.push_tag(tag);
+        if added {
+            self.touch();
+        }
+        added
+    }
+    
+    /// Add a tag without counting it as a change, returning whether it's new
+    fn push_tag(&mut self, tag: &str) -> bool {
+        match clean_tag(tag) {
+            Some(tag) if !self.has_tag(&tag) => {
+                self.tags.push(tag);
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    /// Remove a tag, returning whether the item had it
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|own| own != tag);
+        let removed = self.tags.len() != before;
+        if removed {
+            self.touch();
+        }
+        removed
+    }
+    
+    /// Set how the item repeats
+    pub fn set_recurrence(&mut self, recurrence: Option<RecurrenceRule>) {
+        self.recurrence = recurrence;
+        self.touch();
+    }
+    
+    /// Set when to be reminded of the item
+    pub fn set_reminder(&mut self, reminder: Reminder) {
+        self.reminder = reminder;
+        self.touch();
+    }
+    
+    /// Replace the item's attachments; repeats are dropped
+    pub fn set_attachments(&mut self, attachments: Vec<Attachment>) {
+        self.attachments = unique_attachments(attachments);
+        self.touch();
+    }
+    
+    /// Add an attachment, returning whether the item didn't have it yet
+    pub fn add_attachment(&mut self, attachment: Attachment) -> bool {
+        let added = self.push_attachment(attachment);
+        if added {
+            self.touch();
+        }
+        added
+    }
+    
+    /// Add an attachment without counting it as a change, returning whether it's new
+    fn push_attachment(&mut self, attachment: Attachment) -> bool {
+        if self.attachments.contains(&attachment) {
+            return false;
+        }
+        self.attachments.push(attachment);
+        true
+    }
+    
+    /// Put the item in a project, or take it out of its project
+    pub fn set_project_id(&mut self, project_id: Option<Uuid>) {
+        self.project_id = project_id;
+        self.touch();
+    }
+    
+    // --- Convenience methods ---
+    
+    /// Check if the item is completed
+    pub fn is_completed(&self) -> bool {
+        self.status == Status::Completed
+    }
+    
+    /// Mark the item as completed
+    pub fn mark_completed(&mut self) {
+        self.set_status(Status::Completed);
+    }
+    
+    /// The next occurrence of a recurring item: a fresh copy due one interval on
+    ///
+    /// Returns `None` for items that don't repeat. The copy keeps the title,
+    /// description, priority, parent, metadata, tags, attachments, project, rule
+    /// and reminder, and starts out not started. Metadata about this occurrence
+    /// alone, its focus sessions and its UID on a sync server, stays behind.
+    pub fn next_occurrence(&self) -> Option<TodoItem> {
+        let rule = self.recurrence?;
+        let mut next = TodoItem::new(&self.title);
+        next.description = self.description.clone();
+        next.priority = self.priority;
+        next.parent_id = self.parent_id;
+        next.metadata = self.metadata.clone();
+        for key in OCCURRENCE_METADATA {
+            next.metadata.remove(key);
+        }
+        next.tags = self.tags.clone();
+        next.attachments = self.attachments.clone();
+        next.project_id = self.project_id;
+        next.recurrence = Some(rule);
+        next.reminder = self.reminder;
+        next.recurrence_day = self.recurrence_day.or_else(|| {
+            let due = DateTime::from_timestamp(self.due_date? as i64, 0)?;
+            Some(due.with_timezone(&chrono::Local).day())
+        });
+        next.due_date = Some(rule.next_due(self.due_date, next.recurrence_day, next.created_at, &chrono::Local));
+        next.previous_occurrence = Some(self.id);
+        Some(next)
+    }
+    
+    /// Check if the item is overdue: not completed, and due on a day before today
+    ///
+    /// Due dates picked without a time are stored at the start of their day,
+    /// so comparing timestamps would make a task due today overdue all day.
+    pub fn is_overdue(&self) -> bool {
+        self.is_overdue_at(&chrono::Local::now())
+    }
+    
+    /// Check if the item was overdue at `now`, in its time zone
+    pub fn is_overdue_at<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> bool {
+        let Some(due) = self.due_date.and_then(|due| DateTime::from_timestamp(due as i64, 0)) else {
+            return false;
+        };
+        due.with_timezone(&now.timezone()).date_naive() < now.date_naive() && !self.is_completed()
+    }
+    
+    // --- Builder methods ---
+    
+    /// Set the parent ID and return self (builder pattern)
+    pub fn with_parent(mut self, parent_id: Uuid) -> Self {
+        self.parent_id = Some(parent_id);
+        self
+    }
+    
+    /// Set the priority and return self (builder pattern)
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+    
+    /// Set the status and return self (builder pattern)
+    pub fn with_status(mut self, status: Status) -> Self {
+        self.record_completion(status);
+        self.status = status;
+        self
+    }
+    
+    /// Set the description and return self (builder pattern)
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+    
+    /// Set the due date and return self (builder pattern)
+    pub fn with_due_date(mut self, due_date: u64) -> Self {
+        self.due_date = Some(due_date);
+        self
+    }
+    
+    /// Add a tag and return self (builder pattern)
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.push_tag(tag);
+        self
+    }
+    
+    /// Set the recurrence and return self (builder pattern)
+    pub fn with_recurrence(mut self, recurrence: RecurrenceRule) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+    
+    /// Add an attachment and return self (builder pattern)
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.push_attachment(attachment);
+        self
+    }
+    
+    /// Set the project and return self (builder pattern)
+    pub fn with_project(mut self, project_id: Uuid) -> Self {
+        self.project_id = Some(project_id);
+        self
+    }
+    
+    /// Set when to be reminded and return self (builder pattern)
+    pub fn with_reminder(mut self, reminder: Reminder) -> Self {
+        self.reminder = reminder;
+        self
+    }
+    
+    /// Keep the id a task had in another app, so importing it again finds it
+    pub(crate) fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+    
+    /// Keep when a task was created in another app
+    pub(crate) fn with_created_at(mut self, created_at: u64) -> Self {
+        self.created_at = created_at;
+        self
+    }
+    
+    /// Keep when a task last changed in another app, as one with no changes made here yet
+    pub(crate) fn with_updated_at(mut self, updated_at: u64) -> Self {
+        self.updated_at = updated_at;
+        self.revision = 0;
+        self
+    }
+    
+    /// Keep when a task was completed in another app; ignored unless it's completed
+    pub(crate) fn with_completed_at(mut self, completed_at: u64) -> Self {
+        if self.is_completed() {
+            self.completed_at = Some(completed_at);
+        }
+        self
+    }
+}
+
+impl fmt::Display for TodoItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status_marker = match self.status {
+            Status::Completed => "✓",
+            Status::InProgress => "⊘",
+            Status::NotStarted => "○",
+        };
+        
+        let priority_marker = match self.priority {
+            Priority::High => "!!!",
+            Priority::Medium => "!!",
+            Priority::Low => "!",
+        };
+        
+        write!(f, "[{}] {} {}", status_marker, self.title, priority_marker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDateTime, NaiveTime};
+    
+    #[test]
+    fn test_create_todo_item() {
+        let item = TodoItem::new("Test Task");
+        assert_eq!(item.title(), "Test Task");
+        assert_eq!(item.status(), Status::NotStarted);
+        assert_eq!(item.priority(), Priority::Medium);
+    }
+    
+    #[test]
+    fn test_status_changes() {
+        let mut item = TodoItem::new("Task");
+        assert!(!item.is_completed());
+        
+        item.set_status(Status::InProgress);
+        assert_eq!(item.status(), Status::InProgress);
+        assert!(!item.is_completed());
+        
+        item.mark_completed();
+        assert!(item.is_completed());
+    }
+    
+    #[test]
+    fn test_change_times() {
+        let mut item = TodoItem::new("Task").with_created_at(1_000).with_updated_at(1_000);
+        assert_eq!(item.updated_at(), 1_000);
+        assert_eq!(item.completed_at(), None);
+        
+        item.set_title("Renamed");
+        let renamed = item.updated_at();
+        assert!(renamed > 1_000);
+        assert!(renamed <= now_timestamp());
+        
+        item.mark_completed();
+        let completed = item.completed_at().expect("completed items record when");
+        assert!(completed >= renamed);
+        assert_eq!(completed, item.updated_at());
+        
+        // Completing it again, or editing it, keeps the time it was completed
+        item.set_status(Status::Completed);
+        item.set_priority(Priority::High);
+        assert_eq!(item.completed_at(), Some(completed));
+        assert!(item.updated_at() >= completed);
+        
+        item.set_status(Status::InProgress);
+        assert_eq!(item.completed_at(), None);
+        
+        // Saved before completion times were kept
+        let old: TodoItem = serde_json::from_str(
+            r#"{"id":"6a5d3b9f-2a3c-4d4e-8f9a-1b2c3d4e5f60","title":"Old","description":null,"status":"Completed","priority":"Low","created_at":100,"due_date":null,"modified_at":200,"parent_id":null}"#,
+        ).unwrap();
+        assert_eq!(old.updated_at(), 200);
+        assert_eq!(old.completed_at(), Some(200));
+    }
+    
+    #[test]
+    fn test_each_change_counts_once() {
+        let mut item = TodoItem::new("Task").with_tag("home");
+        assert_eq!(item.revision(), 0);
+        
+        // Many edits in a second stay at the clock's time, but each is counted
+        for n in 0..50 {
+            item.set_title(&format!("Task {}", n));
+        }
+        assert!(item.updated_at() <= now_timestamp());
+        assert_eq!(item.revision(), 50);
+        
+        item.set_tags(vec!["work".to_string(), "errands".to_string(), "work".to_string()]);
+        assert_eq!(item.revision(), 51);
+        item.set_attachments(vec![Attachment::Url("https://example.com".to_string())]);
+        assert_eq!(item.revision(), 52);
+        
+        // Changes that change nothing aren't counted
+        assert!(!item.add_tag("work"));
+        assert!(item.remove_metadata("missing").is_none());
+        assert_eq!(item.revision(), 52);
+    }
+    
+    #[test]
+    fn test_metadata() {
+        let mut item = TodoItem::new("Task with metadata");
+        
+        item.set_metadata("category", "work");
+        item.set_metadata("context", "office");
+        
+        assert_eq!(item.metadata().get("category"), Some(&"work".to_string()));
+        assert_eq!(item.metadata().get("context"), Some(&"office".to_string()));
+        
+        item.remove_metadata("context");
+        assert!(item.metadata().get("context").is_none());
+    }
+    
+    #[test]
+    fn test_tags() {
+        let mut item = TodoItem::new("Tagged task").with_tag("work").with_tag(" #home ");
+        assert_eq!(item.tags(), ["work", "home"]);
+        
+        // Blank tags and repeats are ignored
+        assert!(!item.add_tag("work"));
+        assert!(!item.add_tag("  # "));
+        assert!(item.remove_tag("work"));
+        assert!(!item.remove_tag("work"));
+        assert!(item.has_tag("home"));
+        
+        item.set_tags(vec!["a".to_string(), "b".to_string(), "a".to_string(), String::new()]);
+        assert_eq!(item.tags(), ["a", "b"]);
+        
+        // Items saved before tags existed still load
+        let mut json = serde_json::to_value(&item).unwrap();
+        json.as_object_mut().unwrap().remove("tags");
+        let loaded: TodoItem = serde_json::from_value(json).unwrap();
+        assert!(loaded.tags().is_empty());
+    }
+    
+    #[test]
+    fn test_recurrence() {
+        let jan_31 = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap()
+            .and_hms_opt(9, 0, 0).unwrap().and_utc().timestamp() as u64;
+        let feb_28 = chrono::NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()
+            .and_hms_opt(9, 0, 0).unwrap().and_utc().timestamp() as u64;
+        assert_eq!(RecurrenceRule::Monthly.next_after(jan_31, None, &chrono::Utc), feb_28);
+        assert_eq!(RecurrenceRule::EveryNDays(3).next_after(jan_31, None, &chrono::Utc), jan_31 + 3 * DAY);
+        
+        // Occurrences already in the past are skipped
+        assert_eq!(RecurrenceRule::Daily.next_due(Some(jan_31), None, jan_31 + 2 * DAY + 1, &chrono::Utc), jan_31 + 3 * DAY);
+        assert_eq!(RecurrenceRule::Weekly.next_due(None, None, jan_31, &chrono::Utc), jan_31 + 7 * DAY);
+        
+        let mut item = TodoItem::new("Water plants")
+            .with_recurrence(RecurrenceRule::Weekly)
+            .with_tag("home")
+            .with_project(Uuid::new_v4())
+            .with_status(Status::Completed);
+        item.set_metadata("context", "garden");
+        item.set_metadata(FOCUS_LOG_KEY, "1700000000:25,1700003000:25");
+        item.set_metadata(crate::sync::caldav::UID_KEY, "plants@example.com");
+        let next = item.next_occurrence().unwrap();
+        assert_ne!(next.id(), item.id());
+        assert_eq!(next.status(), Status::NotStarted);
+        assert_eq!(next.tags(), item.tags());
+        assert_eq!(next.project_id(), item.project_id());
+        assert_eq!(next.metadata().get("context").map(String::as_str), Some("garden"));
+        assert!(next.metadata().get(FOCUS_LOG_KEY).is_none());
+        assert!(next.metadata().get(crate::sync::caldav::UID_KEY).is_none());
+        assert_eq!(next.previous_occurrence(), Some(item.id()));
+        assert!(next.due_date().unwrap() > now_timestamp());
+        assert!(TodoItem::new("Once").next_occurrence().is_none());
+    }
+    
+    /// Central European time in 2025, when the clocks went from 02:00 to 03:00 on 30 March
+    #[derive(Debug, Clone, Copy)]
+    struct Cet2025;
+    
+    impl Cet2025 {
+        fn offset(summer: bool) -> FixedOffset {
+            FixedOffset::east_opt(if summer { 7200 } else { 3600 }).unwrap()
+        }
+    }
+    
+    impl TimeZone for Cet2025 {
+        type Offset = FixedOffset;
+        
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            Cet2025
+        }
+        
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+        
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+            let change = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap().and_hms_opt(2, 0, 0).unwrap();
+            if *local < change {
+                MappedLocalTime::Single(Self::offset(false))
+            } else if *local < change + chrono::TimeDelta::hours(1) {
+                MappedLocalTime::None
+            } else {
+                MappedLocalTime::Single(Self::offset(true))
+            }
+        }
+        
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+        
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let change = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            Self::offset(*utc >= change)
+        }
+    }
+    
+    #[test]
+    fn test_monthly_keeps_its_day() {
+        let at = |month, day| chrono::Utc.with_ymd_and_hms(2025, month, day, 9, 0, 0).unwrap().timestamp() as u64;
+        let monthly = |due, day| RecurrenceRule::Monthly.next_after(due, day, &chrono::Utc);
+        
+        // Back on the 31st after February, rather than on the 28th for good
+        assert_eq!(monthly(at(1, 31), None), at(2, 28));
+        assert_eq!(monthly(at(2, 28), Some(31)), at(3, 31));
+        assert_eq!(monthly(at(3, 31), Some(31)), at(4, 30));
+        assert_eq!(monthly(at(4, 30), Some(31)), at(5, 31));
+        assert_eq!(monthly(at(2, 28), None), at(3, 28));
+        assert_eq!(monthly(at(12, 31), None), chrono::Utc.with_ymd_and_hms(2026, 1, 31, 9, 0, 0).unwrap().timestamp() as u64);
+        
+        // Occurrences carry the day on, until a date is picked by hand
+        let mut item = TodoItem::new("Pay rent").with_due_date(at(1, 31)).with_recurrence(RecurrenceRule::Monthly);
+        item.recurrence_day = Some(31);
+        let next = item.next_occurrence().unwrap();
+        assert_eq!(next.recurrence_day, Some(31));
+        let due = DateTime::from_timestamp(next.due_date().unwrap() as i64, 0).unwrap().with_timezone(&chrono::Local).date_naive();
+        assert!(due.succ_opt().unwrap().day() == 1, "{} isn't the last day of its month", due);
+        let mut next = next.next_occurrence().unwrap();
+        assert_eq!(next.recurrence_day, Some(31));
+        next.set_due_date(Some(at(3, 5)));
+        assert_eq!(next.recurrence_day, None);
+    }
+    
+    #[test]
+    fn test_repeats_keep_their_time_across_daylight_saving() {
+        let at = |month, day, hour, minute| Cet2025.with_ymd_and_hms(2025, month, day, hour, minute, 0).unwrap().timestamp() as u64;
+        let next = |rule: RecurrenceRule, due| rule.next_after(due, None, &Cet2025);
+        
+        // 23 hours apart, but 09:00 both days
+        assert_eq!(next(RecurrenceRule::Daily, at(3, 29, 9, 0)), at(3, 30, 9, 0));
+        assert_eq!(at(3, 30, 9, 0) - at(3, 29, 9, 0), 23 * 60 * 60);
+        assert_eq!(next(RecurrenceRule::Weekly, at(3, 25, 18, 0)), at(4, 1, 18, 0));
+        assert_eq!(next(RecurrenceRule::EveryNDays(2), at(3, 29, 0, 0)), at(3, 31, 0, 0));
+        assert_eq!(next(RecurrenceRule::Monthly, at(3, 15, 9, 0)), at(4, 15, 9, 0));
+        // 02:30 doesn't exist on the 30th
+        assert_eq!(next(RecurrenceRule::Daily, at(3, 29, 2, 30)), at(3, 30, 3, 30));
+    }
+    
+    #[test]
+    fn test_due_today_is_not_overdue() {
+        let zone = chrono::FixedOffset::east_opt(3600).unwrap();
+        let at = |day, hour| zone.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap();
+        let now = at(5, 10);
+        let due_on = |day| TodoItem::new("Task").with_due_date(at(day, 0).timestamp() as u64);
+        
+        assert!(!due_on(5).is_overdue_at(&now));
+        assert!(!due_on(5).is_overdue_at(&at(5, 23)));
+        assert!(!due_on(6).is_overdue_at(&now));
+        assert!(due_on(4).is_overdue_at(&now));
+        assert!(!due_on(4).with_status(Status::Completed).is_overdue_at(&now));
+        assert!(!TodoItem::new("Task").is_overdue_at(&now));
+    }
+    
+    #[test]
+    fn test_attachments() {
+        let report = Attachment::parse(" /home/me/Q3 report.pdf ").unwrap();
+        assert_eq!(report, Attachment::File("/home/me/Q3 report.pdf".into()));
+        assert_eq!(report.name(), "Q3 report.pdf");
+        assert_eq!(Attachment::parse("file:///tmp/a.txt"), Some(Attachment::File("/tmp/a.txt".into())));
+        assert_eq!(Attachment::parse(r"C:\notes.txt"), Some(Attachment::File(r"C:\notes.txt".into())));
+        let page = Attachment::parse("https://example.com/spec/").unwrap();
+        assert_eq!(page, Attachment::Url("https://example.com/spec/".to_string()));
+        assert_eq!((page.name().as_str(), page.target().as_str()), ("example.com/spec", "https://example.com/spec/"));
+        assert_eq!(Attachment::parse("mailto:me@example.com").unwrap().name(), "mailto:me@example.com");
+        assert_eq!(Attachment::parse("  "), None);
+        
+        let mut item = TodoItem::new("Review").with_attachment(report.clone()).with_attachment(report.clone());
+        assert_eq!(item.attachments(), std::slice::from_ref(&report));
+        assert!(item.add_attachment(page.clone()));
+        item.set_attachments(vec![page.clone(), report.clone(), page.clone()]);
+        assert_eq!(item.attachments(), [page, report]);
+        
+        // Items saved before attachments existed still load
+        let mut json = serde_json::to_value(&item).unwrap();
+        json.as_object_mut().unwrap().remove("attachments");
+        let loaded: TodoItem = serde_json::from_value(json).unwrap();
+        assert!(loaded.attachments().is_empty());
+    }
+} 
\ No newline at end of file
@@ -0,0 +1,1498 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::mpsc::Receiver;
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use uuid::Uuid;
+use super::todo_item::{TodoItem, Status, Priority};
+use super::project::Project;
+use super::command::{Applied, BulkEdit, Command};
+use super::events::{ListEvent, Subscribers};
+use super::history::History;
+use super::sync::caldav::SyncState;
+
+/// TodoList manages a collection of TodoItems with hierarchy support
+///
+/// Siblings have a manual order: a new item goes after its siblings, as does an
+/// item moved under another parent, and `move_item_before` rearranges them.
+/// Every view and the saved file keep that order.
+#[derive(Debug, Clone)]
+pub struct TodoList {
+    /// The name of this todo list
+    name: String,
+    
+    /// Map of item IDs to TodoItems
+    items: HashMap<Uuid, TodoItem>,
+    
+    /// Map of parent IDs to child item IDs, in order, for quick hierarchy lookups
+    ///
+    /// Not serialized: it is derived on load from each item's parent_id and the
+    /// order items are written in.
+    hierarchy: HashMap<Option<Uuid>, Vec<Uuid>>,
+
+    /// Changes made through a `Dispatcher`, for undo and redo
+    ///
+    /// Not serialized: a loaded list starts with an empty history.
+    history: History,
+
+    /// Where the last CalDAV sync left this list and the server
+    sync_state: SyncState,
+
+    /// How the list is shown, kept with it so each list keeps its own
+    sort_order: SortOrder,
+
+    /// Items taken out of the list by `archive_item`, each subtree parents
+    /// first, in the order they were archived
+    archived: Vec<TodoItem>,
+
+    /// Projects the items can belong to, in order by name
+    projects: Vec<Project>,
+
+    /// Where changes to the items are reported
+    ///
+    /// Not serialized, and not cloned: a copy has no subscribers.
+    subscribers: Subscribers,
+}
+
+/// What siblings are ordered by in a `sorted_view`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// The order the list keeps them in
+    #[default]
+    Manual,
+    /// Highest priority first
+    Priority,
+    /// Soonest due first; items without a due date come last either way
+    DueDate,
+    /// Oldest first
+    Created,
+    /// By title, ignoring case
+    Alphabetical,
+}
+
+/// A sort key and direction; descending reverses the key's order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl SortOrder {
+    /// Whether items keep the list's own order, whatever the direction
+    pub fn is_manual(&self) -> bool {
+        self.key == SortKey::Manual
+    }
+
+    /// Compare two items; ties are broken by id, so the order is stable
+    pub fn compare(&self, a: &TodoItem, b: &TodoItem) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Manual => return Ordering::Equal,
+            SortKey::Priority => b.priority().cmp(&a.priority()),
+            SortKey::DueDate => match (a.due_date(), b.due_date()) {
+                (Some(a_due), Some(b_due)) => a_due.cmp(&b_due),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortKey::Created => a.created_at().cmp(&b.created_at()),
+            SortKey::Alphabetical => a.title().to_lowercase().cmp(&b.title().to_lowercase()),
+        };
+        let ordering = if self.descending { ordering.reverse() } else { ordering };
+        ordering.then_with(|| a.id().cmp(&b.id()))
+    }
+}
+
+/// On-disk shape of a TodoList, without the derived hierarchy map
+///
+/// Items are written in hierarchy order, each parent followed by its subtree,
+/// and read back in the order they come in, which is how siblings keep their
+/// order. A file written by hand or by older versions loads in its own order.
+#[derive(serde::Deserialize)]
+struct SerializedTodoList {
+    name: String,
+    items: ItemsInFileOrder,
+    #[serde(default)]
+    sync_state: SyncState,
+    #[serde(default)]
+    sort_order: SortOrder,
+    #[serde(default)]
+    archived: Vec<TodoItem>,
+    #[serde(default)]
+    projects: Vec<Project>,
+}
+
+/// The same shape, borrowed from a list being written
+#[derive(serde::Serialize)]
+struct SerializedTodoListRef<'a> {
+    name: &'a str,
+    items: ItemsInOrder<'a>,
+    #[serde(skip_serializing_if = "SyncState::is_empty")]
+    sync_state: &'a SyncState,
+    #[serde(skip_serializing_if = "SortOrder::is_manual")]
+    sort_order: SortOrder,
+    #[serde(skip_serializing_if = "<[TodoItem]>::is_empty")]
+    archived: &'a [TodoItem],
+    #[serde(skip_serializing_if = "<[Project]>::is_empty")]
+    projects: &'a [Project],
+}
+
+/// The items of a list as a map from id to item, in hierarchy order
+struct ItemsInOrder<'a>(&'a TodoList);
+
+impl Serialize for ItemsInOrder<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.ordered_items().into_iter().map(|item| (item.id(), item)))
+    }
+}
+
+/// Items read from a map of id to item, in the order they were written
+struct ItemsInFileOrder(Vec<TodoItem>);
+
+impl<'de> Deserialize<'de> for ItemsInFileOrder {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ItemsVisitor;
+        
+        impl<'de> Visitor<'de> for ItemsVisitor {
+            type Value = ItemsInFileOrder;
+            
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of item ids to items")
+            }
+            
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut items = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                // The item carries its id too; that one is used, as it always was
+                while let Some((_, item)) = map.next_entry::<Uuid, TodoItem>()? {
+                    items.push(item);
+                }
+                Ok(ItemsInFileOrder(items))
+            }
+        }
+        
+        deserializer.deserialize_map(ItemsVisitor)
+    }
+}
+
+impl Serialize for TodoList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedTodoListRef {
+            name: self.name.as_str(),
+            items: ItemsInOrder(self),
+            sync_state: &self.sync_state,
+            sort_order: self.sort_order,
+            archived: &self.archived,
+            projects: &self.projects,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TodoList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = SerializedTodoList::deserialize(deserializer)?;
+        let mut list = TodoList::new(&data.name);
+        for item in data.items.0 {
+            list.add_item(item);
+        }
+        list.sync_state = data.sync_state;
+        list.sort_order = data.sort_order;
+        list.archived = data.archived;
+        for project in data.projects {
+            list.put_project(project);
+        }
+        Ok(list)
+    }
+}
+
+impl TodoList {
+    /// Create a new, empty TodoList with the given name
+    pub fn new(name: &str) -> Self {
+        TodoList {
+            name: name.to_string(),
+            items: HashMap::new(),
+            hierarchy: HashMap::new(),
+            history: History::new(),
+            sync_state: SyncState::default(),
+            sort_order: SortOrder::default(),
+            archived: Vec::new(),
+            projects: Vec::new(),
+            subscribers: Subscribers::default(),
+        }
+    }
+    
+    /// Get the name of this TodoList
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    
+    /// Set the name of this TodoList
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+    
+    /// Get the number of items in this TodoList
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+    
+    /// Check if this TodoList is empty
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    
+    /// Add a TodoItem to the list, after its siblings
+    ///
+    /// An item with the same id is replaced, keeping its place if its parent is the same.
+    pub fn add_item(&mut self, item: TodoItem) -> Uuid {
+        // Store the item's ID and parent ID for hierarchy maintenance
+        let id = item.id();
+        let parent_id = item.parent_id();
+        
+        // Add item to the items map
+        let event = match self.items.insert(id, item) {
+            Some(old) if old.parent_id() == parent_id => {
+                self.subscribers.send(ListEvent::ItemUpdated { id });
+                return id;
+            }
+            Some(old) => {
+                self.detach(old.parent_id(), id);
+                ListEvent::ItemMoved { id, parent_id }
+            }
+            None => ListEvent::ItemAdded { id },
+        };
+        
+        // Update the hierarchy map
+        self.hierarchy.entry(parent_id).or_default().push(id);
+        self.subscribers.send(event);
+             
+        id
+    }
+    
+    /// Take an item out of its parent's children, without touching the item
+    fn detach(&mut self, parent_id: Option<Uuid>, id: Uuid) {
+        if let Some(siblings) = self.hierarchy.get_mut(&parent_id) {
+            siblings.retain(|&sibling| sibling != id);
+            if siblings.is_empty() {
+                self.hierarchy.remove(&parent_id);
+            }
+        }
+    }
+    
+    /// Create and add a new TodoItem with the given title
+    pub fn create_item(&mut self, title: &str) -> Uuid {
+        let item = TodoItem::new(title);
+        self.add_item(item)
+    }
+    
+    /// Get a reference to a TodoItem by ID
+    pub fn get_item(&self, id: Uuid) -> Option<&TodoItem> {
+        self.items.get(&id)
+    }
+    
+    /// Get a mutable reference to a TodoItem by ID
+    ///
+    /// Subscribers are told the item was updated, whether or not it's changed.
+    pub fn get_item_mut(&mut self, id: Uuid) -> Option<&mut TodoItem> {
+        let item = self.items.get_mut(&id)?;
+        self.subscribers.send(ListEvent::ItemUpdated { id });
+        Some(item)
+    }
+    
+    /// Get told about changes to the items from now on, wherever they're made
+    ///
+    /// Events queue up in the receiver until taken; dropping it unsubscribes.
+    pub fn subscribe(&mut self) -> Receiver<ListEvent> {
+        self.subscribers.subscribe()
+    }
+    
+    /// Remove a TodoItem from the list
+    /// 
+    /// Returns the removed item if it existed, or None if it didn't
+    pub fn remove_item(&mut self, id: Uuid) -> Option<TodoItem> {
+        // First, check if the item exists
+        if !self.items.contains_key(&id) {
+            return None;
+        }
+        
+        // Find and remove any children of this item
+        if let Some(children) = self.hierarchy.remove(&Some(id)) {
+            // Recursively remove all children
+            for child_id in children {
+                self.remove_item(child_id);
+            }
+        }
+        
+        // Remove the item from its parent's children list, or from the root items
+        let parent_id = self.items.get(&id).and_then(|item| item.parent_id());
+        self.detach(parent_id, id);
+        
+        // Finally, remove the item itself
+        let removed = self.items.remove(&id);
+        self.subscribers.send(ListEvent::ItemRemoved { id });
+        removed
+    }
+    
+    /// Get all root items (items with no parent), in order
+    pub fn root_items(&self) -> Vec<&TodoItem> {
+        match self.hierarchy.get(&None) {
+            Some(root_ids) => root_ids
+                .iter()
+                .filter_map(|id| self.items.get(id))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Get IDs of all root items, in order
+    pub fn root_item_ids(&self) -> Vec<Uuid> {
+        match self.hierarchy.get(&None) {
+            Some(root_ids) => root_ids.clone(),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Get all child items of a given parent, in order
+    pub fn children(&self, parent_id: Uuid) -> Vec<&TodoItem> {
+        match self.hierarchy.get(&Some(parent_id)) {
+            Some(child_ids) => child_ids
+                .iter()
+                .filter_map(|id| self.items.get(id))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Get IDs of all child items of a given parent, in order
+    pub fn child_ids(&self, parent_id: Uuid) -> Vec<Uuid> {
+        match self.hierarchy.get(&Some(parent_id)) {
+            Some(child_ids) => child_ids.clone(),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Get how many of an item's children are completed, out of how many, or
+    /// `None` if it has none
+    pub fn child_progress(&self, parent_id: Uuid) -> Option<(usize, usize)> {
+        let children = self.children(parent_id);
+        let completed = children.iter().filter(|child| child.is_completed()).count();
+        (!children.is_empty()).then_some((completed, children.len()))
+    }
+    
+    /// The status the parent of an item should take for its completion to follow
+    /// its children's, if that isn't its status already
+    ///
+    /// A parent is completed once all its children are, and reopened (not
+    /// started) when one of them no longer is.
+    pub fn parent_status_update(&self, id: Uuid) -> Option<(Uuid, Status)> {
+        let parent = self.get_item(self.get_item(id)?.parent_id()?)?;
+        let (completed, total) = self.child_progress(parent.id())?;
+        match (completed == total, parent.is_completed()) {
+            (true, false) => Some((parent.id(), Status::Completed)),
+            (false, true) => Some((parent.id(), Status::NotStarted)),
+            _ => None,
+        }
+    }
+    
+    /// Move an item to be a child of another item, after its new siblings
+    /// 
+    /// An item that already has that parent keeps its place.
+    /// Returns `Ok(())` if successful, or an error message if not.
+    pub fn move_item(&mut self, item_id: Uuid, new_parent_id: Option<Uuid>) -> Result<(), String> {
+        // Check if the item exists
+        if !self.items.contains_key(&item_id) {
+            return Err(format!("Item with ID {} not found", item_id));
+        }
+        
+        // If there's a new parent, check if it exists
+        if let Some(parent_id) = new_parent_id {
+            if !self.items.contains_key(&parent_id) {
+                return Err(format!("Parent item with ID {} not found", parent_id));
+            }
+            
+            // Check for cycles: an item can't be moved under itself or its descendants
+            if parent_id == item_id || self.is_ancestor(parent_id, item_id) {
+                return Err("Moving this item would create a cycle".to_string());
+            }
+        }
+        
+        // Get the current parent ID
+        let current_parent_id = self.items.get(&item_id).and_then(|item| item.parent_id());
+        if current_parent_id == new_parent_id {
+            return Ok(());
+        }
+        
+        // Remove from current parent's children
+        self.detach(current_parent_id, item_id);
+        
+        // Add to new parent's children
+        self.hierarchy.entry(new_parent_id).or_default().push(item_id);
+            
+        // Update the item's parent_id
+        if let Some(item) = self.items.get_mut(&item_id) {
+            item.set_parent_id(new_parent_id);
+        }
+        self.subscribers.send(ListEvent::ItemMoved { id: item_id, parent_id: new_parent_id });
+        
+        Ok(())
+    }
+    
+    /// Check if one item is an ancestor of another
+    fn is_ancestor(&self, item_id: Uuid, potential_ancestor_id: Uuid) -> bool {
+        // Get the item's parent
+        let parent_id = match self.items.get(&item_id).and_then(|item| item.parent_id()) {
+            Some(id) => id,
+            None => return false, // No parent, so definitely not an ancestor
+        };
+        
+        // Check if the parent is the potential ancestor
+        if parent_id == potential_ancestor_id {
+            return true;
+        }
+        
+        // Recursively check the parent's ancestors
+        self.is_ancestor(parent_id, potential_ancestor_id)
+    }
+    
+    /// Build the command that makes `edit` to the items `ids` all at once
+    /// 
+    /// Run through a `Dispatcher`, it's applied all or nothing and undone in one
+    /// step. Deleting or moving an item takes its descendants along, so those
+    /// among `ids` are left to it. Returns an error if an item or the parent to
+    /// move under doesn't exist, or if that parent is one of the items or below one.
+    pub fn batch(&self, ids: &[Uuid], edit: BulkEdit) -> Result<Command, String> {
+        let mut seen = HashSet::new();
+        let ids: Vec<Uuid> = ids.iter().copied().filter(|&id| seen.insert(id)).collect();
+        if let Some(missing) = ids.iter().find(|id| !self.items.contains_key(id)) {
+            return Err(format!("Item with ID {} not found", missing));
+        }
+        let topmost = || ids.iter().copied().filter(|&id| !ids.iter().any(|&other| self.is_ancestor(id, other)));
+        let commands = match edit {
+            BulkEdit::Complete => ids.iter()
+                .map(|&id| Command::SetStatus { id, status: Status::Completed })
+                .collect(),
+            BulkEdit::SetPriority(priority) => ids.iter()
+                .map(|&id| Command::SetPriority { id, priority })
+                .collect(),
+            BulkEdit::Delete => topmost().map(|id| Command::RemoveItem { id }).collect(),
+            BulkEdit::MoveUnder(parent_id) => {
+                if let Some(parent) = parent_id {
+                    if !self.items.contains_key(&parent) {
+                        return Err(format!("Parent item with ID {} not found", parent));
+                    }
+                    if ids.iter().any(|&id| id == parent || self.is_ancestor(parent, id)) {
+                        return Err("Moving these items would create a cycle".to_string());
+                    }
+                }
+                topmost()
+                    .filter(|&id| self.items[&id].parent_id() != parent_id)
+                    .map(|id| Command::Move { id, parent_id })
+                    .collect()
+            }
+        };
+        Ok(Command::Batch(commands))
+    }
+    
+    /// Get all items matching a filter function
+    pub fn filter_items<F>(&self, filter_fn: F) -> Vec<&TodoItem>
+    where
+        F: Fn(&TodoItem) -> bool,
+    {
+        self.items
+            .values()
+            .filter(|item| filter_fn(item))
+            .collect()
+    }
+    
+    /// Get all completed items
+    pub fn completed_items(&self) -> Vec<&TodoItem> {
+        self.filter_items(|item| item.is_completed())
+    }
+    
+    /// Get all incomplete items
+    pub fn incomplete_items(&self) -> Vec<&TodoItem> {
+        self.filter_items(|item| !item.is_completed())
+    }
+    
+    /// Get the open items with a priority; completed ones no longer need doing
+    pub fn items_by_priority(&self, priority: Priority) -> Vec<&TodoItem> {
+        self.filter_items(|item| !item.is_completed() && item.priority() == priority)
+    }
+    
+    /// Get items by status
+    pub fn items_by_status(&self, status: Status) -> Vec<&TodoItem> {
+        self.filter_items(|item| item.status() == status)
+    }
+    
+    /// Get all overdue items
+    pub fn overdue_items(&self) -> Vec<&TodoItem> {
+        self.filter_items(|item| item.is_overdue())
+    }
+    
+    /// Get items with a tag
+    pub fn items_by_tag(&self, tag: &str) -> Vec<&TodoItem> {
+        self.filter_items(|item| item.has_tag(tag))
+    }
+    
+    /// Get the items in a project, or the items in none with `None`
+    pub fn items_by_project(&self, project_id: Option<Uuid>) -> Vec<&TodoItem> {
+        self.filter_items(|item| item.project_id() == project_id)
+    }
+    
+    /// The projects items can belong to, in order by name
+    pub fn projects(&self) -> &[Project] {
+        &self.projects
+    }
+    
+    /// Get a project by ID
+    pub fn project(&self, id: Uuid) -> Option<&Project> {
+        self.projects.iter().find(|project| project.id() == id)
+    }
+    
+    /// Add a project, or replace the one with the same id, returning the one replaced
+    pub fn put_project(&mut self, project: Project) -> Option<Project> {
+        let replaced = self.projects.iter()
+            .position(|other| other.id() == project.id())
+            .map(|index| self.projects.remove(index));
+        let key = |project: &Project| project.name().to_lowercase();
+        let index = self.projects.partition_point(|other| key(other) <= key(&project));
+        self.projects.insert(index, project);
+        self.subscribers.send(ListEvent::ProjectsChanged);
+        replaced
+    }
+    
+    /// Remove a project; items that belonged to it keep pointing at it
+    ///
+    /// `Command::RemoveProject` takes the items out of it as well.
+    pub fn remove_project(&mut self, id: Uuid) -> Option<Project> {
+        let index = self.projects.iter().position(|project| project.id() == id)?;
+        let removed = self.projects.remove(index);
+        self.subscribers.send(ListEvent::ProjectsChanged);
+        Some(removed)
+    }
+    
+    /// Every tag used in the list, sorted and without repeats
+    pub fn all_tags(&self) -> Vec<String> {
+        let tags: BTreeSet<&String> = self.items.values().flat_map(|item| item.tags()).collect();
+        tags.into_iter().cloned().collect()
+    }
+    
+    /// Tag an item, returning whether it didn't have the tag yet
+    pub fn add_tag(&mut self, id: Uuid, tag: &str) -> Result<bool, String> {
+        self.get_item_mut(id)
+            .map(|item| item.add_tag(tag))
+            .ok_or_else(|| format!("Item with ID {} not found", id))
+    }
+    
+    /// Untag an item, returning whether it had the tag
+    pub fn remove_tag(&mut self, id: Uuid, tag: &str) -> Result<bool, String> {
+        self.get_item_mut(id)
+            .map(|item| item.remove_tag(tag))
+            .ok_or_else(|| format!("Item with ID {} not found", id))
+    }
+    
+    /// Rename a tag on every item that has it, returning how many items changed
+    ///
+    /// Items that already have the new name just lose the old one.
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> usize {
+        let mut renamed = Vec::new();
+        for item in self.items.values_mut().filter(|item| item.has_tag(old)) {
+            let tags = item.tags().iter()
+                .map(|tag| if tag == old { new.to_string() } else { tag.clone() })
+                .collect();
+            item.set_tags(tags);
+            renamed.push(item.id());
+        }
+        for &id in &renamed {
+            self.subscribers.send(ListEvent::ItemUpdated { id });
+        }
+        renamed.len()
+    }
+    
+    /// Remove a tag from every item, returning how many items had it
+    pub fn delete_tag(&mut self, tag: &str) -> usize {
+        let mut deleted = Vec::new();
+        for item in self.items.values_mut() {
+            if item.remove_tag(tag) {
+                deleted.push(item.id());
+            }
+        }
+        for &id in &deleted {
+            self.subscribers.send(ListEvent::ItemUpdated { id });
+        }
+        deleted.len()
+    }
+    
+    /// Get all items as a flat list
+    pub fn all_items(&self) -> Vec<&TodoItem> {
+        self.items.values().collect()
+    }
+    
+    /// Get all items as a vector of references ordered by a specified criterion
+    pub fn sorted_items<F, K>(&self, key_fn: F) -> Vec<&TodoItem>
+    where
+        F: Fn(&TodoItem) -> K,
+        K: Ord,
+    {
+        let mut items: Vec<&TodoItem> = self.items.values().collect();
+        items.sort_by_key(|item| key_fn(item));
+        items
+    }
+    
+    /// Get the `n` items changed last, the latest first, e.g. for a recent activity view
+    pub fn recently_modified(&self, n: usize) -> Vec<&TodoItem> {
+        // The id breaks ties, so items changed in the same second keep an order
+        let mut items = self.sorted_items(|item| std::cmp::Reverse((item.updated_at(), item.created_at(), item.id())));
+        items.truncate(n);
+        items
+    }
+    
+    /// Get a hierarchical representation of the todo list
+    ///
+    /// Returns a vector of (item, depth) pairs in a pre-order traversal,
+    /// where depth is the nesting level (0 for root items).
+    pub fn hierarchical_view(&self) -> Vec<(&TodoItem, usize)> {
+        self.ordered_view(SortOrder::default())
+    }
+    
+    /// Get the hierarchical view with siblings in the list's sort order
+    ///
+    /// Children stay under their parent; only items with the same parent are reordered.
+    pub fn sorted_view(&self) -> Vec<(&TodoItem, usize)> {
+        self.ordered_view(self.sort_order)
+    }
+    
+    /// How the list is sorted for display
+    pub fn sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+    
+    /// Change how the list is sorted for display; this isn't an edit, so it
+    /// isn't recorded in the history
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_order = order;
+    }
+    
+    /// Every item, in hierarchy order, then any that can't be reached from the
+    /// root items, such as those under a missing parent, by id
+    fn ordered_items(&self) -> Vec<&TodoItem> {
+        let mut items: Vec<&TodoItem> = self.hierarchical_view().into_iter().map(|(item, _)| item).collect();
+        if items.len() < self.items.len() {
+            let listed: HashSet<Uuid> = items.iter().map(|item| item.id()).collect();
+            let mut unreachable: Vec<&TodoItem> = self.items.values().filter(|item| !listed.contains(&item.id())).collect();
+            unreachable.sort_by_key(|item| item.id());
+            items.extend(unreachable);
+        }
+        items
+    }
+    
+    /// Pre-order traversal with each item's children in `order`
+    fn ordered_view(&self, order: SortOrder) -> Vec<(&TodoItem, usize)> {
+        let mut result = Vec::with_capacity(self.items.len());
+        
+        // Helper function for recursive traversal
+        fn traverse<'a>(
+            list: &'a TodoList,
+            parent_id: Option<Uuid>,
+            depth: usize,
+            order: SortOrder,
+            result: &mut Vec<(&'a TodoItem, usize)>,
+        ) {
+            // Get children of this parent
+            let mut children = match parent_id {
+                Some(id) => list.children(id),
+                None => list.root_items(),
+            };
+            if !order.is_manual() {
+                children.sort_by(|a, b| order.compare(a, b));
+            }
+            
+            // Add each child to the result, then traverse its children
+            for item in children {
+                result.push((item, depth));
+                traverse(list, Some(item.id()), depth + 1, order, result);
+            }
+        }
+        
+        // Start traversal from root items
+        traverse(self, None, 0, order, &mut result);
+        
+        result
+    }
+    
+    /// Move an item to just before another item among its siblings
+    /// 
+    /// An item with another parent than the target first moves under the target's parent.
+    /// 
+    /// Returns `Ok(())` if successful, or an error message if not.
+    pub fn move_item_before(&mut self, item_id: Uuid, target_id: Uuid) -> Result<(), String> {
+        // Check if both items exist
+        if !self.items.contains_key(&item_id) {
+            return Err(format!("Item with ID {} not found", item_id));
+        }
+        if !self.items.contains_key(&target_id) {
+            return Err(format!("Target item with ID {} not found", target_id));
+        }
+        if item_id == target_id {
+            return Ok(());
+        }
+        
+        // Get the parent IDs for both items
+        let item_parent_id = match self.items.get(&item_id) {
+            Some(item) => item.parent_id(),
+            None => return Err("Item not found".to_string()),
+        };
+        
+        let target_parent_id = match self.items.get(&target_id) {
+            Some(item) => item.parent_id(),
+            None => return Err("Target item not found".to_string()),
+        };
+        
+        // If the parents are different, we need to move the item to the target's parent first
+        if item_parent_id != target_parent_id {
+            self.move_item(item_id, target_parent_id)?;
+        }
+        
+        // Now both items have the same parent, so we can reorder
+        let siblings = self.hierarchy.entry(target_parent_id).or_default();
+        siblings.retain(|&id| id != item_id);
+        let index = siblings.iter().position(|&id| id == target_id).unwrap_or(siblings.len());
+        siblings.insert(index, item_id);
+        self.subscribers.send(ListEvent::ItemMoved { id: item_id, parent_id: target_parent_id });
+        
+        Ok(())
+    }
+    
+    /// Find the index of an item by its ID
+    pub fn find_item_index(&self, id: &Uuid) -> Option<Uuid> {
+        if self.items.contains_key(id) {
+            Some(*id)
+        } else {
+            None
+        }
+    }
+    
+    /// Replace an item at a specific index with a new item
+    pub fn replace_item_at_index(&mut self, id: Uuid, new_item: TodoItem) -> Option<TodoItem> {
+        if !self.items.contains_key(&id) {
+            return None;
+        }
+        
+        // Get the original item to preserve hierarchy relationships
+        let original_item = self.items.get(&id).cloned();
+        
+        if let Some(original) = original_item {
+            // Insert the new item, preserving parent-child relationships
+            let mut item_to_insert = new_item;
+            item_to_insert.set_parent_id(original.parent_id());
+            
+            // Replace the item in the map
+            self.items.insert(id, item_to_insert.clone());
+            self.subscribers.send(ListEvent::ItemUpdated { id });
+            
+            Some(item_to_insert)
+        } else {
+            None
+        }
+    }
+    
+    /// Items that can be archived: completed before `before`, if given, with
+    /// every subtask completed before it too
+    ///
+    /// Only the topmost of those are listed, in hierarchy order, since archiving
+    /// an item takes its subtree along.
+    pub fn archivable(&self, before: Option<u64>) -> Vec<Uuid> {
+        let done = |item: &TodoItem| {
+            item.completed_at().is_some_and(|completed_at| before.is_none_or(|before| completed_at < before))
+        };
+        let mut ids = Vec::new();
+        let mut skip_below = None;
+        for (item, depth) in self.hierarchical_view() {
+            if skip_below.is_some_and(|skip_depth| depth > skip_depth) {
+                continue;
+            }
+            skip_below = None;
+            let mut subtree = Vec::new();
+            self.collect_subtree(item.id(), &mut subtree);
+            if subtree.iter().all(|&id| self.items.get(&id).is_some_and(done)) {
+                ids.push(item.id());
+                skip_below = Some(depth);
+            }
+        }
+        ids
+    }
+    
+    /// Archive every item `archivable` lists, returning their ids
+    pub fn archive_completed(&mut self, before: Option<u64>) -> Vec<Uuid> {
+        let ids = self.archivable(before);
+        for &id in &ids {
+            // Listed items are in the list, so this can't fail
+            let _ = self.archive_item(id);
+        }
+        ids
+    }
+    
+    /// Move an item and its descendants out of the list into the archive
+    pub fn archive_item(&mut self, id: Uuid) -> Result<(), String> {
+        let mut subtree = Vec::new();
+        self.collect_subtree(id, &mut subtree);
+        if subtree.is_empty() {
+            return Err(format!("Item with ID {} not found", id));
+        }
+        let items: Vec<TodoItem> = subtree.iter().filter_map(|id| self.items.get(id).cloned()).collect();
+        self.remove_item(id);
+        self.archived.extend(items);
+        Ok(())
+    }
+    
+    /// Bring an archived item back into the list, with its archived descendants
+    ///
+    /// It goes after its siblings, or among the root items if its parent isn't
+    /// in the list any more.
+    pub fn restore_item(&mut self, id: Uuid) -> Result<(), String> {
+        if !self.archived.iter().any(|item| item.id() == id) {
+            return Err(format!("Archived item with ID {} not found", id));
+        }
+        if self.items.contains_key(&id) {
+            return Err(format!("Item with ID {} already exists", id));
+        }
+        
+        // Parents are archived before their children, so one pass finds the subtree
+        let mut subtree = HashSet::from([id]);
+        let (mut restored, archived): (Vec<TodoItem>, Vec<TodoItem>) = std::mem::take(&mut self.archived)
+            .into_iter()
+            .partition(|item| {
+                let inside = item.id() == id || item.parent_id().is_some_and(|parent| subtree.contains(&parent));
+                if inside {
+                    subtree.insert(item.id());
+                }
+                inside
+            });
+        self.archived = archived;
+        
+        // The item itself first, so descendants archived before it find their parent
+        let index = restored.iter().position(|item| item.id() == id).unwrap_or(0);
+        let mut item = restored.remove(index);
+        if item.parent_id().is_some_and(|parent| !self.items.contains_key(&parent)) {
+            item.set_parent_id(None);
+        }
+        self.add_item(item);
+        for item in restored {
+            self.add_item(item);
+        }
+        Ok(())
+    }
+    
+    /// Archived items with their depth below the archived item they came with
+    ///
+    /// Each subtree is listed parents first, the most recently archived last.
+    pub fn archived_items(&self) -> Vec<(&TodoItem, usize)> {
+        let mut depths: HashMap<Uuid, usize> = HashMap::new();
+        self.archived
+            .iter()
+            .map(|item| {
+                let depth = item.parent_id().and_then(|parent| depths.get(&parent)).map_or(0, |depth| depth + 1);
+                depths.insert(item.id(), depth);
+                (item, depth)
+            })
+            .collect()
+    }
+    
+    /// Whether an item is in the archive
+    pub fn is_archived(&self, id: Uuid) -> bool {
+        self.archived.iter().any(|item| item.id() == id)
+    }
+    
+    /// An item and its descendants, parents first
+    fn collect_subtree(&self, id: Uuid, out: &mut Vec<Uuid>) {
+        if !self.items.contains_key(&id) {
+            return;
+        }
+        out.push(id);
+        for child in self.child_ids(id) {
+            self.collect_subtree(child, out);
+        }
+    }
+    
+    /// The undo and redo stacks
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub(crate) fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    pub(crate) fn subscribers_mut(&mut self) -> &mut Subscribers {
+        &mut self.subscribers
+    }
+
+    /// Where the last CalDAV sync left this list and the server
+    pub fn sync_state(&self) -> &SyncState {
+        &self.sync_state
+    }
+
+    /// Record where a sync left things, once its pulled changes are applied
+    pub fn set_sync_state(&mut self, state: SyncState) {
+        self.sync_state = state;
+    }
+
+    /// Revert the most recent change recorded in the history
+    ///
+    /// Returns the change that reverted it, or `None` if there was nothing to
+    /// undo. A change that no longer applies, e.g. because its item was since
+    /// removed without going through the history, is dropped with an error.
+    pub fn undo(&mut self) -> Result<Option<Applied>, String> {
+        let Some(entry) = self.history.pop_undo() else {
+            return Ok(None);
+        };
+        let reverted = self.revert(entry)?;
+        if let Some(applied) = &reverted {
+            self.history.push_redo(applied.clone());
+        }
+        Ok(reverted)
+    }
+
+    /// Make the most recently undone change again
+    ///
+    /// Returns the change that was made, or `None` if there was nothing to redo.
+    pub fn redo(&mut self) -> Result<Option<Applied>, String> {
+        let Some(entry) = self.history.pop_redo() else {
+            return Ok(None);
+        };
+        let redone = self.revert(entry)?;
+        if let Some(applied) = &redone {
+            self.history.push_undo(applied.clone());
+        }
+        Ok(redone)
+    }
+
+    /// Apply an entry's revert command, returning it with its own revert command
+    fn revert(&mut self, entry: Applied) -> Result<Option<Applied>, String> {
+        let command = entry.undo.clone();
+        Ok(entry.undo.apply(self)?.map(|undo| Applied { command, undo }))
+    }
+
+    /// Verify that the hierarchy map agrees with the items' parent ids
+    ///
+    /// Checks that every item is listed under its parent, once, and nowhere else, that
+    /// the map only lists existing items, that every parent exists and that there
+    /// are no cycles. Walks the whole list, so it's meant for tests and debugging.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        for (parent_id, child_ids) in &self.hierarchy {
+            for (index, child_id) in child_ids.iter().enumerate() {
+                if child_ids[..index].contains(child_id) {
+                    return Err(format!("Item {} is listed twice under {:?}", child_id, parent_id));
+                }
+                let item = self.items
+                    .get(child_id)
+                    .ok_or_else(|| format!("Hierarchy lists missing item {}", child_id))?;
+                if item.parent_id() != *parent_id {
+                    return Err(format!(
+                        "Item {} is listed under {:?} but its parent is {:?}",
+                        child_id, parent_id, item.parent_id()
+                    ));
+                }
+            }
+        }
+        
+        for (id, item) in &self.items {
+            if item.id() != *id {
+                return Err(format!("Item {} is stored under id {}", item.id(), id));
+            }
+            if !self.hierarchy.get(&item.parent_id()).is_some_and(|siblings| siblings.contains(id)) {
+                return Err(format!("Item {} is missing from the hierarchy", id));
+            }
+            
+            // Walking up from any item must reach a root within len() steps
+            let mut ancestor = item.parent_id();
+            let mut depth = 0;
+            while let Some(ancestor_id) = ancestor {
+                depth += 1;
+                if depth > self.items.len() {
+                    return Err(format!("Item {} is part of a cycle", id));
+                }
+                ancestor = self.items
+                    .get(&ancestor_id)
+                    .ok_or_else(|| format!("Item {} has missing ancestor {}", id, ancestor_id))?
+                    .parent_id();
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    
+    #[test]
+    fn test_create_and_add_items() {
+        let mut list = TodoList::new("Test List");
+        
+        // Create an item via TodoList
+        let id1 = list.create_item("Task 1");
+        
+        // Create and add an item via TodoItem
+        let item2 = TodoItem::new("Task 2");
+        let id2 = list.add_item(item2);
+        
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.get_item(id1).unwrap().title(), "Task 1");
+        assert_eq!(list.get_item(id2).unwrap().title(), "Task 2");
+    }
+    
+    #[test]
+    fn test_hierarchical_structure() {
+        let mut list = TodoList::new("Hierarchy Test");
+        
+        // Create parent task
+        let parent_id = list.create_item("Parent Task");
+        
+        // Create child tasks
+        let child1_id = list.create_item("Child 1");
+        let child2_id = list.create_item("Child 2");
+        
+        // Move children under the parent
+        list.move_item(child1_id, Some(parent_id)).unwrap();
+        list.move_item(child2_id, Some(parent_id)).unwrap();
+        
+        // Check parent-child relationships
+        assert_eq!(list.children(parent_id).len(), 2);
+        assert!(list.child_ids(parent_id).contains(&child1_id));
+        assert!(list.child_ids(parent_id).contains(&child2_id));
+        
+        // Verify hierarchy view
+        let hierarchy = list.hierarchical_view();
+        assert_eq!(hierarchy.len(), 3);
+        
+        // Parent should be first, at depth 0
+        assert_eq!(hierarchy[0].0.id(), parent_id);
+        assert_eq!(hierarchy[0].1, 0);
+        
+        // Children should follow, at depth 1
+        assert_eq!(hierarchy[1].1, 1);
+        assert_eq!(hierarchy[2].1, 1);
+    }
+    
+    #[test]
+    fn test_remove_item_with_children() {
+        let mut list = TodoList::new("Removal Test");
+        
+        // Create parent task
+        let parent_id = list.create_item("Parent Task");
+        
+        // Create child tasks
+        let child1_id = list.create_item("Child 1");
+        let child2_id = list.create_item("Child 2");
+        
+        // Move children under the parent
+        list.move_item(child1_id, Some(parent_id)).unwrap();
+        list.move_item(child2_id, Some(parent_id)).unwrap();
+        
+        // Remove the parent (should also remove children)
+        list.remove_item(parent_id);
+        
+        // List should be empty
+        assert_eq!(list.len(), 0);
+        assert!(list.get_item(parent_id).is_none());
+        assert!(list.get_item(child1_id).is_none());
+        assert!(list.get_item(child2_id).is_none());
+    }
+    
+    #[test]
+    fn test_filtering() {
+        let mut list = TodoList::new("Filter Test");
+        
+        // Create items with different statuses and priorities
+        let id1 = list.create_item("High Priority Task");
+        let id2 = list.create_item("Medium Priority Task");
+        let id3 = list.create_item("Completed Task");
+        
+        // Set properties
+        list.get_item_mut(id1).unwrap().set_priority(Priority::High);
+        list.get_item_mut(id2).unwrap().set_priority(Priority::Medium);
+        list.get_item_mut(id3).unwrap().set_status(Status::Completed);
+        
+        // Test filters
+        assert_eq!(list.items_by_priority(Priority::High).len(), 1);
+        assert_eq!(list.items_by_priority(Priority::Medium).len(), 1);
+        assert_eq!(list.completed_items().len(), 1);
+        assert_eq!(list.incomplete_items().len(), 2);
+    }
+    
+    #[test]
+    fn test_tags() {
+        let mut list = TodoList::new("Tag Test");
+        let id1 = list.add_item(TodoItem::new("Report").with_tag("work").with_tag("urgent"));
+        let id2 = list.add_item(TodoItem::new("Groceries").with_tag("home"));
+        let id3 = list.create_item("Untagged");
+        
+        assert_eq!(list.all_tags(), ["home", "urgent", "work"]);
+        assert_eq!(list.items_by_tag("work")[0].id(), id1);
+        
+        assert!(list.add_tag(id3, "work").unwrap());
+        assert!(!list.add_tag(id3, "work").unwrap());
+        assert!(list.add_tag(Uuid::new_v4(), "work").is_err());
+        assert_eq!(list.items_by_tag("work").len(), 2);
+        
+        // Renaming onto an existing tag merges them
+        list.add_tag(id1, "office").unwrap();
+        assert_eq!(list.rename_tag("work", "office"), 2);
+        assert_eq!(list.get_item(id1).unwrap().tags(), ["office", "urgent"]);
+        assert_eq!(list.get_item(id3).unwrap().tags(), ["office"]);
+        
+        assert!(list.remove_tag(id2, "home").unwrap());
+        assert_eq!(list.delete_tag("office"), 2);
+        assert_eq!(list.all_tags(), ["urgent"]);
+    }
+    
+    #[test]
+    fn test_recently_modified() {
+        let mut list = TodoList::new("Activity");
+        let old = list.add_item(TodoItem::new("Old").with_created_at(100).with_updated_at(100));
+        let older = list.add_item(TodoItem::new("Older").with_created_at(50).with_updated_at(50));
+        let new = list.add_item(TodoItem::new("New").with_created_at(200).with_updated_at(200));
+        
+        let ids = |items: Vec<&TodoItem>| items.into_iter().map(TodoItem::id).collect::<Vec<_>>();
+        assert_eq!(ids(list.recently_modified(2)), [new, old]);
+        
+        // Editing an item brings it to the front
+        list.get_item_mut(older).unwrap().set_title("Older, edited");
+        assert_eq!(ids(list.recently_modified(10)), [older, new, old]);
+        assert!(list.recently_modified(0).is_empty());
+    }
+    
+    #[test]
+    fn test_subscribers_hear_about_changes() {
+        let mut list = TodoList::new("Events");
+        let events = list.subscribe();
+        let parent = list.create_item("Parent");
+        let child = list.add_item(TodoItem::new("Child").with_parent(parent));
+        let other = list.create_item("Other");
+        list.get_item_mut(other).unwrap().set_title("Renamed");
+        list.move_item(other, Some(parent)).unwrap();
+        list.remove_item(parent);
+        assert_eq!(events.try_iter().collect::<Vec<_>>(), [
+            ListEvent::ItemAdded { id: parent },
+            ListEvent::ItemAdded { id: child },
+            ListEvent::ItemAdded { id: other },
+            ListEvent::ItemUpdated { id: other },
+            ListEvent::ItemMoved { id: other, parent_id: Some(parent) },
+            ListEvent::ItemRemoved { id: child },
+            ListEvent::ItemRemoved { id: other },
+            ListEvent::ItemRemoved { id: parent },
+        ]);
+        
+        // Copies don't report to the original's subscribers
+        let mut copy = list.clone();
+        copy.create_item("Elsewhere");
+        assert!(events.try_recv().is_err());
+        
+        // A replaced list keeps them, and they're told to start over
+        Command::ReplaceList { list: copy }.apply(&mut list).unwrap();
+        list.create_item("After");
+        assert_eq!(events.try_iter().next(), Some(ListEvent::Reset));
+        assert!(matches!(events.try_recv(), Ok(ListEvent::ItemAdded { .. })));
+    }
+    
+    #[test]
+    fn test_manual_order_is_kept() {
+        let mut list = TodoList::new("Order");
+        let ids: Vec<Uuid> = ["One", "Two", "Three", "Four"].iter().map(|title| list.create_item(title)).collect();
+        let sub_a = list.add_item(TodoItem::new("A").with_parent(ids[0]));
+        let sub_b = list.add_item(TodoItem::new("B").with_parent(ids[0]));
+        assert_eq!(list.root_item_ids(), ids);
+        assert_eq!(list.child_ids(ids[0]), [sub_a, sub_b]);
+        
+        list.move_item_before(ids[3], ids[1]).unwrap();
+        list.move_item_before(sub_b, sub_a).unwrap();
+        assert_eq!(list.root_item_ids(), [ids[0], ids[3], ids[1], ids[2]]);
+        assert_eq!(list.child_ids(ids[0]), [sub_b, sub_a]);
+        
+        // Moving under the same parent keeps the place; another parent puts it last
+        list.move_item(sub_b, Some(ids[0])).unwrap();
+        assert_eq!(list.child_ids(ids[0]), [sub_b, sub_a]);
+        list.move_item(ids[1], Some(ids[0])).unwrap();
+        assert_eq!(list.child_ids(ids[0]), [sub_b, sub_a, ids[1]]);
+        list.check_invariants().unwrap();
+        
+        // Saving writes the same text every time, and loading gets the order back
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(serde_json::to_string(&list).unwrap(), json);
+        let loaded: TodoList = serde_json::from_str(&json).unwrap();
+        let view = |list: &TodoList| list.hierarchical_view().into_iter().map(|(item, depth)| (item.id(), depth)).collect::<Vec<_>>();
+        assert_eq!(view(&loaded), view(&list));
+        assert_eq!(view(&list), [(ids[0], 0), (sub_b, 1), (sub_a, 1), (ids[1], 1), (ids[3], 0), (ids[2], 0)]);
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    }
+    
+    #[test]
+    fn test_projects_keep_to_name_order() {
+        let mut list = TodoList::new("Projects");
+        let events = list.subscribe();
+        let home = Project::new("home", [255, 42, 109]);
+        let work = Project::new("Work", [5, 217, 232]);
+        assert!(list.put_project(work.clone()).is_none());
+        list.put_project(home.clone());
+        let mut chores = home.clone();
+        chores.set_name("Chores");
+        assert_eq!(list.put_project(chores), Some(home.clone()));
+        let names = |list: &TodoList| list.projects().iter().map(|project| project.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names(&list), ["Chores", "Work"]);
+        assert_eq!(events.try_iter().count(), 3);
+        
+        let id = list.add_item(TodoItem::new("Report").with_project(work.id()));
+        list.create_item("Loose");
+        assert_eq!(list.items_by_project(Some(work.id()))[0].id(), id);
+        assert_eq!(list.items_by_project(None).len(), 1);
+        
+        let loaded: TodoList = serde_json::from_str(&serde_json::to_string(&list).unwrap()).unwrap();
+        assert_eq!(loaded.projects(), list.projects());
+        assert_eq!(loaded.get_item(id).unwrap().project_id(), Some(work.id()));
+        assert_eq!(list.remove_project(work.id()), Some(work));
+        assert_eq!(names(&list), ["Chores"]);
+    }
+    
+    #[test]
+    fn test_sorted_view() {
+        let mut list = TodoList::new("Sorting");
+        let errands = list.add_item(TodoItem::new("errands").with_created_at(300).with_priority(Priority::Low));
+        let bills = list.add_item(TodoItem::new("Bills").with_created_at(100).with_due_date(2_000));
+        let taxes = list.add_item(TodoItem::new("Taxes").with_created_at(200).with_priority(Priority::High).with_due_date(1_000));
+        let stamps = list.add_item(TodoItem::new("Stamps").with_parent(errands).with_created_at(400).with_due_date(500));
+        let milk = list.add_item(TodoItem::new("Milk").with_parent(errands).with_created_at(500));
+        
+        let ids = |list: &TodoList| list.sorted_view().into_iter().map(|(item, _)| item.id()).collect::<Vec<_>>();
+        let sort = |list: &mut TodoList, key, descending| list.set_sort_order(SortOrder { key, descending });
+        
+        // Children stay under their parent, sorted among themselves
+        sort(&mut list, SortKey::Alphabetical, false);
+        assert_eq!(ids(&list), [bills, errands, milk, stamps, taxes]);
+        sort(&mut list, SortKey::Alphabetical, true);
+        assert_eq!(ids(&list), [taxes, errands, stamps, milk, bills]);
+        sort(&mut list, SortKey::Priority, false);
+        // Equal priorities fall back to the id, so only the parents' order is known here
+        assert_eq!(ids(&list)[..3], [taxes, bills, errands]);
+        sort(&mut list, SortKey::Created, true);
+        assert_eq!(ids(&list), [errands, milk, stamps, taxes, bills]);
+        
+        // Undated items come last in both directions
+        sort(&mut list, SortKey::DueDate, false);
+        assert_eq!(ids(&list), [taxes, bills, errands, stamps, milk]);
+        sort(&mut list, SortKey::DueDate, true);
+        assert_eq!(ids(&list), [bills, taxes, errands, stamps, milk]);
+        
+        // The order is saved with the list, and a manual one isn't written at all
+        let saved: TodoList = serde_json::from_str(&serde_json::to_string(&list).unwrap()).unwrap();
+        assert_eq!(saved.sort_order(), SortOrder { key: SortKey::DueDate, descending: true });
+        list.set_sort_order(SortOrder::default());
+        assert!(!serde_json::to_string(&list).unwrap().contains("sort_order"));
+        assert_eq!(list.sorted_view().len(), list.hierarchical_view().len());
+    }
+    
+    #[test]
+    fn test_archive() {
+        let done = |title: &str, at: u64| TodoItem::new(title).with_status(Status::Completed).with_completed_at(at);
+        let mut list = TodoList::new("Archive");
+        let trip = list.add_item(done("Trip", 300));
+        let tickets = list.add_item(done("Tickets", 200).with_parent(trip));
+        let move_out = list.add_item(done("Move out", 100));
+        let boxes = list.add_item(TodoItem::new("Boxes").with_parent(move_out));
+        let keys = list.add_item(done("Return keys", 100).with_parent(move_out));
+        let open = list.create_item("Open");
+        
+        // Only the topmost of whole completed subtrees, before the cutoff if there is one
+        assert_eq!(list.archivable(None), [trip, keys]);
+        assert_eq!(list.archivable(Some(300)), [tickets, keys]);
+        assert_eq!(list.archive_completed(None), [trip, keys]);
+        assert_eq!(list.len(), 3);
+        assert!(list.get_item(tickets).is_none());
+        assert_eq!(list.child_ids(move_out), [boxes]);
+        let archived = |list: &TodoList| list.archived_items().into_iter().map(|(item, depth)| (item.id(), depth)).collect::<Vec<_>>();
+        assert_eq!(archived(&list), [(trip, 0), (tickets, 1), (keys, 0)]);
+        assert!(list.archivable(None).is_empty());
+        list.check_invariants().unwrap();
+        
+        // The archive is saved with the list, and not written while empty
+        let saved: TodoList = serde_json::from_str(&serde_json::to_string(&list).unwrap()).unwrap();
+        assert_eq!(archived(&saved), archived(&list));
+        
+        // Restoring brings the subtree back, as a root item once its parent is gone
+        list.restore_item(trip).unwrap();
+        assert_eq!(list.child_ids(trip), [tickets]);
+        list.remove_item(move_out);
+        list.restore_item(keys).unwrap();
+        assert_eq!(list.get_item(keys).unwrap().parent_id(), None);
+        assert_eq!(list.root_item_ids(), [open, trip, keys]);
+        assert!(list.restore_item(keys).is_err());
+        assert!(list.archived_items().is_empty());
+        assert!(!serde_json::to_string(&list).unwrap().contains("archived"));
+        list.check_invariants().unwrap();
+        
+        // Through the history, restoring undoes archiving and the other way round
+        let dispatcher = crate::command::Dispatcher::new(std::sync::Arc::new(std::sync::Mutex::new(list)));
+        dispatcher.execute(Command::Archive { id: trip }).unwrap();
+        assert!(dispatcher.lock().is_archived(tickets));
+        dispatcher.undo().unwrap();
+        assert_eq!(dispatcher.lock().child_ids(trip), [tickets]);
+        assert!(!dispatcher.lock().is_archived(trip));
+    }
+    
+    #[test]
+    fn test_batch() {
+        use std::sync::{Arc, Mutex};
+        use crate::command::Dispatcher;
+        
+        let mut list = TodoList::new("Bulk");
+        let home = list.create_item("Home");
+        let paint = list.create_item("Paint");
+        let brushes = list.create_item("Buy brushes");
+        let taxes = list.create_item("Taxes");
+        list.move_item(brushes, Some(paint)).unwrap();
+        let dispatcher = Dispatcher::new(Arc::new(Mutex::new(list)));
+        let batch = |ids: &[Uuid], edit| dispatcher.lock().batch(ids, edit).unwrap();
+        
+        // Every item changes, and one undo takes them all back
+        dispatcher.execute(batch(&[paint, brushes, taxes], BulkEdit::SetPriority(Priority::High))).unwrap();
+        dispatcher.execute(batch(&[paint, taxes], BulkEdit::Complete)).unwrap();
+        assert_eq!(dispatcher.lock().items_by_priority(Priority::High).len(), 1);
+        assert!([paint, brushes, taxes].iter().all(|&id| dispatcher.lock().get_item(id).unwrap().priority() == Priority::High));
+        assert_eq!(dispatcher.lock().completed_items().len(), 2);
+        dispatcher.undo().unwrap();
+        assert!(dispatcher.lock().completed_items().is_empty());
+        
+        // A selected subtree moves whole, under its topmost selected item
+        dispatcher.execute(batch(&[paint, brushes, taxes], BulkEdit::MoveUnder(Some(home)))).unwrap();
+        {
+            let list = dispatcher.lock();
+            assert_eq!(list.child_ids(home).len(), 2);
+            assert_eq!(list.get_item(brushes).unwrap().parent_id(), Some(paint));
+            list.check_invariants().unwrap();
+        }
+        assert!(dispatcher.lock().batch(&[home, taxes], BulkEdit::MoveUnder(Some(taxes))).is_err());
+        assert!(dispatcher.lock().batch(&[Uuid::new_v4()], BulkEdit::Delete).is_err());
+        
+        dispatcher.execute(batch(&[brushes, home, paint], BulkEdit::Delete)).unwrap();
+        assert!(dispatcher.lock().is_empty());
+        dispatcher.undo().unwrap();
+        assert_eq!(dispatcher.lock().len(), 4);
+        assert_eq!(dispatcher.lock().get_item(taxes).unwrap().parent_id(), Some(home));
+    }
+    
+    #[test]
+    fn test_cycle_prevention() {
+        let mut list = TodoList::new("Cycle Test");
+        
+        // Create a chain of tasks: A -> B -> C
+        let id_a = list.create_item("Task A");
+        let id_b = list.create_item("Task B");
+        let id_c = list.create_item("Task C");
+        
+        list.move_item(id_b, Some(id_a)).unwrap();
+        list.move_item(id_c, Some(id_b)).unwrap();
+        
+        // Trying to make A a child of C would create a cycle
+        assert!(list.move_item(id_a, Some(id_c)).is_err());
+    }
+    
+    /// Something done to the list; indexes pick among every id seen so far,
+    /// including removed ones
+    #[derive(Debug, Clone)]
+    enum Op {
+        Create,
+        Remove(usize),
+        Move(usize, Option<usize>),
+        MoveBefore(usize, usize),
+        Replace(usize),
+    }
+    
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            3 => Just(Op::Create),
+            1 => any::<usize>().prop_map(Op::Remove),
+            3 => (any::<usize>(), proptest::option::of(any::<usize>())).prop_map(|(item, parent)| Op::Move(item, parent)),
+            1 => (any::<usize>(), any::<usize>()).prop_map(|(item, target)| Op::MoveBefore(item, target)),
+            1 => any::<usize>().prop_map(Op::Replace),
+        ]
+    }
+    
+    /// Each item's parent, to compare the list before and after a failed operation
+    fn parents(list: &TodoList) -> HashMap<Uuid, Option<Uuid>> {
+        list.all_items().into_iter().map(|item| (item.id(), item.parent_id())).collect()
+    }
+    
+    proptest! {
+        #[test]
+        fn test_hierarchy_stays_consistent(ops in proptest::collection::vec(op(), 1..60)) {
+            let mut list = TodoList::new("Property Test");
+            let mut seen: Vec<Uuid> = Vec::new();
+            
+            for op in ops {
+                let pick = |index: usize| seen.get(index % seen.len().max(1)).copied();
+                let before = parents(&list);
+                match op {
+                    Op::Create => seen.push(list.create_item("Task")),
+                    Op::Remove(index) => {
+                        if let Some(id) = pick(index) {
+                            let subtree: Vec<Uuid> = before.keys().copied()
+                                .filter(|&other| other == id || list.is_ancestor(other, id))
+                                .collect();
+                            prop_assert_eq!(list.remove_item(id).is_some(), before.contains_key(&id));
+                            for removed in subtree {
+                                prop_assert!(list.get_item(removed).is_none());
+                            }
+                        }
+                    }
+                    Op::Move(index, parent) => {
+                        if let Some(id) = pick(index) {
+                            let parent = parent.and_then(pick);
+                            match list.move_item(id, parent) {
+                                Ok(()) => prop_assert_eq!(list.get_item(id).unwrap().parent_id(), parent),
+                                Err(_) => prop_assert_eq!(parents(&list), before),
+                            }
+                        }
+                    }
+                    Op::MoveBefore(index, target) => {
+                        if let (Some(id), Some(target)) = (pick(index), pick(target)) {
+                            match list.move_item_before(id, target) {
+                                Ok(()) => {
+                                    let parent = list.get_item(target).unwrap().parent_id();
+                                    prop_assert_eq!(list.get_item(id).unwrap().parent_id(), parent);
+                                    let siblings = match parent {
+                                        Some(parent) => list.child_ids(parent),
+                                        None => list.root_item_ids(),
+                                    };
+                                    let position = |id| siblings.iter().position(|&sibling| sibling == id);
+                                    if id != target {
+                                        prop_assert_eq!(position(id).map(|index| index + 1), position(target));
+                                    }
+                                }
+                                Err(_) => prop_assert_eq!(parents(&list), before),
+                            }
+                        }
+                    }
+                    Op::Replace(index) => {
+                        if let Some(id) = pick(index) {
+                            if let Some(mut item) = list.get_item(id).cloned() {
+                                item.set_title("Replaced");
+                                item.set_parent_id(None);
+                                list.replace_item_at_index(id, item);
+                                prop_assert_eq!(list.get_item(id).unwrap().parent_id(), before[&id]);
+                            }
+                        }
+                    }
+                }
+                
+                if let Err(e) = list.check_invariants() {
+                    return Err(TestCaseError::fail(e));
+                }
+                prop_assert_eq!(list.hierarchical_view().len(), list.len());
+            }
+        }
+    }
+}
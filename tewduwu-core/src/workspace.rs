@@ -0,0 +1,269 @@
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use super::todo_list::TodoList;
+
+/// Several named todo lists, saved together, one of which is active
+///
+/// Each list is shared the same way a single list is, so a `Dispatcher` can be
+/// made for any of them. There is always at least one list.
+#[derive(Debug)]
+pub struct Workspace {
+    lists: Vec<Arc<Mutex<TodoList>>>,
+
+    /// Index of the list being shown
+    active: usize,
+}
+
+/// On-disk shape of a Workspace
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedWorkspace<L> {
+    active: usize,
+    lists: Vec<L>,
+}
+
+/// What a data file may hold: a workspace, or a single list saved by older versions
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StoredWorkspace {
+    Workspace(SerializedWorkspace<TodoList>),
+    List(Box<TodoList>),
+}
+
+impl From<StoredWorkspace> for Workspace {
+    fn from(data: StoredWorkspace) -> Self {
+        match data {
+            StoredWorkspace::Workspace(data) => Workspace::from_lists(data.lists, data.active),
+            StoredWorkspace::List(list) => Workspace::new(*list),
+        }
+    }
+}
+
+impl serde::Serialize for Workspace {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let guards: Vec<MutexGuard<'_, TodoList>> = self.lists.iter()
+            .map(|list| list.lock().unwrap_or_else(|e| e.into_inner()))
+            .collect();
+        SerializedWorkspace {
+            active: self.active,
+            lists: guards.iter().map(|list| &**list).collect::<Vec<&TodoList>>(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Workspace {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        StoredWorkspace::deserialize(deserializer).map(Workspace::from)
+    }
+}
+
+impl Workspace {
+    /// Create a workspace holding just this list
+    pub fn new(list: TodoList) -> Self {
+        Self {
+            lists: vec![Arc::new(Mutex::new(list))],
+            active: 0,
+        }
+    }
+
+    /// Create a workspace from lists in order, with `active` clamped to them
+    ///
+    /// Without any lists, an empty one named "Tasks" is added.
+    pub fn from_lists(lists: Vec<TodoList>, active: usize) -> Self {
+        let mut lists: Vec<_> = lists.into_iter().map(|list| Arc::new(Mutex::new(list))).collect();
+        if lists.is_empty() {
+            lists.push(Arc::new(Mutex::new(TodoList::new("Tasks"))));
+        }
+        let active = active.min(lists.len() - 1);
+        Self { lists, active }
+    }
+
+    /// Number of lists; never zero
+    pub fn len(&self) -> usize {
+        self.lists.len()
+    }
+
+    /// Always false: a workspace keeps at least one list
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty()
+    }
+
+    /// Names of the lists in order
+    pub fn names(&self) -> Vec<String> {
+        self.lists.iter()
+            .map(|list| list.lock().unwrap_or_else(|e| e.into_inner()).name().to_string())
+            .collect()
+    }
+
+    /// Index of the active list
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// The active list
+    pub fn active_list(&self) -> &Arc<Mutex<TodoList>> {
+        &self.lists[self.active]
+    }
+
+    /// The list at an index
+    pub fn list(&self, index: usize) -> Option<&Arc<Mutex<TodoList>>> {
+        self.lists.get(index)
+    }
+
+    /// Make the list at an index the active one
+    pub fn set_active(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.lists.len() {
+            return Err(format!("No list at index {}", index));
+        }
+        self.active = index;
+        Ok(())
+    }
+
+    /// Append a list, returning its index
+    pub fn push_list(&mut self, list: TodoList) -> usize {
+        self.lists.push(Arc::new(Mutex::new(list)));
+        self.lists.len() - 1
+    }
+
+    /// Append a new, empty list with the given name, returning its index
+    pub fn add_list(&mut self, name: &str) -> Result<usize, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("A list needs a name".to_string());
+        }
+        Ok(self.push_list(TodoList::new(name)))
+    }
+
+    /// Rename the list at an index
+    pub fn rename_list(&mut self, index: usize, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("A list needs a name".to_string());
+        }
+        let list = self.lists.get(index).ok_or_else(|| format!("No list at index {}", index))?;
+        list.lock().unwrap_or_else(|e| e.into_inner()).set_name(name);
+        Ok(())
+    }
+
+    /// Remove the list at an index and return it; the last list can't be removed
+    ///
+    /// The active list stays the same unless it's the one removed, in which
+    /// case the one before it becomes active.
+    pub fn remove_list(&mut self, index: usize) -> Result<Arc<Mutex<TodoList>>, String> {
+        if index >= self.lists.len() {
+            return Err(format!("No list at index {}", index));
+        }
+        if self.lists.len() == 1 {
+            return Err("A workspace needs at least one list".to_string());
+        }
+        let removed = self.lists.remove(index);
+        if self.active > index || self.active == self.lists.len() {
+            self.active -= 1;
+        }
+        Ok(removed)
+    }
+
+    /// Copy every list into a workspace of its own, e.g. to save it on another thread
+    pub fn snapshot(&self) -> Workspace {
+        let lists = self.lists.iter()
+            .map(|list| list.lock().unwrap_or_else(|e| e.into_inner()).clone())
+            .collect();
+        Workspace::from_lists(lists, self.active)
+    }
+
+    /// Like `snapshot`, but fails instead of waiting for a list that is locked
+    ///
+    /// Meant for panic handlers, where the lock may be held by the panicking thread.
+    pub fn try_snapshot(&self) -> Result<Workspace, String> {
+        let mut lists = Vec::with_capacity(self.lists.len());
+        for list in &self.lists {
+            let list = match list.try_lock() {
+                Ok(list) => list,
+                Err(TryLockError::Poisoned(e)) => e.into_inner(),
+                Err(TryLockError::WouldBlock) => return Err("a list was locked by another thread".to_string()),
+            };
+            lists.push(list.clone());
+        }
+        Ok(Workspace::from_lists(lists, self.active))
+    }
+
+    /// Take the lists out, in order
+    pub fn into_lists(self) -> Vec<TodoList> {
+        self.lists.into_iter()
+            .map(|list| match Arc::try_unwrap(list) {
+                Ok(list) => list.into_inner().unwrap_or_else(|e| e.into_inner()),
+                Err(shared) => shared.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+            })
+            .collect()
+    }
+
+    /// Total number of items across all lists
+    pub fn item_count(&self) -> usize {
+        self.lists.iter()
+            .map(|list| list.lock().unwrap_or_else(|e| e.into_inner()).len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_remove_and_switch() {
+        let mut workspace = Workspace::new(TodoList::new("Inbox"));
+        let work = workspace.add_list("Work").unwrap();
+        let home = workspace.add_list("  Home ").unwrap();
+        assert_eq!(workspace.names(), ["Inbox", "Work", "Home"]);
+        assert!(workspace.add_list(" ").is_err());
+
+        workspace.set_active(home).unwrap();
+        assert!(workspace.set_active(3).is_err());
+        assert_eq!(workspace.active_list().lock().unwrap().name(), "Home");
+
+        // Removing a list before the active one keeps the same list active
+        workspace.remove_list(work).unwrap();
+        assert_eq!(workspace.active_list().lock().unwrap().name(), "Home");
+
+        // Removing the active list activates the one before it
+        workspace.remove_list(1).unwrap();
+        assert_eq!(workspace.active(), 0);
+        assert!(workspace.remove_list(0).is_err());
+
+        workspace.rename_list(0, "Everything").unwrap();
+        assert_eq!(workspace.names(), ["Everything"]);
+    }
+
+    #[test]
+    fn test_json_round_trip_and_legacy_list() {
+        let mut workspace = Workspace::new(TodoList::new("Inbox"));
+        workspace.active_list().lock().unwrap().create_item("Task");
+        let work = workspace.add_list("Work").unwrap();
+        workspace.list(work).unwrap().lock().unwrap().create_item("Report");
+        workspace.set_active(work).unwrap();
+
+        let json = serde_json::to_string(&workspace).unwrap();
+        let loaded: Workspace = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.names(), ["Inbox", "Work"]);
+        assert_eq!(loaded.active(), work);
+        assert_eq!(loaded.item_count(), 2);
+
+        // Data files from before workspaces hold a single list
+        let mut list = TodoList::new("Old");
+        list.create_item("Task");
+        let json = serde_json::to_string(&list).unwrap();
+        let loaded: Workspace = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.names(), ["Old"]);
+        assert_eq!(loaded.item_count(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_is_independent() {
+        let workspace = Workspace::new(TodoList::new("Inbox"));
+        let snapshot = workspace.snapshot();
+        workspace.active_list().lock().unwrap().create_item("Later");
+        assert_eq!(snapshot.item_count(), 0);
+
+        let _guard = workspace.active_list().lock().unwrap();
+        assert!(workspace.try_snapshot().is_err());
+    }
+}